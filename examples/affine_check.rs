@@ -0,0 +1,89 @@
+#![no_std]
+#![no_main]
+
+//! Checks composing, inverting, and placing affine matrices.
+//!
+//! Random scales, rotations, and shears are put together, and each one times
+//! its inverse has to come out within 2 bits of the identity. Four quarter
+//! turns have to land back on the identity, and the background parameters
+//! have to put the texture origin at the display origin. The backdrop goes
+//! green if everything passes (a failure panics, which makes it red).
+
+use gba::{
+  fixed::i16fx8,
+  math::Vec2,
+  prelude::*,
+  random::{Gen32, Xoshiro128},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  gba::mgba_error!("{info}");
+  loop {}
+}
+
+/// Checks that every entry is within `bits` of the identity.
+fn assert_near_identity(m: AffineMatrix, bits: i16, what: &str) {
+  let identity = AffineMatrix::IDENTITY.to_obj_params();
+  for (got, want) in m.to_obj_params().into_iter().zip(identity) {
+    assert!((got - want).abs() <= bits, "{what}: {m:?}");
+  }
+}
+
+fn fx8(bits: u32) -> i16fx8 {
+  i16fx8::from_bits(bits as i16)
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+
+  let quarter = AffineMatrix::from_rotation(0x4000);
+  let full = quarter.then(quarter).then(quarter).then(quarter);
+  assert_near_identity(full, 1, "four quarter turns");
+
+  let mut rng = Xoshiro128::from_seed(98);
+  for _ in 0..500 {
+    // scales from 0.5 to 2.0, and shears up to about 1.0 either way.
+    let sx = fx8(128 + rng.next_bounded_u32(385));
+    let sy = fx8(128 + rng.next_bounded_u32(385));
+    let angle = rng.next_u16();
+    let shear = fx8(rng.next_bounded_u32(512)).sub(fx8(256));
+    let m = AffineMatrix::from_scale(sx, sy)
+      .then(AffineMatrix::from_rotation(angle))
+      .then(AffineMatrix::from_shear_x(shear));
+    let inverse = m.inverse().expect("a scale of 0.5 or more has an inverse");
+    assert_near_identity(m.then(inverse), 2, "m * inverse");
+
+    let together = AffineMatrix::from_scale(sx, sy)
+      .then(AffineMatrix::from_rotation(angle))
+      .to_obj_params();
+    let direct = AffineMatrix::from_scale_rotation(sx, sy, angle);
+    for (a, b) in together.into_iter().zip(direct.to_obj_params()) {
+      assert!((a - b).abs() <= 4, "then vs from_scale_rotation");
+    }
+
+    // the hardware maps screen pixel `s` to `M * s + (x, y)`.
+    let display = Vec2::new(120, 80);
+    let texture = Vec2::new(rng.next_bounded_u32(512) as i32, 64);
+    let params = m.to_bg_params(display, texture);
+    let [a, b, c, d] = m.to_obj_params().map(i32::from);
+    let tx = a * display.x + b * display.y + params.x.to_bits();
+    let ty = c * display.x + d * display.y + params.y.to_bits();
+    assert_eq!((tx, ty), (texture.x << 8, texture.y << 8));
+  }
+
+  let flat = AffineMatrix::from_scale(fx8(256), fx8(256))
+    .then(AffineMatrix { pd: fx8(0), ..AffineMatrix::IDENTITY });
+  assert!(flat.inverse().is_none(), "a zero determinant has no inverse");
+  let tiny = AffineMatrix { pa: fx8(1), pd: fx8(1), ..AffineMatrix::IDENTITY };
+  assert!(tiny.inverse().is_none(), "the inverse doesn't fit in 8.8");
+
+  gba::mgba_info!("affine checks passed");
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
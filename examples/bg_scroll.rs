@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(CHARBLOCK0_4BPP.as_region(), 0);
+  bg_palbank(0).index(1).write(Color::WHITE);
+
+  let tsb = TEXT_SCREENBLOCKS.get_frame(31).unwrap();
+  for y in 0..32 {
+    let row = tsb.get_row(y).unwrap();
+    for (x, addr) in row.iter().enumerate() {
+      addr.write(TextEntry::from_tile(((y * 32 + x) % 256) as u16));
+    }
+  }
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+
+  let mut scroll = BgScroll::default();
+  loop {
+    // Use the D-pad to scroll, with A held to go faster.
+    let keys = KEYINPUT.read();
+    let speed = if keys.a() { 4 } else { 1 };
+    let (dx, dy) = keys.dpad();
+    scroll.scroll_by(dx.to_i32() as i16 * speed, dy.to_i32() as i16 * speed);
+
+    spin_until_vblank();
+    scroll.write_to(BgLayer::Bg0);
+  }
+}
@@ -0,0 +1,50 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  // Check the BIOS against matrices worked out by hand. A quarter turn has
+  // cos = 0 and sin = 1, so the matrix is `[0, -s; s, 0]` for a scale of `s`.
+  let one = i16fx8::from_bits(1 << 8);
+  let half = i16fx8::from_bits(1 << 7);
+  let src = [
+    ObjAffineSource { inv_scale_x: one, inv_scale_y: one, angle: 0 },
+    ObjAffineSource { inv_scale_x: half, inv_scale_y: half, angle: 0x4000 },
+  ];
+  let mut dest = [AffineMatrix::IDENTITY; 2];
+  obj_affine_set(&src, &mut dest);
+  let bits = |m: AffineMatrix| {
+    [m.pa.to_bits(), m.pb.to_bits(), m.pc.to_bits(), m.pd.to_bits()]
+  };
+  let ok =
+    bits(dest[0]) == [256, 0, 0, 256] && bits(dest[1]) == [0, -128, 128, 0];
+
+  // Then spin an object with the BIOS writing straight into the OAM shadow.
+  Cga8x8Thick.bitunpack_4bpp(OBJ_TILES.as_region(), 0);
+  obj_palbank(0).index(1).write(if ok { Color::GREEN } else { Color::RED });
+  let slot = AffineSlot::new(0);
+  let mut oam = OamShadow::new();
+  let obj = oam.obj_mut(0);
+  *obj = ObjAttr::new().with_affine(slot, true);
+  obj.set_x(120 - 8);
+  obj.set_y(80 - 8);
+  obj.set_tile_id(Cga8x8Thick::FACE as u16);
+  DISPCNT.write(DisplayControl::new().with_show_obj(true));
+
+  let mut source =
+    ObjAffineSource { inv_scale_x: one, inv_scale_y: one, angle: 0 };
+  loop {
+    source.angle = source.angle.wrapping_add(0x100);
+    oam.set_affine_from(slot, &source);
+    spin_until_vblank();
+    oam.commit_count(4);
+  }
+}
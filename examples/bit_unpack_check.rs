@@ -0,0 +1,37 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  // Elements are packed low bit first, so bit 0 becomes the low nibble.
+  let src = [0b1010_0101_u8, 0b1111_0000];
+  let mut dest = [0_u32; 2];
+
+  let info = BitUnpackInfo::new(2, 1, 4).unwrap();
+  bit_unpack(&src, &mut dest, &info);
+  let plain_ok = dest == [0x1010_0101, 0x1111_0000];
+
+  // With an offset of 2, 1s become 3s, and 0s only change with touch_zero.
+  bit_unpack(&src, &mut dest, &info.with_offset(2, false));
+  let offset_ok = dest == [0x3030_0303, 0x3333_0000];
+  bit_unpack(&src, &mut dest, &info.with_offset(2, true));
+  let touch_ok = dest == [0x3232_2323, 0x3333_2222];
+
+  // A destination narrower than the source isn't allowed.
+  let reject_ok = BitUnpackInfo::new(2, 4, 2).is_none();
+
+  DISPCNT.write(DisplayControl::new());
+  let ok = plain_ok && offset_ok && touch_ok && reject_ok;
+  BACKDROP_COLOR.write(if ok { Color::GREEN } else { Color::RED });
+  loop {
+    spin_until_vblank();
+  }
+}
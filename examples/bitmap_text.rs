@@ -0,0 +1,57 @@
+#![no_std]
+#![no_main]
+
+//! Prints the keypad state in video mode 3, with no tiles at all.
+//!
+//! The top line is redrawn every frame with `mode3::draw_text`, and shows the
+//! keys held right now. Below it, a `BitmapConsole` logs each key as it's
+//! pressed, and scrolls up once it fills the screen. A see-through label sits
+//! over a colored box, to show text with no background.
+
+use core::fmt::Write;
+use gba::{
+  collections::ArrayString,
+  prelude::*,
+  video::mode3::{self, BitmapConsole},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  init_vblank_irq();
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  let mut console = BitmapConsole::new(Color::WHITE, Color::BLACK);
+  console.clear();
+  console.set_cursor(0, 2);
+  writeln!(console, "press some keys").ok();
+
+  let mut keys = KeyTracker::new();
+  let mut line = ArrayString::<30>::new();
+  let mut frames = 0_u32;
+  loop {
+    wait_for_vblank();
+    keys.update(KEYINPUT.read());
+
+    line.clear();
+    write!(line, "{}", keys.held()).ok();
+    // pad with spaces, to cover up the end of a longer line from before.
+    while line.push(' ').is_ok() {}
+    mode3::draw_text(0, 0, Color::YELLOW, Some(Color::BLACK), &line);
+    mode3::rect_filled(176, 136, 64, 24, Color::BLUE);
+    mode3::draw_text(184, 144, Color::WHITE, None, "LABEL");
+
+    let pressed = keys.just_pressed();
+    if pressed.any() {
+      writeln!(console, "{frames:>6}: {pressed}").ok();
+    }
+    frames += 1;
+  }
+}
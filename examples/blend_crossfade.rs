@@ -0,0 +1,58 @@
+#![no_std]
+#![no_main]
+
+use gba::{
+  math::interp::{Easing, Tween},
+  prelude::*,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(CHARBLOCK0_4BPP.as_region(), 0);
+  bg_palbank(0).index(1).write(Color::WHITE);
+  bg_palbank(1).index(1).write(Color::RED);
+
+  // BG0 and BG1 each show the font, but with different palbanks and offset
+  // by half a tile so that the two layers are easy to tell apart.
+  for (screenblock, palbank) in [(30, 0), (31, 1)] {
+    let tsb = TEXT_SCREENBLOCKS.get_frame(screenblock).unwrap();
+    for y in 0..16 {
+      let row = tsb.get_row(y).unwrap();
+      for (x, addr) in row.iter().enumerate().take(16) {
+        let tile = (y * 16 + x) as u16;
+        addr.write(TextEntry::from_tile(tile).with_palbank(palbank));
+      }
+    }
+  }
+  BG0CNT.write(BackgroundControl::new().with_screenblock(30));
+  BG1CNT.write(BackgroundControl::new().with_screenblock(31));
+  BG1HOFS.write(4);
+  BG1VOFS.write(4);
+  DISPCNT.write(DisplayControl::new().with_show_bg0(true).with_show_bg1(true));
+
+  BLDCNT.write(
+    BlendControl::new()
+      .with_target1_bg1(true)
+      .with_target2_bg0(true)
+      .with_target2_backdrop(true)
+      .with_mode(ColorEffectMode::AlphaBlend),
+  );
+
+  // BG1 fades in as BG0 fades out, and then back the other way, easing in and
+  // out at each end.
+  let mut fade = Tween::new(0_u16, 16, 32, Easing::Smoothstep);
+  loop {
+    spin_until_vblank();
+    let step = fade.tick();
+    BLDALPHA.write(BlendAlpha::from_coefficients(step, 16 - step));
+    if fade.is_finished() {
+      fade.reverse();
+    }
+  }
+}
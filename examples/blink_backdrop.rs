@@ -0,0 +1,22 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  // On for half a second, off for half a second.
+  loop {
+    BACKDROP_COLOR.write(Color::YELLOW);
+    delay_ms(500);
+    BACKDROP_COLOR.write(Color::BLACK);
+    delay_ms(500);
+  }
+}
@@ -0,0 +1,110 @@
+#![no_std]
+#![no_main]
+
+//! Times each of the ways that `copy_words` can copy, and logs the results to
+//! mGBA.
+//!
+//! For each source (IWRAM, EWRAM, and the ROM) and each destination (IWRAM
+//! and VRAM), DMA3, `CpuFastSet`, and a plain loop each copy 32 words and then
+//! 256 words. The difference between the two gives the cycles per word, and
+//! what's left of the short copy is the setup cost. These are the numbers that
+//! `COPY_DMA_MIN_WORDS` and `COPY_FAST_SET_MIN_WORDS` trade off, so run this
+//! with your own wait state settings to tune them.
+
+use gba::{
+  bios::cpu_fast_copy, dma::dma3_copy_u32_slice, prelude::*, profile::profiled,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  gba::mgba_error!("{info}");
+  loop {}
+}
+
+/// One of the ways to copy.
+type CopyFn = fn(&[u32], &mut [u32]);
+
+const SHORT: usize = 32;
+const LONG: usize = 256;
+
+static ROM_WORDS: [u32; LONG] = [0x1234_5678; LONG];
+
+gba::iwram_data! {
+  static mut IWRAM_SRC: [u32; LONG] = [0; LONG];
+}
+
+gba::iwram_data! {
+  static mut IWRAM_DEST: [u32; LONG] = [0; LONG];
+}
+
+gba::ewram_data! {
+  static mut EWRAM_SRC: [u32; LONG] = [0; LONG];
+}
+
+/// The same loop that `copy_words` uses for small copies.
+#[inline(never)]
+fn copy_loop(src: &[u32], dest: &mut [u32]) {
+  for (d, s) in dest.iter_mut().zip(src) {
+    unsafe { (d as *mut u32).write_volatile(*s) };
+  }
+}
+
+/// Logs the cycles per word and setup cost, from a short and a long copy.
+fn log_path(names: [&str; 3], short: u32, long: u32) {
+  let [src, dest, path] = names;
+  let hundredths = (long - short) * 100 / (LONG - SHORT) as u32;
+  let setup = short.saturating_sub(hundredths * SHORT as u32 / 100);
+  gba::mgba_info!(
+    "{src} to {dest}, {path}: {}.{:02} cycles per word, {setup} of setup",
+    hundredths / 100,
+    hundredths % 100
+  );
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  // Safety: these are the only references to the buffers.
+  let (iwram_src, iwram_dest, ewram_src) = unsafe {
+    (
+      &mut *core::ptr::addr_of_mut!(IWRAM_SRC),
+      &mut *core::ptr::addr_of_mut!(IWRAM_DEST),
+      &mut *core::ptr::addr_of_mut!(EWRAM_SRC),
+    )
+  };
+  // Safety: object VRAM isn't being used for anything else, and it's only
+  // written with whole words.
+  let vram_dest = unsafe {
+    core::slice::from_raw_parts_mut(
+      OBJ_TILES.index(0).as_usize() as *mut u32,
+      LONG,
+    )
+  };
+  let sources: [(&str, &[u32]); 3] =
+    [("IWRAM", iwram_src), ("EWRAM", ewram_src), ("ROM", &ROM_WORDS)];
+  let mut dests: [(&str, &mut [u32]); 2] =
+    [("IWRAM", iwram_dest), ("VRAM", vram_dest)];
+  let paths: [(&str, CopyFn); 3] = [
+    ("DMA3", dma3_copy_u32_slice),
+    ("CpuFastSet", cpu_fast_copy),
+    ("loop", copy_loop),
+  ];
+
+  for (src_name, src) in sources {
+    for (dest_name, dest) in dests.iter_mut() {
+      for (path_name, copy) in paths {
+        let ((), short) = profiled(|| copy(&src[..SHORT], &mut dest[..SHORT]));
+        let ((), long) = profiled(|| copy(src, dest));
+        assert_eq!(dest[LONG - 1], src[LONG - 1]);
+        log_path([src_name, dest_name, path_name], short, long);
+      }
+    }
+  }
+
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
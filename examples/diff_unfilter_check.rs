@@ -0,0 +1,64 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// `EXPECTED_8` after an 8-bit difference filter.
+static FILTERED_8: [u32; 3] = [
+  // header: difference filter, 8-bit units, 8 bytes unfiltered
+  0x0000_0881,
+  // 0x0A, then +2, +3, +0
+  0x0003_020A,
+  // +5, -2, -2, +16
+  0x10FE_FE05,
+];
+
+const EXPECTED_8: [u8; 8] = [0x0A, 0x0C, 0x0F, 0x0F, 0x14, 0x12, 0x10, 0x20];
+
+/// `EXPECTED_16` after a 16-bit difference filter.
+static FILTERED_16: [u32; 3] = [
+  // header: difference filter, 16-bit units, 8 bytes unfiltered
+  0x0000_0882,
+  // 1000, then +10
+  0x000A_03E8,
+  // -20, +1010
+  0x03F2_FFEC,
+];
+
+const EXPECTED_16: [u16; 4] = [1000, 1010, 990, 2000];
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let mut bytes = [0_u8; 8];
+  let mut halfwords = [0_u16; 4];
+  let vram = CHARBLOCK0_4BPP.as_region();
+  let ok = diff_8bit_unfilter_wram(&FILTERED_8, &mut bytes) == Ok(8)
+    && bytes == EXPECTED_8
+    && diff_16bit_unfilter(&FILTERED_16, &mut halfwords) == Ok(4)
+    && halfwords == EXPECTED_16
+    && diff_8bit_unfilter_vram(&FILTERED_8, vram) == Ok(8)
+    && vram.index(0).read()[..2]
+      == [
+        u32::from_le_bytes([0x0A, 0x0C, 0x0F, 0x0F]),
+        u32::from_le_bytes([0x14, 0x12, 0x10, 0x20]),
+      ]
+    // Each function only takes its own unit size.
+    && diff_8bit_unfilter_wram(&FILTERED_16, &mut bytes)
+      == Err(DecompressError::BadHeader)
+    && diff_16bit_unfilter(&FILTERED_8, &mut halfwords)
+      == Err(DecompressError::BadHeader)
+    && diff_16bit_unfilter(&FILTERED_16, &mut halfwords[..3])
+      == Err(DecompressError::DestTooSmall);
+
+  DISPCNT.write(DisplayControl::new());
+  BACKDROP_COLOR.write(if ok { Color::GREEN } else { Color::RED });
+  loop {
+    spin_until_vblank();
+  }
+}
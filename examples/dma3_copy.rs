@@ -0,0 +1,46 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+/// 16 solid tiles, tile `n` is filled with palette index `n`.
+static TILES: [Tile4; 16] = {
+  let mut tiles = [[0; 8]; 16];
+  let mut n = 0;
+  while n < 16 {
+    tiles[n] = [0x1111_1111 * n as u32; 8];
+    n += 1;
+  }
+  tiles
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let dest = charblock_4bpp(0).index(0).as_usize() as *mut u32;
+  unsafe { dma3_copy_u32(TILES.as_flattened(), dest) };
+
+  for (i, addr) in bg_palbank(0).iter().enumerate() {
+    let i = i as u16;
+    addr.write(Color::from_rgb(i * 2, 31 - i * 2, 16));
+  }
+
+  // Stripes of each tile across the screen.
+  let tsb = text_screenblock(31);
+  for y in 0..32 {
+    for x in 0..32 {
+      tsb.index(x, y).write(TextEntry::from_tile(((x + y) % 16) as u16));
+    }
+  }
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,51 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+/// Three palbanks worth of colors (as pairs of colors per word), copied one
+/// after the other by chaining DMA3 completion interrupts.
+static PALETTES: [[u32; 8]; 3] = [
+  [0x001F_001F; 8], // red
+  [0x03E0_03E0; 8], // green
+  [0x7C00_7C00; 8], // blue
+];
+
+static COPIES_DONE: GbaCell<u8> = GbaCell::new(0);
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+fn start_copy(n: usize) {
+  let dest = bg_palbank(n).index(0).as_usize() as *mut u32;
+  unsafe { dma3_copy_async(&PALETTES[n], dest) };
+}
+
+extern "C" fn on_dma3_done(_: IrqBits) {
+  let done = COPIES_DONE.read() + 1;
+  COPIES_DONE.write(done);
+  if usize::from(done) < PALETTES.len() {
+    start_copy(usize::from(done));
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  DmaChannel::Dma3.set_irq_handler(Some(on_dma3_done));
+  IME.write(true);
+
+  start_copy(0);
+  while COPIES_DONE.read() < 3 {}
+
+  // Show the backdrop as the last color copied, which is only blue if all
+  // three copies of the chain ran.
+  BACKDROP_COLOR.write(bg_palbank(2).index(0).read());
+  DISPCNT.write(DisplayControl::new());
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+use core::ffi::c_void;
+use gba::prelude::*;
+
+/// The backdrop color for each scanline. Entry 0 is for line 1, because each
+/// hblank DMA sets the color of the line *after* it.
+static SKY: [Color; 160] = {
+  let mut colors = [Color::BLACK; 160];
+  let mut i = 0;
+  while i < 160 {
+    let line = (i + 1) as u16;
+    colors[i] = Color::from_rgb(line / 16, line / 8, 31 - line / 10);
+    i += 1;
+  }
+  colors
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+
+  let control = DmaControl::new()
+    .with_dest_addr_control(DestAddrControl::Fixed)
+    .with_repeat(true)
+    .with_start_time(DmaStartTime::HBlank);
+  loop {
+    spin_until_vblank();
+    // The source address doesn't reset on repeat, so restart each frame.
+    DmaChannel::Dma1.stop();
+    BACKDROP_COLOR.write(Color::from_rgb(0, 0, 31));
+    unsafe {
+      DmaChannel::Dma1.start(
+        SKY.as_ptr().cast(),
+        BACKDROP_COLOR.as_usize() as *mut c_void,
+        1,
+        control,
+      )
+    };
+  }
+}
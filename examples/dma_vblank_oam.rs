@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+use core::ffi::c_void;
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+static mut OAM: OamShadow = OamShadow::new();
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(OBJ_TILES.as_region(), 0);
+  obj_palbank(0).index(1).write(Color::MAGENTA);
+  DISPCNT.write(DisplayControl::new().with_show_obj(true));
+
+  // Copies all of OAM at the start of the next vblank, without the CPU
+  // needing to wait for it.
+  let commit = DmaControl::new()
+    .with_transfer_32bit(true)
+    .with_start_time(DmaStartTime::VBlank);
+
+  let mut x = 0_u16;
+  loop {
+    x = (x + 1) % 240;
+    let oam = unsafe { &mut *core::ptr::addr_of_mut!(OAM) };
+    let obj = oam.obj_mut(0);
+    *obj = ObjAttr::new();
+    obj.set_x(x);
+    obj.set_y(76);
+    obj.set_tile_id(Cga8x8Thick::DIAMOND as u16);
+
+    // Wait for last frame's commit to be done before scheduling this one.
+    while DmaChannel::Dma3.is_active() {}
+    unsafe {
+      DmaChannel::Dma3.start(
+        core::ptr::addr_of!(OAM).cast(),
+        OBJ_ATTR_ALL.index(0).as_usize() as *mut c_void,
+        1024 / 4,
+        commit,
+      )
+    };
+    spin_until_vdraw();
+  }
+}
@@ -0,0 +1,79 @@
+#![no_std]
+#![no_main]
+
+//! Finds the size of the EEPROM, then writes it and reads it back.
+//!
+//! With mGBA, set the save type to "EEPROM" (or leave it on autodetect, which
+//! picks EEPROM because of the save type string).
+//!
+//! The bars at the top are the steps: size detection (white for 8 KiB, gray
+//! for 512 bytes), a whole block write, a write that starts and ends partway
+//! into blocks, and a check that the bytes around that write didn't change.
+//! Each of the other bars shows green when the step worked, or red when it
+//! failed.
+
+use gba::{prelude::*, save::eeprom::*, video::mode3};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+fn show_step(step: i32, color: Color) {
+  mode3::rect_filled(8, 8 + step * 16, mode3::WIDTH - 16, 12, color);
+}
+
+fn pass(ok: bool) -> Color {
+  if ok {
+    Color::GREEN
+  } else {
+    Color::RED
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  mode3::clear_to(Color::BLACK);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  match Eeprom::detect_size() {
+    Ok(size) => {
+      show_step(
+        0,
+        match size {
+          EepromSize::_8KiB => Color::WHITE,
+          EepromSize::_512B => {
+            Color::new().with_red(16).with_green(16).with_blue(16)
+          }
+        },
+      );
+      let eeprom = Eeprom::new(size);
+
+      let mut block = [0; 8];
+      let ok = eeprom.write_block(5, b"GBA SAVE").is_ok()
+        && eeprom.read_block(5, &mut block).is_ok()
+        && &block == b"GBA SAVE";
+      show_step(1, pass(ok));
+
+      // Set blocks 8 to 10 to a known value, then write over the middle.
+      let fill = [0x11; 24];
+      let data = *b"partial write";
+      let mut readback = [0; 24];
+      let ok = eeprom.write(64, &fill).is_ok()
+        && eeprom.write(64 + 5, &data).is_ok()
+        && eeprom.read(64, &mut readback).is_ok();
+      show_step(2, pass(ok && readback[5..][..data.len()] == data));
+      let mut untouched =
+        readback[..5].iter().chain(&readback[5 + data.len()..]);
+      show_step(3, pass(ok && untouched.all(|&byte| byte == 0x11)));
+    }
+    Err(_) => show_step(0, Color::RED),
+  }
+
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,32 @@
+#![no_std]
+#![no_main]
+
+//! Shows what [`detect`] finds, for checking it by hand in each emulator.
+//!
+//! The backdrop is green in mGBA, blue in no$gba, and gray anywhere else (such
+//! as on real hardware). The result is also logged, so it shows in the
+//! emulator's log (with the version, in no$gba).
+
+use gba::{environment::*, prelude::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let environment = detect();
+  BACKDROP_COLOR.write(match environment {
+    Environment::Mgba => Color::GREEN,
+    Environment::Nocash { .. } => Color::BLUE,
+    Environment::UnknownOrHardware => Color::GRAY,
+  });
+  DISPCNT.write(DisplayControl::new());
+  gba::mgba_info!("running in {environment:?}");
+
+  loop {
+    gba::bios::Halt();
+  }
+}
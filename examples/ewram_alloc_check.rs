@@ -0,0 +1,68 @@
+#![no_std]
+#![no_main]
+
+//! Checks the EWRAM allocator. Run this with `--features ewram_alloc`.
+//!
+//! This makes and drops a few thousand allocations of mixed sizes (keeping
+//! some around for a while, so the heap gets split up), and then checks that
+//! the heap merges back into one block, and that a single allocation of the
+//! whole heap works. The backdrop goes green if it all worked, and red if not
+//! (with the reason in mGBA's log).
+
+extern crate alloc;
+
+use alloc::{boxed::Box, vec::Vec};
+use gba::{ewram_alloc::*, prelude::*, random::Lcg32};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  use core::fmt::Write;
+  if let Ok(mut logger) = MgbaBufferedLogger::try_new(MgbaMessageLevel::Error) {
+    writeln!(logger, "{info}").ok();
+  }
+  BACKDROP_COLOR.write(Color::RED);
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  let size = heap_size();
+  assert_eq!(heap_free_bytes(), size);
+
+  let mut rng = Lcg32::new(1);
+  let mut kept: Vec<Vec<u8>> = Vec::new();
+  for round in 0..4000_u32 {
+    let len = 1 + (rng.next_u32() % 300) as usize;
+    let fill = round as u8;
+    let mut bytes = Vec::with_capacity(len);
+    bytes.resize(len, fill);
+    // A few boxes with bigger alignment, just to drop right away.
+    let aligned = Box::new(Aligned32([round; 8]));
+    assert_eq!(&*aligned as *const Aligned32 as usize % 32, 0);
+    assert_eq!(aligned.0[7], round);
+    if round.is_multiple_of(3) {
+      kept.push(bytes);
+    }
+    if kept.len() > 64 {
+      // Drop one from the middle, leaving a hole.
+      let old = kept.swap_remove((rng.next_u32() as usize) % kept.len());
+      assert!(old.iter().all(|&b| b == old[0]));
+    }
+  }
+  drop(kept);
+
+  assert_eq!(heap_free_bytes(), size);
+  assert_eq!(heap_largest_free_block(), size);
+  let whole = Vec::<u8>::with_capacity(size);
+  drop(whole);
+
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
+
+#[repr(align(32))]
+struct Aligned32([u32; 8]);
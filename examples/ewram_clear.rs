@@ -0,0 +1,35 @@
+#![no_std]
+#![no_main]
+
+use gba::{prelude::*, profile::log_profiled};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[link_section = ".ewram"]
+static mut BUFFER: [u32; 8 * 1024] = [0; 8 * 1024];
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  // Safety: this is the only reference to the buffer.
+  let buffer = unsafe { &mut *core::ptr::addr_of_mut!(BUFFER) };
+
+  // Log how long it takes to clear 32K of EWRAM both ways (the results show up
+  // in mGBA's log window).
+  log_profiled("loop", || {
+    for word in buffer.iter_mut() {
+      unsafe { (word as *mut u32).write_volatile(0) };
+    }
+  });
+  log_profiled("cpu_fast_fill", || cpu_fast_fill(0, buffer));
+  log_profiled("cpu_fill32", || cpu_fill32(0, buffer));
+
+  DISPCNT.write(DisplayControl::new());
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
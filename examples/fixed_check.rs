@@ -0,0 +1,103 @@
+#![no_std]
+#![no_main]
+
+//! Checks the fixed-point math against `f64` math.
+//!
+//! Random values go through multiply (truncating and rounded), divide, the
+//! saturating ops, `int`, and the hardware format conversions, and each result
+//! is compared to what the same math in `f64` gives. The backdrop goes green
+//! if they all match (a mismatch panics, which makes it red).
+
+use gba::{
+  fixed::{i32fx16, Fixed},
+  prelude::*,
+  random::Lcg32,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  gba::mgba_error!("{info}");
+  loop {}
+}
+
+/// Checked at compile time.
+const ONE_AND_A_QUARTER: i32fx16 = i32fx16::from_f32(1.25);
+
+fn floor(f: f64) -> f64 {
+  let t = f as i64 as f64;
+  if t > f {
+    t - 1.0
+  } else {
+    t
+  }
+}
+
+fn check_i32fx16(a: i32, b: i32) {
+  let (fa, fb) = (i32fx16::from_bits(a), i32fx16::from_bits(b));
+  let exact = (f64::from(a) / 65536.0) * (f64::from(b) / 65536.0) * 65536.0;
+  if exact.abs() < 2.0e9 {
+    assert_eq!(f64::from(fa.mul(fb).to_bits()), floor(exact), "{a} * {b}");
+    assert_eq!(
+      f64::from(fa.mul_rounded(fb).to_bits()),
+      floor(exact + 0.5),
+      "{a} * {b} rounded"
+    );
+  }
+  let clamped = floor(exact).clamp(f64::from(i32::MIN), f64::from(i32::MAX));
+  assert_eq!(f64::from(fa.saturating_mul(fb).to_bits()), clamped);
+  if b != 0 {
+    let quotient = f64::from(a) / f64::from(b) * 65536.0;
+    if quotient.abs() < 2.0e9 {
+      assert_eq!(fa.div(fb).to_bits(), quotient as i32, "{a} / {b}");
+    }
+  }
+  assert_eq!(f64::from(fa.int()), floor(f64::from(a) / 65536.0));
+  let to_8 = floor(f64::from(a) / 256.0);
+  assert_eq!(f64::from(fa.to_i32fx8().to_bits()), to_8);
+  assert_eq!(
+    f64::from(fa.to_i16fx8().to_bits()),
+    to_8.clamp(f64::from(i16::MIN), f64::from(i16::MAX))
+  );
+}
+
+fn check_i16fx8(a: i16, b: i16) {
+  let (fa, fb) = (Fixed::<i16, 8>::from_bits(a), Fixed::<i16, 8>::from_bits(b));
+  let exact = floor(f64::from(a) * f64::from(b) / 256.0);
+  assert_eq!(fa.wrapping_mul(fb).to_bits(), exact as i64 as i16);
+  assert_eq!(
+    f64::from(fa.saturating_mul(fb).to_bits()),
+    exact.clamp(f64::from(i16::MIN), f64::from(i16::MAX))
+  );
+  assert_eq!(
+    i32::from(fa.saturating_add(fb).to_bits()),
+    (i32::from(a) + i32::from(b)).clamp(-32768, 32767)
+  );
+  assert_eq!(fa.widen().to_bits(), i32::from(a));
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  assert_eq!(ONE_AND_A_QUARTER.to_bits(), 0x1_4000);
+  assert_eq!(i32fx16::from_f32(-0.5).to_bits(), -0x8000);
+  assert_eq!(i32fx16::from_int(-3).to_bits(), -3 << 16);
+  // -0.5 * 1/256 truncates to -1/256, but rounds to 0.
+  let half = Fixed::<i32, 8>::from_bits(-128);
+  let tiny = Fixed::<i32, 8>::from_bits(1);
+  assert_eq!(half.mul(tiny).to_bits(), -1);
+  assert_eq!(half.mul_rounded(tiny).to_bits(), 0);
+
+  let mut rng = Lcg32::new(0x1234_5678);
+  for _ in 0..2000 {
+    check_i32fx16((rng.next_u32() as i32) >> 10, (rng.next_u32() as i32) >> 14);
+    check_i16fx8(rng.next_u32() as i16, (rng.next_u32() >> 16) as i16);
+  }
+  gba::mgba_info!("fixed-point checks passed");
+
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
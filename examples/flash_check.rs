@@ -0,0 +1,71 @@
+#![no_std]
+#![no_main]
+
+//! Detects a flash save chip, then erases, writes, and checks it.
+//!
+//! With mGBA, set the save type to "Flash 512kb" or "Flash 1Mb" (or leave it
+//! on autodetect, which picks 1Mb because of the save type string).
+//!
+//! The bars at the top are the steps: detect, erase, write, overwrite (which
+//! needs the sector erased and rewritten), and a write across the middle of
+//! the chip (a bank switch on the 128 KiB chips). Each bar shows green when the
+//! step worked, or red when it failed. The whole screen is blue if no known
+//! chip was found.
+
+use gba::{prelude::*, save::flash::*, video::mode3};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+fn show_step(step: i32, ok: bool) {
+  let color = if ok { Color::GREEN } else { Color::RED };
+  mode3::rect_filled(8, 8 + step * 16, mode3::WIDTH - 16, 12, color);
+}
+
+fn write_and_check(chip: FlashChip, offset: usize, data: &[u8]) -> bool {
+  let mut buffer = [0; 64];
+  let buffer = &mut buffer[..data.len()];
+  chip.write(offset, data).is_ok()
+    && chip.read(offset, buffer).is_ok()
+    && buffer == data
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  mode3::clear_to(Color::BLACK);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  if let Some(kind) = FlashChip::detect() {
+    show_step(0, true);
+    let chip = FlashChip::new(kind);
+
+    let mut erased = chip.erase_sector(0).is_ok();
+    let mut buffer = [0; 64];
+    erased &= chip.read(0, &mut buffer).is_ok();
+    erased &= buffer.iter().all(|&byte| byte == 0xFF);
+    show_step(1, erased);
+
+    let mut pattern = [0; 64];
+    for (i, byte) in pattern.iter_mut().enumerate() {
+      *byte = (i as u8).wrapping_mul(37);
+    }
+    show_step(2, write_and_check(chip, 0x10, &pattern));
+    // Setting bits that were cleared needs an erase.
+    pattern.reverse();
+    show_step(3, write_and_check(chip, 0x10, &pattern));
+
+    let middle = kind.size() / 2 - pattern.len() / 2;
+    show_step(4, write_and_check(chip, middle, &pattern));
+  } else {
+    mode3::clear_to(Color::BLUE);
+  }
+
+  loop {
+    spin_until_vblank();
+  }
+}
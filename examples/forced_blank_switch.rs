@@ -0,0 +1,96 @@
+#![no_std]
+#![no_main]
+
+//! Switches between a mode 3 "title screen" and a mode 0 "game" with
+//! `with_forced_blank`, without any garbage frames in between.
+//!
+//! The mode 3 bitmap covers the same VRAM as the game's tiles and tilemap, so
+//! either one shown halfway loaded would be a mess. Press Start to switch
+//! between the two. In the game the face moves with the d-pad, and Select
+//! toggles the green swap effect.
+
+use core::fmt::Write;
+use gba::{
+  prelude::*,
+  video::{mode3, text::*},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+const TOP: Color = Color::from_rgb8(0x10, 0x20, 0x60);
+const BOTTOM: Color = Color::from_rgb8(0xF0, 0x90, 0x30);
+
+fn load_title() {
+  DISPCNT.write(
+    DisplayControl::new()
+      .with_video_mode(VideoMode::_3)
+      .with_show_bg2(true)
+      .with_forced_blank(true),
+  );
+  disable_green_swap();
+  for band in 0..16 {
+    let color = TOP.blend(BOTTOM, band as u16);
+    mode3::rect_filled(0, band * 10, mode3::WIDTH, 10, color);
+  }
+  mode3::rect_filled(60, 70, 120, 20, Color::WHITE);
+}
+
+fn load_game() {
+  DISPCNT.write(
+    DisplayControl::new()
+      .with_video_mode(VideoMode::_0)
+      .with_show_obj(true)
+      .with_forced_blank(true),
+  );
+  BACKDROP_COLOR.write(Color::from_rgb8(0x20, 0x50, 0x20));
+  bg_palbank(0).index(1).write(Color::WHITE);
+  obj_palbank(0).index(1).write(Color::YELLOW);
+  load_font_4bpp(0, 0, 1, 0);
+  Cga8x8Thick.bitunpack_4bpp(OBJ_TILES.as_region(), 0);
+  init_oam();
+
+  let mut writer = TextWriter::new(31, 0, 0);
+  writer.clear();
+  writer.set_cursor(1, 1);
+  writer.write_str("the game (start: title)").ok();
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  with_forced_blank(load_title);
+
+  let mut in_game = false;
+  let mut keys = KeyTracker::new();
+  let mut face = ObjAttr::new().with_size(ObjSize::_8x8);
+  face.set_tile_id(Cga8x8Thick::FACE as u16);
+  let (mut x, mut y) = (116_i32, 76_i32);
+  loop {
+    spin_until_vblank();
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().start() {
+      in_game = !in_game;
+      with_forced_blank(if in_game { load_game } else { load_title });
+      continue;
+    }
+    if in_game {
+      let (dx, dy) = keys.held().dpad();
+      x = (x + dx.to_i32()).clamp(0, 240 - 8);
+      y = (y + dy.to_i32()).clamp(0, 160 - 8);
+      face.set_x(x as u16);
+      face.set_y(y as u16);
+      write_obj_attr(0, face);
+      if keys.just_pressed().select() {
+        if GREEN_SWAP.read() {
+          disable_green_swap();
+        } else {
+          enable_green_swap();
+        }
+      }
+    }
+  }
+}
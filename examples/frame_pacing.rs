@@ -0,0 +1,80 @@
+#![no_std]
+#![no_main]
+
+//! Shows that a [`FramePacer`] keeps the game speed the same through lag.
+//!
+//! Both squares move one pixel per update. The top one gets one update per
+//! time around the loop, and the bottom one gets however many the pacer says.
+//! Every 60th time around, the loop is made to take about three frames on
+//! purpose. The top square stalls each time, and falls behind. The bottom one
+//! jumps ahead to where it should be, and keeps the same speed overall. The
+//! total lag frames are logged once a second.
+
+use gba::{pacing::*, prelude::*, time::frame_count};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+const SIZE: u16 = 16;
+
+fn draw_square(x: u16, y: u16, color: Color) {
+  for row in y..(y + SIZE) {
+    for col in x..(x + SIZE) {
+      VIDEO3_VRAM.index(usize::from(col), usize::from(row)).write(color);
+    }
+  }
+}
+
+/// One update: moves `x` a pixel, bouncing off the sides.
+fn step(x: &mut u16, dx: &mut i16) {
+  *x = x.wrapping_add_signed(*dx);
+  if *x == 0 || *x == 240 - SIZE {
+    *dx = -*dx;
+  }
+}
+
+/// Stands in for a frame with too much work to do.
+fn heavy_work() {
+  let start = frame_count();
+  while frame_count().wrapping_sub(start) < 3 {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+  IE.write(IrqBits::VBLANK);
+  IME.write(true);
+
+  video3_clear_to(Color::BLACK);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  let (mut naive_x, mut naive_dx) = (0_u16, 1_i16);
+  let (mut paced_x, mut paced_dx) = (0_u16, 1_i16);
+  let mut pacer = FramePacer::new(1, 8);
+  let mut loops = 0_u32;
+  loop {
+    VBlankIntrWait();
+    let frame = pacer.tick();
+
+    draw_square(naive_x, 40, Color::BLACK);
+    step(&mut naive_x, &mut naive_dx);
+    draw_square(naive_x, 40, Color::YELLOW);
+
+    draw_square(paced_x, 104, Color::BLACK);
+    for _ in 0..frame.steps {
+      step(&mut paced_x, &mut paced_dx);
+    }
+    draw_square(paced_x, 104, Color::GREEN);
+
+    loops += 1;
+    if loops.is_multiple_of(60) {
+      gba::mgba_info!("{} lag frames so far", pacer.lag_frames_total());
+      heavy_work();
+    }
+  }
+}
@@ -0,0 +1,59 @@
+#![no_std]
+#![no_main]
+
+//! Shows the frame timing stats in the top left corner.
+//!
+//! Each frame does a made-up workload of 70,000 cycles (about a quarter of a
+//! frame). Hold A to make it 400,000 cycles instead, which is more than a
+//! whole frame, so the CPU usage goes over 100% and the frame rate drops to
+//! 30. Press Select to log the stats to mGBA.
+
+use gba::{
+  perf::FrameTimer,
+  prelude::*,
+  video::{palram::set_backdrop, text::*},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  bg_palbank(0).index(1).write(Color::WHITE);
+  set_backdrop(Color::from_rgb(0, 0, 12));
+  load_font_4bpp(0, 0, 1, 0);
+  let mut writer = TextWriter::new(31, 0, 0);
+  writer.clear();
+  writer.set_cursor(0, 3);
+  core::fmt::Write::write_str(&mut writer, "hold A for a heavy workload").ok();
+
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+
+  DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+  IE.write(IrqBits::VBLANK);
+  IME.write(true);
+
+  // `busy_wait_cycles` uses timer 3, so the stats use timers 0 and 1.
+  let timers = WideTimer::new(Timer::Timer0);
+  timers.start(TimerScale::_1);
+  let mut perf = FrameTimer::<30>::new(timers);
+  let mut keys = KeyTracker::new();
+
+  loop {
+    perf.frame_start();
+    keys.update(KEYINPUT.read());
+    let workload = if keys.held().a() { 400_000 } else { 70_000 };
+    busy_wait_cycles(workload);
+    if keys.just_pressed().select() {
+      perf.log_mgba();
+    }
+    perf.frame_end();
+
+    VBlankIntrWait();
+    perf.draw_overlay(&mut writer, 0, 0);
+  }
+}
@@ -3,6 +3,7 @@
 
 use gba::prelude::*;
 
+#[cfg(not(feature = "panic_handler"))]
 #[panic_handler]
 fn panic_handler(info: &core::panic::PanicInfo) -> ! {
   #[cfg(debug_assertions)]
@@ -129,8 +130,7 @@ extern "C" fn main() -> ! {
     }
   }
 
-  let no_display = ObjAttr0::new().with_style(ObjDisplayStyle::NotDisplayed);
-  OBJ_ATTR0.iter().skip(creatures.len()).for_each(|va| va.write(no_display));
+  hide_objects(creatures.len(), 128 - creatures.len());
 
   DISPCNT.write(DisplayControl::new().with_show_obj(true).with_show_bg0(true));
 
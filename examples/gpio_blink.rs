@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+//! Toggles the SO pin of the link port at every vblank, which makes a square
+//! wave of about 29.9 Hz (half the frame rate) that can be checked with a
+//! logic analyzer. The backdrop follows the pin: white when high, black when
+//! low.
+//!
+//! Holding A stops the toggling, and B leaves general purpose mode (which
+//! puts `RCNT` back how it was).
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  init_vblank_irq();
+  let gpio = enter_gpio_mode(PinDirections::new().with_output(Pin::So, true));
+
+  let mut high = false;
+  loop {
+    wait_for_vblank();
+    let keys = KEYINPUT.read();
+    if keys.b() {
+      break;
+    }
+    if !keys.a() {
+      high = !high;
+      gpio.set_pin(Pin::So, high);
+    }
+    BACKDROP_COLOR.write(if gpio.pin(Pin::So) {
+      Color::WHITE
+    } else {
+      Color::BLACK
+    });
+  }
+
+  gpio.exit();
+  BACKDROP_COLOR.write(Color::BLUE);
+  loop {
+    wait_for_vblank();
+  }
+}
@@ -4,6 +4,7 @@
 use core::fmt::Write;
 use gba::prelude::*;
 
+#[cfg(not(feature = "panic_handler"))]
 #[panic_handler]
 fn panic_handler(info: &core::panic::PanicInfo) -> ! {
   #[cfg(debug_assertions)]
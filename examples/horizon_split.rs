@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+/// The scanline where the "ground" starts.
+const HORIZON: u8 = 80;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+static GROUND_SCROLL: GbaCell<u16> = GbaCell::new(0);
+
+extern "C" fn on_horizon(_: IrqBits) {
+  BG0HOFS.write(GROUND_SCROLL.read());
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(CHARBLOCK0_4BPP.as_region(), 0);
+  bg_palbank(0).index(1).write(Color::WHITE);
+
+  let tsb = TEXT_SCREENBLOCKS.get_frame(31).unwrap();
+  for y in 0..32 {
+    let row = tsb.get_row(y).unwrap();
+    for (x, addr) in row.iter().enumerate() {
+      addr.write(TextEntry::from_tile(((y * 32 + x) % 256) as u16));
+    }
+  }
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  set_scanline_irq_handler(Some(on_horizon));
+  set_scanline_irq(HORIZON);
+
+  // The sky drifts slowly, the ground below the horizon moves faster.
+  let mut sky = 0_u16;
+  loop {
+    spin_until_vblank();
+    sky = sky.wrapping_add(1);
+    BG0HOFS.write(sky / 4);
+    GROUND_SCROLL.write(sky.wrapping_mul(2));
+  }
+}
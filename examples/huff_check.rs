@@ -0,0 +1,37 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// "ABBA" with 8-bit elements, and a tree with just 'A' (0) and 'B' (1).
+static COMPRESSED: [u32; 3] = [
+  // header: Huffman, 8 bits per element, 4 bytes decompressed
+  0x0000_0428,
+  // tree size 1, root node with two data children, 'A', 'B'
+  0x4241_C001,
+  // the bitstream, high bit first: 0, 1, 1, 0
+  0x6000_0000,
+];
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let mut buffer = [0_u32; 1];
+  let ok = huff_decompress(&COMPRESSED, &mut buffer) == Ok(4)
+    && buffer[0].to_le_bytes() == *b"ABBA"
+    && huff_decompress(&COMPRESSED, &mut []) == Err(DecompressError::DestTooSmall)
+    // 3 bits per element isn't allowed.
+    && huff_decompress(&[0x0000_0423, 0], &mut buffer)
+      == Err(DecompressError::BadHeader);
+
+  DISPCNT.write(DisplayControl::new());
+  BACKDROP_COLOR.write(if ok { Color::GREEN } else { Color::RED });
+  loop {
+    spin_until_vblank();
+  }
+}
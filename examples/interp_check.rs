@@ -0,0 +1,116 @@
+#![no_std]
+#![no_main]
+
+//! Checks the interpolation and easing math against `f64` math.
+//!
+//! Each function is tried at many values of `t` (including past both ends),
+//! and has to match the `f64` result rounded down, or be within a bit of it
+//! for the easing curves. The backdrop goes green if everything passes (a
+//! failure panics, which makes it red).
+
+use gba::{
+  fixed::{i16fx8, i32fx16},
+  math::interp::*,
+  prelude::*,
+  random::{Gen32, Xoshiro128},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  gba::mgba_error!("{info}");
+  loop {}
+}
+
+fn floor(f: f64) -> f64 {
+  let t = f as i64 as f64;
+  if t > f {
+    t - 1.0
+  } else {
+    t
+  }
+}
+
+fn t_of(bits: i32) -> i32fx16 {
+  i32fx16::from_bits(bits)
+}
+
+fn check_lerp(a: i32, b: i32, t: i32) {
+  let exact =
+    f64::from(a) + (f64::from(b) - f64::from(a)) * f64::from(t) / 65536.0;
+  let want = floor(exact).clamp(f64::from(i32::MIN), f64::from(i32::MAX));
+  assert_eq!(f64::from(lerp_unclamped(a, b, t_of(t))), want, "{a} {b} {t}");
+  let clamped = t.clamp(0, 1 << 16);
+  let exact =
+    f64::from(a) + (f64::from(b) - f64::from(a)) * f64::from(clamped) / 65536.0;
+  assert_eq!(f64::from(lerp(a, b, t_of(t))), floor(exact), "{a} {b} {t}");
+  if a != b {
+    let inverse = inverse_lerp(a, b, lerp(a, b, t_of(clamped)));
+    // going back only loses what the rounding down lost.
+    let step = 65536.0 / (f64::from(b) - f64::from(a)).abs();
+    let off = f64::from(inverse.to_bits() - clamped).abs();
+    assert!(off <= step + 1.0, "inverse_lerp {a} {b} {t}: {off}");
+  }
+}
+
+fn check_curve(name: &str, curve: fn(i32fx16) -> i32fx16, f: fn(f64) -> f64) {
+  for bits in (-0x100..=0x1_0100).step_by(97) {
+    let t = f64::from(bits.clamp(0, 1 << 16)) / 65536.0;
+    let want = f(t) * 65536.0;
+    let got = f64::from(curve(t_of(bits)).to_bits());
+    assert!((got - want).abs() <= 1.0, "{name}({bits:#X}): {got} vs {want}");
+  }
+  assert_eq!(curve(t_of(0)).to_bits(), 0, "{name}(0)");
+  assert_eq!(curve(ONE), ONE, "{name}(1)");
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+
+  let mut rng = Xoshiro128::from_seed(99);
+  for _ in 0..3000 {
+    let a = (rng.next_u32() as i32) >> rng.next_bounded(24);
+    let b = (rng.next_u32() as i32) >> rng.next_bounded(24);
+    let t = (rng.next_u32() as i32) >> (12 + rng.next_bounded(8));
+    check_lerp(a, b, t);
+  }
+  check_lerp(i32::MIN, i32::MAX, 1 << 30);
+
+  check_curve("ease_in_quad", ease_in_quad, |t| t * t);
+  check_curve("ease_out_quad", ease_out_quad, |t| 1.0 - (1.0 - t) * (1.0 - t));
+  check_curve("smoothstep", smoothstep, |t| t * t * (3.0 - 2.0 * t));
+  check_curve("ease_in_out_cubic", ease_in_out_cubic, |t| {
+    if t < 0.5 {
+      4.0 * t * t * t
+    } else {
+      let u = 2.0 - 2.0 * t;
+      1.0 - u * u * u / 2.0
+    }
+  });
+
+  // fixed-point values saturate rather than wrap.
+  let top = i16fx8::from_bits(i16::MAX - 10);
+  let over = lerp_unclamped(i16fx8::from_bits(0), top, t_of(2 << 16));
+  assert_eq!(over.to_bits(), i16::MAX);
+  // remap is exact where a `t` in the middle couldn't be.
+  assert_eq!(remap(1_u16, 0, 3, 0_u16, 15), 5);
+  assert_eq!(remap(-5_i32, 0, 10, 100_i32, 200), 100);
+  assert_eq!(remap(7_i32, 7, 7, 0_u8, 9), 9);
+
+  let mut tween = Tween::new(10_i32, 20, 4, Easing::Linear);
+  let steps =
+    [tween.tick(), tween.tick(), tween.tick(), tween.tick(), tween.tick()];
+  assert_eq!(steps, [12, 15, 17, 20, 20]);
+  assert!(tween.is_finished());
+  tween.reverse();
+  assert_eq!(tween.tick(), 17);
+  assert_eq!(Tween::new(1_u8, 2, 0, Easing::InQuad).value(), 2);
+
+  gba::mgba_info!("interpolation checks passed");
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
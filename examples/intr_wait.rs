@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// If the last interrupt that woke us up was the keypad.
+static WOKE_BY_KEYPAD: GbaCell<bool> = GbaCell::new(false);
+
+extern "C" fn on_keypad(_: IrqBits) {
+  WOKE_BY_KEYPAD.write(true);
+}
+
+extern "C" fn on_timer0(_: IrqBits) {
+  WOKE_BY_KEYPAD.write(false);
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  set_handler(Interrupt::Keypad, on_keypad);
+  set_handler(Interrupt::Timer0, on_timer0);
+  KEYCNT.write(KeyControl::new().with_a(true).with_irq_enabled(true));
+  // About once every four seconds.
+  Timer::Timer0.start(
+    0,
+    TimerControl::new().with_scale(TimerScale::_1024).with_overflow_irq(true),
+  );
+  DISPCNT.write(DisplayControl::new());
+
+  loop {
+    // Sleeps until either one happens (this also turns on IME).
+    IntrWait(true, IrqBits::KEYPAD | IrqBits::TIMER0);
+    let msg = if WOKE_BY_KEYPAD.read() {
+      BACKDROP_COLOR.write(Color::GREEN);
+      "woke up from the keypad"
+    } else {
+      BACKDROP_COLOR.write(Color::BLUE);
+      "woke up from timer 0"
+    };
+    if let Ok(mut logger) = MgbaBufferedLogger::try_new(MgbaMessageLevel::Info)
+    {
+      writeln!(logger, "{msg}").ok();
+    }
+  }
+}
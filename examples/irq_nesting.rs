@@ -0,0 +1,75 @@
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// Hblank interrupts seen so far this frame.
+static HBLANKS: GbaCell<u32> = GbaCell::new(0);
+/// Frames where some hblank interrupt didn't happen.
+static BAD_FRAMES: GbaCell<u32> = GbaCell::new(0);
+static FRAMES: GbaCell<u32> = GbaCell::new(0);
+
+extern "C" fn on_hblank(_: IrqBits) {
+  HBLANKS.write(HBLANKS.read() + 1);
+  // A color band per line, so a missed line would show up as a glitch.
+  let line = VCOUNT.read();
+  BACKDROP_COLOR.write(Color(line.wrapping_mul(0x0421) & 0x7FFF));
+}
+
+extern "C" fn on_vblank(_: IrqBits) {
+  // There's an hblank for every one of the 228 lines, including the vblank
+  // lines.
+  if HBLANKS.read() < 228 && FRAMES.read() > 0 {
+    BAD_FRAMES.write(BAD_FRAMES.read() + 1);
+  }
+  HBLANKS.write(0);
+  FRAMES.write(FRAMES.read() + 1);
+}
+
+/// Pretends to mix audio, taking several scanlines of time.
+extern "C" fn on_slow_timer(_: IrqBits) {
+  let _nesting = allow_nesting(IrqBits::HBLANK);
+  // About six scanlines.
+  let mut x = 0_u32;
+  for i in 0..1_500 {
+    x = core::hint::black_box(x.wrapping_add(i));
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  set_handler(Interrupt::HBlank, on_hblank);
+  set_handler(Interrupt::VBlank, on_vblank);
+  set_handler(Interrupt::Timer1, on_slow_timer);
+  DISPSTAT
+    .write(DisplayStatus::new().with_irq_vblank(true).with_irq_hblank(true));
+  // Several times per frame, at a rate that drifts against the display.
+  TIMER1_RELOAD.write(20_000_u16.wrapping_neg());
+  TIMER1_CONTROL
+    .write(TimerControl::new().with_overflow_irq(true).with_enabled(true));
+  IME.write(true);
+
+  DISPCNT.write(DisplayControl::new());
+  let mut next_log = 60;
+  loop {
+    VBlankIntrWait();
+    let frames = FRAMES.read();
+    if frames >= next_log {
+      next_log += 60;
+      if let Ok(mut logger) =
+        MgbaBufferedLogger::try_new(MgbaMessageLevel::Info)
+      {
+        writeln!(logger, "frames: {frames}, bad frames: {}", BAD_FRAMES.read())
+          .ok();
+      }
+    }
+  }
+}
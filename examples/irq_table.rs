@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+static VBLANKS: GbaCell<u32> = GbaCell::new(0);
+static KEY_IRQS: GbaCell<u32> = GbaCell::new(0);
+
+extern "C" fn on_vblank(_: IrqBits) {
+  VBLANKS.write(VBLANKS.read().wrapping_add(1));
+}
+
+extern "C" fn on_keypad(_: IrqBits) {
+  KEY_IRQS.write(KEY_IRQS.read().wrapping_add(1));
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  set_handler(Interrupt::VBlank, on_vblank);
+  set_handler(Interrupt::Keypad, on_keypad);
+  DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+  // Any of A, B, or Start.
+  KEYCNT.write(
+    KeyControl::new()
+      .with_a(true)
+      .with_b(true)
+      .with_start(true)
+      .with_irq_enabled(true),
+  );
+  IME.write(true);
+
+  DISPCNT.write(DisplayControl::new());
+  let mut next_log = 60;
+  loop {
+    VBlankIntrWait();
+    let frames = VBLANKS.read();
+    if frames >= next_log {
+      next_log += 60;
+      if let Ok(mut logger) =
+        MgbaBufferedLogger::try_new(MgbaMessageLevel::Info)
+      {
+        writeln!(logger, "vblanks: {frames}, key irqs: {}", KEY_IRQS.read())
+          .ok();
+      }
+    }
+  }
+}
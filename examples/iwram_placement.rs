@@ -0,0 +1,71 @@
+#![no_std]
+#![no_main]
+
+//! Checks that `iwram_code!`, `iwram_data!`, and `ewram_data!` put things
+//! where they say, and times the same loop from the ROM and from IWRAM.
+//!
+//! The backdrop goes green if everything is in the right place (and red if
+//! not), and the times are logged to mGBA. The IWRAM copy of the loop should
+//! be a lot faster.
+
+use gba::{mem::*, prelude::*, profile::log_profiled};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  loop {}
+}
+
+gba::ewram_data! {
+  static mut BIG: [u32; 16 * 1024] = [0; 16 * 1024];
+}
+
+gba::iwram_data! {
+  static mut SMALL: [u32; 16] = [0; 16];
+}
+
+/// Sums the words with a plain loop (this one stays in the ROM).
+#[inline(never)]
+fn sum_rom(words: &[u32]) -> u32 {
+  let mut total: u32 = 0;
+  let mut i = 0;
+  while i < words.len() {
+    total = total.wrapping_add(words[i]);
+    i += 1;
+  }
+  total
+}
+
+gba::iwram_code! {
+  /// The same loop as `sum_rom`, as ARM code in IWRAM.
+  fn sum_iwram(words: &[u32]) -> u32 {
+    let mut total: u32 = 0;
+    let mut i = 0;
+    while i < words.len() {
+      total = total.wrapping_add(words[i]);
+      i += 1;
+    }
+    total
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  assert!(in_iwram(sum_iwram as *const () as usize));
+  assert!(!in_iwram(sum_rom as *const () as usize));
+  assert!(in_ewram(core::ptr::addr_of!(BIG) as usize));
+  assert!(in_iwram(core::ptr::addr_of!(SMALL) as usize));
+
+  // Safety: this is the only reference to the buffer.
+  let big = unsafe { &*core::ptr::addr_of!(BIG) };
+  let rom = log_profiled("sum_rom", || sum_rom(big));
+  let iwram = log_profiled("sum_iwram", || sum_iwram(big));
+  assert_eq!(rom, iwram);
+
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
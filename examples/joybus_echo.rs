@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+//! A JOY Bus device that sends back every value the master writes.
+//!
+//! The backdrop shows the last event: blue for a reset, green for a received
+//! value, and yellow once the master has read the echo back. The user flags
+//! in the status count up with each event, so the master can see progress
+//! with just the status command.
+
+use gba::{prelude::*, sio::joybus::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  set_handler(Interrupt::Serial, joybus_irq);
+  init_vblank_irq();
+  enter_joybus_mode();
+
+  let mut events: u8 = 0;
+  loop {
+    wait_for_vblank();
+    while let Some(event) = poll() {
+      BACKDROP_COLOR.write(match event {
+        JoyEvent::Reset => Color::BLUE,
+        JoyEvent::Received(word) => {
+          joybus_send(word);
+          Color::GREEN
+        }
+        JoyEvent::SendComplete => Color::YELLOW,
+      });
+      events = events.wrapping_add(1);
+      set_joybus_user_flags(events & 0b11);
+    }
+  }
+}
@@ -0,0 +1,71 @@
+#![no_std]
+#![no_main]
+
+//! Checks the packing of the link port control types.
+//!
+//! Every `PortMode` is checked against the `RCNT` bits it should set, and
+//! every variant of each enum field reads back after it's set, without
+//! changing the fields around it. The backdrop goes green if everything passes
+//! (a failure panics, which makes it red).
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  loop {}
+}
+
+fn check_port_modes() {
+  let base = LinkPortControl::new()
+    .with_pin_levels(0b1010)
+    .with_pin_directions(0b0101)
+    .with_si_irq(true);
+  for (mode, bits) in [
+    (PortMode::Sio, 0b00),
+    (PortMode::SioAlt, 0b01),
+    (PortMode::Gpio, 0b10),
+    (PortMode::JoyBus, 0b11),
+  ] {
+    assert_eq!(LinkPortControl::new().with_mode(mode).to_u16(), bits << 14);
+    let set = base.with_mode(mode);
+    assert_eq!(set.to_u16(), base.to_u16() | bits << 14);
+    assert_eq!(set.mode(), mode);
+    assert_eq!(set.pin_levels(), 0b1010);
+    assert_eq!(set.pin_directions(), 0b0101);
+    assert!(set.si_irq());
+  }
+}
+
+fn check_serial_controls() {
+  let uart =
+    UartControl::new().with_baud_rate(BaudRate::_115200).with_eight_bits(true);
+  for parity in [Parity::Even, Parity::Odd] {
+    let set = uart.with_parity(parity);
+    assert_eq!(set.parity(), parity);
+    assert_eq!(set.baud_rate(), BaudRate::_115200);
+    assert!(set.eight_bits() && !set.cts_enabled() && !set.send_full());
+    assert!(set.is_uart_mode());
+  }
+
+  let normal =
+    NormalControl::new().with_internal_clock(true).with_si_high(true);
+  for speed in [ShiftSpeed::_256KHz, ShiftSpeed::_2MHz] {
+    let set = normal.with_speed(speed);
+    assert_eq!(set.speed(), speed);
+    assert!(set.internal_clock() && set.si_high() && !set.so_idle_high());
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  check_port_modes();
+  check_serial_controls();
+
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
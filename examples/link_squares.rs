@@ -0,0 +1,88 @@
+#![no_std]
+#![no_main]
+
+//! Run two (or up to four) copies linked together, for example with mGBA's
+//! "New multiplayer window". Each player moves their own square with the
+//! d-pad, and every square is drawn on every screen.
+//!
+//! The screen has a red border whenever the link isn't working.
+
+use gba::{prelude::*, video::mode3};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+const SIZE: i32 = 8;
+const PLAYER_COLORS: [Color; 4] =
+  [Color::RED, Color::GREEN, Color::BLUE, Color::YELLOW];
+
+/// Positions are sent as `x | (y << 8)`, which can never be `0xFFFF` on
+/// screen.
+fn pack(x: i32, y: i32) -> u16 {
+  (x as u16) | ((y as u16) << 8)
+}
+
+fn unpack(word: u16) -> (i32, i32) {
+  (i32::from(word & 0xFF), i32::from(word >> 8))
+}
+
+/// The bitmap covers the whole screen (so the backdrop never shows), and the
+/// squares stay inside this border.
+fn show_link(ok: bool) {
+  let color = if ok { Color::BLACK } else { Color::RED };
+  mode3::rect(0, 0, mode3::WIDTH, mode3::HEIGHT, color);
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  mode3::clear_to(Color::BLACK);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  show_link(false);
+  let mut session = loop {
+    if let Ok(session) = MultiplayerSession::establish(BaudRate::_115200) {
+      break session;
+    }
+    spin_until_vblank();
+  };
+
+  let (mut x, mut y) = ((mode3::WIDTH - SIZE) / 2, (mode3::HEIGHT - SIZE) / 2);
+  let mut drawn: [Option<(i32, i32)>; 4] = [None; 4];
+  loop {
+    spin_until_vblank();
+
+    let keys = KEYINPUT.read();
+    x = (x + keys.right() as i32 - keys.left() as i32)
+      .clamp(1, mode3::WIDTH - SIZE - 1);
+    y = (y + keys.down() as i32 - keys.up() as i32)
+      .clamp(1, mode3::HEIGHT - SIZE - 1);
+
+    let words = match session.exchange(pack(x, y)) {
+      Ok(words) => {
+        show_link(true);
+        words
+      }
+      Err(_) => {
+        show_link(false);
+        continue;
+      }
+    };
+
+    for (player, word) in words.iter().enumerate() {
+      if let Some((old_x, old_y)) = drawn[player] {
+        mode3::rect_filled(old_x, old_y, SIZE, SIZE, Color::BLACK);
+      }
+      drawn[player] = word.map(unpack);
+    }
+    for (player, position) in drawn.iter().enumerate() {
+      if let Some((x, y)) = position {
+        mode3::rect_filled(*x, *y, SIZE, SIZE, PLAYER_COLORS[player]);
+      }
+    }
+  }
+}
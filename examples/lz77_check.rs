@@ -0,0 +1,40 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// "AB" as two literals, then one back-reference that copies 14 more bytes
+/// starting from 2 bytes back.
+static COMPRESSED: [u32; 3] = [
+  // header: LZ77, 16 bytes decompressed
+  0x0000_1010,
+  // flags 0b0010_0000, 'A', 'B', first byte of the back-reference
+  0xB042_4120,
+  // last byte of the back-reference, then padding
+  0x0000_0001,
+];
+
+const EXPECTED: &[u8; 16] = b"ABABABABABABABAB";
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let mut buffer = [0_u8; 16];
+  let ok = lz77_decompress(&COMPRESSED, &mut buffer) == Ok(16)
+    && &buffer == EXPECTED
+    && lz77_decompress(&COMPRESSED, &mut buffer[..15])
+      == Err(DecompressError::DestTooSmall)
+    && lz77_decompress(&[0x0000_1020], &mut buffer)
+      == Err(DecompressError::BadHeader);
+
+  DISPCNT.write(DisplayControl::new());
+  BACKDROP_COLOR.write(if ok { Color::GREEN } else { Color::RED });
+  loop {
+    spin_until_vblank();
+  }
+}
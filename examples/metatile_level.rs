@@ -0,0 +1,188 @@
+#![no_std]
+#![no_main]
+
+//! A top-down level made of 2x2 metatiles, with collision flags.
+//!
+//! The level is 64x64 metatiles (128x128 tiles), built at compile time from a
+//! five entry metatile table, and streamed in by a `TiledCamera` that follows
+//! the player. Walk around with the d-pad: brick and water are solid, and the
+//! player is slowed down on sand.
+
+use gba::{
+  prelude::*,
+  video::{
+    camera::TiledCamera,
+    tilemap::{Metatile, MetatileMap},
+  },
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// Can't be walked through.
+const SOLID: u8 = 1 << 0;
+/// Walking is slower.
+const SLOW: u8 = 1 << 1;
+
+/// A row of a 4bpp tile from eight palette indexes, leftmost first.
+const fn row(pixels: [u32; 8]) -> u32 {
+  let mut word = 0;
+  let mut i = 0;
+  while i < 8 {
+    word |= pixels[i] << (i * 4);
+    i += 1;
+  }
+  word
+}
+
+const fn solid_tile(index: u32) -> Tile4 {
+  [index * 0x1111_1111; 8]
+}
+
+const BRICK_TILE: Tile4 = {
+  let mortar = row([4; 8]);
+  let left = row([4, 3, 3, 3, 3, 3, 3, 3]);
+  let middle = row([3, 3, 3, 3, 4, 3, 3, 3]);
+  [mortar, left, left, left, mortar, middle, middle, middle]
+};
+
+const GRASS_TILE: Tile4 = {
+  let plain = row([1; 8]);
+  let tuft = row([1, 1, 5, 1, 1, 1, 1, 1]);
+  [
+    plain,
+    tuft,
+    plain,
+    plain,
+    plain,
+    plain,
+    row([1, 1, 1, 1, 1, 5, 1, 1]),
+    plain,
+  ]
+};
+
+/// The level's tiles: grass, sand, water, and brick.
+static TILESET: [Tile4; 4] =
+  [GRASS_TILE, solid_tile(2), solid_tile(6), BRICK_TILE];
+
+const fn metatile(tile: u16, flags: u8) -> Metatile<2> {
+  let entry = TextEntry::from_tile(tile);
+  Metatile::new([[entry; 2]; 2], flags)
+}
+
+const GRASS: u16 = 0;
+const SAND: u16 = 1;
+const WATER: u16 = 2;
+const BRICK: u16 = 3;
+const FLOWERS: u16 = 4;
+
+static METATILES: [Metatile<2>; 5] = [
+  metatile(0, 0),
+  metatile(1, SLOW),
+  metatile(2, SOLID),
+  metatile(3, SOLID),
+  // grass, with the tufts flipped around so the repeats are less obvious.
+  Metatile::new(
+    [
+      [TextEntry::from_tile(0), TextEntry::from_tile(0).with_hflip(true)],
+      [TextEntry::from_tile(0).with_vflip(true), TextEntry::from_tile(0)],
+    ],
+    0,
+  ),
+];
+
+const SIZE: usize = 64;
+
+const fn make_grid() -> [u16; SIZE * SIZE] {
+  let mut grid = [GRASS; SIZE * SIZE];
+  let mut y = 0;
+  while y < SIZE {
+    let mut x = 0;
+    while x < SIZE {
+      let hash = (x * 7 + y * 13 + x * y) % 17;
+      grid[y * SIZE + x] = if x == 0 || y == 0 || x == SIZE - 1 || y == SIZE - 1
+      {
+        BRICK
+      } else if (x / 8) % 2 == 1 && (y / 8) % 2 == 1 && x % 8 > 2 && y % 8 > 2 {
+        // a pond in every other block, with a sand shore.
+        if x % 8 > 3 && x % 8 < 7 && y % 8 > 3 && y % 8 < 7 {
+          WATER
+        } else {
+          SAND
+        }
+      } else if x % 16 == 4 && y % 16 < 12 {
+        BRICK
+      } else if hash == 0 {
+        FLOWERS
+      } else {
+        GRASS
+      };
+      x += 1;
+    }
+    y += 1;
+  }
+  grid
+}
+
+static GRID: [u16; SIZE * SIZE] = make_grid();
+
+static LEVEL: MetatileMap<'static, 2> =
+  MetatileMap::new(&TILESET, &METATILES, &GRID, SIZE, SIZE);
+
+/// If an 8x8 box at `(x, y)` overlaps anything solid.
+fn blocked(x: i32, y: i32) -> bool {
+  [(x, y), (x + 7, y), (x, y + 7), (x + 7, y + 7)]
+    .iter()
+    .any(|&(x, y)| LEVEL.flags_at(x, y) & SOLID != 0)
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let size = TextBackgroundSize::_32x32;
+  let mut camera = TiledCamera::new(&LEVEL, BgLayer::Bg0, 31, size);
+  let (mut x, mut y) = (24_i32, 24_i32);
+  with_forced_blank(|| {
+    LEVEL.load_tileset(0, 0);
+    let palbank = bg_palbank(0);
+    palbank.index(1).write(Color::from_rgb8(0x40, 0xA0, 0x40));
+    palbank.index(2).write(Color::from_rgb8(0xE0, 0xD0, 0x90));
+    palbank.index(3).write(Color::from_rgb8(0xA0, 0x40, 0x30));
+    palbank.index(4).write(Color::from_rgb8(0xC0, 0xC0, 0xB0));
+    palbank.index(5).write(Color::from_rgb8(0x20, 0x70, 0x20));
+    palbank.index(6).write(Color::from_rgb8(0x30, 0x60, 0xD0));
+    Cga8x8Thick.bitunpack_4bpp(OBJ_TILES.as_region(), 0);
+    obj_palbank(0).index(1).write(Color::YELLOW);
+    init_oam();
+    DISPCNT
+      .write(DisplayControl::new().with_show_obj(true).with_forced_blank(true));
+    setup_text_background(BgLayer::Bg0, 0, 31, size, 0);
+    camera.jump_to(0, 0);
+  });
+
+  let mut player = ObjAttr::new().with_size(ObjSize::_8x8);
+  player.set_tile_id(Cga8x8Thick::FACE as u16);
+  loop {
+    let speed = if LEVEL.flags_at(x + 4, y + 4) & SLOW != 0 { 1 } else { 2 };
+    let (dx, dy) = KEYINPUT.read().dpad();
+    // each axis separately, so walls can be slid along.
+    let new_x = x + dx.to_i32() * speed;
+    if !blocked(new_x, y) {
+      x = new_x;
+    }
+    let new_y = y + dy.to_i32() * speed;
+    if !blocked(x, new_y) {
+      y = new_y;
+    }
+
+    spin_until_vblank();
+    let (cam_x, cam_y) = (x - (120 - 4), y - (80 - 4));
+    camera.update(cam_x.max(0) as usize, cam_y.max(0) as usize);
+    let (cam_x, cam_y) = camera.position();
+    player.set_x((x - cam_x as i32) as u16);
+    player.set_y((y - cam_y as i32) as u16);
+    write_obj_attr(0, player);
+  }
+}
@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+//! Logs a message at each level with the `mgba_*` macros.
+//!
+//! In mGBA, the messages show up in the log view (turn on the debug level to
+//! see all of them). Press A to log a message too long for mGBA's buffer,
+//! which gets split up into more than one entry, and Start to log a fatal
+//! message, which stops the emulator. The backdrop is green if mGBA logging is
+//! available, and red if not.
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let available = mgba_logging_available();
+  BACKDROP_COLOR.write(if available { Color::GREEN } else { Color::RED });
+  DISPCNT.write(DisplayControl::new());
+
+  gba::mgba_debug!("debug: the smallest details");
+  gba::mgba_info!("info: logging available = {available}");
+  gba::mgba_warning!("warning: {} is more than {}", 3, 2);
+  gba::mgba_error!("error: this one's just a test");
+
+  DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+  IE.write(IrqBits::VBLANK);
+  IME.write(true);
+
+  let mut keys = KeyTracker::new();
+  keys.update(KEYINPUT.read());
+  let mut frames: u32 = 0;
+  loop {
+    VBlankIntrWait();
+    frames += 1;
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().a() {
+      gba::mgba_info!(
+        "frame {frames}: {}",
+        "this message is longer than the 256 bytes that mGBA's log buffer \
+         holds, so the logger sends the first 256 bytes as one entry, and \
+         then keeps going with the rest as another entry. Nothing is lost, \
+         the message just shows up in two (or more) pieces. The pieces are \
+         split wherever the buffer fills up, even in the middle of a word."
+      );
+    }
+    if keys.just_pressed().start() {
+      gba::mgba_fatal!("fatal: stopped on frame {frames}");
+    }
+  }
+}
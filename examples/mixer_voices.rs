@@ -0,0 +1,88 @@
+#![no_std]
+#![no_main]
+
+use gba::{
+  prelude::*,
+  sound::{mixer::*, stream::*},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// The rate that the samples were "recorded" at.
+const SAMPLE_RATE: u32 = 16384;
+
+/// A short decaying "pluck", made at compile time so that it's in ROM.
+static PLUCK: [i8; 4096] = make_pluck();
+
+const fn make_pluck() -> [i8; 4096] {
+  let mut out = [0_i8; 4096];
+  let mut i = 0;
+  while i < out.len() {
+    // A 256 Hz square wave, fading out over the length of the sample.
+    let high = (i / 32) & 1 == 0;
+    let level = 100 - (i * 100 / out.len()) as i32;
+    out[i] = if high { level } else { -level } as i8;
+    i += 1;
+  }
+  out
+}
+
+static MIXER: IrqMutex<Mixer<4>> = IrqMutex::new(Mixer::new());
+
+extern "C" fn irq_handler(bits: IrqBits) {
+  if bits.vblank() {
+    stream_vblank();
+    MIXER.with_in_handler(fill_stream);
+  }
+}
+
+/// * A: the pluck.
+/// * B: the pluck, a fifth higher.
+/// * R: the pluck, an octave higher, panned right.
+/// * L: a looping drone (from the middle of the pluck), until Start.
+#[no_mangle]
+extern "C" fn main() -> ! {
+  RUST_IRQ_HANDLER.write(Some(irq_handler));
+  init_vblank_irq();
+  DISPCNT.write(DisplayControl::new());
+
+  let rate = StreamRate::nearest(18157);
+  gba::sound::enable();
+  start_stream(StreamConfig::new(rate).with_stereo(true)).unwrap();
+
+  let pluck =
+    Voice::new(&PLUCK).with_pitch(SAMPLE_RATE, rate.hz()).with_volume(40);
+  let fifth = pluck.with_rate(pluck.rate() * 3 / 2);
+  let octave = pluck.with_rate(pluck.rate() * 2).with_pan(64);
+  let drone = pluck.with_loop(1024, 1024 + 64).with_volume(24);
+
+  let mut keys = KeyTracker::new();
+  loop {
+    wait_for_vblank();
+    keys.update(KEYINPUT.read());
+    let pressed = keys.just_pressed();
+    MIXER.with(|mixer| {
+      for (key, voice) in
+        [(pressed.a(), pluck), (pressed.b(), fifth), (pressed.r(), octave)]
+      {
+        if key {
+          // Voice 3 is kept for the drone.
+          let slot = mixer.free_slot().filter(|&slot| slot < 3).unwrap_or(0);
+          mixer.play(slot, voice);
+        }
+      }
+      if pressed.l() {
+        mixer.play(3, drone);
+      }
+      if pressed.start() {
+        mixer.stop_all();
+      }
+      let playing = (0..4).filter(|&slot| mixer.is_playing(slot)).count();
+      BACKDROP_COLOR.write(Color::new().with_green(playing as u16 * 7));
+    });
+  }
+}
@@ -0,0 +1,51 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  BG_PALETTE.index(1).write(Color::BLUE);
+  BG_PALETTE.index(2).write(Color::YELLOW);
+
+  // Affine backgrounds always use 8bpp tiles. Tile 0 is solid, and tile 1 is
+  // an outlined box.
+  CHARBLOCK0_8BPP.index(0).write([0x01010101; 16]);
+  let mut boxed = [0x02020202_u32; 16];
+  for row in 1..7 {
+    boxed[row * 2] = 0x01010102;
+    boxed[row * 2 + 1] = 0x02010101;
+  }
+  CHARBLOCK0_8BPP.index(1).write(boxed);
+
+  // Size 0 affine backgrounds are 16x16 tiles, with two entries per `u8x2`.
+  let sb = AFFINE0_SCREENBLOCKS.get_frame(31).unwrap();
+  for y in 0..16 {
+    for x in 0..8 {
+      let tile = ((x + y) % 2) as u8;
+      sb.index(x, y).write(u8x2::from([tile, tile ^ 1]));
+    }
+  }
+  BG2CNT.write(
+    BackgroundControl::new().with_screenblock(31).with_is_affine_wrapping(true),
+  );
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_2).with_show_bg2(true),
+  );
+
+  let mut angle = 0_u16;
+  loop {
+    let one = i16fx8::from_bits(1 << 8);
+    let params =
+      BgAffineParams::from_scale_rotation(one, one, angle, (64, 64), (120, 80));
+    spin_until_vblank();
+    params.write_bg2();
+    angle = angle.wrapping_add(0x100);
+  }
+}
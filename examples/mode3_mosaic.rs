@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+use gba::{prelude::*, video::mode3};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+const FADE_FRAMES: u16 = 45;
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  // Some stripes and shapes, so that the effect is easy to see.
+  for x in (0..mode3::WIDTH).step_by(8) {
+    mode3::rect_filled(x, 0, 4, mode3::HEIGHT, Color::BLUE);
+  }
+  mode3::circle_filled(120, 80, 50, Color::YELLOW);
+  mode3::rect_filled(100, 60, 40, 40, Color::RED);
+
+  BG2CNT.write(BackgroundControl::new().with_mosaic(true));
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  let mut elapsed = 0_u16;
+  let mut pixelating = true;
+  loop {
+    spin_until_vblank();
+    MOSAIC.write(mosaic_fade(elapsed, FADE_FRAMES));
+    if pixelating {
+      elapsed += 1;
+      pixelating = elapsed < FADE_FRAMES;
+    } else {
+      elapsed -= 1;
+      pixelating = elapsed == 0;
+    }
+  }
+}
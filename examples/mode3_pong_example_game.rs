@@ -99,6 +99,7 @@ static SPRITE_POSITIONS: [GbaCell<u16>; 6] = [
   GbaCell::new(0),
 ];
 
+#[cfg(not(feature = "panic_handler"))]
 #[panic_handler]
 fn panic_handler(_: &core::panic::PanicInfo) -> ! {
   loop {}
@@ -8,6 +8,7 @@
 
 use gba::prelude::*;
 
+#[cfg(not(feature = "panic_handler"))]
 #[panic_handler]
 fn panic_handler(_: &core::panic::PanicInfo) -> ! {
   loop {}
@@ -0,0 +1,41 @@
+#![no_std]
+#![no_main]
+
+use gba::{prelude::*, video::mode3};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  mode3::clear_to(Color::BLACK);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  let r = 12;
+  let (mut x, mut y) = (40, 30);
+  let (mut dx, mut dy) = (2, 1);
+  loop {
+    spin_until_vblank();
+    mode3::circle_filled(x, y, r, Color::BLACK);
+
+    // The ball is allowed to go partly off screen, it just gets clipped.
+    x += dx;
+    y += dy;
+    if !(0..=mode3::WIDTH).contains(&x) {
+      dx = -dx;
+    }
+    if !(0..=mode3::HEIGHT).contains(&y) {
+      dy = -dy;
+    }
+
+    mode3::rect(0, 0, mode3::WIDTH, mode3::HEIGHT, Color::BLUE);
+    mode3::line(0, 0, mode3::WIDTH - 1, mode3::HEIGHT - 1, Color::GREEN);
+    mode3::circle_filled(x, y, r, Color::RED);
+    mode3::circle(x, y, r, Color::WHITE);
+  }
+}
@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+use gba::{prelude::*, video::mode4};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  BG_PALETTE.index(1).write(Color::BLUE);
+  BG_PALETTE.index(2).write(Color::CYAN);
+  BG_PALETTE.index(3).write(Color::WHITE);
+
+  mode4::clear_to(BitmapPage::Page0, 0);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_4).with_show_bg2(true),
+  );
+
+  let mut page = BitmapPage::Page1;
+  let mut t = 0_i32;
+  loop {
+    // Draw a scrolling dither pattern into the offscreen page. Each band is
+    // two palette indexes alternated every pixel, with the pattern swapped on
+    // every other row.
+    for y in 0..mode4::HEIGHT {
+      let band = (((y + t) / 16) % 3) as u16;
+      let pair = (band + 1) | (band << 8);
+      let pair = if y % 2 == 0 { pair } else { pair.swap_bytes() };
+      for x in (0..mode4::WIDTH).step_by(2) {
+        mode4::put_pixel_pair(page, x, y, pair);
+      }
+    }
+    mode4::rect_filled(page, 100 + (t % 40), 60, 33, 40, 3);
+    mode4::put_pixel(page, 119, 100, 1);
+
+    spin_until_vblank();
+    page = mode4::flip();
+    t += 1;
+  }
+}
@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+use gba::{prelude::*, video::mode5};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  // Draw a different picture into each page ahead of time.
+  mode5::clear_to(BitmapPage::Page0, Color::BLUE);
+  mode5::rect_filled(BitmapPage::Page0, 20, 20, 40, 40, Color::YELLOW);
+  mode5::clear_to(BitmapPage::Page1, Color::RED);
+  mode5::rect_filled(BitmapPage::Page1, 100, 68, 40, 40, Color::WHITE);
+  for page in [BitmapPage::Page0, BitmapPage::Page1] {
+    // The border should land right on the edge of the screen once stretched.
+    mode5::line(page, 0, 0, mode5::WIDTH - 1, 0, Color::GREEN);
+    mode5::line(page, 0, 0, 0, mode5::HEIGHT - 1, Color::GREEN);
+    mode5::line(
+      page,
+      mode5::WIDTH - 1,
+      mode5::HEIGHT - 1,
+      mode5::WIDTH - 1,
+      0,
+      Color::GREEN,
+    );
+    mode5::line(
+      page,
+      mode5::WIDTH - 1,
+      mode5::HEIGHT - 1,
+      0,
+      mode5::HEIGHT - 1,
+      Color::GREEN,
+    );
+  }
+
+  mode5::stretch_to_screen();
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_5).with_show_bg2(true),
+  );
+
+  let mut countdown = 60;
+  loop {
+    spin_until_vblank();
+    countdown -= 1;
+    if countdown == 0 {
+      mode5::flip();
+      countdown = 60;
+    }
+  }
+}
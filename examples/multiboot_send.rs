@@ -0,0 +1,86 @@
+#![no_std]
+#![no_main]
+
+//! Sends a tiny program to a second GBA that has no cartridge, over the link
+//! cable in multi-player mode. With mGBA, open a second multiplayer window
+//! with no ROM loaded (booting the BIOS), then press A here.
+//!
+//! The program that's sent is a few hand assembled ARM instructions that turn
+//! the client's screen green. Its header uses this ROM's own Nintendo logo,
+//! and [`multiboot::send`] fixes the checksum.
+//!
+//! The bar at the top shows the header being sent, then the rest of the
+//! screen turns green if the client booted the program, or red on an error.
+
+use gba::{prelude::*, sio::multiboot, video::mode3};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// The smallest program: the header, then 0x100 bytes.
+const PAYLOAD_LEN: usize = 0x1C0;
+
+/// Runs from `0x0200_00E0`.
+const PAYLOAD_CODE: [u32; 7] = [
+  0xE3A0_0405, // mov r0, #0x0500_0000 (palette RAM)
+  0xE3A0_1FF8, // mov r1, #0x03E0 (green)
+  0xE1C0_10B0, // strh r1, [r0]
+  0xE3A0_0301, // mov r0, #0x0400_0000 (DISPCNT)
+  0xE3A0_1000, // mov r1, #0
+  0xE1C0_10B0, // strh r1, [r0]
+  0xEAFF_FFFE, // b . (loop forever)
+];
+
+fn build_payload() -> Align4<[u8; PAYLOAD_LEN]> {
+  let mut payload = Align4([0; PAYLOAD_LEN]);
+  // Safety: the ROM header is always readable.
+  let rom_header =
+    unsafe { core::slice::from_raw_parts(0x0800_0000 as *const u8, 0xC0) };
+  payload.0[..0xC0].copy_from_slice(rom_header);
+  // The multiboot entry point, at 0xC0, is a branch to 0xE0 (the BIOS writes
+  // to the bytes at 0xC4 and 0xC5, so the code can't start right away).
+  payload.0[0xC0..0xC4].copy_from_slice(&0xEA00_0006_u32.to_le_bytes());
+  for (i, op) in PAYLOAD_CODE.iter().enumerate() {
+    let at = 0xE0 + i * 4;
+    payload.0[at..at + 4].copy_from_slice(&op.to_le_bytes());
+  }
+  payload
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  mode3::clear_to(Color::BLACK);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+  let payload = build_payload();
+
+  let mut keys = KeyTracker::new();
+  loop {
+    spin_until_vblank();
+    keys.update(KEYINPUT.read());
+    if !keys.just_pressed().a() {
+      continue;
+    }
+
+    mode3::clear_to(Color::BLACK);
+    let result = multiboot::send_with_progress(
+      &payload.0,
+      ClientMask::new().with_client1(true),
+      multiboot::DEFAULT_PALETTE_DATA,
+      |progress| {
+        if let multiboot::MultibootProgress::Header(sent) = progress {
+          mode3::rect_filled(0, 0, i32::from(sent) * 2, 8, Color::WHITE);
+        }
+      },
+    );
+    let color = match result {
+      Ok(()) => Color::GREEN,
+      Err(_) => Color::RED,
+    };
+    mode3::rect_filled(0, 16, mode3::WIDTH, mode3::HEIGHT - 16, color);
+  }
+}
@@ -0,0 +1,63 @@
+#![no_std]
+#![no_main]
+
+//! Times a loop with no$gba's `%param%` specials, and logs through whichever
+//! emulator is running the game.
+//!
+//! Each time A is pressed, a loop runs between a `%zeroclks%` and a
+//! `%lastclks%`, so no$gba's debug messages show how many cycles it took. The
+//! cycle counter is also read directly, and logged with `mgba_info!` (which
+//! goes to no$gba or mGBA, whichever is there). The backdrop is green in
+//! no$gba, blue in mGBA, and red anywhere else.
+
+use gba::{debug_log::*, nocash::*, prelude::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// Something to time.
+#[inline(never)]
+fn busy_work(n: u32) -> u32 {
+  let mut total: u32 = 0;
+  for i in 0..n {
+    total = total.wrapping_mul(31).wrapping_add(i);
+  }
+  total
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  BACKDROP_COLOR.write(match debug_backend() {
+    Some(DebugBackend::Nocash) => Color::GREEN,
+    Some(DebugBackend::Mgba) => Color::BLUE,
+    None => Color::RED,
+  });
+  DISPCNT.write(DisplayControl::new());
+  gba::mgba_info!("logging with {:?}", debug_backend());
+
+  DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+  IE.write(IrqBits::VBLANK);
+  IME.write(true);
+
+  let mut keys = KeyTracker::new();
+  keys.update(KEYINPUT.read());
+  loop {
+    VBlankIntrWait();
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().a() {
+      let before = nocash_clock_cycles();
+      nocash_print_with_params(c"frame %frame%: starting%zeroclks%");
+      let total = busy_work(1000);
+      nocash_print_with_params(c"busy_work took %lastclks% cycles");
+      let after = nocash_clock_cycles();
+      if let (Some(before), Some(after)) = (before, after) {
+        gba::mgba_info!("{} cycles in all (total {total})", after - before);
+      } else {
+        gba::mgba_info!("no cycle counter here (total {total})");
+      }
+    }
+  }
+}
@@ -0,0 +1,61 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// * A: snare (short white noise).
+/// * B: kick (low noise, fading fast).
+/// * L: cowbell (metallic).
+/// * R: buzzer (7-bit buzz, while held).
+#[no_mangle]
+extern "C" fn main() -> ! {
+  gba::sound::enable();
+  LEFT_RIGHT_VOLUME.write(dmg_stereo_defaults());
+  SOUND_MIX.write(SoundMix::new().with_psg(PsgMix::_100));
+
+  init_vblank_irq();
+  DISPCNT.write(DisplayControl::new());
+
+  let fade = Envelope::new(15, 1, false).unwrap();
+  let snare =
+    NoiseSettings { envelope: fade, length: Some(24), ..white_noise() };
+  let kick = NoiseSettings {
+    envelope: fade,
+    shift_clock: 7,
+    length: Some(16),
+    ..white_noise()
+  };
+  let cowbell = NoiseSettings { envelope: fade, ..metallic() };
+
+  let mut keys = KeyTracker::new();
+  loop {
+    wait_for_vblank();
+    keys.update(KEYINPUT.read());
+    let pressed = keys.just_pressed();
+
+    if pressed.a() {
+      play_noise(snare);
+    }
+    if pressed.b() {
+      play_noise(kick);
+    }
+    if pressed.l() {
+      play_noise(cowbell);
+    }
+    if pressed.r() {
+      play_noise(buzz());
+    }
+    if keys.just_released().r() {
+      stop_noise();
+    }
+
+    let playing = SOUND_ENABLED.read().noise_playing();
+    BACKDROP_COLOR.write(if playing { Color::WHITE } else { Color::BLACK });
+  }
+}
@@ -0,0 +1,60 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// A4 and the notes around it, from 2 octaves down to 2 octaves up, rounded
+/// to the nearest Hz.
+const EXPECTED: [(i8, u32); 9] = [
+  (-24, 110),
+  (-12, 220),
+  (-9, 262),
+  (-1, 415),
+  (0, 440),
+  (1, 466),
+  (7, 659),
+  (12, 880),
+  (24, 1760),
+];
+
+/// A sample recorded at 13379 Hz, as a BIOS wave header.
+static WAVE: WaveData =
+  WaveData { kind: 0, stat: 0, freq: 13379 * 1024, loop_start: 0, size: 0 };
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let table_ok =
+    EXPECTED.iter().all(|&(offset, hz)| note_to_sample_rate(440, offset) == hz);
+
+  // Both ways of going up an octave from the base key (60) should agree, to
+  // within 0.1%.
+  let bios = midi_key_to_freq(&WAVE, 72, 0);
+  let pure = note_to_sample_rate(13379, 12);
+  let bios_ok = bios.abs_diff(pure) <= pure / 1000;
+
+  // Play the note on tone 2 so that it can be heard too.
+  gba::sound::enable();
+  LEFT_RIGHT_VOLUME.write(dmg_stereo_defaults());
+  TONE2_PATTERN.write(TonePattern::new().with_duty(2).with_volume(12));
+  if let Some(freq) =
+    ToneFrequency::new().with_enabled(true).with_hz(note_to_sample_rate(440, 0))
+  {
+    TONE2_FREQUENCY.write(freq);
+  }
+
+  DISPCNT.write(DisplayControl::new());
+  BACKDROP_COLOR.write(if table_ok && bios_ok {
+    Color::GREEN
+  } else {
+    Color::RED
+  });
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,42 @@
+#![no_std]
+#![no_main]
+
+use gba::{
+  math::{cos, sin},
+  prelude::*,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(OBJ_TILES.as_region(), 0);
+  obj_palbank(0).index(1).write(Color::CYAN);
+
+  let mut oam = OamShadow::new();
+  oam.commit();
+  DISPCNT.write(DisplayControl::new().with_show_obj(true));
+
+  // 64 objects going around in a circle.
+  let mut t = 0_u16;
+  loop {
+    t = t.wrapping_add(1);
+    for i in 0..64 {
+      let angle = (i as u16).wrapping_mul(1024).wrapping_add(t << 8);
+      let x = 116 + ((cos(angle).to_bits() as i32 * 64) >> 14);
+      let y = 76 + ((sin(angle).to_bits() as i32 * 64) >> 14);
+      let obj = oam.obj_mut(i);
+      *obj = ObjAttr::new();
+      obj.set_x(x as u16);
+      obj.set_y(y as u16);
+      obj.set_tile_id(Cga8x8Thick::CLUB as u16);
+    }
+
+    spin_until_vblank();
+    oam.commit_count(64);
+  }
+}
@@ -0,0 +1,182 @@
+#![no_std]
+#![no_main]
+
+//! A 256 color (8bpp) object over a 256 color background, next to a 4bpp
+//! object.
+//!
+//! The background uses all 255 colors of the background palette, for diagonal
+//! bands of gradient. The object palette is shared between the two objects:
+//! the 4bpp face gets one bank from a `PalBankAllocator`, and the 8bpp ball
+//! gets the two banks after it, with its tiles moved to use those indexes.
+//! Object VRAM is shared the same way with a `TileAllocator`, which puts the
+//! ball's tiles on an even slot. Move the face with the d-pad, the ball
+//! bounces around on its own.
+
+use gba::{
+  prelude::*,
+  video::palram::{
+    write_bank, write_banks_8bpp, write_full_palette, PalBankAllocator, Palette,
+  },
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// Background tile `t` is rows of indexes `1 + t * 8 ..`, so the 32 tiles go
+/// through the whole palette.
+const fn band_tile(t: usize) -> Tile8 {
+  let mut bytes = [0; 64];
+  let mut i = 0;
+  while i < 64 {
+    let index = 1 + t * 8 + i / 8;
+    bytes[i] = if index > 255 { 255 } else { index as u8 };
+    i += 1;
+  }
+  tile8_from_bytes(bytes)
+}
+
+static BAND_TILES: [Tile8; 32] = {
+  let mut tiles = [[0; 16]; 32];
+  let mut t = 0;
+  while t < 32 {
+    tiles[t] = band_tile(t);
+    t += 1;
+  }
+  tiles
+};
+
+/// A 16x16 shaded ball, using indexes `1..32` (brightest in the middle), as
+/// four 8bpp tiles in 1D order.
+static BALL_TILES: [Tile8; 4] = {
+  let mut tiles = [[0; 16]; 4];
+  let mut t = 0;
+  while t < 4 {
+    let mut bytes = [0; 64];
+    let mut i = 0;
+    while i < 64 {
+      let x = (t % 2 * 8 + i % 8) as i32 * 2 - 15;
+      let y = (t / 2 * 8 + i / 8) as i32 * 2 - 15;
+      let d2 = x * x + y * y;
+      if d2 < 16 * 16 {
+        bytes[i] = (31 - d2 * 30 / (16 * 16)) as u8;
+      }
+      i += 1;
+    }
+    tiles[t] = tile8_from_bytes(bytes);
+    t += 1;
+  }
+  tiles
+};
+
+/// A smiley face, in index 1.
+const FACE_TILE: Tile4 = {
+  const ROWS: [u8; 8] = [
+    0b0011_1100,
+    0b0111_1110,
+    0b1101_1011,
+    0b1111_1111,
+    0b1011_1101,
+    0b1100_0011,
+    0b0111_1110,
+    0b0011_1100,
+  ];
+  let mut tile = [0; 8];
+  let mut y = 0;
+  while y < 8 {
+    let mut x = 0;
+    while x < 8 {
+      // the leftmost pixel is the lowest nibble.
+      if ROWS[y] & (0x80 >> x) != 0 {
+        tile[y] |= 1 << (x * 4);
+      }
+      x += 1;
+    }
+    y += 1;
+  }
+  tile
+};
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  // the background: the whole palette, one gradient.
+  let mut bg_colors = [Color::BLACK; 256];
+  for (i, color) in bg_colors.iter_mut().enumerate() {
+    let t = i as u16 / 2;
+    *color = Color::from_rgb(t / 4, 31 - t / 4, 16);
+  }
+  write_full_palette(Palette::Bg, &bg_colors);
+  copy_tiles_8bpp(0, 0, &BAND_TILES);
+  let tsb = TEXT_SCREENBLOCKS.get_frame(31).unwrap();
+  for y in 0..32 {
+    let row = tsb.get_row(y).unwrap();
+    for (x, addr) in row.iter().enumerate() {
+      addr.write(TextEntry::from_tile(((x / 2 + y) % 32) as u16));
+    }
+  }
+
+  // the objects: palettes first.
+  let mut banks = PalBankAllocator::new(Palette::Obj);
+  let face_bank = banks.alloc_bank().unwrap();
+  let mut face_colors = [Color::BLACK; 16];
+  face_colors[1] = Color::YELLOW;
+  write_bank(face_bank, &face_colors);
+  let ball_banks = banks.alloc_banks(2).unwrap();
+  // dark at the edge, up to bright orange in the middle.
+  let shades: [Color; 32] = core::array::from_fn(|i| {
+    let i = i as u16;
+    Color::from_rgb(i, i * 3 / 4, i / 2)
+  });
+  write_banks_8bpp(ball_banks, &shades);
+  let offset = (ball_banks.index() * 16) as u8;
+
+  // then tiles.
+  let mut slots = TileAllocator::new_obj();
+  let face_slot = slots.alloc_obj(ObjSize::_8x8, false).unwrap();
+  copy_obj_tiles_4bpp(usize::from(face_slot.0), &[FACE_TILE]);
+  let ball_slot = slots.alloc_obj(ObjSize::_16x16, true).unwrap();
+  let ball_tiles = BALL_TILES.map(|tile| tile8_offset_indexes(tile, offset));
+  copy_obj_tiles_8bpp(usize::from(ball_slot.0), &ball_tiles);
+
+  let mut face = ObjAttr::new().with_size(ObjSize::_8x8);
+  face.set_tile_id(face_slot.0);
+  face.set_palbank(face_bank.index());
+  let mut ball = ObjAttr::new().with_size(ObjSize::_16x16).with_bpp8(true);
+  ball.set_tile_id(ball_slot.0);
+
+  init_oam();
+  setup_text_background_8bpp(
+    BgLayer::Bg0,
+    0,
+    31,
+    TextBackgroundSize::_32x32,
+    1,
+  );
+  let dispcnt = DISPCNT.read();
+  DISPCNT.write(dispcnt.with_obj_vram_1d(true).with_show_obj(true));
+
+  let (mut face_x, mut face_y) = (60_i32, 76_i32);
+  let (mut ball_x, mut ball_y, mut ball_dx, mut ball_dy) = (120, 40, 1, 1);
+  loop {
+    let (dx, dy) = KEYINPUT.read().dpad();
+    face_x = (face_x + dx.to_i32()).clamp(0, 240 - 8);
+    face_y = (face_y + dy.to_i32()).clamp(0, 160 - 8);
+    face.set_x(face_x as u16);
+    face.set_y(face_y as u16);
+    if !(0..240 - 16).contains(&(ball_x + ball_dx)) {
+      ball_dx = -ball_dx;
+    }
+    if !(0..160 - 16).contains(&(ball_y + ball_dy)) {
+      ball_dy = -ball_dy;
+    }
+    (ball_x, ball_y) = (ball_x + ball_dx, ball_y + ball_dy);
+    ball.set_x(ball_x as u16);
+    ball.set_y(ball_y as u16);
+
+    spin_until_vblank();
+    write_obj_attr(0, face);
+    write_obj_attr(1, ball);
+  }
+}
@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+use gba::{math::sin, prelude::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(OBJ_TILES.as_region(), 0);
+  obj_palbank(0).index(1).write(Color::GREEN);
+
+  let slot = AffineSlot::new(0);
+  let mut oam = OamShadow::new();
+  let obj = oam.obj_mut(0);
+  *obj = ObjAttr::new().with_affine(slot, true);
+  // A double size 8x8 object is drawn in a 16x16 area.
+  obj.set_x(120 - 8);
+  obj.set_y(80 - 8);
+  obj.set_tile_id(Cga8x8Thick::FACE as u16);
+  DISPCNT.write(DisplayControl::new().with_show_obj(true));
+
+  let mut angle = 0_u16;
+  loop {
+    angle = angle.wrapping_add(0x100);
+    // Zoom between 1.0 and 2.0 while spinning.
+    let zoom = (1 << 8) + ((sin(angle).to_bits() as i32 + (1 << 14)) >> 7);
+    let scale = i16fx8::from_bits(zoom as i16);
+    oam
+      .set_affine(slot, AffineMatrix::from_scale_rotation(scale, scale, angle));
+
+    spin_until_vblank();
+    oam.commit_count(4);
+  }
+}
@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+use gba::{
+  math::{Rect, Vec2},
+  prelude::*,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+const SIZE: Vec2<i32> = Vec2::new(8, 8);
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(OBJ_TILES.as_region(), 0);
+  obj_palbank(0).index(1).write(Color::YELLOW);
+
+  init_oam();
+  let mut obj = ObjAttr::new().with_size(ObjSize::_8x8);
+  obj.set_tile_id(Cga8x8Thick::FACE as u16);
+
+  DISPCNT.write(DisplayControl::new().with_show_obj(true));
+
+  let mut face = Rect::from_position_size(Vec2::new(112, 72), SIZE);
+  loop {
+    let (dx, dy) = KEYINPUT.read().dpad();
+    let step = Vec2::new(dx.to_i32(), dy.to_i32());
+    face = face.translate(step).clamp_within(Rect::SCREEN);
+    obj.set_x(face.x as u16);
+    obj.set_y(face.y as u16);
+
+    spin_until_vblank();
+    write_obj_attr(0, obj);
+  }
+}
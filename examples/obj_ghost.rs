@@ -0,0 +1,104 @@
+#![no_std]
+#![no_main]
+
+//! A semi-transparent "ghost" object drifting over a background.
+//!
+//! `BLDCNT` is never set up for blending the object: a semi-transparent
+//! object always blends, and `set_translucent` only fills in the target2
+//! layers (and `BLDALPHA`). The ghost is a double size affine object, so it
+//! can swell and shrink without getting cut off, and it still blends.
+//!
+//! * L and R make the ghost fainter and stronger.
+//! * A toggles the mosaic effect on the ghost.
+//! * B toggles between semi-transparent and a normal, solid object.
+
+use gba::{
+  math::{cos, sin},
+  prelude::*,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  // the background: the whole font, in yellow.
+  Cga8x8Thick.bitunpack_4bpp(CHARBLOCK0_4BPP.as_region(), 0);
+  bg_palbank(0).index(1).write(Color::YELLOW);
+  BACKDROP_COLOR.write(Color::from_rgb8(0x20, 0x20, 0x60));
+  let tsb = TEXT_SCREENBLOCKS.get_frame(31).unwrap();
+  for y in 0..20 {
+    let row = tsb.get_row(y).unwrap();
+    for (x, addr) in row.iter().enumerate().take(30) {
+      let tile = ((y * 30 + x) % 256) as u16;
+      addr.write(TextEntry::from_tile(tile));
+    }
+  }
+  BG0CNT.write(BackgroundControl::new().with_screenblock(31));
+
+  // the ghost: a light blue face.
+  Cga8x8Thick.bitunpack_4bpp(OBJ_TILES.as_region(), 0);
+  obj_palbank(0).index(1).write(Color::from_rgb8(0xC0, 0xE0, 0xFF));
+  MOSAIC.write(Mosaic::new().with_obj_h_extra(1).with_obj_v_extra(1));
+
+  let slot = AffineSlot::new(0);
+  let mut oam = OamShadow::new();
+  let ghost = oam.obj_mut(0);
+  *ghost = ObjAttr::new().with_affine(slot, true);
+  ghost.set_tile_id(Cga8x8Thick::FACE as u16);
+  let mut strength = 8_u16;
+  oam
+    .set_translucent(0, BlendAlpha::from_coefficients(strength, 16 - strength));
+
+  DISPCNT.write(DisplayControl::new().with_show_bg0(true).with_show_obj(true));
+
+  let mut keys = KeyTracker::new();
+  let mut solid = false;
+  let mut mosaic = false;
+  let mut angle = 0_u16;
+  loop {
+    keys.update(KEYINPUT.read());
+    let pressed = keys.just_pressed();
+    if pressed.l() || pressed.r() {
+      strength = if pressed.r() {
+        (strength + 1).min(16)
+      } else {
+        strength.saturating_sub(1)
+      };
+      // changing the coefficients doesn't need the object to change.
+      BLDALPHA.write(BlendAlpha::from_coefficients(strength, 16 - strength));
+    }
+    if pressed.a() {
+      mosaic = !mosaic;
+      oam.set_mosaic(0, mosaic);
+    }
+    if pressed.b() {
+      solid = !solid;
+      if solid {
+        oam.clear_effect(0);
+      } else {
+        oam.set_translucent(
+          0,
+          BlendAlpha::from_coefficients(strength, 16 - strength),
+        );
+      }
+    }
+
+    // drift around the middle of the screen, swelling and shrinking.
+    angle = angle.wrapping_add(0x80);
+    let x = 120 - 8 + (cos(angle).to_bits() as i32 >> 9);
+    let y = 80 - 8 + (sin(angle.wrapping_mul(2)).to_bits() as i32 >> 10);
+    let ghost = oam.obj_mut(0);
+    ghost.set_x(x as u16);
+    ghost.set_y(y as u16);
+    let swell = (1 << 8) - ((sin(angle.wrapping_mul(3)).to_bits() as i32) >> 8);
+    let scale = i16fx8::from_bits(swell as i16);
+    oam.set_affine(slot, AffineMatrix::from_scale_rotation(scale, scale, 0));
+
+    spin_until_vblank();
+    oam.commit_count(4);
+  }
+}
@@ -0,0 +1,99 @@
+#![no_std]
+#![no_main]
+
+//! A 32x32 object that looks the same with 1D and 2D object tile mapping.
+//!
+//! Press A to switch mappings. Each time, the object's tiles are cleared out
+//! of object VRAM and loaded again: a `TileAllocator` finds room for them
+//! with the new mapping (a run of 16 slots for 1D, or a 4x4 part of the 32
+//! slot wide grid for 2D), `copy_obj_4bpp` puts each row of tiles where that
+//! mapping looks for it, and a `VideoConfig` sets the mapping in `DISPCNT`.
+//! If any of those didn't match, the target would show up scrambled. The
+//! backdrop is blue for 1D and green for 2D.
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// A target, in rings of indexes 1 and 2, with the top left quarter marked
+/// in index 3 so that it's easy to see if any tiles are out of place.
+fn target_tiles() -> [Tile4; 16] {
+  core::array::from_fn(|t| {
+    let mut tile = [0; 8];
+    for (y, row) in tile.iter_mut().enumerate() {
+      for x in 0..8 {
+        let px = (t % 4 * 8 + x) as i32 * 2 - 31;
+        let py = (t / 4 * 8 + y) as i32 * 2 - 31;
+        let ring = (px * px + py * py) / (8 * 8 * 4);
+        let index = if ring >= 4 {
+          0
+        } else if px < 0 && py < 0 && ring == 3 {
+          3
+        } else {
+          1 + ring as u32 % 2
+        };
+        // the leftmost pixel is the lowest nibble.
+        *row |= index << (x * 4);
+      }
+    }
+    tile
+  })
+}
+
+/// Loads the target for `mapping`, and gives its attributes.
+fn load_target(tiles: &[Tile4; 16], mapping: ObjTileMapping) -> ObjAttr {
+  OBJ_TILES.iter().for_each(|t| t.write([0; 8]));
+  let mut slots = TileAllocator::new_obj().with_obj_mapping(mapping);
+  // something else already loaded, so the target isn't at slot 0.
+  slots.alloc_obj(ObjSize::_16x16, false).unwrap();
+  let slot = slots.alloc_obj(ObjSize::_32x32, false).unwrap();
+  copy_obj_4bpp(usize::from(slot.0), ObjSize::_32x32, mapping, tiles);
+  let mut obj = ObjAttr::new().with_size(ObjSize::_32x32);
+  obj.set_tile_id(slot.0);
+  obj.set_x(104);
+  obj.set_y(64);
+  obj
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  OBJ_PALETTE.index(1).write(Color::WHITE);
+  OBJ_PALETTE.index(2).write(Color::RED);
+  OBJ_PALETTE.index(3).write(Color::YELLOW);
+  let tiles = target_tiles();
+  init_oam();
+
+  let mut config = VideoConfig {
+    obj_mapping: ObjTileMapping::OneD,
+    show_obj: true,
+    ..VideoConfig::new()
+  };
+  let mut keys = KeyTracker::new();
+  let mut load = true;
+  loop {
+    if load {
+      load = false;
+      let obj = load_target(&tiles, config.obj_mapping);
+      spin_until_vblank();
+      BACKDROP_COLOR.write(match config.obj_mapping {
+        ObjTileMapping::OneD => Color::BLUE,
+        ObjTileMapping::TwoD => Color::GREEN,
+      });
+      write_obj_attr(0, obj);
+      config.apply();
+    }
+    spin_until_vblank();
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().a() {
+      config.obj_mapping = match config.obj_mapping {
+        ObjTileMapping::OneD => ObjTileMapping::TwoD,
+        ObjTileMapping::TwoD => ObjTileMapping::OneD,
+      };
+      load = true;
+    }
+  }
+}
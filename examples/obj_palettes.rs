@@ -0,0 +1,63 @@
+#![no_std]
+#![no_main]
+
+use gba::{prelude::*, video::palram::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+const fn two_tone(a: Color, b: Color) -> [Color; 16] {
+  let mut colors = [Color::BLACK; 16];
+  colors[1] = a;
+  colors[2] = b;
+  colors
+}
+
+const FIRE: [Color; 16] = two_tone(Color::RED, Color::YELLOW);
+const ICE: [Color; 16] = two_tone(Color::BLUE, Color::CYAN);
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  set_backdrop(Color::from_rgb(4, 4, 4));
+
+  init_oam();
+
+  // Loading the same palette twice gives back the same bank.
+  let mut banks = PalBankAllocator::new(Palette::Obj);
+  let fire = banks.find_or_alloc(&FIRE).unwrap();
+  let ice = banks.find_or_alloc(&ICE).unwrap();
+  let fire_again = banks.find_or_alloc(&FIRE).unwrap();
+  assert_eq!(fire, fire_again);
+
+  // Tile 1 is a box: color 1 on the border, color 2 inside.
+  let mut tile = [0x2222_2222_u32; 8];
+  tile[0] = 0x1111_1111;
+  tile[7] = 0x1111_1111;
+  for row in &mut tile[1..7] {
+    *row = 0x1222_2221;
+  }
+  OBJ_TILES.index(1).write(tile);
+
+  for (i, bank) in [fire, ice].into_iter().enumerate() {
+    let mut obj = ObjAttr::new();
+    obj.set_x(100 + 32 * i as u16);
+    obj.set_y(76);
+    obj.set_tile_id(1);
+    obj.set_palbank(bank.index());
+    OBJ_ATTR_ALL.index(i).write(obj);
+  }
+  for i in 2..128 {
+    let mut obj = ObjAttr::new();
+    obj.set_style(ObjDisplayStyle::NotDisplayed);
+    OBJ_ATTR_ALL.index(i).write(obj);
+  }
+
+  DISPCNT
+    .write(DisplayControl::new().with_show_obj(true).with_obj_vram_1d(true));
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,89 @@
+#![no_std]
+#![no_main]
+
+//! Uses an object as a window mask: a "flashlight" that shows a hidden layer.
+//!
+//! The object is a 32x32 circle set to the OBJ window mode, so it isn't drawn
+//! itself. Outside of it only BG0 (dim blue text) shows, and inside of it
+//! only BG1 (the same text in white, shifted over a little) shows. Move the
+//! flashlight with the d-pad.
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// One 4bpp tile of a 32x32 circle, with the pixels inside it as color 1.
+fn circle_tile(tile_x: usize, tile_y: usize) -> Tile4 {
+  let mut tile = [0_u32; 8];
+  for (row, word) in tile.iter_mut().enumerate() {
+    for col in 0..8 {
+      let dx = (tile_x * 8 + col) as i32 * 2 - 31;
+      let dy = (tile_y * 8 + row) as i32 * 2 - 31;
+      if dx * dx + dy * dy <= 32 * 32 {
+        *word |= 1 << (col * 4);
+      }
+    }
+  }
+  tile
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(CHARBLOCK0_4BPP.as_region(), 0);
+  bg_palbank(0).index(1).write(Color::from_rgb8(0x20, 0x30, 0x70));
+  bg_palbank(1).index(1).write(Color::WHITE);
+  for (screenblock, palbank) in [(30, 0), (31, 1)] {
+    let tsb = TEXT_SCREENBLOCKS.get_frame(screenblock).unwrap();
+    for y in 0..20 {
+      let row = tsb.get_row(y).unwrap();
+      for (x, addr) in row.iter().enumerate().take(30) {
+        let tile = ((y * 30 + x) % 256) as u16;
+        addr.write(TextEntry::from_tile(tile).with_palbank(palbank));
+      }
+    }
+  }
+  BG0CNT.write(BackgroundControl::new().with_screenblock(30));
+  BG1CNT.write(BackgroundControl::new().with_screenblock(31));
+  BG1HOFS.write(4);
+
+  // the mask. Only which pixels are opaque matters, not their color.
+  for tile_y in 0..4 {
+    for tile_x in 0..4 {
+      let index = tile_y * 4 + tile_x;
+      OBJ_TILES.index(index).write(circle_tile(tile_x, tile_y));
+    }
+  }
+  let mut oam = OamShadow::new();
+  *oam.obj_mut(0) = ObjAttr::new().with_size(ObjSize::_32x32);
+  oam.set_obj_window(0);
+
+  WINOUT
+    .write(WindowOutside::new().with_outside_bg0(true).with_obj_win_bg1(true));
+  DISPCNT.write(
+    DisplayControl::new()
+      .with_obj_vram_1d(true)
+      .with_show_bg0(true)
+      .with_show_bg1(true)
+      .with_show_obj(true)
+      .with_enable_obj_win(true),
+  );
+
+  let mut keys = KeyTracker::new();
+  let (mut x, mut y) = (120 - 16_i32, 80 - 16_i32);
+  loop {
+    keys.update(KEYINPUT.read());
+    let (dx, dy) = keys.held().dpad();
+    x = (x + 2 * dx.to_i32()).clamp(-16, 240 - 16);
+    y = (y + 2 * dy.to_i32()).clamp(-16, 160 - 16);
+    let light = oam.obj_mut(0);
+    light.set_x(x as u16);
+    light.set_y(y as u16);
+
+    spin_until_vblank();
+    oam.commit_count(1);
+  }
+}
@@ -0,0 +1,123 @@
+#![no_std]
+#![no_main]
+
+//! Fades a tiled scene out to black and back in, and then cross-fades it to
+//! a grayscale copy of its palette and back, using `palram::fade`.
+//!
+//! Both palettes (all 512 entries) are faded, so the backdrop fades too. The
+//! first step of the first fade is timed, and the cycle count is logged to
+//! mGBA. Press B during a fade out to cancel it, which puts the colors back
+//! at once.
+
+use core::ptr::addr_of_mut;
+use gba::{
+  prelude::*,
+  video::palram::fade::{fade_between, FadeToColor, PaletteSnapshot},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+gba::ewram_data! {
+  static mut SCENE: PaletteSnapshot = PaletteSnapshot::new();
+}
+
+gba::ewram_data! {
+  static mut GRAYS: PaletteSnapshot = PaletteSnapshot::new();
+}
+
+/// The text color of each bank used by the scene.
+const BANK_COLORS: [Color; 4] = [
+  Color::WHITE,
+  Color::from_rgb8(0xFF, 0x60, 0x40),
+  Color::from_rgb8(0x40, 0xE0, 0x60),
+  Color::from_rgb8(0xFF, 0xD0, 0x20),
+];
+
+fn wait_frames(frames: u32) {
+  for _ in 0..frames {
+    VBlankIntrWait();
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(CHARBLOCK0_4BPP.as_region(), 0);
+  for (bank, color) in BANK_COLORS.iter().enumerate() {
+    bg_palbank(bank).index(1).write(*color);
+  }
+  BACKDROP_COLOR.write(Color::from_rgb8(0x20, 0x30, 0x80));
+
+  // the font, one text color per group of four rows.
+  let tsb = TEXT_SCREENBLOCKS.get_frame(31).unwrap();
+  for y in 0..16 {
+    let row = tsb.get_row(y).unwrap();
+    for (x, addr) in row.iter().enumerate().take(16) {
+      let tile = (y * 16 + x) as u16;
+      let palbank = (y / 4) as u16;
+      addr.write(TextEntry::from_tile(tile).with_palbank(palbank));
+    }
+  }
+  BG0CNT.write(BackgroundControl::new().with_screenblock(31));
+  DISPCNT.write(DisplayControl::new().with_show_bg0(true));
+
+  DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+  IE.write(IrqBits::VBLANK);
+  IME.write(true);
+
+  let timers = WideTimer::new(Timer::Timer0);
+  timers.start(TimerScale::_1);
+  let mut keys = KeyTracker::new();
+  let mut timed = false;
+
+  let scene = unsafe { &mut *addr_of_mut!(SCENE) };
+  let grays = unsafe { &mut *addr_of_mut!(GRAYS) };
+  grays.capture(0..512);
+  for color in grays.colors_mut().iter_mut() {
+    *color = color.grayscale();
+  }
+
+  loop {
+    // out to black, and then back in.
+    let mut fade = FadeToColor::new(scene, 0..512, Color::BLACK, 32);
+    let finished = loop {
+      VBlankIntrWait();
+      keys.update(KEYINPUT.read());
+      if keys.just_pressed().b() {
+        break false;
+      }
+      let start = timers.read();
+      let done = fade.step();
+      let cycles = timers.read() - start;
+      if !timed {
+        gba::mgba_info!("one 512 entry fade step: {cycles} cycles");
+        timed = true;
+      }
+      if done {
+        break true;
+      }
+    };
+    if finished {
+      wait_frames(30);
+      while !fade.step_back() {
+        VBlankIntrWait();
+      }
+    } else {
+      fade.cancel();
+    }
+    wait_frames(60);
+
+    // over to grayscale, and then back to color.
+    for progress in (0..=16).chain((0..16).rev()) {
+      wait_frames(2);
+      fade_between(scene, grays, 0..512, progress);
+      if progress == 16 {
+        wait_frames(60);
+      }
+    }
+    wait_frames(60);
+  }
+}
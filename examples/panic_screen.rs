@@ -0,0 +1,44 @@
+#![no_std]
+#![no_main]
+
+//! Panics on purpose, to check the `panic_handler` feature's panic screen. Run
+//! this with `--features panic_handler`.
+//!
+//! Press A to panic: the screen should go dark red with the panic's location
+//! and message (which has the frame count in it, and is long enough to wrap),
+//! and mGBA's log should have the same message. Press B to panic with a
+//! message that panics again while it's being shown, which should just give a
+//! magenta screen.
+
+use core::fmt;
+use gba::prelude::*;
+
+/// A message that panics when it's formatted.
+struct PanicsWhenShown;
+impl fmt::Display for PanicsWhenShown {
+  fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+    panic!("panicked while showing a panic")
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  BACKDROP_COLOR.write(Color::from_rgb(0, 0, 12));
+
+  let mut frames: u32 = 0;
+  loop {
+    spin_until_vblank();
+    frames = frames.wrapping_add(1);
+    let keys = KEYINPUT.read();
+    if keys.a() {
+      panic!(
+        "this is an intentional panic after {frames} frames, with a message \
+         long enough to wrap onto the next lines of the screen"
+      );
+    }
+    if keys.b() {
+      panic!("{}", PanicsWhenShown);
+    }
+  }
+}
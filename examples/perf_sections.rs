@@ -0,0 +1,92 @@
+#![no_std]
+#![no_main]
+
+//! Times lopsided sections of a frame with `perf::Counters`, and logs a
+//! report to mGBA once a second.
+//!
+//! "physics" takes about 120,000 cycles, "render" about 40,000 of its own
+//! plus a nested "sprites" of about 25,000, and "audio" runs twice for about
+//! 4,000 each. The first frame checks that the nested time is only counted
+//! once: the backdrop goes green if the counts are right (a failure panics,
+//! which makes it red).
+
+use gba::{perf::Counters, prelude::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  gba::mgba_error!("{info}");
+  loop {}
+}
+
+/// How far over a count can be, for the guard and `busy_wait_cycles`
+/// overhead.
+const SLACK: u32 = 1_000;
+
+fn frame(counters: &Counters<4>) {
+  {
+    let _physics = counters.section("physics");
+    busy_wait_cycles(120_000);
+  }
+  {
+    let _render = counters.section("render");
+    busy_wait_cycles(40_000);
+    let _sprites = counters.section("sprites");
+    busy_wait_cycles(25_000);
+  }
+  for _ in 0..2 {
+    let _audio = counters.section("audio");
+    busy_wait_cycles(4_000);
+  }
+}
+
+fn assert_cycles(counters: &Counters<4>, name: &str, expected: u32) {
+  let cycles = counters.cycles(name);
+  assert!(
+    (expected..expected + SLACK).contains(&cycles),
+    "{name}: {cycles} cycles, expected about {expected}"
+  );
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+
+  // `busy_wait_cycles` uses timer 3, so the counters use timers 0 and 1.
+  let timers = WideTimer::new(Timer::Timer0);
+  timers.start(TimerScale::_1);
+  let counters = Counters::<4>::new(timers);
+
+  // check one frame with interrupts still off, so nothing else gets counted.
+  let start = timers.read();
+  frame(&counters);
+  let whole = timers.read() - start;
+  assert_cycles(&counters, "physics", 120_000);
+  assert_cycles(&counters, "render", 40_000);
+  assert_cycles(&counters, "sprites", 25_000);
+  assert_cycles(&counters, "audio", 8_000);
+  assert_eq!(counters.calls("audio"), 2);
+  assert!(counters.total_cycles() <= whole, "nested cycles counted twice");
+  gba::mgba_info!("perf section checks passed");
+  BACKDROP_COLOR.write(Color::GREEN);
+
+  DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+  IE.write(IrqBits::VBLANK);
+  IME.write(true);
+
+  let mut frames = 0_u32;
+  loop {
+    VBlankIntrWait();
+    counters.frame_reset();
+    frame(&counters);
+    frames += 1;
+    if frames.is_multiple_of(60) {
+      if let Ok(mut logger) =
+        MgbaBufferedLogger::try_new(MgbaMessageLevel::Info)
+      {
+        counters.report(&mut logger).ok();
+      }
+    }
+  }
+}
@@ -0,0 +1,36 @@
+#![no_std]
+#![no_main]
+
+use gba::{prelude::*, profile::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  // Nothing at all should cost (close to) nothing, since the stopwatch
+  // overhead is subtracted.
+  let ((), empty) = profiled(|| ());
+  assert!(empty < 64);
+
+  let sum = log_profiled("sum of 1000 values", || {
+    let mut sum = 0_u32;
+    for i in 0..1000 {
+      sum = sum.wrapping_add(core::hint::black_box(i));
+    }
+    sum
+  });
+  assert_eq!(sum, 499_500);
+
+  // A stopwatch can't share timers with a running one.
+  let watch = Stopwatch::start(WideTimer::new(Timer::Timer0)).unwrap();
+  assert!(Stopwatch::start(WideTimer::new(Timer::Timer1)).is_err());
+  let _ = watch.stop();
+
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,92 @@
+#![no_std]
+#![no_main]
+
+//! Sanity checks for the random number generators.
+//!
+//! Bounded outputs have to stay in range, small buckets have to fill up
+//! about evenly, a saved state has to give the same outputs again, and a
+//! shuffle has to keep every element. The backdrop goes green if everything
+//! passes (a failure panics, which makes it red).
+
+use gba::{
+  prelude::*,
+  random::{Gen32, KeypressSeeder, Lcg32, Xoshiro128},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  gba::mgba_error!("{info}");
+  loop {}
+}
+
+/// How many samples go in each bucket test.
+const SAMPLES: u32 = 12_000;
+
+/// Fills `N` buckets from `roll`, and checks each is within 15% of even.
+fn check_buckets<const N: usize>(name: &str, mut roll: impl FnMut() -> usize) {
+  let mut buckets = [0_u32; N];
+  for _ in 0..SAMPLES {
+    let i = roll();
+    assert!(i < N, "{name} gave {i}, out of 0..{N}");
+    buckets[i] += 1;
+  }
+  let even = SAMPLES / N as u32;
+  for (i, &count) in buckets.iter().enumerate() {
+    assert!(
+      count.abs_diff(even) < even * 15 / 100,
+      "{name} bucket {i}: {count}"
+    );
+  }
+}
+
+fn check_generator(rng: &mut impl Gen32, name: &str) {
+  check_buckets::<6>(name, || usize::from(rng.next_bounded(6)));
+  check_buckets::<10>(name, || rng.next_bounded_u32(10) as usize);
+  check_buckets::<16>(name, || usize::from(rng.next_u8() >> 4));
+  // a bound just over half the range is where modulo bias would show most.
+  let big = (u32::MAX / 2) + 2;
+  check_buckets::<2>(name, || (rng.next_bounded_u32(big) >= big / 2) as usize);
+
+  let hits = (0..SAMPLES).filter(|_| rng.chance(1, 4)).count() as u32;
+  assert!(hits.abs_diff(SAMPLES / 4) < SAMPLES / 40, "{name} chance: {hits}");
+  assert!((0..100).all(|_| rng.chance(5, 5)));
+  assert!(!(0..100).any(|_| rng.chance(0, 5)));
+
+  let mut deck = [0_u8; 52];
+  deck.iter_mut().enumerate().for_each(|(i, card)| *card = i as u8);
+  rng.shuffle(&mut deck);
+  let mut seen = 0_u64;
+  deck.iter().for_each(|&card| seen |= 1 << card);
+  assert_eq!(seen, (1 << 52) - 1, "{name} shuffle lost a card");
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+
+  check_generator(&mut Lcg32::new(7), "Lcg32");
+  check_generator(&mut Xoshiro128::from_seed(7), "Xoshiro128");
+
+  // a saved state picks up with the same outputs.
+  let mut rng = Xoshiro128::from_seed(0xC0FFEE);
+  for _ in 0..37 {
+    rng.next_u32();
+  }
+  let mut resumed = Xoshiro128::from_state(rng.to_state());
+  assert!((0..100).all(|_| rng.next_u32() == resumed.next_u32()));
+  assert_ne!(Xoshiro128::from_state([0; 4]).to_state(), [0; 4]);
+
+  let mut seeder = KeypressSeeder::new();
+  assert!(seeder.update(KeyInput::new(), 123).is_none());
+  let press = KeyInput::new().with_a(true);
+  assert!(seeder.update(press, 456).is_some());
+  assert!(seeder.update(press, 789).is_none());
+
+  gba::mgba_info!("random checks passed");
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,70 @@
+#![no_std]
+#![no_main]
+
+//! A sky gradient, done only by changing the backdrop color partway down the
+//! screen.
+//!
+//! Nothing is drawn at all: the backdrop (index 0 of the background palette)
+//! is the only color on screen. A `RasterPalette` starts it each frame as a
+//! deep blue at the top, and changes it every 4 lines to get lighter toward
+//! the horizon, then to greens for the ground below. Each line only has the
+//! one color to change, so every change is done by hblank DMA.
+
+use gba::{prelude::*, video::palram::raster_palette::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// How many lines each band of color is.
+const BAND: usize = 4;
+
+/// The sky is the first 28 bands, and the ground is the other 12.
+const SKY_BANDS: usize = 28;
+
+static COLORS: [Color; 160 / BAND] = {
+  let top = Color::from_rgb(1, 3, 14);
+  let horizon = Color::from_rgb(26, 28, 31);
+  let ground = Color::from_rgb(4, 18, 3);
+  let far_ground = Color::from_rgb(12, 22, 10);
+  let mut colors = [Color::BLACK; 160 / BAND];
+  let mut i = 0;
+  while i < colors.len() {
+    colors[i] = if i < SKY_BANDS {
+      top.blend(horizon, (i * 16 / (SKY_BANDS - 1)) as u16)
+    } else {
+      let down = i - SKY_BANDS;
+      far_ground
+        .blend(ground, (down * 16 / (colors.len() - SKY_BANDS - 1)) as u16)
+    };
+    i += 1;
+  }
+  colors
+};
+
+static ENTRIES: [RasterEntry; 160 / BAND] = {
+  let mut entries = [RasterEntry::new(0, 0..1, &[Color::BLACK]); 160 / BAND];
+  let mut i = 0;
+  while i < entries.len() {
+    let color = core::slice::from_ref(&COLORS[i]);
+    entries[i] = RasterEntry::new((i * BAND) as u8, 0..1, color);
+    i += 1;
+  }
+  entries
+};
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  let raster = RasterPalette::start(3, &ENTRIES);
+  assert!(raster.uses_dma());
+  IME.write(true);
+
+  loop {
+    wait_for_vblank();
+  }
+}
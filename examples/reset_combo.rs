@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+//! Resets with A+B+Start+Select, using `system::SoftResetCombo`.
+//!
+//! The combo is checked by the keypad interrupt, which requests a reset that
+//! the main loop then does, and the backdrop starts cycling from black again.
+//! The cleanup function says so in mGBA's log before the reset.
+//!
+//! Hold L while the program starts to have the main loop get stuck instead
+//! (with a red backdrop), and the interrupt reset immediately.
+
+use gba::{prelude::*, system::SoftResetCombo};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+fn cleanup() {
+  gba::mgba_info!("cleaning up before the reset");
+}
+
+/// Clears everything except the cartridge and starts back from the ROM's
+/// entry point, the same as turning the GBA off and on.
+const RESET: SoftResetCombo = SoftResetCombo::new().with_cleanup(cleanup);
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  let stuck = KEYINPUT.read().l();
+  RESET.arm_interrupt(stuck);
+  IME.write(true);
+
+  if stuck {
+    BACKDROP_COLOR.write(Color::RED);
+    // never checks for a reset request.
+    loop {
+      spin_until_vblank();
+    }
+  }
+
+  let mut frame = 0_u16;
+  loop {
+    spin_until_vblank();
+    // Cycle the backdrop so it's easy to see that the program restarted.
+    frame = frame.wrapping_add(1);
+    BACKDROP_COLOR.write(Color::new().with_blue((frame >> 2) & 31));
+    gba::system::reset_if_requested();
+  }
+}
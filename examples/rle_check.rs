@@ -0,0 +1,36 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// Eight copies of the byte 7.
+static COMPRESSED: [u32; 2] = [
+  // header: run-length, 8 bytes decompressed
+  0x0000_0830,
+  // flag (compressed, 8 - 3 = 5), then the byte to repeat
+  0x0000_0785,
+];
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let mut buffer = [0_u8; 8];
+  let ok = rl_decompress(&COMPRESSED, &mut buffer) == Ok(8)
+    && buffer == [7; 8]
+    && rl_decompress(&COMPRESSED, &mut buffer[..4])
+      == Err(DecompressError::DestTooSmall)
+    // A size of zero isn't allowed.
+    && rl_decompress(&[0x0000_0030], &mut buffer)
+      == Err(DecompressError::BadHeader);
+
+  DISPCNT.write(DisplayControl::new());
+  BACKDROP_COLOR.write(if ok { Color::GREEN } else { Color::RED });
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,78 @@
+#![no_std]
+#![no_main]
+
+//! Shows the date and time from the cartridge's real-time clock.
+//!
+//! With mGBA, turn on the real-time clock in the game overrides, and it'll use
+//! the computer's clock. Press Start to set the clock back to the start of
+//! 2000, and see it count up from there.
+
+use core::fmt::Write;
+use gba::{
+  gpio::rtc::{self, DateTime},
+  prelude::*,
+  video::{palram::set_backdrop, text::*},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  bg_palbank(0).index(1).write(Color::WHITE);
+  set_backdrop(Color::from_rgb(0, 0, 12));
+  load_font_4bpp(0, 0, 1, 0);
+
+  let mut writer = TextWriter::new(31, 0, 0);
+  writer.clear();
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+
+  match rtc::init() {
+    Ok(status) if status.power_failure() => {
+      writeln!(writer, "clock lost power, reset").ok();
+    }
+    Ok(_) => {
+      writeln!(writer, "clock found").ok();
+    }
+    Err(error) => {
+      writeln!(writer, "no clock: {error:?}").ok();
+      set_backdrop(Color::from_rgb(16, 0, 0));
+    }
+  }
+
+  let mut keys = KeyTracker::new();
+  loop {
+    spin_until_vblank();
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().start() {
+      rtc::set_datetime(&DateTime::RESET).ok();
+    }
+
+    writer.set_cursor(0, 2);
+    match rtc::datetime() {
+      Ok(now) => {
+        write!(
+          writer,
+          "{:04}-{:02}-{:02} {}  {:02}:{:02}:{:02}",
+          now.year,
+          now.month,
+          now.day,
+          WEEKDAYS[usize::from(now.weekday)],
+          now.hour,
+          now.minute,
+          now.second
+        )
+        .ok();
+      }
+      Err(error) => {
+        write!(writer, "{error:?}                     ").ok();
+      }
+    }
+  }
+}
@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+//! Rumbles the cart's motor (or the Game Boy Player's controller).
+//!
+//! A gives a hard pulse, B a light one, and holding L rumbles until it's let
+//! go. The backdrop is red while the rumble is on. With mGBA, set the game
+//! overrides to have a rumble motor, and use a gamepad that can rumble.
+
+use gba::{
+  gpio::rumble::{self, RumbleStrength},
+  prelude::*,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  set_handler(Interrupt::Serial, rumble::gb_player_irq);
+  init_vblank_irq();
+  DISPCNT.write(DisplayControl::new());
+  // There's no logo to show, so this only links up with a Game Boy Player
+  // that's already been told about the game some other way.
+  rumble::start_gb_player_link();
+
+  let mut keys = KeyTracker::new();
+  keys.update(KEYINPUT.read());
+  loop {
+    wait_for_vblank();
+    rumble::tick();
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().a() {
+      rumble::pulse(30);
+    }
+    if keys.just_pressed().b() {
+      rumble::pulse_with(30, RumbleStrength::Light);
+    }
+    if keys.just_pressed().l() {
+      rumble::set_rumble(true);
+    }
+    if keys.just_released().l() {
+      rumble::set_rumble(false);
+    }
+    BACKDROP_COLOR.write(if rumble::is_rumbling() {
+      Color::RED
+    } else {
+      Color::BLACK
+    });
+  }
+}
@@ -0,0 +1,68 @@
+#![no_std]
+#![no_main]
+
+//! Finds the cart's save memory, and counts how many times the game has booted
+//! in a save slot.
+//!
+//! Run this with each of mGBA's save types (SRAM, flash, and EEPROM) to check
+//! each of the drivers. The screen shows the kind of save memory found, how
+//! loading the slot went, and the boot count. Press A to reset and count up.
+
+use core::fmt::Write;
+use gba::{
+  prelude::*,
+  video::{palram::set_backdrop, text::*},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  bg_palbank(0).index(1).write(Color::WHITE);
+  set_backdrop(Color::from_rgb(0, 0, 12));
+  load_font_4bpp(0, 0, 1, 0);
+
+  let mut writer = TextWriter::new(31, 0, 0);
+  writer.clear();
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+
+  match SaveMedia::init() {
+    Ok(media) => {
+      writeln!(writer, "save: {} ({} bytes)", media.name(), media.len()).ok();
+      writeln!(writer, "slots: {}", media.slot_count()).ok();
+      let boots = match media.load_slot::<u32>(0) {
+        Ok(boots) => boots,
+        Err(error) => {
+          writeln!(writer, "load: {error:?}").ok();
+          0
+        }
+      };
+      let boots = boots.wrapping_add(1);
+      writeln!(writer, "boots: {boots}").ok();
+      if let Err(error) = media.save_slot(0, &boots) {
+        writeln!(writer, "save: {error:?}").ok();
+        set_backdrop(Color::from_rgb(16, 0, 0));
+      }
+    }
+    Err(error) => {
+      writeln!(writer, "no save memory: {error:?}").ok();
+      set_backdrop(Color::from_rgb(16, 0, 0));
+    }
+  }
+
+  // Keys still held from before the reset don't count as new presses.
+  let mut keys = KeyTracker::new();
+  keys.update(KEYINPUT.read());
+  loop {
+    spin_until_vblank();
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().a() {
+      soft_reset_to(ResetTarget::Rom);
+    }
+  }
+}
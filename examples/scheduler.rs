@@ -0,0 +1,120 @@
+#![no_std]
+#![no_main]
+
+//! Screen shakes and delayed sound effects from a `Scheduler`.
+//!
+//! * A: an explosion. The screen shakes right away (moving every 2 frames,
+//!   stopped by a second timer after 40 frames), and the boom comes 20 frames
+//!   later, as if from far away.
+//! * B: a three note chime, with each note on its own timer.
+//! * Start: cancels everything waiting, and stops any shake.
+//!
+//! The scheduler goes by the frame count, so the timing stays right even when
+//! a frame is missed. With too many timers at once, the new ones are dropped,
+//! and the backdrop flashes red to show that.
+
+use gba::{prelude::*, scheduler::*, time::FrameInstant};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Event {
+  Shake,
+  StopShake,
+  Boom,
+  Note(u32),
+}
+
+/// Where the background moves to on each step of a shake. The offsets wrap at
+/// 512, so 509 is 3 pixels the other way.
+const SHAKE: [(u16, u16); 4] = [(3, 1), (509, 511), (1, 510), (510, 2)];
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  gba::sound::enable();
+  LEFT_RIGHT_VOLUME.write(dmg_stereo_defaults());
+  SOUND_MIX.write(SoundMix::new().with_psg(PsgMix::_100));
+
+  Cga8x8Thick.bitunpack_4bpp(CHARBLOCK0_4BPP.as_region(), 0);
+  bg_palbank(0).index(1).write(Color::WHITE);
+  let tsb = TEXT_SCREENBLOCKS.get_frame(31).unwrap();
+  for y in 0..32 {
+    let row = tsb.get_row(y).unwrap();
+    for (x, addr) in row.iter().enumerate() {
+      addr.write(TextEntry::from_tile(((y * 32 + x) % 256) as u16));
+    }
+  }
+  init_vblank_irq();
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+
+  let fade = Envelope::new(15, 2, false).unwrap();
+  let boom = NoiseSettings {
+    envelope: fade,
+    shift_clock: 7,
+    length: Some(48),
+    ..white_noise()
+  };
+
+  let mut timers = Scheduler::<Event, 8>::new();
+  let mut shake: Option<TimerHandle> = None;
+  let mut step = 0;
+  let mut scroll = BgScroll::default();
+  let mut keys = KeyTracker::new();
+  loop {
+    wait_for_vblank();
+    scroll.write_to(BgLayer::Bg0);
+    keys.update(KEYINPUT.read());
+    let pressed = keys.just_pressed();
+
+    let mut full = false;
+    if pressed.a() {
+      if shake.is_none() {
+        match timers.every(2, Event::Shake) {
+          Ok(handle) => {
+            shake = Some(handle);
+            full |= timers.after(40, Event::StopShake).is_err();
+          }
+          Err(_) => full = true,
+        }
+      }
+      full |= timers.after(20, Event::Boom).is_err();
+    }
+    if pressed.b() {
+      for (frames, hz) in [(1, 523), (10, 659), (20, 784)] {
+        full |= timers.after(frames, Event::Note(hz)).is_err();
+      }
+    }
+    if pressed.start() {
+      timers.clear();
+      shake = None;
+      scroll.set(0, 0);
+    }
+    BACKDROP_COLOR.write(if full { Color::RED } else { Color::BLACK });
+
+    for &event in timers.update(FrameInstant::now()).iter() {
+      match event {
+        Event::Shake => {
+          let (x, y) = SHAKE[step % SHAKE.len()];
+          step += 1;
+          scroll.set(x, y);
+        }
+        Event::StopShake => {
+          if let Some(handle) = shake.take() {
+            timers.cancel(handle);
+          }
+          scroll.set(0, 0);
+        }
+        Event::Boom => play_noise(boom),
+        Event::Note(hz) => {
+          let rate = rate_from_hz(hz).unwrap();
+          play_tone1(rate, Duty::_50, fade, None, Some(32));
+        }
+      }
+    }
+  }
+}
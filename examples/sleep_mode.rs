@@ -0,0 +1,39 @@
+#![no_std]
+#![no_main]
+
+use gba::{
+  power::{stop, StopConfig},
+  prelude::*,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let sleep_combo = KeyInput::from_keys(&[Key::Select, Key::L, Key::R]);
+  init_vblank_irq();
+  DISPCNT.write(DisplayControl::new());
+
+  let mut frame = 0_u16;
+  loop {
+    wait_for_vblank();
+    frame = frame.wrapping_add(1);
+    BACKDROP_COLOR.write(Color::new().with_green((frame >> 2) & 31));
+
+    if KEYINPUT.read().contains(sleep_combo) {
+      // Wait for the keys to be let go, or they'd wake us right back up.
+      while KEYINPUT.read() != KeyInput::new() {
+        wait_for_vblank();
+      }
+      stop(StopConfig::new().with_keypad_wake(KeyControl::any_key())).unwrap();
+      // Don't let the key that woke us up count as input.
+      while KEYINPUT.read() != KeyInput::new() {
+        wait_for_vblank();
+      }
+    }
+  }
+}
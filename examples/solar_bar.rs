@@ -0,0 +1,55 @@
+#![no_std]
+#![no_main]
+
+//! Shows the Boktai solar sensor's reading as a bar.
+//!
+//! The top bar is the raw reading (longer is brighter), and the bar below it
+//! is the calibrated level out of 10. Press A with the sensor dark and B with
+//! it in full light to calibrate. The screen goes red if there's no sensor.
+//!
+//! With mGBA, set the game overrides to have a solar sensor, and move the
+//! solar slider to change the reading.
+
+use gba::{
+  gpio::solar::{self, SolarCalibration},
+  prelude::*,
+  video::mode3,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  let mut calibration = SolarCalibration::new(u8::MAX, 0);
+  let mut keys = KeyTracker::new();
+  keys.update(KEYINPUT.read());
+  loop {
+    spin_until_vblank();
+    keys.update(KEYINPUT.read());
+    let Some(raw) = solar::read_luminance() else {
+      mode3::clear_to(Color::RED);
+      continue;
+    };
+    if keys.just_pressed().a() {
+      calibration.dark = raw;
+    }
+    if keys.just_pressed().b() {
+      calibration.bright = raw;
+    }
+
+    mode3::clear_to(Color::BLACK);
+    let light = i32::from(u8::MAX - raw);
+    mode3::rect_filled(0, 40, light * mode3::WIDTH / 255, 24, Color::YELLOW);
+    let level = i32::from(calibration.level(raw));
+    let max = i32::from(SolarCalibration::MAX_LEVEL);
+    mode3::rect_filled(0, 96, level * mode3::WIDTH / max, 24, Color::WHITE);
+  }
+}
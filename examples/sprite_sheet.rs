@@ -0,0 +1,124 @@
+#![no_std]
+#![no_main]
+
+//! A 64x64 object spinning through four frames from a `SpriteSheet`, with
+//! either 1D or 2D object tile mapping.
+//!
+//! Press A to switch mappings. The sheet is freed and loaded again from a
+//! `TileAllocator` set up for the new mapping, which puts the four frames one
+//! after the other (1D) or side by side across the 32 slot wide grid (2D).
+//! The `AnimationPlayer` counts in frame numbers, which the sheet turns into
+//! tile indexes, so the animation doesn't change between the two. The
+//! backdrop is blue for 1D and green for 2D.
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// The color index of pixel `(x, y)` of frame `f`: a ring (index 1), with a
+/// hand (index 2) pointing up, right, down, then left, and a dot in the middle
+/// (index 3).
+const fn pixel(f: usize, x: usize, y: usize) -> u32 {
+  let dx = x as i32 * 2 - 63;
+  let dy = y as i32 * 2 - 63;
+  let d2 = dx * dx + dy * dy;
+  let (along, across) = match f {
+    0 => (-dy, dx),
+    1 => (dx, dy),
+    2 => (dy, dx),
+    _ => (-dx, dy),
+  };
+  if d2 < 10 * 10 {
+    3
+  } else if d2 > 50 * 50 && d2 < 62 * 62 {
+    1
+  } else if along > 0 && along < 48 && across > -6 && across < 6 {
+    2
+  } else {
+    0
+  }
+}
+
+/// All four frames, each 64 tiles in rows.
+static FRAMES: [Tile4; 64 * 4] = {
+  let mut tiles = [[0; 8]; 64 * 4];
+  let mut t = 0;
+  while t < tiles.len() {
+    let (f, tile) = (t / 64, t % 64);
+    let mut y = 0;
+    while y < 8 {
+      let mut x = 0;
+      while x < 8 {
+        // the leftmost pixel is the lowest nibble.
+        let index = pixel(f, tile % 8 * 8 + x, tile / 8 * 8 + y);
+        tiles[t][y] |= index << (x * 4);
+        x += 1;
+      }
+      y += 1;
+    }
+    t += 1;
+  }
+  tiles
+};
+
+static SPIN: Animation =
+  Animation::new(&[(0, 12), (1, 12), (2, 12), (3, 12)], true);
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  OBJ_PALETTE.index(1).write(Color::WHITE);
+  OBJ_PALETTE.index(2).write(Color::RED);
+  OBJ_PALETTE.index(3).write(Color::YELLOW);
+  init_oam();
+
+  let mut config = VideoConfig {
+    obj_mapping: ObjTileMapping::OneD,
+    show_obj: true,
+    ..VideoConfig::new()
+  };
+  let mut slots = TileAllocator::new_obj().with_obj_mapping(config.obj_mapping);
+  let mut sheet =
+    SpriteSheet::alloc_4bpp(&mut slots, ObjSize::_64x64, &FRAMES).unwrap();
+  let mut player = AnimationPlayer::new();
+  player.play(&SPIN);
+  let mut obj = ObjAttr::new().with_size(ObjSize::_64x64);
+  obj.set_x(88);
+  obj.set_y(48);
+  let mut keys = KeyTracker::new();
+  let mut reloaded = true;
+  loop {
+    if let Some(frame) = player.tick() {
+      obj.2 = sheet.attr2(frame);
+    }
+    spin_until_vblank();
+    if reloaded {
+      reloaded = false;
+      BACKDROP_COLOR.write(match config.obj_mapping {
+        ObjTileMapping::OneD => Color::BLUE,
+        ObjTileMapping::TwoD => Color::GREEN,
+      });
+      config.apply();
+    }
+    write_obj_attr(0, obj);
+
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().a() {
+      config.obj_mapping = match config.obj_mapping {
+        ObjTileMapping::OneD => ObjTileMapping::TwoD,
+        ObjTileMapping::TwoD => ObjTileMapping::OneD,
+      };
+      // hide the object while its tiles are out of place.
+      write_obj_attr(0, ObjAttr::HIDDEN);
+      sheet.free(&mut slots);
+      slots = slots.with_obj_mapping(config.obj_mapping);
+      sheet =
+        SpriteSheet::alloc_4bpp(&mut slots, ObjSize::_64x64, &FRAMES).unwrap();
+      obj.2 = sheet.attr2(player.current_tile().unwrap_or(0));
+      reloaded = true;
+    }
+  }
+}
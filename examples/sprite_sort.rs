@@ -0,0 +1,128 @@
+#![no_std]
+#![no_main]
+
+//! Characters walking north and south past each other, kept in the right
+//! draw order by a `SpriteSorter`.
+//!
+//! Each character is 16x16, and they overlap as they walk. The sort key is the
+//! bottom of each character, so whoever is lower on the screen is always in
+//! front. The middle character spins with an affine matrix, which stays with
+//! it whatever OAM index it's sorted to. Hold A to stop the sorting, and the
+//! characters keep the order they had instead.
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// One quarter of a 16x16 rounded square: outlined in index 2, filled with
+/// index 1, and with an eye (index 2) in the upper quarters.
+const fn character_tile(quarter: usize) -> Tile4 {
+  let mut tile = [0; 8];
+  let mut y = 0;
+  while y < 8 {
+    let mut x = 0;
+    while x < 8 {
+      let px = (quarter % 2) * 8 + x;
+      let py = (quarter / 2) * 8 + y;
+      let edge_x = px == 0 || px == 15;
+      let edge_y = py == 0 || py == 15;
+      let corner = (px < 2 || px > 13) && (py < 2 || py > 13);
+      let eye = py == 5 && (px == 5 || px == 10);
+      let index = if corner {
+        0
+      } else if edge_x || edge_y || eye {
+        2
+      } else {
+        1
+      };
+      tile[y] |= index << (x * 4);
+      x += 1;
+    }
+    y += 1;
+  }
+  tile
+}
+
+const CHARACTER: [Tile4; 4] =
+  [character_tile(0), character_tile(1), character_tile(2), character_tile(3)];
+
+const COLORS: [Color; 6] = [
+  Color::RED,
+  Color::ORANGE,
+  Color::YELLOW,
+  Color::GREEN,
+  Color::CYAN,
+  Color::MAGENTA,
+];
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  BACKDROP_COLOR.write(Color::from_rgb8(0x30, 0x50, 0x30));
+  copy_obj_tiles_4bpp(0, &CHARACTER);
+  for (bank, color) in COLORS.iter().enumerate() {
+    let palbank = obj_palbank(bank);
+    palbank.index(1).write(*color);
+    palbank.index(2).write(Color::BLACK);
+  }
+
+  let mut sorter = SpriteSorter::new();
+  let spinner = AffineSlot::new(0);
+  let mut walkers = [(0_i32, 0_i32, 0_i32); 6];
+  let handles = core::array::from_fn::<_, 6, _>(|i| {
+    let x = 60 + i as i32 * 10;
+    let y = 20 + (i as i32 * 37) % 100;
+    let dy = if i % 2 == 0 { 1 } else { -1 };
+    walkers[i] = (x, y, dy);
+    let mut obj = ObjAttr::new().with_size(ObjSize::_16x16);
+    obj.set_palbank(i as u16);
+    if i == 2 {
+      obj.set_affine(spinner, false);
+    }
+    sorter.add(obj, 0).unwrap()
+  });
+
+  DISPCNT
+    .write(DisplayControl::new().with_obj_vram_1d(true).with_show_obj(true));
+
+  let mut angle = 0_u16;
+  let mut frame = 0_u32;
+  loop {
+    frame += 1;
+    for (i, (walker, handle)) in walkers.iter_mut().zip(&handles).enumerate() {
+      let (x, y, dy) = walker;
+      // each character walks at its own pace.
+      if frame.is_multiple_of(i as u32 % 3 + 1) {
+        *y += *dy;
+        if *y <= 8 || *y >= 160 - 24 {
+          *dy = -*dy;
+        }
+      }
+      let obj = sorter.get_mut(*handle).unwrap();
+      obj.set_x(*x as u16);
+      obj.set_y(*y as u16);
+      sorter.set_key(*handle, (*y + 16) as u16);
+    }
+    angle = angle.wrapping_add(0x200);
+    let one = i16fx8::from_bits(1 << 8);
+    sorter
+      .oam_mut()
+      .set_affine(spinner, AffineMatrix::from_scale_rotation(one, one, angle));
+
+    spin_until_vblank();
+    if KEYINPUT.read().a() {
+      // write everything in the last order, without sorting.
+      for handle in &handles {
+        let index = sorter.oam_index(*handle).unwrap();
+        let obj = *sorter.get(*handle).unwrap();
+        *sorter.oam_mut().obj_mut(index) = obj;
+      }
+      sorter.oam().commit();
+    } else {
+      sorter.commit_sorted();
+    }
+  }
+}
@@ -0,0 +1,71 @@
+#![no_std]
+#![no_main]
+
+//! Saves a struct to SRAM, soft resets, and loads it back.
+//!
+//! At boot the example loads slot 0. A white bar at the top shows how many
+//! times A was pressed, and the rest of the screen shows what loading gave:
+//! green for a good save, blue for an empty slot, or red for a corrupt one.
+//!
+//! Pressing A adds one to the count, saves, and soft resets, so the bar should
+//! be one longer after the reset. Pressing B writes garbage over the middle of
+//! the slot and resets, which should show red.
+
+use gba::{prelude::*, save::sram, video::mode3};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct Progress {
+  presses: u16,
+  last_keys: u16,
+  frames: u32,
+}
+// Safety: all fields are plain integers, and there's no padding.
+unsafe impl bytemuck::Zeroable for Progress {}
+// Safety: as above.
+unsafe impl bytemuck::Pod for Progress {}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  mode3::clear_to(Color::BLACK);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  let loaded = load_slot::<Progress>(0);
+  let (mut progress, color) = match loaded {
+    Ok(progress) => (progress, Color::GREEN),
+    Err(SaveError::Empty) => {
+      (Progress { presses: 0, last_keys: 0, frames: 0 }, Color::BLUE)
+    }
+    Err(_) => (Progress { presses: 0, last_keys: 0, frames: 0 }, Color::RED),
+  };
+  mode3::rect_filled(0, 16, mode3::WIDTH, mode3::HEIGHT - 16, color);
+  let bar = (i32::from(progress.presses) * 4).min(mode3::WIDTH);
+  mode3::rect_filled(0, 0, bar, 8, Color::WHITE);
+
+  // Keys still held from before the reset don't count as new presses.
+  let mut keys = KeyTracker::new();
+  keys.update(KEYINPUT.read());
+  loop {
+    spin_until_vblank();
+    keys.update(KEYINPUT.read());
+    progress.frames = progress.frames.wrapping_add(1);
+    if keys.just_pressed().a() {
+      progress.presses = progress.presses.wrapping_add(1);
+      progress.last_keys = KEYINPUT.read().to_u16();
+      save_slot(0, &progress).ok();
+      soft_reset_to(ResetTarget::Rom);
+    }
+    if keys.just_pressed().b() {
+      sram::write(SLOT_HEADER_LEN + 2, &[0x5A; 2]).ok();
+      soft_reset_to(ResetTarget::Rom);
+    }
+  }
+}
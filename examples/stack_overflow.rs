@@ -0,0 +1,67 @@
+#![no_std]
+#![no_main]
+
+//! Recurses a little deeper each frame until the stack check catches it.
+//!
+//! Each frame, `main` calls `recurse` one level deeper than the last, and logs
+//! the stack's high water mark to mGBA. The vblank handler checks the stack
+//! guard, and once the stack has grown into it the handler panics, which turns
+//! the backdrop red and logs the panic message. Until then the backdrop is
+//! green.
+
+use gba::{debug::stack, prelude::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  gba::mgba_error!("{info}");
+  loop {}
+}
+
+extern "C" fn on_vblank(_: IrqBits) {
+  stack::check_stack();
+}
+
+/// Uses about 80 bytes of stack per level.
+///
+/// That's less than `GUARD_BYTES`, so going one level deeper each frame can't
+/// skip past the guard and into the statics.
+#[inline(never)]
+fn recurse(depth: u32) -> u32 {
+  let mut local = [depth; 16];
+  core::hint::black_box(&mut local);
+  if depth == 0 {
+    local[0]
+  } else {
+    recurse(depth - 1).wrapping_add(local[15])
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  stack::init();
+  gba::mgba_info!(
+    "statics use {} bytes of IWRAM, which leaves {} for the stack",
+    stack::iwram_static_bytes(),
+    stack::stack_capacity()
+  );
+
+  BACKDROP_COLOR.write(Color::GREEN);
+  DISPCNT.write(DisplayControl::new());
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  set_handler(Interrupt::VBlank, on_vblank);
+  DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+  IME.write(true);
+
+  let mut depth = 0;
+  loop {
+    VBlankIntrWait();
+    core::hint::black_box(recurse(depth));
+    gba::mgba_info!(
+      "depth {depth}: {} bytes of stack used",
+      stack::stack_high_water()
+    );
+    depth += 1;
+  }
+}
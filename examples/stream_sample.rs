@@ -0,0 +1,76 @@
+#![no_std]
+#![no_main]
+
+use gba::{prelude::*, sound::stream::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// The rate that `SAMPLE` was "recorded" at.
+const SAMPLE_RATE: u32 = 16384;
+
+/// Half a second of a rising, buzzy tone, made at compile time so that it's
+/// stored in ROM like a real sample would be.
+static SAMPLE: [i8; 8192] = make_sample();
+
+const fn make_sample() -> [i8; 8192] {
+  let mut out = [0_i8; 8192];
+  let mut i = 0;
+  let mut phase = 0_u32;
+  while i < out.len() {
+    // A saw wave, with the pitch going up from 256 Hz to 512 Hz.
+    let hz = 256 + (i as u32 * 256 / out.len() as u32);
+    phase = phase.wrapping_add(hz * (1 << 16) / SAMPLE_RATE);
+    out[i] = (((phase >> 8) & 0xFF) as i32 - 128) as i8 / 2;
+    i += 1;
+  }
+  out
+}
+
+/// Plays `SAMPLE` on a loop, resampled to the stream's rate.
+struct Looper {
+  /// The position in `SAMPLE`, as 20.12 fixed point.
+  position: u32,
+  /// How far to move through `SAMPLE` for each output sample.
+  step: u32,
+}
+impl StreamFill for Looper {
+  fn fill(&mut self, samples: &mut [i8]) {
+    let end = (SAMPLE.len() as u32) << 12;
+    for out in samples {
+      *out = SAMPLE[(self.position >> 12) as usize];
+      self.position += self.step;
+      if self.position >= end {
+        self.position -= end;
+      }
+    }
+  }
+}
+
+extern "C" fn irq_handler(bits: IrqBits) {
+  if bits.vblank() {
+    stream_vblank();
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  RUST_IRQ_HANDLER.write(Some(irq_handler));
+  init_vblank_irq();
+  DISPCNT.write(DisplayControl::new());
+
+  let rate = StreamRate::nearest(SAMPLE_RATE);
+  gba::sound::enable();
+  start_stream(StreamConfig::new(rate)).unwrap();
+
+  let step = (SAMPLE_RATE << 12) / rate.hz();
+  let mut looper = Looper { position: 0, step };
+  loop {
+    wait_for_vblank();
+    let filled = fill_stream(&mut looper);
+    BACKDROP_COLOR.write(if filled { Color::GREEN } else { Color::RED });
+  }
+}
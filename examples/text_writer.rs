@@ -0,0 +1,36 @@
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use gba::{
+  prelude::*,
+  video::{palram::set_backdrop, text::*},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  bg_palbank(0).index(1).write(Color::WHITE);
+  set_backdrop(Color::from_rgb(0, 0, 12));
+  load_font_4bpp(0, 0, 1, 0);
+
+  let mut writer = TextWriter::new(31, 0, 0);
+  writer.clear();
+  writeln!(writer, "Hello, world!").ok();
+
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+
+  let mut frame = 0_u32;
+  loop {
+    spin_until_vblank();
+    writer.set_cursor(0, 2);
+    write!(writer, "frame: {frame}").ok();
+    frame = frame.wrapping_add(1);
+  }
+}
@@ -0,0 +1,90 @@
+#![no_std]
+#![no_main]
+
+//! Scrolls around a 256x256 tile map with `camera::TiledCamera`, streaming
+//! it through a single 32x32 screenblock.
+//!
+//! The map is a grid of 16x16 tile cells, each labelled with its column and
+//! row (in hex), in alternating colors. Scroll with the d-pad, and hold A to
+//! go 8 pixels a frame, the fastest that still only streams one column and
+//! one row per frame. Press Start to jump back to the top left corner.
+
+use gba::{
+  prelude::*,
+  video::{camera::TiledCamera, tilemap::TileGrid},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+const MAP_SIZE: usize = 256;
+
+const fn hex_digit(digit: usize) -> u16 {
+  b"0123456789ABCDEF"[digit] as u16
+}
+
+const fn make_map() -> [TextEntry; MAP_SIZE * MAP_SIZE] {
+  let mut map = [TextEntry::new(); MAP_SIZE * MAP_SIZE];
+  let mut y = 0;
+  while y < MAP_SIZE {
+    let mut x = 0;
+    while x < MAP_SIZE {
+      let (cell_x, cell_y) = (x / 16, y / 16);
+      let tile = match (x % 16, y % 16) {
+        (0, 0) => 0xC5,
+        (0, _) => Cga8x8Thick::BOX_VERTICAL as u16,
+        (_, 0) => Cga8x8Thick::BOX_HORIZONTAL as u16,
+        (2, 2) => hex_digit(cell_x),
+        (3, 2) => hex_digit(cell_y),
+        _ => b'.' as u16,
+      };
+      let palbank = ((cell_x + cell_y) % 2) as u16;
+      map[y * MAP_SIZE + x] = TextEntry::from_tile(tile).with_palbank(palbank);
+      x += 1;
+    }
+    y += 1;
+  }
+  map
+}
+
+static MAP: [TextEntry; MAP_SIZE * MAP_SIZE] = make_map();
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(CHARBLOCK0_4BPP.as_region(), 0);
+  bg_palbank(0).index(1).write(Color::WHITE);
+  bg_palbank(1).index(1).write(Color::from_rgb8(0xFF, 0xC0, 0x40));
+  BACKDROP_COLOR.write(Color::from_rgb8(0x10, 0x18, 0x30));
+
+  let size = TextBackgroundSize::_32x32;
+  let map = TileGrid::new(&MAP, MAP_SIZE, MAP_SIZE);
+  let mut camera = TiledCamera::new(map, BgLayer::Bg0, 31, size);
+  with_forced_blank(|| {
+    DISPCNT.write(DisplayControl::new().with_forced_blank(true));
+    setup_text_background(BgLayer::Bg0, 0, 31, size, 0);
+    camera.jump_to(0, 0);
+  });
+
+  let (max_x, max_y) = camera.max_position();
+  let (mut x, mut y) = (0_usize, 0_usize);
+  let mut keys = KeyTracker::new();
+  loop {
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().start() {
+      (x, y) = (0, 0);
+    }
+    let speed = if keys.held().a() { 8 } else { 2 };
+    let (dx, dy) = keys.held().dpad();
+    x = x.saturating_add_signed(dx.to_i32() as isize * speed).min(max_x);
+    y = y.saturating_add_signed(dy.to_i32() as isize * speed).min(max_y);
+
+    spin_until_vblank();
+    let strips = camera.update(x, y);
+    if strips.redraw {
+      gba::mgba_info!("redrew the whole view");
+    }
+  }
+}
@@ -0,0 +1,43 @@
+#![no_std]
+#![no_main]
+
+use gba::{prelude::*, video::palram::*};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// An arrow pointing to the upper left, as a tool would export it.
+const ARROW: Tile4 = include_tiles_4bpp!("assets/arrow.4bpp")[0];
+
+/// The arrow's palette: white on black.
+static ARROW_PALETTE: &[Color] = include_palette!("assets/arrow.pal");
+
+const TILES: [Tile4; 4] = [
+  ARROW,
+  tile4_flip_h(ARROW),
+  tile4_flip_v(ARROW),
+  tile4_flip_h(tile4_flip_v(ARROW)),
+];
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  let colors = ARROW_PALETTE.try_into().expect("the palette is 16 colors");
+  write_bank(PalBank::new(Palette::Bg, 0), colors);
+  copy_tiles_4bpp(0, 1, &TILES);
+
+  // Show the four tiles in a square, each pointing towards its own corner.
+  let sb = text_screenblock(31);
+  sb.index(14, 9).write(TextEntry::from_tile(1));
+  sb.index(15, 9).write(TextEntry::from_tile(2));
+  sb.index(14, 10).write(TextEntry::from_tile(3));
+  sb.index(15, 10).write(TextEntry::from_tile(4));
+
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,72 @@
+#![no_std]
+#![no_main]
+
+//! Rolls a ball around the screen by tilting the cart.
+//!
+//! The position the cart is in at boot is flat, and pressing A makes the
+//! current position flat instead. The screen goes red if there's no tilt
+//! sensor.
+//!
+//! With mGBA, set the game overrides to have a tilt sensor, and tilt with the
+//! host's controls.
+
+use gba::{gpio::tilt, prelude::*, video::mode3};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+const SIZE: i32 = 8;
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  init_vblank_irq();
+  mode3::clear_to(Color::BLACK);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+  if tilt::calibrate().is_none() {
+    mode3::clear_to(Color::RED);
+    loop {
+      wait_for_vblank();
+    }
+  }
+
+  // In 1/16ths of a pixel.
+  let (mut x, mut y) = ((mode3::WIDTH - SIZE) * 8, (mode3::HEIGHT - SIZE) * 8);
+  let (mut dx, mut dy) = (0, 0);
+  let mut keys = KeyTracker::new();
+  keys.update(KEYINPUT.read());
+  tilt::start_sample();
+  loop {
+    wait_for_vblank();
+    mode3::rect_filled(x / 16, y / 16, SIZE, SIZE, Color::BLACK);
+
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().a() {
+      tilt::calibrate();
+    }
+    if tilt::is_ready() {
+      let (tilt_x, tilt_y) = tilt::read_centered();
+      tilt::start_sample();
+      dx = (dx + i32::from(tilt_x) / 8).clamp(-64, 64);
+      dy = (dy + i32::from(tilt_y) / 8).clamp(-64, 64);
+    }
+    x += dx;
+    y += dy;
+    let max_x = (mode3::WIDTH - SIZE) * 16;
+    let max_y = (mode3::HEIGHT - SIZE) * 16;
+    if !(0..=max_x).contains(&x) {
+      x = x.clamp(0, max_x);
+      dx = 0;
+    }
+    if !(0..=max_y).contains(&y) {
+      y = y.clamp(0, max_y);
+      dy = 0;
+    }
+
+    mode3::rect_filled(x / 16, y / 16, SIZE, SIZE, Color::WHITE);
+  }
+}
@@ -0,0 +1,29 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+static LIT: GbaCell<bool> = GbaCell::new(false);
+
+extern "C" fn toggle(_: IrqBits) {
+  // All the work happens here, the main loop does nothing.
+  let lit = !LIT.read();
+  LIT.write(lit);
+  BACKDROP_COLOR.write(if lit { Color::CYAN } else { Color::BLACK });
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  DISPCNT.write(DisplayControl::new());
+  periodic_irq(Timer::Timer0, 2, toggle).unwrap();
+  loop {
+    IntrWait(true, IrqBits::TIMER0);
+  }
+}
@@ -0,0 +1,38 @@
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  // Time a mode 3 clear. That could take more than 65,536 cycles, so count in
+  // ticks of 64 cycles to keep the timer from overflowing.
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+  Timer::Timer2.start(0, TimerControl::new().with_scale(TimerScale::_64));
+  let before = Timer::Timer2.count();
+  video3_clear_to(Color::from_rgb(0, 0, 16));
+  let after = Timer::Timer2.count();
+  Timer::Timer2.stop();
+
+  if let Ok(mut logger) = MgbaBufferedLogger::try_new(MgbaMessageLevel::Debug) {
+    let ticks = after.wrapping_sub(before);
+    writeln!(
+      logger,
+      "clear took {ticks} ticks (~{} cycles)",
+      ticks as u32 * 64
+    )
+    .ok();
+  }
+  loop {
+    spin_until_vblank();
+  }
+}
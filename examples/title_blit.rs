@@ -0,0 +1,45 @@
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use gba::prelude::*;
+
+/// A stand-in "title screen": a diagonal color gradient.
+static TITLE: Align4<[u16; 240 * 160]> = Align4({
+  let mut image = [0; 240 * 160];
+  let mut i = 0;
+  while i < image.len() {
+    let (x, y) = ((i % 240) as u16, (i / 240) as u16);
+    image[i] = Color::from_rgb(x / 8, y / 5, (x + y) / 13).0;
+    i += 1;
+  }
+  image
+});
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  spin_until_vblank();
+  let start = vcount();
+  blit_u16(&TITLE.0, BlitDest::Mode3);
+  let end = vcount();
+
+  // VCOUNT goes up by one every 1232 CPU cycles, and a frame is 228 lines.
+  let lines = (end + 228 - start) % 228;
+  if let Ok(mut logger) = MgbaBufferedLogger::try_new(MgbaMessageLevel::Debug) {
+    writeln!(logger, "title blit took {lines} scanlines").ok();
+  }
+
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,58 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// * A: a short beep (a limited length tone).
+/// * B: a tone that plays while the button is held (a continuous tone).
+/// * L: a rising "power up" sweep.
+/// * Up / Down: change the pitch of the next tone.
+#[no_mangle]
+extern "C" fn main() -> ! {
+  gba::sound::enable();
+  LEFT_RIGHT_VOLUME.write(dmg_stereo_defaults());
+  SOUND_MIX.write(SoundMix::new().with_psg(PsgMix::_100));
+
+  init_vblank_irq();
+  DISPCNT.write(DisplayControl::new());
+
+  let mut keys = KeyTracker::new();
+  let mut hz = 440_u32;
+  loop {
+    wait_for_vblank();
+    keys.update(KEYINPUT.read());
+    let pressed = keys.just_pressed();
+
+    if pressed.up() {
+      hz = note_to_sample_rate(hz, 1).min(4000);
+    }
+    if pressed.down() {
+      hz = note_to_sample_rate(hz, -1).max(100);
+    }
+    let rate = rate_from_hz(hz).unwrap();
+
+    if pressed.a() {
+      let fade = Envelope::new(15, 1, false).unwrap();
+      play_tone1(rate, Duty::_50, fade, None, Some(32));
+    }
+    if pressed.b() {
+      play_tone1(rate, Duty::_25, Envelope::constant(12), None, None);
+    }
+    if keys.just_released().b() {
+      stop_tone1();
+    }
+    if pressed.l() {
+      let sweep = Sweep::new(2, 3, false);
+      play_tone1(rate, Duty::_12_5, Envelope::constant(12), sweep, Some(64));
+    }
+
+    let playing = SOUND_ENABLED.read().tone1_playing();
+    BACKDROP_COLOR.write(if playing { Color::GREEN } else { Color::BLACK });
+  }
+}
@@ -0,0 +1,93 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// How many frames each step of the jingle lasts.
+const FRAMES_PER_STEP: u8 = 8;
+
+/// The melody (on tone 1) and bass (on tone 2), one note per step. `None` is a
+/// rest, where the voice keeps the last note fading out.
+const MELODY: [Option<Note>; 16] = [
+  Some(Note::C5),
+  Some(Note::E5),
+  Some(Note::G5),
+  Some(Note::C6),
+  None,
+  Some(Note::G5),
+  Some(Note::C6),
+  None,
+  Some(Note::A5),
+  Some(Note::F5),
+  Some(Note::A5),
+  Some(Note::C6),
+  Some(Note::B5),
+  Some(Note::G5),
+  Some(Note::C6),
+  None,
+];
+const BASS: [Option<Note>; 16] = [
+  Some(Note::C3),
+  None,
+  Some(Note::G3),
+  None,
+  Some(Note::C3),
+  None,
+  Some(Note::G3),
+  None,
+  Some(Note::F3),
+  None,
+  Some(Note::C4),
+  None,
+  Some(Note::G3),
+  None,
+  Some(Note::C3),
+  None,
+];
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  gba::sound::enable();
+  LEFT_RIGHT_VOLUME.write(dmg_stereo_defaults());
+  SOUND_MIX.write(SoundMix::new().with_psg(PsgMix::_100));
+
+  init_vblank_irq();
+  DISPCNT.write(DisplayControl::new());
+
+  // The table should agree with working out the rate from a frequency.
+  let table_ok = Some(Note::A4.to_tone_period()) == rate_from_hz(440)
+    && Some(Note::A5.to_tone_period()) == rate_from_hz(880);
+  BACKDROP_COLOR.write(if table_ok { Color::GREEN } else { Color::RED });
+
+  let lead = Envelope::new(13, 2, false).unwrap();
+  let bass = Envelope::new(15, 4, false).unwrap();
+  let mut step = 0;
+  let mut frames_left = 0;
+  loop {
+    wait_for_vblank();
+    if frames_left > 0 {
+      frames_left -= 1;
+      continue;
+    }
+    frames_left = FRAMES_PER_STEP;
+
+    if let Some(note) = MELODY[step] {
+      play_tone1(note.to_tone_period(), Duty::_25, lead, None, None);
+    }
+    if let Some(note) = BASS[step] {
+      play_tone2(note.to_tone_period(), Duty::_50, bass, None);
+    }
+    step += 1;
+    if step == MELODY.len() {
+      // Go back to the start, after a short pause.
+      step = 0;
+      frames_left = 60;
+    }
+  }
+}
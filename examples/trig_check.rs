@@ -0,0 +1,91 @@
+#![no_std]
+#![no_main]
+
+//! Checks `sin` and `cos` against `f64` math, for every binary angle.
+//!
+//! Each result has to be within the 2 bit bound from the `math` module docs.
+//! Then angles go out through `cos` and `sin` and back through the BIOS
+//! `ArcTan2`, and the largest difference is logged. The backdrop goes green if
+//! everything's within bounds (a mismatch panics, which makes it red).
+
+use gba::{
+  math::{cos, sin},
+  prelude::*,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  gba::mgba_error!("{info}");
+  loop {}
+}
+
+const PI: f64 = core::f64::consts::PI;
+
+/// `sin(x)` for `x` in `-pi..=pi`, by its Taylor series.
+fn reference_sin(x: f64) -> f64 {
+  // fold into -pi/2..=pi/2, where the series converges quickly.
+  let x = if x > PI / 2.0 {
+    PI - x
+  } else if x < -PI / 2.0 {
+    -PI - x
+  } else {
+    x
+  };
+  let mut term = x;
+  let mut total = x;
+  for n in 1..12 {
+    term *= -x * x / f64::from((2 * n) * (2 * n + 1));
+    total += term;
+  }
+  total
+}
+
+/// The exact sine of a binary angle, in `i16fx14` bits.
+fn exact_bits(angle: u16) -> f64 {
+  let x = f64::from(angle as i16) / 32768.0 * PI;
+  reference_sin(x) * 16384.0
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+
+  for (angle, want) in
+    [(0, 0), (0x4000, 0x4000), (0x8000, 0), (0xC000, -0x4000)]
+  {
+    assert_eq!(sin(angle).to_bits(), want, "sin({angle:#X})");
+  }
+  let quarter = AffineMatrix::from_rotation(0x4000);
+  assert_eq!(
+    [quarter.pa, quarter.pb, quarter.pc, quarter.pd].map(|p| p.to_bits()),
+    [0, -256, 256, 0]
+  );
+
+  let mut worst = 0.0;
+  for angle in 0..=u16::MAX {
+    let sin_error = (f64::from(sin(angle).to_bits()) - exact_bits(angle)).abs();
+    let cos_exact = exact_bits(angle.wrapping_add(0x4000));
+    let cos_error = (f64::from(cos(angle).to_bits()) - cos_exact).abs();
+    assert!(sin_error <= 2.0, "sin({angle:#X}) is {sin_error} bits off");
+    assert!(cos_error <= 2.0, "cos({angle:#X}) is {cos_error} bits off");
+    worst = sin_error.max(cos_error).max(worst);
+  }
+  gba::mgba_info!("sin and cos are at most {worst:.3} bits off");
+
+  let mut worst_turn = 0;
+  for step in 0..=u8::MAX {
+    let angle = u16::from(step) << 8;
+    let back = ArcTan2(cos(angle), sin(angle));
+    let error = (back.wrapping_sub(angle) as i16).unsigned_abs();
+    assert!(error < 0x100, "ArcTan2 gave {back:#X} for {angle:#X}");
+    worst_turn = worst_turn.max(error);
+  }
+  gba::mgba_info!("ArcTan2 round trips are at most {worst_turn:#X} off");
+
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
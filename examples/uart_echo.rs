@@ -0,0 +1,48 @@
+#![no_std]
+#![no_main]
+
+//! Sends a greeting over the link port in UART mode (115200 baud, 8N1), then
+//! echoes back every byte received. Pressing A panics, and the panic message
+//! is sent over the UART too.
+//!
+//! The backdrop turns red if the driver has counted any errors.
+
+use core::fmt::Write;
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  writeln!(Uart, "{info}").ok();
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  set_handler(Interrupt::Serial, uart_irq);
+  init_vblank_irq();
+  start_uart(UartConfig::new(BaudRate::_115200));
+
+  writeln!(Uart, "hello from the GBA, press A to panic").ok();
+
+  let mut keys = KeyTracker::new();
+  loop {
+    wait_for_vblank();
+    keys.update(KEYINPUT.read());
+    if keys.just_pressed().a() {
+      panic!("A was pressed");
+    }
+
+    while let Some(byte) = Uart.read_byte() {
+      Uart.write_byte(byte);
+    }
+
+    let counters = Uart.counters();
+    BACKDROP_COLOR.write(if counters == UartCounters::default() {
+      Color::GREEN
+    } else {
+      Color::RED
+    });
+  }
+}
@@ -0,0 +1,52 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+const SIZE: u16 = 16;
+
+fn draw_square(x: u16, y: u16, color: Color) {
+  for row in y..(y + SIZE) {
+    for col in x..(x + SIZE) {
+      VIDEO3_VRAM.index(usize::from(col), usize::from(row)).write(color);
+    }
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+  IE.write(IrqBits::VBLANK);
+  IME.write(true);
+
+  video3_clear_to(Color::BLACK);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+
+  let mut x = 0_u16;
+  let mut dx = 1_i16;
+  let mut frame = 0_u32;
+  loop {
+    // Alternate between the two wait styles, they should look the same.
+    if frame % 120 < 60 {
+      wait_for_vblank();
+    } else {
+      spin_until_vblank();
+    }
+    frame += 1;
+
+    draw_square(x, 72, Color::BLACK);
+    x = x.wrapping_add_signed(dx);
+    if x == 0 || x == 240 - SIZE {
+      dx = -dx;
+    }
+    draw_square(x, 72, Color::YELLOW);
+  }
+}
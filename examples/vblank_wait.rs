@@ -0,0 +1,23 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  init_vblank_irq();
+  DISPCNT.write(DisplayControl::new());
+  let mut red = 0_u16;
+  loop {
+    wait_for_vblank();
+    // Fades up over about half a second, then starts over.
+    red = (red + 1) & 31;
+    BACKDROP_COLOR.write(Color::new().with_red(red));
+  }
+}
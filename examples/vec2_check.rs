@@ -0,0 +1,113 @@
+#![no_std]
+#![no_main]
+
+//! Checks the `Vec2` and `Rect` math.
+//!
+//! This covers the wrapping and saturating ops at the edges of the fixed-point
+//! types, rounding to pixels, `normalize_fast`, and the rectangle tests. The
+//! backdrop goes green if everything passes (a failure panics, which makes it
+//! red).
+
+use gba::{
+  fixed::{i16fx8, i32fx8},
+  math::{Rect, Vec2},
+  prelude::*,
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  gba::mgba_error!("{info}");
+  loop {}
+}
+
+fn fx8(bits: i32) -> i32fx8 {
+  i32fx8::from_bits(bits)
+}
+
+fn check_overflow() {
+  let max = Vec2::new(i16fx8::from_bits(i16::MAX), i16fx8::from_bits(i16::MIN));
+  let one = Vec2::new(i16fx8::from_bits(1), i16fx8::from_bits(1));
+  let wrapped = max.wrapping_add(one);
+  assert_eq!((wrapped.x.to_bits(), wrapped.y.to_bits()), (i16::MIN, -32767));
+  let saturated = max.saturating_add(one);
+  assert_eq!(
+    (saturated.x.to_bits(), saturated.y.to_bits()),
+    (i16::MAX, -32767)
+  );
+  let saturated = max.saturating_sub(one);
+  assert_eq!(
+    (saturated.x.to_bits(), saturated.y.to_bits()),
+    (i16::MAX - 1, i16::MIN)
+  );
+
+  let big = Vec2::new(i32::MAX, i32::MIN);
+  assert_eq!(big.wrapping_add(Vec2::new(1, -1)), Vec2::new(i32::MIN, i32::MAX));
+  assert_eq!(big.saturating_add(Vec2::new(1, -1)), big);
+}
+
+fn check_rounding() {
+  let v = Vec2::new(fx8(0x180), fx8(-0x180));
+  assert_eq!(v.to_pixels_floor(), Vec2::new(1, -2));
+  assert_eq!(v.to_pixels_nearest(), Vec2::new(2, -1));
+  let v = Vec2::new(fx8(0x17F), fx8(-0x181));
+  assert_eq!(v.to_pixels_nearest(), Vec2::new(1, -2));
+  assert_eq!(
+    Vec2::new(3, -4).to_fixed::<8>(),
+    Vec2::new(fx8(0x300), fx8(-0x400))
+  );
+}
+
+fn check_lengths() {
+  let v = Vec2::new(fx8(3 << 8), fx8(-4 << 8));
+  assert_eq!(v.dot(v), v.length_squared());
+  assert_eq!(v.length_squared().to_bits(), 25 << 8);
+  assert_eq!(v.length().to_bits(), 5 << 8);
+  let unit = v.normalize_fast();
+  assert_eq!((unit.x.to_bits(), unit.y.to_bits()), (153, -204));
+  // too big to square in 32 bits, which `length` still handles.
+  let far = Vec2::new(fx8(3 << 20), fx8(4 << 20));
+  assert_eq!(far.length().to_bits(), 5 << 20);
+  let unit = far.normalize_fast();
+  assert_eq!((unit.x.to_bits(), unit.y.to_bits()), (153, 204));
+  let zero = Vec2::new(fx8(0), fx8(0));
+  assert_eq!(zero.normalize_fast(), zero);
+  assert_eq!(Vec2::new(3, 4).length_squared(), 25);
+}
+
+fn check_rects() {
+  let a = Rect::new(10, 10, 20, 10);
+  assert!(a.contains_point(Vec2::new(10, 10)));
+  assert!(a.contains_point(Vec2::new(29, 19)));
+  assert!(!a.contains_point(Vec2::new(30, 19)));
+  assert!(a.intersects(Rect::new(29, 19, 5, 5)));
+  assert!(!a.intersects(Rect::new(30, 10, 5, 5)));
+  assert!(!a.intersects(Rect::new(15, 15, 0, 5)));
+  assert_eq!(
+    a.intersection(Rect::new(0, 15, 15, 100)),
+    Some(Rect::new(10, 15, 5, 5))
+  );
+
+  let sprite = Rect::new(236, -3, 8, 8);
+  assert_eq!(sprite.clamp_within(Rect::SCREEN), Rect::new(232, 0, 8, 8));
+  let too_wide = Rect::new(-50, 100, 300, 8);
+  assert_eq!(too_wide.clamp_within(Rect::SCREEN), Rect::new(0, 100, 300, 8));
+  let edge = Rect::new(i32::MAX - 4, 0, 10, 1);
+  assert_eq!(edge.right(), i32::MAX);
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  check_overflow();
+  check_rounding();
+  check_lengths();
+  check_rects();
+  gba::mgba_info!("vec2 and rect checks passed");
+
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
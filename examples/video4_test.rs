@@ -3,6 +3,7 @@
 
 use gba::prelude::*;
 
+#[cfg(not(feature = "panic_handler"))]
 #[panic_handler]
 fn panic_handler(info: &core::panic::PanicInfo) -> ! {
   #[cfg(debug_assertions)]
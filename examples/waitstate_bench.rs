@@ -0,0 +1,66 @@
+#![no_std]
+#![no_main]
+
+//! Times the same code (which runs from the ROM) with different wait state
+//! settings, to show how much faster the ROM can be.
+//!
+//! The screen shows how many cycles each setting took. The default hardware
+//! setting should be the slowest by far, and the prefetch buffer should help
+//! it a lot.
+
+use core::fmt::Write;
+use gba::{
+  prelude::*,
+  profile::profiled,
+  video::{palram::set_backdrop, text::*},
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// Some busy work that runs from the ROM.
+#[inline(never)]
+fn work() -> u32 {
+  let mut x: u32 = 1;
+  for i in 0..1000 {
+    x = core::hint::black_box(x.wrapping_mul(31).wrapping_add(i));
+  }
+  x
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  bg_palbank(0).index(1).write(Color::WHITE);
+  set_backdrop(Color::from_rgb(0, 0, 12));
+  load_font_4bpp(0, 0, 1, 0);
+
+  let mut writer = TextWriter::new(31, 0, 0);
+  writer.clear();
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+
+  let settings = [
+    ("hardware", WaitstateControl::default_hardware()),
+    (
+      "hardware+prefetch",
+      WaitstateControl::default_hardware().with_prefetch(true),
+    ),
+    ("commercial", WaitstateControl::fast_commercial_cart()),
+  ];
+  for (name, setting) in settings {
+    let cycles = {
+      // Safety: every cart that runs this at all handles these settings, and
+      // the old setting is put back at the end.
+      let _guard = unsafe { WaitstateGuard::new(setting) };
+      profiled(work).1
+    };
+    writeln!(writer, "{name}: {cycles} cycles").ok();
+  }
+
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,83 @@
+#![no_std]
+#![no_main]
+
+//! Checks the packing of `WaitstateControl`.
+//!
+//! `fast_commercial_cart` has to be `0x4317`, the value the assembly runtime
+//! used to write by hand. Each field is also checked against the bits it
+//! should set, and every value of each field reads back after it's set,
+//! without changing the fields around it. The backdrop goes green if
+//! everything passes (a failure panics, which makes it red).
+
+use gba::{prelude::*, waitstate::PhiOutput};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  BACKDROP_COLOR.write(Color::RED);
+  loop {}
+}
+
+const CYCLES: [WaitCycles; 4] =
+  [WaitCycles::_4, WaitCycles::_3, WaitCycles::_2, WaitCycles::_8];
+const SECOND: [SecondAccess; 2] = [SecondAccess::Slow, SecondAccess::Fast];
+const PHI: [PhiOutput; 4] =
+  [PhiOutput::Disabled, PhiOutput::_4MHz, PhiOutput::_8MHz, PhiOutput::_16MHz];
+
+/// Sets each of `values` (which are in bit order, from 0 up) at bit `low` of
+/// a value with every other bit set, and checks the bits and the getter.
+fn check_field<E: Copy + PartialEq + core::fmt::Debug>(
+  low: u16, values: &[E], with: fn(WaitstateControl, E) -> WaitstateControl,
+  get: fn(WaitstateControl) -> E,
+) {
+  let mask = (values.len() as u16 - 1) << low;
+  // every field at its highest value, and prefetch on.
+  let full = WaitstateControl::new()
+    .with_sram(WaitCycles::_8)
+    .with_ws0_first(WaitCycles::_8)
+    .with_ws0_second(SecondAccess::Fast)
+    .with_ws1_first(WaitCycles::_8)
+    .with_ws1_second(SecondAccess::Fast)
+    .with_ws2_first(WaitCycles::_8)
+    .with_ws2_second(SecondAccess::Fast)
+    .with_phi(PhiOutput::_16MHz)
+    .with_prefetch(true);
+  assert_eq!(full.to_u16(), 0x5FFF);
+  for (bits, &value) in values.iter().enumerate() {
+    let bits = (bits as u16) << low;
+    assert_eq!(with(WaitstateControl::new(), value).to_u16(), bits);
+    let set = with(full, value);
+    assert_eq!(set.to_u16(), full.to_u16() & !mask | bits);
+    assert_eq!(get(set), value);
+  }
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  assert_eq!(WaitstateControl::fast_commercial_cart().to_u16(), 0x4317);
+  assert_eq!(WaitstateControl::default_hardware().to_u16(), 0);
+
+  check_field(0, &CYCLES, WaitstateControl::with_sram, |w| w.sram());
+  check_field(2, &CYCLES, WaitstateControl::with_ws0_first, |w| w.ws0_first());
+  check_field(4, &SECOND, WaitstateControl::with_ws0_second, |w| {
+    w.ws0_second()
+  });
+  check_field(5, &CYCLES, WaitstateControl::with_ws1_first, |w| w.ws1_first());
+  check_field(7, &SECOND, WaitstateControl::with_ws1_second, |w| {
+    w.ws1_second()
+  });
+  check_field(8, &CYCLES, WaitstateControl::with_ws2_first, |w| w.ws2_first());
+  check_field(10, &SECOND, WaitstateControl::with_ws2_second, |w| {
+    w.ws2_second()
+  });
+  check_field(11, &PHI, WaitstateControl::with_phi, |w| w.phi());
+  check_field(14, &[false, true], WaitstateControl::with_prefetch, |w| {
+    w.prefetch()
+  });
+
+  BACKDROP_COLOR.write(Color::GREEN);
+  loop {
+    spin_until_vblank();
+  }
+}
@@ -0,0 +1,53 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+// The "walk cycle" uses a few of the font glyphs as stand-in art.
+static WALK: Animation = Animation::new(
+  &[(Cga8x8Thick::FACE as u16, 8), (Cga8x8Thick::FACE_INVERSE as u16, 8)],
+  true,
+);
+static STAND: Animation =
+  Animation::new(&[(Cga8x8Thick::FACE as u16, 1)], false);
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(OBJ_TILES.as_region(), 0);
+  obj_palbank(0).index(1).write(Color::WHITE);
+
+  let mut sprites = SpriteAllocator::new();
+  let hero = sprites.alloc().unwrap();
+  *sprites.get_mut(hero).unwrap() = ObjAttr::new();
+  DISPCNT.write(DisplayControl::new().with_show_obj(true));
+
+  let mut player = AnimationPlayer::new();
+  player.play(&STAND);
+  let (mut x, mut y) = (116_i32, 76_i32);
+  loop {
+    let (dx, dy) = KEYINPUT.read().dpad();
+    let walking = dx.to_i32() != 0 || dy.to_i32() != 0;
+    let wanted: &'static Animation = if walking { &WALK } else { &STAND };
+    if player.animation() != Some(wanted) {
+      player.play(wanted);
+    }
+    x = (x + dx.to_i32()).clamp(0, 240 - 8);
+    y = (y + dy.to_i32()).clamp(0, 160 - 8);
+
+    let obj = sprites.get_mut(hero).unwrap();
+    obj.set_x(x as u16);
+    obj.set_y(y as u16);
+    if let Some(tile) = player.tick() {
+      obj.set_tile_id(tile);
+    }
+
+    spin_until_vblank();
+    sprites.oam().commit_count(4);
+  }
+}
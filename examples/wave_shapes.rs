@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+/// Each built-in wave, and a color to show while it plays.
+const SHAPES: [(&[u8; 16], Color); 4] = [
+  (&WAVE_SQUARE, Color::RED),
+  (&WAVE_SAW, Color::GREEN),
+  (&WAVE_TRIANGLE, Color::BLUE),
+  (&WAVE_SINE, Color::YELLOW),
+];
+
+/// Plays each built-in wave for a second, then a 64 sample wave made from two
+/// of them (which plays an octave lower), and repeats.
+#[no_mangle]
+extern "C" fn main() -> ! {
+  gba::sound::enable();
+  LEFT_RIGHT_VOLUME.write(dmg_stereo_defaults());
+  SOUND_MIX.write(SoundMix::new().with_psg(PsgMix::_100));
+
+  init_vblank_irq();
+  DISPCNT.write(DisplayControl::new());
+
+  let rate = wave_rate_from_hz(440).unwrap();
+  let mut both = [0_u8; 32];
+  both[..16].copy_from_slice(&WAVE_SINE);
+  both[16..].copy_from_slice(&WAVE_SQUARE);
+  loop {
+    for (shape, color) in SHAPES {
+      stop_wave();
+      // Play just bank 0.
+      WAVE_BANK.write(WaveBank::new());
+      write_wave_ram(false, shape);
+      play_wave(rate, WaveVolume::_50, None);
+      BACKDROP_COLOR.write(color);
+      for _ in 0..60 {
+        wait_for_vblank();
+      }
+    }
+
+    stop_wave();
+    write_wave_ram_64(&both);
+    play_wave(rate, WaveVolume::_50, None);
+    BACKDROP_COLOR.write(Color::WHITE);
+    for _ in 0..60 {
+      wait_for_vblank();
+    }
+  }
+}
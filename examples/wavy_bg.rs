@@ -0,0 +1,54 @@
+#![no_std]
+#![no_main]
+
+use gba::{math::sin, prelude::*};
+
+/// One wave period every 64 lines, with some extra on the end so that any 160
+/// line window of the table can be used.
+static WAVE: [u16; 160 + 64] = {
+  let mut table = [0; 160 + 64];
+  let mut i = 0;
+  while i < table.len() {
+    let s = sin((i as u16) << 10).to_bits() as i32;
+    table[i] = ((s * 6) >> 14) as u16;
+    i += 1;
+  }
+  table
+};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(CHARBLOCK0_4BPP.as_region(), 0);
+  bg_palbank(0).index(1).write(Color::WHITE);
+
+  let tsb = TEXT_SCREENBLOCKS.get_frame(31).unwrap();
+  for y in 0..32 {
+    let row = tsb.get_row(y).unwrap();
+    for (x, addr) in row.iter().enumerate() {
+      addr.write(TextEntry::from_tile(((y * 32 + x) % 256) as u16));
+    }
+  }
+  DISPCNT.write(DisplayControl::new().with_video_mode(VideoMode::_0));
+  setup_text_background(BgLayer::Bg0, 0, 31, TextBackgroundSize::_32x32, 0);
+
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  let mut effect = HblankEffect::start(1, window(0), BG0HOFS);
+  IME.write(true);
+
+  let mut phase = 0;
+  loop {
+    wait_for_vblank();
+    phase = (phase + 1) % 64;
+    effect.set_table(window(phase));
+  }
+}
+
+fn window(phase: usize) -> &'static [u16; 160] {
+  WAVE[phase..phase + 160].try_into().unwrap()
+}
@@ -0,0 +1,50 @@
+#![no_std]
+#![no_main]
+
+use gba::prelude::*;
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+  loop {}
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  Cga8x8Thick.bitunpack_4bpp(CHARBLOCK0_4BPP.as_region(), 0);
+  bg_palbank(0).index(1).write(Color::WHITE);
+  BACKDROP_COLOR.write(Color::BLUE);
+
+  // Fill the whole screen with text so the window's edges are easy to see.
+  let tsb = TEXT_SCREENBLOCKS.get_frame(31).unwrap();
+  for y in 0..32 {
+    let row = tsb.get_row(y).unwrap();
+    for (x, addr) in row.iter().enumerate() {
+      addr.write(TextEntry::from_tile(((y * 32 + x) % 256) as u16));
+    }
+  }
+  BG0CNT.write(BackgroundControl::new().with_screenblock(31));
+
+  // BG0 is only visible inside window 0, and only the backdrop is outside.
+  WININ.write(WindowInside::new().with_win0_bg0(true));
+  WINOUT.write(WindowOutside::new());
+  DISPCNT
+    .write(DisplayControl::new().with_show_bg0(true).with_enable_win0(true));
+
+  let (w, h) = (64, 48);
+  let (mut x, mut y) = (0, 0);
+  let (mut dx, mut dy) = (2, 1);
+  loop {
+    spin_until_vblank();
+    // The rectangle goes partly off screen at each edge, and gets clamped.
+    Window::rect(x, y, w, h).write_win0();
+    x += dx;
+    y += dy;
+    if !(-w / 2..=240 - w / 2).contains(&x) {
+      dx = -dx;
+    }
+    if !(-h / 2..=160 - h / 2).contains(&y) {
+      dy = -dy;
+    }
+  }
+}
@@ -0,0 +1,230 @@
+//! A bump allocator for memory that's all freed at once.
+//!
+//! A lot of what a game allocates lives exactly as long as a level (or a
+//! menu, or a cutscene): the map data, the entities, and any scratch space
+//! used while loading. An [`Arena`] hands out parts of one region of memory
+//! (usually a static in EWRAM) by moving a position forward, and it's freed by
+//! moving the position back. There are no per-allocation headers, and nothing
+//! to fragment.
+//!
+//! ```no_run
+//! # use gba::arena::Arena;
+//! #[link_section = ".ewram"]
+//! static mut LEVEL_MEMORY: [u8; 64 * 1024] = [0; 64 * 1024];
+//!
+//! // Safety: this is the only place that uses the static.
+//! let mut arena =
+//!   Arena::new(unsafe { &mut *core::ptr::addr_of_mut!(LEVEL_MEMORY) });
+//! loop {
+//!   arena.scope(|arena| {
+//!     let enemies: &mut [[i16; 2]] = arena.alloc_slice(32).unwrap();
+//!     let timer = arena.alloc(0_u32).unwrap();
+//!     // play the level...
+//!   });
+//!   // all of the level's memory is free again here.
+//! }
+//! ```
+//!
+//! ## Checkpoints
+//!
+//! [`checkpoint`](Arena::checkpoint) gives a [`Mark`] of the current position,
+//! and [`reset_to`](Arena::reset_to) frees everything allocated after it.
+//! Marks can be nested (eg: one for the level, and one inside that for the
+//! scratch space used while loading it), as long as they're reset in the
+//! opposite order to how they were taken. Resetting needs `&mut` access to the
+//! arena, so the borrow checker makes sure that nothing allocated is still in
+//! use by then.
+//!
+//! ## Only `Copy` types
+//!
+//! Nothing is ever dropped when memory is freed, so only `Copy` types (which
+//! can't have any drop code) can be allocated. A value that needs dropping
+//! would otherwise just be leaked, silently.
+
+use core::{
+  cell::Cell,
+  mem::{align_of, size_of, MaybeUninit},
+  ptr::NonNull,
+};
+
+/// A position in an [`Arena`], from [`Arena::checkpoint`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mark {
+  used: usize,
+}
+impl Mark {
+  /// The number of bytes (including alignment padding) that were in use when
+  /// this was taken.
+  #[inline]
+  #[must_use]
+  pub const fn used(self) -> usize {
+    self.used
+  }
+}
+
+/// Hands out memory from a region, which is freed all at once.
+///
+/// See the [module docs](crate::arena) for an overview.
+#[derive(Debug)]
+pub struct Arena {
+  start: NonNull<u8>,
+  capacity: usize,
+  used: Cell<usize>,
+}
+impl Arena {
+  /// Makes an arena that allocates from `region`, with nothing allocated yet.
+  #[inline]
+  #[must_use]
+  pub const fn new(region: &'static mut [u8]) -> Self {
+    let capacity = region.len();
+    // Safety: a slice's pointer is never null.
+    let start = unsafe { NonNull::new_unchecked(region.as_mut_ptr()) };
+    Self { start, capacity, used: Cell::new(0) }
+  }
+
+  /// The size of the whole region, in bytes.
+  #[inline]
+  #[must_use]
+  pub const fn capacity(&self) -> usize {
+    self.capacity
+  }
+
+  /// The number of bytes in use, including alignment padding.
+  #[inline]
+  #[must_use]
+  pub fn used(&self) -> usize {
+    self.used.get()
+  }
+
+  /// The number of bytes that are free.
+  ///
+  /// An allocation of this size might not fit, since it can need up to
+  /// `align - 1` bytes of padding first.
+  #[inline]
+  #[must_use]
+  pub fn remaining(&self) -> usize {
+    self.capacity - self.used.get()
+  }
+
+  /// Moves a value into the arena.
+  ///
+  /// Gives `None` if there isn't room. Zero-sized types always fit, and don't
+  /// use any space.
+  #[inline]
+  #[allow(clippy::mut_from_ref)]
+  pub fn alloc<T: Copy>(&self, value: T) -> Option<&mut T> {
+    let p = self.bump(size_of::<T>(), align_of::<T>())?.cast::<T>();
+    // Safety: `bump` gives a pointer that's aligned for `T`, with room for
+    // one, that isn't part of any other allocation.
+    unsafe {
+      p.as_ptr().write(value);
+      Some(&mut *p.as_ptr())
+    }
+  }
+
+  /// Allocates `len` values of `T`, all set to the default value.
+  ///
+  /// Gives `None` if there isn't room.
+  #[inline]
+  #[allow(clippy::mut_from_ref)]
+  pub fn alloc_slice<T: Copy + Default>(&self, len: usize) -> Option<&mut [T]> {
+    let slice = self.alloc_uninit_slice(len)?;
+    slice.fill(MaybeUninit::new(T::default()));
+    // Safety: every element was just set.
+    Some(unsafe { &mut *(slice as *mut [MaybeUninit<T>] as *mut [T]) })
+  }
+
+  /// Allocates a copy of a slice.
+  ///
+  /// Gives `None` if there isn't room.
+  #[inline]
+  #[allow(clippy::mut_from_ref)]
+  pub fn alloc_slice_copy<T: Copy>(&self, src: &[T]) -> Option<&mut [T]> {
+    let slice = self.alloc_uninit_slice::<T>(src.len())?;
+    // Safety: `slice` is `src.len()` elements, and is a new allocation so it
+    // can't overlap `src`.
+    unsafe {
+      let p = slice.as_mut_ptr().cast::<T>();
+      core::ptr::copy_nonoverlapping(src.as_ptr(), p, src.len());
+      Some(core::slice::from_raw_parts_mut(p, src.len()))
+    }
+  }
+
+  /// Allocates space for `len` values of `T`, without setting them.
+  ///
+  /// This is for memory that's about to be filled in anyway (eg: by a BIOS
+  /// decompression function), where setting it first would just waste time.
+  ///
+  /// Gives `None` if there isn't room.
+  #[inline]
+  #[allow(clippy::mut_from_ref)]
+  pub fn alloc_uninit_slice<T: Copy>(
+    &self, len: usize,
+  ) -> Option<&mut [MaybeUninit<T>]> {
+    let size = size_of::<T>().checked_mul(len)?;
+    let p = self.bump(size, align_of::<T>())?.cast::<MaybeUninit<T>>();
+    // Safety: `bump` gives a pointer that's aligned for `T`, with room for
+    // `len` of them, that isn't part of any other allocation.
+    Some(unsafe { core::slice::from_raw_parts_mut(p.as_ptr(), len) })
+  }
+
+  /// Marks the current position, so that everything allocated after this can
+  /// be freed with [`reset_to`](Self::reset_to).
+  #[inline]
+  #[must_use]
+  pub fn checkpoint(&self) -> Mark {
+    Mark { used: self.used.get() }
+  }
+
+  /// Frees everything allocated since `mark` was taken.
+  ///
+  /// If the arena has already been reset to before `mark` (or if it's from a
+  /// different arena and is past the current position) this does nothing.
+  #[inline]
+  pub fn reset_to(&mut self, mark: Mark) {
+    if mark.used < self.used.get() {
+      self.used.set(mark.used);
+    }
+  }
+
+  /// Frees everything.
+  #[inline]
+  pub fn reset(&mut self) {
+    self.used.set(0);
+  }
+
+  /// Runs `f` with the arena, and then frees everything that it allocated.
+  ///
+  /// The closure's output can't borrow from the arena, so it can't keep
+  /// anything that's freed.
+  #[inline]
+  pub fn scope<R>(&mut self, f: impl FnOnce(&Self) -> R) -> R {
+    let mark = self.checkpoint();
+    let out = f(self);
+    self.reset_to(mark);
+    out
+  }
+
+  /// Takes `size` bytes aligned to `align` (a power of two) from the free
+  /// space, or gives `None` if they don't fit.
+  ///
+  /// Zero sized requests don't use any space (not even padding), and get a
+  /// dangling pointer with the right alignment.
+  #[inline]
+  fn bump(&self, size: usize, align: usize) -> Option<NonNull<u8>> {
+    if size == 0 {
+      // Safety: `align` is never 0.
+      return Some(unsafe { NonNull::new_unchecked(align as *mut u8) });
+    }
+    let used = self.used.get();
+    let addr = (self.start.as_ptr() as usize).wrapping_add(used);
+    let padding = addr.wrapping_neg() & (align - 1);
+    let end = used.checked_add(padding)?.checked_add(size)?;
+    if end > self.capacity {
+      return None;
+    }
+    self.used.set(end);
+    // Safety: `used + padding` is within the region, so it's not null.
+    Some(unsafe { self.start.add(used + padding) })
+  }
+}
@@ -8,6 +8,9 @@
 //!   within the system on its own without you having to do anything.
 //! * If a function is set in the `RUST_IRQ_HANDLER` variable then that function
 //!   will be called and passed the bits for which interrupt(s) occurred.
+//! * On each vblank interrupt it also adds one to the
+//!   [`frame_count`](crate::time::frame_count), before calling the Rust
+//!   handler.
 
 use crate::{
   dma::DmaControl,
@@ -15,6 +18,8 @@ use crate::{
   interrupts::IrqFn,
   mgba::MGBA_LOGGING_ENABLE_REQUEST,
   mmio::{DMA3_SRC, IME, MGBA_LOG_ENABLE, WAITCNT},
+  rom::{Header, MultibootHeader, RUNTIME_HEADER},
+  waitstate::WaitstateControl,
 };
 
 const DMA_32_BIT_MEMCPY: DmaControl =
@@ -23,6 +28,24 @@ const DMA_32_BIT_MEMCPY: DmaControl =
 const DMA3_OFFSET: usize = DMA3_SRC.as_usize() - 0x0400_0000;
 const WAITCNT_OFFSET: usize = WAITCNT.as_usize() - 0x0400_0000;
 
+/// The bytes of [`RUNTIME_HEADER`], 16 at a time for `.octa`.
+const HEADER_OCTAS: [u128; Header::LEN / 16] = {
+  let bytes = RUNTIME_HEADER.to_bytes();
+  let mut octas = [0; Header::LEN / 16];
+  let mut i = 0;
+  while i < octas.len() {
+    let mut octa = [0; 16];
+    let mut j = 0;
+    while j < 16 {
+      octa[j] = bytes[16 * i + j];
+      j += 1;
+    }
+    octas[i] = u128::from_le_bytes(octa);
+    i += 1;
+  }
+  octas
+};
+
 // Proc-macros can't see the target being built for, so we use this declarative
 // macro to determine if we're on a thumb target (and need to force our asm into
 // a32 mode) or if we're not on thumb (and our asm can pass through untouched).
@@ -42,6 +65,7 @@ macro_rules! force_a32 {
     )
   }
 }
+pub(crate) use force_a32;
 
 core::arch::global_asm! {
   bracer::put_fn_in_section!(".text.gba_rom_header"),
@@ -49,9 +73,12 @@ core::arch::global_asm! {
   "__start:",
 
   force_a32!{
-    // space for the header
-    "b 1f",
-    ".space 0xE0",
+    // the header, which starts with a branch to `1:`, and then space for the
+    // multiboot fields.
+    ".octa {h0}", ".octa {h1}", ".octa {h2}", ".octa {h3}",
+    ".octa {h4}", ".octa {h5}", ".octa {h6}", ".octa {h7}",
+    ".octa {h8}", ".octa {h9}", ".octa {h10}", ".octa {h11}",
+    ".space {multiboot_fields}",
     "1:", /* post header */
 
     // set the waitstate control to the GBATEK suggested setting.
@@ -115,18 +142,37 @@ core::arch::global_asm! {
   },
 
   // Define Our Constants
+  h0 = const HEADER_OCTAS[0],
+  h1 = const HEADER_OCTAS[1],
+  h2 = const HEADER_OCTAS[2],
+  h3 = const HEADER_OCTAS[3],
+  h4 = const HEADER_OCTAS[4],
+  h5 = const HEADER_OCTAS[5],
+  h6 = const HEADER_OCTAS[6],
+  h7 = const HEADER_OCTAS[7],
+  h8 = const HEADER_OCTAS[8],
+  h9 = const HEADER_OCTAS[9],
+  h10 = const HEADER_OCTAS[10],
+  h11 = const HEADER_OCTAS[11],
+  multiboot_fields = const MultibootHeader::LEN - Header::LEN,
   mmio_base = const 0x0400_0000,
   waitcnt_offset = const WAITCNT_OFFSET,
-  waitcnt_setting = const 0x4317 /*sram8,r0:3.1,r1:4.2,r2:8.2,no_phi,prefetch*/,
+  waitcnt_setting = const WaitstateControl::fast_commercial_cart().to_u16(),
   dma3_offset = const DMA3_OFFSET,
   dma3_setting = const DMA_32_BIT_MEMCPY.to_u16(),
   mgba_log_enable = const MGBA_LOG_ENABLE.as_usize(),
   mgba_logging_enable_request = const MGBA_LOGGING_ENABLE_REQUEST,
 }
 
-// This handler DOES NOT allow nested interrupts at this time.
+// Nested interrupts are allowed if a Rust handler unmasks them (see
+// `interrupts::allow_nesting`). A nested interrupt overwrites both `spsr_irq`
+// and `lr_irq`, so both are saved on the user stack around the Rust call.
+//
+// The handler is in IWRAM (like `iwram_code!` functions), since it runs on
+// every interrupt. The BIOS is only told where it is after `__start` has copied
+// the IWRAM sections.
 core::arch::global_asm! {
-  bracer::put_fn_in_section!(".text.gba_rom_header"),
+  bracer::put_fn_in_section!(".iwram.__runtime_irq_handler"),
   ".global __runtime_irq_handler",
   // On Entry: r0 = 0x0400_0000 (mmio_base)
   // We're allowed to use the usual C ABI registers.
@@ -139,6 +185,12 @@ core::arch::global_asm! {
     "ldr  r0, [r12, #0x200]!",  // load IE_IF with r12 writeback
     "and  r0, r0, r0, LSR #16", // bits = IE & IF
     "strh r0, [r12, #2]",       // write16 to just IF
+    // count vblanks for `time::frame_count`
+    "tst   r0, #1",             // if bits has vblank
+    "ldrne r1, ={FRAME_COUNT}",
+    "ldrne r2, [r1]",
+    "addne r2, r2, #1",
+    "strne r2, [r1]",           // then add 1 to the count
     // handle BIOS IntrWait system
     "ldr  r1, [r12, #-0x208]!", // load BIOS_IF_?? with r12 writeback
     "orr  r1, r1, r0",          // mark `bits` as `has_occurred`
@@ -149,12 +201,15 @@ core::arch::global_asm! {
     "ldr r12, [r12]",
     bracer::when!(("r12" != "#0")[1] {
       bracer::a32_read_spsr_to!("r3"),
+      "mov r2, lr",
       bracer::a32_set_cpu_control!(System, irq_masked = true, fiq_masked = true),
-      "push {{r3, lr}}",
+      // r12 is only pushed to keep the stack aligned to 8.
+      "push {{r2, r3, r12, lr}}",
       bracer::a32_fake_blx!("r12"),
-      "pop {{r3, lr}}",
+      "pop {{r2, r3, r12, lr}}",
       bracer::a32_set_cpu_control!(IRQ, irq_masked = true, fiq_masked = true),
       bracer::a32_write_spsr_from!("r3"),
+      "mov lr, r2",
     }),
 
     // return to the BIOS
@@ -163,4 +218,5 @@ core::arch::global_asm! {
 
   // Define Our Constants
   RUST_IRQ_HANDLER = sym crate::RUST_IRQ_HANDLER,
+  FRAME_COUNT = sym crate::time::FRAME_COUNT,
 }
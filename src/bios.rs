@@ -9,7 +9,15 @@
 //! of the function ends up inlined). Despite this higher cost, some bios
 //! functions are useful enough to justify the overhead.
 
-use crate::{fixed::i16fx14, interrupts::IrqBits};
+use crate::{
+  arena::Arena,
+  fixed::{i16fx14, i16fx8, i32fx8},
+  interrupts::IrqBits,
+  macros::{pub_const_fn_new_zeroed, u8_bool_field},
+  video::{obj::AffineMatrix, BgAffineParams},
+};
+use core::mem::MaybeUninit;
+use voladdress::{Safe, VolRegion};
 
 // Note(Lokathor): All `swi` calls will preserve the flags. You should generally
 // not use any other inline-asm options with `swi` calls.
@@ -18,11 +26,14 @@ use crate::{fixed::i16fx14, interrupts::IrqBits};
 ///
 /// This clears the BIOS portion of IWRAM (the top `0x200` bytes), resets the
 /// SVC, IRQ, and SYS stack pointers to their defaults, then performs a `bx r14`
-/// to go to an address based on what's written to the byte at `0x0300_7FFA`:
+/// to go to an address based on what's written to the byte at `0x0300_7FFA`
+/// ([`SOFT_RESET_TARGET`](crate::mmio::SOFT_RESET_TARGET)):
 /// * zero: `0x0800_0000` (ROM)
-/// * non-zero: `0x0200_0000` (IWRAM).
+/// * non-zero: `0x0200_0000` (EWRAM).
 ///
 /// (Note: the target address is determined *before* clearing the top of IWRAM.)
+///
+/// Use [`soft_reset_to`] to set the target and reset in one step.
 #[inline]
 #[instruction_set(arm::t32)]
 pub fn SoftReset() -> ! {
@@ -34,6 +45,168 @@ pub fn SoftReset() -> ! {
   };
 }
 
+/// Where [`SoftReset`] starts running the program again.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum ResetTarget {
+  /// `0x0800_0000`, the start of the ROM. This is the normal reset.
+  #[default]
+  Rom = 0,
+  /// `0x0200_0000`, the start of EWRAM. This is for programs that were loaded
+  /// into EWRAM (such as multiboot programs).
+  Ewram = 1,
+}
+
+/// Sets which address to reset to, and then does a [`SoftReset`].
+#[inline]
+pub fn soft_reset_to(target: ResetTarget) -> ! {
+  crate::mmio::SOFT_RESET_TARGET.write(target as u8);
+  SoftReset()
+}
+
+/// The parts of the system for [`RegisterRamReset`] to reset.
+///
+/// * `ewram`: Clears EWRAM (all 256K).
+/// * `iwram`: Clears IWRAM, *except* for the last `0x200` bytes (which have the
+///   BIOS variables and the default stacks).
+/// * `palram`, `vram`, `oam`: Clears that video memory.
+/// * `sio`: Resets the serial registers (and switches to general purpose mode).
+/// * `sound`: Resets the sound registers.
+/// * `other`: Resets all the other registers (except the serial and sound
+///   ones).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct RamResetFlags(u8);
+impl RamResetFlags {
+  pub_const_fn_new_zeroed!();
+  u8_bool_field!(0, ewram, with_ewram);
+  u8_bool_field!(1, iwram, with_iwram);
+  u8_bool_field!(2, palram, with_palram);
+  u8_bool_field!(3, vram, with_vram);
+  u8_bool_field!(4, oam, with_oam);
+  u8_bool_field!(5, sio, with_sio);
+  u8_bool_field!(6, sound, with_sound);
+  u8_bool_field!(7, other, with_other);
+
+  /// Everything.
+  pub const ALL: Self = Self(0xFF);
+
+  /// Just the video memory (palette, VRAM, and OAM).
+  pub const VIDEO: Self =
+    Self::new().with_palram(true).with_vram(true).with_oam(true);
+
+  /// Every register, but none of the memory.
+  pub const REGISTERS: Self =
+    Self::new().with_sio(true).with_sound(true).with_other(true);
+
+  #[inline]
+  #[must_use]
+  #[allow(missing_docs)]
+  pub const fn to_u8(self) -> u8 {
+    self.0
+  }
+}
+
+/// `0x01`: Resets the memory and registers selected.
+///
+/// The sharp edges:
+/// * Clearing IWRAM clears all of your IWRAM statics, and also any part of the
+///   stack that's below the top `0x200` bytes. Rust code can't keep running
+///   safely after that, so with `iwram` set you should call [`SoftReset`] right
+///   after this (see [`reset_and_restart`]).
+/// * Clearing EWRAM likewise clears any EWRAM statics (and any code or data
+///   that was loaded there).
+/// * Before handing off to another program (such as with multiboot), reset the
+///   `sio` and `sound` registers so it starts from a known state.
+///
+/// ## Safety
+/// * If `iwram` or `ewram` is set, then Rust data in that memory is gone. You
+///   must not use any of it afterwards.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn RegisterRamReset(flags: RamResetFlags) {
+  core::arch::asm! {
+    "swi #0x01",
+    inout("r0") u32::from(flags.to_u8()) => _,
+    out("r1") _,
+    out("r3") _,
+    options(preserves_flags),
+  }
+}
+
+/// Resets the memory and registers selected, then does a [`SoftReset`] to
+/// `target`.
+///
+/// Since nothing runs between the two calls, this is safe for any `flags`
+/// (including clearing IWRAM).
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn reset_and_restart(flags: RamResetFlags, target: ResetTarget) -> ! {
+  crate::mmio::SOFT_RESET_TARGET.write(target as u8);
+  unsafe {
+    core::arch::asm! {
+      "swi #0x01",
+      "swi #0x00",
+      in("r0") u32::from(flags.to_u8()),
+      options(noreturn),
+    }
+  }
+}
+
+/// `0x02`: Halts the CPU until any enabled interrupt happens.
+///
+/// The CPU stops running until an interrupt that's enabled in
+/// [`IE`](crate::mmio::IE) is flagged, which saves power. This is the lower
+/// level primitive that [`IntrWait`] is built on. Unlike `IntrWait` it doesn't
+/// check *which* interrupt happened, and it doesn't touch
+/// [`IME`](crate::mmio::IME).
+///
+/// If `IME` is off the CPU still wakes up, but the interrupt handler won't run
+/// (and the interrupt stays flagged in [`IF`](crate::mmio::IF)). If no
+/// interrupt is enabled in `IE` this never returns.
+#[inline]
+#[instruction_set(arm::t32)]
+pub fn Halt() {
+  unsafe {
+    core::arch::asm! {
+      "swi #0x02",
+      out("r0") _,
+      out("r1") _,
+      out("r3") _,
+      options(preserves_flags),
+    }
+  };
+}
+
+/// `0x03`: Stops the CPU, the display, and the sound until a wake interrupt.
+///
+/// This is the lowest power state. Only the keypad, gamepak, or serial
+/// interrupts can wake the system (and only when they're enabled in
+/// [`IE`](crate::mmio::IE)). The timers and the display are stopped, so
+/// nothing else will ever happen.
+///
+/// Prefer [`power::stop`](crate::power::stop), which handles all of the setup.
+///
+/// ## Safety
+/// * At least one of the keypad, gamepak, or serial interrupts must be enabled
+///   and configured, or the system stays stopped until it's turned off.
+/// * The display should be off ([`forced_blank`] set in
+///   [`DISPCNT`](crate::mmio::DISPCNT)) first. Otherwise the LCD is left
+///   showing a frozen image, which can leave an afterimage on hardware.
+///
+/// [`forced_blank`]: crate::video::DisplayControl::forced_blank
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn Stop() {
+  core::arch::asm! {
+    "swi #0x03",
+    out("r0") _,
+    out("r1") _,
+    out("r3") _,
+    options(preserves_flags),
+  }
+}
+
 /// `0x04`: Waits for a specific interrupt type(s) to happen.
 ///
 /// Pauses the CPU until any of the interrupt types set in `target_irqs` to
@@ -54,9 +227,28 @@ pub fn SoftReset() -> ! {
 ///   this function will wait for a new target interrupt to occur.
 /// * Otherwise, any previous interrupts that match `target_irqs` will cause the
 ///   function to return immediately without waiting for a new interrupt.
+///
+/// That BIOS variable is [`BIOS_IF`](crate::mmio::BIOS_IF), and it's up to the
+/// interrupt handler to set bits in it: the BIOS itself doesn't. An interrupt
+/// handler that acknowledges [`IF`](crate::mmio::IF) but not `BIOS_IF` will
+/// make this hang forever, even though the interrupts are happening. The
+/// crate's assembly runtime handler always sets both, so this is only a
+/// concern if you replace it.
+///
+/// ## Panics
+/// * With debug assertions on, this checks that at least one of the
+///   `target_irqs` is enabled in [`IE`](crate::mmio::IE), and panics instead of
+///   hanging if none are.
 #[inline]
 #[instruction_set(arm::t32)]
+#[cfg_attr(feature = "track_caller", track_caller)]
 pub fn IntrWait(ignore_existing: bool, target_irqs: IrqBits) {
+  if cfg!(debug_assertions) {
+    assert!(
+      !crate::mmio::IE.read().intersection(target_irqs).is_empty(),
+      "IntrWait would hang: none of the target interrupts are enabled in IE"
+    );
+  }
   unsafe {
     core::arch::asm! {
       "swi #0x04",
@@ -83,10 +275,143 @@ pub fn VBlankIntrWait() {
   };
 }
 
+/// The output of [`Div`] and [`DivArm`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DivOutput {
+  /// The quotient, rounded toward zero (same as `/`).
+  pub quotient: i32,
+  /// The remainder, which has the sign of the numerator (same as `%`).
+  pub remainder: i32,
+  /// The absolute value of the quotient.
+  pub abs_quotient: u32,
+}
+
+/// `0x06`: Signed division, without checking the inputs.
+///
+/// The BIOS division is faster than the software division that the compiler
+/// uses for `/` and `%` on `i32`, though it's still slow compared to other
+/// math, since the GBA has no hardware divider. You also get both the quotient
+/// and the remainder from one call.
+///
+/// ## Safety
+/// * `denominator` must not be 0. The BIOS goes into an endless loop when it
+///   is.
+/// * The division must not overflow (`i32::MIN` divided by -1), which the BIOS
+///   doesn't handle correctly.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn DivUnchecked(numerator: i32, denominator: i32) -> DivOutput {
+  let quotient: i32;
+  let remainder: i32;
+  let abs_quotient: u32;
+  unsafe {
+    core::arch::asm! {
+      "swi #0x06",
+      inout("r0") numerator => quotient,
+      inout("r1") denominator => remainder,
+      out("r2") _,
+      out("r3") abs_quotient,
+      options(pure, nomem, preserves_flags),
+    }
+  };
+  DivOutput { quotient, remainder, abs_quotient }
+}
+
+/// `0x06`: Signed division.
+///
+/// This gives the same results as [`i32::checked_div`] and
+/// [`i32::checked_rem`], but uses the BIOS (see [`DivUnchecked`]).
+///
+/// * **Returns:** `None` if `denominator` is 0, or if the division would
+///   overflow (`i32::MIN` divided by -1).
+#[inline]
+#[must_use]
+pub fn Div(numerator: i32, denominator: i32) -> Option<DivOutput> {
+  if denominator == 0 || (numerator == i32::MIN && denominator == -1) {
+    None
+  } else {
+    Some(unsafe { DivUnchecked(numerator, denominator) })
+  }
+}
+
+/// `0x07`: Signed division, with the arguments in the other order.
+///
+/// This is the same as [`Div`], except that the BIOS function takes the
+/// denominator first (to match the ARM C library). It's a few cycles slower,
+/// since the BIOS just swaps the arguments and then does `Div`, so this only
+/// exists for completeness.
+#[inline]
+#[must_use]
+#[instruction_set(arm::t32)]
+pub fn DivArm(denominator: i32, numerator: i32) -> Option<DivOutput> {
+  if denominator == 0 || (numerator == i32::MIN && denominator == -1) {
+    return None;
+  }
+  let quotient: i32;
+  let remainder: i32;
+  let abs_quotient: u32;
+  unsafe {
+    core::arch::asm! {
+      "swi #0x07",
+      inout("r0") denominator => quotient,
+      inout("r1") numerator => remainder,
+      out("r2") _,
+      out("r3") abs_quotient,
+      options(pure, nomem, preserves_flags),
+    }
+  };
+  Some(DivOutput { quotient, remainder, abs_quotient })
+}
+
+/// `0x08`: Integer square root.
+///
+/// * **Returns:** The square root of `x`, rounded down.
+///
+/// For a fixed-point value the result has half as many fraction bits as the
+/// input, so a value with 16 fraction bits gives a result with 8 fraction
+/// bits (eg: the square root of `2.0` as a `u32` with 16 fraction bits is
+/// `Sqrt(2 << 16) == 362`, which is about `1.414 * 256`).
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// assert_eq!(Sqrt(144), 12);
+/// assert_eq!(Sqrt(u32::MAX), u16::MAX);
+/// ```
+#[inline]
+#[must_use]
+#[instruction_set(arm::t32)]
+pub fn Sqrt(x: u32) -> u16 {
+  let output: u32;
+  unsafe {
+    core::arch::asm! {
+      "swi #0x08",
+      inout("r0") x => output,
+      out("r1") _,
+      out("r3") _,
+      options(pure, nomem, preserves_flags),
+    }
+  };
+  output as u16
+}
+
 /// `0x09`: Arc tangent.
 ///
-/// * **Returns:** The output is in the range +/- `pi/2`, but accuracy is worse
-///   outside of +/- `pi/4`.
+/// * `theta` is the *tangent* value, as a 1.14 fixed-point number (so the input
+///   range is about -2.0 to +2.0).
+/// * **Returns:** The angle, in the range +/- `pi/2`, but accuracy is worse
+///   outside of +/- `pi/4`. The output bits are a "binary angle" like the one
+///   [`ArcTan2`] gives, with `0x4000` (1.0 as an `i16fx14`) meaning `pi/2`, and
+///   `-0x4000` meaning `-pi/2`.
+///
+/// To get an angle from anywhere on the circle (or to avoid dividing to get a
+/// tangent in the first place), use [`ArcTan2`].
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// // tan(pi/4) == 1.0, and pi/4 is half of a quarter turn.
+/// let angle = ArcTan(i16fx14::from_bits(0x4000));
+/// assert!((angle.to_bits() - 0x2000).abs() < 0x10);
+/// ```
 #[inline]
 #[instruction_set(arm::t32)]
 pub fn ArcTan(theta: i16fx14) -> i16fx14 {
@@ -107,8 +432,22 @@ pub fn ArcTan(theta: i16fx14) -> i16fx14 {
 ///
 /// [wp-atan2]: https://en.wikipedia.org/wiki/Atan2
 ///
-/// * **Returns:** The angle of the input vector, with `u16::MAX` being
-///   equivalent to `2pi`.
+/// * `x` and `y` are the vector's coordinates as 1.14 fixed-point numbers. Only
+///   their ratio matters, so scaling both by the same amount gives the same
+///   angle.
+/// * **Returns:** The angle of the input vector, counter-clockwise from the
+///   `+x` direction, as a 16-bit "binary angle": the full `u16` range is one
+///   turn, so `0x4000` is `pi/2`, `0x8000` is `pi`, and `0xC000` is `3pi/2`.
+///   With `u16` wrapping math, adding angles wraps around the circle correctly.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// let one = i16fx14::from_bits(0x4000);
+/// let zero = i16fx14::from_bits(0);
+/// assert_eq!(ArcTan2(one, zero), 0x0000);
+/// assert_eq!(ArcTan2(zero, one), 0x4000);
+/// assert_eq!(ArcTan2(-one, zero), 0x8000);
+/// ```
 #[inline]
 #[instruction_set(arm::t32)]
 pub fn ArcTan2(x: i16fx14, y: i16fx14) -> u16 {
@@ -127,6 +466,379 @@ pub fn ArcTan2(x: i16fx14, y: i16fx14) -> u16 {
   output as u16
 }
 
+/// `0x0C`: Copy or fill memory in blocks of 8 words.
+///
+/// * `control` bits `0..=20` are the number of words. This is rounded *up* to
+///   the next multiple of 8 by the BIOS, so pass a multiple of 8.
+/// * If `control` bit 24 is set then the word at `src` is used to fill `dest`,
+///   otherwise `src` is copied to `dest`.
+///
+/// ## Safety
+/// * Both pointers must be aligned to 4.
+/// * `src` must be readable (one word when filling) and `dest` must be writable
+///   for the number of words given.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn CpuFastSet(src: *const u32, dest: *mut u32, control: u32) {
+  core::arch::asm! {
+    "swi #0x0C",
+    inout("r0") src => _,
+    inout("r1") dest => _,
+    inout("r2") control => _,
+    out("r3") _,
+    options(preserves_flags),
+  }
+}
+
+/// The input to [`BgAffineSet`] for one background.
+///
+/// Unlike [`ObjAffineSource`], the scale here is the scale that you see on
+/// screen (the BIOS does the inverting).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C, align(4))]
+pub struct BgAffineSource {
+  /// The background pixel to scale and rotate around.
+  pub center_x: i32fx8,
+  /// See `center_x`.
+  pub center_y: i32fx8,
+  /// The screen pixel where the center should appear.
+  pub display_x: i16,
+  /// See `display_x`.
+  pub display_y: i16,
+  /// How much bigger the background should look (eg: 2.0 is twice as big).
+  pub scale_x: i16fx8,
+  /// See `scale_x`.
+  pub scale_y: i16fx8,
+  /// A counter-clockwise [binary angle](crate::math). The BIOS only uses the
+  /// upper 8 bits.
+  pub angle: u16,
+}
+
+/// `0x0D`: Gets a checksum of the BIOS.
+///
+/// This is an undocumented BIOS function. See [`detect_bios`] for what the
+/// known values are.
+#[inline]
+#[must_use]
+#[instruction_set(arm::t32)]
+pub fn GetBiosChecksum() -> u32 {
+  let checksum: u32;
+  unsafe {
+    core::arch::asm! {
+      "swi #0x0D",
+      out("r0") checksum,
+      out("r1") _,
+      out("r3") _,
+      options(pure, nomem, preserves_flags),
+    }
+  };
+  checksum
+}
+
+/// Which BIOS the program is running with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BiosKind {
+  /// The BIOS of a GBA (or GBA SP, or Game Boy Player).
+  Gba,
+  /// The GBA BIOS of a Nintendo DS, running a GBA game.
+  Ds,
+  /// Some other BIOS, with the checksum it gave.
+  ///
+  /// This is usually a replacement BIOS (such as in an emulator that doesn't
+  /// have a copy of the real one). Emulators that stub out the checksum
+  /// function entirely tend to give 0 here.
+  Unknown(u32),
+}
+impl BiosKind {
+  /// If this is one of the official BIOSes.
+  #[inline]
+  #[must_use]
+  pub const fn is_official(self) -> bool {
+    matches!(self, Self::Gba | Self::Ds)
+  }
+}
+
+/// Works out which BIOS the program is running with, from its checksum.
+///
+/// Note that emulators with a built-in replacement BIOS often copy the
+/// official checksum (mGBA does, for example), so `Gba` doesn't prove that the
+/// real BIOS is there. But an `Unknown` result does mean some BIOS behavior
+/// might be different from the real thing.
+#[inline]
+#[must_use]
+pub fn detect_bios() -> BiosKind {
+  match GetBiosChecksum() {
+    0xBAAE_187F => BiosKind::Gba,
+    0xBAAE_1880 => BiosKind::Ds,
+    other => BiosKind::Unknown(other),
+  }
+}
+
+/// `0x0E`: Computes background affine parameters from scales and angles.
+///
+/// This is the BIOS version of [`BgAffineParams::from_scale_rotation`]. Each
+/// output is laid out the same as the affine registers of a background, so
+/// `dest` can point right at [`BG2PA`](crate::mmio::BG2PA) or
+/// [`BG3PA`](crate::mmio::BG3PA).
+///
+/// ## Safety
+/// * `src` must be readable, and `dest` writable, for `count` elements.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn BgAffineSet(
+  src: *const BgAffineSource, dest: *mut BgAffineParams, count: u32,
+) {
+  core::arch::asm! {
+    "swi #0x0E",
+    inout("r0") src => _,
+    inout("r1") dest => _,
+    inout("r2") count => _,
+    out("r3") _,
+    options(preserves_flags),
+  }
+}
+
+/// Computes background affine parameters with [`BgAffineSet`].
+///
+/// ## Panics
+/// * The slices must be the same length.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn bg_affine_set(src: &[BgAffineSource], dest: &mut [BgAffineParams]) {
+  assert_eq!(src.len(), dest.len(), "slices must be the same length");
+  unsafe { BgAffineSet(src.as_ptr(), dest.as_mut_ptr(), src.len() as u32) };
+}
+
+/// The input to [`ObjAffineSet`] for one matrix.
+///
+/// **Note:** the scale here goes straight into the matrix, and the matrix is
+/// the *inverse* of what you see on screen (see [`AffineMatrix`]). So a scale
+/// of 2.0 makes the object look half as big.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C, align(4))]
+pub struct ObjAffineSource {
+  /// The inverse of the horizontal scale.
+  pub inv_scale_x: i16fx8,
+  /// The inverse of the vertical scale.
+  pub inv_scale_y: i16fx8,
+  /// A counter-clockwise [binary angle](crate::math). The BIOS only uses the
+  /// upper 8 bits.
+  pub angle: u16,
+}
+
+/// The [`ObjAffineSet`] stride for writing to packed `[pa, pb, pc, pd]`
+/// values, such as a slice of [`AffineMatrix`].
+pub const OBJ_AFFINE_STRIDE_PACKED: u32 = 2;
+
+/// The [`ObjAffineSet`] stride for writing to OAM (or an exact copy of it).
+///
+/// In OAM each matrix value is in the last halfword of an 8 byte object
+/// entry. Using [`OBJ_AFFINE_STRIDE_PACKED`] there instead would write over
+/// object attributes.
+pub const OBJ_AFFINE_STRIDE_OAM: u32 = 8;
+
+/// `0x0F`: Computes object affine matrices from scales and angles.
+///
+/// For each source, four `i16` values (`pa`, `pb`, `pc`, `pd`) are written
+/// starting at `dest`, each one `stride` bytes after the last. The next
+/// matrix starts right after the last value of the previous one. Use
+/// [`OBJ_AFFINE_STRIDE_PACKED`] or [`OBJ_AFFINE_STRIDE_OAM`].
+///
+/// ## Safety
+/// * `src` must be readable for `count` elements.
+/// * `dest` must be aligned to 2, and writable for `4 * count` values spaced
+///   out by `stride` bytes.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn ObjAffineSet(
+  src: *const ObjAffineSource, dest: *mut i16, count: u32, stride: u32,
+) {
+  core::arch::asm! {
+    "swi #0x0F",
+    inout("r0") src => _,
+    inout("r1") dest => _,
+    inout("r2") count => _,
+    inout("r3") stride => _,
+    options(preserves_flags),
+  }
+}
+
+/// Computes object affine matrices with [`ObjAffineSet`].
+///
+/// To write a matrix into an [`OamShadow`](crate::video::obj::OamShadow) use
+/// [`OamShadow::set_affine_from`](crate::video::obj::OamShadow::set_affine_from).
+///
+/// ## Panics
+/// * The slices must be the same length.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn obj_affine_set(src: &[ObjAffineSource], dest: &mut [AffineMatrix]) {
+  assert_eq!(src.len(), dest.len(), "slices must be the same length");
+  unsafe {
+    ObjAffineSet(
+      src.as_ptr(),
+      dest.as_mut_ptr().cast(),
+      src.len() as u32,
+      OBJ_AFFINE_STRIDE_PACKED,
+    )
+  };
+}
+
+/// `0x0B`: Copy or fill memory, a halfword or word at a time.
+///
+/// * `control` bits `0..=20` are the number of units (halfwords or words).
+/// * If `control` bit 24 is set then the unit at `src` is used to fill `dest`,
+///   otherwise `src` is copied to `dest`.
+/// * If `control` bit 26 is set the units are words, otherwise halfwords.
+///
+/// This is slower than [`CpuFastSet`], but it works for any length, and for
+/// halfwords.
+///
+/// ## Safety
+/// * Both pointers must be aligned to the unit size.
+/// * `src` must be readable (one unit when filling) and `dest` must be writable
+///   for the number of units given.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn CpuSet(
+  src: *const core::ffi::c_void, dest: *mut core::ffi::c_void, control: u32,
+) {
+  core::arch::asm! {
+    "swi #0x0B",
+    inout("r0") src => _,
+    inout("r1") dest => _,
+    inout("r2") control => _,
+    out("r3") _,
+    options(preserves_flags),
+  }
+}
+
+/// The `CpuSet` and `CpuFastSet` control bit for filling instead of copying.
+const CPU_SET_FILL: u32 = 1 << 24;
+/// The `CpuSet` control bit for 32-bit units.
+const CPU_SET_32BIT: u32 = 1 << 26;
+/// The largest count that fits in the `CpuSet` and `CpuFastSet` control.
+const CPU_SET_MAX_COUNT: usize = (1 << 21) - 1;
+
+/// Copies words with [`CpuFastSet`].
+///
+/// `CpuFastSet` only works in blocks of 8 words, so if the length isn't a
+/// multiple of 8 the last few words are copied with a loop. Slices of `u32`
+/// are always aligned to 4, which is all the BIOS needs.
+///
+/// ## Panics
+/// * The slices must be the same length, and no more than 2,097,151 elements.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn cpu_fast_copy(src: &[u32], dest: &mut [u32]) {
+  assert_eq!(src.len(), dest.len(), "slices must be the same length");
+  // Any RAM in the GBA is far smaller than the max count, this is only to
+  // keep the control bits from being stomped.
+  assert!(src.len() <= CPU_SET_MAX_COUNT, "too many elements for CpuSet");
+  let blocks = src.len() & !7;
+  if blocks > 0 {
+    unsafe { CpuFastSet(src.as_ptr(), dest.as_mut_ptr(), blocks as u32) };
+  }
+  for (d, s) in dest[blocks..].iter_mut().zip(&src[blocks..]) {
+    unsafe { (d as *mut u32).write_volatile(*s) };
+  }
+}
+
+/// Fills words with [`CpuFastSet`].
+///
+/// Like with [`cpu_fast_copy`], the last few words are written with a loop if
+/// the length isn't a multiple of 8.
+///
+/// ## Panics
+/// * `dest` can't be more than 2,097,151 elements.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn cpu_fast_fill(value: u32, dest: &mut [u32]) {
+  assert!(dest.len() <= CPU_SET_MAX_COUNT, "too many elements for CpuSet");
+  let blocks = dest.len() & !7;
+  if blocks > 0 {
+    unsafe {
+      CpuFastSet(&value, dest.as_mut_ptr(), blocks as u32 | CPU_SET_FILL)
+    };
+  }
+  for d in &mut dest[blocks..] {
+    unsafe { (d as *mut u32).write_volatile(value) };
+  }
+}
+
+/// Copies halfwords with [`CpuSet`].
+///
+/// Slices of `u16` are always aligned to 2, which is all the BIOS needs.
+/// Watch out for copying to SRAM, which can only be written a byte at a time.
+///
+/// ## Panics
+/// * The slices must be the same length, and no more than 2,097,151 elements.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn cpu_copy16(src: &[u16], dest: &mut [u16]) {
+  assert_eq!(src.len(), dest.len(), "slices must be the same length");
+  assert!(src.len() <= CPU_SET_MAX_COUNT, "too many elements for CpuSet");
+  if src.is_empty() {
+    return;
+  }
+  unsafe {
+    CpuSet(src.as_ptr().cast(), dest.as_mut_ptr().cast(), src.len() as u32)
+  };
+}
+
+/// Fills halfwords with [`CpuSet`].
+///
+/// ## Panics
+/// * `dest` can't be more than 2,097,151 elements.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn cpu_fill16(value: u16, dest: &mut [u16]) {
+  assert!(dest.len() <= CPU_SET_MAX_COUNT, "too many elements for CpuSet");
+  if dest.is_empty() {
+    return;
+  }
+  let control = dest.len() as u32 | CPU_SET_FILL;
+  unsafe {
+    CpuSet((&value as *const u16).cast(), dest.as_mut_ptr().cast(), control)
+  };
+}
+
+/// Copies words with [`CpuSet`].
+///
+/// Unlike [`cpu_fast_copy`] this handles any length in the BIOS, but it's
+/// slower for long copies.
+///
+/// ## Panics
+/// * The slices must be the same length, and no more than 2,097,151 elements.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn cpu_copy32(src: &[u32], dest: &mut [u32]) {
+  assert_eq!(src.len(), dest.len(), "slices must be the same length");
+  assert!(src.len() <= CPU_SET_MAX_COUNT, "too many elements for CpuSet");
+  if src.is_empty() {
+    return;
+  }
+  let control = src.len() as u32 | CPU_SET_32BIT;
+  unsafe { CpuSet(src.as_ptr().cast(), dest.as_mut_ptr().cast(), control) };
+}
+
+/// Fills words with [`CpuSet`].
+///
+/// ## Panics
+/// * `dest` can't be more than 2,097,151 elements.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn cpu_fill32(value: u32, dest: &mut [u32]) {
+  assert!(dest.len() <= CPU_SET_MAX_COUNT, "too many elements for CpuSet");
+  if dest.is_empty() {
+    return;
+  }
+  let control = dest.len() as u32 | CPU_SET_FILL | CPU_SET_32BIT;
+  unsafe {
+    CpuSet((&value as *const u32).cast(), dest.as_mut_ptr().cast(), control)
+  };
+}
+
 /// Used to provide info to a call of the [`BitUnPack`] function.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(C)]
@@ -142,6 +854,61 @@ pub struct BitUnpackInfo {
   /// If bit `31` is set then offset value is *also* added to zero elements.
   pub offset_and_touch_zero: u32,
 }
+impl BitUnpackInfo {
+  /// Makes the info for unpacking `src_byte_len` bytes, with no offset.
+  ///
+  /// * **Returns:** `None` unless `src_elem_width` is 1, 2, 4, or 8,
+  ///   `dest_elem_width` is 1, 2, 4, 8, 16, or 32, and the destination width is
+  ///   at least the source width.
+  #[inline]
+  #[must_use]
+  pub const fn new(
+    src_byte_len: u16, src_elem_width: u8, dest_elem_width: u8,
+  ) -> Option<Self> {
+    let src_ok = matches!(src_elem_width, 1 | 2 | 4 | 8);
+    let dest_ok = matches!(dest_elem_width, 1 | 2 | 4 | 8 | 16 | 32);
+    if src_ok && dest_ok && dest_elem_width >= src_elem_width {
+      Some(Self {
+        src_byte_len,
+        src_elem_width,
+        dest_elem_width,
+        offset_and_touch_zero: 0,
+      })
+    } else {
+      None
+    }
+  }
+
+  /// Sets the offset that's added to each element, and if zero elements get
+  /// it too.
+  ///
+  /// Only the low 31 bits of `offset` are used.
+  #[inline]
+  #[must_use]
+  pub const fn with_offset(self, offset: u32, touch_zero: bool) -> Self {
+    let touch_zero = if touch_zero { 1 << 31 } else { 0 };
+    Self { offset_and_touch_zero: (offset & 0x7FFF_FFFF) | touch_zero, ..self }
+  }
+
+  /// If the widths are a combination that [`BitUnPack`] supports.
+  #[inline]
+  #[must_use]
+  pub const fn is_valid(&self) -> bool {
+    Self::new(self.src_byte_len, self.src_elem_width, self.dest_elem_width)
+      .is_some()
+  }
+
+  /// The number of whole words of output that unpacking will write.
+  ///
+  /// Any partial word at the end is dropped by the BIOS.
+  #[inline]
+  #[must_use]
+  pub const fn dest_word_len(&self) -> usize {
+    let src_bits = self.src_byte_len as usize * 8;
+    let elements = src_bits / self.src_elem_width as usize;
+    elements * self.dest_elem_width as usize / 32
+  }
+}
 
 /// `0x10`: Copy data from `src` to `dest` while increasing the bit depth of the
 /// elements copied.
@@ -182,6 +949,25 @@ pub unsafe fn BitUnPack(src: *const u8, dest: *mut u32, info: &BitUnpackInfo) {
   }
 }
 
+/// Bit unpacks `src` into `dest` (see [`BitUnPack`]).
+///
+/// The BIOS reads `info` through a pointer while it works, which is why it's
+/// passed by reference.
+///
+/// ## Panics
+/// * `info` must be valid (see [`BitUnpackInfo::is_valid`]).
+/// * `src` must have at least `info.src_byte_len` bytes.
+/// * `dest` must have at least
+///   [`info.dest_word_len()`](BitUnpackInfo::dest_word_len) words.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn bit_unpack(src: &[u8], dest: &mut [u32], info: &BitUnpackInfo) {
+  assert!(info.is_valid(), "invalid BitUnpackInfo widths");
+  assert!(src.len() >= usize::from(info.src_byte_len), "src is too short");
+  assert!(dest.len() >= info.dest_word_len(), "dest is too short");
+  unsafe { BitUnPack(src.as_ptr(), dest.as_mut_ptr(), info) };
+}
+
 /// `0x11`: Decompress LZ77 data from `src` to `dest` using 8-bit writes.
 ///
 /// * The `src` is the LZ77 header and data, and must start aligned to 4.
@@ -221,7 +1007,7 @@ pub unsafe fn LZ77UnCompReadNormalWrite8bit(src: *const u8, dest: *mut u8) {
 /// * The `src` is the LZ77 header and data, and must start aligned to 4.
 /// * The `dest` pointer is written 16 bits at a time, so it must have align 2.
 ///
-/// See [`LZ77UnCompReadNormalWrite16bit`] for a description of the LZ77 format
+/// See [`LZ77UnCompReadNormalWrite8bit`] for a description of the LZ77 format
 /// used.
 #[inline]
 #[instruction_set(arm::t32)]
@@ -235,6 +1021,141 @@ pub unsafe fn LZ77UnCompReadNormalWrite16bit(src: *const u8, dest: *mut u16) {
   }
 }
 
+/// An error from checking compressed data before decompressing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DecompressError {
+  /// The source is too short to have a header, or the header is for a
+  /// different compression type (or has bad settings for the type).
+  BadHeader,
+  /// The destination is smaller than the decompressed size in the header.
+  DestTooSmall,
+}
+
+/// Views a slice that's been filled in by the BIOS as initialized.
+///
+/// ## Safety
+/// * Every element must have been written.
+#[inline]
+unsafe fn assume_init_slice<T>(s: &mut [MaybeUninit<T>]) -> &mut [T] {
+  unsafe { &mut *(s as *mut [MaybeUninit<T>] as *mut [T]) }
+}
+
+/// Reads a BIOS compression (or difference filter) header, checking the type
+/// (bits 4-7).
+///
+/// Gives the low 4 bits of the header and the decompressed size in bytes. A
+/// size of 0 counts as a bad header, since the BIOS functions don't expect it.
+#[inline]
+fn read_compression_header(
+  src: &[u32], kind: u32,
+) -> Result<(u32, usize), DecompressError> {
+  let header = *src.first().ok_or(DecompressError::BadHeader)?;
+  let len = (header >> 8) as usize;
+  if (header >> 4) & 0xF != kind || len == 0 {
+    return Err(DecompressError::BadHeader);
+  }
+  Ok((header & 0xF, len))
+}
+
+/// Gets the decompressed size in bytes of some LZ77 data.
+///
+/// ## Failure
+/// * If `src` doesn't start with an LZ77 header (with a non-zero size) you get
+///   [`DecompressError::BadHeader`].
+#[inline]
+pub fn lz77_decompressed_len(src: &[u32]) -> Result<usize, DecompressError> {
+  match read_compression_header(src, 1)? {
+    (0, len) => Ok(len),
+    _ => Err(DecompressError::BadHeader),
+  }
+}
+
+/// Decompresses LZ77 data into a buffer in normal memory.
+///
+/// This uses [`LZ77UnCompReadNormalWrite8bit`]. The BIOS writes a byte at a
+/// time, so this can't be used for VRAM (which ignores byte writes). Use
+/// [`lz77_decompress_vram`] for that.
+///
+/// Data from most GBA asset tools (such as `grit`, or `gbalzss`) is in the
+/// right format. Use [`Align4::as_u32_slice`](crate::Align4::as_u32_slice) to
+/// get the `&[u32]` from an included file.
+///
+/// * **Returns:** The number of bytes written (the size from the header).
+///
+/// ## Failure
+/// * The header is checked first, and if it's not LZ77 data, or if its size
+///   doesn't fit in `dest`, you get an error and nothing is written.
+/// * The data after the header can't be checked without decompressing it, and
+///   the BIOS will read past the end of `src` if the data is cut off. Only use
+///   data that you trust.
+#[inline]
+pub fn lz77_decompress(
+  src: &[u32], dest: &mut [u8],
+) -> Result<usize, DecompressError> {
+  let len = lz77_decompressed_len(src)?;
+  if len > dest.len() {
+    return Err(DecompressError::DestTooSmall);
+  }
+  unsafe {
+    LZ77UnCompReadNormalWrite8bit(src.as_ptr().cast(), dest.as_mut_ptr())
+  };
+  Ok(len)
+}
+
+/// Decompresses LZ77 data into new space in an [`Arena`].
+///
+/// This is [`lz77_decompress`], with a destination of exactly the decompressed
+/// size allocated for it. It's for data that's only needed for a while (eg:
+/// while loading a level), and is freed along with the rest of the arena.
+///
+/// ## Failure
+/// * The same as with [`lz77_decompress`], with
+///   [`DecompressError::DestTooSmall`] if there isn't room in the arena. On an
+///   error nothing is allocated.
+#[inline]
+#[allow(clippy::mut_from_ref)]
+pub fn lz77_decompress_in<'a>(
+  src: &[u32], arena: &'a Arena,
+) -> Result<&'a mut [u8], DecompressError> {
+  let len = lz77_decompressed_len(src)?;
+  let dest =
+    arena.alloc_uninit_slice(len).ok_or(DecompressError::DestTooSmall)?;
+  unsafe {
+    LZ77UnCompReadNormalWrite8bit(
+      src.as_ptr().cast(),
+      dest.as_mut_ptr().cast(),
+    );
+    Ok(assume_init_slice(dest))
+  }
+}
+
+/// Decompresses LZ77 data into VRAM (or any other memory that needs 16-bit
+/// writes).
+///
+/// This uses [`LZ77UnCompReadNormalWrite16bit`]. It's otherwise the same as
+/// [`lz77_decompress`], except that when the size is odd the final halfword is
+/// written in full, so `dest` needs room for the size rounded up to even.
+///
+/// ## Failure
+/// * Same as with [`lz77_decompress`].
+///
+/// ## Panics
+/// * `dest` must be aligned to 2.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn lz77_decompress_vram<T>(
+  src: &[u32], dest: VolRegion<T, Safe, Safe>,
+) -> Result<usize, DecompressError> {
+  let len = lz77_decompressed_len(src)?;
+  if (len + 1) & !1 > dest.len() * size_of::<T>() {
+    return Err(DecompressError::DestTooSmall);
+  }
+  let dest = dest.index(0).as_usize() as *mut u16;
+  assert!(dest.is_aligned(), "dest must be aligned to 2");
+  unsafe { LZ77UnCompReadNormalWrite16bit(src.as_ptr().cast(), dest) };
+  Ok(len)
+}
+
 /// `0x13`: Decompress huffman encoded data.
 ///
 /// * `src` points to the header and data (must be aligned to 4).
@@ -272,6 +1193,92 @@ pub unsafe fn HuffUnCompReadNormal(src: *const u8, dest: *mut u32) {
   }
 }
 
+/// Gets the decompressed size in bytes of some Huffman data.
+///
+/// This also checks that the bits per element is 4 or 8, and that `src` is
+/// long enough to hold the whole tree table.
+///
+/// ## Failure
+/// * If `src` doesn't start with a Huffman header that passes the above checks
+///   you get [`DecompressError::BadHeader`].
+#[inline]
+pub fn huff_decompressed_len(src: &[u32]) -> Result<usize, DecompressError> {
+  let (bits, len) = read_compression_header(src, 2)?;
+  if bits != 4 && bits != 8 {
+    return Err(DecompressError::BadHeader);
+  }
+  // The tree size byte comes right after the header, and the tree table
+  // (which includes that byte) then takes up `(tree_size + 1) * 2` bytes.
+  let tree_size = src.get(1).ok_or(DecompressError::BadHeader)? & 0xFF;
+  let tree_bytes = (tree_size as usize + 1) * 2;
+  if src.len() * 4 < 4 + tree_bytes {
+    return Err(DecompressError::BadHeader);
+  }
+  Ok(len)
+}
+
+/// Decompresses Huffman data.
+///
+/// This uses [`HuffUnCompReadNormal`], which writes a word at a time, so
+/// `dest` needs room for the size rounded up to a multiple of 4 (and it works
+/// for VRAM as well as normal memory). Data from `grit` (and other GBA asset
+/// tools) is in the right format.
+///
+/// A few quirks of the format to keep in mind:
+/// * The source must be aligned to 4, which `&[u32]` ensures.
+/// * The tree table size is stored in one byte, so the table is at most 512
+///   bytes. With 8 bits per element a full tree of all 256 values needs 511
+///   nodes, which just fits.
+/// * The compressed bitstream is read in words, and starts at the first word
+///   after the tree table.
+///
+/// * **Returns:** The number of bytes of decompressed data (the size from the
+///   header).
+///
+/// ## Failure
+/// * The header is checked first (see [`huff_decompressed_len`]), and if it's
+///   bad, or if its size doesn't fit in `dest`, you get an error and nothing is
+///   written.
+/// * The tree and bitstream can't be checked without decompressing them, so
+///   corrupt data can make the BIOS read past the end of `src`. Only use data
+///   that you trust.
+#[inline]
+pub fn huff_decompress(
+  src: &[u32], dest: &mut [u32],
+) -> Result<usize, DecompressError> {
+  let len = huff_decompressed_len(src)?;
+  if len.div_ceil(4) > dest.len() {
+    return Err(DecompressError::DestTooSmall);
+  }
+  unsafe { HuffUnCompReadNormal(src.as_ptr().cast(), dest.as_mut_ptr()) };
+  Ok(len)
+}
+
+/// Decompresses Huffman data into new space in an [`Arena`].
+///
+/// This works like [`lz77_decompress_in`], except that (as with
+/// [`huff_decompress`]) the output is words, with the size rounded up to a
+/// multiple of 4. Use [`huff_decompressed_len`] for the size in bytes.
+///
+/// ## Failure
+/// * The same as with [`huff_decompress`], with
+///   [`DecompressError::DestTooSmall`] if there isn't room in the arena. On an
+///   error nothing is allocated.
+#[inline]
+#[allow(clippy::mut_from_ref)]
+pub fn huff_decompress_in<'a>(
+  src: &[u32], arena: &'a Arena,
+) -> Result<&'a mut [u32], DecompressError> {
+  let len = huff_decompressed_len(src)?;
+  let dest = arena
+    .alloc_uninit_slice(len.div_ceil(4))
+    .ok_or(DecompressError::DestTooSmall)?;
+  unsafe {
+    HuffUnCompReadNormal(src.as_ptr().cast(), dest.as_mut_ptr().cast());
+    Ok(assume_init_slice(dest))
+  }
+}
+
 /// `0x14`: Decompress run-length encoded data (8-bit writes).
 ///
 /// * `src` points to the header and data (must be aligned to 4).
@@ -316,3 +1323,528 @@ pub unsafe fn RLUnCompReadNormalWrite16bit(src: *const u8, dest: *mut u16) {
     options(preserves_flags),
   }
 }
+
+/// Gets the decompressed size in bytes of some run-length encoded data.
+///
+/// ## Failure
+/// * If `src` doesn't start with a run-length header (with a non-zero size) you
+///   get [`DecompressError::BadHeader`].
+#[inline]
+pub fn rl_decompressed_len(src: &[u32]) -> Result<usize, DecompressError> {
+  match read_compression_header(src, 3)? {
+    (0, len) => Ok(len),
+    _ => Err(DecompressError::BadHeader),
+  }
+}
+
+/// Decompresses run-length encoded data into a buffer in normal memory.
+///
+/// This uses [`RLUnCompReadNormalWrite8bit`], and works just like
+/// [`lz77_decompress`] (including not being usable for VRAM). Use
+/// [`rl_decompress_vram`] for VRAM.
+///
+/// * **Returns:** The number of bytes written (the size from the header).
+///
+/// ## Failure
+/// * The header is checked first, and if it's not run-length data, or if its
+///   size doesn't fit in `dest`, you get an error and nothing is written.
+/// * The data after the header can't be checked without decompressing it, and
+///   the BIOS will read past the end of `src` if the data is cut off. Only use
+///   data that you trust.
+#[inline]
+pub fn rl_decompress(
+  src: &[u32], dest: &mut [u8],
+) -> Result<usize, DecompressError> {
+  let len = rl_decompressed_len(src)?;
+  if len > dest.len() {
+    return Err(DecompressError::DestTooSmall);
+  }
+  unsafe {
+    RLUnCompReadNormalWrite8bit(src.as_ptr().cast(), dest.as_mut_ptr())
+  };
+  Ok(len)
+}
+
+/// Decompresses run-length encoded data into new space in an [`Arena`].
+///
+/// This works like [`lz77_decompress_in`].
+///
+/// ## Failure
+/// * The same as with [`rl_decompress`], with [`DecompressError::DestTooSmall`]
+///   if there isn't room in the arena. On an error nothing is allocated.
+#[inline]
+#[allow(clippy::mut_from_ref)]
+pub fn rl_decompress_in<'a>(
+  src: &[u32], arena: &'a Arena,
+) -> Result<&'a mut [u8], DecompressError> {
+  let len = rl_decompressed_len(src)?;
+  let dest =
+    arena.alloc_uninit_slice(len).ok_or(DecompressError::DestTooSmall)?;
+  unsafe {
+    RLUnCompReadNormalWrite8bit(src.as_ptr().cast(), dest.as_mut_ptr().cast());
+    Ok(assume_init_slice(dest))
+  }
+}
+
+/// Decompresses run-length encoded data into VRAM (or any other memory that
+/// needs 16-bit writes).
+///
+/// This uses [`RLUnCompReadNormalWrite16bit`], and works just like
+/// [`lz77_decompress_vram`]: when the size is odd the final halfword is written
+/// in full, so `dest` needs room for the size rounded up to even.
+///
+/// ## Failure
+/// * Same as with [`rl_decompress`].
+///
+/// ## Panics
+/// * `dest` must be aligned to 2.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn rl_decompress_vram<T>(
+  src: &[u32], dest: VolRegion<T, Safe, Safe>,
+) -> Result<usize, DecompressError> {
+  let len = rl_decompressed_len(src)?;
+  if (len + 1) & !1 > dest.len() * size_of::<T>() {
+    return Err(DecompressError::DestTooSmall);
+  }
+  let dest = dest.index(0).as_usize() as *mut u16;
+  assert!(dest.is_aligned(), "dest must be aligned to 2");
+  unsafe { RLUnCompReadNormalWrite16bit(src.as_ptr().cast(), dest) };
+  Ok(len)
+}
+
+/// `0x16`: Undoes an 8-bit difference filter (8-bit writes).
+///
+/// * `src` points to the header and data (must be aligned to 4).
+/// * `dest` points to the output buffer.
+///
+/// ## Data Format
+/// * `header` (32bit)
+///   * Bits 0-3: Data unit size in bytes (1 for this function)
+///   * Bits 4-7: magic number `0b1000`
+///   * Bit: 8-31:  Size of unfiltered data in *bytes*
+/// * Data units: the first unit is stored as is, and each unit after that is
+///   stored as the difference from the unit before it (wrapping).
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn Diff8bitUnFilterWram(src: *const u8, dest: *mut u8) {
+  core::arch::asm! {
+    "swi #0x16",
+    inout("r0") src => _,
+    inout("r1") dest => _,
+    out("r3") _,
+    options(preserves_flags),
+  }
+}
+
+/// `0x17`: Undoes an 8-bit difference filter (16-bit writes).
+///
+/// * `src` points to the header and data (must be aligned to 4).
+/// * `dest` points to the output buffer.
+///
+/// ## Data Format
+/// * See [`Diff8bitUnFilterWram`]
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn Diff8bitUnFilterVram(src: *const u8, dest: *mut u16) {
+  core::arch::asm! {
+    "swi #0x17",
+    inout("r0") src => _,
+    inout("r1") dest => _,
+    out("r3") _,
+    options(preserves_flags),
+  }
+}
+
+/// `0x18`: Undoes a 16-bit difference filter.
+///
+/// * `src` points to the header and data (must be aligned to 4).
+/// * `dest` points to the output buffer (must be aligned to 2).
+///
+/// ## Data Format
+/// * Same as [`Diff8bitUnFilterWram`], but the data unit size is 2, and each
+///   unit is a `u16`.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn Diff16bitUnFilter(src: *const u8, dest: *mut u16) {
+  core::arch::asm! {
+    "swi #0x18",
+    inout("r0") src => _,
+    inout("r1") dest => _,
+    out("r3") _,
+    options(preserves_flags),
+  }
+}
+
+/// Gets the unfiltered size in bytes of some 8-bit difference filtered data.
+///
+/// ## Failure
+/// * If `src` doesn't start with a difference filter header for 8-bit units
+///   (with a non-zero size) you get [`DecompressError::BadHeader`].
+#[inline]
+pub fn diff_8bit_unfiltered_len(src: &[u32]) -> Result<usize, DecompressError> {
+  match read_compression_header(src, 8)? {
+    (1, len) => Ok(len),
+    _ => Err(DecompressError::BadHeader),
+  }
+}
+
+/// Gets the unfiltered size in bytes of some 16-bit difference filtered data.
+///
+/// ## Failure
+/// * If `src` doesn't start with a difference filter header for 16-bit units
+///   (with a non-zero, even size) you get [`DecompressError::BadHeader`].
+#[inline]
+pub fn diff_16bit_unfiltered_len(
+  src: &[u32],
+) -> Result<usize, DecompressError> {
+  match read_compression_header(src, 8)? {
+    (2, len) if len & 1 == 0 => Ok(len),
+    _ => Err(DecompressError::BadHeader),
+  }
+}
+
+/// Undoes an 8-bit difference filter into a buffer in normal memory.
+///
+/// This uses [`Diff8bitUnFilterWram`], and works just like
+/// [`lz77_decompress`] (including not being usable for VRAM). Use
+/// [`diff_8bit_unfilter_vram`] for VRAM.
+///
+/// * **Returns:** The number of bytes written (the size from the header).
+///
+/// ## Failure
+/// * The header is checked first, and if it's not 8-bit difference filtered
+///   data, or if its size doesn't fit in `dest`, you get an error and nothing
+///   is written.
+/// * The BIOS will read past the end of `src` if the data is cut off. Only use
+///   data that you trust.
+#[inline]
+pub fn diff_8bit_unfilter_wram(
+  src: &[u32], dest: &mut [u8],
+) -> Result<usize, DecompressError> {
+  let len = diff_8bit_unfiltered_len(src)?;
+  if len > dest.len() {
+    return Err(DecompressError::DestTooSmall);
+  }
+  unsafe { Diff8bitUnFilterWram(src.as_ptr().cast(), dest.as_mut_ptr()) };
+  Ok(len)
+}
+
+/// Undoes an 8-bit difference filter into VRAM (or any other memory that
+/// needs 16-bit writes).
+///
+/// This uses [`Diff8bitUnFilterVram`], and works just like
+/// [`lz77_decompress_vram`]: when the size is odd the final halfword is written
+/// in full, so `dest` needs room for the size rounded up to even.
+///
+/// ## Failure
+/// * Same as with [`diff_8bit_unfilter_wram`].
+///
+/// ## Panics
+/// * `dest` must be aligned to 2.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn diff_8bit_unfilter_vram<T>(
+  src: &[u32], dest: VolRegion<T, Safe, Safe>,
+) -> Result<usize, DecompressError> {
+  let len = diff_8bit_unfiltered_len(src)?;
+  if (len + 1) & !1 > dest.len() * size_of::<T>() {
+    return Err(DecompressError::DestTooSmall);
+  }
+  let dest = dest.index(0).as_usize() as *mut u16;
+  assert!(dest.is_aligned(), "dest must be aligned to 2");
+  unsafe { Diff8bitUnFilterVram(src.as_ptr().cast(), dest) };
+  Ok(len)
+}
+
+/// Undoes a 16-bit difference filter.
+///
+/// This uses [`Diff16bitUnFilter`]. The output is written 16 bits at a time,
+/// so to unfilter straight into VRAM you can call that function directly.
+///
+/// * **Returns:** The number of `u16` values written (half the size from the
+///   header).
+///
+/// ## Failure
+/// * Same as with [`diff_8bit_unfilter_wram`].
+#[inline]
+pub fn diff_16bit_unfilter(
+  src: &[u32], dest: &mut [u16],
+) -> Result<usize, DecompressError> {
+  let len = diff_16bit_unfiltered_len(src)? / 2;
+  if len > dest.len() {
+    return Err(DecompressError::DestTooSmall);
+  }
+  unsafe { Diff16bitUnFilter(src.as_ptr().cast(), dest.as_mut_ptr()) };
+  Ok(len)
+}
+
+/// The header for a sample in the format used by the BIOS sound functions
+/// (and the "Sappy" / `m4a` sound driver).
+///
+/// The sample data (as `i8` values) follows right after the header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct WaveData {
+  /// The kind of sample. 0 is normal 8-bit PCM.
+  pub kind: u16,
+  /// Bit 14 is set if the sample loops.
+  pub stat: u16,
+  /// The sample rate of the sample's base note (middle C, key 60), in Hz
+  /// times 1024.
+  pub freq: u32,
+  /// Where the loop starts, in samples.
+  pub loop_start: u32,
+  /// The length of the sample, in samples.
+  pub size: u32,
+}
+
+/// `0x1F`: Gets the sample rate to play a sample at for a MIDI key.
+///
+/// * `key` is the MIDI key number (60 is middle C).
+/// * `fine` adjusts the pitch up between `key` and the next key, in 1/256ths of
+///   a semitone.
+///
+/// The output is `wave.freq / 2^((180 - key - fine/256) / 12)`. Since `freq`
+/// is in Hz times 1024 (which is `2^(120 / 12)`), that's the sample rate in
+/// Hz.
+///
+/// ## Safety
+/// * `wave` must point to a readable [`WaveData`]. Only the `freq` field is
+///   actually used.
+#[inline]
+#[must_use]
+#[instruction_set(arm::t32)]
+pub unsafe fn MidiKey2Freq(wave: *const WaveData, key: u8, fine: u8) -> u32 {
+  let freq: u32;
+  unsafe {
+    core::arch::asm! {
+      "swi #0x1F",
+      inlateout("r0") wave => freq,
+      inlateout("r1") key as u32 => _,
+      inlateout("r2") fine as u32 => _,
+      out("r3") _,
+      options(pure, readonly, preserves_flags),
+    }
+  };
+  freq
+}
+
+/// Gets the sample rate to play `wave` at for a MIDI key.
+///
+/// This is a safe version of [`MidiKey2Freq`]. For a version that doesn't use
+/// the BIOS or a [`WaveData`], see
+/// [`note_to_sample_rate`](crate::sound::note_to_sample_rate).
+#[inline]
+#[must_use]
+pub fn midi_key_to_freq(wave: &WaveData, key: u8, fine: u8) -> u32 {
+  unsafe { MidiKey2Freq(wave, key, fine) }
+}
+
+/// The clients (children) to send a multiboot program to.
+///
+/// Client `n` is multi-player `n` (1 to 3), and its bit is bit `n`, the same
+/// as in the multiboot handshake replies.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct ClientMask(u8);
+impl ClientMask {
+  pub_const_fn_new_zeroed!();
+  u8_bool_field!(1, client1, with_client1);
+  u8_bool_field!(2, client2, with_client2);
+  u8_bool_field!(3, client3, with_client3);
+
+  /// All three clients.
+  pub const ALL: Self = Self(0b1110);
+
+  /// Makes a mask from bits 1-3 of `bits` (the other bits are ignored).
+  #[inline]
+  #[must_use]
+  pub const fn from_u8(bits: u8) -> Self {
+    Self(bits & Self::ALL.0)
+  }
+
+  /// The mask bits.
+  #[inline]
+  #[must_use]
+  pub const fn to_u8(self) -> u8 {
+    self.0
+  }
+
+  /// If no clients are set.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(self) -> bool {
+    self.0 == 0
+  }
+
+  /// If client `n` (1 to 3) is set.
+  #[inline]
+  #[must_use]
+  pub const fn contains(self, n: usize) -> bool {
+    n >= 1 && n <= 3 && self.0 & (1 << n) != 0
+  }
+}
+
+/// The link mode that [`MultiBoot`] sends with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u32)]
+pub enum MbMode {
+  /// Normal mode, 32-bit, at 256KHz. One client only.
+  Normal256KHz = 0,
+  /// Multi-player mode at 115200 baud. Up to three clients.
+  MultiPlay = 1,
+  /// Normal mode, 32-bit, at 2MHz. One client only.
+  Normal2MHz = 2,
+}
+
+/// Why sending a multiboot program failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MbError {
+  /// The program isn't a usable size (see [`MultiBootParam::new`]), or isn't
+  /// aligned to 4.
+  BadImage,
+  /// This GBA isn't the multi-player parent, so it can't send.
+  NotParent,
+  /// No clients could be found on the link.
+  NoClients,
+  /// These clients didn't answer the handshake (or answered it wrong).
+  ClientsMissing(ClientMask),
+  /// A link transfer failed or timed out partway through.
+  LinkError,
+  /// The BIOS reported that the transfer failed.
+  Failed,
+}
+
+/// The parameter block for [`MultiBoot`], laid out as the BIOS expects.
+///
+/// Most of the fields are work space for the BIOS, and only the public fields
+/// need to be filled in, with the values from the multiboot handshake (see
+/// [`sio::multiboot`](crate::sio::multiboot), which does all of this).
+#[derive(Debug)]
+#[repr(C)]
+pub struct MultiBootParam<'a> {
+  reserved1: [u32; 5],
+  /// The final handshake value: `0x11` plus the sum of all three
+  /// `client_data` values (using `0xFF` for missing clients), truncated to a
+  /// byte.
+  pub handshake_data: u8,
+  padding: u8,
+  handshake_timeout: u16,
+  /// Must be 0.
+  pub probe_count: u8,
+  /// The random byte each client sent during the handshake.
+  pub client_data: [u8; 3],
+  /// The palette setting for the client's boot logo, from the `0x63pp`
+  /// handshake message.
+  pub palette_data: u8,
+  response_bit: u8,
+  /// The clients to send to.
+  pub client_bit: ClientMask,
+  reserved2: u8,
+  boot_srcp: *const u8,
+  boot_endp: *const u8,
+  masterp: *const u8,
+  reserved3: [*const u8; 3],
+  system_work2: [u32; 4],
+  sendflag: u8,
+  probe_target_bit: u8,
+  check_wait: u8,
+  server_type: u8,
+  image: core::marker::PhantomData<&'a [u8]>,
+}
+impl<'a> MultiBootParam<'a> {
+  /// The size of the header at the start of a multiboot program.
+  pub const HEADER_LEN: usize = 0xC0;
+  /// The largest multiboot program, including the header (256 KiB, all of
+  /// EWRAM).
+  pub const MAX_LEN: usize = 0x4_0000;
+
+  /// Makes a parameter block for sending `image`, with all the public fields
+  /// zeroed.
+  ///
+  /// `image` is the whole program, header included. The BIOS only sends the
+  /// part after the header (the header is sent during the handshake).
+  ///
+  /// ## Failure
+  /// * [`MbError::BadImage`] unless `image` is aligned to 4, and the part after
+  ///   the header is `0x100` to `0x3_FF40` bytes and a multiple of 16.
+  #[inline]
+  pub fn new(image: &'a [u8]) -> Result<Self, MbError> {
+    let len = image.len();
+    if !(Self::HEADER_LEN + 0x100..=Self::MAX_LEN).contains(&len)
+      || !(len - Self::HEADER_LEN).is_multiple_of(16)
+      || !(image.as_ptr() as usize).is_multiple_of(4)
+    {
+      return Err(MbError::BadImage);
+    }
+    let range = image.as_ptr_range();
+    Ok(Self {
+      reserved1: [0; 5],
+      handshake_data: 0,
+      padding: 0,
+      handshake_timeout: 0,
+      probe_count: 0,
+      client_data: [0; 3],
+      palette_data: 0,
+      response_bit: 0,
+      client_bit: ClientMask::new(),
+      reserved2: 0,
+      boot_srcp: image[Self::HEADER_LEN..].as_ptr(),
+      boot_endp: range.end,
+      masterp: core::ptr::null(),
+      reserved3: [core::ptr::null(); 3],
+      system_work2: [0; 4],
+      sendflag: 0,
+      probe_target_bit: 0,
+      check_wait: 0,
+      server_type: 0,
+      image: core::marker::PhantomData,
+    })
+  }
+}
+
+/// `0x25`: Sends a multiboot program to the clients.
+///
+/// The handshake with the clients must already be done, and the handshake
+/// results put in `param`. This takes over the serial port and blocks until
+/// the whole program is sent, which takes a while for a large program.
+/// Returns 0 on success and 1 on failure.
+///
+/// ## Safety
+/// * `param` must point to a valid [`MultiBootParam`], and its program must
+///   stay readable for the whole call.
+#[inline]
+#[instruction_set(arm::t32)]
+pub unsafe fn MultiBoot(param: *mut MultiBootParam<'_>, mode: MbMode) -> u32 {
+  let output: u32;
+  unsafe {
+    core::arch::asm! {
+      "swi #0x25",
+      inlateout("r0") param => output,
+      inlateout("r1") mode as u32 => _,
+      out("r2") _,
+      out("r3") _,
+      options(preserves_flags),
+    }
+  };
+  output
+}
+
+/// Sends a multiboot program, using a parameter block that's already filled
+/// in from the handshake.
+///
+/// This is a safe version of [`MultiBoot`].
+///
+/// ## Failure
+/// * [`MbError::Failed`] if the BIOS reports a failure.
+#[inline]
+pub fn multi_boot(
+  param: &mut MultiBootParam<'_>, transfer_mode: MbMode,
+) -> Result<(), MbError> {
+  if unsafe { MultiBoot(param, transfer_mode) } == 0 {
+    Ok(())
+  } else {
+    Err(MbError::Failed)
+  }
+}
@@ -0,0 +1,252 @@
+use core::{
+  fmt::{self, Debug, Display, Write},
+  ops::Deref,
+};
+
+/// A string of up to `N` bytes, stored inline.
+///
+/// This derefs to a `str`. It can be written to with `write!` (it's a
+/// [`fmt::Write`]), and text that doesn't fit is cut off at the last whole
+/// `char` that fits. When that happens the write gives an error, and
+/// [`was_truncated`](Self::was_truncated) is set until the string is cleared,
+/// so that a message that was cut short can still be shown (or logged) with a
+/// marker on the end.
+///
+/// ```no_run
+/// # use gba::collections::ArrayString;
+/// use core::fmt::Write;
+/// let mut text: ArrayString<16> = ArrayString::new();
+/// let score = 1234;
+/// write!(text, "score: {score}").ok();
+/// assert_eq!(text.as_str(), "score: 1234");
+/// ```
+#[derive(Clone, Copy)]
+pub struct ArrayString<const N: usize> {
+  bytes: [u8; N],
+  len: usize,
+  truncated: bool,
+}
+impl<const N: usize> ArrayString<N> {
+  /// Makes an empty string.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { bytes: [0; N], len: 0, truncated: false }
+  }
+
+  /// Makes a string holding a copy of `s`.
+  ///
+  /// ## Failure
+  /// * If `s` is longer than `N` bytes.
+  #[inline]
+  pub const fn try_from_str(s: &str) -> Result<Self, ()> {
+    let mut out = Self::new();
+    match out.push_str(s) {
+      Ok(()) => Ok(out),
+      Err(()) => Err(()),
+    }
+  }
+
+  /// The string, as a `str`.
+  #[inline]
+  #[must_use]
+  pub const fn as_str(&self) -> &str {
+    // Safety: only whole `str`s and `char`s are put in, and the length is only
+    // ever cut at a `char` boundary.
+    unsafe {
+      core::str::from_utf8_unchecked(core::slice::from_raw_parts(
+        self.bytes.as_ptr(),
+        self.len,
+      ))
+    }
+  }
+
+  /// The length, in bytes.
+  #[inline]
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.len
+  }
+
+  /// If the string is empty.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// The most bytes the string can hold (`N`).
+  #[inline]
+  #[must_use]
+  pub const fn capacity(&self) -> usize {
+    N
+  }
+
+  /// How many more bytes there's room for.
+  #[inline]
+  #[must_use]
+  pub const fn remaining_capacity(&self) -> usize {
+    N - self.len
+  }
+
+  /// If any text was cut off since the string was made (or last cleared).
+  #[inline]
+  #[must_use]
+  pub const fn was_truncated(&self) -> bool {
+    self.truncated
+  }
+
+  /// Adds all of `s` to the end.
+  ///
+  /// ## Failure
+  /// * If `s` doesn't fit then nothing is added. This doesn't count as
+  ///   truncating.
+  #[inline]
+  pub const fn push_str(&mut self, s: &str) -> Result<(), ()> {
+    if s.len() > N - self.len {
+      return Err(());
+    }
+    self.copy_in(s.as_bytes(), s.len());
+    Ok(())
+  }
+
+  /// Adds as much of `s` as fits to the end, cutting it off at the last whole
+  /// `char` that fits.
+  ///
+  /// Gives the number of bytes added. If any of `s` was cut off then
+  /// [`was_truncated`](Self::was_truncated) is set.
+  #[inline]
+  pub fn push_str_truncating(&mut self, s: &str) -> usize {
+    let mut count = s.len().min(N - self.len);
+    while !s.is_char_boundary(count) {
+      count -= 1;
+    }
+    if count < s.len() {
+      self.truncated = true;
+    }
+    self.copy_in(s.as_bytes(), count);
+    count
+  }
+
+  /// Adds a `char` to the end.
+  ///
+  /// ## Failure
+  /// * If the `char` doesn't fit it's given back.
+  #[inline]
+  pub fn push(&mut self, c: char) -> Result<(), char> {
+    let mut buf = [0; 4];
+    let s = c.encode_utf8(&mut buf);
+    self.push_str(s).map_err(|()| c)
+  }
+
+  /// Removes the last `char`.
+  #[inline]
+  pub fn pop(&mut self) -> Option<char> {
+    let c = self.as_str().chars().next_back()?;
+    self.len -= c.len_utf8();
+    Some(c)
+  }
+
+  /// Cuts the string down to `len` bytes.
+  ///
+  /// Does nothing if the string is already that short.
+  ///
+  /// ## Panics
+  /// * `len` must be on a `char` boundary.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn truncate(&mut self, len: usize) {
+    if len < self.len {
+      assert!(self.as_str().is_char_boundary(len), "not a char boundary");
+      self.len = len;
+    }
+  }
+
+  /// Empties the string, and clears the truncated flag.
+  #[inline]
+  pub const fn clear(&mut self) {
+    self.len = 0;
+    self.truncated = false;
+  }
+
+  /// Copies the first `count` bytes of `bytes` on to the end. There must be
+  /// room, and `count` must be a `char` boundary.
+  #[inline]
+  const fn copy_in(&mut self, bytes: &[u8], count: usize) {
+    let mut i = 0;
+    while i < count {
+      self.bytes[self.len + i] = bytes[i];
+      i += 1;
+    }
+    self.len += count;
+  }
+}
+impl<const N: usize> Write for ArrayString<N> {
+  /// Adds as much of `s` as fits (see
+  /// [`push_str_truncating`](ArrayString::push_str_truncating)), and gives an
+  /// error if any was cut off.
+  #[inline]
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    if self.push_str_truncating(s) == s.len() {
+      Ok(())
+    } else {
+      Err(fmt::Error)
+    }
+  }
+}
+impl<const N: usize> Default for ArrayString<N> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+impl<const N: usize> Deref for ArrayString<N> {
+  type Target = str;
+  #[inline]
+  fn deref(&self) -> &str {
+    self.as_str()
+  }
+}
+impl<const N: usize> AsRef<str> for ArrayString<N> {
+  #[inline]
+  fn as_ref(&self) -> &str {
+    self.as_str()
+  }
+}
+impl<const N: usize> Debug for ArrayString<N> {
+  #[inline]
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    Debug::fmt(self.as_str(), f)
+  }
+}
+impl<const N: usize> Display for ArrayString<N> {
+  #[inline]
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    Display::fmt(self.as_str(), f)
+  }
+}
+impl<const N: usize> PartialEq for ArrayString<N> {
+  #[inline]
+  fn eq(&self, other: &Self) -> bool {
+    self.as_str() == other.as_str()
+  }
+}
+impl<const N: usize> Eq for ArrayString<N> {}
+impl<const N: usize> PartialEq<str> for ArrayString<N> {
+  #[inline]
+  fn eq(&self, other: &str) -> bool {
+    self.as_str() == other
+  }
+}
+impl<const N: usize> PartialEq<&str> for ArrayString<N> {
+  #[inline]
+  fn eq(&self, other: &&str) -> bool {
+    self.as_str() == *other
+  }
+}
+impl<const N: usize> core::hash::Hash for ArrayString<N> {
+  #[inline]
+  fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+    self.as_str().hash(state)
+  }
+}
@@ -0,0 +1,344 @@
+use core::{
+  fmt::{self, Debug},
+  hash::{Hash, Hasher},
+  mem::MaybeUninit,
+  ops::{Deref, DerefMut},
+  ptr,
+};
+
+/// A list of up to `N` elements, stored inline.
+///
+/// This derefs to a slice of the elements, so all of the slice methods (such
+/// as `iter`, `sort`, and indexing) work on it too.
+pub struct ArrayVec<T, const N: usize> {
+  data: [MaybeUninit<T>; N],
+  len: usize,
+}
+impl<T, const N: usize> ArrayVec<T, N> {
+  /// Makes an empty list.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { data: [const { MaybeUninit::uninit() }; N], len: 0 }
+  }
+
+  /// The number of elements.
+  #[inline]
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.len
+  }
+
+  /// If there are no elements.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// If there's no room for any more elements.
+  #[inline]
+  #[must_use]
+  pub const fn is_full(&self) -> bool {
+    self.len == N
+  }
+
+  /// The most elements the list can hold (`N`).
+  #[inline]
+  #[must_use]
+  pub const fn capacity(&self) -> usize {
+    N
+  }
+
+  /// How many more elements there's room for.
+  #[inline]
+  #[must_use]
+  pub const fn remaining_capacity(&self) -> usize {
+    N - self.len
+  }
+
+  /// The elements, as a slice.
+  #[inline]
+  #[must_use]
+  pub const fn as_slice(&self) -> &[T] {
+    // Safety: the first `len` elements are always initialized.
+    unsafe { core::slice::from_raw_parts(self.data.as_ptr().cast(), self.len) }
+  }
+
+  /// The elements, as a mutable slice.
+  #[inline]
+  #[must_use]
+  pub const fn as_mut_slice(&mut self) -> &mut [T] {
+    // Safety: the first `len` elements are always initialized.
+    unsafe {
+      core::slice::from_raw_parts_mut(self.data.as_mut_ptr().cast(), self.len)
+    }
+  }
+
+  /// Adds an element to the end.
+  ///
+  /// ## Failure
+  /// * If the list is full the element is given back.
+  #[inline]
+  pub const fn push(&mut self, value: T) -> Result<(), T> {
+    if self.len == N {
+      return Err(value);
+    }
+    self.data[self.len] = MaybeUninit::new(value);
+    self.len += 1;
+    Ok(())
+  }
+
+  /// Removes the last element.
+  #[inline]
+  pub const fn pop(&mut self) -> Option<T> {
+    if self.len == 0 {
+      return None;
+    }
+    self.len -= 1;
+    // Safety: this element was initialized, and it's now past the end so it
+    // won't be read (or dropped) again.
+    Some(unsafe { self.data[self.len].assume_init_read() })
+  }
+
+  /// Inserts an element at `index`, moving all the elements after it back by
+  /// one.
+  ///
+  /// ## Failure
+  /// * If the list is full, or `index` is greater than the length, the element
+  ///   is given back.
+  #[inline]
+  pub fn insert(&mut self, index: usize, value: T) -> Result<(), T> {
+    if self.len == N || index > self.len {
+      return Err(value);
+    }
+    // Safety: there's room for one more, so moving `index..len` back by one
+    // stays in bounds, and the element at `index` is then overwritten.
+    unsafe {
+      let p = self.data.as_mut_ptr().add(index);
+      ptr::copy(p, p.add(1), self.len - index);
+      p.write(MaybeUninit::new(value));
+    }
+    self.len += 1;
+    Ok(())
+  }
+
+  /// Removes the element at `index`, moving all the elements after it forward
+  /// by one.
+  ///
+  /// This keeps the order of the elements, but it's slower than
+  /// [`swap_remove`](Self::swap_remove) when there are many elements after
+  /// `index`.
+  #[inline]
+  pub fn remove(&mut self, index: usize) -> Option<T> {
+    if index >= self.len {
+      return None;
+    }
+    // Safety: `index` is in bounds, and the elements after it are moved over
+    // it once it's read out.
+    unsafe {
+      let p = self.data.as_mut_ptr().add(index);
+      let value = p.read().assume_init();
+      ptr::copy(p.add(1), p, self.len - index - 1);
+      self.len -= 1;
+      Some(value)
+    }
+  }
+
+  /// Removes the element at `index`, and moves the last element into its
+  /// place.
+  ///
+  /// This doesn't keep the order of the elements, but it doesn't have to move
+  /// any others either.
+  #[inline]
+  pub fn swap_remove(&mut self, index: usize) -> Option<T> {
+    if index >= self.len {
+      return None;
+    }
+    self.len -= 1;
+    // Safety: both `index` and the old last element are initialized. The last
+    // element is now past the end, so it's moved rather than copied.
+    unsafe {
+      let p = self.data.as_mut_ptr();
+      let value = p.add(index).read().assume_init();
+      if index != self.len {
+        ptr::copy_nonoverlapping(p.add(self.len), p.add(index), 1);
+      }
+      Some(value)
+    }
+  }
+
+  /// Cuts the list down to `len` elements, dropping the rest.
+  ///
+  /// Does nothing if the list is already that short.
+  #[inline]
+  pub fn truncate(&mut self, len: usize) {
+    if len >= self.len {
+      return;
+    }
+    let old_len = self.len;
+    // The length is cut first, so if a drop panics the rest are leaked rather
+    // than dropped twice.
+    self.len = len;
+    // Safety: `len..old_len` were initialized, and are now past the end.
+    unsafe {
+      let tail = ptr::slice_from_raw_parts_mut(
+        self.data.as_mut_ptr().add(len).cast::<T>(),
+        old_len - len,
+      );
+      ptr::drop_in_place(tail);
+    }
+  }
+
+  /// Removes every element.
+  #[inline]
+  pub fn clear(&mut self) {
+    self.truncate(0);
+  }
+
+  /// Keeps only the elements that `keep` gives `true` for, in their order.
+  #[inline]
+  pub fn retain(&mut self, mut keep: impl FnMut(&T) -> bool) {
+    let len = self.len;
+    // Until this is done the list counts as empty, so a panic in `keep` just
+    // leaks the elements.
+    self.len = 0;
+    let p = self.data.as_mut_ptr().cast::<T>();
+    let mut kept = 0;
+    for i in 0..len {
+      // Safety: `i` is below the old length, and everything before `kept` has
+      // been moved into place already.
+      unsafe {
+        let item = p.add(i);
+        if keep(&*item) {
+          if i != kept {
+            ptr::copy_nonoverlapping(item, p.add(kept), 1);
+          }
+          kept += 1;
+        } else {
+          ptr::drop_in_place(item);
+        }
+      }
+    }
+    self.len = kept;
+  }
+
+  /// Adds clones of all the elements of a slice to the end.
+  ///
+  /// ## Failure
+  /// * If they don't all fit, nothing is added.
+  #[inline]
+  pub fn extend_from_slice(&mut self, other: &[T]) -> Result<(), ()>
+  where
+    T: Clone,
+  {
+    if other.len() > self.remaining_capacity() {
+      return Err(());
+    }
+    for item in other {
+      self.data[self.len] = MaybeUninit::new(item.clone());
+      self.len += 1;
+    }
+    Ok(())
+  }
+}
+impl<T, const N: usize> Drop for ArrayVec<T, N> {
+  #[inline]
+  fn drop(&mut self) {
+    self.clear();
+  }
+}
+impl<T, const N: usize> Default for ArrayVec<T, N> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+impl<T, const N: usize> Deref for ArrayVec<T, N> {
+  type Target = [T];
+  #[inline]
+  fn deref(&self) -> &[T] {
+    self.as_slice()
+  }
+}
+impl<T, const N: usize> DerefMut for ArrayVec<T, N> {
+  #[inline]
+  fn deref_mut(&mut self) -> &mut [T] {
+    self.as_mut_slice()
+  }
+}
+impl<T: Clone, const N: usize> Clone for ArrayVec<T, N> {
+  #[inline]
+  fn clone(&self) -> Self {
+    let mut out = Self::new();
+    for item in self.iter() {
+      out.data[out.len] = MaybeUninit::new(item.clone());
+      out.len += 1;
+    }
+    out
+  }
+}
+impl<T: Debug, const N: usize> Debug for ArrayVec<T, N> {
+  #[inline]
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list().entries(self.iter()).finish()
+  }
+}
+impl<T: PartialEq, const N: usize> PartialEq for ArrayVec<T, N> {
+  #[inline]
+  fn eq(&self, other: &Self) -> bool {
+    self.as_slice() == other.as_slice()
+  }
+}
+impl<T: Eq, const N: usize> Eq for ArrayVec<T, N> {}
+impl<T: Hash, const N: usize> Hash for ArrayVec<T, N> {
+  #[inline]
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.as_slice().hash(state)
+  }
+}
+impl<T, const N: usize> Extend<T> for ArrayVec<T, N> {
+  /// Adds every element of the iterator to the end.
+  ///
+  /// ## Panics
+  /// * If the elements don't fit. Elements pushed before running out of room
+  ///   are kept.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+    for item in iter {
+      if self.push(item).is_err() {
+        panic!("ArrayVec is full");
+      }
+    }
+  }
+}
+impl<T, const N: usize> FromIterator<T> for ArrayVec<T, N> {
+  /// Collects an iterator into a list.
+  ///
+  /// ## Panics
+  /// * If there are more than `N` elements.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+    let mut out = Self::new();
+    out.extend(iter);
+    out
+  }
+}
+impl<'a, T, const N: usize> IntoIterator for &'a ArrayVec<T, N> {
+  type Item = &'a T;
+  type IntoIter = core::slice::Iter<'a, T>;
+  #[inline]
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+impl<'a, T, const N: usize> IntoIterator for &'a mut ArrayVec<T, N> {
+  type Item = &'a mut T;
+  type IntoIter = core::slice::IterMut<'a, T>;
+  #[inline]
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter_mut()
+  }
+}
@@ -0,0 +1,43 @@
+//! Collections with a fixed capacity, that don't need an allocator.
+//!
+//! * [`ArrayVec`] is a list, like `Vec`.
+//! * [`ArrayString`] is a text buffer, like `String`, which can be written to
+//!   with `write!`.
+//! * [`RingDeque`] is a queue that can be pushed and popped at both ends, like
+//!   `VecDeque`.
+//!
+//! The capacity is a const generic, and the storage is inline, so each of
+//! these is just an array and a length (it can go in a `static`, or on the
+//! stack, with no heap). The capacity can't ever change, so each method that
+//! adds an element gives an `Err` (usually with the element given back) when
+//! there's no room, rather than panicking. Methods that take an index give
+//! `None` when there's nothing at that index, where the `std` versions would
+//! panic. The few that do panic say so in their docs.
+//!
+//! Elements are only dropped when they're removed (or when the collection is
+//! dropped), and for `Copy` elements none of that costs anything.
+//!
+//! ```no_run
+//! # use gba::collections::ArrayVec;
+//! #[derive(Clone, Copy)]
+//! struct Bullet {
+//!   x: i16,
+//!   y: i16,
+//! }
+//! let mut bullets: ArrayVec<Bullet, 32> = ArrayVec::new();
+//! if bullets.push(Bullet { x: 10, y: 20 }).is_err() {
+//!   // too many bullets on screen, so this one just doesn't fire.
+//! }
+//! for bullet in bullets.iter_mut() {
+//!   bullet.y -= 4;
+//! }
+//! bullets.retain(|b| b.y >= 0);
+//! ```
+
+mod array_string;
+mod array_vec;
+mod ring_deque;
+
+pub use array_string::*;
+pub use array_vec::*;
+pub use ring_deque::*;
@@ -0,0 +1,287 @@
+use core::{
+  fmt::{self, Debug},
+  iter::FusedIterator,
+  mem::MaybeUninit,
+};
+
+/// A queue of up to `N` elements, stored inline, that can be pushed to and
+/// popped from at both ends.
+///
+/// The elements are kept in a ring: pushing and popping at either end never
+/// moves any other elements. Positions wrap around with a compare rather than
+/// a `%`, since the GBA has no divide instruction.
+///
+/// This is a good fit for sharing data with an interrupt handler (eg: bytes
+/// received over serial, pushed by the handler and popped by the main loop),
+/// when it's kept in an [`IrqMutex`](crate::interrupts::IrqMutex).
+pub struct RingDeque<T, const N: usize> {
+  data: [MaybeUninit<T>; N],
+  head: usize,
+  len: usize,
+}
+impl<T, const N: usize> RingDeque<T, N> {
+  /// Makes an empty queue.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { data: [const { MaybeUninit::uninit() }; N], head: 0, len: 0 }
+  }
+
+  /// The number of elements.
+  #[inline]
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.len
+  }
+
+  /// If there are no elements.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// If there's no room for any more elements.
+  #[inline]
+  #[must_use]
+  pub const fn is_full(&self) -> bool {
+    self.len == N
+  }
+
+  /// The most elements the queue can hold (`N`).
+  #[inline]
+  #[must_use]
+  pub const fn capacity(&self) -> usize {
+    N
+  }
+
+  /// The slot of the element `i` places from the front. Both `head` and `i`
+  /// must be less than `N`.
+  #[inline]
+  #[must_use]
+  const fn slot(&self, i: usize) -> usize {
+    let slot = self.head + i;
+    if slot >= N {
+      slot - N
+    } else {
+      slot
+    }
+  }
+
+  /// Adds an element to the back.
+  ///
+  /// ## Failure
+  /// * If the queue is full the element is given back.
+  #[inline]
+  pub const fn push_back(&mut self, value: T) -> Result<(), T> {
+    if self.len == N {
+      return Err(value);
+    }
+    let slot = self.slot(self.len);
+    self.data[slot] = MaybeUninit::new(value);
+    self.len += 1;
+    Ok(())
+  }
+
+  /// Adds an element to the front.
+  ///
+  /// ## Failure
+  /// * If the queue is full the element is given back.
+  #[inline]
+  pub const fn push_front(&mut self, value: T) -> Result<(), T> {
+    if self.len == N {
+      return Err(value);
+    }
+    self.head = if self.head == 0 { N - 1 } else { self.head - 1 };
+    self.data[self.head] = MaybeUninit::new(value);
+    self.len += 1;
+    Ok(())
+  }
+
+  /// Adds an element to the back, removing the front element to make room if
+  /// the queue is full.
+  ///
+  /// Gives the element that was removed, if any. This is for keeping only the
+  /// most recent `N` of something (eg: log lines). If `N` is 0 the element
+  /// itself is given back.
+  #[inline]
+  pub fn push_back_overwrite(&mut self, value: T) -> Option<T> {
+    if N == 0 {
+      return Some(value);
+    }
+    let oldest = if self.len == N { self.pop_front() } else { None };
+    // there's room now, so this can't fail.
+    let _ = self.push_back(value);
+    oldest
+  }
+
+  /// Removes the front element.
+  #[inline]
+  pub const fn pop_front(&mut self) -> Option<T> {
+    if self.len == 0 {
+      return None;
+    }
+    // Safety: the front element is initialized, and it's no longer part of
+    // the queue once `head` moves past it.
+    let value = unsafe { self.data[self.head].assume_init_read() };
+    self.head = self.slot(1);
+    self.len -= 1;
+    Some(value)
+  }
+
+  /// Removes the back element.
+  #[inline]
+  pub const fn pop_back(&mut self) -> Option<T> {
+    if self.len == 0 {
+      return None;
+    }
+    self.len -= 1;
+    let slot = self.slot(self.len);
+    // Safety: the back element is initialized, and it's no longer part of the
+    // queue now that the length is cut.
+    Some(unsafe { self.data[slot].assume_init_read() })
+  }
+
+  /// The element `i` places from the front.
+  #[inline]
+  #[must_use]
+  pub fn get(&self, i: usize) -> Option<&T> {
+    if i >= self.len {
+      return None;
+    }
+    // Safety: every element within the length is initialized.
+    Some(unsafe { self.data[self.slot(i)].assume_init_ref() })
+  }
+
+  /// The element `i` places from the front, mutably.
+  #[inline]
+  #[must_use]
+  pub fn get_mut(&mut self, i: usize) -> Option<&mut T> {
+    if i >= self.len {
+      return None;
+    }
+    let slot = self.slot(i);
+    // Safety: every element within the length is initialized.
+    Some(unsafe { self.data[slot].assume_init_mut() })
+  }
+
+  /// The front element.
+  #[inline]
+  #[must_use]
+  pub fn front(&self) -> Option<&T> {
+    self.get(0)
+  }
+
+  /// The back element.
+  #[inline]
+  #[must_use]
+  pub fn back(&self) -> Option<&T> {
+    self.get(self.len.wrapping_sub(1))
+  }
+
+  /// The elements as two slices, front to back: the second slice continues
+  /// where the first one ends.
+  #[inline]
+  #[must_use]
+  pub fn as_slices(&self) -> (&[T], &[T]) {
+    let first_len = self.len.min(N - self.head);
+    let p = self.data.as_ptr().cast::<T>();
+    // Safety: `head..head + first_len` and `0..len - first_len` are the
+    // initialized parts of the ring, and they don't overlap.
+    unsafe {
+      (
+        core::slice::from_raw_parts(p.add(self.head), first_len),
+        core::slice::from_raw_parts(p, self.len - first_len),
+      )
+    }
+  }
+
+  /// An iterator over the elements, front to back.
+  #[inline]
+  #[must_use]
+  pub fn iter(&self) -> RingDequeIter<'_, T> {
+    let (first, second) = self.as_slices();
+    RingDequeIter { first: first.iter(), second: second.iter() }
+  }
+
+  /// Removes every element.
+  #[inline]
+  pub fn clear(&mut self) {
+    if core::mem::needs_drop::<T>() {
+      while self.pop_front().is_some() {}
+    }
+    self.head = 0;
+    self.len = 0;
+  }
+}
+impl<T, const N: usize> Drop for RingDeque<T, N> {
+  #[inline]
+  fn drop(&mut self) {
+    self.clear();
+  }
+}
+impl<T, const N: usize> Default for RingDeque<T, N> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+impl<T: Clone, const N: usize> Clone for RingDeque<T, N> {
+  #[inline]
+  fn clone(&self) -> Self {
+    let mut out = Self::new();
+    for item in self.iter() {
+      // the same capacity, so everything fits.
+      let _ = out.push_back(item.clone());
+    }
+    out
+  }
+}
+impl<T: Debug, const N: usize> Debug for RingDeque<T, N> {
+  #[inline]
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_list().entries(self.iter()).finish()
+  }
+}
+impl<T: PartialEq, const N: usize> PartialEq for RingDeque<T, N> {
+  #[inline]
+  fn eq(&self, other: &Self) -> bool {
+    self.len == other.len && self.iter().eq(other.iter())
+  }
+}
+impl<T: Eq, const N: usize> Eq for RingDeque<T, N> {}
+impl<'a, T, const N: usize> IntoIterator for &'a RingDeque<T, N> {
+  type Item = &'a T;
+  type IntoIter = RingDequeIter<'a, T>;
+  #[inline]
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+/// An iterator over the elements of a [`RingDeque`], front to back.
+#[derive(Debug, Clone)]
+pub struct RingDequeIter<'a, T> {
+  first: core::slice::Iter<'a, T>,
+  second: core::slice::Iter<'a, T>,
+}
+impl<'a, T> Iterator for RingDequeIter<'a, T> {
+  type Item = &'a T;
+  #[inline]
+  fn next(&mut self) -> Option<&'a T> {
+    self.first.next().or_else(|| self.second.next())
+  }
+  #[inline]
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let len = self.first.len() + self.second.len();
+    (len, Some(len))
+  }
+}
+impl<'a, T> DoubleEndedIterator for RingDequeIter<'a, T> {
+  #[inline]
+  fn next_back(&mut self) -> Option<&'a T> {
+    self.second.next_back().or_else(|| self.first.next_back())
+  }
+}
+impl<T> ExactSizeIterator for RingDequeIter<'_, T> {}
+impl<T> FusedIterator for RingDequeIter<'_, T> {}
@@ -0,0 +1,82 @@
+//! Assertions that report through the emulator log before panicking.
+//!
+//! A plain [`assert!`] failure goes to the panic handler, which (without the
+//! `panic_handler` feature) usually just loops, so nothing says what went
+//! wrong. [`gba_assert!`](crate::gba_assert) and
+//! [`gba_debug_assert!`](crate::gba_debug_assert) log the message and where
+//! the assertion is at the `Fatal` level first, which halts mGBA (so a CI run
+//! stops there instead of hanging). Outside of mGBA (including on hardware),
+//! they go on to panic like [`assert!`].
+//!
+//! When the condition holds, the only cost is checking it: the message is
+//! only formatted in the failing branch, which is a call to a cold function.
+//!
+//! During a test with the `test_runner` module (with the `test_runner`
+//! feature), a failure is logged and panics without halting the emulator, so
+//! it fails just that test with the message, and the other tests still run.
+
+use crate::{debug_log::debug_log, mgba::MgbaMessageLevel};
+use core::{fmt::Arguments, panic::Location};
+
+/// Asserts that a condition is true, logging at the `Fatal` level and then
+/// panicking if it's not.
+///
+/// This takes the same arguments as [`assert!`]: the condition, and then an
+/// optional message like [`format_args!`]. See the [module
+/// docs](crate::debug::assert) for what a failure does.
+///
+/// ```no_run
+/// # let (index, len) = (2, 3);
+/// gba::gba_assert!(index < len, "index {index} is past the end ({len})");
+/// ```
+#[macro_export]
+macro_rules! gba_assert {
+  ($cond:expr $(,)?) => {
+    if !$cond {
+      $crate::debug::assert::assert_failed(
+        ::core::panic::Location::caller(),
+        ::core::format_args!(
+          "assertion failed: {}",
+          ::core::stringify!($cond)
+        ),
+      )
+    }
+  };
+  ($cond:expr, $($arg:tt)+) => {
+    if !$cond {
+      $crate::debug::assert::assert_failed(
+        ::core::panic::Location::caller(),
+        ::core::format_args!($($arg)+),
+      )
+    }
+  };
+}
+
+/// Like [`gba_assert!`](crate::gba_assert), but only with debug assertions
+/// on.
+///
+/// Like [`debug_assert!`], in a build without debug assertions (such as a
+/// normal release build) nothing is checked, and the condition isn't even
+/// evaluated.
+#[macro_export]
+macro_rules! gba_debug_assert {
+  ($($arg:tt)*) => {
+    if ::core::cfg!(debug_assertions) {
+      $crate::gba_assert!($($arg)*);
+    }
+  };
+}
+
+/// The failing branch of [`gba_assert!`](crate::gba_assert).
+#[doc(hidden)]
+#[cold]
+#[inline(never)]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn assert_failed(location: &Location<'_>, args: Arguments<'_>) -> ! {
+  #[cfg(feature = "test_runner")]
+  if crate::test_runner::in_test() {
+    panic!("{args}");
+  }
+  debug_log(MgbaMessageLevel::Fatal, format_args!("{location}: {args}"));
+  panic!("{args}")
+}
@@ -0,0 +1,12 @@
+//! Tools for finding problems while developing a game.
+//!
+//! * [`assert`](mod@assert): Assertions that log where they failed before
+//!   panicking, with [`gba_assert!`](crate::gba_assert) and
+//!   [`gba_debug_assert!`](crate::gba_debug_assert).
+//! * [`stack`]: How much of the IWRAM the stack uses, and a check for when it
+//!   runs into the statics.
+//!
+//! For logging, see [`mgba`](crate::mgba) and [`nocash`](crate::nocash).
+
+pub mod assert;
+pub mod stack;
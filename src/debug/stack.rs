@@ -0,0 +1,165 @@
+//! Measuring the stack, and catching it when it overflows.
+//!
+//! The IWRAM is 32 KiB, and it holds the IWRAM statics and code (`.data` and
+//! `.bss`, placed from the bottom up) and the stacks. The BIOS puts the user
+//! stack at `0x0300_7F00` (the IRQ and supervisor stacks are above that), and
+//! it grows down towards the statics. Nothing stops it when it gets there: it
+//! just starts overwriting statics, which breaks things in ways that are very
+//! hard to trace back to the stack.
+//!
+//! [`init`] paints all of the free space below the stack with
+//! [`STACK_CANARY`]. After that:
+//! * [`stack_high_water`] finds the lowest word that isn't the canary any more,
+//!   which is the most stack that's been used since `init`.
+//! * [`check_stack_canary`] checks the [`GUARD_BYTES`] right above the statics.
+//!   If the stack has gotten into them, it's about to overflow (or already
+//!   has).
+//! * [`check_stack`] is the same check, but panics. Calling it once a frame,
+//!   such as from the vblank handler in debug builds, catches an overflow close
+//!   to when it happens.
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! use gba::debug::stack;
+//! extern "C" fn on_vblank(_: IrqBits) {
+//!   if cfg!(debug_assertions) {
+//!     stack::check_stack();
+//!   }
+//! }
+//! stack::init();
+//! // ...
+//! let used = stack::stack_high_water();
+//! ```
+//!
+//! A value on the stack can happen to equal the canary, in which case the high
+//! water mark is a little low, but that's very unlikely to matter.
+
+use crate::interrupts::irq_free;
+
+/// The top of the user stack, where the BIOS sets `sp` to.
+pub const STACK_TOP: usize = 0x0300_7F00;
+
+/// The start of the IWRAM.
+const IWRAM_START: usize = 0x0300_0000;
+
+/// What [`init`] fills the free stack space with.
+pub const STACK_CANARY: u32 = 0x5AC3_A53C;
+
+/// How much of the space right above the statics [`check_stack_canary`]
+/// checks.
+///
+/// A stack frame doesn't write every word of itself, so checking more than one
+/// word catches the stack even if it skips over part of the guard.
+pub const GUARD_BYTES: usize = 256;
+
+/// How much space is left below the stack pointer when painting, so that
+/// [`init`] doesn't paint over its own stack frame.
+const PAINT_MARGIN: usize = 64;
+
+extern "C" {
+  /// Set by the linker script at the end of the `.bss`, which is the end of the
+  /// IWRAM statics.
+  static __bss_end: u8;
+}
+
+/// The end of the IWRAM statics (and code), where the free space starts.
+#[inline]
+#[must_use]
+pub fn statics_end() -> usize {
+  core::ptr::addr_of!(__bss_end) as usize
+}
+
+/// How many bytes of the IWRAM the statics and code use.
+#[inline]
+#[must_use]
+pub fn iwram_static_bytes() -> usize {
+  statics_end() - IWRAM_START
+}
+
+/// How many bytes the stack can grow to before it reaches the statics.
+#[inline]
+#[must_use]
+pub fn stack_capacity() -> usize {
+  STACK_TOP.saturating_sub(statics_end())
+}
+
+/// The current stack pointer.
+#[inline]
+#[must_use]
+pub fn stack_pointer() -> usize {
+  let sp: usize;
+  // Safety: this just reads `sp`.
+  unsafe {
+    core::arch::asm!("mov {}, sp", out(reg) sp, options(nomem, nostack));
+  }
+  sp
+}
+
+/// Paints the free space between the statics and the stack pointer with
+/// [`STACK_CANARY`].
+///
+/// Call this early in `main`, before the stack has gotten deep. It can be
+/// called again later to reset [`stack_high_water`]. Interrupts are off while
+/// it paints, since an interrupt handler uses the same stack.
+#[inline(never)]
+pub fn init() {
+  irq_free(|| {
+    let end = (stack_pointer() - PAINT_MARGIN) & !3;
+    let mut addr = (statics_end() + 3) & !3;
+    while addr < end {
+      // Safety: this is free space below the stack that nothing is using,
+      // and with interrupts off nothing can start using it.
+      unsafe { (addr as *mut u32).write_volatile(STACK_CANARY) };
+      addr += 4;
+    }
+  });
+}
+
+/// The most bytes of stack that have been used since [`init`].
+///
+/// This scans up from the statics for the first word that isn't the canary,
+/// so it takes a little while (up to a few thousand reads). Without `init`,
+/// the space was never painted, so this is just [`stack_capacity`].
+#[inline]
+#[must_use]
+pub fn stack_high_water() -> usize {
+  let mut addr = (statics_end() + 3) & !3;
+  while addr < STACK_TOP {
+    // Safety: this is in the IWRAM, and reading it is fine.
+    if unsafe { (addr as *const u32).read_volatile() } != STACK_CANARY {
+      break;
+    }
+    addr += 4;
+  }
+  STACK_TOP - addr
+}
+
+/// If the [`GUARD_BYTES`] right above the statics are all still the canary.
+///
+/// `false` means the stack has grown all the way down to the statics (or
+/// [`init`] wasn't called).
+#[inline]
+#[must_use]
+pub fn check_stack_canary() -> bool {
+  let start = (statics_end() + 3) & !3;
+  (start..start + GUARD_BYTES).step_by(4).all(|addr| {
+    // Safety: this is in the IWRAM, and reading it is fine.
+    unsafe { (addr as *const u32).read_volatile() == STACK_CANARY }
+  })
+}
+
+/// Panics if [`check_stack_canary`] is `false`.
+///
+/// ## Panics
+/// * If the stack has gotten into the guard space above the statics.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn check_stack() {
+  if !check_stack_canary() {
+    panic!(
+      "stack overflow: the stack got within {GUARD_BYTES} bytes of the IWRAM \
+       statics (which end at {:#010X})",
+      statics_end()
+    );
+  }
+}
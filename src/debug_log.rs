@@ -0,0 +1,91 @@
+//! Picks where the `mgba_*` logging macros send their messages.
+//!
+//! The macros (such as [`mgba_info!`](crate::mgba_info)) work with two
+//! emulators: mGBA (see [`mgba`](crate::mgba)) and no$gba (see
+//! [`nocash`](crate::nocash)). Normally they use whichever one the game is
+//! running in, as found by [`environment`](crate::environment).
+//! [`set_debug_backend`] picks one explicitly instead, and then the macros only
+//! use that one.
+//!
+//! When the picked emulator isn't there (such as on real hardware) the macros
+//! do nothing.
+
+use crate::{
+  environment::{is_mgba, is_nocash},
+  gba_cell::GbaCell,
+  mgba::{mgba_logging_available, MgbaBufferedLogger, MgbaMessageLevel},
+  nocash::{nocash_available, NocashLogger},
+};
+use core::fmt::{Arguments, Write};
+
+/// Where debug messages go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DebugBackend {
+  /// mGBA's debug output, which keeps the message level.
+  Mgba,
+  /// No$gba's debug messages, where the level is put at the start of the
+  /// message.
+  Nocash,
+}
+
+/// The picked backend: 0 for none, 1 for mGBA, and 2 for no$gba.
+static OVERRIDE: GbaCell<u8> = GbaCell::new(0);
+
+/// Picks the backend for the logging macros, or `None` to go back to using
+/// whichever emulator is there.
+#[inline]
+pub fn set_debug_backend(backend: Option<DebugBackend>) {
+  OVERRIDE.write(match backend {
+    None => 0,
+    Some(DebugBackend::Mgba) => 1,
+    Some(DebugBackend::Nocash) => 2,
+  });
+}
+
+/// The backend that the logging macros use right now.
+///
+/// This is the one from [`set_debug_backend`] if there is one, and otherwise
+/// the emulator that's there. `None` means the messages go nowhere.
+#[inline]
+#[must_use]
+pub fn debug_backend() -> Option<DebugBackend> {
+  let backend = match OVERRIDE.read() {
+    1 => DebugBackend::Mgba,
+    2 => DebugBackend::Nocash,
+    _ if is_mgba() => return Some(DebugBackend::Mgba),
+    _ if is_nocash() => return Some(DebugBackend::Nocash),
+    _ => return None,
+  };
+  let available = match backend {
+    DebugBackend::Mgba => mgba_logging_available(),
+    DebugBackend::Nocash => nocash_available(),
+  };
+  available.then_some(backend)
+}
+
+/// Sends one message (and a newline) to the [`debug_backend`].
+///
+/// This is what the logging macros call.
+#[inline]
+pub fn debug_log(level: MgbaMessageLevel, args: Arguments<'_>) {
+  match debug_backend() {
+    Some(DebugBackend::Mgba) => {
+      if let Ok(mut logger) = MgbaBufferedLogger::try_new(level) {
+        writeln!(logger, "{args}").ok();
+      }
+    }
+    Some(DebugBackend::Nocash) => {
+      if let Ok(mut logger) = NocashLogger::try_new() {
+        let prefix = match level {
+          MgbaMessageLevel::Fatal => "FATAL",
+          MgbaMessageLevel::Error => "ERROR",
+          MgbaMessageLevel::Warning => "WARN",
+          MgbaMessageLevel::Info => "INFO",
+          MgbaMessageLevel::Debug => "DEBUG",
+        };
+        writeln!(logger, "{prefix}: {args}").ok();
+      }
+    }
+    None => (),
+  }
+}
@@ -41,7 +41,20 @@
 //!
 //! In the future the situation may improve.
 
-use crate::macros::{pub_const_fn_new_zeroed, u16_bool_field, u16_enum_field};
+use crate::{
+  gba_cell::GbaCell,
+  interrupts::{
+    clear_handler, irq_free, set_handler, Interrupt, IrqBits, IrqFn,
+  },
+  macros::{pub_const_fn_new_zeroed, register_enum_field, u16_bool_field},
+  mmio::*,
+  video::DisplayStatus,
+};
+use core::{
+  ffi::c_void,
+  sync::atomic::{compiler_fence, Ordering},
+};
+use voladdress::{Safe, Unsafe, VolAddress};
 
 /// Sets the change in destination address after each transfer.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -115,19 +128,20 @@ pub enum DmaStartTime {
 pub struct DmaControl(u16);
 impl DmaControl {
   pub_const_fn_new_zeroed!();
-  u16_enum_field!(
-    5 - 6: DestAddrControl,
-    dest_addr_control,
-    with_dest_addr_control
+  register_enum_field!(
+    5 - 6: DestAddrControl [Increment, Decrement, Fixed, IncReload],
+    dest_addr_control, with_dest_addr_control, DEST_ADDR_CONTROL_MASK
   );
-  u16_enum_field!(
-    7 - 8: SrcAddrControl,
-    src_addr_control,
-    with_src_addr_control
+  register_enum_field!(
+    7 - 8: SrcAddrControl [Increment, Decrement, Fixed],
+    src_addr_control, with_src_addr_control, SRC_ADDR_CONTROL_MASK
   );
   u16_bool_field!(9, repeat, with_repeat);
   u16_bool_field!(10, transfer_32bit, with_transfer_32bit);
-  u16_enum_field!(12 - 13: DmaStartTime, start_time, with_start_time);
+  register_enum_field!(
+    12 - 13: DmaStartTime [Immediate, VBlank, HBlank, Special],
+    start_time, with_start_time, START_TIME_MASK
+  );
   u16_bool_field!(14, irq_after, with_irq_after);
   u16_bool_field!(15, enabled, with_enabled);
 
@@ -162,3 +176,598 @@ unsafe fn stm_dma(
     options(nostack, preserves_flags)
   );
 }
+
+/// The MMIO controls of a single DMA unit.
+pub(crate) struct DmaRegisters {
+  pub(crate) src: VolAddress<*const c_void, (), Unsafe>,
+  pub(crate) dest: VolAddress<*mut c_void, (), Unsafe>,
+  pub(crate) count: VolAddress<u16, (), Unsafe>,
+  pub(crate) control: VolAddress<DmaControl, Safe, Unsafe>,
+}
+impl DmaRegisters {
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub(crate) const fn of(channel: usize) -> Self {
+    assert!(channel < 4, "DMA channel out of range");
+    Self {
+      src: DMA_SRC.index(channel),
+      dest: DMA_DEST.index(channel),
+      count: DMA_COUNT.index(channel),
+      control: DMA_CONTROL.index(channel),
+    }
+  }
+}
+
+/// One of the four DMA units.
+///
+/// The units differ in what they can access and how much they can move per
+/// transfer (see the [module](self) notes), and [`start`](Self::start)
+/// checks for these limits at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+#[allow(missing_docs)]
+pub enum DmaChannel {
+  Dma0 = 0,
+  Dma1 = 1,
+  Dma2 = 2,
+  Dma3 = 3,
+}
+impl DmaChannel {
+  /// The channel's index (`0..4`).
+  #[inline]
+  #[must_use]
+  pub const fn index(self) -> usize {
+    self as usize
+  }
+
+  /// The most elements a single transfer of this channel can move.
+  ///
+  /// This is `0x4000` for DMA 0, 1 and 2, and `0x1_0000` for DMA 3.
+  #[inline]
+  #[must_use]
+  pub const fn max_count(self) -> usize {
+    match self {
+      DmaChannel::Dma3 => 0x1_0000,
+      _ => 0x4000,
+    }
+  }
+
+  /// Programs and enables the channel.
+  ///
+  /// The channel is disabled first, so this can also reprogram a channel
+  /// that's already running. The `control` is used as given except that the
+  /// `enabled` bit is always set. With an `Immediate` start time the CPU is
+  /// paused until the transfer is done, otherwise the transfer happens later
+  /// at the start time.
+  ///
+  /// ## Panics
+  /// * `count` must be in `1..=self.max_count()`.
+  /// * DMA 0 can only read from internal memory (below `0x0800_0000`).
+  /// * DMA 0, 1, and 2 can only write to internal memory.
+  ///
+  /// ## Safety
+  /// * `src` must be readable and `dest` must be writable for `count` elements,
+  ///   both aligned to the element size, for as long as the transfer (or
+  ///   repeated transfers) might run.
+  /// * See the module notes about altering memory that Rust uses.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub unsafe fn start(
+    self, src: *const c_void, dest: *mut c_void, count: usize,
+    control: DmaControl,
+  ) {
+    const GAMEPAK: usize = 0x0800_0000;
+    assert!(count > 0 && count <= self.max_count(), "invalid DMA count");
+    if self == DmaChannel::Dma0 {
+      assert!((src as usize) < GAMEPAK, "DMA0 can't read from the gamepak");
+    }
+    if self != DmaChannel::Dma3 {
+      assert!((dest as usize) < GAMEPAK, "only DMA3 can write to the gamepak");
+    }
+    let regs = DmaRegisters::of(self.index());
+    unsafe {
+      regs.control.write(DmaControl::new());
+      regs.src.write(src);
+      regs.dest.write(dest);
+      // the count register is 14 or 16 bits, and the max count is written as 0
+      regs.count.write((count & (self.max_count() - 1)) as u16);
+      regs.control.write(control.with_enabled(true));
+    }
+  }
+
+  /// Stops the channel.
+  ///
+  /// The whole control register is written in a single halfword write, so a
+  /// repeating transfer will either run again or not, it won't be left half
+  /// configured. If a transfer was already in progress it finishes first,
+  /// since the CPU isn't running during a transfer.
+  #[inline]
+  pub fn stop(self) {
+    let regs = DmaRegisters::of(self.index());
+    unsafe { regs.control.write(DmaControl::new()) };
+  }
+
+  /// If the channel is enabled.
+  ///
+  /// A channel with `repeat` set stays enabled until stopped, otherwise this
+  /// becomes `false` once the transfer is done.
+  #[inline]
+  #[must_use]
+  pub fn is_active(self) -> bool {
+    DmaRegisters::of(self.index()).control.read().enabled()
+  }
+
+  /// Sets the function called when this channel's transfer-complete
+  /// interrupt fires.
+  ///
+  /// This is the channel's entry of the
+  /// [handler table](crate::interrupts::set_handler), so it's called when
+  /// [`RUST_IRQ_HANDLER`](crate::RUST_IRQ_HANDLER) is
+  /// [`irq_table_dispatch`](crate::interrupts::irq_table_dispatch). It also
+  /// turns the channel's bit in [`IE`] on (or off, for `None`). The interrupt
+  /// only fires for transfers that have `irq_after` set in their control.
+  ///
+  /// The assembly runtime acknowledges the interrupt in [`IF`] *before* any
+  /// Rust handler runs, so a handler can safely start another transfer with
+  /// `irq_after` set: if that transfer finishes while the handler is still
+  /// running, its interrupt will be flagged again and handled next.
+  #[inline]
+  pub fn set_irq_handler(self, handler: Option<IrqFn>) {
+    let source = match self {
+      DmaChannel::Dma0 => Interrupt::Dma0,
+      DmaChannel::Dma1 => Interrupt::Dma1,
+      DmaChannel::Dma2 => Interrupt::Dma2,
+      DmaChannel::Dma3 => Interrupt::Dma3,
+    };
+    match handler {
+      Some(handler) => set_handler(source, handler),
+      None => clear_handler(source),
+    }
+  }
+}
+
+/// Starts a DMA3 copy that sends an interrupt when it's done.
+///
+/// This doesn't wait for the transfer, and the DMA3 handler set with
+/// [`DmaChannel::set_irq_handler`] runs once it's complete. Use
+/// [`dma3_is_busy`] to poll instead.
+///
+/// Note that the CPU is still paused while the DMA runs (so this won't
+/// actually return until the copy is over). What this gives you is that the
+/// follow up work (such as starting the next copy of a chain) happens in the
+/// interrupt handler, instead of your code needing to wait for it.
+///
+/// ## Panics
+/// * `src` must not be empty, or more than 65,536 elements.
+/// * `dest` must be aligned to 4.
+///
+/// ## Safety
+/// * `src` must stay readable and `dest` must be writable for `src.len()` words
+///   until the transfer is done.
+/// * The regions must not overlap.
+/// * See the module notes about altering memory that Rust uses.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub unsafe fn dma3_copy_async(src: &[u32], dest: *mut u32) {
+  assert!(dest.is_aligned(), "destination must be aligned to 4");
+  let control =
+    DmaControl::new().with_transfer_32bit(true).with_irq_after(true);
+  unsafe {
+    DmaChannel::Dma3.start(src.as_ptr().cast(), dest.cast(), src.len(), control)
+  };
+}
+
+/// If DMA3 is currently enabled.
+#[inline]
+#[must_use]
+pub fn dma3_is_busy() -> bool {
+  DmaChannel::Dma3.is_active()
+}
+
+/// Table address of the effect on each DMA unit, or 0 for no effect.
+static HBLANK_EFFECT_SRC: [GbaCell<u32>; 4] =
+  [GbaCell::new(0), GbaCell::new(0), GbaCell::new(0), GbaCell::new(0)];
+/// Destination address of the effect on each DMA unit.
+static HBLANK_EFFECT_DEST: [GbaCell<u32>; 4] =
+  [GbaCell::new(0), GbaCell::new(0), GbaCell::new(0), GbaCell::new(0)];
+/// If the effect on each DMA unit uses 32-bit elements.
+static HBLANK_EFFECT_32BIT: [GbaCell<bool>; 4] = [
+  GbaCell::new(false),
+  GbaCell::new(false),
+  GbaCell::new(false),
+  GbaCell::new(false),
+];
+
+/// A per-scanline effect, where a DMA unit writes one table entry to a
+/// register during each hblank.
+///
+/// This is how "wavy" backgrounds (a table of `BGxHOFS` values), palette
+/// gradients (a table of colors for one palette entry), and similar effects
+/// are done.
+///
+/// ## Timing
+///
+/// A value written during a line's hblank affects the *next* line. So each
+/// vblank the effect writes `table[0]` to the destination directly (for line
+/// 0), and then restarts the DMA unit at `table[1]`. This restart is what
+/// keeps the table lined up with the screen, and it's done by
+/// [`restart_hblank_effects`], which runs from the vblank interrupt:
+/// * Starting an effect sets it as the [`Interrupt::VBlank`] entry of the
+///   [handler table](crate::interrupts::set_handler), and turns on the vblank
+///   interrupt in [`DISPSTAT`] and [`IE`].
+/// * [`RUST_IRQ_HANDLER`](crate::RUST_IRQ_HANDLER) must be
+///   [`irq_table_dispatch`](crate::interrupts::irq_table_dispatch), and you
+///   still need to enable [`IME`].
+/// * To run other code each vblank too, set your own vblank handler after
+///   starting the effect, and call [`restart_hblank_effects`] from it.
+///
+/// The effect begins at the first vblank after it's started. The DMA also
+/// fires during the hblank of line 159, which reads the element just past the
+/// end of the table, but that write happens during vblank and has no visible
+/// effect.
+///
+/// ## DMA Units
+///
+/// DMA 0 can't read from ROM, so use DMA 1, 2, or 3 if the table is in ROM.
+/// The effect's DMA unit shouldn't be used for anything else while the effect
+/// is running.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct HblankEffect {
+  channel: usize,
+}
+impl HblankEffect {
+  /// Starts an effect on the DMA unit given.
+  ///
+  /// The table's element type must be 2 or 4 bytes, and the transfer size
+  /// is picked to match. Use a 4 byte element type to set register pairs
+  /// (eg: both `BG0HOFS` and `BG0VOFS` at once).
+  ///
+  /// ## Panics
+  /// * The channel must be in `0..4`.
+  /// * The element type must have a size of 2 or 4.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn start<T, R>(
+    channel: usize, table: &'static [T; 160], dest: VolAddress<T, R, Safe>,
+  ) -> Self {
+    let size = core::mem::size_of::<T>();
+    assert!(size == 2 || size == 4, "element size must be 2 or 4 bytes");
+    let regs = DmaRegisters::of(channel);
+    unsafe { regs.control.write(DmaControl::new()) };
+    HBLANK_EFFECT_SRC[channel].write(table.as_ptr() as u32);
+    HBLANK_EFFECT_DEST[channel].write(dest.as_usize() as u32);
+    HBLANK_EFFECT_32BIT[channel].write(size == 4);
+    irq_free(|| DISPSTAT.apply(|s| *s = s.with_irq_vblank(true)));
+    set_handler(Interrupt::VBlank, restart_hblank_effects);
+    Self { channel }
+  }
+
+  /// The DMA unit this effect uses.
+  #[inline]
+  #[must_use]
+  pub const fn channel(&self) -> usize {
+    self.channel
+  }
+
+  /// Changes the table used, starting from the next vblank.
+  ///
+  /// This is the easy way to animate an effect: have one long table and pass
+  /// a different 160 element window of it each frame.
+  #[inline]
+  pub fn set_table<T>(&mut self, table: &'static [T; 160]) {
+    HBLANK_EFFECT_SRC[self.channel].write(table.as_ptr() as u32);
+  }
+
+  /// Stops the effect, disabling the DMA unit.
+  ///
+  /// This leaves the vblank interrupt enabled, since other code might be
+  /// using it.
+  #[inline]
+  pub fn stop(self) {
+    HBLANK_EFFECT_SRC[self.channel].write(0);
+    let regs = DmaRegisters::of(self.channel);
+    unsafe { regs.control.write(DmaControl::new()) };
+  }
+}
+
+/// The vblank handler that restarts all [`HblankEffect`]s.
+///
+/// [`HblankEffect::start`] sets this as the vblank handler, so it only needs
+/// calling directly from a vblank handler of your own. It does nothing unless
+/// `bits` has the `vblank` bit set.
+#[inline]
+pub extern "C" fn restart_hblank_effects(bits: IrqBits) {
+  if !bits.vblank() {
+    return;
+  }
+  for channel in 0..4 {
+    let table = HBLANK_EFFECT_SRC[channel].read();
+    if table == 0 {
+      continue;
+    }
+    let dest = HBLANK_EFFECT_DEST[channel].read();
+    let transfer_32bit = HBLANK_EFFECT_32BIT[channel].read();
+    let regs = DmaRegisters::of(channel);
+    unsafe {
+      regs.control.write(DmaControl::new());
+      // Line 0 is set directly, the DMA handles lines 1 through 159.
+      let step = if transfer_32bit {
+        (dest as *mut u32).write_volatile((table as *const u32).read());
+        4
+      } else {
+        (dest as *mut u16).write_volatile((table as *const u16).read());
+        2
+      };
+      regs.src.write((table + step) as *const c_void);
+      regs.dest.write(dest as *mut c_void);
+      regs.count.write(1);
+      regs.control.write(
+        DmaControl::new()
+          .with_dest_addr_control(DestAddrControl::Fixed)
+          .with_src_addr_control(SrcAddrControl::Increment)
+          .with_repeat(true)
+          .with_transfer_32bit(transfer_32bit)
+          .with_start_time(DmaStartTime::HBlank)
+          .with_enabled(true),
+      );
+    }
+  }
+}
+
+/// Runs an immediate DMA3 transfer, and waits for it to finish.
+///
+/// The `control` gives the element size and address controls, it's used with
+/// the `enabled` bit set and an immediate start time. The CPU is paused while
+/// the DMA runs, so the wait loop is just a formality that covers the 2 cycle
+/// start delay.
+///
+/// ## Safety
+/// * `src` must be readable and `dest` writable for `count` elements (of 2 or 4
+///   bytes, depending on `transfer_32bit`), and both aligned to that size.
+/// * `count` must not be 0, which the hardware treats as 65,536.
+#[inline]
+pub(crate) unsafe fn dma3_run(
+  src: *const c_void, dest: *mut c_void, count: u16, control: DmaControl,
+) {
+  debug_assert!(count != 0);
+  unsafe {
+    DMA3_SRC.write(src);
+    DMA3_DEST.write(dest);
+    DMA3_COUNT.write(count);
+    DMA3_CONTROL.write(
+      control.with_start_time(DmaStartTime::Immediate).with_enabled(true),
+    );
+  }
+  while DMA3_CONTROL.read().enabled() {}
+}
+
+/// The largest count used for one DMA3 transfer by the copy functions.
+const DMA3_MAX_COUNT: usize = 0xFFFF;
+
+/// Transfers `count` elements with DMA3, splitting it into as many transfers
+/// as the count register needs. A `count` of 0 does nothing.
+///
+/// With a `Fixed` source address control every transfer reads from `src`,
+/// otherwise the source advances along with the destination.
+#[inline]
+unsafe fn dma3_chunks(
+  src: *const c_void, dest: *mut c_void, count: usize, control: DmaControl,
+) {
+  let size = if control.transfer_32bit() { 4 } else { 2 };
+  let src_step = match control.src_addr_control() {
+    SrcAddrControl::Fixed => 0,
+    _ => size,
+  };
+  let mut done = 0;
+  while done < count {
+    let chunk = (count - done).min(DMA3_MAX_COUNT);
+    unsafe {
+      dma3_run(
+        src.byte_add(done * src_step),
+        dest.byte_add(done * size),
+        chunk as u16,
+        control,
+      )
+    };
+    done += chunk;
+  }
+}
+
+/// Copies a slice of `u16` to `dest` using DMA3.
+///
+/// * Copies of more than 65,535 elements are split into several transfers.
+/// * An empty slice does nothing (rather than the hardware's "count 0 means
+///   65,536" behavior).
+/// * The CPU, and so also interrupt handling, is paused until the copy is done.
+///   Large copies can make you miss interrupts that need quick handling (such
+///   as serial or sound timing ones).
+///
+/// ## Panics
+/// * `dest` must be aligned to 2.
+///
+/// ## Safety
+/// * `dest` must be writable for `src.len()` elements.
+/// * The regions must not overlap.
+/// * See the module notes about altering memory that Rust uses.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub unsafe fn dma3_copy_u16(src: &[u16], dest: *mut u16) {
+  assert!(dest.is_aligned(), "destination must be aligned to 2");
+  let control = DmaControl::new();
+  unsafe { dma3_chunks(src.as_ptr().cast(), dest.cast(), src.len(), control) }
+}
+
+/// Copies a slice of `u32` to `dest` using DMA3.
+///
+/// This uses 32-bit transfers, otherwise it works like [`dma3_copy_u16`].
+///
+/// ## Panics
+/// * `dest` must be aligned to 4.
+///
+/// ## Safety
+/// * `dest` must be writable for `src.len()` elements.
+/// * The regions must not overlap.
+/// * See the module notes about altering memory that Rust uses.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub unsafe fn dma3_copy_u32(src: &[u32], dest: *mut u32) {
+  assert!(dest.is_aligned(), "destination must be aligned to 4");
+  let control = DmaControl::new().with_transfer_32bit(true);
+  unsafe { dma3_chunks(src.as_ptr().cast(), dest.cast(), src.len(), control) }
+}
+
+/// Copies between two `u16` slices using DMA3.
+///
+/// This is the safe version of [`dma3_copy_u16`] for RAM to RAM copies. The
+/// borrows make sure the regions are valid and don't overlap, and compiler
+/// fences keep other accesses to the slices from being moved across the
+/// transfer.
+///
+/// ## Panics
+/// * The slices must be the same length.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn dma3_copy_u16_slice(src: &[u16], dest: &mut [u16]) {
+  assert_eq!(src.len(), dest.len(), "slices must be the same length");
+  compiler_fence(Ordering::SeqCst);
+  unsafe { dma3_copy_u16(src, dest.as_mut_ptr()) };
+  compiler_fence(Ordering::SeqCst);
+}
+
+/// Copies between two `u32` slices using DMA3.
+///
+/// This is the safe version of [`dma3_copy_u32`], see
+/// [`dma3_copy_u16_slice`].
+///
+/// ## Panics
+/// * The slices must be the same length.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn dma3_copy_u32_slice(src: &[u32], dest: &mut [u32]) {
+  assert_eq!(src.len(), dest.len(), "slices must be the same length");
+  compiler_fence(Ordering::SeqCst);
+  unsafe { dma3_copy_u32(src, dest.as_mut_ptr()) };
+  compiler_fence(Ordering::SeqCst);
+}
+
+/// Fills `count` words at `dest` with `value` using DMA3.
+///
+/// The value is copied to the stack, and the DMA reads it from there with a
+/// fixed source address, so any value can be passed directly.
+///
+/// * Fills of more than 65,535 words are split into several transfers, and a
+///   `count` of 0 does nothing.
+/// * Like with [`dma3_copy_u32`], the CPU and interrupts are paused until the
+///   fill is done.
+///
+/// Going by the GBATEK timings, filling VRAM this way costs about 3 cycles per
+/// word (after a few cycles of setup), so clearing a mode 3 bitmap takes about
+/// a fifth of a frame. That's a lot faster than a simple CPU loop of `str`
+/// instructions (around 6 cycles per word from ROM), and very close to the
+/// `stm` based [`set_u32x80_unchecked`](crate::mem::set_u32x80_unchecked).
+/// Because the DMA doesn't need any code to be in IWRAM, it's a good default.
+///
+/// ## Panics
+/// * `dest` must be aligned to 4.
+///
+/// ## Safety
+/// * `dest` must be writable for `count` words.
+/// * See the module notes about altering memory that Rust uses.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub unsafe fn dma3_fill_u32(value: u32, dest: *mut u32, count: usize) {
+  assert!(dest.is_aligned(), "destination must be aligned to 4");
+  let src = value;
+  let control = DmaControl::new()
+    .with_src_addr_control(SrcAddrControl::Fixed)
+    .with_transfer_32bit(true);
+  compiler_fence(Ordering::SeqCst);
+  unsafe {
+    dma3_chunks((&src as *const u32).cast(), dest.cast(), count, control)
+  };
+  compiler_fence(Ordering::SeqCst);
+}
+
+/// Fills a slice with `value` using DMA3.
+///
+/// This is the safe version of [`dma3_fill_u32`].
+#[inline]
+pub fn dma3_fill_u32_slice(dest: &mut [u32], value: u32) {
+  unsafe { dma3_fill_u32(value, dest.as_mut_ptr(), dest.len()) };
+}
+
+/// The DMA control for streaming samples to a sound FIFO.
+///
+/// Per GBATEK, a sound FIFO DMA must use:
+/// * The `Special` start time, so that the transfer happens when the FIFO asks
+///   for more data (when it's down to 16 bytes or less).
+/// * A `Fixed` destination address (the FIFO register itself).
+/// * `repeat`, so that the DMA keeps going after each request.
+/// * 32-bit transfers. The count register is ignored in this mode, and each
+///   request always moves 4 words (16 samples).
+pub const SOUND_FIFO_DMA_CONTROL: DmaControl = DmaControl::new()
+  .with_dest_addr_control(DestAddrControl::Fixed)
+  .with_src_addr_control(SrcAddrControl::Increment)
+  .with_repeat(true)
+  .with_transfer_32bit(true)
+  .with_start_time(DmaStartTime::Special)
+  .with_enabled(true);
+
+/// Sets up a DMA unit to feed a sound FIFO from `src`.
+#[inline]
+unsafe fn setup_sound_dma(
+  regs: DmaRegisters, src: *const u32, fifo: VolAddress<u32, (), Safe>,
+) {
+  unsafe {
+    regs.control.write(DmaControl::new());
+    regs.src.write(src.cast());
+    regs.dest.write(fifo.as_usize() as *mut c_void);
+    regs.count.write(4);
+    regs.control.write(SOUND_FIFO_DMA_CONTROL);
+  }
+}
+
+/// Starts DMA1 streaming samples from `src` into [`FIFO_A`].
+///
+/// The DMA unit is always stopped before being reprogrammed, so this is also
+/// the way to switch to a new buffer (eg: swapping buffers when a timer
+/// overflows). It's only a few register writes, so it's fine to call from an
+/// interrupt handler.
+///
+/// This only handles the DMA side. Sound A must also be given a timer and
+/// enabled in [`SOUND_MIX`], and that timer must be running.
+///
+/// ## Safety
+/// * `src` must be aligned to 4, and stay readable for as long as the DMA unit
+///   runs. The DMA keeps reading forward from `src` until it's stopped or
+///   pointed somewhere else, so make sure that happens before it passes the end
+///   of your buffer.
+#[inline]
+pub unsafe fn setup_sound_dma_a(src: *const u32) {
+  unsafe { setup_sound_dma(DmaRegisters::of(1), src, FIFO_A) }
+}
+
+/// Starts DMA2 streaming samples from `src` into [`FIFO_B`].
+///
+/// Works like [`setup_sound_dma_a`], but for Sound B.
+///
+/// ## Safety
+/// * As with [`setup_sound_dma_a`].
+#[inline]
+pub unsafe fn setup_sound_dma_b(src: *const u32) {
+  unsafe { setup_sound_dma(DmaRegisters::of(2), src, FIFO_B) }
+}
+
+/// Stops the DMA1 sound stream started with [`setup_sound_dma_a`].
+#[inline]
+pub fn stop_sound_dma_a() {
+  unsafe { DMA1_CONTROL.write(DmaControl::new()) };
+}
+
+/// Stops the DMA2 sound stream started with [`setup_sound_dma_b`].
+#[inline]
+pub fn stop_sound_dma_b() {
+  unsafe { DMA2_CONTROL.write(DmaControl::new()) };
+}
@@ -0,0 +1,154 @@
+//! Finds out what the game is running on: mGBA, no$gba, or something else
+//! (usually real hardware).
+//!
+//! Some code needs to act differently in an emulator, such as sending debug
+//! messages (the `mgba_*` logging macros use this to pick where messages go,
+//! see [`debug_log`](crate::debug_log)) or the test runner, which only works
+//! in mGBA. [`detect`] gives the [`Environment`]:
+//!
+//! ```no_run
+//! # use gba::environment::*;
+//! match detect() {
+//!   Environment::Mgba => gba::mgba_info!("hello, mGBA"),
+//!   Environment::Nocash { id } => gba::mgba_info!("hello, {id}"),
+//!   Environment::UnknownOrHardware => (),
+//! }
+//! ```
+//!
+//! ## What's checked
+//!
+//! The check is done the first time it's needed, and the answer is kept in a
+//! static after that, so it's cheap to call often. Everything it reads is in
+//! the part of the IO area (past `0x0400_0400`) that has nothing there on real
+//! hardware. Reading those addresses just gives "open bus" values (whatever
+//! was last on the bus), and writing them does nothing, so none of this can
+//! fault or change anything on hardware.
+//!
+//! * **mGBA:** The assembly runtime writes
+//!   [`MGBA_LOGGING_ENABLE_REQUEST`](crate::mgba::MGBA_LOGGING_ENABLE_REQUEST)
+//!   (`0xC0DE`) to [`MGBA_LOG_ENABLE`] (`0x04FF_F780`) at startup. mGBA answers
+//!   by reading back [`MGBA_LOGGING_ENABLE_RESPONSE`] (`0x1DEA`) there, which
+//!   is what's checked. mGBA doesn't have a register with its version, so
+//!   there's no version to give.
+//! * **no$gba:** [`NOCASH_ID`] (16 bytes at `0x04FF_FA00`) holds no$gba's name
+//!   and version, such as `no$gba v3.05`. It's checked for starting with
+//!   `no$gba`.
+//!
+//! Other emulators usually don't have either, so they count as
+//! [`UnknownOrHardware`](Environment::UnknownOrHardware) too.
+
+use crate::{
+  collections::ArrayString,
+  gba_cell::GbaCell,
+  mgba::MGBA_LOGGING_ENABLE_RESPONSE,
+  mmio::{MGBA_LOG_ENABLE, NOCASH_ID},
+};
+
+/// What [`NOCASH_ID`] starts with in no$gba.
+const NOCASH_ID_PREFIX: &[u8] = b"no$gba";
+
+/// Not checked yet.
+const UNCHECKED: u8 = 0;
+/// Checked, and it's neither emulator.
+const UNKNOWN: u8 = 1;
+/// Checked, and it's mGBA.
+const MGBA: u8 = 2;
+/// Checked, and it's no$gba.
+const NOCASH: u8 = 3;
+
+/// The result of the check, once it's done.
+static DETECTED: GbaCell<u8> = GbaCell::new(UNCHECKED);
+
+/// What the game is running on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Environment {
+  /// mGBA, with its debug output on.
+  Mgba,
+  /// No$gba.
+  Nocash {
+    /// Its name and version (such as `no$gba v3.05`) from [`NOCASH_ID`],
+    /// without the padding.
+    id: ArrayString<16>,
+  },
+  /// Some other emulator, or (most likely) real hardware.
+  UnknownOrHardware,
+}
+impl Environment {
+  /// If this is an emulator that the crate knows about.
+  #[inline]
+  #[must_use]
+  pub const fn is_known_emulator(&self) -> bool {
+    !matches!(self, Self::UnknownOrHardware)
+  }
+}
+
+/// Finds out what the game is running on.
+///
+/// See the [module docs](self) for how.
+#[inline]
+#[must_use]
+pub fn detect() -> Environment {
+  match detected() {
+    MGBA => Environment::Mgba,
+    NOCASH => Environment::Nocash { id: nocash_id() },
+    _ => Environment::UnknownOrHardware,
+  }
+}
+
+/// If the game is running in mGBA (with its debug output on).
+///
+/// This is the same as checking for [`Environment::Mgba`], without making the
+/// whole [`Environment`].
+#[inline]
+#[must_use]
+pub fn is_mgba() -> bool {
+  detected() == MGBA
+}
+
+/// If the game is running in no$gba.
+///
+/// This is the same as checking for [`Environment::Nocash`], without reading
+/// the ID.
+#[inline]
+#[must_use]
+pub fn is_nocash() -> bool {
+  detected() == NOCASH
+}
+
+/// Does the check, if it isn't done yet, and gives the result.
+#[inline]
+#[must_use]
+fn detected() -> u8 {
+  let mut state = DETECTED.read();
+  if state == UNCHECKED {
+    state = if MGBA_LOG_ENABLE.read() == MGBA_LOGGING_ENABLE_RESPONSE {
+      MGBA
+    } else if NOCASH_ID_PREFIX
+      .iter()
+      .enumerate()
+      .all(|(i, &b)| NOCASH_ID.index(i).read() == b)
+    {
+      NOCASH
+    } else {
+      UNKNOWN
+    };
+    DETECTED.write(state);
+  }
+  state
+}
+
+/// Reads [`NOCASH_ID`], without the trailing spaces (and stopping at anything
+/// that isn't printable ASCII).
+#[inline]
+#[must_use]
+fn nocash_id() -> ArrayString<16> {
+  let mut id = ArrayString::new();
+  for byte in NOCASH_ID.iter().map(|a| a.read()) {
+    if !(b' '..=b'~').contains(&byte) || id.push(char::from(byte)).is_err() {
+      break;
+    }
+  }
+  let trimmed = id.as_str().trim_end().len();
+  id.truncate(trimmed);
+  id
+}
@@ -0,0 +1,282 @@
+//! A global allocator for the EWRAM, so that `alloc`'s `Box`, `Vec`, and so on
+//! work.
+//!
+//! This is only here with the `ewram_alloc` feature, which also makes
+//! [`EwramAllocator`] the `#[global_allocator]`. The `alloc` crate also has to
+//! be built, so add it to `build-std` (as in
+//! `build-std = ["core", "alloc"]`), and then use it with
+//! `extern crate alloc;`.
+//!
+//! The heap is all of the EWRAM after the `.ewram` section that the linker
+//! script puts there (the linker's `__ewram_end`), up to the end of the EWRAM
+//! at `0x0204_0000`. Statics with `#[link_section = ".ewram"]` stay out of the
+//! heap's way, but anything else that uses the EWRAM directly (such as a
+//! buffer at a fixed address) will clash with it.
+//!
+//! ## How it works
+//! The free memory is kept as a list of blocks in address order. Allocating
+//! takes the first block that's big enough (first fit), and splits off
+//! whatever's left over as a new free block. Freeing puts the block back in
+//! the list, and merges it with the blocks right before and after it if
+//! they're free, so freeing everything always gives back the one full size
+//! block.
+//!
+//! Every allocation is rounded up to a multiple of 8 bytes, and at least 8
+//! bytes, since that's the size of a free block's header (its size and the
+//! address of the next one). So lots of small allocations waste some space,
+//! and allocations that stay around while others come and go between them can
+//! leave the free space split up into blocks too small to use (the usual
+//! fragmentation problem of first fit). Allocating long-lived things first
+//! helps. [`heap_free_bytes`] and [`heap_largest_free_block`] show how it's
+//! going.
+//!
+//! Each allocation and free runs with interrupts off, so it's fine to allocate
+//! in an interrupt handler (it just delays other interrupts a bit). When an
+//! allocation fails, the request and the largest free block are logged to
+//! mGBA or no$gba, and then `alloc` panics as usual.
+
+use crate::interrupts::IrqMutex;
+use core::{
+  alloc::{GlobalAlloc, Layout},
+  ptr::null_mut,
+};
+
+/// The end of the EWRAM.
+const EWRAM_END: usize = 0x0204_0000;
+
+/// The smallest block, and what all blocks are a multiple of.
+///
+/// This is the size of a free block's header.
+pub const MIN_BLOCK: usize = 8;
+
+extern "C" {
+  /// Set by the linker script at the end of the `.ewram` section.
+  static __ewram_end: u8;
+}
+
+/// The header at the start of each free block.
+#[derive(Clone, Copy)]
+#[repr(C, align(8))]
+struct FreeBlock {
+  /// The size of the block, including this header.
+  size: usize,
+  /// The address of the next free block, or 0 for the end of the list.
+  next: usize,
+}
+
+/// The list of free blocks.
+struct Heap {
+  /// The address of the first free block, or 0 if there's none left.
+  first: usize,
+  /// If the heap has been set up yet.
+  ready: bool,
+}
+
+static HEAP: IrqMutex<Heap> = IrqMutex::new(Heap { first: 0, ready: false });
+
+/// Rounds `addr` up to a multiple of `align` (which is a power of 2).
+const fn align_up(addr: usize, align: usize) -> usize {
+  (addr + align - 1) & !(align - 1)
+}
+
+/// The size of the block used for `layout`.
+const fn block_size(layout: Layout) -> usize {
+  let size = if layout.size() < MIN_BLOCK { MIN_BLOCK } else { layout.size() };
+  align_up(size, MIN_BLOCK)
+}
+
+/// Gets the block header at `addr`.
+///
+/// ## Safety
+/// * `addr` has to be a block in the heap, and nothing else can be using it.
+unsafe fn block<'a>(addr: usize) -> &'a mut FreeBlock {
+  &mut *(addr as *mut FreeBlock)
+}
+
+/// Writes a new block header at `addr`, giving `addr`.
+///
+/// ## Safety
+/// * `addr` to `addr + size` has to be in the heap, and not allocated.
+unsafe fn new_block(addr: usize, size: usize, next: usize) -> usize {
+  (addr as *mut FreeBlock).write(FreeBlock { size, next });
+  addr
+}
+
+impl Heap {
+  /// Makes the whole heap one free block, the first time it's used.
+  fn prepare(&mut self) {
+    if self.ready {
+      return;
+    }
+    self.ready = true;
+    let start = align_up(heap_start(), MIN_BLOCK);
+    if start + MIN_BLOCK <= EWRAM_END {
+      // Safety: this is the whole heap, and it's all free.
+      self.first = unsafe { new_block(start, EWRAM_END - start, 0) };
+    }
+  }
+
+  /// First fit: takes the first free block that can hold `size` bytes aligned
+  /// to `align`, and gives back whatever's left on either side.
+  fn alloc(&mut self, size: usize, align: usize) -> *mut u8 {
+    self.prepare();
+    let mut prev = 0;
+    let mut current = self.first;
+    while current != 0 {
+      // Safety: everything in the list is a free block.
+      let FreeBlock { size: current_size, next } = *unsafe { block(current) };
+      let end = current + current_size;
+      let mut start = align_up(current, align);
+      if start != current && start - current < MIN_BLOCK {
+        // There has to be room for a free block before it.
+        start = align_up(current + MIN_BLOCK, align);
+      }
+      if start + size <= end {
+        let mut rest = next;
+        // Safety: the space before and after the allocation is still free,
+        // and each part is a multiple of `MIN_BLOCK`.
+        unsafe {
+          if end - (start + size) > 0 {
+            rest = new_block(start + size, end - (start + size), rest);
+          }
+          if start > current {
+            rest = new_block(current, start - current, rest);
+          }
+        }
+        if prev == 0 {
+          self.first = rest;
+        } else {
+          // Safety: `prev` is a free block.
+          unsafe { block(prev).next = rest };
+        }
+        return start as *mut u8;
+      }
+      prev = current;
+      current = next;
+    }
+    null_mut()
+  }
+
+  /// Puts a block back in the list (in address order), merging it with the
+  /// free blocks on either side.
+  fn free(&mut self, addr: usize, size: usize) {
+    let mut prev = 0;
+    let mut next = self.first;
+    while next != 0 && next < addr {
+      prev = next;
+      // Safety: everything in the list is a free block.
+      next = unsafe { block(next).next };
+    }
+    // Safety: the block was allocated, and now it's free.
+    let freed = unsafe { new_block(addr, size, next) };
+    if next != 0 && addr + size == next {
+      // Safety: `next` is a free block right after this one.
+      unsafe {
+        let after = block(next);
+        let merged = block(freed);
+        merged.size += after.size;
+        merged.next = after.next;
+      }
+    }
+    if prev == 0 {
+      self.first = freed;
+    } else {
+      // Safety: `prev` is a free block right before this one.
+      unsafe {
+        let before = block(prev);
+        if prev + before.size == freed {
+          let merged = block(freed);
+          before.size += merged.size;
+          before.next = merged.next;
+        } else {
+          before.next = freed;
+        }
+      }
+    }
+  }
+
+  /// Calls `f` with the size of each free block.
+  fn for_each_free(&mut self, mut f: impl FnMut(usize)) {
+    self.prepare();
+    let mut current = self.first;
+    while current != 0 {
+      // Safety: everything in the list is a free block.
+      let FreeBlock { size, next } = *unsafe { block(current) };
+      f(size);
+      current = next;
+    }
+  }
+}
+
+/// Where the heap starts: the end of the linker's `.ewram` section.
+fn heap_start() -> usize {
+  core::ptr::addr_of!(__ewram_end) as usize
+}
+
+/// How many bytes are free in the heap, in total.
+#[inline]
+#[must_use]
+pub fn heap_free_bytes() -> usize {
+  HEAP.with(|heap| {
+    let mut total = 0;
+    heap.for_each_free(|size| total += size);
+    total
+  })
+}
+
+/// The size of the biggest free block, which is the most that can be
+/// allocated right now.
+#[inline]
+#[must_use]
+pub fn heap_largest_free_block() -> usize {
+  HEAP.with(|heap| {
+    let mut largest = 0;
+    heap.for_each_free(|size| largest = largest.max(size));
+    largest
+  })
+}
+
+/// The size of the heap when nothing is allocated.
+#[inline]
+#[must_use]
+pub fn heap_size() -> usize {
+  EWRAM_END.saturating_sub(align_up(heap_start(), MIN_BLOCK))
+}
+
+/// The EWRAM allocator (see the [module docs](self)).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EwramAllocator;
+
+// Safety: the heap is only touched with interrupts off, blocks are only handed
+// out once, and they're aligned and sized as asked.
+unsafe impl GlobalAlloc for EwramAllocator {
+  #[inline]
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    let size = block_size(layout);
+    let align = layout.align().max(MIN_BLOCK);
+    let ptr = HEAP.with(|heap| heap.alloc(size, align));
+    if ptr.is_null() {
+      log_failure(layout);
+    }
+    ptr
+  }
+
+  #[inline]
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    HEAP.with(|heap| heap.free(ptr as usize, block_size(layout)));
+  }
+}
+
+/// Logs a failed allocation to mGBA or no$gba.
+#[cold]
+fn log_failure(layout: Layout) {
+  crate::mgba_error!(
+    "couldn't allocate {} bytes (align {}), the largest free block is {}",
+    layout.size(),
+    layout.align(),
+    heap_largest_free_block()
+  );
+}
+
+#[global_allocator]
+static ALLOCATOR: EwramAllocator = EwramAllocator;
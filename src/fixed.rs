@@ -31,6 +31,19 @@ pub type i16fx14 = Fixed<i16, 14>;
 #[cfg(not(feature = "fixed"))]
 pub type i32fx8 = Fixed<i32, 8>;
 
+/// `i32` with 16 bits of fixed-point fraction.
+///
+/// The hardware doesn't use this, but it's handy for positions and speeds that
+/// need more precision than 8 bits, which are then converted with
+/// [`to_i32fx8`](Fixed::to_i32fx8) or [`to_i16fx8`](Fixed::to_i16fx8) when
+/// they're written to the hardware.
+///
+/// * This build of the docs does not use the `fixed` feature and uses the
+///   crate's internal fixed point type.
+#[allow(non_camel_case_types)]
+#[cfg(not(feature = "fixed"))]
+pub type i32fx16 = Fixed<i32, 16>;
+
 /// `i16` with 8 bits of fixed-point fraction.
 ///
 /// This is used by the affine matrix entries.
@@ -39,7 +52,7 @@ pub type i32fx8 = Fixed<i32, 8>;
 ///   type from the `fixed` crate.
 #[allow(non_camel_case_types)]
 #[cfg(feature = "fixed")]
-pub type i16fx8 = ::fixed::FixedI32<::fixed::types::extra::U8>;
+pub type i16fx8 = ::fixed::FixedI16<::fixed::types::extra::U8>;
 
 /// `i16` with 14 bits of fixed-point fraction.
 ///
@@ -50,7 +63,7 @@ pub type i16fx8 = ::fixed::FixedI32<::fixed::types::extra::U8>;
 ///   type from the `fixed` crate.
 #[allow(non_camel_case_types)]
 #[cfg(feature = "fixed")]
-pub type i16fx14 = ::fixed::FixedI32<::fixed::types::extra::U14>;
+pub type i16fx14 = ::fixed::FixedI16<::fixed::types::extra::U14>;
 
 /// `i32` with 8 bits of fixed-point fraction.
 ///
@@ -62,6 +75,17 @@ pub type i16fx14 = ::fixed::FixedI32<::fixed::types::extra::U14>;
 #[cfg(feature = "fixed")]
 pub type i32fx8 = ::fixed::FixedI32<::fixed::types::extra::U8>;
 
+/// `i32` with 16 bits of fixed-point fraction.
+///
+/// The hardware doesn't use this, but it's handy for positions and speeds that
+/// need more precision than 8 bits.
+///
+/// * This build of the docs uses the `fixed` feature and uses the fixed point
+///   type from the `fixed` crate.
+#[allow(non_camel_case_types)]
+#[cfg(feature = "fixed")]
+pub type i32fx16 = ::fixed::FixedI32<::fixed::types::extra::U16>;
+
 /// A [fixed-point][wp-fp] number. This transparently wraps an integer with a
 /// const generic for how many bits are fractional.
 ///
@@ -75,6 +99,22 @@ pub type i32fx8 = ::fixed::FixedI32<::fixed::types::extra::U8>;
 ///   should be *less than* the number of bits in the integer's type. Multiply
 ///   and divide ops need to shift the value by `B`, and so if `B` is greater
 ///   than or equal to the integer's size the op will panic.
+///
+/// ## Overflow and Rounding
+/// * Like the integer ops, `+`, `-`, `*`, and `/` panic on overflow when debug
+///   assertions are on, and wrap when they're off. The `wrapping_` and
+///   `saturating_` methods pick one explicitly.
+/// * Multiply and divide are done in a bigger integer (`i32` for `i8` and
+///   `i16`, or `i64` for `i32`), so only the *result* has to fit, not the value
+///   before it's shifted back down.
+/// * Multiply *truncates* the bits below the fraction, which is rounding
+///   towards negative infinity (so `-0.5 * 1/256` is `-1/256`, not `0`).
+///   [`mul_rounded`](Self::mul_rounded) rounds to the nearest value instead,
+///   with halves going up.
+/// * Divide rounds towards zero, like integer division.
+/// * [`from_f32`](Self::from_f32) rounds to the nearest value, with halves
+///   going away from zero. It's a `const fn`, so it's fine for constants and
+///   statics, but at runtime it's slow (the GBA doesn't do floats).
 #[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct Fixed<I, const B: u32>(I);
@@ -153,6 +193,90 @@ macro_rules! impl_common_fixed_ops {
         Self(i << B)
       }
 
+      /// Makes a value from a whole number.
+      ///
+      /// ## Panics
+      /// * If the number doesn't fit, when debug assertions are on (otherwise
+      ///   it wraps like [`wrapping_from`](Self::wrapping_from)).
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn from_int(i: $t) -> Self {
+        let shifted = i << B;
+        if cfg!(debug_assertions) && (shifted >> B) != i {
+          panic!("attempt to make a fixed-point value with overflow");
+        }
+        Self(shifted)
+      }
+
+      /// The whole number part, rounded towards negative infinity.
+      ///
+      /// This is just the bits above the fraction, so `-1.5` gives `-2`. Use
+      /// [`trunc`](Self::trunc) to round towards zero instead.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn int(self) -> $t {
+        self.0 >> B
+      }
+
+      /// Makes a value from an `f32`, rounding to the nearest value (with
+      /// halves going away from zero).
+      ///
+      /// Values that don't fit are clamped to the smallest or largest value,
+      /// and NaN gives 0.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn from_f32(f: f32) -> Self {
+        let scaled = f * (1_u64 << B) as f32;
+        let rounded = if scaled < 0.0 { scaled - 0.5 } else { scaled + 0.5 };
+        Self(rounded as $t)
+      }
+
+      /// Converts the value to an `f32`.
+      ///
+      /// This is mostly useful for checking values, since floats are slow on
+      /// the GBA.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn to_f32(self) -> f32 {
+        self.0 as f32 / (1_u64 << B) as f32
+      }
+
+      /// Addition, wrapping on overflow.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn wrapping_add(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_add(rhs.0))
+      }
+
+      /// Subtraction, wrapping on overflow.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn wrapping_sub(self, rhs: Self) -> Self {
+        Self(self.0.wrapping_sub(rhs.0))
+      }
+
+      /// Addition, staying at the smallest or largest value on overflow.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self(self.0.saturating_add(rhs.0))
+      }
+
+      /// Subtraction, staying at the smallest or largest value on overflow.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self(self.0.saturating_sub(rhs.0))
+      }
+
       /// Makes a `Fixed` directly from a raw inner value (no shift).
       #[inline]
       #[must_use]
@@ -272,7 +396,7 @@ impl_common_fixed_ops!(u16);
 impl_common_fixed_ops!(u32);
 
 macro_rules! impl_signed_fixed_ops {
-  ($t:ty, $unsigned:ty) => {
+  ($t:ty, $unsigned:ty, $wide:ty) => {
     impl<const B: u32> Fixed<$t, B> {
       /// Negate.
       #[inline]
@@ -291,22 +415,87 @@ macro_rules! impl_signed_fixed_ops {
       }
 
       /// Multiply.
+      ///
+      /// The bits below the fraction are truncated (see the
+      /// [type docs](Fixed#overflow-and-rounding)).
+      ///
+      /// ## Panics
+      /// * If the result doesn't fit, when debug assertions are on (otherwise
+      ///   it wraps).
       #[inline]
       #[must_use]
       #[cfg_attr(feature = "track_caller", track_caller)]
       pub const fn mul(self, rhs: Self) -> Self {
-        let raw = (self.0 as i32) * (rhs.0 as i32);
-        Self((raw >> B) as $t)
+        let wide = ((self.0 as $wide) * (rhs.0 as $wide)) >> B;
+        if cfg!(debug_assertions)
+          && (wide < <$t>::MIN as $wide || wide > <$t>::MAX as $wide)
+        {
+          panic!("attempt to multiply with overflow");
+        }
+        Self(wide as $t)
+      }
+
+      /// Multiply, rounding to the nearest value (with halves going up)
+      /// rather than truncating.
+      ///
+      /// ## Panics
+      /// * As with [`mul`](Self::mul).
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn mul_rounded(self, rhs: Self) -> Self {
+        let half = (1 as $wide) << B >> 1;
+        let wide = ((self.0 as $wide) * (rhs.0 as $wide) + half) >> B;
+        if cfg!(debug_assertions)
+          && (wide < <$t>::MIN as $wide || wide > <$t>::MAX as $wide)
+        {
+          panic!("attempt to multiply with overflow");
+        }
+        Self(wide as $t)
+      }
+
+      /// Multiply, wrapping on overflow.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn wrapping_mul(self, rhs: Self) -> Self {
+        Self((((self.0 as $wide) * (rhs.0 as $wide)) >> B) as $t)
+      }
+
+      /// Multiply, staying at the smallest or largest value on overflow.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn saturating_mul(self, rhs: Self) -> Self {
+        let wide = ((self.0 as $wide) * (rhs.0 as $wide)) >> B;
+        if wide < <$t>::MIN as $wide {
+          Self(<$t>::MIN)
+        } else if wide > <$t>::MAX as $wide {
+          Self(<$t>::MAX)
+        } else {
+          Self(wide as $t)
+        }
       }
 
       /// Divide.
+      ///
+      /// The result rounds towards zero.
+      ///
+      /// ## Panics
+      /// * If `rhs` is zero.
+      /// * If the result doesn't fit, when debug assertions are on (otherwise
+      ///   it wraps).
       #[inline]
       #[must_use]
       #[cfg_attr(feature = "track_caller", track_caller)]
       pub const fn div(self, rhs: Self) -> Self {
-        let m = (self.0 as i32) * (1 << B);
-        let d = m / (rhs.0 as i32);
-        Self(d as $t)
+        let wide = ((self.0 as $wide) << B) / (rhs.0 as $wide);
+        if cfg!(debug_assertions)
+          && (wide < <$t>::MIN as $wide || wide > <$t>::MAX as $wide)
+        {
+          panic!("attempt to divide with overflow");
+        }
+        Self(wide as $t)
       }
 
       /// Fractional part of the value.
@@ -343,30 +532,95 @@ macro_rules! impl_signed_fixed_ops {
     }
   };
 }
-impl_signed_fixed_ops!(i8, u8);
-impl_signed_fixed_ops!(i16, u16);
-impl_signed_fixed_ops!(i32, u32);
+impl_signed_fixed_ops!(i8, u8, i32);
+impl_signed_fixed_ops!(i16, u16, i32);
+impl_signed_fixed_ops!(i32, u32, i64);
 
 macro_rules! impl_unsigned_fixed_ops {
-  ($t:ty) => {
+  ($t:ty, $wide:ty) => {
     impl<const B: u32> Fixed<$t, B> {
       /// Multiply.
+      ///
+      /// The bits below the fraction are truncated (see the
+      /// [type docs](Fixed#overflow-and-rounding)).
+      ///
+      /// ## Panics
+      /// * If the result doesn't fit, when debug assertions are on (otherwise
+      ///   it wraps).
       #[inline]
       #[must_use]
       #[cfg_attr(feature = "track_caller", track_caller)]
       pub const fn mul(self, rhs: Self) -> Self {
-        let raw = (self.0 as u32) * (rhs.0 as u32);
-        Self((raw >> B) as $t)
+        let wide = ((self.0 as $wide) * (rhs.0 as $wide)) >> B;
+        if cfg!(debug_assertions)
+          && (wide < <$t>::MIN as $wide || wide > <$t>::MAX as $wide)
+        {
+          panic!("attempt to multiply with overflow");
+        }
+        Self(wide as $t)
+      }
+
+      /// Multiply, rounding to the nearest value (with halves going up)
+      /// rather than truncating.
+      ///
+      /// ## Panics
+      /// * As with [`mul`](Self::mul).
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn mul_rounded(self, rhs: Self) -> Self {
+        let half = (1 as $wide) << B >> 1;
+        let wide = ((self.0 as $wide) * (rhs.0 as $wide) + half) >> B;
+        if cfg!(debug_assertions)
+          && (wide < <$t>::MIN as $wide || wide > <$t>::MAX as $wide)
+        {
+          panic!("attempt to multiply with overflow");
+        }
+        Self(wide as $t)
+      }
+
+      /// Multiply, wrapping on overflow.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn wrapping_mul(self, rhs: Self) -> Self {
+        Self((((self.0 as $wide) * (rhs.0 as $wide)) >> B) as $t)
+      }
+
+      /// Multiply, staying at the smallest or largest value on overflow.
+      #[inline]
+      #[must_use]
+      #[cfg_attr(feature = "track_caller", track_caller)]
+      pub const fn saturating_mul(self, rhs: Self) -> Self {
+        let wide = ((self.0 as $wide) * (rhs.0 as $wide)) >> B;
+        if wide < <$t>::MIN as $wide {
+          Self(<$t>::MIN)
+        } else if wide > <$t>::MAX as $wide {
+          Self(<$t>::MAX)
+        } else {
+          Self(wide as $t)
+        }
       }
 
       /// Divide.
+      ///
+      /// The result rounds towards zero.
+      ///
+      /// ## Panics
+      /// * If `rhs` is zero.
+      /// * If the result doesn't fit, when debug assertions are on (otherwise
+      ///   it wraps).
       #[inline]
       #[must_use]
       #[cfg_attr(feature = "track_caller", track_caller)]
       pub const fn div(self, rhs: Self) -> Self {
-        let m = (self.0 as u32) * (1 << B);
-        let d = m / (rhs.0 as u32);
-        Self(d as $t)
+        let wide = ((self.0 as $wide) << B) / (rhs.0 as $wide);
+        if cfg!(debug_assertions)
+          && (wide < <$t>::MIN as $wide || wide > <$t>::MAX as $wide)
+        {
+          panic!("attempt to divide with overflow");
+        }
+        Self(wide as $t)
       }
 
       /// Fractional part of the value.
@@ -396,6 +650,59 @@ macro_rules! impl_unsigned_fixed_ops {
     }
   };
 }
-impl_unsigned_fixed_ops!(u8);
-impl_unsigned_fixed_ops!(u16);
-impl_unsigned_fixed_ops!(u32);
+impl_unsigned_fixed_ops!(u8, u32);
+impl_unsigned_fixed_ops!(u16, u32);
+impl_unsigned_fixed_ops!(u32, u64);
+
+impl<const B: u32> Fixed<i16, B> {
+  /// Widens the value to an `i32`, with the same fraction bits.
+  #[inline]
+  #[must_use]
+  pub const fn widen(self) -> Fixed<i32, B> {
+    Fixed(self.0 as i32)
+  }
+}
+
+impl<const B: u32> Fixed<i32, B> {
+  /// Changes the number of fraction bits to `N`.
+  ///
+  /// Going to fewer bits truncates the ones that are dropped (rounding
+  /// towards negative infinity), and going to more bits can overflow if the
+  /// whole part gets too big.
+  #[inline]
+  #[must_use]
+  pub const fn to_frac<const N: u32>(self) -> Fixed<i32, N> {
+    if N >= B {
+      Fixed(self.0 << (N - B))
+    } else {
+      Fixed(self.0 >> (B - N))
+    }
+  }
+
+  /// Converts to the hardware's 20.8 format, used by the background reference
+  /// points ([`BG2X`](crate::mmio::BG2X) and so on).
+  #[inline]
+  #[must_use]
+  pub const fn to_i32fx8(self) -> Fixed<i32, 8> {
+    self.to_frac::<8>()
+  }
+
+  /// Converts to the hardware's 8.8 format, used by the affine matrix entries
+  /// ([`BG2PA`](crate::mmio::BG2PA),
+  /// [`AffineMatrix`](crate::video::obj::AffineMatrix), and so on).
+  ///
+  /// Values outside of the 8.8 range (about `-128.0..128.0`) are clamped to
+  /// the smallest or largest value.
+  #[inline]
+  #[must_use]
+  pub const fn to_i16fx8(self) -> Fixed<i16, 8> {
+    let bits = self.to_frac::<8>().0;
+    Fixed(if bits < i16::MIN as i32 {
+      i16::MIN
+    } else if bits > i16::MAX as i32 {
+      i16::MAX
+    } else {
+      bits as i16
+    })
+  }
+}
@@ -28,13 +28,14 @@ use core::{
 };
 
 use crate::{
+  bios::{RamResetFlags, ResetTarget},
   fixed::Fixed,
   interrupts::IrqFn,
   keys::{KeyControl, KeyInput},
   video::Color,
 };
 
-/// A GBA-specific wrapper around Rust's [`UnsafeCell`](core::cell::UnsafeCell)
+/// A GBA-specific wrapper around Rust's [`UnsafeCell`]
 /// type.
 ///
 /// Supports any data type that implements the [`GbaCellSafe`] marker trait.
@@ -235,6 +236,7 @@ unsafe impl GbaCellSafe for NonZeroU32 {}
 unsafe impl GbaCellSafe for NonZeroU8 {}
 unsafe impl GbaCellSafe for Option<bool> {}
 unsafe impl GbaCellSafe for Option<char> {}
+unsafe impl GbaCellSafe for Option<fn()> {}
 unsafe impl GbaCellSafe for Option<IrqFn> {}
 unsafe impl GbaCellSafe for Option<NonZeroI16> {}
 unsafe impl GbaCellSafe for Option<NonZeroI32> {}
@@ -242,6 +244,8 @@ unsafe impl GbaCellSafe for Option<NonZeroI8> {}
 unsafe impl GbaCellSafe for Option<NonZeroU16> {}
 unsafe impl GbaCellSafe for Option<NonZeroU32> {}
 unsafe impl GbaCellSafe for Option<NonZeroU8> {}
+unsafe impl GbaCellSafe for RamResetFlags {}
+unsafe impl GbaCellSafe for ResetTarget {}
 unsafe impl GbaCellSafe for u16 {}
 unsafe impl GbaCellSafe for u32 {}
 unsafe impl GbaCellSafe for u8 {}
@@ -0,0 +1,35 @@
+//! Module for the hardware that's built into some cartridges.
+//!
+//! Some carts have extra hardware wired to the cartridge's I/O port: four pins
+//! that are mapped over the ROM at [`IO_PORT_DATA`](crate::mmio::IO_PORT_DATA),
+//! with [`IO_PORT_DIRECTION`](crate::mmio::IO_PORT_DIRECTION) picking which
+//! pins are outputs, and [`IO_PORT_CONTROL`](crate::mmio::IO_PORT_CONTROL)
+//! letting the pins be read back. This isn't the link port's general purpose
+//! mode, which is [`sio::gpio`](crate::sio::gpio).
+//!
+//! * [`rtc`]: the real-time clock.
+//! * [`rumble`]: the rumble motor, and the Game Boy Player's rumble.
+//! * [`solar`]: the solar sensor.
+//! * [`tilt`]: the tilt sensor, which is mapped over the SRAM instead.
+//!
+//! Flash carts and emulators only act like this hardware is there when they're
+//! told to, or when they know the game's code from a list. With mGBA, set the
+//! hardware in the game overrides.
+
+#[cfg(feature = "on_gba")]
+pub mod rtc;
+#[cfg(feature = "on_gba")]
+pub mod rumble;
+#[cfg(feature = "on_gba")]
+pub mod solar;
+#[cfg(feature = "on_gba")]
+pub mod tilt;
+
+/// Makes the I/O port pins readable.
+///
+/// Until this is done, reading the I/O port addresses gives the ROM data that's
+/// under them.
+#[cfg(feature = "on_gba")]
+pub(crate) fn enable_port_reads() {
+  crate::mmio::IO_PORT_CONTROL.write(1);
+}
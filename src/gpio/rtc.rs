@@ -0,0 +1,334 @@
+//! The S-3511A real-time clock, as used by the Pokémon games and others.
+//!
+//! The clock is on three of the I/O port pins (SCK is bit 0, SIO bit 1, and CS
+//! bit 2), and talks over a 3-wire serial line: CS goes high, a command byte is
+//! sent (highest bit first), and then the command's data bytes are sent or
+//! read (lowest bit first), one bit per rising edge of SCK. All values are
+//! binary coded decimal.
+//!
+//! ```no_run
+//! # use gba::gpio::rtc;
+//! match rtc::init() {
+//!   Ok(status) if status.power_failure() => { /* the time was lost */ }
+//!   Ok(_) => {
+//!     let now = rtc::datetime();
+//!   }
+//!   Err(_) => { /* no clock */ }
+//! }
+//! ```
+//!
+//! The clock keeps counting while the GBA is off (as long as the battery
+//! lasts), but if it loses power it starts back at 2000-01-01 0:00:00 with the
+//! [`power_failure`](RtcStatus::power_failure) flag set. A clock that lost
+//! power partway can also give values that aren't valid at all, which are
+//! reported as [`RtcError::InvalidTime`].
+
+use super::enable_port_reads;
+use crate::{
+  macros::{pub_const_fn_new_zeroed, u8_bool_field},
+  mmio::{IO_PORT_DATA, IO_PORT_DIRECTION},
+};
+
+const SCK: u16 = 1 << 0;
+const SIO: u16 = 1 << 1;
+const CS: u16 = 1 << 2;
+
+const CMD_RESET: u8 = 0x60;
+const CMD_STATUS: u8 = 0x62;
+const CMD_DATETIME: u8 = 0x64;
+const CMD_TIME: u8 = 0x66;
+const READ: u8 = 1;
+
+/// An error from the real-time clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RtcError {
+  /// The clock didn't answer the way a clock would.
+  NotPresent,
+  /// The clock gave a time that isn't valid, or [`set_datetime`] was given
+  /// one.
+  InvalidTime,
+}
+
+/// The clock's status register.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct RtcStatus(u8);
+impl RtcStatus {
+  pub_const_fn_new_zeroed!();
+  u8_bool_field!(1, frequency_irq, with_frequency_irq);
+  u8_bool_field!(3, minute_irq, with_minute_irq);
+  u8_bool_field!(5, alarm_irq, with_alarm_irq);
+  u8_bool_field!(6, hour_24, with_hour_24);
+  u8_bool_field!(7, power_failure, with_power_failure);
+}
+
+/// A date and time, as kept by the real-time clock.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DateTime {
+  /// `2000..=2099`.
+  pub year: u16,
+  /// `1..=12`.
+  pub month: u8,
+  /// `1..=31`, depending on the month.
+  pub day: u8,
+  /// `0..=6`. The clock just counts this up each day, so which day is 0 is up
+  /// to the game (the clock starts at 0 after it's reset).
+  pub weekday: u8,
+  /// `0..=23`.
+  pub hour: u8,
+  /// `0..=59`.
+  pub minute: u8,
+  /// `0..=59`.
+  pub second: u8,
+}
+impl DateTime {
+  /// The time the clock goes back to when it's reset: 2000-01-01, 0:00:00.
+  pub const RESET: Self = Self {
+    year: 2000,
+    month: 1,
+    day: 1,
+    weekday: 0,
+    hour: 0,
+    minute: 0,
+    second: 0,
+  };
+
+  /// If every field is in range (counting leap years for February).
+  #[inline]
+  #[must_use]
+  pub const fn is_valid(&self) -> bool {
+    (2000 <= self.year && self.year <= 2099)
+      && (1 <= self.month && self.month <= 12)
+      && (1 <= self.day && self.day <= days_in_month(self.year, self.month))
+      && self.weekday <= 6
+      && self.hour <= 23
+      && self.minute <= 59
+      && self.second <= 59
+  }
+}
+
+/// How many days `month` has in `year`.
+const fn days_in_month(year: u16, month: u8) -> u8 {
+  match month {
+    2 if year.is_multiple_of(4) => 29,
+    2 => 28,
+    4 | 6 | 9 | 11 => 30,
+    _ => 31,
+  }
+}
+
+/// A BCD byte's value, if both digits are `0..=9`.
+const fn from_bcd(bcd: u8) -> Option<u8> {
+  let (high, low) = (bcd >> 4, bcd & 0xF);
+  if high <= 9 && low <= 9 {
+    Some(high * 10 + low)
+  } else {
+    None
+  }
+}
+
+/// A value `0..=99` as a BCD byte.
+const fn to_bcd(value: u8) -> u8 {
+  ((value / 10) << 4) | (value % 10)
+}
+
+/// Spends a little time with SCK low, so the clock sees the edge.
+fn clock_low(sio: u16) {
+  for _ in 0..5 {
+    IO_PORT_DATA.write(sio | CS);
+  }
+}
+
+/// Runs one command: CS high, the command byte, `f` for the data, then CS low.
+fn transfer<T>(command: u8, f: impl FnOnce() -> T) -> T {
+  enable_port_reads();
+  IO_PORT_DATA.write(SCK);
+  IO_PORT_DATA.write(SCK | CS);
+  IO_PORT_DIRECTION.write(SCK | SIO | CS);
+  for i in (0..8).rev() {
+    let bit = (u16::from(command) >> i) & 1;
+    clock_low(bit << 1);
+    IO_PORT_DATA.write((bit << 1) | SCK | CS);
+  }
+  if command & READ != 0 {
+    IO_PORT_DIRECTION.write(SCK | CS);
+  }
+  let out = f();
+  IO_PORT_DATA.write(SCK);
+  IO_PORT_DATA.write(SCK);
+  out
+}
+
+/// Sends one data byte, lowest bit first.
+fn write_byte(byte: u8) {
+  for i in 0..8 {
+    let bit = (u16::from(byte) >> i) & 1;
+    clock_low(bit << 1);
+    IO_PORT_DATA.write((bit << 1) | SCK | CS);
+  }
+}
+
+/// Reads one data byte, lowest bit first.
+fn read_byte() -> u8 {
+  let mut byte = 0;
+  for _ in 0..8 {
+    clock_low(0);
+    IO_PORT_DATA.write(SCK | CS);
+    let bit = (IO_PORT_DATA.read() & SIO) >> 1;
+    byte = (byte >> 1) | ((bit as u8) << 7);
+  }
+  byte
+}
+
+/// Reads the status register.
+#[inline]
+#[must_use]
+pub fn status() -> RtcStatus {
+  RtcStatus(transfer(CMD_STATUS | READ, read_byte))
+}
+
+/// Writes the status register.
+///
+/// The [`power_failure`](RtcStatus::power_failure) bit can't be written, it's
+/// only cleared by [`reset`].
+#[inline]
+pub fn set_status(status: RtcStatus) {
+  transfer(CMD_STATUS, || write_byte(status.0));
+}
+
+/// Resets the clock: the time goes back to [`DateTime::RESET`], and the status
+/// goes back to all 0 (which is 12-hour mode).
+#[inline]
+pub fn reset() {
+  transfer(CMD_RESET, || ());
+}
+
+/// Gets the clock ready to use, giving the status from before.
+///
+/// If the clock lost power, it's reset (the old status says so with
+/// [`power_failure`](RtcStatus::power_failure)). Then it's put into 24-hour
+/// mode, which [`set_datetime`] needs ([`datetime`] reads either mode).
+///
+/// ## Failure
+/// * [`RtcError::NotPresent`] if the clock doesn't keep the 24-hour setting.
+#[inline]
+pub fn init() -> Result<RtcStatus, RtcError> {
+  let old = status();
+  if old.power_failure() {
+    reset();
+  }
+  let current = if old.power_failure() { RtcStatus::new() } else { old };
+  if !current.hour_24() {
+    set_status(current.with_hour_24(true));
+  }
+  if status().hour_24() {
+    Ok(old)
+  } else {
+    Err(RtcError::NotPresent)
+  }
+}
+
+/// Decodes the three time bytes (hour, minute, second).
+fn decode_time(bytes: [u8; 3], hour_24: bool) -> Option<(u8, u8, u8)> {
+  // Bit 7 of the hour is set in the afternoon, in both modes.
+  let pm = bytes[0] & 0x80 != 0;
+  let mut hour = from_bcd(bytes[0] & 0x3F)?;
+  if !hour_24 {
+    // 12-hour mode counts 0 to 11, with the afternoon bit.
+    if hour > 11 {
+      return None;
+    }
+    if pm {
+      hour += 12;
+    }
+  }
+  Some((hour, from_bcd(bytes[1])?, from_bcd(bytes[2])?))
+}
+
+/// Decodes the seven date and time bytes.
+fn decode_datetime(bytes: [u8; 7], hour_24: bool) -> Option<DateTime> {
+  let (hour, minute, second) =
+    decode_time([bytes[4], bytes[5], bytes[6]], hour_24)?;
+  Some(DateTime {
+    year: 2000 + u16::from(from_bcd(bytes[0])?),
+    month: from_bcd(bytes[1])?,
+    day: from_bcd(bytes[2])?,
+    weekday: bytes[3],
+    hour,
+    minute,
+    second,
+  })
+}
+
+/// Reads the date and time.
+///
+/// ## Failure
+/// * [`RtcError::InvalidTime`] if the clock gives a value that isn't valid BCD,
+///   or is out of range. This happens when the clock lost power, or there's no
+///   clock at all.
+#[inline]
+pub fn datetime() -> Result<DateTime, RtcError> {
+  let hour_24 = status().hour_24();
+  let mut bytes = [0; 7];
+  transfer(CMD_DATETIME | READ, || {
+    for byte in bytes.iter_mut() {
+      *byte = read_byte();
+    }
+  });
+  match decode_datetime(bytes, hour_24) {
+    Some(datetime) if datetime.is_valid() => Ok(datetime),
+    _ => Err(RtcError::InvalidTime),
+  }
+}
+
+/// Reads just the time, as `(hour, minute, second)`.
+///
+/// ## Failure
+/// * [`RtcError::InvalidTime`] as with [`datetime`].
+#[inline]
+pub fn time() -> Result<(u8, u8, u8), RtcError> {
+  let hour_24 = status().hour_24();
+  let mut bytes = [0; 3];
+  transfer(CMD_TIME | READ, || {
+    for byte in bytes.iter_mut() {
+      *byte = read_byte();
+    }
+  });
+  match decode_time(bytes, hour_24) {
+    Some(time @ (hour, minute, second))
+      if hour <= 23 && minute <= 59 && second <= 59 =>
+    {
+      Ok(time)
+    }
+    _ => Err(RtcError::InvalidTime),
+  }
+}
+
+/// Sets the date and time.
+///
+/// The clock has to be in 24-hour mode (see [`init`]).
+///
+/// ## Failure
+/// * [`RtcError::InvalidTime`] if `datetime` isn't valid (see
+///   [`DateTime::is_valid`]).
+#[inline]
+pub fn set_datetime(datetime: &DateTime) -> Result<(), RtcError> {
+  if !datetime.is_valid() {
+    return Err(RtcError::InvalidTime);
+  }
+  let bytes = [
+    to_bcd((datetime.year - 2000) as u8),
+    to_bcd(datetime.month),
+    to_bcd(datetime.day),
+    datetime.weekday,
+    to_bcd(datetime.hour),
+    to_bcd(datetime.minute),
+    to_bcd(datetime.second),
+  ];
+  transfer(CMD_DATETIME, || {
+    for byte in bytes {
+      write_byte(byte);
+    }
+  });
+  Ok(())
+}
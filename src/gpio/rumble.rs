@@ -0,0 +1,319 @@
+//! Rumble, from a motor in the cart or from the Game Boy Player.
+//!
+//! Carts with a rumble motor (such as WarioWare: Twisted! and Drill Dozer) run
+//! it from bit 3 of the I/O port. The GameCube's Game Boy Player can rumble
+//! the GameCube controller instead, but first the game has to find out that
+//! it's running on one (see [`detect_gb_player`]), and then keep a serial link
+//! going with it (see [`start_gb_player_link`]).
+//!
+//! [`set_rumble`] turns the rumble on or off directly. For timed rumble, call
+//! [`tick`] once per frame and use [`pulse`] (or [`pulse_with`] for a lighter
+//! rumble), which stops by itself.
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # use gba::gpio::rumble::{self, RumbleStrength};
+//! // On a hit:
+//! rumble::pulse_with(20, RumbleStrength::Medium);
+//! loop {
+//!   wait_for_vblank();
+//!   rumble::tick();
+//! }
+//! ```
+
+use super::enable_port_reads;
+use crate::{
+  interrupts::{IrqBits, IrqMutex},
+  mmio::{IO_PORT_DATA, IO_PORT_DIRECTION},
+};
+
+/// The I/O port pin that runs the rumble motor.
+const MOTOR: u16 = 1 << 3;
+
+/// How strong a timed rumble is.
+///
+/// The motor can only be on or off, so the weaker strengths turn it on for
+/// just some of the frames.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RumbleStrength {
+  /// On one frame out of four.
+  Light,
+  /// On every other frame.
+  Medium,
+  /// On every frame.
+  #[default]
+  Hard,
+}
+impl RumbleStrength {
+  /// If the motor is on during frame number `frame` of a pulse.
+  #[inline]
+  #[must_use]
+  pub const fn is_on(self, frame: u16) -> bool {
+    match self {
+      Self::Light => frame.is_multiple_of(4),
+      Self::Medium => frame.is_multiple_of(2),
+      Self::Hard => true,
+    }
+  }
+}
+
+/// The state shared by the rumble functions and [`gb_player_irq`].
+struct RumbleState {
+  /// Frames left in the current pulse.
+  frames_left: u16,
+  /// Frames done in the current pulse.
+  frame: u16,
+  strength: RumbleStrength,
+  /// If the rumble is on right now.
+  on: bool,
+  /// The Game Boy Player link, if it's been started.
+  gb_player: Option<GbPlayerLink>,
+}
+
+static STATE: IrqMutex<RumbleState> = IrqMutex::new(RumbleState {
+  frames_left: 0,
+  frame: 0,
+  strength: RumbleStrength::Hard,
+  on: false,
+  gb_player: None,
+});
+
+/// Sets the motor (and the Game Boy Player, if its link is going).
+fn apply(state: &mut RumbleState, on: bool) {
+  state.on = on;
+  enable_port_reads();
+  IO_PORT_DIRECTION.write(IO_PORT_DIRECTION.read() | MOTOR);
+  let data = IO_PORT_DATA.read();
+  IO_PORT_DATA.write(if on { data | MOTOR } else { data & !MOTOR });
+}
+
+/// Turns the rumble on or off, and stops any [`pulse`].
+#[inline]
+pub fn set_rumble(on: bool) {
+  STATE.with(|state| {
+    state.frames_left = 0;
+    apply(state, on);
+  });
+}
+
+/// Rumbles at full strength for `frames` frames.
+///
+/// This needs [`tick`] to be called once per frame.
+#[inline]
+pub fn pulse(frames: u16) {
+  pulse_with(frames, RumbleStrength::Hard);
+}
+
+/// Rumbles at `strength` for `frames` frames, replacing any pulse that's
+/// already going.
+///
+/// This needs [`tick`] to be called once per frame.
+#[inline]
+pub fn pulse_with(frames: u16, strength: RumbleStrength) {
+  STATE.with(|state| {
+    state.frames_left = frames;
+    state.frame = 0;
+    state.strength = strength;
+    apply(state, frames > 0);
+  });
+}
+
+/// Moves the current pulse on by one frame, and stops the rumble when it's
+/// done.
+///
+/// Call this once per frame, from the main loop or from the vblank handler.
+/// It does nothing when there's no pulse going, so it doesn't undo
+/// [`set_rumble`].
+#[inline]
+pub fn tick() {
+  STATE.with(|state| {
+    if state.frames_left == 0 {
+      return;
+    }
+    state.frames_left -= 1;
+    state.frame = state.frame.wrapping_add(1);
+    let on = state.frames_left > 0 && state.strength.is_on(state.frame);
+    apply(state, on);
+  });
+}
+
+/// If the rumble is on right now.
+#[inline]
+#[must_use]
+pub fn is_rumbling() -> bool {
+  STATE.with(|state| state.on)
+}
+
+/// How many frames [`detect_gb_player`] waits by default.
+///
+/// The Game Boy Player checks the screen once per frame, and answers within a
+/// few frames of seeing the logo. Official games show the logo for longer, but
+/// there's nothing to gain from that.
+pub const GB_PLAYER_DETECT_FRAMES: u32 = 8;
+
+/// Checks if the game is running on a Game Boy Player.
+///
+/// The Game Boy Player looks for its own logo on the screen (the exact tiles
+/// and palette of the official splash screen), and if it sees it, it answers
+/// by showing all four directions of the d-pad as held, which a real d-pad
+/// can't do. So before calling this, show the logo (this crate can't include
+/// it). This then waits up to `frames` vblanks (see
+/// [`GB_PLAYER_DETECT_FRAMES`]) for the answer, and returns as soon as it's
+/// seen.
+///
+/// Without a Game Boy Player this takes the full `frames`, so pass 0 to skip
+/// the check entirely (for example, if a save says the check failed before).
+#[inline]
+#[must_use]
+pub fn detect_gb_player(frames: u32) -> bool {
+  use crate::{mmio::KEYINPUT, video::spin_until_vblank};
+  for _ in 0..frames {
+    spin_until_vblank();
+    let keys = KEYINPUT.read();
+    if keys.up() && keys.down() && keys.left() && keys.right() {
+      return true;
+    }
+  }
+  false
+}
+
+/// The words the Game Boy Player sends, in order. The last one repeats until
+/// the whole thing starts over.
+const GB_PLAYER_SENDS: [u32; 13] = [
+  0x0000_494E,
+  0x0000_494E,
+  0xB6B1_494E,
+  0xB6B1_544E,
+  0xABB1_544E,
+  0xABB1_4E45,
+  0xB1BA_4E45,
+  0xB1BA_4F44,
+  0xB0BB_4F44,
+  0xB0BB_8002,
+  0x1000_0010,
+  0x2000_0013,
+  0x3000_0003,
+];
+
+/// The word the GBA sends with each of [`GB_PLAYER_SENDS`]. After the
+/// handshake, the GBA sends a rumble command every time instead.
+const GB_PLAYER_REPLIES: [u32; 12] = [
+  0x0000_0000,
+  0x494E_B6B1,
+  0x494E_B6B1,
+  0x544E_B6B1,
+  0x544E_ABB1,
+  0x4E45_ABB1,
+  0x4E45_B1BA,
+  0x4F44_B1BA,
+  0x4F44_B0BB,
+  0x8000_B0BB,
+  0x1000_0010,
+  0x2000_0013,
+];
+
+const GB_PLAYER_RUMBLE_ON: u32 = 0x4000_0026;
+const GB_PLAYER_RUMBLE_OFF: u32 = 0x4000_0004;
+
+/// Where the Game Boy Player link is in its sequence.
+struct GbPlayerLink {
+  /// The index (in [`GB_PLAYER_SENDS`]) of the next transfer.
+  next: usize,
+}
+impl GbPlayerLink {
+  /// The word to send in the next transfer.
+  const fn reply(&self, rumble: bool) -> u32 {
+    if self.next < GB_PLAYER_REPLIES.len() {
+      GB_PLAYER_REPLIES[self.next]
+    } else if rumble {
+      GB_PLAYER_RUMBLE_ON
+    } else {
+      GB_PLAYER_RUMBLE_OFF
+    }
+  }
+
+  /// Moves on after a transfer where the Game Boy Player sent `received`.
+  fn advance(&mut self, received: u32) {
+    let last = GB_PLAYER_SENDS.len() - 1;
+    let expected = GB_PLAYER_SENDS[self.next.min(last)];
+    self.next = if received == expected {
+      (self.next + 1).min(last + 1)
+    } else if received == GB_PLAYER_SENDS[0] {
+      // It started over.
+      1
+    } else {
+      self.next
+    };
+  }
+}
+
+/// Starts the serial link that the Game Boy Player uses for rumble.
+///
+/// This is normal mode, with 32-bit transfers clocked by the Game Boy Player,
+/// so it takes over the link port (it isn't the JOY Bus mode that the GameCube
+/// uses with a link cable). Set the serial interrupt to call [`gb_player_irq`]
+/// first. After this, the rumble functions control the GameCube controller's
+/// rumble as well as the cart's. Without a Game Boy Player nothing ever clocks
+/// the link, so this is harmless.
+#[inline]
+pub fn start_gb_player_link() {
+  use crate::{
+    mmio::{RCNT, SIOCNT_NORMAL, SIODATA32},
+    sio::{normal::NormalControl, LinkPortControl},
+  };
+  let reply = STATE.with(|state| {
+    let link = GbPlayerLink { next: 0 };
+    let reply = link.reply(state.on);
+    state.gb_player = Some(link);
+    reply
+  });
+  RCNT.write(LinkPortControl::new());
+  let control =
+    NormalControl::new().with_transfer_32bit(true).with_irq_enabled(true);
+  SIOCNT_NORMAL.write(control);
+  SIODATA32.write(reply);
+  SIOCNT_NORMAL.write(control.with_start(true));
+}
+
+/// Stops the Game Boy Player link, leaving the link port in normal mode with
+/// nothing going.
+#[inline]
+pub fn stop_gb_player_link() {
+  use crate::{mmio::SIOCNT_NORMAL, sio::normal::NormalControl};
+  STATE.with(|state| state.gb_player = None);
+  SIOCNT_NORMAL.write(NormalControl::new());
+}
+
+/// The serial interrupt handler for the Game Boy Player link.
+///
+/// Set this with [`set_handler`](crate::interrupts::set_handler), or call it
+/// from your own handler when the `serial` bit is set. Each transfer it
+/// checks where the link is in the handshake, sets the next word to send
+/// (which is the rumble command once the handshake is done), and gets ready
+/// for the next transfer.
+#[link_section = ".iwram.gb_player_irq"]
+#[inline(never)]
+pub extern "C" fn gb_player_irq(_: IrqBits) {
+  use crate::mmio::{SIOCNT_NORMAL, SIODATA32};
+  let received = SIODATA32.read();
+  let reply = STATE.with_in_handler(|state| {
+    let on = state.on;
+    let link = state.gb_player.as_mut()?;
+    link.advance(received);
+    Some(link.reply(on))
+  });
+  if let Some(reply) = reply {
+    SIODATA32.write(reply);
+    SIOCNT_NORMAL.apply(|control| *control = control.with_start(true));
+  }
+}
+
+/// Turns the Game Boy Player's rumble on or off.
+///
+/// This is the same as [`set_rumble`], which also drives the cart's motor
+/// (when there isn't one, that does nothing). The new setting is sent with
+/// the next transfer, once the handshake is done.
+#[inline]
+pub fn gb_player_rumble(on: bool) {
+  set_rumble(on);
+}
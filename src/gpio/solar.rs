@@ -0,0 +1,116 @@
+//! The solar sensor of the Boktai carts.
+//!
+//! The sensor has an 8-bit counter and a light level, and it compares the two.
+//! It's on all four of the I/O port pins: bit 0 clocks the counter up, bit 1
+//! resets it to 0, bit 2 is chip select (low selects the sensor), and bit 3 is
+//! the sensor's output flag, which goes high once the counter reaches the
+//! light level. So a reading resets the counter and then clocks it up until
+//! the flag flips.
+//!
+//! The count is the sensor's raw value, where *lower is brighter*: the more
+//! light there is, the sooner the flag flips. How the raw value maps to actual
+//! sunlight is different for each sensor, so games let the player calibrate it
+//! (see [`SolarCalibration`]).
+//!
+//! ```no_run
+//! # use gba::gpio::solar::{self, SolarCalibration};
+//! let calibration = SolarCalibration::new(0xE8, 0x50);
+//! if let Some(raw) = solar::read_luminance() {
+//!   let level = calibration.level(raw);
+//! }
+//! ```
+//!
+//! mGBA emulates the sensor with a slider for the light level, once the game
+//! overrides are set to have one.
+
+use super::enable_port_reads;
+use crate::mmio::{IO_PORT_DATA, IO_PORT_DIRECTION};
+
+const CLK: u16 = 1 << 0;
+const RST: u16 = 1 << 1;
+/// High is *not* selected.
+const CS: u16 = 1 << 2;
+const FLAG: u16 = 1 << 3;
+
+/// Writes the pins a few times over, so the sensor sees the change.
+fn set_pins(pins: u16) {
+  for _ in 0..4 {
+    IO_PORT_DATA.write(pins);
+  }
+}
+
+/// Reads the sensor's raw value, where lower is brighter.
+///
+/// This takes up to 256 clocks of the counter, which is well under a
+/// scanline.
+///
+/// ## Failure
+/// * `None` if the flag never gets set, even when the counter reaches its top.
+///   That doesn't happen with a sensor, so this means there isn't one.
+#[inline]
+#[must_use]
+pub fn read_luminance() -> Option<u8> {
+  enable_port_reads();
+  IO_PORT_DIRECTION.write(CLK | RST | CS);
+  set_pins(RST);
+  set_pins(0);
+  let mut found = None;
+  for count in 0..=u8::MAX {
+    if IO_PORT_DATA.read() & FLAG != 0 {
+      found = Some(count);
+      break;
+    }
+    set_pins(CLK);
+    set_pins(0);
+  }
+  // Leave the sensor reset and not selected.
+  set_pins(RST | CS);
+  found
+}
+
+/// Maps raw sensor readings to a `0..=10` light level, from the readings the
+/// player gave for dark and bright.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SolarCalibration {
+  /// The raw reading for no light, which is level 0.
+  pub dark: u8,
+  /// The raw reading for full light, which is level 10.
+  pub bright: u8,
+}
+impl SolarCalibration {
+  /// The highest level that [`level`](Self::level) gives.
+  pub const MAX_LEVEL: u8 = 10;
+
+  /// Makes a calibration from the dark and bright readings.
+  ///
+  /// Since lower readings are brighter, `bright` is normally lower than
+  /// `dark`.
+  #[inline]
+  #[must_use]
+  pub const fn new(dark: u8, bright: u8) -> Self {
+    Self { dark, bright }
+  }
+
+  /// The light level of a raw reading, `0..=10`.
+  ///
+  /// Readings darker than `dark` are level 0, and readings brighter than
+  /// `bright` are level 10. Levels round down, so level 10 is only reached at
+  /// `bright` itself. If `bright` isn't lower than `dark`, there's no range to
+  /// map, so readings at or below `bright` are 10 and the rest are 0.
+  #[inline]
+  #[must_use]
+  pub const fn level(self, raw: u8) -> u8 {
+    if self.dark <= self.bright {
+      return if raw <= self.bright { Self::MAX_LEVEL } else { 0 };
+    }
+    if raw >= self.dark {
+      0
+    } else if raw <= self.bright {
+      Self::MAX_LEVEL
+    } else {
+      let range = (self.dark - self.bright) as u16;
+      let light = (self.dark - raw) as u16;
+      (light * Self::MAX_LEVEL as u16 / range) as u8
+    }
+  }
+}
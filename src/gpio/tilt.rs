@@ -0,0 +1,161 @@
+//! The tilt sensor of WarioWare: Twisted!, Yoshi Topsy-Turvy, and others.
+//!
+//! Unlike the other hardware in this module, the tilt sensor isn't on the I/O
+//! port. It's a two-axis accelerometer mapped into the SRAM addresses, so it
+//! has to be accessed one byte at a time, with the SRAM wait states set to 8
+//! cycles (the functions here handle both). Writing `0x55` to `0x0E00_8000`
+//! and then `0xAA` to `0x0E00_8100` starts a sample, and bit 7 of
+//! `0x0E00_8300` goes high when it's ready. Each axis is 12 bits, with the low
+//! 8 bits at `0x0E00_8200` (x) or `0x0E00_8400` (y), and the high 4 bits at
+//! `0x0E00_8300` (x) or `0x0E00_8500` (y).
+//!
+//! A sample is ready well within a frame, so the usual way is to take one
+//! sample per frame: read the last one and start the next.
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # use gba::gpio::tilt;
+//! tilt::calibrate();
+//! tilt::start_sample();
+//! loop {
+//!   wait_for_vblank();
+//!   if tilt::is_ready() {
+//!     let (x, y) = tilt::read_centered();
+//!     tilt::start_sample();
+//!   }
+//! }
+//! ```
+//!
+//! mGBA emulates the sensor with the host's controls (such as a gamepad's
+//! stick, or a phone's accelerometer), once the game overrides are set to
+//! have one.
+
+use crate::{
+  gba_cell::GbaCell,
+  mmio::{Safe, VolAddress},
+};
+
+// Safety: these are the sensor's addresses, and they're fine to access one
+// byte at a time.
+const START_1: VolAddress<u8, (), Safe> =
+  unsafe { VolAddress::new(0x0E00_8000) };
+const START_2: VolAddress<u8, (), Safe> =
+  unsafe { VolAddress::new(0x0E00_8100) };
+const X_LOW: VolAddress<u8, Safe, ()> = unsafe { VolAddress::new(0x0E00_8200) };
+const X_HIGH: VolAddress<u8, Safe, ()> =
+  unsafe { VolAddress::new(0x0E00_8300) };
+const Y_LOW: VolAddress<u8, Safe, ()> = unsafe { VolAddress::new(0x0E00_8400) };
+const Y_HIGH: VolAddress<u8, Safe, ()> =
+  unsafe { VolAddress::new(0x0E00_8500) };
+
+/// The ready bit, in [`X_HIGH`].
+const READY: u8 = 1 << 7;
+
+/// How many times [`calibrate`] checks for a sample before giving up.
+const CALIBRATE_POLLS: u32 = 10_000;
+
+/// One reading of the sensor.
+///
+/// Each axis is `0..=0xFFF`, and a cart held flat reads about
+/// [`TiltSample::NEUTRAL`] on both. Tilting the cart moves each axis a few
+/// hundred either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TiltSample {
+  /// Left and right.
+  pub x: u16,
+  /// Towards and away from the player.
+  pub y: u16,
+}
+impl TiltSample {
+  /// About what a flat cart reads, with a real sensor and in mGBA.
+  ///
+  /// This is the neutral position from before [`calibrate`] is called.
+  pub const NEUTRAL: Self = Self { x: 0x3A0, y: 0x3A0 };
+
+  /// Packs the sample into a `u32`, so it fits in a [`GbaCell`].
+  const fn to_u32(self) -> u32 {
+    (self.x as u32) | ((self.y as u32) << 16)
+  }
+
+  /// Unpacks a sample packed with [`to_u32`](Self::to_u32).
+  const fn from_u32(packed: u32) -> Self {
+    Self { x: packed as u16, y: (packed >> 16) as u16 }
+  }
+}
+
+/// The neutral position that [`read_centered`] subtracts.
+static NEUTRAL: GbaCell<u32> = GbaCell::new(TiltSample::NEUTRAL.to_u32());
+
+/// Starts taking a sample.
+///
+/// Check [`is_ready`] before reading it with [`read`].
+#[inline]
+pub fn start_sample() {
+  crate::save::use_slow_sram_waits();
+  START_1.write(0x55);
+  START_2.write(0xAA);
+}
+
+/// If the sample from [`start_sample`] is ready.
+#[inline]
+#[must_use]
+pub fn is_ready() -> bool {
+  crate::save::use_slow_sram_waits();
+  X_HIGH.read() & READY != 0
+}
+
+/// Reads the last sample.
+///
+/// This doesn't wait for the sample to be ready, so check [`is_ready`] first.
+#[inline]
+#[must_use]
+pub fn read() -> TiltSample {
+  crate::save::use_slow_sram_waits();
+  let x = u16::from(X_LOW.read()) | (u16::from(X_HIGH.read() & 0xF) << 8);
+  let y = u16::from(Y_LOW.read()) | (u16::from(Y_HIGH.read() & 0xF) << 8);
+  TiltSample { x, y }
+}
+
+/// Reads the last sample, as how far each axis is from the neutral position
+/// (see [`calibrate`]).
+///
+/// Like [`read`], this doesn't wait for the sample to be ready.
+#[inline]
+#[must_use]
+pub fn read_centered() -> (i16, i16) {
+  let sample = read();
+  let neutral = neutral();
+  (sample.x as i16 - neutral.x as i16, sample.y as i16 - neutral.y as i16)
+}
+
+/// The neutral position that [`read_centered`] uses.
+#[inline]
+#[must_use]
+pub fn neutral() -> TiltSample {
+  TiltSample::from_u32(NEUTRAL.read())
+}
+
+/// Sets the neutral position that [`read_centered`] uses, such as one that was
+/// kept in the save.
+#[inline]
+pub fn set_neutral(neutral: TiltSample) {
+  NEUTRAL.write(neutral.to_u32());
+}
+
+/// Takes a sample and uses it as the neutral position, giving the sample.
+///
+/// Call this while the player holds the cart how they want to play (games
+/// usually ask them to hold it flat, then press a button). This waits for the
+/// sample, so it takes a little while.
+///
+/// ## Failure
+/// * `None` if the sample is never ready, which means there's no sensor. The
+///   neutral position is left alone.
+#[inline]
+pub fn calibrate() -> Option<TiltSample> {
+  start_sample();
+  (0..CALIBRATE_POLLS).find(|_| is_ready())?;
+  let sample = read();
+  set_neutral(sample);
+  Some(sample)
+}
@@ -43,7 +43,484 @@ impl IrqBits {
   pub const fn to_u16(self) -> u16 {
     self.0
   }
+
+  /// Makes bits from a raw `u16`.
+  ///
+  /// Bits 14 and 15 don't mean anything and are cleared.
+  #[inline]
+  #[must_use]
+  pub const fn from_u16(bits: u16) -> Self {
+    Self(bits & 0x3FFF)
+  }
+
+  /// The bits set in either `self` or `other`.
+  #[inline]
+  #[must_use]
+  pub const fn union(self, other: Self) -> Self {
+    Self(self.0 | other.0)
+  }
+
+  /// The bits set in both `self` and `other`.
+  #[inline]
+  #[must_use]
+  pub const fn intersection(self, other: Self) -> Self {
+    Self(self.0 & other.0)
+  }
+
+  /// The bits set in `self` but not in `other`.
+  #[inline]
+  #[must_use]
+  pub const fn difference(self, other: Self) -> Self {
+    Self(self.0 & !other.0)
+  }
+
+  /// If every bit set in `other` is also set in `self`.
+  #[inline]
+  #[must_use]
+  pub const fn contains(self, other: Self) -> bool {
+    (self.0 & other.0) == other.0
+  }
+
+  /// If no bits are set.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(self) -> bool {
+    self.0 == 0
+  }
+}
+
+// The operator traits can't be const yet, so each one also has a const method
+// above.
+impl core::ops::BitOr for IrqBits {
+  type Output = Self;
+  #[inline]
+  fn bitor(self, rhs: Self) -> Self {
+    self.union(rhs)
+  }
+}
+impl core::ops::BitOrAssign for IrqBits {
+  #[inline]
+  fn bitor_assign(&mut self, rhs: Self) {
+    *self = self.union(rhs);
+  }
+}
+impl core::ops::BitAnd for IrqBits {
+  type Output = Self;
+  #[inline]
+  fn bitand(self, rhs: Self) -> Self {
+    self.intersection(rhs)
+  }
+}
+impl core::ops::BitAndAssign for IrqBits {
+  #[inline]
+  fn bitand_assign(&mut self, rhs: Self) {
+    *self = self.intersection(rhs);
+  }
+}
+
+/// Turns on the interrupts in `bits`, leaving the others in
+/// [`IE`](crate::mmio::IE) as they were.
+///
+/// The read-modify-write of `IE` happens with [`IME`](crate::mmio::IME) off, so
+/// an interrupt handler that also changes `IE` can't have its change lost.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn enable_irqs(bits: IrqBits) {
+  use crate::mmio::IE;
+  irq_free(|| IE.apply(|ie| *ie = ie.union(bits)));
+}
+
+/// Turns off the interrupts in `bits`, leaving the others in
+/// [`IE`](crate::mmio::IE) as they were.
+///
+/// Like [`enable_irqs`], this happens with [`IME`](crate::mmio::IME) off.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn disable_irqs(bits: IrqBits) {
+  use crate::mmio::IE;
+  irq_free(|| IE.apply(|ie| *ie = ie.difference(bits)));
+}
+
+/// Acknowledges the interrupts in `bits`.
+///
+/// [`IF`](crate::mmio::IF) is "write 1 to clear": writing a bit clears it, and
+/// writing 0 leaves it alone. That means reading `IF` and writing it back
+/// clears *everything* that was pending, so don't do that. This writes only
+/// `bits` to `IF`.
+///
+/// It also marks `bits` in [`BIOS_IF`](crate::mmio::BIOS_IF), which is what
+/// [`IntrWait`](crate::bios::IntrWait) and
+/// [`VBlankIntrWait`](crate::bios::VBlankIntrWait) check to see if the
+/// interrupt they're waiting on has happened. Without that those functions
+/// would never return.
+///
+/// The assembly runtime's interrupt handler already does all of this before
+/// any Rust handler runs, so you only need this if you handle interrupts some
+/// other way (eg: polling `IF` with interrupts off).
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn acknowledge_irqs(bits: IrqBits) {
+  use crate::mmio::{BIOS_IF, IF};
+  irq_free(|| {
+    IF.write(bits);
+    BIOS_IF.apply(|b| *b = b.union(bits));
+  });
+}
+
+/// One of the fourteen interrupt sources.
+///
+/// The value of each variant is its bit position within [`IrqBits`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+#[allow(missing_docs)]
+pub enum Interrupt {
+  VBlank = 0,
+  HBlank = 1,
+  VCounter = 2,
+  Timer0 = 3,
+  Timer1 = 4,
+  Timer2 = 5,
+  Timer3 = 6,
+  Serial = 7,
+  Dma0 = 8,
+  Dma1 = 9,
+  Dma2 = 10,
+  Dma3 = 11,
+  Keypad = 12,
+  Gamepak = 13,
+}
+impl Interrupt {
+  /// Every interrupt source, in bit order.
+  pub const ALL: [Self; 14] = [
+    Self::VBlank,
+    Self::HBlank,
+    Self::VCounter,
+    Self::Timer0,
+    Self::Timer1,
+    Self::Timer2,
+    Self::Timer3,
+    Self::Serial,
+    Self::Dma0,
+    Self::Dma1,
+    Self::Dma2,
+    Self::Dma3,
+    Self::Keypad,
+    Self::Gamepak,
+  ];
+
+  /// The [`IrqBits`] with only this source's bit set.
+  #[inline]
+  #[must_use]
+  pub const fn bits(self) -> IrqBits {
+    IrqBits(1 << (self as u16))
+  }
 }
 
-// TODO: might want to support bit ops. But it's not super important right now
-// since they can't be implented as const traits yet anyway.
+/// The handlers called by [`irq_table_dispatch`], indexed by [`Interrupt`].
+#[cfg(feature = "on_gba")]
+static IRQ_TABLE: [crate::gba_cell::GbaCell<Option<IrqFn>>; 14] =
+  [const { crate::gba_cell::GbaCell::new(None) }; 14];
+
+/// Runs `f` with [`IME`](crate::mmio::IME) off, then restores it.
+///
+/// No interrupt handler can run during `f`, so this is a critical section for
+/// sharing data with handlers (see [`IrqMutex`]). `IME` is put back to
+/// whatever it was before rather than always being turned on, so calls can be
+/// nested, and calling this from inside a handler is fine too.
+///
+/// Interrupts that happen during `f` aren't lost: they stay flagged in
+/// [`IF`](crate::mmio::IF) and are handled as soon as `IME` is back on. Still,
+/// keep `f` short so that time-critical interrupts (such as hblank) aren't
+/// delayed.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn irq_free<T>(f: impl FnOnce() -> T) -> T {
+  use crate::mmio::IME;
+  let ime = IME.read();
+  IME.write(false);
+  let out = f();
+  IME.write(ime);
+  out
+}
+
+/// Sets the function that [`irq_table_dispatch`] calls for `source`.
+///
+/// This also sets `source`'s bit in [`IE`](crate::mmio::IE). The device itself
+/// still has to be told to send the interrupt (eg: the `irq_vblank` bit of
+/// [`DISPSTAT`](crate::mmio::DISPSTAT), or [`KEYCNT`](crate::mmio::KEYCNT)),
+/// and [`IME`](crate::mmio::IME) has to be on.
+///
+/// The table and `IE` are updated with `IME` off, so this can't race with
+/// the interrupt handler.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn set_handler(source: Interrupt, handler: IrqFn) {
+  use crate::mmio::IE;
+  irq_free(|| {
+    IRQ_TABLE[source as usize].write(Some(handler));
+    IE.apply(|bits| bits.0 |= source.bits().0);
+  });
+}
+
+/// Removes the handler for `source`, and clears its bit in
+/// [`IE`](crate::mmio::IE).
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn clear_handler(source: Interrupt) {
+  use crate::mmio::IE;
+  irq_free(|| {
+    IRQ_TABLE[source as usize].write(None);
+    IE.apply(|bits| bits.0 &= !source.bits().0);
+  });
+}
+
+crate::iwram_code! {
+  /// An interrupt handler that calls the handlers set with [`set_handler`].
+  ///
+  /// Assign this to [`RUST_IRQ_HANDLER`](crate::RUST_IRQ_HANDLER) to use the
+  /// handler table:
+  ///
+  /// ```no_run
+  /// # use gba::prelude::*;
+  /// RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  /// ```
+  ///
+  /// Each source with a bit set in `bits` has its handler called, in bit order
+  /// (so vblank first). Every handler gets the full `bits` value.
+  ///
+  /// ## How This Fits With The Runtime
+  ///
+  /// The assembly runtime's handler is the "master" handler that the BIOS
+  /// calls. It acknowledges the interrupt in both [`IF`](crate::mmio::IF) and
+  /// the BIOS's `IntrWait` flags *before* calling into Rust, which is the order
+  /// GBATEK recommends: if the same source fires again while its handler is
+  /// running, that new edge gets flagged and is handled right after, instead of
+  /// being cleared along with the old one. Handlers run with further interrupts
+  /// masked, so they won't be interrupted themselves.
+  ///
+  /// This is ARM code in IWRAM (see [`iwram_code!`](crate::iwram_code)), as is
+  /// the runtime's handler, so that dispatching is quick.
+  #[cfg(feature = "on_gba")]
+  pub extern "C" fn irq_table_dispatch(bits: IrqBits) {
+    for source in Interrupt::ALL {
+      if bits.0 & source.bits().0 == 0 {
+        continue;
+      }
+      if let Some(handler) = IRQ_TABLE[source as usize].read() {
+        unsafe { handler(bits) };
+      }
+    }
+  }
+}
+
+/// Sets the CPU's IRQ mask bit (in `CPSR`), returning the old setting.
+#[cfg(feature = "on_gba")]
+#[instruction_set(arm::a32)]
+fn swap_cpu_irq_masked(masked: bool) -> bool {
+  let old: u32;
+  unsafe {
+    core::arch::asm! {
+      "mrs {old}, CPSR",
+      "bic {new}, {old}, #0x80",
+      "orr {new}, {new}, {masked}, LSL #7",
+      "msr CPSR_c, {new}",
+      old = out(reg) old,
+      new = out(reg) _,
+      masked = in(reg) u32::from(masked),
+      options(nomem, nostack, preserves_flags)
+    }
+  }
+  (old & 0x80) != 0
+}
+
+/// Lets other interrupts interrupt the current interrupt handler.
+///
+/// Normally a handler runs with all further interrupts blocked, so a slow
+/// handler (such as mixing audio in a timer interrupt) will delay a
+/// time-critical one (such as an hblank effect). Calling this in the slow
+/// handler lets the interrupts in `allowed` run in the middle of it:
+/// * [`IE`](crate::mmio::IE) is limited to just the sources that are in both
+///   `allowed` and `IE`.
+/// * [`IME`](crate::mmio::IME) is turned on.
+/// * The CPU's own IRQ mask bit is cleared.
+///
+/// All of that is undone when the returned guard is dropped, so keep it
+/// alive for the slow part of the handler:
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// extern "C" fn slow_timer_handler(_: IrqBits) {
+///   let _nesting = allow_nesting(IrqBits::HBLANK);
+///   // mix audio, etc.
+/// }
+/// ```
+///
+/// The assembly runtime saves the registers that a nested interrupt would
+/// otherwise overwrite, so this is all that's needed. Each level of nesting
+/// uses some of the (small) IRQ stack, so don't allow the interrupt that's
+/// currently being handled, or it could nest without limit if it's slow
+/// enough.
+///
+/// Outside of an interrupt handler this is harmless, though not useful: the
+/// guard puts things back the way they were when it's dropped.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn allow_nesting(allowed: IrqBits) -> NestingGuard {
+  use crate::mmio::{IE, IME};
+  let ie = IE.read();
+  let ime = IME.read();
+  IE.write(ie.intersection(allowed));
+  IME.write(true);
+  let cpu_irq_masked = swap_cpu_irq_masked(false);
+  NestingGuard { ie, ime, cpu_irq_masked }
+}
+
+/// Undoes [`allow_nesting`] when dropped.
+#[cfg(feature = "on_gba")]
+#[derive(Debug)]
+#[must_use = "nesting is turned back off as soon as this is dropped"]
+pub struct NestingGuard {
+  ie: IrqBits,
+  ime: bool,
+  cpu_irq_masked: bool,
+}
+#[cfg(feature = "on_gba")]
+impl Drop for NestingGuard {
+  #[inline]
+  fn drop(&mut self) {
+    use crate::mmio::{IE, IME};
+    swap_cpu_irq_masked(true);
+    IE.write(self.ie);
+    IME.write(self.ime);
+    swap_cpu_irq_masked(self.cpu_irq_masked);
+  }
+}
+
+/// A value shared between the main program and interrupt handlers.
+///
+/// The GBA has no atomic read-modify-write instructions, so the only way to
+/// safely share data that's bigger than a register (or that needs updating
+/// in more than one step) is to keep interrupts from running while it's in
+/// use. This type only gives out `&mut T` inside a closure, and:
+/// * [`with`](Self::with) runs the closure inside [`irq_free`], for use from
+///   the main program.
+/// * [`with_in_handler`](Self::with_in_handler) skips turning off `IME`, for
+///   use from inside an interrupt handler, where interrupts are already masked.
+///
+/// Both also track if the value is already borrowed, and panic rather than
+/// give out two `&mut T` at once (eg: calling `with` inside `with` on the same
+/// mutex, or a nested interrupt handler touching a value that the handler it
+/// interrupted was using).
+///
+/// For values that fit in a register and only need plain reads and writes,
+/// [`GbaCell`](crate::gba_cell::GbaCell) is simpler and cheaper.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// static FRAME_COUNT: IrqMutex<u32> = IrqMutex::new(0);
+///
+/// extern "C" fn on_vblank(_: IrqBits) {
+///   FRAME_COUNT.with_in_handler(|count| *count += 1);
+/// }
+///
+/// fn frames() -> u32 {
+///   FRAME_COUNT.with(|count| *count)
+/// }
+/// ```
+#[cfg(feature = "on_gba")]
+pub struct IrqMutex<T> {
+  borrowed: core::cell::Cell<bool>,
+  value: core::cell::UnsafeCell<T>,
+}
+#[cfg(feature = "on_gba")]
+impl<T> core::fmt::Debug for IrqMutex<T> {
+  #[inline]
+  fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+    f.debug_struct("IrqMutex").finish_non_exhaustive()
+  }
+}
+// Safety: all access goes through `borrow`, which makes sure only one `&mut T`
+// exists at a time. Interrupt handlers are the only other "thread", and they
+// always run to completion before the code they interrupted resumes.
+#[cfg(feature = "on_gba")]
+unsafe impl<T: Send> Sync for IrqMutex<T> {}
+#[cfg(feature = "on_gba")]
+impl<T> IrqMutex<T> {
+  /// Wraps a value.
+  #[inline]
+  #[must_use]
+  pub const fn new(value: T) -> Self {
+    Self {
+      borrowed: core::cell::Cell::new(false),
+      value: core::cell::UnsafeCell::new(value),
+    }
+  }
+
+  /// Runs `f` on the value, with interrupts off.
+  ///
+  /// ## Panics
+  /// * If the value is already borrowed.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn with<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+    irq_free(|| self.borrow(f))
+  }
+
+  /// Runs `f` on the value, without touching [`IME`](crate::mmio::IME).
+  ///
+  /// This is meant for interrupt handlers, which already run with further
+  /// interrupts masked (unless they use [`allow_nesting`]). It's not unsound to
+  /// call it elsewhere, but then a handler that uses the same mutex could
+  /// interrupt `f`, find the value borrowed, and panic.
+  ///
+  /// ## Panics
+  /// * If the value is already borrowed.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn with_in_handler<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+    self.borrow(f)
+  }
+
+  /// Gets the value directly, since `&mut self` means no one else has it.
+  #[inline]
+  pub fn get_mut(&mut self) -> &mut T {
+    self.value.get_mut()
+  }
+
+  /// Unwraps the value.
+  #[inline]
+  pub fn into_inner(self) -> T {
+    self.value.into_inner()
+  }
+
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  fn borrow<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+    // An interrupt between the check and the set is fine: any handler that
+    // runs then is finished (and has cleared the flag again) before we
+    // continue.
+    assert!(!self.borrowed.get(), "IrqMutex already borrowed");
+    self.borrowed.set(true);
+    let _release = Release(&self.borrowed);
+    f(unsafe { &mut *self.value.get() })
+  }
+}
+
+/// Clears an [`IrqMutex`]'s borrow flag when dropped, so that it's cleared
+/// even if the closure doesn't return normally.
+#[cfg(feature = "on_gba")]
+struct Release<'a>(&'a core::cell::Cell<bool>);
+#[cfg(feature = "on_gba")]
+impl Drop for Release<'_> {
+  #[inline]
+  fn drop(&mut self) {
+    self.0.set(false);
+  }
+}
+#[cfg(feature = "on_gba")]
+impl<T: Default> Default for IrqMutex<T> {
+  #[inline]
+  fn default() -> Self {
+    Self::new(T::default())
+  }
+}
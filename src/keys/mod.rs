@@ -0,0 +1,766 @@
+//! Module for interfacing with the device's button inputs.
+//!
+//! The GBA has two primary face buttons (A and B), two secondary face buttons
+//! (Select and Start), a 4-way directional pad ("D-pad"), and two shoulder
+//! buttons (L and R).
+//!
+//! To get the state of all the buttons just read from
+//! [`KEYINPUT`](crate::mmio::KEYINPUT). For consistency, you should usually
+//! read the buttons only once per frame. Then use that same data for all user
+//! input considerations across that entire frame. Otherwise, small fluctuations
+//! in pressure can cause inconsistencies in the reading during a frame.
+//!
+//! In addition to simply providing inputs, the buttons can also trigger a
+//! hardware interrupt. Set the desired set of buttons that will trigger a key
+//! interrupt with [`KEYCNT`](crate::mmio::KEYCNT), and when that button
+//! combination is pressed the key interrupt will be fired. Key interrupts
+//! aren't a good fit for standard inputs, but as a way to provide a single
+//! extra special input it works okay. For example, this is generally how games
+//! with a "soft reset" button combination do that. The key interrupt handler
+//! sets a "reset requested" flag when the key interrupt occurs, and then the
+//! main game loop checks the flag each frame and performs a soft reset instead
+//! of the normal game simulation when the flag is set.
+
+use crate::{
+  macros::{pub_const_fn_new_zeroed, u16_bool_field},
+  time::FrameInstant,
+};
+use core::{fmt, ops};
+
+pub mod replay;
+
+/// One of the GBA's ten keys.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum Key {
+  A = 0,
+  B = 1,
+  Select = 2,
+  Start = 3,
+  Right = 4,
+  Left = 5,
+  Up = 6,
+  Down = 7,
+  R = 8,
+  L = 9,
+}
+impl Key {
+  /// All of the keys, in bit order.
+  pub const ALL: [Self; 10] = [
+    Self::A,
+    Self::B,
+    Self::Select,
+    Self::Start,
+    Self::Right,
+    Self::Left,
+    Self::Up,
+    Self::Down,
+    Self::R,
+    Self::L,
+  ];
+
+  /// The bit for this key within the key registers.
+  #[inline]
+  #[must_use]
+  pub const fn mask(self) -> u16 {
+    1 << (self as u16)
+  }
+
+  /// The key's name, in all caps.
+  #[inline]
+  #[must_use]
+  pub const fn name(self) -> &'static str {
+    match self {
+      Self::A => "A",
+      Self::B => "B",
+      Self::Select => "SELECT",
+      Self::Start => "START",
+      Self::Right => "RIGHT",
+      Self::Left => "LEFT",
+      Self::Up => "UP",
+      Self::Down => "DOWN",
+      Self::R => "R",
+      Self::L => "L",
+    }
+  }
+}
+impl fmt::Display for Key {
+  #[inline]
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(self.name())
+  }
+}
+
+/// A direction along one axis of the D-pad.
+///
+/// The "plus" direction is the one that increases screen coordinates, so Right
+/// along the column axis and Down along the row axis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(i8)]
+pub enum TriBool {
+  Minus = -1,
+  #[default]
+  Neutral = 0,
+  Plus = 1,
+}
+impl TriBool {
+  /// Converts to `-1`, `0`, or `1`.
+  #[inline]
+  #[must_use]
+  pub const fn to_i32(self) -> i32 {
+    self as i32
+  }
+}
+
+/// How to resolve Simultaneous Opposite Cardinal Directions (SOCD).
+///
+/// Normally a D-pad can't report both opposite directions at once, but a worn
+/// D-pad or replayed input can still do it, so the program needs a rule for
+/// what direction that counts as.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SocdPolicy {
+  /// Both directions cancel out to [`TriBool::Neutral`].
+  #[default]
+  Neutral,
+  /// The plus direction (Right or Down) wins.
+  FavorPlus,
+  /// The minus direction (Left or Up) wins.
+  FavorMinus,
+}
+impl SocdPolicy {
+  #[inline]
+  #[must_use]
+  const fn resolve(self, minus: bool, plus: bool) -> TriBool {
+    match (minus, plus) {
+      (false, false) => TriBool::Neutral,
+      (true, false) => TriBool::Minus,
+      (false, true) => TriBool::Plus,
+      (true, true) => match self {
+        Self::Neutral => TriBool::Neutral,
+        Self::FavorPlus => TriBool::Plus,
+        Self::FavorMinus => TriBool::Minus,
+      },
+    }
+  }
+}
+
+/// [`KEYINPUT`](crate::prelude::KEYINPUT): Key input data.
+///
+/// Each key on the GBA is represented by a single bit within this value, so all
+/// button state can be captured in the same moment. The `input.getter()` method
+/// for each button will return `true` if that button was held down at the time
+/// of reading this input data.
+///
+/// Generally you should read `KEYINPUT` just once per frame, and then use that
+/// data for the entire frame's computation. If you read `KEYINPUT` each time
+/// you need to check a particular button for input then small variations in
+/// button contact can cause confusing frame inputs. For example, a particular
+/// button reads as pressed at one part of a frame and then released later
+/// during the same frame. Reading the input only once per frame is a simple
+/// form of "debouncing" that will help maintain consistency in the program.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct KeyInput(u16);
+impl KeyInput {
+  /// An input with all keys released.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self(0xFFFF)
+  }
+  u16_bool_field!(inverted 0, a, with_a);
+  u16_bool_field!(inverted 1, b, with_b);
+  u16_bool_field!(inverted 2, select, with_select);
+  u16_bool_field!(inverted 3, start, with_start);
+  u16_bool_field!(inverted 4, right, with_right);
+  u16_bool_field!(inverted 5, left, with_left);
+  u16_bool_field!(inverted 6, up, with_up);
+  u16_bool_field!(inverted 7, down, with_down);
+  u16_bool_field!(inverted 8, r, with_r);
+  u16_bool_field!(inverted 9, l, with_l);
+
+  /// Unwraps the input into its raw `u16` form.
+  ///
+  /// Internally this type uses a "low-active" convention: A bit is 0 when the
+  /// key is *pressed*, and 1 when a key is *released*. The accessor methods
+  /// handle this automatically, but when unwrapping the value into a `u16` you
+  /// may need to consider this yourself.
+  #[inline]
+  #[must_use]
+  pub const fn to_u16(self) -> u16 {
+    self.0
+  }
+
+  /// The bits of the `u16` that are actually used for keys.
+  const KEY_MASK: u16 = 0b11_1111_1111;
+
+  /// Gets the keys as "high-active" bits, with the unused bits cleared.
+  #[inline]
+  #[must_use]
+  const fn pressed(self) -> u16 {
+    !self.0 & Self::KEY_MASK
+  }
+
+  /// Builds a value from "high-active" bits. Any unused bits are ignored.
+  #[inline]
+  #[must_use]
+  const fn from_pressed(pressed: u16) -> Self {
+    Self(!(pressed & Self::KEY_MASK))
+  }
+
+  /// The keys pressed in *either* input.
+  #[inline]
+  #[must_use]
+  pub const fn union(self, other: Self) -> Self {
+    Self::from_pressed(self.pressed() | other.pressed())
+  }
+
+  /// The keys pressed in *both* inputs.
+  #[inline]
+  #[must_use]
+  pub const fn intersection(self, other: Self) -> Self {
+    Self::from_pressed(self.pressed() & other.pressed())
+  }
+
+  /// Makes an input with exactly the keys given pressed.
+  #[inline]
+  #[must_use]
+  pub const fn from_keys(keys: &[Key]) -> Self {
+    let mut pressed = 0;
+    let mut i = 0;
+    while i < keys.len() {
+      pressed |= keys[i].mask();
+      i += 1;
+    }
+    Self::from_pressed(pressed)
+  }
+
+  /// An iterator over the keys pressed, in bit order.
+  ///
+  /// The unused bits of the value are always skipped.
+  #[inline]
+  pub fn iter_pressed(self) -> impl Iterator<Item = Key> {
+    let pressed = self.pressed();
+    Key::ALL.into_iter().filter(move |key| pressed & key.mask() != 0)
+  }
+
+  /// The direction along the column axis: Right is Plus, Left is Minus.
+  ///
+  /// If both are pressed this favors Right, as with [`SocdPolicy::FavorPlus`].
+  #[inline]
+  #[must_use]
+  pub const fn column_direction(self) -> TriBool {
+    self.column_direction_with(SocdPolicy::FavorPlus)
+  }
+
+  /// The direction along the row axis: Down is Plus, Up is Minus.
+  ///
+  /// If both are pressed this favors Down, as with [`SocdPolicy::FavorPlus`].
+  #[inline]
+  #[must_use]
+  pub const fn row_direction(self) -> TriBool {
+    self.row_direction_with(SocdPolicy::FavorPlus)
+  }
+
+  /// The column direction, with Left+Right resolved by the policy given.
+  #[inline]
+  #[must_use]
+  pub const fn column_direction_with(self, policy: SocdPolicy) -> TriBool {
+    policy.resolve(self.left(), self.right())
+  }
+
+  /// The row direction, with Up+Down resolved by the policy given.
+  #[inline]
+  #[must_use]
+  pub const fn row_direction_with(self, policy: SocdPolicy) -> TriBool {
+    policy.resolve(self.up(), self.down())
+  }
+
+  /// The `(column, row)` directions of the D-pad.
+  ///
+  /// This uses the same policy as [`column_direction`](Self::column_direction)
+  /// and [`row_direction`](Self::row_direction).
+  #[inline]
+  #[must_use]
+  pub const fn dpad(self) -> (TriBool, TriBool) {
+    (self.column_direction(), self.row_direction())
+  }
+
+  /// The keys pressed in `self` that are *not* pressed in `other`.
+  #[inline]
+  #[must_use]
+  pub const fn difference(self, other: Self) -> Self {
+    Self::from_pressed(self.pressed() & !other.pressed())
+  }
+
+  /// If every key pressed in `other` is also pressed in `self`.
+  ///
+  /// This makes checking for a button combination a single call:
+  /// ```no_run
+  /// # use gba::prelude::*;
+  /// let chord = KeyInput::new().with_a(true).with_b(true);
+  /// if KEYINPUT.read().contains(chord) {
+  ///   // A and B are both held (other keys might be held too).
+  /// }
+  /// ```
+  #[inline]
+  #[must_use]
+  pub const fn contains(self, other: Self) -> bool {
+    (self.pressed() & other.pressed()) == other.pressed()
+  }
+
+  /// If any key at all is pressed.
+  #[inline]
+  #[must_use]
+  pub const fn any(self) -> bool {
+    self.pressed() != 0
+  }
+
+  /// If all ten keys are pressed.
+  #[inline]
+  #[must_use]
+  pub const fn all(self) -> bool {
+    self.pressed() == Self::KEY_MASK
+  }
+
+  /// The number of keys pressed.
+  #[inline]
+  #[must_use]
+  pub const fn count(self) -> u32 {
+    self.pressed().count_ones()
+  }
+}
+impl From<KeyInput> for u16 {
+  #[inline]
+  #[must_use]
+  fn from(value: KeyInput) -> Self {
+    value.to_u16()
+  }
+}
+impl From<u16> for KeyInput {
+  #[inline]
+  #[must_use]
+  fn from(value: u16) -> Self {
+    Self(value)
+  }
+}
+impl From<Key> for KeyInput {
+  #[inline]
+  fn from(key: Key) -> Self {
+    Self::from_pressed(key.mask())
+  }
+}
+/// Prints the pressed keys joined with `+`, such as `A+START+RIGHT`, or
+/// `NONE` if no keys are pressed.
+impl fmt::Display for KeyInput {
+  #[inline]
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if !self.any() {
+      return f.write_str("NONE");
+    }
+    for (i, key) in self.iter_pressed().enumerate() {
+      if i > 0 {
+        f.write_str("+")?;
+      }
+      fmt::Display::fmt(&key, f)?;
+    }
+    Ok(())
+  }
+}
+impl ops::BitAnd for KeyInput {
+  type Output = Self;
+
+  #[inline]
+  fn bitand(self, other: Self) -> Self {
+    self.intersection(other)
+  }
+}
+impl ops::BitAndAssign for KeyInput {
+  #[inline]
+  fn bitand_assign(&mut self, other: Self) {
+    *self = *self & other;
+  }
+}
+impl ops::BitOr for KeyInput {
+  type Output = Self;
+
+  #[inline]
+  fn bitor(self, other: Self) -> Self {
+    self.union(other)
+  }
+}
+impl ops::BitOrAssign for KeyInput {
+  #[inline]
+  fn bitor_assign(&mut self, other: Self) {
+    *self = *self | other;
+  }
+}
+impl ops::BitXor for KeyInput {
+  type Output = Self;
+
+  #[inline]
+  fn bitxor(self, other: Self) -> Self {
+    Self::from_pressed(self.pressed() ^ other.pressed())
+  }
+}
+impl ops::BitXorAssign for KeyInput {
+  #[inline]
+  fn bitxor_assign(&mut self, other: Self) {
+    *self = *self ^ other;
+  }
+}
+impl ops::Not for KeyInput {
+  type Output = Self;
+
+  /// Flips the pressed state of all ten keys.
+  ///
+  /// The unused upper bits of the output are always in the "released" state.
+  #[inline]
+  fn not(self) -> Self {
+    Self::from_pressed(!self.pressed())
+  }
+}
+
+/// Tracks key input from frame to frame so that key edges can be detected.
+///
+/// Call [`update`](KeyTracker::update) once per frame with that frame's
+/// [`KEYINPUT`](crate::prelude::KEYINPUT) value, and then check the tracker as
+/// necessary during the rest of the frame.
+///
+/// A new tracker considers all keys to have been released on the "previous"
+/// frame, so any keys that are held at boot will show up as "just pressed"
+/// after the first update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyTracker {
+  current: KeyInput,
+  previous: KeyInput,
+}
+impl KeyTracker {
+  /// A tracker with all keys released.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { current: KeyInput::new(), previous: KeyInput::new() }
+  }
+
+  /// Advances the tracker by one frame, using the new key input.
+  #[inline]
+  pub fn update(&mut self, keys: KeyInput) {
+    self.previous = self.current;
+    self.current = keys;
+  }
+
+  /// The keys held during the latest update.
+  #[inline]
+  #[must_use]
+  pub const fn held(&self) -> KeyInput {
+    self.current
+  }
+
+  /// The keys that were released before the latest update, and held now.
+  #[inline]
+  #[must_use]
+  pub const fn just_pressed(&self) -> KeyInput {
+    self.current.difference(self.previous)
+  }
+
+  /// The keys that were held before the latest update, and released now.
+  #[inline]
+  #[must_use]
+  pub const fn just_released(&self) -> KeyInput {
+    self.previous.difference(self.current)
+  }
+}
+impl Default for KeyTracker {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Key auto-repeat, such as for scrolling through a menu.
+///
+/// Call [`update`](KeyRepeat::update) once per frame with that frame's key
+/// input. A key "fires" on the frame that it's first pressed, then again once
+/// it's been held for `delay` frames, and then every `interval` frames after
+/// that for as long as it stays held. Each key is timed separately, and
+/// releasing a key resets its timing.
+///
+/// The `delay` and `interval` can be changed at any time, and the new values
+/// will be used the next time that a key's repeat timer is started.
+///
+/// A repeat made with [`timed`](Self::timed) counts the frames that really
+/// went by between updates instead, so the repeat rate stays the same when
+/// frames are missed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyRepeat {
+  /// Frames after the initial press before the first repeat.
+  pub delay: u16,
+  /// Frames between each repeat after the first.
+  pub interval: u16,
+  held: KeyInput,
+  countdowns: [u16; 10],
+  timed: bool,
+  last_update: Option<FrameInstant>,
+}
+impl KeyRepeat {
+  /// Makes a new repeat tracker with all keys released.
+  #[inline]
+  #[must_use]
+  pub const fn new(delay: u16, interval: u16) -> Self {
+    Self {
+      delay,
+      interval,
+      held: KeyInput::new(),
+      countdowns: [0; 10],
+      timed: false,
+      last_update: None,
+    }
+  }
+
+  /// Makes a new repeat tracker that times keys with
+  /// [`frame_count`](crate::time::frame_count), rather than counting each
+  /// update as one frame.
+  ///
+  /// The vblank interrupt has to be on (see the [`time`](crate::time)
+  /// module). A key still fires at most once per update.
+  #[cfg(feature = "on_gba")]
+  #[inline]
+  #[must_use]
+  pub const fn timed(delay: u16, interval: u16) -> Self {
+    Self { timed: true, ..Self::new(delay, interval) }
+  }
+
+  /// Advances by one frame (or, if [`timed`](Self::timed), the frames since
+  /// the last update), returning the keys that fire this frame.
+  ///
+  /// ## Panics
+  /// * With debug assertions on, if this is [`timed`](Self::timed) and the
+  ///   vblank interrupt is off (see [`frame_count`](crate::time::frame_count)).
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn update(&mut self, keys: KeyInput) -> KeyInput {
+    let frames = self.frames_since_update();
+    let pressed = keys.pressed();
+    let was_pressed = self.held.pressed();
+    let mut fire = 0_u16;
+    for (i, countdown) in self.countdowns.iter_mut().enumerate() {
+      let bit = 1 << i;
+      if pressed & bit == 0 {
+        *countdown = 0;
+      } else if was_pressed & bit == 0 {
+        fire |= bit;
+        *countdown = self.delay;
+      } else {
+        *countdown = countdown.saturating_sub(frames);
+        if *countdown == 0 {
+          fire |= bit;
+          *countdown = self.interval;
+        }
+      }
+    }
+    self.held = keys;
+    KeyInput::from_pressed(fire)
+  }
+
+  /// The frames that this update covers: always 1 unless this is
+  /// [`timed`](Self::timed).
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  fn frames_since_update(&mut self) -> u16 {
+    #[cfg(feature = "on_gba")]
+    if self.timed {
+      let now = FrameInstant::now();
+      let frames = self.last_update.map_or(1, |last| now.since(last));
+      self.last_update = Some(now);
+      return u16::try_from(frames).unwrap_or(u16::MAX);
+    }
+    1
+  }
+}
+
+/// If all of the keys in `chord` are held in `keys`.
+///
+/// Other keys being held as well doesn't matter.
+#[inline]
+#[must_use]
+pub const fn chord_held(keys: KeyInput, chord: KeyInput) -> bool {
+  keys.contains(chord)
+}
+
+/// Detects an ordered sequence of key presses, such as a cheat code.
+///
+/// Each frame, call [`update`](Combo::update) with the keys that were *just
+/// pressed* that frame (see [`KeyTracker::just_pressed`]). Each step of the
+/// sequence matches when all of its keys are just pressed on the same frame.
+///
+/// * Pressing a key that doesn't belong to the current step resets the
+///   sequence, except that pressing a key again which was already matched by an
+///   earlier step is ignored.
+/// * If a timeout is set, taking more than that many frames between steps also
+///   resets the sequence.
+///
+/// ```
+/// # use gba::prelude::*;
+/// const U: KeyInput = KeyInput::new().with_up(true);
+/// const D: KeyInput = KeyInput::new().with_down(true);
+/// const L: KeyInput = KeyInput::new().with_left(true);
+/// const R: KeyInput = KeyInput::new().with_right(true);
+/// const B: KeyInput = KeyInput::new().with_b(true);
+/// const A: KeyInput = KeyInput::new().with_a(true);
+/// static KONAMI: [KeyInput; 10] = [U, U, D, D, L, R, L, R, B, A];
+/// let mut combo = Combo::new(&KONAMI, Some(30));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Combo {
+  steps: &'static [KeyInput],
+  timeout: Option<u16>,
+  matched: usize,
+  frames_waiting: u16,
+}
+impl Combo {
+  /// Makes a new combo that needs the steps given, in order.
+  ///
+  /// * `timeout` is the maximum number of frames allowed between each step, or
+  ///   `None` to allow any amount of time.
+  #[inline]
+  #[must_use]
+  pub const fn new(steps: &'static [KeyInput], timeout: Option<u16>) -> Self {
+    Self { steps, timeout, matched: 0, frames_waiting: 0 }
+  }
+
+  /// The number of steps matched so far.
+  #[inline]
+  #[must_use]
+  pub const fn progress(&self) -> usize {
+    self.matched
+  }
+
+  /// Resets back to the start of the sequence.
+  #[inline]
+  pub fn reset(&mut self) {
+    self.matched = 0;
+    self.frames_waiting = 0;
+  }
+
+  /// Advances one frame, returning `true` when the whole sequence completes.
+  ///
+  /// After completing, the combo resets and can be matched again.
+  #[inline]
+  pub fn update(&mut self, edges: KeyInput) -> bool {
+    if self.matched > 0 {
+      self.frames_waiting = self.frames_waiting.saturating_add(1);
+      if let Some(timeout) = self.timeout {
+        if self.frames_waiting > timeout {
+          self.reset();
+        }
+      }
+    }
+    if !edges.any() {
+      return false;
+    }
+    if !self.try_step(edges) {
+      self.reset();
+      // the wrong keys might still be the start of a new attempt.
+      self.try_step(edges);
+    }
+    if self.matched > 0 && self.matched == self.steps.len() {
+      self.reset();
+      true
+    } else {
+      false
+    }
+  }
+
+  /// Tries to match the next step, returning `false` if the keys are wrong.
+  #[inline]
+  fn try_step(&mut self, edges: KeyInput) -> bool {
+    let already_matched = self.steps[..self.matched]
+      .iter()
+      .fold(KeyInput::new(), |acc, step| acc.union(*step));
+    match self.steps.get(self.matched) {
+      Some(&step)
+        if edges.contains(step)
+          && already_matched.union(step).contains(edges) =>
+      {
+        self.matched += 1;
+        self.frames_waiting = 0;
+        true
+      }
+      _ => already_matched.contains(edges),
+    }
+  }
+}
+
+/// [`KEYCNT`](crate::prelude::KEYCNT): Determines when a key interrupt will be
+/// sent.
+///
+/// A key interrupt can be sent if `irq_enabled` is set and the correct buttons
+/// are pushed. This *should not* be used for normal user input. For normal
+/// input you should just read [`KEYINPUT`](crate::prelude::KEYINPUT). Instead,
+/// the key interrupts are primarily used for breaking the CPU out of Halt
+/// state.
+///
+/// * If `irq_all` is `true` then *all* buttons enabled must be pressed at once.
+/// * Otherwise *any* buttons enabled can be pressed to trigger the interrupt.
+///
+/// As with all interrupts, the key interrupt must also be set to be received in
+/// the [`IE`](crate::prelude::IE) control, and interrupts must be enabled with
+/// the [`IME`](crate::prelude::IME) control, or the key interrupt won't
+/// actually be triggered even when `irq_enabled` is set.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct KeyControl(u16);
+impl KeyControl {
+  pub_const_fn_new_zeroed!();
+  u16_bool_field!(0, a, with_a);
+  u16_bool_field!(1, b, with_b);
+  u16_bool_field!(2, select, with_select);
+  u16_bool_field!(3, start, with_start);
+  u16_bool_field!(4, right, with_right);
+  u16_bool_field!(5, left, with_left);
+  u16_bool_field!(6, up, with_up);
+  u16_bool_field!(7, down, with_down);
+  u16_bool_field!(8, r, with_r);
+  u16_bool_field!(9, l, with_l);
+
+  u16_bool_field!(14, irq_enabled, with_irq_enabled);
+  u16_bool_field!(15, irq_all, with_irq_all);
+
+  /// A key interrupt for the keys pressed in `keys`, with the IRQ enabled.
+  ///
+  /// * If `logical_and` is `true` then all the keys are needed at once.
+  /// * Otherwise any one of the keys will trigger the interrupt.
+  #[inline]
+  #[must_use]
+  pub const fn from_keys(keys: KeyInput, logical_and: bool) -> Self {
+    Self(keys.pressed()).with_irq_enabled(true).with_irq_all(logical_and)
+  }
+
+  /// A key interrupt that fires when any key is pressed.
+  ///
+  /// This is the usual setting for waking the GBA from the low power "Stop"
+  /// state.
+  #[inline]
+  #[must_use]
+  pub const fn any_key() -> Self {
+    Self::from_keys(KeyInput::from_pressed(KeyInput::KEY_MASK), false)
+  }
+}
+
+/// Sets [`KEYCNT`](crate::prelude::KEYCNT) and enables the keypad interrupt in
+/// [`IE`](crate::prelude::IE).
+///
+/// See [`KeyControl::from_keys`] for what the arguments mean. This does *not*
+/// set [`IME`](crate::prelude::IME), so you'll still need to do that yourself
+/// (along with setting up an interrupt handler, if you want one).
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn arm_keypad_interrupt(keys: KeyInput, logical_and: bool) {
+  use crate::{
+    interrupts::{enable_irqs, IrqBits},
+    mmio::KEYCNT,
+  };
+  KEYCNT.write(KeyControl::from_keys(keys, logical_and));
+  enable_irqs(IrqBits::KEYPAD);
+}
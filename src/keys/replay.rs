@@ -0,0 +1,144 @@
+//! Recording and playback of key input.
+//!
+//! Key input is stored as a run-length encoded list of `(keys, frames)` pairs,
+//! so holding the same keys for many frames in a row only takes up a single
+//! entry. This is the format used by both [`InputRecorder`] and
+//! [`InputPlayback`], and a recording can be baked into the ROM as a
+//! `&'static [(KeyInput, u16)]` to play back later (eg: for an attract mode
+//! demo).
+
+use super::KeyInput;
+use crate::collections::ArrayVec;
+
+/// Records one [`KeyInput`] per frame into a fixed size buffer.
+///
+/// Each entry of the buffer is a run of identical input, so `N` is the maximum
+/// number of *changes* in input that can be recorded, not the number of
+/// frames.
+#[derive(Debug, Clone)]
+pub struct InputRecorder<const N: usize> {
+  runs: ArrayVec<(KeyInput, u16), N>,
+  full: bool,
+}
+impl<const N: usize> InputRecorder<N> {
+  /// Makes a new, empty recorder.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { runs: ArrayVec::new(), full: false }
+  }
+
+  /// Records the input for one frame.
+  ///
+  /// ## Failure
+  /// * If the buffer doesn't have room for the input this frame then recording
+  ///   stops, and this and all later calls will return `Err`. The frames
+  ///   recorded before that point are kept.
+  #[inline]
+  pub fn record(&mut self, keys: KeyInput) -> Result<(), ()> {
+    if self.full {
+      return Err(());
+    }
+    if let Some((last_keys, frames)) = self.runs.last_mut() {
+      if *last_keys == keys && *frames < u16::MAX {
+        *frames += 1;
+        return Ok(());
+      }
+    }
+    self.runs.push((keys, 1)).map_err(|_| self.full = true)
+  }
+
+  /// The number of runs recorded.
+  #[inline]
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.runs.len()
+  }
+
+  /// If nothing has been recorded.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.runs.is_empty()
+  }
+
+  /// If recording has stopped because the buffer ran out of space.
+  #[inline]
+  #[must_use]
+  pub const fn is_full(&self) -> bool {
+    self.full
+  }
+
+  /// The runs recorded so far.
+  #[inline]
+  #[must_use]
+  pub fn as_slice(&self) -> &[(KeyInput, u16)] {
+    self.runs.as_slice()
+  }
+
+  /// A playback of everything recorded so far.
+  #[inline]
+  #[must_use]
+  pub fn playback(&self) -> InputPlayback<'_> {
+    InputPlayback::new(self.as_slice())
+  }
+
+  /// Clears the recording so that a new one can begin.
+  #[inline]
+  pub fn clear(&mut self) {
+    self.runs.clear();
+    self.full = false;
+  }
+}
+impl<const N: usize> Default for InputRecorder<N> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Plays back recorded input, one [`KeyInput`] per frame.
+///
+/// This is an iterator, and it gives `None` once all runs have been played.
+/// Any runs with a frame count of 0 are skipped.
+#[derive(Debug, Clone)]
+pub struct InputPlayback<'a> {
+  runs: &'a [(KeyInput, u16)],
+  frames_played: u16,
+}
+impl<'a> InputPlayback<'a> {
+  /// Makes a playback of the runs given.
+  #[inline]
+  #[must_use]
+  pub const fn new(runs: &'a [(KeyInput, u16)]) -> Self {
+    Self { runs, frames_played: 0 }
+  }
+
+  /// If all frames have been played.
+  #[inline]
+  #[must_use]
+  pub fn is_finished(&self) -> bool {
+    match self.runs.split_first() {
+      None => true,
+      Some(((_, frames), rest)) => {
+        self.frames_played >= *frames && rest.iter().all(|(_, f)| *f == 0)
+      }
+    }
+  }
+}
+impl Iterator for InputPlayback<'_> {
+  type Item = KeyInput;
+
+  #[inline]
+  fn next(&mut self) -> Option<KeyInput> {
+    loop {
+      let ((keys, frames), rest) = self.runs.split_first()?;
+      if self.frames_played < *frames {
+        self.frames_played += 1;
+        return Some(*keys);
+      }
+      self.runs = rest;
+      self.frames_played = 0;
+    }
+  }
+}
@@ -91,30 +91,64 @@ use prelude::{GbaCell, IrqFn};
 
 mod macros;
 
+pub mod arena;
 #[cfg(feature = "on_gba")]
 mod asm_runtime;
 #[cfg(feature = "on_gba")]
 pub mod bios;
 pub mod builtin_art;
+pub mod collections;
 #[cfg(feature = "critical-section")]
 mod critical_section;
 #[cfg(feature = "on_gba")]
+pub mod debug;
+#[cfg(feature = "on_gba")]
+pub mod debug_log;
+#[cfg(feature = "on_gba")]
 pub mod dma;
+#[cfg(feature = "on_gba")]
+pub mod environment;
+#[cfg(all(feature = "on_gba", feature = "ewram_alloc"))]
+pub mod ewram_alloc;
 pub mod fixed;
 #[cfg(feature = "on_gba")]
 pub mod gba_cell;
+pub mod gpio;
 pub mod interrupts;
 pub mod keys;
+pub mod math;
 pub mod mem;
 #[cfg(feature = "on_gba")]
+mod mem_fns;
+#[cfg(feature = "on_gba")]
 pub mod mgba;
 #[cfg(feature = "on_gba")]
 pub mod mmio;
+#[cfg(feature = "on_gba")]
+pub mod nocash;
+pub mod pacing;
+#[cfg(feature = "on_gba")]
+pub mod panic_screen;
+pub mod perf;
+#[cfg(feature = "on_gba")]
+pub mod power;
 pub mod prelude;
+#[cfg(feature = "on_gba")]
+pub mod profile;
 pub mod random;
+pub mod rom;
+pub mod save;
+pub mod scheduler;
+pub mod sio;
 pub mod sound;
+#[cfg(feature = "on_gba")]
+pub mod system;
+#[cfg(all(feature = "on_gba", feature = "test_runner"))]
+pub mod test_runner;
+pub mod time;
 pub mod timers;
 pub mod video;
+pub mod waitstate;
 
 /// The function pointer that the assembly runtime calls when an interrupt
 /// occurs.
@@ -135,15 +169,12 @@ impl<const N: usize> Align4<[u8; N]> {
   /// * If the number of bytes isn't a multiple of 4
   #[inline]
   #[must_use]
-  pub fn as_u32_slice(&self) -> &[u32] {
-    assert!(self.0.len() % 4 == 0);
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn as_u32_slice(&self) -> &[u32] {
+    assert!(N.is_multiple_of(4), "the length isn't a multiple of 4 bytes");
     // Safety: our struct is aligned to 4, so the pointer will already be
     // aligned, we only need to check the length
-    unsafe {
-      let data: *const u8 = self.0.as_ptr();
-      let len: usize = self.0.len();
-      core::slice::from_raw_parts(data.cast::<u32>(), len / 4)
-    }
+    unsafe { core::slice::from_raw_parts(self.0.as_ptr().cast::<u32>(), N / 4) }
   }
 
   /// Views these bytes as a slice of `u16`
@@ -151,22 +182,133 @@ impl<const N: usize> Align4<[u8; N]> {
   /// * If the number of bytes isn't a multiple of 2
   #[inline]
   #[must_use]
-  pub fn as_u16_slice(&self) -> &[u16] {
-    assert!(self.0.len() % 2 == 0);
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn as_u16_slice(&self) -> &[u16] {
+    assert!(N.is_multiple_of(2), "the length isn't a multiple of 2 bytes");
     // Safety: our struct is aligned to 4, so the pointer will already be
     // aligned, we only need to check the length
-    unsafe {
-      let data: *const u8 = self.0.as_ptr();
-      let len: usize = self.0.len();
-      core::slice::from_raw_parts(data.cast::<u16>(), len / 2)
-    }
+    unsafe { core::slice::from_raw_parts(self.0.as_ptr().cast::<u16>(), N / 2) }
+  }
+
+  /// Views these bytes as a slice of [`Color`](video::Color), two bytes
+  /// (little-endian) per color.
+  /// ## Panics
+  /// * If the number of bytes isn't a multiple of 2
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn as_color_slice(&self) -> &[video::Color] {
+    assert!(N.is_multiple_of(2), "the length isn't a whole number of colors");
+    // Safety: `Color` is a transparent `u16`, so this is the same as
+    // `as_u16_slice`
+    unsafe { core::slice::from_raw_parts(self.0.as_ptr().cast(), N / 2) }
+  }
+
+  /// Views these bytes as a slice of [`Tile4`](video::Tile4), 32 bytes per
+  /// tile, in the usual GBA layout (see
+  /// [`tile4_from_bytes`](video::tile4_from_bytes)).
+  /// ## Panics
+  /// * If the number of bytes isn't a multiple of 32
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn as_tile4_slice(&self) -> &[video::Tile4] {
+    assert!(N.is_multiple_of(32), "the length isn't a whole number of tiles");
+    // Safety: `Tile4` is `[u32; 8]`, which any bits are valid for, and which
+    // only needs an alignment of 4
+    unsafe { core::slice::from_raw_parts(self.0.as_ptr().cast(), N / 32) }
   }
 }
 
 /// Works like [`include_bytes!`], but the value is wrapped in [`Align4`].
+///
+/// To use the data as some other type, see [`include_u16_data!`],
+/// [`include_u32_data!`], [`include_palette!`], and [`include_tiles_4bpp!`].
 #[macro_export]
 macro_rules! include_aligned_bytes {
   ($file:expr $(,)?) => {{
-    Align4(*include_bytes!($file))
+    $crate::Align4(*include_bytes!($file))
+  }};
+}
+
+/// Includes a file as aligned data viewed with one of the [`Align4`] methods.
+///
+/// Both steps happen in a `const`, so a file of the wrong length for the type
+/// is a compile error.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __include_aligned_as {
+  ($file:expr, $as_slice:ident, $t:ty) => {{
+    const BYTES: &$crate::Align4<[u8; include_bytes!($file).len()]> =
+      &$crate::Align4(*include_bytes!($file));
+    const DATA: &[$t] = BYTES.$as_slice();
+    DATA
   }};
 }
+
+/// Includes a file as a `&'static [u16]`, aligned to 4.
+///
+/// The bytes are little-endian. This can be used for a `static` or a `const`,
+/// and a file that isn't a whole number of `u16` is a compile error.
+///
+/// ```no_run
+/// // the file is 14 bytes long, which is 7 `u16` values.
+/// static TEXT: &[u16] = gba::include_u16_data!(concat!(
+///   env!("CARGO_MANIFEST_DIR"),
+///   "/examples/foo.txt"
+/// ));
+/// ```
+#[macro_export]
+macro_rules! include_u16_data {
+  ($file:expr $(,)?) => {
+    $crate::__include_aligned_as!($file, as_u16_slice, u16)
+  };
+}
+
+/// Includes a file as a `&'static [u32]`, aligned to 4.
+///
+/// The bytes are little-endian. This can be used for a `static` or a `const`,
+/// and a file that isn't a whole number of `u32` is a compile error:
+///
+/// ```compile_fail
+/// // the file is 14 bytes long.
+/// static WORDS: &[u32] = gba::include_u32_data!(concat!(
+///   env!("CARGO_MANIFEST_DIR"),
+///   "/examples/foo.txt"
+/// ));
+/// ```
+#[macro_export]
+macro_rules! include_u32_data {
+  ($file:expr $(,)?) => {
+    $crate::__include_aligned_as!($file, as_u32_slice, u32)
+  };
+}
+
+/// Includes a raw palette file (two little-endian bytes per color, as most
+/// tools export a `.pal` for the GBA) as a `&'static [Color]`.
+///
+/// This can be used for a `static` or a `const`, and a file with an odd
+/// number of bytes is a compile error.
+///
+/// [`Color`]: crate::video::Color
+#[macro_export]
+macro_rules! include_palette {
+  ($file:expr $(,)?) => {
+    $crate::__include_aligned_as!($file, as_color_slice, $crate::video::Color)
+  };
+}
+
+/// Includes a raw 4bpp tile file (32 bytes per tile) as a
+/// `&'static [Tile4]`.
+///
+/// This can be used for a `static` or a `const`, and a file that isn't a
+/// whole number of tiles is a compile error. The tiles can go to VRAM with
+/// [`copy_tiles_4bpp`](crate::video::copy_tiles_4bpp).
+///
+/// [`Tile4`]: crate::video::Tile4
+#[macro_export]
+macro_rules! include_tiles_4bpp {
+  ($file:expr $(,)?) => {
+    $crate::__include_aligned_as!($file, as_tile4_slice, $crate::video::Tile4)
+  };
+}
@@ -57,45 +57,104 @@ macro_rules! u16_bool_field {
 }
 pub(crate) use u16_bool_field;
 
-macro_rules! u16_enum_field {
-  ($low:literal - $high:literal : $t:ty, $get:ident, $with:ident) => {
+// A multi-bit field of a `u16` register newtype, holding an enum.
+//
+// This makes the `$get` and `$with` accessors, and a public `$mask` const with
+// the field's bits. The variants in the list are checked against the field's
+// width at compile time (when the `$with` method makes use of the mask), so
+// list all of them: a variant that didn't fit would be cut off, and silently
+// write some other variant.
+//
+// Normally the enum's discriminants are already shifted into place (eg:
+// `Vertical = 2 << 14` for bits 14-15). With `value`, the discriminants are
+// plain values (`Vertical = 2`) that get shifted, so the same enum can be used
+// for fields in different positions.
+macro_rules! register_enum_field {
+  (
+    $low:literal - $high:literal : $t:ident [$($v:ident),+ $(,)?],
+    $get:ident, $with:ident, $mask:ident
+  ) => {
+    #[doc = concat!("The bits of the `", stringify!($get), "` field.")]
+    pub const $mask: u16 = {
+      let mask = bitfrob::u16_region_mask($low, $high);
+      $(
+        assert!(
+          $t::$v as u16 & !mask == 0,
+          concat!(stringify!($t), "::", stringify!($v), " doesn't fit its field"),
+        );
+      )+
+      mask
+    };
+    #[inline]
+    #[must_use]
+    #[allow(missing_docs)]
+    pub const fn $get(self) -> $t {
+      unsafe { core::mem::transmute::<u16, $t>(self.0 & Self::$mask) }
+    }
+    #[inline]
+    #[must_use]
+    #[allow(missing_docs)]
+    pub const fn $with(self, val: $t) -> Self {
+      Self(self.0 & !Self::$mask | val as u16)
+    }
+  };
+  (
+    value $low:literal - $high:literal : $t:ident [$($v:ident),+ $(,)?],
+    $get:ident, $with:ident, $mask:ident
+  ) => {
+    #[doc = concat!("The bits of the `", stringify!($get), "` field.")]
+    pub const $mask: u16 = {
+      let mask = bitfrob::u16_region_mask($low, $high);
+      $(
+        assert!(
+          $t::$v as u16 <= mask >> $low,
+          concat!(stringify!($t), "::", stringify!($v), " doesn't fit its field"),
+        );
+      )+
+      mask
+    };
     #[inline]
     #[must_use]
     #[allow(missing_docs)]
     pub const fn $get(self) -> $t {
       unsafe {
-        core::mem::transmute::<u16, $t>(bitfrob::u16_get_region(
-          $low, $high, self.0,
-        ))
+        core::mem::transmute::<u16, $t>((self.0 & Self::$mask) >> $low)
       }
     }
     #[inline]
     #[must_use]
     #[allow(missing_docs)]
     pub const fn $with(self, val: $t) -> Self {
-      Self(bitfrob::u16_with_region($low, $high, self.0, val as u16))
+      Self(self.0 & !Self::$mask | (val as u16) << $low)
     }
   };
 }
-pub(crate) use u16_enum_field;
+pub(crate) use register_enum_field;
 
-macro_rules! u16_int_field {
-  ($low:literal - $high:literal, $get:ident, $with:ident) => {
+// A multi-bit field of a `u16` register newtype, holding a number.
+//
+// This makes the `$get` and `$with` accessors, and a public `$mask` const with
+// the field's bits. The number is shifted into place, and the bits of it that
+// don't fit the field are dropped.
+macro_rules! register_int_field {
+  ($low:literal - $high:literal, $get:ident, $with:ident, $mask:ident) => {
+    #[doc = concat!("The bits of the `", stringify!($get), "` field.")]
+    pub const $mask: u16 = bitfrob::u16_region_mask($low, $high);
     #[inline]
     #[must_use]
     #[allow(missing_docs)]
     pub const fn $get(self) -> u16 {
-      bitfrob::u16_get_value($low, $high, self.0)
+      (self.0 & Self::$mask) >> $low
     }
     #[inline]
     #[must_use]
     #[allow(missing_docs)]
     pub const fn $with(self, val: u16) -> Self {
-      Self(bitfrob::u16_with_value($low, $high, self.0, val))
+      Self(self.0 & !Self::$mask | (val << $low) & Self::$mask)
     }
   };
 }
-pub(crate) use u16_int_field;
+pub(crate) use register_int_field;
 
 macro_rules! u8_bool_field {
   ($bit:expr, $get:ident, $with:ident) => {
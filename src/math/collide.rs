@@ -0,0 +1,657 @@
+//! Rectangle collision: overlap tests, sweeps, and a broad phase grid.
+//!
+//! Everything here uses an [`Aabb`] (an axis-aligned bounding box), with its
+//! position and size as `Fixed<i32, 8>` (pixels with 8 bits of fraction). The
+//! math is done in 64 bits, so there's no overflow anywhere in the `i32`
+//! range.
+//!
+//! ## Edges
+//!
+//! A box covers `pos..pos + size` on each axis, and two boxes only overlap if
+//! some part of one is strictly inside the other. Boxes that just touch along
+//! an edge don't overlap, so an object can stand on the floor (or slide along
+//! a wall) without colliding with it every frame.
+//!
+//! A box with a width or height of 0 works like a line (or a point, if it's 0
+//! in both), and overlaps another box when it's strictly inside it. Negative
+//! sizes count as 0.
+//!
+//! ## Sweeps
+//!
+//! Moving a box by its whole velocity and then checking for overlap misses
+//! anything thinner than the distance moved. [`Aabb::sweep`] instead finds the
+//! first point along the movement where the box touches the other one, so
+//! even a very fast object can't pass through a wall. For a map of
+//! [`Metatile`]s, [`sweep_map`] does the same against every tile with certain
+//! collision flags, and [`move_and_slide`] uses that for the usual platformer
+//! movement:
+//!
+//! ```no_run
+//! # use gba::fixed::Fixed;
+//! # use gba::math::{Vec2, collide::*};
+//! # use gba::video::tilemap::MetatileMap;
+//! type Fx = Fixed<i32, 8>;
+//! # let level: MetatileMap<'static, 2> = MetatileMap::new(&[], &[], &[], 0, 0);
+//! const SOLID: u8 = 1 << 0;
+//! let mut player = Aabb::new(
+//!   Vec2::new(Fx::from_int(16), Fx::from_int(16)),
+//!   Vec2::new(Fx::from_int(12), Fx::from_int(16)),
+//! );
+//! let mut velocity = Vec2::new(Fx::from_int(1), Fx::from_int(0));
+//! loop {
+//!   velocity.y += Fx::from_bits(0x40); // gravity
+//!   let (moved, blocked) = move_and_slide(&level, player, velocity, SOLID);
+//!   player = moved;
+//!   if blocked.y != 0 {
+//!     velocity.y = Fx::from_int(0);
+//!   }
+//!   let on_ground = blocked.y < 0;
+//!   # break;
+//! }
+//! ```
+//!
+//! ## Broad phase
+//!
+//! Checking every pair of objects against each other gets slow quickly. A
+//! [`GridBroadphase`] puts each box into the cells of a grid that it covers,
+//! and only gives the pairs that share a cell, which are then checked with
+//! [`Aabb::overlaps`].
+
+use super::Vec2;
+use crate::{
+  collections::ArrayVec,
+  fixed::Fixed,
+  video::tilemap::{Metatile, MetatileMap},
+};
+
+/// An axis-aligned bounding box, from its top left corner and its size.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Aabb {
+  pub pos: Vec2<Fixed<i32, 8>>,
+  pub size: Vec2<Fixed<i32, 8>>,
+}
+impl Aabb {
+  /// Makes a box.
+  #[inline]
+  #[must_use]
+  pub const fn new(
+    pos: Vec2<Fixed<i32, 8>>, size: Vec2<Fixed<i32, 8>>,
+  ) -> Self {
+    Self { pos, size }
+  }
+
+  /// Makes a box covering the same pixels as a [`Rect`](super::Rect).
+  ///
+  /// Positions past the range of `Fixed<i32, 8>` (about 8 million pixels)
+  /// are clamped to it.
+  #[inline]
+  #[must_use]
+  pub const fn from_rect(rect: super::Rect) -> Self {
+    const fn pixels(i: i32) -> Fixed<i32, 8> {
+      Fixed::<i32, 8>::from_bits(i.saturating_mul(1 << 8))
+    }
+    Self::new(
+      Vec2::new(pixels(rect.x), pixels(rect.y)),
+      Vec2::new(pixels(rect.width), pixels(rect.height)),
+    )
+  }
+
+  /// The corner just past the bottom right of the box.
+  ///
+  /// This uses saturating math, so a box near the limits of the type is cut
+  /// off there rather than wrapping.
+  #[inline]
+  #[must_use]
+  pub const fn max(self) -> Vec2<Fixed<i32, 8>> {
+    const fn edge(pos: Fixed<i32, 8>, size: Fixed<i32, 8>) -> Fixed<i32, 8> {
+      let size = if size.to_bits() < 0 { 0 } else { size.to_bits() };
+      Fixed::<i32, 8>::from_bits(pos.to_bits().saturating_add(size))
+    }
+    Vec2::new(edge(self.pos.x, self.size.x), edge(self.pos.y, self.size.y))
+  }
+
+  /// The same box moved by `offset`.
+  #[inline]
+  #[must_use]
+  pub const fn translate(self, offset: Vec2<Fixed<i32, 8>>) -> Self {
+    Self::new(self.pos.saturating_add(offset), self.size)
+  }
+
+  /// If the two boxes overlap.
+  ///
+  /// Boxes that only touch along an edge (or at a corner) don't overlap.
+  #[inline]
+  #[must_use]
+  pub const fn overlaps(self, other: Self) -> bool {
+    let (a, b) = (self.bounds(), other.bounds());
+    a[0] < b[2] && b[0] < a[2] && a[1] < b[3] && b[1] < a[3]
+  }
+
+  /// If the point is inside the box.
+  ///
+  /// Like with [`Rect::contains_point`](super::Rect::contains_point), the top
+  /// and left edges are inside the box and the bottom and right edges aren't,
+  /// so a point on the edge between two boxes is in exactly one of them.
+  #[inline]
+  #[must_use]
+  pub const fn contains_point(self, point: Vec2<Fixed<i32, 8>>) -> bool {
+    let a = self.bounds();
+    let (x, y) = (point.x.to_bits() as i64, point.y.to_bits() as i64);
+    a[0] <= x && x < a[2] && a[1] <= y && y < a[3]
+  }
+
+  /// Finds where this box, moving by `velocity`, first touches `other` (which
+  /// doesn't move).
+  ///
+  /// Gives `None` if the box doesn't touch `other` at any point of the
+  /// movement. Some cases to keep in mind:
+  /// * If the boxes already overlap there's no hit. That has to be sorted out
+  ///   some other way (see [`overlaps`](Self::overlaps)).
+  /// * If the boxes already touch, and the box is moving into `other`, the hit
+  ///   is at time 0 (and the box can't move along that axis). Moving away, or
+  ///   sliding along the touching edges, isn't a hit.
+  /// * A box that ends up exactly touching `other` at the end of the movement
+  ///   gives a hit at time 1.
+  /// * If the velocity is zero there's no hit.
+  /// * If the box reaches a corner of `other` exactly diagonally (touching on
+  ///   both axes at once), the hit is on the vertical axis. That way an object
+  ///   that lands right on the corner of a ledge stands on it.
+  #[inline]
+  #[must_use]
+  pub const fn sweep(
+    self, velocity: Vec2<Fixed<i32, 8>>, other: Self,
+  ) -> Option<Hit> {
+    let v = [velocity.x.to_bits() as i64, velocity.y.to_bits() as i64];
+    match sweep_bounds(self.bounds(), v, other.bounds()) {
+      Some((time, on_x)) => Some(Hit::new(time, on_x, v)),
+      None => None,
+    }
+  }
+
+  /// The edges as `[left, top, right, bottom]`, in 64 bits.
+  #[inline]
+  #[must_use]
+  const fn bounds(self) -> [i64; 4] {
+    let max = self.max();
+    [
+      self.pos.x.to_bits() as i64,
+      self.pos.y.to_bits() as i64,
+      max.x.to_bits() as i64,
+      max.y.to_bits() as i64,
+    ]
+  }
+}
+
+/// Where a sweep first touches a box, from [`Aabb::sweep`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Hit {
+  /// How far along the movement the boxes touch, from 0.0 (at the start) to
+  /// 1.0 (at the end). This is rounded down.
+  pub time: Fixed<i16, 14>,
+  /// The side of the other box that was hit, pointing out of it: `(-1, 0)`
+  /// for its left side, `(0, -1)` for its top, and so on.
+  pub normal: Vec2<i32>,
+  /// How far the box moves before it touches the other box.
+  ///
+  /// On the axis of the normal this is exact, so the box ends up touching.
+  /// On the other axis it's rounded towards zero, which never moves the box
+  /// into something.
+  pub delta: Vec2<Fixed<i32, 8>>,
+}
+impl Hit {
+  /// Makes the hit for moving by `v` until `time`, touching on the `x` axis
+  /// if `on_x` (and on the `y` axis otherwise).
+  #[inline]
+  #[must_use]
+  const fn new(time: Toi, on_x: bool, v: [i64; 2]) -> Self {
+    let (hit, other) = if on_x { (0, 1) } else { (1, 0) };
+    let sign = v[hit].signum();
+    let mut delta = [0; 2];
+    delta[hit] = sign * time.num;
+    // this rounds towards zero, and `v * num` fits since `num <= den`.
+    delta[other] = v[other] * time.num / time.den;
+    let mut normal = [0; 2];
+    normal[hit] = -sign as i32;
+    Self {
+      time: Fixed::<i16, 14>::from_bits(((time.num << 14) / time.den) as i16),
+      normal: Vec2::new(normal[0], normal[1]),
+      delta: Vec2::new(
+        Fixed::<i32, 8>::from_bits(delta[0] as i32),
+        Fixed::<i32, 8>::from_bits(delta[1] as i32),
+      ),
+    }
+  }
+
+  /// The velocity that's left after moving up to the hit, with the part
+  /// going into the other box taken out.
+  ///
+  /// Moving by [`delta`](Self::delta) and then sweeping again with this
+  /// makes the box slide along the surface it hit.
+  #[inline]
+  #[must_use]
+  pub const fn slide_velocity(
+    self, velocity: Vec2<Fixed<i32, 8>>,
+  ) -> Vec2<Fixed<i32, 8>> {
+    let rest = velocity.saturating_sub(self.delta);
+    if self.normal.x != 0 {
+      Vec2::new(Fixed::<i32, 8>::from_bits(0), rest.y)
+    } else {
+      Vec2::new(rest.x, Fixed::<i32, 8>::from_bits(0))
+    }
+  }
+}
+
+/// A time along a sweep, as the fraction `num / den`.
+///
+/// `den` is positive, except for [`Toi::NEVER`] (which is after every other
+/// time).
+#[derive(Clone, Copy)]
+struct Toi {
+  num: i64,
+  den: i64,
+}
+impl Toi {
+  /// The start of the movement, or before it.
+  const BEFORE: Self = Self { num: -1, den: 1 };
+  /// The end of the movement.
+  const END: Self = Self { num: 1, den: 1 };
+  /// After every other time.
+  const NEVER: Self = Self { num: 1, den: 0 };
+
+  /// If this is before `other`.
+  #[inline]
+  #[must_use]
+  const fn lt(self, other: Self) -> bool {
+    (self.num as i128) * (other.den as i128)
+      < (other.num as i128) * (self.den as i128)
+  }
+}
+
+/// When the range `a_min..a_max`, moving by `v`, starts and stops overlapping
+/// `b_min..b_max`.
+///
+/// Gives `None` if it never does.
+#[inline]
+#[must_use]
+const fn axis_times(
+  a_min: i64, a_max: i64, b_min: i64, b_max: i64, v: i64,
+) -> Option<(Toi, Toi)> {
+  if v > 0 {
+    Some((
+      Toi { num: b_min - a_max, den: v },
+      Toi { num: b_max - a_min, den: v },
+    ))
+  } else if v < 0 {
+    Some((
+      Toi { num: a_min - b_max, den: -v },
+      Toi { num: a_max - b_min, den: -v },
+    ))
+  } else if a_min < b_max && b_min < a_max {
+    Some((Toi::BEFORE, Toi::NEVER))
+  } else {
+    None
+  }
+}
+
+/// The time that box `a`, moving by `v`, first touches box `b` (both as
+/// `[left, top, right, bottom]`), and if that's on the `x` axis.
+#[inline]
+#[must_use]
+const fn sweep_bounds(
+  a: [i64; 4], v: [i64; 2], b: [i64; 4],
+) -> Option<(Toi, bool)> {
+  if v[0] == 0 && v[1] == 0 {
+    return None;
+  }
+  let Some((enter_x, exit_x)) = axis_times(a[0], a[2], b[0], b[2], v[0]) else {
+    return None;
+  };
+  let Some((enter_y, exit_y)) = axis_times(a[1], a[3], b[1], b[3], v[1]) else {
+    return None;
+  };
+  // ties go to the `y` axis.
+  let (enter, on_x) =
+    if enter_y.lt(enter_x) { (enter_x, true) } else { (enter_y, false) };
+  let exit = if exit_y.lt(exit_x) { exit_y } else { exit_x };
+  // entering before the start means the boxes already overlap.
+  if enter.num < 0 || Toi::END.lt(enter) || !enter.lt(exit) {
+    return None;
+  }
+  Some((enter, on_x))
+}
+
+/// Finds where a box, moving by `velocity`, first touches a metatile of the
+/// map that has any of the flags in `mask`.
+///
+/// This works like [`Aabb::sweep`] against each of those metatiles, and gives
+/// the earliest hit. Metatiles that the box already overlaps are skipped (so
+/// an object stuck in a wall can move out of it), and outside of the map there
+/// are no flags.
+///
+/// Along a flat surface made of several metatiles the hit is always on that
+/// surface, so a box moving along a floor won't catch on the edges between
+/// its metatiles.
+#[inline]
+#[must_use]
+pub fn sweep_map<const N: usize>(
+  map: &MetatileMap<'_, N>, aabb: Aabb, velocity: Vec2<Fixed<i32, 8>>, mask: u8,
+) -> Option<Hit> {
+  let a = aabb.bounds();
+  let v = [velocity.x.to_bits() as i64, velocity.y.to_bits() as i64];
+  let (width, height) = map.size_in_metatiles();
+  if width == 0 || height == 0 {
+    return None;
+  }
+  let size = (N as i64 * 8) << 8;
+  // the metatiles that the swept area covers, clamped to the map.
+  let cells = |min: i64, max: i64, d: i64, count: usize| {
+    let (lo, hi) = if d < 0 { (min + d, max) } else { (min, max + d) };
+    let first = lo.div_euclid(size).max(0);
+    let last = hi.div_euclid(size).min(count as i64 - 1);
+    first..=last
+  };
+  let xs = cells(a[0], a[2], v[0], width);
+  let ys = cells(a[1], a[3], v[1], height);
+  let metatiles: &[Metatile<N>] = map.metatiles();
+  let mut best: Option<(Toi, bool)> = None;
+  for y in ys {
+    for x in xs.clone() {
+      let Some(index) = map.metatile_index(x as usize, y as usize) else {
+        continue;
+      };
+      if metatiles[usize::from(index)].flags & mask == 0 {
+        continue;
+      }
+      let b = [x * size, y * size, (x + 1) * size, (y + 1) * size];
+      if let Some((time, on_x)) = sweep_bounds(a, v, b) {
+        let better = match best {
+          None => true,
+          Some((best_time, best_x)) => {
+            time.lt(best_time) || (!best_time.lt(time) && best_x && !on_x)
+          }
+        };
+        if better {
+          best = Some((time, on_x));
+        }
+      }
+    }
+  }
+  best.map(|(time, on_x)| Hit::new(time, on_x, v))
+}
+
+/// Moves a box by `velocity` through a map, sliding along any metatiles with
+/// flags in `mask` that it hits.
+///
+/// Gives the moved box, and which sides were blocked as a normal: `blocked.y`
+/// is `-1` if the box landed on something (the top of a metatile), `1` if it
+/// hit its head, and `0` if neither. `blocked.x` is the same for walls.
+///
+/// See [`sweep_map`] for the details of how hits are found.
+#[inline]
+#[must_use]
+pub fn move_and_slide<const N: usize>(
+  map: &MetatileMap<'_, N>, aabb: Aabb, velocity: Vec2<Fixed<i32, 8>>, mask: u8,
+) -> (Aabb, Vec2<i32>) {
+  let (mut aabb, mut velocity) = (aabb, velocity);
+  let mut blocked = Vec2::new(0, 0);
+  // each hit stops the box on one axis, so there are at most two.
+  for _ in 0..2 {
+    match sweep_map(map, aabb, velocity, mask) {
+      None => return (aabb.translate(velocity), blocked),
+      Some(hit) => {
+        aabb = aabb.translate(hit.delta);
+        velocity = hit.slide_velocity(velocity);
+        blocked += hit.normal;
+      }
+    }
+  }
+  (aabb, blocked)
+}
+
+/// One of the boxes in a [`GridBroadphase`].
+#[derive(Debug, Clone, Copy)]
+struct GridEntry<H> {
+  handle: H,
+  /// The first and last cells covered, as `[x_min, y_min, x_max, y_max]`.
+  cells: [u16; 4],
+}
+
+/// Sorts up to 128 boxes into a `CELLS_X` by `CELLS_Y` grid, to find which
+/// ones might overlap.
+///
+/// Each cell is `1 << cell_shift` pixels square, and the grid starts at
+/// `(0, 0)`. Boxes that go off the edges of the grid are counted in the cells
+/// along those edges, so they're still checked (but many boxes far off the
+/// grid make those cells slow).
+///
+/// The boxes are given with a handle (such as an index into your list of
+/// entities), and the grid gives pairs of handles back. Rebuild the grid each
+/// frame with [`clear`](Self::clear) and [`insert`](Self::insert):
+///
+/// ```no_run
+/// # use gba::math::collide::*;
+/// # let boxes: [Aabb; 0] = [];
+/// // 16 by 16 cells of 16 pixels cover a 256 by 256 pixel area.
+/// let mut grid: GridBroadphase<16, 16> = GridBroadphase::new(4);
+/// for (i, aabb) in boxes.iter().enumerate() {
+///   grid.insert(i as u16, *aabb).ok();
+/// }
+/// for (a, b) in grid.pairs() {
+///   if boxes[usize::from(a)].overlaps(boxes[usize::from(b)]) {
+///     // handle the collision.
+///   }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct GridBroadphase<const CELLS_X: usize, const CELLS_Y: usize, H = u16> {
+  cell_shift: u32,
+  entries: ArrayVec<GridEntry<H>, 128>,
+  /// For each cell, a bit for each entry covering it.
+  cells: [[u128; CELLS_X]; CELLS_Y],
+}
+impl<const CELLS_X: usize, const CELLS_Y: usize, H: Copy>
+  GridBroadphase<CELLS_X, CELLS_Y, H>
+{
+  /// Makes an empty grid, with cells `1 << cell_shift` pixels square.
+  ///
+  /// ## Panics
+  /// * `cell_shift` must be less than 24.
+  /// * `CELLS_X` and `CELLS_Y` must be between 1 and 65,536.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(cell_shift: u32) -> Self {
+    assert!(cell_shift < 24, "cell_shift too big");
+    assert!(CELLS_X > 0 && CELLS_X <= 1 << 16, "bad CELLS_X");
+    assert!(CELLS_Y > 0 && CELLS_Y <= 1 << 16, "bad CELLS_Y");
+    Self {
+      cell_shift,
+      entries: ArrayVec::new(),
+      cells: [[0; CELLS_X]; CELLS_Y],
+    }
+  }
+
+  /// The number of boxes.
+  #[inline]
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// If there are no boxes.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Removes every box.
+  #[inline]
+  pub fn clear(&mut self) {
+    for entry in self.entries.iter() {
+      let [x0, y0, x1, y1] = entry.cells.map(usize::from);
+      for row in &mut self.cells[y0..=y1] {
+        row[x0..=x1].fill(0);
+      }
+    }
+    self.entries.clear();
+  }
+
+  /// Adds a box, with its handle.
+  ///
+  /// ## Failure
+  /// * If there are already 128 boxes the handle is given back.
+  #[inline]
+  pub fn insert(&mut self, handle: H, aabb: Aabb) -> Result<(), H> {
+    let bit = 1_u128 << self.entries.len();
+    let cells = self.cell_range(aabb);
+    self.entries.push(GridEntry { handle, cells }).map_err(|e| e.handle)?;
+    let [x0, y0, x1, y1] = cells.map(usize::from);
+    for row in &mut self.cells[y0..=y1] {
+      for cell in &mut row[x0..=x1] {
+        *cell |= bit;
+      }
+    }
+    Ok(())
+  }
+
+  /// Every pair of boxes that share a cell, each pair given once.
+  ///
+  /// These are only the pairs that *might* overlap, so check each one with
+  /// [`Aabb::overlaps`] (or something more exact).
+  #[inline]
+  #[must_use]
+  pub fn pairs(&self) -> CandidatePairs<'_, CELLS_X, CELLS_Y, H> {
+    CandidatePairs {
+      grid: self,
+      x: 0,
+      y: 0,
+      firsts: self.cells[0][0],
+      first: 0,
+      seconds: 0,
+    }
+  }
+
+  /// Every box that shares a cell with `aabb`.
+  ///
+  /// This is for checking one thing (such as the player's attack) against
+  /// everything in the grid. Like with [`pairs`](Self::pairs), these boxes
+  /// only *might* overlap it.
+  #[inline]
+  #[must_use]
+  pub fn query(&self, aabb: Aabb) -> GridQuery<'_, H> {
+    let [x0, y0, x1, y1] = self.cell_range(aabb).map(usize::from);
+    let mut bits = 0;
+    for row in &self.cells[y0..=y1] {
+      for cell in &row[x0..=x1] {
+        bits |= *cell;
+      }
+    }
+    GridQuery { entries: &self.entries, bits }
+  }
+
+  /// The cells that a box covers, clamped to the grid.
+  #[inline]
+  #[must_use]
+  fn cell_range(&self, aabb: Aabb) -> [u16; 4] {
+    let shift = 8 + self.cell_shift;
+    let cell = |bits: i32, count: usize| {
+      (bits >> shift).clamp(0, count as i32 - 1) as u16
+    };
+    let max = aabb.max();
+    // the bottom and right edges aren't in the box, so a box that ends right
+    // on a cell edge doesn't cover the next cell.
+    let x0 = cell(aabb.pos.x.to_bits(), CELLS_X);
+    let y0 = cell(aabb.pos.y.to_bits(), CELLS_Y);
+    let x1 = cell(max.x.to_bits().saturating_sub(1), CELLS_X).max(x0);
+    let y1 = cell(max.y.to_bits().saturating_sub(1), CELLS_Y).max(y0);
+    [x0, y0, x1, y1]
+  }
+}
+
+/// The boxes that share a cell with a given box, from
+/// [`GridBroadphase::query`].
+#[derive(Debug, Clone)]
+pub struct GridQuery<'a, H> {
+  entries: &'a [GridEntry<H>],
+  /// The entries that haven't been given yet.
+  bits: u128,
+}
+impl<H: Copy> Iterator for GridQuery<'_, H> {
+  type Item = H;
+  #[inline]
+  fn next(&mut self) -> Option<H> {
+    if self.bits == 0 {
+      return None;
+    }
+    let i = self.bits.trailing_zeros() as usize;
+    self.bits &= self.bits - 1;
+    Some(self.entries[i].handle)
+  }
+  #[inline]
+  fn size_hint(&self) -> (usize, Option<usize>) {
+    let len = self.bits.count_ones() as usize;
+    (len, Some(len))
+  }
+}
+impl<H: Copy> ExactSizeIterator for GridQuery<'_, H> {}
+impl<H: Copy> core::iter::FusedIterator for GridQuery<'_, H> {}
+
+/// The pairs of boxes that share a cell of a [`GridBroadphase`], from
+/// [`GridBroadphase::pairs`].
+#[derive(Debug, Clone)]
+pub struct CandidatePairs<'a, const CELLS_X: usize, const CELLS_Y: usize, H> {
+  grid: &'a GridBroadphase<CELLS_X, CELLS_Y, H>,
+  x: usize,
+  y: usize,
+  /// The entries in this cell that haven't been the first of a pair yet.
+  firsts: u128,
+  first: usize,
+  /// The entries in this cell after `first` that haven't been paired with it
+  /// yet.
+  seconds: u128,
+}
+impl<const CELLS_X: usize, const CELLS_Y: usize, H: Copy> Iterator
+  for CandidatePairs<'_, CELLS_X, CELLS_Y, H>
+{
+  type Item = (H, H);
+  #[inline]
+  fn next(&mut self) -> Option<(H, H)> {
+    loop {
+      if self.seconds != 0 {
+        let second = self.seconds.trailing_zeros() as usize;
+        self.seconds &= self.seconds - 1;
+        let (a, b) =
+          (&self.grid.entries[self.first], &self.grid.entries[second]);
+        // a pair can share several cells, so it's only given in the first of
+        // them (the top left of the cells they share).
+        let x = a.cells[0].max(b.cells[0]);
+        let y = a.cells[1].max(b.cells[1]);
+        if usize::from(x) == self.x && usize::from(y) == self.y {
+          return Some((a.handle, b.handle));
+        }
+      } else if self.firsts != 0 {
+        self.first = self.firsts.trailing_zeros() as usize;
+        self.firsts &= self.firsts - 1;
+        self.seconds = self.firsts;
+      } else {
+        if self.y == CELLS_Y {
+          return None;
+        }
+        self.x += 1;
+        if self.x == CELLS_X {
+          self.x = 0;
+          self.y += 1;
+          if self.y == CELLS_Y {
+            return None;
+          }
+        }
+        self.firsts = self.grid.cells[self.y][self.x];
+      }
+    }
+  }
+}
+impl<const CELLS_X: usize, const CELLS_Y: usize, H: Copy>
+  core::iter::FusedIterator for CandidatePairs<'_, CELLS_X, CELLS_Y, H>
+{
+}
@@ -0,0 +1,347 @@
+//! Interpolation and easing, for transitions, camera moves, and tweens.
+//!
+//! Everything here measures progress with a `t` that's an [`i32fx16`], where
+//! `0.0` is the start and `1.0` (`0x1_0000`) is the end. The values being
+//! interpolated can be any [`Interpolate`] type: the crate's fixed-point types,
+//! and the plain integer types.
+//!
+//! The functions without `_unclamped` clamp `t` to `0.0..=1.0` first, so the
+//! result always stays between the two end points. The `_unclamped` ones let
+//! `t` go past either end to extrapolate, and a result past the limits of the
+//! type is clamped to those limits (it never wraps). The math is done in 64
+//! bits, so there's no overflow on the way there either.
+//!
+//! Results are rounded down (towards negative infinity), like
+//! [`Fixed::mul`](crate::fixed::Fixed), and the end points are always exact:
+//! `lerp(a, b, 0.0) == a` and `lerp(a, b, 1.0) == b`.
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # use gba::math::interp::*;
+//! // slide a menu in from off the left edge over 20 frames.
+//! let mut slide = Tween::new(-64_i32, 16, 20, Easing::OutQuad);
+//! loop {
+//!   let x = slide.tick();
+//!   // ... draw the menu at `x` ...
+//!   # break;
+//! }
+//! ```
+
+use crate::fixed::{i32fx16, Fixed};
+
+/// The `t` for the end of an interpolation.
+pub const ONE: i32fx16 = i32fx16::from_bits(1 << 16);
+
+/// A type that can be interpolated.
+///
+/// The methods convert to and from the raw bits as an `i64`, which is where the
+/// interpolation math happens. This is implemented for the crate's fixed-point
+/// types and the integer types up to 32 bits, and other types shouldn't
+/// usually need it.
+pub trait Interpolate: Copy {
+  /// The raw bits of the value.
+  fn to_bits_i64(self) -> i64;
+
+  /// The value for some raw bits, clamped to the limits of the type.
+  fn from_bits_i64_saturating(bits: i64) -> Self;
+}
+
+macro_rules! impl_interpolate_int {
+  ($($t:ty),*) => {
+    $(
+      impl Interpolate for $t {
+        #[inline]
+        fn to_bits_i64(self) -> i64 {
+          self as i64
+        }
+        #[inline]
+        fn from_bits_i64_saturating(bits: i64) -> Self {
+          bits.clamp(<$t>::MIN as i64, <$t>::MAX as i64) as $t
+        }
+      }
+      impl<const B: u32> Interpolate for Fixed<$t, B> {
+        #[inline]
+        fn to_bits_i64(self) -> i64 {
+          self.to_bits() as i64
+        }
+        #[inline]
+        fn from_bits_i64_saturating(bits: i64) -> Self {
+          Self::from_bits(<$t>::from_bits_i64_saturating(bits))
+        }
+      }
+    )*
+  };
+}
+impl_interpolate_int!(i8, i16, i32, u8, u16, u32);
+
+/// Clamps `t` to `0.0..=1.0`.
+#[inline]
+#[must_use]
+pub const fn clamp_t(t: i32fx16) -> i32fx16 {
+  let bits = t.to_bits();
+  if bits < 0 {
+    i32fx16::from_bits(0)
+  } else if bits > ONE.to_bits() {
+    ONE
+  } else {
+    t
+  }
+}
+
+/// `a + (b - a) * t`, for the raw bits.
+const fn lerp_bits(a: i64, b: i64, t: i32fx16) -> i64 {
+  a + ((b - a).saturating_mul(t.to_bits() as i64) >> 16)
+}
+
+/// `(v - a) / (b - a)`, for the raw bits, as `t` bits (rounded towards zero).
+///
+/// When `a == b` there's no range, so this is 1.0 for `v >= a` and 0.0 below.
+const fn inverse_lerp_bits(a: i64, b: i64, v: i64) -> i64 {
+  if a == b {
+    return if v >= a { 1 << 16 } else { 0 };
+  }
+  ((v - a) << 16) / (b - a)
+}
+
+/// Maps `v` from `a..=b` onto `c..=d`, for the raw bits, clamped to `c..=d`.
+///
+/// This is done in one step, with no rounding of a `t` in the middle, so an
+/// exact result (such as the end points, or the middle) comes out exactly.
+/// The result is rounded towards `c`, and when `a == b` it's `d` for `v >= a`
+/// and `c` below.
+pub(crate) const fn remap_bits(v: i64, a: i64, b: i64, c: i64, d: i64) -> i64 {
+  let (low, high) = if a <= b { (a, b) } else { (b, a) };
+  let v = if v < low {
+    low
+  } else if v > high {
+    high
+  } else {
+    v
+  };
+  if a == b {
+    return if v >= a { d } else { c };
+  }
+  // the product can be past 64 bits, but the quotient is in `c..=d`.
+  c + ((v - a) as i128 * (d - c) as i128 / (b - a) as i128) as i64
+}
+
+/// The value `t` of the way from `a` to `b`, with `t` clamped to
+/// `0.0..=1.0`.
+#[inline]
+#[must_use]
+pub fn lerp<T: Interpolate>(a: T, b: T, t: i32fx16) -> T {
+  lerp_unclamped(a, b, clamp_t(t))
+}
+
+/// The value `t` of the way from `a` to `b`, where `t` can go past the ends.
+///
+/// A result past the limits of `T` is clamped to them.
+#[inline]
+#[must_use]
+pub fn lerp_unclamped<T: Interpolate>(a: T, b: T, t: i32fx16) -> T {
+  T::from_bits_i64_saturating(lerp_bits(a.to_bits_i64(), b.to_bits_i64(), t))
+}
+
+/// How far `v` is from `a` to `b`, clamped to `0.0..=1.0`.
+///
+/// This is the opposite of [`lerp`]. If `a == b` there's no range, so it's
+/// `1.0` for `v` at or past `a`, and `0.0` before it.
+#[inline]
+#[must_use]
+pub fn inverse_lerp<T: Interpolate>(a: T, b: T, v: T) -> i32fx16 {
+  clamp_t(inverse_lerp_unclamped(a, b, v))
+}
+
+/// How far `v` is from `a` to `b`, which is below `0.0` or above `1.0` when
+/// `v` is outside of `a..=b`.
+///
+/// The result is rounded towards zero, and clamped to the limits of
+/// [`i32fx16`].
+#[inline]
+#[must_use]
+pub fn inverse_lerp_unclamped<T: Interpolate>(a: T, b: T, v: T) -> i32fx16 {
+  let bits =
+    inverse_lerp_bits(a.to_bits_i64(), b.to_bits_i64(), v.to_bits_i64());
+  i32fx16::from_bits(i32::from_bits_i64_saturating(bits))
+}
+
+/// Maps `v` from the range `a..=b` onto the range `c..=d`.
+///
+/// `v` is clamped to `a..=b` first, so the result stays in `c..=d`. This is
+/// [`lerp`] of [`inverse_lerp`], but done in one step so there's no rounding
+/// in the middle (such as with a third of the way, which `t` can't hold
+/// exactly). The result is rounded towards `c`.
+#[inline]
+#[must_use]
+pub fn remap<T: Interpolate, U: Interpolate>(
+  v: T, a: T, b: T, c: U, d: U,
+) -> U {
+  let bits = remap_bits(
+    v.to_bits_i64(),
+    a.to_bits_i64(),
+    b.to_bits_i64(),
+    c.to_bits_i64(),
+    d.to_bits_i64(),
+  );
+  U::from_bits_i64_saturating(bits)
+}
+
+/// `t` as raw `u64` bits, clamped to `0.0..=1.0`.
+const fn t_bits(t: i32fx16) -> u64 {
+  clamp_t(t).to_bits() as u64
+}
+
+/// Starts slow and speeds up: `t * t`.
+#[inline]
+#[must_use]
+pub const fn ease_in_quad(t: i32fx16) -> i32fx16 {
+  let t = t_bits(t);
+  i32fx16::from_bits(((t * t) >> 16) as i32)
+}
+
+/// Starts fast and slows down: `1 - (1 - t) * (1 - t)`.
+#[inline]
+#[must_use]
+pub const fn ease_out_quad(t: i32fx16) -> i32fx16 {
+  let u = (1 << 16) - t_bits(t);
+  i32fx16::from_bits(((1 << 16) - ((u * u) >> 16)) as i32)
+}
+
+/// Speeds up and then slows down, with a gentle start and end:
+/// `t * t * (3 - 2 * t)`.
+#[inline]
+#[must_use]
+pub const fn smoothstep(t: i32fx16) -> i32fx16 {
+  let t = t_bits(t);
+  // 48 fractional bits, which is at most 2^49.
+  let curve = t * t * ((3 << 16) - 2 * t);
+  i32fx16::from_bits((curve >> 32) as i32)
+}
+
+/// Speeds up and then slows down, more sharply than [`smoothstep`]: `4 * t^3`
+/// for the first half, and the same curve mirrored for the second half.
+#[inline]
+#[must_use]
+pub const fn ease_in_out_cubic(t: i32fx16) -> i32fx16 {
+  let t = t_bits(t);
+  if t < (1 << 15) {
+    // 48 fractional bits, times 4.
+    i32fx16::from_bits(((4 * t * t * t) >> 32) as i32)
+  } else {
+    // `1 - (2 - 2 * t)^3 / 2`
+    let u = (2 << 16) - 2 * t;
+    i32fx16::from_bits(((1 << 16) - ((u * u * u) >> 33)) as i32)
+  }
+}
+
+/// The easing curves, for a [`Tween`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Easing {
+  /// No easing, `t` stays as it is.
+  #[default]
+  Linear,
+  /// [`ease_in_quad`]
+  InQuad,
+  /// [`ease_out_quad`]
+  OutQuad,
+  /// [`smoothstep`]
+  Smoothstep,
+  /// [`ease_in_out_cubic`]
+  InOutCubic,
+}
+impl Easing {
+  /// Applies the curve to `t`, which is clamped to `0.0..=1.0`.
+  #[inline]
+  #[must_use]
+  pub const fn apply(self, t: i32fx16) -> i32fx16 {
+    match self {
+      Self::Linear => clamp_t(t),
+      Self::InQuad => ease_in_quad(t),
+      Self::OutQuad => ease_out_quad(t),
+      Self::Smoothstep => smoothstep(t),
+      Self::InOutCubic => ease_in_out_cubic(t),
+    }
+  }
+}
+
+/// Goes from one value to another over some number of frames, with an easing
+/// curve.
+///
+/// Call [`tick`](Self::tick) once per frame. It gives the value for the frame
+/// after the last one, so the first `tick` is already one frame in, and the
+/// `frames`th gives `to` exactly. After that it stays at `to`.
+///
+/// A `Tween` is `const` to make, so a table of them can go in level data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tween<T> {
+  from: T,
+  to: T,
+  frames: u16,
+  elapsed: u16,
+  easing: Easing,
+}
+impl<T: Interpolate> Tween<T> {
+  /// Makes a tween from `from` to `to` over `frames` frames.
+  ///
+  /// With 0 frames, the tween is already at `to`.
+  #[inline]
+  #[must_use]
+  pub const fn new(from: T, to: T, frames: u16, easing: Easing) -> Self {
+    Self { from, to, frames, elapsed: 0, easing }
+  }
+
+  /// Moves ahead one frame, and gives the new value.
+  #[inline]
+  pub fn tick(&mut self) -> T {
+    if self.elapsed < self.frames {
+      self.elapsed += 1;
+    }
+    self.value()
+  }
+
+  /// The value for the current frame.
+  #[inline]
+  #[must_use]
+  pub fn value(&self) -> T {
+    lerp(self.from, self.to, self.easing.apply(self.progress()))
+  }
+
+  /// How far along the tween is, before easing.
+  #[inline]
+  #[must_use]
+  pub const fn progress(&self) -> i32fx16 {
+    let bits =
+      remap_bits(self.elapsed as i64, 0, self.frames as i64, 0, 1 << 16);
+    i32fx16::from_bits(bits as i32)
+  }
+
+  /// How many frames have gone by.
+  #[inline]
+  #[must_use]
+  pub const fn elapsed(&self) -> u16 {
+    self.elapsed
+  }
+
+  /// If the tween has reached `to`.
+  #[inline]
+  #[must_use]
+  pub const fn is_finished(&self) -> bool {
+    self.elapsed >= self.frames
+  }
+
+  /// Goes back to the start.
+  #[inline]
+  pub fn restart(&mut self) {
+    self.elapsed = 0;
+  }
+
+  /// Swaps the ends and heads back, so a transition can turn around partway.
+  ///
+  /// With [`Easing::Linear`] the value carries on from where it was. With the
+  /// other curves it can jump, since the curve isn't the same going back.
+  #[inline]
+  pub fn reverse(&mut self) {
+    core::mem::swap(&mut self.from, &mut self.to);
+    self.elapsed = self.frames - self.elapsed;
+  }
+}
@@ -0,0 +1,502 @@
+//! Math support for things like affine transformations.
+//!
+//! For interpolation and easing curves, see [`interp`], and for rectangle
+//! collision, see [`collide`].
+//!
+//! Angles are given as 16-bit "binary angles": the full `u16` range is one
+//! turn, so `0x4000` is 90 degrees, `0x8000` is 180 degrees, and so on. This
+//! lets angles wrap around naturally with wrapping arithmetic. It's also what
+//! [`ArcTan2`](crate::bios::ArcTan2) gives, so an angle from it can go right
+//! back into [`sin`] and [`cos`].
+//!
+//! ## Accuracy
+//! The table holds a quarter turn in 128 steps (512 steps for the whole turn),
+//! and angles between steps are linearly interpolated, so the results change
+//! smoothly as the angle does. Every result is within `2` bits (`2 / 16384`,
+//! about `0.00012`) of the exact value, and the quarter turns are exact: `sin`
+//! gives exactly `0`, `1`, `0`, and `-1` at `0`, `0x4000`, `0x8000`, and
+//! `0xC000`.
+
+use crate::fixed::{i16fx14, Fixed};
+use core::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+pub mod collide;
+pub mod interp;
+
+/// `sin` for the first quarter turn, in 128 steps (with both end points).
+///
+/// Values are `i16` with 14 fractional bits, rounded to nearest.
+const QUARTER_SINE: [i16; 129] = [
+  0, 201, 402, 603, 804, 1005, 1205, 1406, 1606, 1806, 2006, 2205, 2404, 2603,
+  2801, 2999, 3196, 3393, 3590, 3786, 3981, 4176, 4370, 4563, 4756, 4948, 5139,
+  5330, 5520, 5708, 5897, 6084, 6270, 6455, 6639, 6823, 7005, 7186, 7366, 7545,
+  7723, 7900, 8076, 8250, 8423, 8595, 8765, 8935, 9102, 9269, 9434, 9598, 9760,
+  9921, 10080, 10238, 10394, 10549, 10702, 10853, 11003, 11151, 11297, 11442,
+  11585, 11727, 11866, 12004, 12140, 12274, 12406, 12537, 12665, 12792, 12916,
+  13039, 13160, 13279, 13395, 13510, 13623, 13733, 13842, 13949, 14053, 14155,
+  14256, 14354, 14449, 14543, 14635, 14724, 14811, 14896, 14978, 15059, 15137,
+  15213, 15286, 15357, 15426, 15493, 15557, 15619, 15679, 15736, 15791, 15843,
+  15893, 15941, 15986, 16029, 16069, 16107, 16143, 16176, 16207, 16235, 16261,
+  16284, 16305, 16324, 16340, 16353, 16364, 16373, 16379, 16383, 16384,
+];
+
+/// The bits of a binary angle that pick the table step, out of a quarter turn.
+const STEP_SHIFT: u32 = 7;
+
+/// The sine of `angle` within the first quarter turn (`0..=0x4000`),
+/// interpolated between table entries.
+const fn quarter_sine(angle: u16) -> i16 {
+  let i = (angle >> STEP_SHIFT) as usize;
+  if i == QUARTER_SINE.len() - 1 {
+    return QUARTER_SINE[i];
+  }
+  let fraction = (angle & ((1 << STEP_SHIFT) - 1)) as i32;
+  let (low, high) = (QUARTER_SINE[i] as i32, QUARTER_SINE[i + 1] as i32);
+  let rounding = 1 << (STEP_SHIFT - 1);
+  (low + (((high - low) * fraction + rounding) >> STEP_SHIFT)) as i16
+}
+
+/// The sine of a binary angle.
+///
+/// See the [module docs](self) for how accurate it is.
+#[inline]
+#[must_use]
+pub const fn sin(angle: u16) -> i16fx14 {
+  let within = angle & 0x3FFF;
+  let bits = match angle >> 14 {
+    0 => quarter_sine(within),
+    1 => quarter_sine(0x4000 - within),
+    2 => -quarter_sine(within),
+    _ => -quarter_sine(0x4000 - within),
+  };
+  i16fx14::from_bits(bits)
+}
+
+/// The cosine of a binary angle.
+///
+/// See the [module docs](self) for how accurate it is.
+#[inline]
+#[must_use]
+pub const fn cos(angle: u16) -> i16fx14 {
+  sin(angle.wrapping_add(0x4000))
+}
+
+/// A 2D vector, such as a position, a velocity, or a size.
+///
+/// This is mostly used with `i32` (for pixels) and the [fixed-point
+/// types](crate::fixed) (for positions and speeds between pixels), but any
+/// type works for the operators.
+///
+/// `+`, `-`, and `*` (by a scalar) work the same as they do on `T`, which for
+/// `i32` and the fixed-point types means they panic on overflow in debug
+/// builds and wrap in release builds. For one or the other no matter the
+/// build, use the `wrapping_` and `saturating_` methods.
+///
+/// Fields are public, and [`new`](Self::new) is `const`, so vectors can be
+/// written right into level data:
+///
+/// ```no_run
+/// # use gba::math::Vec2;
+/// const SPAWN: Vec2<i32> = Vec2::new(16, 120);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Vec2<T> {
+  pub x: T,
+  pub y: T,
+}
+impl<T> Vec2<T> {
+  /// Makes a vector.
+  #[inline]
+  #[must_use]
+  pub const fn new(x: T, y: T) -> Self {
+    Self { x, y }
+  }
+}
+impl<T> From<(T, T)> for Vec2<T> {
+  #[inline]
+  fn from((x, y): (T, T)) -> Self {
+    Self { x, y }
+  }
+}
+impl<T> From<Vec2<T>> for (T, T) {
+  #[inline]
+  fn from(v: Vec2<T>) -> Self {
+    (v.x, v.y)
+  }
+}
+impl<T: Add<Output = T>> Add for Vec2<T> {
+  type Output = Self;
+  #[inline]
+  fn add(self, rhs: Self) -> Self {
+    Self { x: self.x + rhs.x, y: self.y + rhs.y }
+  }
+}
+impl<T: Sub<Output = T>> Sub for Vec2<T> {
+  type Output = Self;
+  #[inline]
+  fn sub(self, rhs: Self) -> Self {
+    Self { x: self.x - rhs.x, y: self.y - rhs.y }
+  }
+}
+impl<T: Neg<Output = T>> Neg for Vec2<T> {
+  type Output = Self;
+  #[inline]
+  fn neg(self) -> Self {
+    Self { x: -self.x, y: -self.y }
+  }
+}
+impl<T: Mul<Output = T> + Copy> Mul<T> for Vec2<T> {
+  type Output = Self;
+  #[inline]
+  fn mul(self, rhs: T) -> Self {
+    Self { x: self.x * rhs, y: self.y * rhs }
+  }
+}
+impl<T: Add<Output = T> + Copy> AddAssign for Vec2<T> {
+  #[inline]
+  fn add_assign(&mut self, rhs: Self) {
+    *self = *self + rhs;
+  }
+}
+impl<T: Sub<Output = T> + Copy> SubAssign for Vec2<T> {
+  #[inline]
+  fn sub_assign(&mut self, rhs: Self) {
+    *self = *self - rhs;
+  }
+}
+impl<T: Mul<Output = T> + Copy> MulAssign<T> for Vec2<T> {
+  #[inline]
+  fn mul_assign(&mut self, rhs: T) {
+    *self = *self * rhs;
+  }
+}
+
+macro_rules! impl_vec2_wrapping_saturating {
+  ($t:ty $(, $b:ident)?) => {
+    impl$(<const $b: u32>)? Vec2<$t> {
+      /// Adds each axis, wrapping on overflow.
+      #[inline]
+      #[must_use]
+      pub const fn wrapping_add(self, rhs: Self) -> Self {
+        Self::new(self.x.wrapping_add(rhs.x), self.y.wrapping_add(rhs.y))
+      }
+
+      /// Subtracts each axis, wrapping on overflow.
+      #[inline]
+      #[must_use]
+      pub const fn wrapping_sub(self, rhs: Self) -> Self {
+        Self::new(self.x.wrapping_sub(rhs.x), self.y.wrapping_sub(rhs.y))
+      }
+
+      /// Adds each axis, stopping at the type's limits.
+      #[inline]
+      #[must_use]
+      pub const fn saturating_add(self, rhs: Self) -> Self {
+        Self::new(self.x.saturating_add(rhs.x), self.y.saturating_add(rhs.y))
+      }
+
+      /// Subtracts each axis, stopping at the type's limits.
+      #[inline]
+      #[must_use]
+      pub const fn saturating_sub(self, rhs: Self) -> Self {
+        Self::new(self.x.saturating_sub(rhs.x), self.y.saturating_sub(rhs.y))
+      }
+    }
+  };
+}
+impl_vec2_wrapping_saturating!(i32);
+impl_vec2_wrapping_saturating!(Fixed<i16, B>, B);
+impl_vec2_wrapping_saturating!(Fixed<i32, B>, B);
+
+impl Vec2<i32> {
+  /// The dot product.
+  ///
+  /// Overflow panics in debug builds and wraps in release builds.
+  #[inline]
+  #[must_use]
+  pub const fn dot(self, rhs: Self) -> i32 {
+    self.x * rhs.x + self.y * rhs.y
+  }
+
+  /// The length squared, which is the same as the dot product with itself.
+  ///
+  /// Overflow panics in debug builds and wraps in release builds, which
+  /// happens once the length is over 46,340 or so.
+  #[inline]
+  #[must_use]
+  pub const fn length_squared(self) -> i32 {
+    self.dot(self)
+  }
+
+  /// Converts each axis to a fixed-point value.
+  ///
+  /// Overflow panics in debug builds (see
+  /// [`Fixed::from_int`](crate::fixed::Fixed)).
+  #[inline]
+  #[must_use]
+  pub const fn to_fixed<const B: u32>(self) -> Vec2<Fixed<i32, B>> {
+    Vec2::new(
+      Fixed::<i32, B>::from_int(self.x),
+      Fixed::<i32, B>::from_int(self.y),
+    )
+  }
+}
+
+macro_rules! impl_vec2_fixed {
+  ($t:ty, $wide:ty) => {
+    impl<const B: u32> Vec2<Fixed<$t, B>> {
+      /// The dot product.
+      ///
+      /// Each product is rounded down, like [`Fixed::mul`], and overflow
+      /// panics in debug builds and wraps in release builds.
+      #[inline]
+      #[must_use]
+      pub const fn dot(self, rhs: Self) -> Fixed<$t, B> {
+        self.x.mul(rhs.x).add(self.y.mul(rhs.y))
+      }
+
+      /// The length squared, which is the same as the dot product with
+      /// itself.
+      ///
+      /// Overflow panics in debug builds and wraps in release builds, which
+      /// happens once the length is over the square root of the type's
+      /// largest value.
+      #[inline]
+      #[must_use]
+      pub const fn length_squared(self) -> Fixed<$t, B> {
+        self.dot(self)
+      }
+
+      /// The length, using the BIOS [`Sqrt`](crate::bios::Sqrt).
+      ///
+      /// This works for any vector (the math is done in 64 bits), and the
+      /// result is rounded down. A length too big for the type is clamped to
+      /// the type's largest value.
+      #[inline]
+      #[must_use]
+      pub fn length(self) -> Fixed<$t, B> {
+        let (x, y) = (self.x.to_bits() as i64, self.y.to_bits() as i64);
+        let mut sum = (x * x + y * y) as u64;
+        // the BIOS only takes a `u32`, so drop pairs of bits (which is half
+        // as many bits of the root) until it fits.
+        let mut shift = 0;
+        while sum > u64::from(u32::MAX) {
+          sum >>= 2;
+          shift += 1;
+        }
+        let root = u64::from(crate::bios::Sqrt(sum as u32)) << shift;
+        Fixed::<$t, B>::from_bits(root.min(<$t>::MAX as u64) as $t)
+      }
+
+      /// A vector in the same direction with a length of about 1.0.
+      ///
+      /// The length comes from the BIOS [`Sqrt`](crate::bios::Sqrt), which
+      /// gives 16 bits of result, so the length of the result is within a few
+      /// bits of 1.0. The zero vector stays zero.
+      ///
+      /// If 1.0 doesn't fit in the type (such as with 15 fractional bits in an
+      /// `i16`), the axes wrap.
+      #[inline]
+      #[must_use]
+      pub fn normalize_fast(self) -> Self {
+        let length = self.length().to_bits() as $wide;
+        if length == 0 {
+          return self;
+        }
+        let scale = |bits: $t| (((bits as $wide) << B) / length) as $t;
+        Self::new(
+          Fixed::<$t, B>::from_bits(scale(self.x.to_bits())),
+          Fixed::<$t, B>::from_bits(scale(self.y.to_bits())),
+        )
+      }
+
+      /// Converts to whole pixels, rounding each axis down (towards negative
+      /// infinity).
+      #[inline]
+      #[must_use]
+      pub const fn to_pixels_floor(self) -> Vec2<i32> {
+        Vec2::new(self.x.int() as i32, self.y.int() as i32)
+      }
+
+      /// Converts to whole pixels, rounding each axis to the nearest (with
+      /// halves rounding up, towards positive infinity).
+      #[inline]
+      #[must_use]
+      pub const fn to_pixels_nearest(self) -> Vec2<i32> {
+        let half = (1 as $wide) << B >> 1;
+        Vec2::new(
+          ((self.x.to_bits() as $wide + half) >> B) as i32,
+          ((self.y.to_bits() as $wide + half) >> B) as i32,
+        )
+      }
+    }
+  };
+}
+impl_vec2_fixed!(i16, i64);
+impl_vec2_fixed!(i32, i64);
+
+/// A rectangle of pixels, from its top left corner and its size.
+///
+/// The rectangle covers `x..x + width` and `y..y + height`, so the right and
+/// bottom edges aren't in it, and a rectangle with a width or height of 0 (or
+/// less) is empty. The edges use saturating math, so a rectangle near the `i32`
+/// limits is cut off there rather than wrapping.
+///
+/// Like [`Vec2`], this is `const` to make, for level data:
+///
+/// ```no_run
+/// # use gba::math::Rect;
+/// const DOOR: Rect = Rect::new(200, 96, 16, 32);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rect {
+  pub x: i32,
+  pub y: i32,
+  pub width: i32,
+  pub height: i32,
+}
+impl Rect {
+  /// The whole screen.
+  pub const SCREEN: Self = Self::new(0, 0, 240, 160);
+
+  /// Makes a rectangle.
+  #[inline]
+  #[must_use]
+  pub const fn new(x: i32, y: i32, width: i32, height: i32) -> Self {
+    Self { x, y, width, height }
+  }
+
+  /// Makes a rectangle from its top left corner and size as vectors.
+  #[inline]
+  #[must_use]
+  pub const fn from_position_size(
+    position: Vec2<i32>, size: Vec2<i32>,
+  ) -> Self {
+    Self::new(position.x, position.y, size.x, size.y)
+  }
+
+  /// The top left corner.
+  #[inline]
+  #[must_use]
+  pub const fn position(self) -> Vec2<i32> {
+    Vec2::new(self.x, self.y)
+  }
+
+  /// The width and height.
+  #[inline]
+  #[must_use]
+  pub const fn size(self) -> Vec2<i32> {
+    Vec2::new(self.width, self.height)
+  }
+
+  /// The `x` just past the right edge.
+  #[inline]
+  #[must_use]
+  pub const fn right(self) -> i32 {
+    self.x.saturating_add(self.width)
+  }
+
+  /// The `y` just past the bottom edge.
+  #[inline]
+  #[must_use]
+  pub const fn bottom(self) -> i32 {
+    self.y.saturating_add(self.height)
+  }
+
+  /// If the rectangle has no pixels in it.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(self) -> bool {
+    self.width <= 0 || self.height <= 0
+  }
+
+  /// The same rectangle moved by `offset`.
+  #[inline]
+  #[must_use]
+  pub const fn translate(self, offset: Vec2<i32>) -> Self {
+    Self::new(
+      self.x.saturating_add(offset.x),
+      self.y.saturating_add(offset.y),
+      self.width,
+      self.height,
+    )
+  }
+
+  /// If the point is one of the pixels of the rectangle.
+  #[inline]
+  #[must_use]
+  pub const fn contains_point(self, point: Vec2<i32>) -> bool {
+    self.x <= point.x
+      && point.x < self.right()
+      && self.y <= point.y
+      && point.y < self.bottom()
+  }
+
+  /// If the two rectangles have any pixels in common.
+  ///
+  /// Rectangles that only touch along an edge don't intersect, and an empty
+  /// rectangle doesn't intersect anything.
+  #[inline]
+  #[must_use]
+  pub const fn intersects(self, other: Self) -> bool {
+    !self.is_empty()
+      && !other.is_empty()
+      && self.x < other.right()
+      && other.x < self.right()
+      && self.y < other.bottom()
+      && other.y < self.bottom()
+  }
+
+  /// The pixels the two rectangles have in common, if any.
+  #[inline]
+  #[must_use]
+  pub const fn intersection(self, other: Self) -> Option<Self> {
+    if !self.intersects(other) {
+      return None;
+    }
+    let x = if self.x > other.x { self.x } else { other.x };
+    let y = if self.y > other.y { self.y } else { other.y };
+    let right =
+      if self.right() < other.right() { self.right() } else { other.right() };
+    let bottom = if self.bottom() < other.bottom() {
+      self.bottom()
+    } else {
+      other.bottom()
+    };
+    Some(Self::new(x, y, right - x, bottom - y))
+  }
+
+  /// The same rectangle moved as little as possible to be inside of
+  /// `bounds`.
+  ///
+  /// If the rectangle is wider or taller than `bounds`, it's lined up with
+  /// the left or top edge of `bounds` on that axis (and sticks out past the
+  /// other edge).
+  #[inline]
+  #[must_use]
+  pub const fn clamp_within(self, bounds: Self) -> Self {
+    Self::new(
+      clamp_axis(self.x, self.width, bounds.x, bounds.right()),
+      clamp_axis(self.y, self.height, bounds.y, bounds.bottom()),
+      self.width,
+      self.height,
+    )
+  }
+}
+
+/// Moves `start..start + size` to be inside of `min..end`, or to start at
+/// `min` if it's too big to fit.
+const fn clamp_axis(start: i32, size: i32, min: i32, end: i32) -> i32 {
+  let max = end.saturating_sub(size);
+  if start > max {
+    if max > min {
+      max
+    } else {
+      min
+    }
+  } else if start < min {
+    min
+  } else {
+    start
+  }
+}
@@ -9,11 +9,22 @@ use crate::macros::on_gba_or_unimplemented;
 /// * As with all copying routines, the source must be readable for the size you
 ///   specify, and the destination must be writable for the size you specify.
 /// * The regions must not overlap.
+///
+/// ## Panics
+/// * With debug assertions on, if `dest` is in PALRAM, VRAM, or OAM (see
+///   [`in_video_memory`]). Use [`copy_u8_src`](crate::video::vram::copy_u8_src)
+///   for those.
 #[cfg_attr(feature = "on_gba", instruction_set(arm::a32))]
 #[cfg_attr(feature = "on_gba", link_section = ".iwram.copy_u8_unchecked")]
 pub unsafe extern "C" fn copy_u8_unchecked(
   dest: *mut u8, src: *const u8, byte_count: usize,
 ) {
+  if cfg!(debug_assertions) {
+    assert!(
+      byte_count == 0 || !in_video_memory(dest as usize),
+      "video memory can't be written a byte at a time, use vram::copy_u8_src"
+    );
+  }
   on_gba_or_unimplemented!(unsafe {
     // Note(Lokathor): This loop setup assumes that the `byte_count` is usually
     // greater than 0, and so subtracts first and then does a conditional
@@ -36,8 +47,9 @@ pub unsafe extern "C" fn copy_u8_unchecked(
 
 /// Copies `[u32; 8]` sized chunks, to `dest` from `src`
 ///
-/// This will, in general, be slightly faster than a generic `memcpy`, but
-/// slightly slower than using DMA.
+/// This is the same loop that `memcpy` uses once its pointers are aligned, but
+/// without the checks for alignment and leftover bytes. It's slightly slower
+/// than using DMA.
 ///
 /// Particularly, this helps with:
 /// * [`Tile4`][crate::video::Tile4] (one loop per tile).
@@ -136,3 +148,347 @@ pub unsafe extern "C" fn set_u32x80_unchecked(
     )
   });
 }
+
+/// Copies at or above this many words use
+/// [`CpuFastSet`](crate::bios::CpuFastSet) in [`copy_words`] (when DMA isn't
+/// being used).
+///
+/// Below this the setup cost of the BIOS call is more than the faster copy
+/// loop saves (the `copy_words_bench` example measures both).
+#[cfg(feature = "on_gba")]
+pub const COPY_FAST_SET_MIN_WORDS: usize = 32;
+
+/// Copies at or above this many words use DMA3 in [`copy_words`] (when DMA
+/// is allowed).
+#[cfg(feature = "on_gba")]
+pub const COPY_DMA_MIN_WORDS: usize = 8;
+
+/// Copies one element at a time (with volatile writes, so it's VRAM safe).
+#[cfg(feature = "on_gba")]
+#[inline]
+fn copy_loop<T: Copy>(src: &[T], dest: &mut [T]) {
+  for (d, s) in dest.iter_mut().zip(src) {
+    unsafe { (d as *mut T).write_volatile(*s) };
+  }
+}
+
+/// Copies words from `src` to `dest`, picking the best way for the size.
+///
+/// * When `dest` is in PALRAM, VRAM, or OAM, copies of [`COPY_DMA_MIN_WORDS`]
+///   or more use DMA3. Use [`copy_words_with`] to also allow DMA for other
+///   destinations.
+/// * Otherwise copies of [`COPY_FAST_SET_MIN_WORDS`] or more use `CpuFastSet`
+///   for the largest multiple of 8 words, and a simple loop for the rest.
+/// * Anything smaller uses a simple loop.
+///
+/// How many cycles each way takes per word, and how many it takes to set up,
+/// depends on the source and destination memory and on the wait state
+/// settings. The `copy_words_bench` example measures all of them with the
+/// [`profile`](crate::profile) stopwatch and logs them to mGBA, so run it
+/// with your own settings before changing the thresholds. Remember that a DMA
+/// pauses the CPU, including interrupts.
+///
+/// This is what the crate's own copies into video memory use, such as
+/// [`copy_tiles_4bpp`](crate::video::copy_tiles_4bpp),
+/// [`OamShadow::commit`](crate::video::obj::OamShadow::commit), and
+/// [`blit_u16`](crate::video::blit::blit_u16).
+///
+/// ## Panics
+/// * The slices must be the same length.
+#[cfg(feature = "on_gba")]
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_words(src: &[u32], dest: &mut [u32]) {
+  copy_words_with(src, dest, false);
+}
+
+/// Copies words from `src` to `dest`, optionally allowing DMA for any
+/// destination.
+///
+/// This is like [`copy_words`], except that when `allow_dma` is set DMA3 is
+/// used for large copies to any destination, not just video memory.
+///
+/// ## Panics
+/// * The slices must be the same length.
+#[cfg(feature = "on_gba")]
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_words_with(src: &[u32], dest: &mut [u32], allow_dma: bool) {
+  assert_eq!(src.len(), dest.len(), "slices must be the same length");
+  let len = src.len();
+  let dma = allow_dma || in_video_memory(dest.as_ptr() as usize);
+  if dma && len >= COPY_DMA_MIN_WORDS {
+    crate::dma::dma3_copy_u32_slice(src, dest);
+  } else if len >= COPY_FAST_SET_MIN_WORDS {
+    crate::bios::cpu_fast_copy(src, dest);
+  } else {
+    copy_loop(src, dest);
+  }
+}
+
+/// Copies halfwords from `src` to `dest`, picking the best way for the size.
+///
+/// When both slices have the same alignment (both start on a multiple of 4,
+/// or both don't) the bulk of the copy is done as words with
+/// [`copy_words`], and only the leading and trailing halfwords are copied on
+/// their own. Otherwise this uses a 16-bit DMA3 when `dest` is video memory,
+/// or a simple loop.
+///
+/// ## Panics
+/// * The slices must be the same length.
+#[cfg(feature = "on_gba")]
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_halfwords(src: &[u16], dest: &mut [u16]) {
+  copy_halfwords_with(src, dest, false);
+}
+
+/// Copies halfwords from `src` to `dest`, optionally allowing DMA for any
+/// destination.
+///
+/// This is like [`copy_halfwords`], see [`copy_words_with`] for what
+/// `allow_dma` does.
+///
+/// ## Panics
+/// * The slices must be the same length.
+#[cfg(feature = "on_gba")]
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_halfwords_with(src: &[u16], dest: &mut [u16], allow_dma: bool) {
+  assert_eq!(src.len(), dest.len(), "slices must be the same length");
+  let src_addr = src.as_ptr() as usize;
+  let dest_addr = dest.as_ptr() as usize;
+  if (src_addr ^ dest_addr) & 0b10 != 0 {
+    // The slices can never be word aligned at the same time.
+    let dma = allow_dma || in_video_memory(dest_addr);
+    if dma && src.len() >= COPY_DMA_MIN_WORDS * 2 {
+      crate::dma::dma3_copy_u16_slice(src, dest);
+    } else {
+      copy_loop(src, dest);
+    }
+    return;
+  }
+  let head = if src_addr & 0b10 != 0 { src.len().min(1) } else { 0 };
+  let words = (src.len() - head) / 2;
+  let (src_head, src_rest) = src.split_at(head);
+  let (dest_head, dest_rest) = dest.split_at_mut(head);
+  copy_loop(src_head, dest_head);
+  let (src_mid, src_tail) = src_rest.split_at(words * 2);
+  let (dest_mid, dest_tail) = dest_rest.split_at_mut(words * 2);
+  // Safety: both middle parts start aligned to 4 and have an even length.
+  let (src_words, dest_words) = unsafe {
+    (
+      core::slice::from_raw_parts(src_mid.as_ptr().cast::<u32>(), words),
+      core::slice::from_raw_parts_mut(
+        dest_mid.as_mut_ptr().cast::<u32>(),
+        words,
+      ),
+    )
+  };
+  copy_words_with(src_words, dest_words, allow_dma);
+  copy_loop(src_tail, dest_tail);
+}
+
+/// Copies `count` words to `dest` from `src` with [`copy_words`].
+///
+/// This is for the crate's own copies to video memory, where the destination
+/// is an address rather than a slice.
+///
+/// ## Safety
+/// * The source must be readable and the destination writable for `count`
+///   words.
+/// * Both pointers must be aligned to 4.
+/// * The regions must not overlap.
+#[inline]
+pub(crate) unsafe fn copy_words_to(
+  dest: *mut u32, src: *const u32, count: usize,
+) {
+  on_gba_or_unimplemented!(unsafe {
+    copy_words(
+      core::slice::from_raw_parts(src, count),
+      core::slice::from_raw_parts_mut(dest, count),
+    );
+  });
+}
+
+/// If `addr` is in IWRAM (`0x0300_0000` to `0x0300_7FFF`).
+///
+/// This can check that an [`iwram_code!`](crate::iwram_code) function (or
+/// [`iwram_data!`](crate::iwram_data) static) really was put there, such as
+/// with `debug_assert!(in_iwram(my_fn as *const () as usize))`.
+///
+/// The IWRAM is also seen over and over up to `0x03FF_FFFF`, but nothing the
+/// linker places is ever there, so those addresses don't count.
+#[inline]
+#[must_use]
+pub const fn in_iwram(addr: usize) -> bool {
+  0x0300_0000 <= addr && addr < 0x0300_8000
+}
+
+/// If `addr` is in EWRAM (`0x0200_0000` to `0x0203_FFFF`).
+///
+/// As with [`in_iwram`], the mirrors of the EWRAM don't count.
+#[inline]
+#[must_use]
+pub const fn in_ewram(addr: usize) -> bool {
+  0x0200_0000 <= addr && addr < 0x0204_0000
+}
+
+/// If `addr` is in PALRAM, VRAM, or OAM (`0x0500_0000` to `0x07FF_FFFF`,
+/// including the mirrors).
+///
+/// None of these can be written one byte at a time: a byte written to PALRAM
+/// or the background part of VRAM is written to both halves of its halfword,
+/// and a byte written to the object part of VRAM or to OAM is just ignored.
+/// Only 16-bit and 32-bit writes work, such as with
+/// [`copy_u8_src`](crate::video::vram::copy_u8_src).
+#[inline]
+#[must_use]
+pub const fn in_video_memory(addr: usize) -> bool {
+  0x0500_0000 <= addr && addr < 0x0800_0000
+}
+
+/// Puts a function in IWRAM, as ARM code.
+///
+/// Code in the ROM runs as Thumb code over the ROM's 16-bit bus, with wait
+/// states. Code in IWRAM has a 32-bit bus with no wait states, which makes
+/// ARM code the faster choice there. That's the best place for hot loops,
+/// such as sound mixing or per-pixel effects. The assembly runtime copies the
+/// IWRAM sections out of the ROM before `main` is called, so the function can
+/// be called like any other.
+///
+/// ```no_run
+/// gba::iwram_code! {
+///   /// Sums up the samples.
+///   pub fn sum(samples: &[i8]) -> i32 {
+///     let mut total = 0;
+///     let mut i = 0;
+///     while i < samples.len() {
+///       total += samples[i] as i32;
+///       i += 1;
+///     }
+///     total
+///   }
+/// }
+/// ```
+///
+/// This works with `pub`, `unsafe`, and `extern "C"` functions, with any
+/// attributes on them. Each function gets its own section
+/// (`.iwram.function_name`), so the linker only keeps the ones that are used.
+/// It's also marked `#[inline(never)]`, since inlining it would put a copy of
+/// it wherever it's called from. Without the `on_gba` feature the function is
+/// left as it is.
+///
+/// ## Keep It Small
+/// * All of the IWRAM is 32 KiB, and it holds the statics and the stack as
+///   well. If too much is put there, the link fails with an error like ``region
+///   `iwram' overflowed by 1234 bytes``. The stack grows down from the top of
+///   the IWRAM, and the linker can't see how big it gets, so leave some room. A
+///   big (or recursive) function in IWRAM can use less code space but run the
+///   stack right into the statics, which just crashes.
+/// * Calls from ARM code to Thumb code (which is everything else, including
+///   most of `core`) can't be inlined, so the fastest loops don't call anything
+///   at all. Calling other `iwram_code!` functions is fine.
+#[macro_export]
+macro_rules! iwram_code {
+  ($(#[$m:meta])* $vis:vis fn $name:ident $($rest:tt)*) => {
+    $crate::__iwram_fn! { ($(#[$m])*) ($vis fn) $name $($rest)* }
+  };
+  ($(#[$m:meta])* $vis:vis unsafe fn $name:ident $($rest:tt)*) => {
+    $crate::__iwram_fn! { ($(#[$m])*) ($vis unsafe fn) $name $($rest)* }
+  };
+  ($(#[$m:meta])* $vis:vis extern $abi:literal fn $name:ident $($rest:tt)*) => {
+    $crate::__iwram_fn! { ($(#[$m])*) ($vis extern $abi fn) $name $($rest)* }
+  };
+  (
+    $(#[$m:meta])* $vis:vis unsafe extern $abi:literal fn $name:ident
+    $($rest:tt)*
+  ) => {
+    $crate::__iwram_fn! {
+      ($(#[$m])*) ($vis unsafe extern $abi fn) $name $($rest)*
+    }
+  };
+}
+
+#[doc(hidden)]
+#[cfg(feature = "on_gba")]
+#[macro_export]
+macro_rules! __iwram_fn {
+  (($($attrs:tt)*) ($($head:tt)*) $name:ident $($rest:tt)*) => {
+    $($attrs)*
+    #[instruction_set(arm::a32)]
+    #[link_section = concat!(".iwram.", stringify!($name))]
+    #[inline(never)]
+    $($head)* $name $($rest)*
+  };
+}
+#[doc(hidden)]
+#[cfg(not(feature = "on_gba"))]
+#[macro_export]
+macro_rules! __iwram_fn {
+  (($($attrs:tt)*) ($($head:tt)*) $name:ident $($rest:tt)*) => {
+    $($attrs)*
+    $($head)* $name $($rest)*
+  };
+}
+
+/// Puts a static in EWRAM.
+///
+/// The EWRAM is 256 KiB, so it's the place for big buffers that don't fit in
+/// the 32 KiB of IWRAM. It's slower, with a 16-bit bus and 2 wait states. The
+/// assembly runtime copies the static's starting value out of the ROM before
+/// `main` is called (so even an all-zero static takes up space in the ROM).
+///
+/// ```no_run
+/// gba::ewram_data! {
+///   /// A whole mode 3 frame.
+///   static mut BACK_BUFFER: [u16; 240 * 160] = [0; 240 * 160];
+/// }
+/// ```
+///
+/// Without the `on_gba` feature the static is left as it is.
+#[macro_export]
+macro_rules! ewram_data {
+  ($(#[$m:meta])* $vis:vis static $($rest:tt)*) => {
+    $crate::__placed_static! { ".ewram" ($(#[$m])*) ($vis static) $($rest)* }
+  };
+}
+
+/// Puts a static in IWRAM.
+///
+/// Statics already go in IWRAM by default, so this is just for saying so
+/// explicitly (and keeping it that way if the defaults ever change). The IWRAM
+/// is small, see [`iwram_code!`](crate::iwram_code) for what happens when
+/// it's full.
+///
+/// ```no_run
+/// gba::iwram_data! {
+///   static mut SCRATCH: [u32; 64] = [0; 64];
+/// }
+/// ```
+#[macro_export]
+macro_rules! iwram_data {
+  ($(#[$m:meta])* $vis:vis static $($rest:tt)*) => {
+    $crate::__placed_static! { ".iwram" ($(#[$m])*) ($vis static) $($rest)* }
+  };
+}
+
+#[doc(hidden)]
+#[cfg(feature = "on_gba")]
+#[macro_export]
+macro_rules! __placed_static {
+  ($section:literal ($($attrs:tt)*) ($($head:tt)*) $($rest:tt)*) => {
+    $($attrs)*
+    #[link_section = $section]
+    $($head)* $($rest)*
+  };
+}
+#[doc(hidden)]
+#[cfg(not(feature = "on_gba"))]
+#[macro_export]
+macro_rules! __placed_static {
+  ($section:literal ($($attrs:tt)*) ($($head:tt)*) $($rest:tt)*) => {
+    $($attrs)*
+    $($head)* $($rest)*
+  };
+}
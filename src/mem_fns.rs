@@ -0,0 +1,319 @@
+//! The memory functions that the compiler calls: `memcpy`, `memmove`, and
+//! `memset`, plus the ARM EABI versions (`__aeabi_memcpy` and so on).
+//!
+//! These replace the ones from `compiler_builtins`, which are plain Thumb code
+//! in the ROM. These are ARM code in IWRAM (see
+//! [`iwram_code!`](crate::iwram_code) for why that's faster), and once both
+//! pointers are aligned they move 8 words per loop with `ldm`/`stm`.
+//! * When the pointers have the same alignment, any bytes before the first
+//!   aligned word are done one at a time, then the words, then any bytes left
+//!   over at the end.
+//! * When the pointers are both even but not the same alignment, they go a
+//!   halfword at a time. That's also what keeps copies to VRAM working, since
+//!   VRAM can't be written one byte at a time.
+//! * Anything else goes a byte at a time.
+//!
+//! `memmove` goes forwards when `dest` is before `src` (or they don't overlap
+//! at all), and otherwise goes backwards from the end, with the same loops.
+//!
+//! None of these can be used until `__start` copies the IWRAM sections, but
+//! nothing before that needs them.
+
+use crate::asm_runtime::force_a32;
+
+core::arch::global_asm! {
+  bracer::put_fn_in_section!(".iwram.__aeabi_memcpy"),
+  ".align 2",
+  ".global memcpy",
+  ".type memcpy, %function",
+  ".global __aeabi_memcpy",
+  ".type __aeabi_memcpy, %function",
+  ".global __aeabi_memcpy4",
+  ".type __aeabi_memcpy4, %function",
+  ".global __aeabi_memcpy8",
+  ".type __aeabi_memcpy8, %function",
+
+  force_a32!{
+    // memcpy(dest: r0, src: r1, count: r2) -> dest
+    "memcpy:",
+    "push   {{r0, lr}}",
+    "bl     __aeabi_memcpy",
+    "pop    {{r0, lr}}",
+    "bx     lr",
+
+    // __aeabi_memcpy(dest: r0, src: r1, count: r2)
+    "__aeabi_memcpy:",
+    "eor    r3, r0, r1",
+    "tst    r3, #3",
+    "bne    .Lmemcpy_not_same_align",
+    // Bytes until dest is aligned to 2, then to 4.
+    "tst    r0, #1",
+    "beq    1f",
+    "subs   r2, r2, #1",
+    "bxlt   lr",
+    "ldrb   r3, [r1], #1",
+    "strb   r3, [r0], #1",
+    "1:",
+    "tst    r0, #2",
+    "beq    __aeabi_memcpy4",
+    "subs   r2, r2, #2",
+    "addlt  r2, r2, #2",
+    "blt    .Lmemcpy_bytes",
+    "ldrh   r3, [r1], #2",
+    "strh   r3, [r0], #2",
+
+    // __aeabi_memcpy4(dest: r0, src: r1, count: r2), both aligned to 4.
+    "__aeabi_memcpy8:",
+    "__aeabi_memcpy4:",
+    "cmp    r2, #32",
+    "blt    2f",
+    "push   {{r4-r9}}",
+    "sub    r2, r2, #32",
+    "1:",
+    "ldmia  r1!, {{r3-r9, r12}}",
+    "stmia  r0!, {{r3-r9, r12}}",
+    "subs   r2, r2, #32",
+    "bge    1b",
+    "add    r2, r2, #32",
+    "pop    {{r4-r9}}",
+    "2:",
+    "subs   r2, r2, #4",
+    "ldrge  r3, [r1], #4",
+    "strge  r3, [r0], #4",
+    "bge    2b",
+    // The last 0 to 3 bytes: bit 1 of the count goes to C, and bit 0 to N.
+    "add    r2, r2, #4",
+    "movs   r2, r2, lsl #31",
+    "ldrhcs r3, [r1], #2",
+    "strhcs r3, [r0], #2",
+    "ldrbmi r3, [r1], #1",
+    "strbmi r3, [r0], #1",
+    "bx     lr",
+
+    ".Lmemcpy_not_same_align:",
+    "tst    r3, #1",
+    "bne    .Lmemcpy_bytes",
+    // Both are even (or both odd, which one byte fixes), so use halfwords.
+    "tst    r0, #1",
+    "beq    1f",
+    "subs   r2, r2, #1",
+    "bxlt   lr",
+    "ldrb   r3, [r1], #1",
+    "strb   r3, [r0], #1",
+    "1:",
+    "subs   r2, r2, #2",
+    "ldrhge r3, [r1], #2",
+    "strhge r3, [r0], #2",
+    "bge    1b",
+    // The count is now -1 (one byte left) or -2 (none).
+    "tst    r2, #1",
+    "ldrbne r3, [r1]",
+    "strbne r3, [r0]",
+    "bx     lr",
+
+    ".Lmemcpy_bytes:",
+    "subs   r2, r2, #1",
+    "ldrbge r3, [r1], #1",
+    "strbge r3, [r0], #1",
+    "bgt    .Lmemcpy_bytes",
+    "bx     lr",
+  },
+}
+
+core::arch::global_asm! {
+  bracer::put_fn_in_section!(".iwram.__aeabi_memmove"),
+  ".align 2",
+  ".global memmove",
+  ".type memmove, %function",
+  ".global __aeabi_memmove",
+  ".type __aeabi_memmove, %function",
+  ".global __aeabi_memmove4",
+  ".type __aeabi_memmove4, %function",
+  ".global __aeabi_memmove8",
+  ".type __aeabi_memmove8, %function",
+
+  force_a32!{
+    // memmove(dest: r0, src: r1, count: r2) -> dest
+    "memmove:",
+    "push   {{r0, lr}}",
+    "bl     __aeabi_memmove",
+    "pop    {{r0, lr}}",
+    "bx     lr",
+
+    // __aeabi_memmove(dest: r0, src: r1, count: r2)
+    "__aeabi_memmove8:",
+    "__aeabi_memmove4:",
+    "__aeabi_memmove:",
+    // Forwards is fine if dest is first, or if dest is after all of src.
+    "cmp    r0, r1",
+    "bls    __aeabi_memcpy",
+    "add    r3, r1, r2",
+    "cmp    r0, r3",
+    "bhs    __aeabi_memcpy",
+
+    // Otherwise go backwards, from the end of both.
+    "add    r0, r0, r2",
+    "mov    r1, r3",
+    "eor    r3, r0, r1",
+    "tst    r3, #3",
+    "bne    .Lmemmove_not_same_align",
+    "tst    r0, #1",
+    "beq    1f",
+    "subs   r2, r2, #1",
+    "bxlt   lr",
+    "ldrb   r3, [r1, #-1]!",
+    "strb   r3, [r0, #-1]!",
+    "1:",
+    "tst    r0, #2",
+    "beq    2f",
+    "subs   r2, r2, #2",
+    "addlt  r2, r2, #2",
+    "blt    .Lmemmove_bytes",
+    "ldrh   r3, [r1, #-2]!",
+    "strh   r3, [r0, #-2]!",
+    "2:",
+    "cmp    r2, #32",
+    "blt    2f",
+    "push   {{r4-r9}}",
+    "sub    r2, r2, #32",
+    "1:",
+    "ldmdb  r1!, {{r3-r9, r12}}",
+    "stmdb  r0!, {{r3-r9, r12}}",
+    "subs   r2, r2, #32",
+    "bge    1b",
+    "add    r2, r2, #32",
+    "pop    {{r4-r9}}",
+    "2:",
+    "subs   r2, r2, #4",
+    "ldrge  r3, [r1, #-4]!",
+    "strge  r3, [r0, #-4]!",
+    "bge    2b",
+    "add    r2, r2, #4",
+    "movs   r2, r2, lsl #31",
+    "ldrhcs r3, [r1, #-2]!",
+    "strhcs r3, [r0, #-2]!",
+    "ldrbmi r3, [r1, #-1]!",
+    "strbmi r3, [r0, #-1]!",
+    "bx     lr",
+
+    ".Lmemmove_not_same_align:",
+    "tst    r3, #1",
+    "bne    .Lmemmove_bytes",
+    "tst    r0, #1",
+    "beq    1f",
+    "subs   r2, r2, #1",
+    "bxlt   lr",
+    "ldrb   r3, [r1, #-1]!",
+    "strb   r3, [r0, #-1]!",
+    "1:",
+    "subs   r2, r2, #2",
+    "ldrhge r3, [r1, #-2]!",
+    "strhge r3, [r0, #-2]!",
+    "bge    1b",
+    "tst    r2, #1",
+    "ldrbne r3, [r1, #-1]",
+    "strbne r3, [r0, #-1]",
+    "bx     lr",
+
+    ".Lmemmove_bytes:",
+    "subs   r2, r2, #1",
+    "ldrbge r3, [r1, #-1]!",
+    "strbge r3, [r0, #-1]!",
+    "bgt    .Lmemmove_bytes",
+    "bx     lr",
+  },
+}
+
+core::arch::global_asm! {
+  bracer::put_fn_in_section!(".iwram.__aeabi_memset"),
+  ".align 2",
+  ".global memset",
+  ".type memset, %function",
+  ".global __aeabi_memset",
+  ".type __aeabi_memset, %function",
+  ".global __aeabi_memset4",
+  ".type __aeabi_memset4, %function",
+  ".global __aeabi_memset8",
+  ".type __aeabi_memset8, %function",
+  ".global __aeabi_memclr",
+  ".type __aeabi_memclr, %function",
+  ".global __aeabi_memclr4",
+  ".type __aeabi_memclr4, %function",
+  ".global __aeabi_memclr8",
+  ".type __aeabi_memclr8, %function",
+
+  force_a32!{
+    // memset(dest: r0, byte: r1, count: r2) -> dest
+    "memset:",
+    "push   {{r0, lr}}",
+    "mov    r3, r1",
+    "mov    r1, r2",
+    "mov    r2, r3",
+    "bl     __aeabi_memset",
+    "pop    {{r0, lr}}",
+    "bx     lr",
+
+    // __aeabi_memclr(dest: r0, count: r1)
+    "__aeabi_memclr8:",
+    "__aeabi_memclr4:",
+    "mov    r2, #0",
+    "b      .Lmemset_words",
+    "__aeabi_memclr:",
+    "mov    r2, #0",
+
+    // __aeabi_memset(dest: r0, count: r1, byte: r2)
+    "__aeabi_memset:",
+    "and    r2, r2, #0xFF",
+    "orr    r2, r2, r2, lsl #8",
+    "orr    r2, r2, r2, lsl #16",
+    "tst    r0, #1",
+    "beq    1f",
+    "subs   r1, r1, #1",
+    "bxlt   lr",
+    "strb   r2, [r0], #1",
+    "1:",
+    "tst    r0, #2",
+    "beq    .Lmemset_words",
+    "subs   r1, r1, #2",
+    "strhge r2, [r0], #2",
+    "bge    .Lmemset_words",
+    // The count is now -1 (one byte left) or -2 (none).
+    "tst    r1, #1",
+    "strbne r2, [r0]",
+    "bx     lr",
+
+    // __aeabi_memset4(dest: r0, count: r1, byte: r2), dest aligned to 4.
+    "__aeabi_memset8:",
+    "__aeabi_memset4:",
+    "and    r2, r2, #0xFF",
+    "orr    r2, r2, r2, lsl #8",
+    "orr    r2, r2, r2, lsl #16",
+    ".Lmemset_words:",
+    "cmp    r1, #32",
+    "blt    2f",
+    "push   {{r4-r9}}",
+    "mov    r3, r2",
+    "mov    r4, r2",
+    "mov    r5, r2",
+    "mov    r6, r2",
+    "mov    r7, r2",
+    "mov    r8, r2",
+    "mov    r9, r2",
+    "sub    r1, r1, #32",
+    "1:",
+    "stmia  r0!, {{r2-r9}}",
+    "subs   r1, r1, #32",
+    "bge    1b",
+    "add    r1, r1, #32",
+    "pop    {{r4-r9}}",
+    "2:",
+    "subs   r1, r1, #4",
+    "strge  r2, [r0], #4",
+    "bge    2b",
+    "add    r1, r1, #4",
+    "movs   r1, r1, lsl #31",
+    "strhcs r2, [r0], #2",
+    "strbmi r2, [r0], #1",
+    "bx     lr",
+  },
+}
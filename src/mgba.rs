@@ -18,19 +18,33 @@
 //! # use gba::prelude::*;
 //! use core::fmt::Write;
 //! let log_level = MgbaMessageLevel::Debug;
-//! if let Ok(logger) = MgbaBufferedLogger::try_new(log_level) {
+//! if let Ok(mut logger) = MgbaBufferedLogger::try_new(log_level) {
 //!   writeln!(logger, "hello").ok();
 //! }
 //! ```
 //!
+//! For one message at a time, the [`mgba_debug!`](crate::mgba_debug),
+//! [`mgba_info!`](crate::mgba_info), [`mgba_warning!`](crate::mgba_warning),
+//! and [`mgba_error!`](crate::mgba_error) macros do all of that, and take
+//! arguments like [`format_args!`]. When logging isn't available they skip even
+//! the formatting, so leaving them in a game costs next to nothing on real
+//! hardware. They also work in no$gba (see [`debug_log`](crate::debug_log)).
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! let frames = 12;
+//! gba::mgba_info!("{frames} frames so far");
+//! ```
+//!
 //! ## Fine Details
-//! Even when the program is running within mGBA, the [`MGBA_LOG_ENABLE`]
-//! address needs to be written with the [`MGBA_LOGGING_ENABLE_REQUEST`] value
-//! to allow logging. This is automatically done for you by the assembly
-//! runtime. If the `MGBA_LOG_ENABLE` address reads back
-//! [`MGBA_LOGGING_ENABLE_RESPONSE`] then mGBA logging is possible. If you're
-//! running outside of mGBA then the `MGBA_LOG_ENABLE` address maps to nothing.
-//! Writes will do no harm, and reads won't read the correct value.
+//! Even when the program is running within mGBA, the
+//! [`MGBA_LOG_ENABLE`](crate::mmio::MGBA_LOG_ENABLE) address needs to be
+//! written with the [`MGBA_LOGGING_ENABLE_REQUEST`] value to allow logging.
+//! This is automatically done for you by the assembly runtime. If the
+//! `MGBA_LOG_ENABLE` address reads back [`MGBA_LOGGING_ENABLE_RESPONSE`] then
+//! mGBA logging is possible. If you're running outside of mGBA then the
+//! `MGBA_LOG_ENABLE` address maps to nothing. Writes will do no harm, and reads
+//! won't read the correct value.
 //!
 //! Once you know that logging is possible, write your message to
 //! [`MGBA_LOG_BUFFER`]. This works similar to a C-style string: the first 0
@@ -41,7 +55,7 @@
 //! logs at that message level and also implicitly zeroes the message buffer so
 //! that it's ready for the next message.
 
-use crate::mmio::{MGBA_LOG_BUFFER, MGBA_LOG_ENABLE, MGBA_LOG_SEND};
+use crate::mmio::{MGBA_LOG_BUFFER, MGBA_LOG_SEND};
 
 pub const MGBA_LOGGING_ENABLE_REQUEST: u16 = 0xC0DE;
 
@@ -60,10 +74,12 @@ pub enum MgbaMessageLevel {
 }
 
 /// Returns if mGBA logging is possible.
+///
+/// The answer is checked once and then kept (see
+/// [`environment`](crate::environment)), so this is cheap to call often.
 #[inline]
 pub fn mgba_logging_available() -> bool {
-  // the `__start` function writes the request, so here we just check success.
-  MGBA_LOG_ENABLE.read() == MGBA_LOGGING_ENABLE_RESPONSE
+  crate::environment::is_mgba()
 }
 
 pub struct MgbaBufferedLogger {
@@ -110,3 +126,76 @@ impl core::fmt::Write for MgbaBufferedLogger {
     Ok(())
   }
 }
+
+/// Logs one message to mGBA at the given [`MgbaMessageLevel`], if logging is
+/// available.
+///
+/// Outside of mGBA, this goes to no$gba instead if that's where the game is
+/// running (see [`debug_log`](crate::debug_log) for how that's picked).
+///
+/// The rest of the arguments work like [`format_args!`]. A newline is added
+/// to the end, and any newlines in the message split it into more than one
+/// log entry. The level's usually picked by the other macros, such as
+/// [`mgba_info!`](crate::mgba_info), but it can also be picked at runtime:
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// # let failed = true;
+/// let level =
+///   if failed { MgbaMessageLevel::Error } else { MgbaMessageLevel::Info };
+/// gba::mgba_log!(level, "done (failed: {failed})");
+/// ```
+#[macro_export]
+macro_rules! mgba_log {
+  ($level:expr, $($arg:tt)*) => {
+    $crate::debug_log::debug_log($level, ::core::format_args!($($arg)*))
+  };
+}
+
+/// Logs a message to mGBA at the `Fatal` level, which **halts the emulator**
+/// and shows the message in a pop up.
+///
+/// Outside of mGBA this doesn't stop anything (no$gba just shows the message),
+/// and the program keeps going, so it's usually followed by a panic or a loop.
+#[macro_export]
+macro_rules! mgba_fatal {
+  ($($arg:tt)*) => {
+    $crate::mgba_log!($crate::mgba::MgbaMessageLevel::Fatal, $($arg)*)
+  };
+}
+
+/// Logs a message to mGBA at the `Error` level (see
+/// [`mgba_log!`](crate::mgba_log)).
+#[macro_export]
+macro_rules! mgba_error {
+  ($($arg:tt)*) => {
+    $crate::mgba_log!($crate::mgba::MgbaMessageLevel::Error, $($arg)*)
+  };
+}
+
+/// Logs a message to mGBA at the `Warning` level (see
+/// [`mgba_log!`](crate::mgba_log)).
+#[macro_export]
+macro_rules! mgba_warning {
+  ($($arg:tt)*) => {
+    $crate::mgba_log!($crate::mgba::MgbaMessageLevel::Warning, $($arg)*)
+  };
+}
+
+/// Logs a message to mGBA at the `Info` level (see
+/// [`mgba_log!`](crate::mgba_log)).
+#[macro_export]
+macro_rules! mgba_info {
+  ($($arg:tt)*) => {
+    $crate::mgba_log!($crate::mgba::MgbaMessageLevel::Info, $($arg)*)
+  };
+}
+
+/// Logs a message to mGBA at the `Debug` level (see
+/// [`mgba_log!`](crate::mgba_log)).
+#[macro_export]
+macro_rules! mgba_debug {
+  ($($arg:tt)*) => {
+    $crate::mgba_log!($crate::mgba::MgbaMessageLevel::Debug, $($arg)*)
+  };
+}
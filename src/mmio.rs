@@ -26,6 +26,7 @@
 
 use core::{ffi::c_void, mem::size_of};
 use crate::prelude::*;
+use crate::sio::joybus::{JoyControl, JoyStatus};
 pub use bitfrob::u8x2;
 pub use voladdress::{Safe, Unsafe, VolAddress, VolBlock, VolSeries, VolGrid2dStrided, VolGrid2d};
 
@@ -47,6 +48,7 @@ macro_rules! def_mmio {
 // Video
 
 def_mmio!(0x0400_0000 = DISPCNT: VolAddress<DisplayControl, Safe, Safe>; "Display Control");
+def_mmio!(0x0400_0002 = GREEN_SWAP: VolAddress<bool, Safe, Safe>; "Green Swap (undocumented): when set, the green channel of each pair of pixels is swapped.\n\nSee [`enable_green_swap`].");
 def_mmio!(0x0400_0004 = DISPSTAT: VolAddress<DisplayStatus, Safe, Safe>; "Display Status");
 def_mmio!(0x0400_0006 = VCOUNT: VolAddress<u16, Safe, ()>; "Vertical Counter");
 
@@ -54,6 +56,7 @@ def_mmio!(0x0400_0008 = BG0CNT: VolAddress<BackgroundControl, Safe, Safe>; "Back
 def_mmio!(0x0400_000A = BG1CNT: VolAddress<BackgroundControl, Safe, Safe>; "Background 1 Control");
 def_mmio!(0x0400_000C = BG2CNT: VolAddress<BackgroundControl, Safe, Safe>; "Background 2 Control");
 def_mmio!(0x0400_000E = BG3CNT: VolAddress<BackgroundControl, Safe, Safe>; "Background 3 Control");
+def_mmio!(0x0400_0008 = BG_CONTROL: VolBlock<BackgroundControl, Safe, Safe, 4>; "All four background controls, indexed by layer.");
 
 def_mmio!(0x0400_0010 = BG0HOFS: VolAddress<u16, (), Safe>; "Background 0 Horizontal Offset (9-bit, text mode)");
 def_mmio!(0x0400_0012 = BG0VOFS: VolAddress<u16, (), Safe>; "Background 0 Vertical Offset (9-bit, text mode)");
@@ -67,6 +70,9 @@ def_mmio!(0x0400_001A = BG2VOFS: VolAddress<u16, (), Safe>; "Background 2 Vertic
 def_mmio!(0x0400_001C = BG3HOFS: VolAddress<u16, (), Safe>; "Background 3 Horizontal Offset (9-bit, text mode)");
 def_mmio!(0x0400_001E = BG3VOFS: VolAddress<u16, (), Safe>; "Background 3 Vertical Offset (9-bit, text mode)");
 
+def_mmio!(0x0400_0010 = BG_HOFS: VolSeries<u16, (), Safe, 4, {size_of::<[u16;2]>()}>; "All four background horizontal offsets, indexed by layer.");
+def_mmio!(0x0400_0012 = BG_VOFS: VolSeries<u16, (), Safe, 4, {size_of::<[u16;2]>()}>; "All four background vertical offsets, indexed by layer.");
+
 def_mmio!(0x0400_0020 = BG2PA: VolAddress<i16fx8, (), Safe>; "Background 2 Param A (affine mode)");
 def_mmio!(0x0400_0022 = BG2PB: VolAddress<i16fx8, (), Safe>; "Background 2 Param B (affine mode)");
 def_mmio!(0x0400_0024 = BG2PC: VolAddress<i16fx8, (), Safe>; "Background 2 Param C (affine mode)");
@@ -81,17 +87,17 @@ def_mmio!(0x0400_0036 = BG3PD: VolAddress<i16fx8, (), Safe>; "Background 3 Param
 def_mmio!(0x0400_0038 = BG3X/["BG3X_L", "BG3X_H"]: VolAddress<i32fx8, (), Safe>; "Background 3 X Reference Point (affine/bitmap modes)");
 def_mmio!(0x0400_003C = BG3Y/["BG3Y_L", "BG3Y_H"]: VolAddress<i32fx8, (), Safe>; "Background 3 Y Reference Point (affine/bitmap modes)");
 
-def_mmio!(0x0400_0040 = WIN0H: VolAddress<u8x2, (), Safe>; "Window 0 Horizontal: high=left, low=(right+1)");
-def_mmio!(0x0400_0042 = WIN1H: VolAddress<u8x2, (), Safe>; "Window 1 Horizontal: high=left, low=(right+1)");
-def_mmio!(0x0400_0044 = WIN0V: VolAddress<u8x2, (), Safe>; "Window 0 Vertical: high=top, low=(bottom+1)");
-def_mmio!(0x0400_0046 = WIN1V: VolAddress<u8x2, (), Safe>; "Window 1 Vertical: high=top, low=(bottom+1)");
+def_mmio!(0x0400_0040 = WIN0H: VolAddress<WindowRange, (), Safe>; "Window 0 Horizontal: high=left, low=(right+1)");
+def_mmio!(0x0400_0042 = WIN1H: VolAddress<WindowRange, (), Safe>; "Window 1 Horizontal: high=left, low=(right+1)");
+def_mmio!(0x0400_0044 = WIN0V: VolAddress<WindowRange, (), Safe>; "Window 0 Vertical: high=top, low=(bottom+1)");
+def_mmio!(0x0400_0046 = WIN1V: VolAddress<WindowRange, (), Safe>; "Window 1 Vertical: high=top, low=(bottom+1)");
 def_mmio!(0x0400_0048 = WININ: VolAddress<WindowInside, Safe, Safe>; "Controls the inside Windows 0 and 1");
 def_mmio!(0x0400_004A = WINOUT: VolAddress<WindowOutside, Safe, Safe>; "Controls inside the object window and outside of windows");
 
 def_mmio!(0x0400_004C = MOSAIC: VolAddress<Mosaic, (), Safe>; "Sets the intensity of all mosaic effects");
 def_mmio!(0x0400_0050 = BLDCNT: VolAddress<BlendControl, Safe, Safe>; "Sets color blend effects");
-def_mmio!(0x0400_0052 = BLDALPHA: VolAddress<u8x2, Safe, Safe>;"Sets EVA(low) and EVB(high) alpha blend coefficients, allows `0..=16`, in 1/16th units");
-def_mmio!(0x0400_0054 = BLDY: VolAddress<u8, (), Safe>;"Sets EVY brightness blend coefficient, allows `0..=16`, in 1/16th units");
+def_mmio!(0x0400_0052 = BLDALPHA: VolAddress<BlendAlpha, (), Safe>;"Sets EVA(low) and EVB(high) alpha blend coefficients, allows `0..=16`, in 1/16th units");
+def_mmio!(0x0400_0054 = BLDY: VolAddress<BlendBrightness, (), Safe>;"Sets EVY brightness blend coefficient, allows `0..=16`, in 1/16th units");
 
 // Sound
 
@@ -140,6 +146,11 @@ def_mmio!(0x0400_00D8 = DMA3_DEST/["DMA3DAD"]: VolAddress<*mut c_void, (), Unsaf
 def_mmio!(0x0400_00DC = DMA3_COUNT/["DMA3CNT_L"]: VolAddress<u16, (), Unsafe>; "DMA3 Transfer Count (16-bit, 0=max)");
 def_mmio!(0x0400_00DE = DMA3_CONTROL/["DMA3_CNT_H"]: VolAddress<DmaControl, Safe, Unsafe>; "DMA3 Control Bits");
 
+def_mmio!(0x0400_00B0 = DMA_SRC: VolSeries<*const c_void, (), Unsafe, 4, {size_of::<[u32;3]>()}>; "All four DMA source addresses, indexed by channel.\n\nWhich memory each channel can read differs, see the individual registers.");
+def_mmio!(0x0400_00B4 = DMA_DEST: VolSeries<*mut c_void, (), Unsafe, 4, {size_of::<[u32;3]>()}>; "All four DMA destination addresses, indexed by channel.\n\nWhich memory each channel can write differs, see the individual registers.");
+def_mmio!(0x0400_00B8 = DMA_COUNT: VolSeries<u16, (), Unsafe, 4, {size_of::<[u32;3]>()}>; "All four DMA transfer counts, indexed by channel.\n\nChannels 0 to 2 only use the low 14 bits.");
+def_mmio!(0x0400_00BA = DMA_CONTROL: VolSeries<DmaControl, Safe, Unsafe, 4, {size_of::<[u32;3]>()}>; "All four DMA controls, indexed by channel.");
+
 // Timers
 
 def_mmio!(0x0400_0100 = TIMER0_COUNT/["TM0CNT_L"]: VolAddress<u16, Safe, ()>; "Timer 0 Count read");
@@ -158,6 +169,49 @@ def_mmio!(0x0400_010C = TIMER3_COUNT/["TM3CNT_L"]: VolAddress<u16, Safe, ()>; "T
 def_mmio!(0x0400_010C = TIMER3_RELOAD/["TM3CNT_L"]: VolAddress<u16, (), Safe>; "Timer 3 Reload write");
 def_mmio!(0x0400_010E = TIMER3_CONTROL/["TM3CNT_H"]: VolAddress<TimerControl, Safe, Safe>; "Timer 3 control");
 
+def_mmio!(0x0400_0100 = TIMER_COUNT: VolSeries<u16, Safe, (), 4, {size_of::<[u16;2]>()}>; "All four timer counts (read), indexed by timer.");
+def_mmio!(0x0400_0100 = TIMER_RELOAD: VolSeries<u16, (), Safe, 4, {size_of::<[u16;2]>()}>; "All four timer reloads (write), indexed by timer.");
+def_mmio!(0x0400_0102 = TIMER_CONTROL: VolSeries<TimerControl, Safe, Safe, 4, {size_of::<[u16;2]>()}>; "All four timer controls, indexed by timer.");
+
+/// Gets the count of timer `n`, which is read-only.
+///
+/// ## Panics
+/// * The index must be in `0..4`.
+#[inline]
+#[must_use]
+#[cfg_attr(feature="track_caller", track_caller)]
+pub const fn timer_counter(n: usize) -> VolAddress<u16, Safe, ()> {
+  assert!(n < 4, "timer index out of range");
+  TIMER_COUNT.index(n)
+}
+
+/// Gets the reload value of timer `n`, which is write-only.
+///
+/// This is the same address as [`timer_counter`]: writes set the reload, but
+/// reads give the current count.
+///
+/// ## Panics
+/// * The index must be in `0..4`.
+#[inline]
+#[must_use]
+#[cfg_attr(feature="track_caller", track_caller)]
+pub const fn timer_reload(n: usize) -> VolAddress<u16, (), Safe> {
+  assert!(n < 4, "timer index out of range");
+  TIMER_RELOAD.index(n)
+}
+
+/// Gets the control of timer `n`.
+///
+/// ## Panics
+/// * The index must be in `0..4`.
+#[inline]
+#[must_use]
+#[cfg_attr(feature="track_caller", track_caller)]
+pub const fn timer_control(n: usize) -> VolAddress<TimerControl, Safe, Safe> {
+  assert!(n < 4, "timer index out of range");
+  TIMER_CONTROL.index(n)
+}
+
 // Serial (part 1)
 
 def_mmio!(0x0400_0120 = SIODATA32: VolAddress<u32, Safe, Safe>);
@@ -166,6 +220,9 @@ def_mmio!(0x0400_0122 = SIOMULTI1: VolAddress<u16, Safe, Safe>);
 def_mmio!(0x0400_0124 = SIOMULTI2: VolAddress<u16, Safe, Safe>);
 def_mmio!(0x0400_0126 = SIOMULTI3: VolAddress<u16, Safe, Safe>);
 def_mmio!(0x0400_0128 = SIOCNT: VolAddress<u16, Safe, Safe>);
+def_mmio!(0x0400_0128 = SIOCNT_NORMAL: VolAddress<NormalControl, Safe, Safe>; "Serial control, in normal mode (see [`sio::normal`](crate::sio::normal)).");
+def_mmio!(0x0400_0128 = SIOCNT_MULTI: VolAddress<MultiplayerControl, Safe, Safe>; "Serial control, in multi-player mode (see [`sio::multiplayer`](crate::sio::multiplayer)).");
+def_mmio!(0x0400_0128 = SIOCNT_UART: VolAddress<UartControl, Safe, Safe>; "Serial control, in UART mode (see [`sio::uart`](crate::sio::uart)).");
 def_mmio!(0x0400_012A = SIOMLT_SEND: VolAddress<u16, Safe, Safe>);
 def_mmio!(0x0400_012A = SIODATA8: VolAddress<u8, Safe, Safe>);
 
@@ -176,18 +233,20 @@ def_mmio!(0x0400_0132 = KEYCNT: VolAddress<KeyControl, Safe, Safe>; "Key control
 
 // Serial (part 2)
 
-def_mmio!(0x0400_0134 = RCNT: VolAddress<u16, Safe, Safe>);
-def_mmio!(0x0400_0140 = JOYCNT: VolAddress<u16, Safe, Safe>);
-def_mmio!(0x0400_0150 = JOY_RECV: VolAddress<u32, Safe, Safe>);
-def_mmio!(0x0400_0154 = JOY_TRANS: VolAddress<u32, Safe, Safe>);
-def_mmio!(0x0400_0158 = JOYSTAT: VolAddress<u8, Safe, Safe>);
+def_mmio!(0x0400_0134 = RCNT: VolAddress<LinkPortControl, Safe, Safe>; "Link port mode selection, and the pins in general purpose mode (see [`sio`](crate::sio)).");
+def_mmio!(0x0400_0140 = JOYCNT: VolAddress<JoyControl, Safe, Safe>; "JOY Bus control (see [`sio::joybus`](crate::sio::joybus)).");
+def_mmio!(0x0400_0150 = JOY_RECV: VolAddress<u32, Safe, Safe>; "JOY Bus data written by the master.");
+def_mmio!(0x0400_0154 = JOY_TRANS: VolAddress<u32, Safe, Safe>; "JOY Bus data for the master to read.");
+def_mmio!(0x0400_0158 = JOYSTAT: VolAddress<JoyStatus, Safe, Safe>; "JOY Bus status (see [`sio::joybus`](crate::sio::joybus)).");
 
 // Interrupts
 
 def_mmio!(0x0400_0200 = IE: VolAddress<IrqBits, Safe, Safe>; "Interrupts Enabled: sets which interrupts will be accepted when a subsystem fires an interrupt");
 def_mmio!(0x0400_0202 = IF: VolAddress<IrqBits, Safe, Safe>; "Interrupts Flagged: reads which interrupts are pending, writing bit(s) will clear a pending interrupt.");
-def_mmio!(0x0400_0204 = WAITCNT: VolAddress<u16, Safe, Unsafe>; "Wait state control for interfacing with the ROM.\n\nThis can make reading the ROM give garbage when it's mis-configured!");
+def_mmio!(0x0400_0204 = WAITCNT: VolAddress<WaitstateControl, Safe, Unsafe>; "Wait state control for interfacing with the ROM.\n\nThis can make reading the ROM give garbage when it's mis-configured!");
 def_mmio!(0x0400_0208 = IME: VolAddress<bool, Safe, Safe>; "Interrupt Master Enable: Allows turning on/off all interrupts with a single access.");
+def_mmio!(0x0300_7FF8 = BIOS_IF: VolAddress<IrqBits, Safe, Safe>; "The BIOS's copy of the interrupt flags.\n\nThe BIOS `IntrWait` functions return once a bit they're waiting on is set here. Unlike [`IF`], bits are *set* by writing them (the interrupt handler would normally do this), and `IntrWait` clears them.");
+def_mmio!(0x0300_7FFA = SOFT_RESET_TARGET: VolAddress<u8, Safe, Safe>; "Where `SoftReset` starts the program again: 0 for ROM, otherwise EWRAM.");
 
 // mGBA Logging
 
@@ -195,6 +254,15 @@ def_mmio!(0x04FF_F600 = MGBA_LOG_BUFFER: VolBlock<u8, Safe, Safe, 256>; "The buf
 def_mmio!(0x04FF_F700 = MGBA_LOG_SEND: VolAddress<MgbaMessageLevel, (), Safe>; "Write to this each time you want to reset a message (it also resets the buffer).");
 def_mmio!(0x04FF_F780 = MGBA_LOG_ENABLE: VolAddress<u16, Safe, Safe>; "Allows you to attempt to activate mGBA logging.");
 
+// No$gba Debugging
+
+def_mmio!(0x04FF_FA00 = NOCASH_ID: VolBlock<u8, Safe, (), 16>; "No$gba's name and version (such as `no$gba v3.05`), padded with spaces.\n\nOutside of no$gba this is just whatever the bus gives.");
+def_mmio!(0x04FF_FA10 = NOCASH_STRING_OUT: VolAddress<*const u8, (), Unsafe>; "Write the address of a null-terminated string to print it to no$gba's debug messages, as is.");
+def_mmio!(0x04FF_FA14 = NOCASH_STRING_OUT_PARAMS: VolAddress<*const u8, (), Unsafe>; "Write the address of a null-terminated string to print it to no$gba's debug messages, with the `%param%` specials filled in.");
+def_mmio!(0x04FF_FA18 = NOCASH_STRING_OUT_PARAMS_LINE: VolAddress<*const u8, (), Unsafe>; "Like [`NOCASH_STRING_OUT_PARAMS`], and then ends the line.");
+def_mmio!(0x04FF_FA1C = NOCASH_CHAR_OUT: VolAddress<u8, (), Safe>; "Write a byte to add it to no$gba's debug messages, as is.\n\nA newline ends the message.");
+def_mmio!(0x04FF_FA20 = NOCASH_CLOCKS: VolBlock<u32, Safe, (), 2>; "The CPU cycles since no$gba started, as a 64-bit count (low word first).");
+
 // Palette RAM (PALRAM)
 
 def_mmio!(0x0500_0000 = BACKDROP_COLOR: VolAddress<Color, Safe, Safe>; "Color that's shown when no BG or OBJ draws to a pixel");
@@ -249,6 +317,46 @@ def_mmio!(0x0600_0000 = AFFINE2_SCREENBLOCKS: VolGrid2dStrided<u8x2, Safe, Safe,
 
 def_mmio!(0x0600_0000 = AFFINE3_SCREENBLOCKS: VolGrid2dStrided<u8x2, Safe, Safe, 64, 128, 25, SCREENBLOCK_INDEX_OFFSET>; "Affine screenblocks (size 3).");
 
+/// Gets a charblock by index, viewed as 4bpp tiles.
+///
+/// ## Panics
+/// * The index must be in `0..4`.
+#[inline]
+#[must_use]
+#[cfg_attr(feature="track_caller", track_caller)]
+pub const fn charblock_4bpp(charblock: usize) -> VolBlock<Tile4, Safe, Safe, 512> {
+  assert!(charblock < 4, "charblock index out of range");
+  let u = CHARBLOCK0_4BPP.index(0).as_usize() + charblock * BG_TILE_REGION_SIZE / 4;
+  unsafe { VolBlock::new(u) }
+}
+
+/// Gets a charblock by index, viewed as 8bpp tiles.
+///
+/// ## Panics
+/// * The index must be in `0..4`.
+#[inline]
+#[must_use]
+#[cfg_attr(feature="track_caller", track_caller)]
+pub const fn charblock_8bpp(charblock: usize) -> VolBlock<Tile8, Safe, Safe, 256> {
+  assert!(charblock < 4, "charblock index out of range");
+  let u = CHARBLOCK0_8BPP.index(0).as_usize() + charblock * BG_TILE_REGION_SIZE / 4;
+  unsafe { VolBlock::new(u) }
+}
+
+/// Gets a text screenblock by index.
+///
+/// ## Panics
+/// * The index must be in `0..32`.
+#[inline]
+#[must_use]
+#[cfg_attr(feature="track_caller", track_caller)]
+pub const fn text_screenblock(screenblock: usize) -> VolGrid2d<TextEntry, Safe, Safe, 32, 32> {
+  match TEXT_SCREENBLOCKS.get_frame(screenblock) {
+    Some(grid) => grid,
+    None => panic!("screenblock index out of range"),
+  }
+}
+
 def_mmio!(0x0600_0000 = VIDEO3_VRAM: VolGrid2d<Color, Safe, Safe, 240, 160>; "Video mode 3 bitmap");
 
 def_mmio!(0x0600_0000 = VIDEO4_VRAM: VolGrid2dStrided<u8x2, Safe, Safe, 120, 160, 2, 0xA000>; "Video mode 4 palette maps (frames 0 and 1). Each entry is two palette indexes.");
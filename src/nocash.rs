@@ -0,0 +1,113 @@
+//! Lets you send debug messages to the no$gba emulator.
+//!
+//! No$gba has its own debug output, separate from mGBA's (see
+//! [`mgba`](crate::mgba)). It's available when
+//! [`NOCASH_ID`](crate::mmio::NOCASH_ID) starts with `no$gba`, which
+//! [`nocash_available`] checks. Messages go to its "TTY Debug Messages" window.
+//!
+//! * [`NocashLogger`] is a [`Write`](core::fmt::Write) that sends each byte as
+//!   is, so it works like the mGBA logger.
+//! * [`nocash_print_with_params`] sends a string that no$gba fills in the
+//!   `%param%` specials of, such as `%frame%` (the frame count), `%scanline%`,
+//!   `%totalclks%` (cycles since it started), `%lastclks%` (cycles since the
+//!   last `%zeroclks%`), `%zeroclks%` itself, and registers like `%r0%` or
+//!   `%pc%`. Those are read right when the string is sent, so they're handy for
+//!   timing code.
+//! * [`nocash_clock_cycles`] reads the cycle count directly.
+//!
+//! The `mgba_*` logging macros (such as [`mgba_info!`](crate::mgba_info)) use
+//! no$gba when mGBA isn't there (see [`debug_log`](crate::debug_log)).
+//!
+//! On real hardware (and in other emulators) none of this does anything: the
+//! ID doesn't match, so the logger can't be made, and the other addresses
+//! aren't connected to anything.
+//!
+//! ```no_run
+//! # use gba::nocash::*;
+//! use core::fmt::Write;
+//! if let Ok(mut logger) = NocashLogger::try_new() {
+//!   writeln!(logger, "hello").ok();
+//! }
+//! nocash_print_with_params(c"frame %frame%, cycles %lastclks%%zeroclks%");
+//! ```
+
+use crate::mmio::{
+  NOCASH_CHAR_OUT, NOCASH_CLOCKS, NOCASH_STRING_OUT_PARAMS_LINE,
+};
+use core::ffi::CStr;
+
+/// Returns if no$gba debug messages are possible.
+///
+/// The answer is checked once and then kept (see
+/// [`environment`](crate::environment)), so this is cheap to call often.
+#[inline]
+pub fn nocash_available() -> bool {
+  crate::environment::is_nocash()
+}
+
+/// Sends a string to no$gba's debug messages with the `%param%` specials
+/// filled in, and ends the line.
+///
+/// See the [module docs](self) for the specials. This does nothing if no$gba
+/// isn't there.
+#[inline]
+pub fn nocash_print_with_params(message: &CStr) {
+  if nocash_available() {
+    // Safety: no$gba reads the string right away, while it's still borrowed.
+    unsafe { NOCASH_STRING_OUT_PARAMS_LINE.write(message.as_ptr().cast()) };
+  }
+}
+
+/// The CPU cycles since no$gba started.
+///
+/// This is `None` if no$gba isn't there.
+#[inline]
+#[must_use]
+pub fn nocash_clock_cycles() -> Option<u64> {
+  if nocash_available() {
+    let low = NOCASH_CLOCKS.index(0).read();
+    let high = NOCASH_CLOCKS.index(1).read();
+    Some(u64::from(low) | (u64::from(high) << 32))
+  } else {
+    None
+  }
+}
+
+/// Writes to no$gba's debug messages, one byte at a time.
+///
+/// Each newline ends a message, and so does dropping the logger if the last
+/// message wasn't ended yet. The bytes are sent as is, so `%param%` specials
+/// aren't filled in (use [`nocash_print_with_params`] for those).
+#[derive(Debug)]
+pub struct NocashLogger {
+  pending: bool,
+}
+impl NocashLogger {
+  /// Makes a logger, if no$gba is there.
+  #[inline]
+  pub fn try_new() -> Result<Self, ()> {
+    if nocash_available() {
+      Ok(Self { pending: false })
+    } else {
+      Err(())
+    }
+  }
+}
+impl Drop for NocashLogger {
+  #[inline]
+  fn drop(&mut self) {
+    if self.pending {
+      NOCASH_CHAR_OUT.write(b'\n');
+    }
+  }
+}
+impl core::fmt::Write for NocashLogger {
+  #[inline]
+  fn write_str(&mut self, s: &str) -> core::fmt::Result {
+    for b in s.as_bytes().iter().copied() {
+      NOCASH_CHAR_OUT.write(b);
+      self.pending = b != b'\n';
+    }
+    Ok(())
+  }
+}
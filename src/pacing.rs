@@ -0,0 +1,185 @@
+//! Fixed-timestep frame pacing, so the game runs at the same speed even when
+//! a frame misses its vblank.
+//!
+//! The usual game loop does one update per vblank. When a frame takes too
+//! long, its vblank is missed and the next update happens a frame late, so the
+//! game slows down. A [`FramePacer`] counts how many vblanks really went by
+//! since the last loop, and says how many fixed updates ("steps") to run to
+//! catch up:
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # use gba::pacing::FramePacer;
+//! # fn update() {}
+//! # fn draw() {}
+//! DISPSTAT.write(DisplayStatus::new().with_irq_vblank(true));
+//! IE.write(IrqBits::VBLANK);
+//! IME.write(true);
+//!
+//! let mut pacer = FramePacer::new(1, 4);
+//! loop {
+//!   VBlankIntrWait();
+//!   draw();
+//!   for _ in 0..pacer.tick().steps {
+//!     update();
+//!   }
+//! }
+//! ```
+//!
+//! ## The frame count
+//! [`tick`](FramePacer::tick) reads [`frame_count`], which the assembly
+//! runtime's interrupt handler counts up on each vblank interrupt (see the
+//! [`time`](crate::time) module). It only counts while the vblank interrupt is
+//! on. The pacer only looks at the difference between two counts, so the
+//! count wrapping is fine.
+//!
+//! ## Lag and the step cap
+//! Each tick after the first expects one vblank to have passed. Any more than
+//! that are *lag frames*, which are added up in
+//! [`lag_frames_total`](FramePacer::lag_frames_total) (eg: for a perf
+//! overlay). If updates themselves are what's slow, running more of them to
+//! catch up only makes the next frame later (the "spiral of death"), so the
+//! steps are capped at `max_steps` per tick. Time past the cap is dropped, and
+//! the game slows down instead.
+//!
+//! ## Steps longer than a frame
+//! With a step of more than one frame (eg: 2, for 30 updates a second), some
+//! ticks have zero steps, and [`alpha`](PacedFrame::alpha) is how far the time
+//! is between the last step and the next one. Drawing things at
+//! `previous + (current - previous) * alpha` smooths out their movement.
+//!
+//! [`FramePacer::tick_at`] does all of the math given a frame count, so it
+//! works with counts from anywhere (such as a script of frames).
+
+use crate::fixed::Fixed;
+#[cfg(feature = "on_gba")]
+use crate::time::frame_count;
+
+/// What to do this time around the game loop, from [`FramePacer::tick`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PacedFrame {
+  /// How many fixed updates to run.
+  pub steps: u32,
+  /// How far (from 0 up to, but not including, 1) the time is between the
+  /// last step and the next one.
+  ///
+  /// This is always 0 when a step is one frame long.
+  pub alpha: Fixed<i16, 14>,
+  /// How many frames more than one went by since the last tick.
+  pub lag_frames: u32,
+}
+
+/// Says how many fixed updates to run each time around the game loop.
+///
+/// See the [module docs](self) for an overview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FramePacer {
+  frames_per_step: u32,
+  max_steps: u32,
+  last_count: Option<u32>,
+  pending_frames: u32,
+  lag_frames_total: u32,
+}
+impl FramePacer {
+  /// Makes a pacer with steps that are `frames_per_step` frames long, that
+  /// gives at most `max_steps` steps per tick.
+  ///
+  /// `FramePacer::new(1, 4)` does one update per frame, and catches up on up
+  /// to 3 lag frames at a time.
+  ///
+  /// ## Panics
+  /// * If `frames_per_step` or `max_steps` is 0.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(frames_per_step: u32, max_steps: u32) -> Self {
+    assert!(frames_per_step > 0, "a step has to be at least one frame");
+    assert!(max_steps > 0, "the pacer has to allow at least one step");
+    Self {
+      frames_per_step,
+      max_steps,
+      last_count: None,
+      pending_frames: 0,
+      lag_frames_total: 0,
+    }
+  }
+
+  /// How many frames long a step is.
+  #[inline]
+  #[must_use]
+  pub const fn frames_per_step(&self) -> u32 {
+    self.frames_per_step
+  }
+
+  /// The most steps that one tick gives.
+  #[inline]
+  #[must_use]
+  pub const fn max_steps(&self) -> u32 {
+    self.max_steps
+  }
+
+  /// The lag frames of every tick so far, added up (saturating).
+  #[inline]
+  #[must_use]
+  pub const fn lag_frames_total(&self) -> u32 {
+    self.lag_frames_total
+  }
+
+  /// Works out this time around the loop from [`frame_count`].
+  ///
+  /// Call this once per loop.
+  ///
+  /// ## Panics
+  /// * With debug assertions on, if the vblank interrupt is off (see
+  ///   [`frame_count`]).
+  #[cfg(feature = "on_gba")]
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn tick(&mut self) -> PacedFrame {
+    self.tick_at(frame_count())
+  }
+
+  /// Works out this time around the loop, given the current frame count.
+  ///
+  /// The first tick (and the first after [`resync`](Self::resync)) has no
+  /// count to compare with, so it gives one step.
+  #[inline]
+  pub fn tick_at(&mut self, count: u32) -> PacedFrame {
+    let Some(last_count) = self.last_count.replace(count) else {
+      self.pending_frames = 0;
+      return PacedFrame {
+        steps: 1,
+        alpha: Fixed::<i16, 14>::from_bits(0),
+        lag_frames: 0,
+      };
+    };
+    let elapsed = count.wrapping_sub(last_count);
+    let lag_frames = elapsed.saturating_sub(1);
+    self.lag_frames_total = self.lag_frames_total.saturating_add(lag_frames);
+
+    let pending = self.pending_frames.saturating_add(elapsed);
+    let mut steps = pending / self.frames_per_step;
+    self.pending_frames = pending % self.frames_per_step;
+    if steps > self.max_steps {
+      steps = self.max_steps;
+    }
+    // `pending_frames` is below `frames_per_step`, so this is below 1.
+    let alpha =
+      (u64::from(self.pending_frames) << 14) / u64::from(self.frames_per_step);
+    PacedFrame {
+      steps,
+      alpha: Fixed::<i16, 14>::from_bits(alpha as i16),
+      lag_frames,
+    }
+  }
+
+  /// Forgets the last tick, so the next one starts fresh (with one step).
+  ///
+  /// Call this after something that's meant to take a while (such as loading
+  /// a level), so that the pacer doesn't try to catch up on it.
+  #[inline]
+  pub fn resync(&mut self) {
+    self.last_count = None;
+    self.pending_frames = 0;
+  }
+}
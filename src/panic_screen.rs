@@ -0,0 +1,185 @@
+//! A panic handler that shows the panic on the screen.
+//!
+//! [`panic_screen`] logs the panic to mGBA or no$gba (if either is there), and
+//! then shows it on the screen in white on dark red, so that a panic on real
+//! hardware still says what went wrong. With the `panic_handler` feature, it's
+//! also used as the `#[panic_handler]`, and a program can't have one of its
+//! own (the crate's examples and ROM tests leave theirs out when the feature is
+//! on, so `--all-features` builds work). Without the feature, a program's own
+//! handler can call it:
+//!
+//! ```no_run
+//! #[panic_handler]
+//! fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+//!   gba::panic_screen::panic_screen(info)
+//! }
+//! ```
+//!
+//! The screen takes over the display from whatever it was doing: it turns off
+//! interrupts and DMA, sets video mode 3 with no effects, and draws the
+//! message with the [`CGA_8X8_THICK`](crate::builtin_art::CGA_8X8_THICK) font,
+//! 30 columns by 20 rows. The message is formatted into a buffer on the stack
+//! (nothing is allocated), and anything past the end of the screen is cut off.
+//! Characters that aren't ASCII are shown as `?`.
+//!
+//! If something panics while the panic is being shown (such as a `Display`
+//! impl in the message), the screen is given up on: the backdrop turns
+//! magenta, and the CPU halts for good.
+
+use crate::{
+  dma::DmaControl,
+  fixed::{i16fx8, i32fx8},
+  gba_cell::GbaCell,
+  mmio::*,
+  video::{
+    BackgroundControl, BlendControl, Color, DisplayControl, Mosaic, VideoMode,
+  },
+};
+use core::{fmt::Write, panic::PanicInfo};
+
+/// The text columns on the screen.
+const COLUMNS: usize = 240 / 8;
+
+/// The text rows on the screen.
+const ROWS: usize = 160 / 8;
+
+/// The background color.
+const DARK_RED: Color = Color::from_rgb(12, 0, 0);
+
+/// If a panic is already being shown.
+static PANICKING: GbaCell<bool> = GbaCell::new(false);
+
+/// Text formatted into a fixed buffer, cut off when it's full.
+struct TextBuffer {
+  bytes: [u8; COLUMNS * ROWS],
+  len: usize,
+}
+impl Write for TextBuffer {
+  fn write_str(&mut self, s: &str) -> core::fmt::Result {
+    let room = self.bytes.len() - self.len;
+    let count = s.len().min(room);
+    self.bytes[self.len..self.len + count]
+      .copy_from_slice(&s.as_bytes()[..count]);
+    self.len += count;
+    Ok(())
+  }
+}
+
+/// Shows a panic on the screen (and in the emulator's log), and then loops
+/// forever.
+///
+/// See the [module docs](self).
+#[inline]
+pub fn panic_screen(info: &PanicInfo) -> ! {
+  IME.write(false);
+  if PANICKING.read() {
+    give_up();
+  }
+  PANICKING.write(true);
+
+  crate::mgba_error!("{info}");
+
+  let mut text = TextBuffer { bytes: [0; COLUMNS * ROWS], len: 0 };
+  match info.location() {
+    Some(location) => writeln!(
+      text,
+      "PANIC at {}:{}:{}",
+      location.file(),
+      location.line(),
+      location.column()
+    ),
+    None => writeln!(text, "PANIC"),
+  }
+  .ok();
+  write!(text, "{}", info.message()).ok();
+
+  take_over_display();
+  draw_text(&text.bytes[..text.len]);
+  sleep_forever()
+}
+
+/// Shows `text` on the screen the same way as a panic, and then loops forever.
+///
+/// This is for things that have to stop the program but aren't a panic (such
+/// as the test runner finding it's not in mGBA).
+#[cfg(feature = "test_runner")]
+pub(crate) fn message_screen(text: &[u8]) -> ! {
+  IME.write(false);
+  take_over_display();
+  draw_text(text);
+  sleep_forever()
+}
+
+/// Shows just a magenta backdrop, for when showing the panic went wrong.
+fn give_up() -> ! {
+  DISPCNT.write(DisplayControl::new());
+  BACKDROP_COLOR.write(Color::MAGENTA);
+  sleep_forever()
+}
+
+/// Halts with no interrupts enabled, so the CPU never wakes up again.
+fn sleep_forever() -> ! {
+  IE.write(crate::interrupts::IrqBits::new());
+  loop {
+    crate::bios::Halt();
+  }
+}
+
+/// Stops DMA, and sets up video mode 3 with nothing else going on.
+fn take_over_display() {
+  for control in DMA_CONTROL.iter() {
+    // Safety: stopping a transfer is always fine.
+    unsafe { control.write(DmaControl::new()) };
+  }
+  DISPCNT.write(DisplayControl::new().with_forced_blank(true));
+  BG2CNT.write(BackgroundControl::new());
+  BG2PA.write(i16fx8::from_bits(1 << 8));
+  BG2PB.write(i16fx8::from_bits(0));
+  BG2PC.write(i16fx8::from_bits(0));
+  BG2PD.write(i16fx8::from_bits(1 << 8));
+  BG2X.write(i32fx8::from_bits(0));
+  BG2Y.write(i32fx8::from_bits(0));
+  MOSAIC.write(Mosaic::new());
+  BLDCNT.write(BlendControl::new());
+  crate::video::mode3::clear_to(DARK_RED);
+  DISPCNT.write(
+    DisplayControl::new().with_video_mode(VideoMode::_3).with_show_bg2(true),
+  );
+}
+
+/// Draws `text` from the top left, wrapping at the edge of the screen.
+fn draw_text(text: &[u8]) {
+  let (mut column, mut row) = (0, 0);
+  for &byte in text {
+    if row >= ROWS {
+      return;
+    }
+    let glyph = match byte {
+      b'\n' => {
+        (column, row) = (0, row + 1);
+        continue;
+      }
+      // The rest of a UTF-8 character.
+      0x80..=0xBF => continue,
+      0xC0.. => b'?',
+      _ => byte,
+    };
+    let (x, y) = (column as i32 * 8, row as i32 * 8);
+    crate::video::mode3::draw_glyph(x, y, glyph, Color::WHITE, None);
+    column += 1;
+    if column == COLUMNS {
+      (column, row) = (0, row + 1);
+    }
+  }
+}
+
+#[cfg(feature = "panic_handler")]
+#[panic_handler]
+fn panic_handler(info: &PanicInfo) -> ! {
+  // so a failing ROM test is still reported when every feature is on.
+  #[cfg(feature = "test_runner")]
+  if crate::test_runner::in_test() {
+    crate::test_runner::test_panic_handler(info)
+  }
+  panic_screen(info)
+}
@@ -0,0 +1,506 @@
+//! Frame timing statistics: how much of each frame the game's update uses.
+//!
+//! A frame is 280,896 CPU cycles ([`CYCLES_PER_FRAME`]). A [`FrameTimer`]
+//! reads a [`WideTimer`] at [`frame_start`](FrameTimer::frame_start) and
+//! [`frame_end`](FrameTimer::frame_end), which go around the game's update
+//! (before the wait for vblank), and keeps the cycle counts of the last `N`
+//! frames in a [`FrameStats`]. From those you get the CPU usage, the frame
+//! rate, and the worst frame.
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # use gba::perf::*;
+//! let timers = WideTimer::new(Timer::Timer0);
+//! timers.start(TimerScale::_1);
+//! let mut perf = FrameTimer::<60>::new(timers);
+//! loop {
+//!   perf.frame_start();
+//!   // ... update the game ...
+//!   perf.frame_end();
+//!   spin_until_vblank();
+//!   // ... draw ...
+//!   # break;
+//! }
+//! ```
+//!
+//! For a breakdown of where the cycles go within a frame, [`Counters`] times
+//! named sections.
+//!
+//! The frame rate assumes the usual game loop, where a frame that runs over
+//! [`CYCLES_PER_FRAME`] misses its vblank and waits for the next one. So it's
+//! 60 as long as every frame fits, and goes down only when frames overrun.
+//! (The GBA's real refresh rate is about 59.73 Hz, but 60 is what people
+//! expect to see.)
+
+#[cfg(feature = "on_gba")]
+use crate::{timers::WideTimer, video::text::TextWriter};
+#[cfg(feature = "on_gba")]
+use core::cell::Cell;
+
+/// The CPU cycles in one frame, including the blanking periods.
+pub const CYCLES_PER_FRAME: u32 = 280_896;
+
+/// The frame rate when every frame fits into its vblank.
+const FULL_FPS: u32 = 60;
+
+/// The cycle counts of the last `N` frames.
+///
+/// This is just the math part of a [`FrameTimer`], so it works with cycle
+/// counts from anywhere. Until `N` frames are recorded, the stats are of the
+/// frames so far.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameStats<const N: usize> {
+  cycles: [u32; N],
+  len: usize,
+  next: usize,
+}
+impl<const N: usize> FrameStats<N> {
+  /// Makes stats with no frames recorded.
+  ///
+  /// ## Panics
+  /// * `N` can't be 0.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new() -> Self {
+    assert!(N > 0, "the stats need room for at least one frame");
+    Self { cycles: [0; N], len: 0, next: 0 }
+  }
+
+  /// Records the cycle count of a frame, replacing the oldest one once there
+  /// are `N`.
+  #[inline]
+  pub fn record(&mut self, cycles: u32) {
+    self.cycles[self.next] = cycles;
+    self.next = (self.next + 1) % N;
+    self.len = (self.len + 1).min(N);
+  }
+
+  /// Forgets all of the frames recorded.
+  #[inline]
+  pub fn clear(&mut self) {
+    self.len = 0;
+    self.next = 0;
+  }
+
+  /// The recorded cycle counts, in no particular order.
+  #[inline]
+  fn recorded(&self) -> &[u32] {
+    &self.cycles[..self.len]
+  }
+
+  /// How many frames are recorded, up to `N`.
+  #[inline]
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.len
+  }
+
+  /// If no frames are recorded.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// The cycle count of the newest frame, or 0 with no frames.
+  #[inline]
+  #[must_use]
+  pub const fn last_frame_cycles(&self) -> u32 {
+    if self.len == 0 {
+      0
+    } else {
+      self.cycles[(self.next + N - 1) % N]
+    }
+  }
+
+  /// The average cycle count, rounded down, or 0 with no frames.
+  #[inline]
+  #[must_use]
+  pub fn average_cycles(&self) -> u32 {
+    if self.is_empty() {
+      return 0;
+    }
+    let total: u64 = self.recorded().iter().copied().map(u64::from).sum();
+    (total / self.len as u64) as u32
+  }
+
+  /// The biggest cycle count, or 0 with no frames.
+  #[inline]
+  #[must_use]
+  pub fn worst_frame_cycles(&self) -> u32 {
+    self.recorded().iter().copied().max().unwrap_or(0)
+  }
+
+  /// The average cycle count as a percent of [`CYCLES_PER_FRAME`], rounded
+  /// to the nearest percent.
+  ///
+  /// This is over 100 when frames overrun on average.
+  #[inline]
+  #[must_use]
+  pub fn cpu_usage_percent(&self) -> u32 {
+    frame_percent(self.average_cycles())
+  }
+
+  /// The frames per second, rounded down.
+  ///
+  /// Each frame counts as taking one vblank, plus one more for each full
+  /// [`CYCLES_PER_FRAME`] that it ran over, so this is 60 until some frame
+  /// overruns. With no frames it's also 60.
+  #[inline]
+  #[must_use]
+  pub fn fps(&self) -> u32 {
+    if self.is_empty() {
+      return FULL_FPS;
+    }
+    let vblanks: u64 = self
+      .recorded()
+      .iter()
+      .map(|&cycles| u64::from(cycles.div_ceil(CYCLES_PER_FRAME).max(1)))
+      .sum();
+    (FULL_FPS as u64 * self.len as u64 / vblanks) as u32
+  }
+}
+impl<const N: usize> Default for FrameStats<N> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Times each frame's update with a running [`WideTimer`].
+///
+/// The timers have to be started at [`TimerScale::_1`] (one tick per CPU
+/// cycle) by the caller, and left running. Since only the difference between
+/// two reads is used, the timers can be shared with anything else that just
+/// reads them.
+///
+/// [`TimerScale::_1`]: crate::timers::TimerScale::_1
+#[cfg(feature = "on_gba")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FrameTimer<const N: usize> {
+  timers: WideTimer,
+  start: u32,
+  stats: FrameStats<N>,
+}
+#[cfg(feature = "on_gba")]
+impl<const N: usize> FrameTimer<N> {
+  /// Makes a frame timer that reads the timers given.
+  ///
+  /// ## Panics
+  /// * `N` can't be 0.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(timers: WideTimer) -> Self {
+    Self { timers, start: 0, stats: FrameStats::new() }
+  }
+
+  /// Marks the start of the frame's update.
+  #[inline]
+  pub fn frame_start(&mut self) {
+    self.start = self.timers.read();
+  }
+
+  /// Marks the end of the frame's update, and records the cycles since
+  /// [`frame_start`](Self::frame_start).
+  #[inline]
+  pub fn frame_end(&mut self) {
+    self.stats.record(self.timers.read().wrapping_sub(self.start));
+  }
+
+  /// The stats so far.
+  #[inline]
+  #[must_use]
+  pub const fn stats(&self) -> &FrameStats<N> {
+    &self.stats
+  }
+
+  /// See [`FrameStats::cpu_usage_percent`].
+  #[inline]
+  #[must_use]
+  pub fn cpu_usage_percent(&self) -> u32 {
+    self.stats.cpu_usage_percent()
+  }
+
+  /// See [`FrameStats::fps`].
+  #[inline]
+  #[must_use]
+  pub fn fps(&self) -> u32 {
+    self.stats.fps()
+  }
+
+  /// See [`FrameStats::worst_frame_cycles`].
+  #[inline]
+  #[must_use]
+  pub fn worst_frame_cycles(&self) -> u32 {
+    self.stats.worst_frame_cycles()
+  }
+
+  /// Prints the stats as two lines of 14 characters, starting at `col` and
+  /// `row` of the writer.
+  ///
+  /// ```text
+  /// CPU  87% 60fps
+  /// max     245123
+  /// ```
+  ///
+  /// The first line is the CPU usage and the frame rate, and the second is
+  /// the worst frame's cycle count. Each line is padded out to the same width
+  /// so that old digits get covered up, and nothing else on the rows is
+  /// touched, so this can go in a corner of a background that has other text.
+  /// The writer's cursor is left after the second line.
+  ///
+  /// `col` should be 16 or less, so that the lines fit on the screen. A line
+  /// that goes past the edge wraps to the next row, which clears that row.
+  #[inline]
+  pub fn draw_overlay(&self, writer: &mut TextWriter, col: u8, row: u8) {
+    use core::fmt::Write;
+    writer.set_cursor(col, row);
+    write!(writer, "CPU{:>4}% {:>2}fps", self.cpu_usage_percent(), self.fps())
+      .ok();
+    writer.set_cursor(col, row + 1);
+    write!(writer, "max{:>11}", self.worst_frame_cycles()).ok();
+  }
+
+  /// Logs the stats to mGBA at the debug level.
+  ///
+  /// If mGBA logging isn't available the stats just aren't logged.
+  #[inline]
+  pub fn log_mgba(&self) {
+    use crate::mgba::{MgbaBufferedLogger, MgbaMessageLevel};
+    use core::fmt::Write;
+    if let Ok(mut logger) = MgbaBufferedLogger::try_new(MgbaMessageLevel::Debug)
+    {
+      writeln!(
+        logger,
+        "frames: {}% CPU, {} fps, average {} cycles, worst {} cycles",
+        self.cpu_usage_percent(),
+        self.fps(),
+        self.stats.average_cycles(),
+        self.worst_frame_cycles()
+      )
+      .ok();
+    }
+  }
+}
+
+/// One named section's totals.
+#[cfg(feature = "on_gba")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Section {
+  name: &'static str,
+  cycles: u32,
+  calls: u32,
+}
+
+/// Cycle counts for named sections of a frame, such as "physics" and
+/// "render".
+///
+/// Each call to [`section`](Self::section) gives a guard, and the cycles from
+/// then until the guard is dropped are added to that name. There's room for
+/// `N` different names, which are found with a linear search, so keep `N`
+/// small. [`frame_reset`](Self::frame_reset) zeroes the counts (usually at
+/// the start of each frame), and [`report`](Self::report) prints them.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// # use gba::perf::*;
+/// # fn physics() {}
+/// # fn render() {}
+/// let timers = WideTimer::new(Timer::Timer0);
+/// timers.start(TimerScale::_1);
+/// let counters = Counters::<4>::new(timers);
+/// loop {
+///   counters.frame_reset();
+///   {
+///     let _physics = counters.section("physics");
+///     physics();
+///   }
+///   {
+///     let _render = counters.section("render");
+///     render();
+///   }
+///   # break;
+/// }
+/// ```
+///
+/// ## Nesting
+/// Sections can be nested, and each one only counts its *own* time: the
+/// cycles spent in a section nested within it are taken back out. So the
+/// counts of all the sections never add up to more than the time they took
+/// together. Guards have to be dropped in the opposite order that they were
+/// made (which happens on its own when each one is a local in its own scope),
+/// or the counts come out wrong.
+///
+/// ## Overhead
+/// Each guard costs two reads of the [`WideTimer`], one when it's made and one
+/// when it's dropped, plus the name search and a few adds. Only part of that
+/// lands inside the section's own count (about the cost of one timer read),
+/// the rest goes to the section around it, if there is one.
+///
+/// As with [`FrameTimer`], the timers have to be started at
+/// [`TimerScale::_1`](crate::timers::TimerScale::_1) by the caller.
+#[cfg(feature = "on_gba")]
+#[derive(Debug)]
+pub struct Counters<const N: usize> {
+  timers: WideTimer,
+  sections: [Cell<Section>; N],
+  len: Cell<usize>,
+  /// The cycles of the sections nested in the innermost running section, so
+  /// far.
+  nested: Cell<u32>,
+}
+#[cfg(feature = "on_gba")]
+impl<const N: usize> Counters<N> {
+  /// An unused slot.
+  const EMPTY: Section = Section { name: "", cycles: 0, calls: 0 };
+
+  /// Makes counters that read the timers given, with no names yet.
+  #[inline]
+  #[must_use]
+  pub const fn new(timers: WideTimer) -> Self {
+    Self {
+      timers,
+      sections: [const { Cell::new(Self::EMPTY) }; N],
+      len: Cell::new(0),
+      nested: Cell::new(0),
+    }
+  }
+
+  /// The slot for `name`, if it's been used.
+  #[inline]
+  fn find(&self, name: &str) -> Option<usize> {
+    self.sections[..self.len.get()].iter().position(|s| s.get().name == name)
+  }
+
+  /// Starts timing a section, which ends when the guard is dropped.
+  ///
+  /// ## Panics
+  /// * If this would be the `N + 1`th different name.
+  #[inline]
+  #[must_use = "the section ends when the guard is dropped"]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn section(&self, name: &'static str) -> SectionGuard<'_, N> {
+    let index = match self.find(name) {
+      Some(index) => index,
+      None => {
+        let index = self.len.get();
+        assert!(index < N, "more than {N} section names");
+        self.sections[index].set(Section { name, ..Self::EMPTY });
+        self.len.set(index + 1);
+        index
+      }
+    };
+    let outer_nested = self.nested.replace(0);
+    let start = self.timers.read();
+    SectionGuard { counters: self, index, outer_nested, start }
+  }
+
+  /// Zeroes the cycles and calls of every section.
+  ///
+  /// The names are kept, so the slots don't have to be found again.
+  #[inline]
+  pub fn frame_reset(&self) {
+    for slot in &self.sections[..self.len.get()] {
+      slot.set(Section { name: slot.get().name, ..Self::EMPTY });
+    }
+  }
+
+  /// The cycles counted for `name` since the last reset, or 0 if it's not a
+  /// section name.
+  #[inline]
+  #[must_use]
+  pub fn cycles(&self, name: &str) -> u32 {
+    self.find(name).map_or(0, |i| self.sections[i].get().cycles)
+  }
+
+  /// How many times the section `name` ran since the last reset.
+  #[inline]
+  #[must_use]
+  pub fn calls(&self, name: &str) -> u32 {
+    self.find(name).map_or(0, |i| self.sections[i].get().calls)
+  }
+
+  /// The cycles of all sections together since the last reset.
+  #[inline]
+  #[must_use]
+  pub fn total_cycles(&self) -> u32 {
+    self.sections[..self.len.get()]
+      .iter()
+      .fold(0, |total: u32, s| total.saturating_add(s.get().cycles))
+  }
+
+  /// Writes a table of the sections, with the most cycles first.
+  ///
+  /// ```text
+  /// section           cycles  calls  frame
+  /// physics           120344      1    43%
+  /// render             40112      1    14%
+  /// total             160456           57%
+  /// ```
+  ///
+  /// The last column is the share of [`CYCLES_PER_FRAME`]. To log it to mGBA,
+  /// pass an [`MgbaBufferedLogger`](crate::mgba::MgbaBufferedLogger), which
+  /// makes each line its own log entry.
+  #[inline]
+  pub fn report(&self, out: &mut impl core::fmt::Write) -> core::fmt::Result {
+    let len = self.len.get();
+    let mut sorted = [Self::EMPTY; N];
+    for (dest, slot) in sorted.iter_mut().zip(&self.sections[..len]) {
+      *dest = slot.get();
+    }
+    let sorted = &mut sorted[..len];
+    sorted.sort_unstable_by_key(|s| core::cmp::Reverse(s.cycles));
+
+    writeln!(
+      out,
+      "{:<16}{:>8}{:>7}{:>7}",
+      "section", "cycles", "calls", "frame"
+    )?;
+    for s in sorted.iter() {
+      let percent = frame_percent(s.cycles);
+      writeln!(
+        out,
+        "{:<16}{:>8}{:>7}{:>6}%",
+        s.name, s.cycles, s.calls, percent
+      )?;
+    }
+    let total = self.total_cycles();
+    writeln!(out, "{:<16}{:>8}{:>13}%", "total", total, frame_percent(total))
+  }
+}
+
+/// Some cycles as a percent of [`CYCLES_PER_FRAME`], rounded to the nearest
+/// percent.
+#[inline]
+const fn frame_percent(cycles: u32) -> u32 {
+  let frame = CYCLES_PER_FRAME as u64;
+  ((cycles as u64 * 100 + frame / 2) / frame) as u32
+}
+
+/// A running section of some [`Counters`], which ends when this is dropped.
+#[cfg(feature = "on_gba")]
+#[derive(Debug)]
+pub struct SectionGuard<'a, const N: usize> {
+  counters: &'a Counters<N>,
+  index: usize,
+  /// The nested cycles of the section around this one, to put back after.
+  outer_nested: u32,
+  start: u32,
+}
+#[cfg(feature = "on_gba")]
+impl<const N: usize> Drop for SectionGuard<'_, N> {
+  #[inline]
+  fn drop(&mut self) {
+    let elapsed = self.counters.timers.read().wrapping_sub(self.start);
+    let counters = self.counters;
+    let own = elapsed.saturating_sub(counters.nested.get());
+    let slot = &counters.sections[self.index];
+    let mut section = slot.get();
+    section.cycles = section.cycles.saturating_add(own);
+    section.calls = section.calls.saturating_add(1);
+    slot.set(section);
+    counters.nested.set(self.outer_nested.saturating_add(elapsed));
+  }
+}
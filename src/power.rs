@@ -0,0 +1,131 @@
+//! Putting the GBA into its low power "Stop" state.
+//!
+//! For short waits, halting the CPU until an interrupt (with
+//! [`Halt`](crate::bios::Halt), [`IntrWait`](crate::bios::IntrWait), or
+//! [`wait_for_vblank`](crate::video::wait_for_vblank)) is the usual way to
+//! save power. The display and sound keep running normally while halted.
+//!
+//! Stop goes further: the CPU, the display, the sound, and the timers all
+//! stop, which saves the most battery, and is how games do a "sleep mode".
+//! The catch is that only a keypad, gamepak, or serial interrupt can wake the
+//! system, so [`stop`] requires a [`StopConfig`] with at least one of those.
+
+use crate::{
+  bios::Stop,
+  interrupts::IrqBits,
+  keys::KeyControl,
+  mmio::{DISPCNT, IE, KEYCNT, SOUND_ENABLED},
+};
+
+/// The interrupts that can wake the system from [`stop`].
+///
+/// ```no_run
+/// # use gba::{prelude::*, power::*};
+/// // Sleep until any key is pressed.
+/// stop(StopConfig::new().with_keypad_wake(KeyControl::any_key())).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct StopConfig {
+  keypad: Option<KeyControl>,
+  serial: bool,
+  gamepak: bool,
+}
+impl StopConfig {
+  /// A config with no wake sources (which `stop` will refuse).
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { keypad: None, serial: false, gamepak: false }
+  }
+
+  /// Wakes up from the keypad interrupt, using the key settings given.
+  ///
+  /// The `irq_enabled` bit is always set. See [`KeyControl::from_keys`] and
+  /// [`KeyControl::any_key`].
+  #[inline]
+  #[must_use]
+  pub const fn with_keypad_wake(self, keys: KeyControl) -> Self {
+    Self { keypad: Some(keys.with_irq_enabled(true)), ..self }
+  }
+
+  /// Wakes up from the serial interrupt.
+  ///
+  /// The serial port must already be set to send its interrupt.
+  #[inline]
+  #[must_use]
+  pub const fn with_serial_wake(self, serial: bool) -> Self {
+    Self { serial, ..self }
+  }
+
+  /// Wakes up from the gamepak interrupt (which happens when the cartridge is
+  /// removed).
+  #[inline]
+  #[must_use]
+  pub const fn with_gamepak_wake(self, gamepak: bool) -> Self {
+    Self { gamepak, ..self }
+  }
+
+  /// If at least one wake source is set.
+  #[inline]
+  #[must_use]
+  pub const fn can_wake(&self) -> bool {
+    self.keypad.is_some() || self.serial || self.gamepak
+  }
+}
+
+/// Puts the system into the Stop state until one of the wake interrupts in
+/// `config` happens.
+///
+/// Before stopping, this:
+/// * Turns off the display (with `forced_blank`), so the LCD doesn't hold a
+///   frozen image.
+/// * Turns off the sound.
+/// * Sets [`IE`] to only the wake interrupts (and sets [`KEYCNT`] when waking
+///   from the keypad).
+///
+/// After waking up, [`DISPCNT`], [`IE`], and [`KEYCNT`] are put back as they
+/// were, and the sound is turned back on if it was on. Turning the sound off
+/// also resets all the other sound registers (that's how the hardware works),
+/// so you'll need to set those up again after waking. If [`IME`] is on the
+/// wake interrupt's handler runs as soon as the system wakes.
+///
+/// With a keypad wake source, it's best to wait until the keys used to go to
+/// sleep are let go, or they can wake the system right away.
+///
+/// ## Failure
+/// * If `config` has no wake sources this returns an error without doing
+///   anything, since the system could never wake up.
+///
+/// [`IME`]: crate::mmio::IME
+#[inline]
+pub fn stop(config: StopConfig) -> Result<(), ()> {
+  if !config.can_wake() {
+    return Err(());
+  }
+  let display = DISPCNT.read();
+  let sound = SOUND_ENABLED.read();
+  let ie = IE.read();
+  let keycnt = KEYCNT.read();
+
+  DISPCNT.write(display.with_forced_blank(true));
+  SOUND_ENABLED.write(sound.with_enabled(false));
+  if let Some(keys) = config.keypad {
+    KEYCNT.write(keys);
+  }
+  IE.write(
+    IrqBits::new()
+      .with_keypad(config.keypad.is_some())
+      .with_serial(config.serial)
+      .with_gamepak(config.gamepak),
+  );
+  // Safety: there's a wake source, and the display is off.
+  unsafe { Stop() };
+
+  IE.write(ie);
+  KEYCNT.write(keycnt);
+  if sound.enabled() {
+    SOUND_ENABLED.write(sound);
+  }
+  DISPCNT.write(display);
+  Ok(())
+}
@@ -3,17 +3,24 @@
 #[cfg(feature = "on_gba")]
 pub use crate::{
   asm_runtime::*, bios::*, dma::*, gba_cell::*, mgba::*, mmio::*,
-  RUST_IRQ_HANDLER,
+  video::blit::*, RUST_IRQ_HANDLER,
 };
 
 pub use crate::{
   builtin_art::*,
   fixed::*,
-  include_aligned_bytes,
+  include_aligned_bytes, include_palette, include_tiles_4bpp, include_u16_data,
+  include_u32_data,
   interrupts::*,
-  keys::*,
-  sound::*,
+  keys::{replay::*, *},
+  save::*,
+  sio::{gpio::*, multiplayer::*, normal::*, uart::*, *},
+  sound::{noise::*, tone::*, wave::*, *},
   timers::*,
-  video::{obj::*, *},
+  video::{
+    animation::*, obj::*, sprite_alloc::*, sprite_sheet::*, sprite_sort::*,
+    tile_alloc::*, *,
+  },
+  waitstate::*,
   Align4,
 };
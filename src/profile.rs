@@ -0,0 +1,150 @@
+//! Measuring how many CPU cycles some code takes.
+//!
+//! A [`Stopwatch`] runs a [`WideTimer`] at one tick per CPU cycle. Starting
+//! and stopping a stopwatch takes a few cycles itself, so that overhead is
+//! measured the first time it's needed and then subtracted from all results.
+//!
+//! By default the stopwatch functions use timers 2 and 3, which leaves timers 0
+//! and 1 free for sound. Use [`set_profile_timers`] to pick a different pair.
+
+use crate::{
+  gba_cell::GbaCell,
+  timers::{Timer, TimerScale, WideTimer},
+};
+
+/// Which timers are owned by a running stopwatch, one bit per timer.
+static TIMERS_IN_USE: GbaCell<u8> = GbaCell::new(0);
+
+/// The lower timer used by [`profiled`] and [`log_profiled`].
+static PROFILE_LOW_TIMER: GbaCell<u8> = GbaCell::new(Timer::Timer2 as u8);
+
+/// The measured overhead of a stopwatch, or `u32::MAX` if not yet measured.
+static OVERHEAD: GbaCell<u32> = GbaCell::new(u32::MAX);
+
+/// Sets the timer pair that [`profiled`] and [`log_profiled`] use.
+#[inline]
+pub fn set_profile_timers(timers: WideTimer) {
+  PROFILE_LOW_TIMER.write(timers.low() as u8);
+}
+
+/// The timer pair that [`profiled`] and [`log_profiled`] use.
+#[inline]
+#[must_use]
+pub fn profile_timers() -> WideTimer {
+  WideTimer::new(match PROFILE_LOW_TIMER.read() {
+    0 => Timer::Timer0,
+    1 => Timer::Timer1,
+    _ => Timer::Timer2,
+  })
+}
+
+/// A running measurement of CPU cycles.
+///
+/// The timers are released when the stopwatch is dropped (including when it's
+/// consumed by [`stop`](Self::stop)).
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Stopwatch {
+  timers: WideTimer,
+}
+impl Stopwatch {
+  #[inline]
+  #[must_use]
+  const fn timer_bits(timers: WideTimer) -> u8 {
+    0b11 << timers.low() as u8
+  }
+
+  /// Starts a stopwatch using the timer pair given.
+  ///
+  /// Gives an error if either timer is already in use by another stopwatch,
+  /// since restarting the timers would break the other measurement.
+  #[inline]
+  pub fn start(timers: WideTimer) -> Result<Self, ()> {
+    // measure the overhead before starting, so that doesn't get counted.
+    overhead(timers)?;
+    Self::start_raw(timers)
+  }
+
+  #[inline]
+  fn start_raw(timers: WideTimer) -> Result<Self, ()> {
+    let bits = Self::timer_bits(timers);
+    let in_use = TIMERS_IN_USE.read();
+    if in_use & bits != 0 {
+      return Err(());
+    }
+    TIMERS_IN_USE.write(in_use | bits);
+    timers.start(TimerScale::_1);
+    Ok(Self { timers })
+  }
+
+  /// CPU cycles since the stopwatch started, minus the stopwatch overhead.
+  #[inline]
+  #[must_use]
+  pub fn elapsed(&self) -> u32 {
+    self.timers.read().saturating_sub(OVERHEAD.read())
+  }
+
+  /// Stops the stopwatch, giving the CPU cycles elapsed.
+  #[inline]
+  #[must_use]
+  pub fn stop(self) -> u32 {
+    self.elapsed()
+  }
+}
+impl Drop for Stopwatch {
+  #[inline]
+  fn drop(&mut self) {
+    self.timers.stop();
+    let bits = Self::timer_bits(self.timers);
+    TIMERS_IN_USE.write(TIMERS_IN_USE.read() & !bits);
+  }
+}
+
+/// Gets the stopwatch overhead, measuring it the first time.
+#[inline]
+fn overhead(timers: WideTimer) -> Result<u32, ()> {
+  let cached = OVERHEAD.read();
+  if cached != u32::MAX {
+    return Ok(cached);
+  }
+  let watch = Stopwatch::start_raw(timers)?;
+  let measured = watch.timers.read();
+  drop(watch);
+  OVERHEAD.write(measured);
+  Ok(measured)
+}
+
+/// Runs `f`, giving its output and how many CPU cycles it took.
+///
+/// This uses the timers set by [`set_profile_timers`].
+///
+/// ## Panics
+/// * This can't be nested, or used while a [`Stopwatch`] has the same timers.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn profiled<T>(f: impl FnOnce() -> T) -> (T, u32) {
+  let watch = match Stopwatch::start(profile_timers()) {
+    Ok(watch) => watch,
+    Err(()) => panic!("the profiling timers are already in use"),
+  };
+  let out = f();
+  let cycles = watch.stop();
+  (out, cycles)
+}
+
+/// Runs `f` like [`profiled`], and logs `label: N cycles` to mGBA.
+///
+/// If mGBA logging isn't available the result just isn't logged.
+///
+/// ## Panics
+/// * As with [`profiled`].
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn log_profiled<T>(label: &str, f: impl FnOnce() -> T) -> T {
+  use crate::mgba::{MgbaBufferedLogger, MgbaMessageLevel};
+  use core::fmt::Write;
+  let (out, cycles) = profiled(f);
+  if let Ok(mut logger) = MgbaBufferedLogger::try_new(MgbaMessageLevel::Debug) {
+    writeln!(logger, "{label}: {cycles} cycles").ok();
+  }
+  out
+}
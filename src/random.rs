@@ -1,3 +1,5 @@
+use crate::keys::KeyInput;
+
 // Note(Lokathor): We have a generic LCG type below, but for now we can hide the
 // process of having to pick what multiplier and increment to use behind a
 // newtype that selects some default constants.
@@ -104,6 +106,155 @@ impl<const MUL: u32, const ADD: u32> GenericLcg32<MUL, ADD> {
   }
 }
 
+/// The [xoshiro128\*\*][xoshiro] generator, with 128 bits of state and 32 bits
+/// of output.
+///
+/// [xoshiro]: https://prng.di.unimi.it/
+///
+/// This is a better generator than [`Lcg32`]: all 32 bits of each output are
+/// good, and the period is `2^128 - 1`. Each step is only shifts, xors, and
+/// rotates (the two multiplies are by 5 and 9, which the compiler turns into
+/// shifts and adds), so it's still fast without a hardware multiply in the hot
+/// path.
+///
+/// The whole state is four `u32`, and [`to_state`](Self::to_state) and
+/// [`from_state`](Self::from_state) give and take it exactly, so a generator
+/// can be kept in a save file or next to a replay, and picked up again later
+/// with the same outputs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Xoshiro128([u32; 4]);
+impl Xoshiro128 {
+  /// Makes a generator from a single `u32` seed.
+  ///
+  /// The seed is spread out over the state (with [SplitMix32][splitmix]), so
+  /// seeds that are close together still give unrelated sequences, and every
+  /// seed (including 0) gives a working generator.
+  ///
+  /// [splitmix]: https://prng.di.unimi.it/splitmix64.c
+  #[inline]
+  #[must_use]
+  pub const fn from_seed(seed: u32) -> Self {
+    let mut state = [0; 4];
+    let mut counter = seed;
+    let mut i = 0;
+    while i < 4 {
+      counter = counter.wrapping_add(0x9E37_79B9);
+      state[i] = split_mix(counter);
+      i += 1;
+    }
+    // `split_mix` only gives 0 for one input, so this is never all zero.
+    Self(state)
+  }
+
+  /// Makes a generator from a state saved with [`to_state`](Self::to_state).
+  ///
+  /// The all zero state is the one state that doesn't work (it only ever gives
+  /// 0), so it's replaced with the state from
+  /// [`from_seed(0)`](Self::from_seed).
+  #[inline]
+  #[must_use]
+  pub const fn from_state(state: [u32; 4]) -> Self {
+    if state[0] | state[1] | state[2] | state[3] == 0 {
+      Self::from_seed(0)
+    } else {
+      Self(state)
+    }
+  }
+
+  /// The whole state of the generator.
+  #[inline]
+  #[must_use]
+  pub const fn to_state(&self) -> [u32; 4] {
+    self.0
+  }
+
+  /// Advances the generator one step, producing a `u32` of output.
+  #[inline]
+  pub fn next_u32(&mut self) -> u32 {
+    let [s0, s1, s2, s3] = &mut self.0;
+    let output = s1.wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+    let t = *s1 << 9;
+    *s2 ^= *s0;
+    *s3 ^= *s1;
+    *s1 ^= *s2;
+    *s0 ^= *s3;
+    *s2 ^= t;
+    *s3 = s3.rotate_left(11);
+    output
+  }
+}
+impl Default for Xoshiro128 {
+  #[inline]
+  fn default() -> Self {
+    Self::from_seed(0)
+  }
+}
+
+/// Scrambles `x` so that every input bit affects every output bit.
+const fn split_mix(x: u32) -> u32 {
+  let x = (x ^ (x >> 16)).wrapping_mul(0x85EB_CA6B);
+  let x = (x ^ (x >> 13)).wrapping_mul(0xC2B2_AE35);
+  x ^ (x >> 16)
+}
+
+/// Seeds a [`Xoshiro128`] from when the player first presses a key.
+///
+/// The GBA has no hardware source of randomness, but a player can't press a
+/// key on the exact same frame and timer cycle every time. So this counts the
+/// frames, and when a key is first pressed (usually on a title screen), it
+/// mixes the frame count, the low bits of a running timer, and the keys into a
+/// seed.
+///
+/// Call [`update`](Self::update) once per frame, with that frame's keys and the
+/// count of any timer that's been running since startup:
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// # use gba::random::*;
+/// TIMER0_CONTROL.write(TimerControl::new().with_enabled(true));
+/// let mut seeder = KeypressSeeder::new();
+/// let mut rng = loop {
+///   VBlankIntrWait();
+///   if let Some(rng) = seeder.update(KEYINPUT.read(), TIMER0_COUNT.read()) {
+///     break rng;
+///   }
+/// };
+/// let roll = rng.next_bounded(6) + 1;
+/// ```
+///
+/// Once there's a generator, keep using it (and if replays matter, keep its
+/// [state](Xoshiro128::to_state) rather than the keypress).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct KeypressSeeder {
+  frames: u32,
+  seeded: bool,
+}
+impl KeypressSeeder {
+  /// Makes a seeder that hasn't counted any frames yet.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { frames: 0, seeded: false }
+  }
+
+  /// Counts a frame, and on the first frame with a key pressed, gives the
+  /// seeded generator.
+  ///
+  /// After that first time this always gives `None`.
+  #[inline]
+  pub fn update(&mut self, keys: KeyInput, timer: u16) -> Option<Xoshiro128> {
+    self.frames = self.frames.wrapping_add(1);
+    if self.seeded || keys.iter_pressed().next().is_none() {
+      return None;
+    }
+    self.seeded = true;
+    let entropy = self.frames.rotate_left(16)
+      ^ u32::from(timer)
+      ^ (u32::from(keys.to_u16()) << 6);
+    Some(Xoshiro128::from_seed(split_mix(entropy)))
+  }
+}
+
 /// A trait for pseudorandom number generators that have `u32`
 /// output from each step of the generator.
 pub trait Gen32 {
@@ -151,6 +302,41 @@ pub trait Gen32 {
     high
   }
 
+  /// Produce a value that's strictly less than `b`, with the full `u32` range.
+  ///
+  /// Like [`next_bounded`](Self::next_bounded), this is [Lemire's
+  /// method][lemire], so there's no bias towards lower values.
+  ///
+  /// [lemire]: https://arxiv.org/abs/1805.10941
+  ///
+  /// ## Panics
+  /// * If `b` is zero.
+  #[inline]
+  #[track_caller]
+  fn next_bounded_u32(&mut self, b: u32) -> u32 {
+    assert!(b != 0, "Gen32::next_bounded_u32> Bound must be non-zero.");
+    let mut mul: u64 = u64::from(b) * u64::from(self.next_u32());
+    if (mul as u32) < b {
+      let threshold = b.wrapping_neg() % b;
+      while (mul as u32) < threshold {
+        mul = u64::from(b) * u64::from(self.next_u32());
+      }
+    }
+    (mul >> 32) as u32
+  }
+
+  /// Produce `true` with a chance of `numerator` in `denominator`.
+  ///
+  /// A `numerator` at or above the `denominator` is always `true`.
+  ///
+  /// ## Panics
+  /// * If `denominator` is zero.
+  #[inline]
+  #[track_caller]
+  fn chance(&mut self, numerator: u16, denominator: u16) -> bool {
+    self.next_bounded(denominator) < numerator
+  }
+
   /// Pick a random element of the slice, by value.
   ///
   /// ## Panics
@@ -206,6 +392,13 @@ impl Gen32 for Lcg32 {
   }
 }
 
+impl Gen32 for Xoshiro128 {
+  #[inline]
+  fn next_u32(&mut self) -> u32 {
+    Xoshiro128::next_u32(self)
+  }
+}
+
 #[inline]
 const fn saturating_usize_as_u16(val: usize) -> u16 {
   if val <= u16::MAX as usize {
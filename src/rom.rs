@@ -0,0 +1,435 @@
+//! The cartridge header at the start of every GBA program.
+//!
+//! The BIOS won't run a program unless the first 192 bytes are a valid
+//! [`Header`]: the Nintendo logo has to be exact, a fixed byte has to be
+//! `0x96`, and the complement check has to match the bytes before it. Those
+//! are usually fixed up after building with an external tool (`gbafix`), but a
+//! [`Header`] can be built in a `const` with all of them right.
+//!
+//! The crate's assembly runtime already starts the program with a valid header
+//! (with the multiboot fields after it), so a ROM built with it boots without
+//! any more steps. Its title, game code, and maker code are blank, unless the
+//! `GBA_ROM_TITLE`, `GBA_ROM_GAME_CODE`, and `GBA_ROM_MAKER_CODE` environment
+//! variables are set when the crate is built, such as in the `[env]` section
+//! of `.cargo/config.toml`:
+//!
+//! ```toml
+//! [env]
+//! GBA_ROM_TITLE = "MYGAME"
+//! GBA_ROM_GAME_CODE = "AMGE"
+//! GBA_ROM_MAKER_CODE = "01"
+//! ```
+//!
+//! [`Header::validate`] checks a header that came from somewhere else, such
+//! as a program about to be sent with multiboot (see
+//! [`sio::multiboot`](crate::sio::multiboot)).
+
+/// The logo bitmap that the BIOS checks for (and shows while booting).
+pub const LOGO: [u8; 156] = [
+  0x24, 0xFF, 0xAE, 0x51, 0x69, 0x9A, 0xA2, 0x21, 0x3D, 0x84, 0x82, 0x0A, 0x84,
+  0xE4, 0x09, 0xAD, 0x11, 0x24, 0x8B, 0x98, 0xC0, 0x81, 0x7F, 0x21, 0xA3, 0x52,
+  0xBE, 0x19, 0x93, 0x09, 0xCE, 0x20, 0x10, 0x46, 0x4A, 0x4A, 0xF8, 0x27, 0x31,
+  0xEC, 0x58, 0xC7, 0xE8, 0x33, 0x82, 0xE3, 0xCE, 0xBF, 0x85, 0xF4, 0xDF, 0x94,
+  0xCE, 0x4B, 0x09, 0xC1, 0x94, 0x56, 0x8A, 0xC0, 0x13, 0x72, 0xA7, 0xFC, 0x9F,
+  0x84, 0x4D, 0x73, 0xA3, 0xCA, 0x9A, 0x61, 0x58, 0x97, 0xA3, 0x27, 0xFC, 0x03,
+  0x98, 0x76, 0x23, 0x1D, 0xC7, 0x61, 0x03, 0x04, 0xAE, 0x56, 0xBF, 0x38, 0x84,
+  0x00, 0x40, 0xA7, 0x0E, 0xFD, 0xFF, 0x52, 0xFE, 0x03, 0x6F, 0x95, 0x30, 0xF1,
+  0x97, 0xFB, 0xC0, 0x85, 0x60, 0xD6, 0x80, 0x25, 0xA9, 0x63, 0xBE, 0x03, 0x01,
+  0x4E, 0x38, 0xE2, 0xF9, 0xA2, 0x34, 0xFF, 0xBB, 0x3E, 0x03, 0x44, 0x78, 0x00,
+  0x90, 0xCB, 0x88, 0x11, 0x3A, 0x94, 0x65, 0xC0, 0x7C, 0x63, 0x87, 0xF0, 0x3C,
+  0xAF, 0xD6, 0x25, 0xE4, 0x8B, 0x38, 0x0A, 0xAC, 0x72, 0x21, 0xD4, 0xF8, 0x07,
+];
+
+/// The byte that has to be at `0xB2`.
+const FIXED_VALUE: u8 = 0x96;
+
+/// Copies `s` into `N` bytes, cut off or padded with zeroes.
+const fn padded<const N: usize>(s: &str) -> [u8; N] {
+  let bytes = s.as_bytes();
+  let mut out = [0; N];
+  let mut i = 0;
+  while i < N && i < bytes.len() {
+    out[i] = bytes[i];
+    i += 1;
+  }
+  out
+}
+
+/// The 192 byte header at the start of a program.
+///
+/// The `with_` methods update the complement check as they go, so a header
+/// built with them is always valid. The fields are laid out as in the ROM, so
+/// the whole header can be placed in the ROM as-is.
+///
+/// ```no_run
+/// # use gba::rom::Header;
+/// const HEADER: Header = Header::new()
+///   .with_entry_branch(0xC0)
+///   .with_title("MYGAME")
+///   .with_game_code("AMGE")
+///   .with_maker_code("01");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Header {
+  entry: u32,
+  logo: [u8; 156],
+  title: [u8; 12],
+  game_code: [u8; 4],
+  maker_code: [u8; 2],
+  fixed: u8,
+  unit_code: u8,
+  device_type: u8,
+  reserved1: [u8; 7],
+  software_version: u8,
+  complement: u8,
+  reserved2: [u8; 2],
+}
+const _: () = assert!(core::mem::size_of::<Header>() == Header::LEN);
+impl Header {
+  /// The size of the header, in bytes.
+  pub const LEN: usize = 0xC0;
+
+  /// A header with the logo, the fixed byte, and the check set, and
+  /// everything else zeroed.
+  ///
+  /// The entry point is zero too, which isn't an instruction that can start a
+  /// program, so set it with [`with_entry_branch`](Self::with_entry_branch)
+  /// or [`with_entry`](Self::with_entry).
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self {
+      entry: 0,
+      logo: LOGO,
+      title: [0; 12],
+      game_code: [0; 4],
+      maker_code: [0; 2],
+      fixed: FIXED_VALUE,
+      unit_code: 0,
+      device_type: 0,
+      reserved1: [0; 7],
+      software_version: 0,
+      complement: 0,
+      reserved2: [0; 2],
+    }
+    .with_fixed_complement()
+  }
+
+  /// Reads a header from the first 192 bytes of a program.
+  #[inline]
+  #[must_use]
+  pub const fn from_bytes(bytes: &[u8; Self::LEN]) -> Self {
+    // Safety: the header is all integers, so any bytes are a valid header, and
+    // `read_unaligned` doesn't need the bytes to be aligned.
+    unsafe { bytes.as_ptr().cast::<Self>().read_unaligned() }
+  }
+
+  /// The header's bytes, as they go in the ROM.
+  #[inline]
+  #[must_use]
+  pub const fn to_bytes(self) -> [u8; Self::LEN] {
+    // Safety: the header has no padding, so all of its bytes are initialized.
+    unsafe { core::mem::transmute(self) }
+  }
+
+  /// Sets the first instruction, which is where the program starts.
+  ///
+  /// This should be an a32 instruction, usually a branch past the header.
+  #[inline]
+  #[must_use]
+  pub const fn with_entry(self, instruction: u32) -> Self {
+    // the entry point isn't part of the complement check.
+    Self { entry: instruction, ..self }
+  }
+
+  /// Sets the first instruction to an a32 `b` to `offset` bytes from the
+  /// start of the program.
+  ///
+  /// ## Panics
+  /// * `offset` must be a multiple of 4, and at least 8.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn with_entry_branch(self, offset: u32) -> Self {
+    assert!(offset >= 8 && offset.is_multiple_of(4), "unusable branch offset");
+    // a `b` is relative to the instruction's address plus 8, in words.
+    self.with_entry(0xEA00_0000 | ((offset - 8) / 4))
+  }
+
+  /// Sets the title, which is cut off or padded with zeroes to 12 bytes.
+  ///
+  /// The title is usually uppercase ASCII.
+  #[inline]
+  #[must_use]
+  pub const fn with_title(self, title: &str) -> Self {
+    Self { title: padded(title), ..self }.with_fixed_complement()
+  }
+
+  /// Sets the game code, which is cut off or padded with zeroes to 4 bytes.
+  ///
+  /// The code is usually 4 uppercase ASCII letters: the kind of game, two
+  /// letters for the game itself, and the region.
+  #[inline]
+  #[must_use]
+  pub const fn with_game_code(self, code: &str) -> Self {
+    Self { game_code: padded(code), ..self }.with_fixed_complement()
+  }
+
+  /// Sets the maker code, which is cut off or padded with zeroes to 2 bytes.
+  #[inline]
+  #[must_use]
+  pub const fn with_maker_code(self, code: &str) -> Self {
+    Self { maker_code: padded(code), ..self }.with_fixed_complement()
+  }
+
+  /// Sets the software version, which is usually 0.
+  #[inline]
+  #[must_use]
+  pub const fn with_software_version(self, version: u8) -> Self {
+    Self { software_version: version, ..self }.with_fixed_complement()
+  }
+
+  /// Sets the device type, which is normally 0.
+  #[inline]
+  #[must_use]
+  pub const fn with_device_type(self, device_type: u8) -> Self {
+    Self { device_type, ..self }.with_fixed_complement()
+  }
+
+  /// Sets the check to match the rest of the header.
+  #[inline]
+  #[must_use]
+  const fn with_fixed_complement(self) -> Self {
+    Self { complement: self.computed_complement(), ..self }
+  }
+
+  /// The first instruction.
+  #[inline]
+  #[must_use]
+  pub const fn entry(&self) -> u32 {
+    self.entry
+  }
+
+  /// The title, with any padding.
+  #[inline]
+  #[must_use]
+  pub const fn title(&self) -> &[u8; 12] {
+    &self.title
+  }
+
+  /// The game code.
+  #[inline]
+  #[must_use]
+  pub const fn game_code(&self) -> &[u8; 4] {
+    &self.game_code
+  }
+
+  /// The maker code.
+  #[inline]
+  #[must_use]
+  pub const fn maker_code(&self) -> &[u8; 2] {
+    &self.maker_code
+  }
+
+  /// The software version.
+  #[inline]
+  #[must_use]
+  pub const fn software_version(&self) -> u8 {
+    self.software_version
+  }
+
+  /// The complement check stored in the header (at `0xBD`).
+  #[inline]
+  #[must_use]
+  pub const fn complement(&self) -> u8 {
+    self.complement
+  }
+
+  /// The complement check that the rest of the header needs.
+  ///
+  /// This is the negative of the sum of the bytes from `0xA0` to `0xBC`, minus
+  /// `0x19`, which is also how `gbafix` works it out.
+  #[inline]
+  #[must_use]
+  pub const fn computed_complement(&self) -> u8 {
+    let bytes = self.to_bytes();
+    let mut sum = 0_u8;
+    let mut i = 0xA0;
+    while i < 0xBD {
+      sum = sum.wrapping_add(bytes[i]);
+      i += 1;
+    }
+    0_u8.wrapping_sub(sum).wrapping_sub(0x19)
+  }
+
+  /// Checks the parts of the header that the BIOS checks.
+  ///
+  /// The title and codes can be anything, and the entry point isn't checked.
+  #[inline]
+  pub fn validate(&self) -> Result<(), HeaderError> {
+    if self.logo != LOGO {
+      return Err(HeaderError::BadLogo);
+    }
+    if self.fixed != FIXED_VALUE {
+      return Err(HeaderError::BadFixedValue(self.fixed));
+    }
+    let expected = self.computed_complement();
+    if self.complement != expected {
+      return Err(HeaderError::BadComplement {
+        expected,
+        found: self.complement,
+      });
+    }
+    Ok(())
+  }
+}
+impl Default for Header {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Why a [`Header`] isn't valid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeaderError {
+  /// The logo isn't exactly [`LOGO`].
+  BadLogo,
+  /// The byte at `0xB2` isn't `0x96` (it's this instead).
+  BadFixedValue(u8),
+  /// The complement check doesn't match the rest of the header.
+  BadComplement {
+    /// The check that the header needs.
+    expected: u8,
+    /// The check in the header.
+    found: u8,
+  },
+}
+
+/// A [`Header`] followed by the fields used by a program sent with multiboot
+/// (or over the Joy Bus), which is `0xE4` bytes in all.
+///
+/// The BIOS fills in the boot mode and client ID when it receives a program,
+/// so a multiboot program can tell how it was started. A program sent this way
+/// starts at [`Header::entry`] like a cartridge does, so a program that can
+/// run either way should branch past all of this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct MultibootHeader {
+  /// The cartridge header.
+  pub header: Header,
+  ram_entry: u32,
+  boot_mode: u8,
+  client_id: u8,
+  reserved: [u8; 26],
+  joybus_entry: u32,
+}
+const _: () =
+  assert!(core::mem::size_of::<MultibootHeader>() == MultibootHeader::LEN);
+impl MultibootHeader {
+  /// The size of the header with the multiboot fields, in bytes.
+  pub const LEN: usize = 0xE4;
+
+  /// The header given, with the multiboot fields zeroed.
+  #[inline]
+  #[must_use]
+  pub const fn new(header: Header) -> Self {
+    Self {
+      header,
+      ram_entry: 0,
+      boot_mode: 0,
+      client_id: 0,
+      reserved: [0; 26],
+      joybus_entry: 0,
+    }
+  }
+
+  /// The bytes, as they go in the ROM.
+  #[inline]
+  #[must_use]
+  pub const fn to_bytes(self) -> [u8; Self::LEN] {
+    // Safety: there's no padding, so all of the bytes are initialized.
+    unsafe { core::mem::transmute(self) }
+  }
+
+  /// Sets the instruction at `0xC0`, where some multiboot programs start.
+  #[inline]
+  #[must_use]
+  pub const fn with_ram_entry(self, instruction: u32) -> Self {
+    Self { ram_entry: instruction, ..self }
+  }
+
+  /// Sets the boot mode byte, which the BIOS sets to how the program was sent
+  /// (1 for Joy Bus, 2 for normal mode, 3 for multi-player mode).
+  #[inline]
+  #[must_use]
+  pub const fn with_boot_mode(self, boot_mode: u8) -> Self {
+    Self { boot_mode, ..self }
+  }
+
+  /// Sets the client ID byte, which the BIOS sets to the multi-player
+  /// client number (1 to 3) that the program was sent to.
+  #[inline]
+  #[must_use]
+  pub const fn with_client_id(self, client_id: u8) -> Self {
+    Self { client_id, ..self }
+  }
+
+  /// Sets the instruction at `0xE0`, where a program sent over the Joy Bus
+  /// starts.
+  #[inline]
+  #[must_use]
+  pub const fn with_joybus_entry(self, instruction: u32) -> Self {
+    Self { joybus_entry: instruction, ..self }
+  }
+
+  /// The instruction at `0xC0`.
+  #[inline]
+  #[must_use]
+  pub const fn ram_entry(&self) -> u32 {
+    self.ram_entry
+  }
+
+  /// The boot mode byte.
+  #[inline]
+  #[must_use]
+  pub const fn boot_mode(&self) -> u8 {
+    self.boot_mode
+  }
+
+  /// The client ID byte.
+  #[inline]
+  #[must_use]
+  pub const fn client_id(&self) -> u8 {
+    self.client_id
+  }
+
+  /// The instruction at `0xE0`.
+  #[inline]
+  #[must_use]
+  pub const fn joybus_entry(&self) -> u32 {
+    self.joybus_entry
+  }
+}
+
+/// Reads an environment variable at build time, or gives `""`.
+macro_rules! env_or_blank {
+  ($name:literal) => {
+    match option_env!($name) {
+      Some(value) => value,
+      None => "",
+    }
+  };
+}
+
+/// The header that the assembly runtime starts the program with.
+///
+/// The runtime leaves the multiboot fields zeroed after it, and the entry
+/// point branches past those too, to where the runtime's code goes on.
+#[cfg(feature = "on_gba")]
+pub(crate) const RUNTIME_HEADER: Header = Header::new()
+  .with_entry_branch(MultibootHeader::LEN as u32)
+  .with_title(env_or_blank!("GBA_ROM_TITLE"))
+  .with_game_code(env_or_blank!("GBA_ROM_GAME_CODE"))
+  .with_maker_code(env_or_blank!("GBA_ROM_MAKER_CODE"));
@@ -0,0 +1,316 @@
+//! EEPROM save memory, 512 bytes or 8 KiB, at the top of the ROM space.
+//!
+//! The EEPROM is a serial chip: every access is a stream of bits, one per
+//! halfword, sent to or read from `0x0DFF_FF00` with DMA3 (the chip counts
+//! the bus accesses, so the bits have to come all in one transfer). The memory
+//! is read and written 8 bytes (64 bits) at a time, as "blocks".
+//!
+//! * A read sends `11`, then the block number, then `0`, and then reads 68 bits
+//!   back: 4 bits to skip, and then the 64 bits of the block.
+//! * A write sends `10`, then the block number, then the 64 bits, then `0`, and
+//!   then the chip is busy (reading as 0) until the write is done.
+//!
+//! The block number is 6 bits for the 512 byte chip and 14 bits for the 8 KiB
+//! chip (of which only 10 are used), and that's the only difference between
+//! the two. Using the wrong size gives garbage, see [`Eeprom::detect_size`].
+//!
+//! The EEPROM only works with 8 wait cycles for the ROM's third wait state
+//! region, so that's set in [`WAITCNT`] during each access (with a
+//! [`WaitstateGuard`]) and put back afterwards.
+//!
+//! ```no_run
+//! # use gba::save::eeprom::*;
+//! let eeprom = Eeprom::new(EepromSize::_8KiB);
+//! eeprom.write_block(3, b"8 bytes!").unwrap();
+//! let mut block = [0; 8];
+//! eeprom.read_block(3, &mut block).unwrap();
+//! ```
+//!
+//! Using this module puts the [`EEPROM_SAVE_ID`] string in the ROM, so that
+//! emulators and flash carts can tell that the game saves to EEPROM.
+
+use super::SaveError;
+use crate::{
+  dma::dma3_copy_u16,
+  mmio::WAITCNT,
+  timers::{busy_wait_cycles, ms_to_cycles},
+  waitstate::{SecondAccess, WaitCycles, WaitstateGuard},
+  Align4,
+};
+
+/// Where the EEPROM is accessed (it's seen on all of `0x0D00_0000` and up if
+/// the ROM is 16 MiB or less, but this works for any ROM size).
+const EEPROM_ADDR: usize = 0x0DFF_FF00;
+
+/// The save type string that emulators and flash carts look for in the ROM.
+pub static EEPROM_SAVE_ID: Align4<[u8; 12]> = Align4(*b"EEPROM_V124\0");
+
+/// How long a block write can take, at the most.
+const WRITE_TIMEOUT_MS: u32 = 10;
+
+/// The size of an EEPROM chip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EepromSize {
+  /// 512 bytes (64 blocks), with 6-bit block numbers.
+  _512B,
+  /// 8 KiB (1024 blocks), with 14-bit block numbers.
+  _8KiB,
+}
+impl EepromSize {
+  /// The size of the chip, in bytes.
+  #[inline]
+  #[must_use]
+  pub const fn size(self) -> usize {
+    self.block_count() * 8
+  }
+
+  /// How many 8 byte blocks the chip has.
+  #[inline]
+  #[must_use]
+  pub const fn block_count(self) -> usize {
+    match self {
+      Self::_512B => 64,
+      Self::_8KiB => 1024,
+    }
+  }
+
+  /// How many bits each block number is sent as.
+  const fn address_bits(self) -> usize {
+    match self {
+      Self::_512B => 6,
+      Self::_8KiB => 14,
+    }
+  }
+}
+
+/// Puts the low `count` bits of `value` into `bits`, highest bit first, one
+/// per halfword.
+fn pack_bits(bits: &mut [u16], value: u64, count: usize) {
+  for (i, bit) in bits[..count].iter_mut().enumerate() {
+    *bit = ((value >> (count - 1 - i)) & 1) as u16;
+  }
+}
+
+/// Sets up the wait states for the EEPROM, runs `f`, and then puts the wait
+/// states back.
+fn with_eeprom_waits<T>(f: impl FnOnce() -> T) -> T {
+  // Reading the save type string keeps it from being left out of the ROM.
+  // Safety: it's a normal static.
+  unsafe { core::ptr::read_volatile(EEPROM_SAVE_ID.0.as_ptr()) };
+  let eeprom_waits = WAITCNT
+    .read()
+    .with_ws2_first(WaitCycles::_8)
+    .with_ws2_second(SecondAccess::Slow);
+  // Safety: this only changes the wait states of ROM region 2, which is the
+  // one for the EEPROM, to 8 cycles for all accesses.
+  let _guard = unsafe { WaitstateGuard::new(eeprom_waits) };
+  f()
+}
+
+/// An EEPROM chip of a known size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Eeprom {
+  size: EepromSize,
+}
+impl Eeprom {
+  /// Uses a chip that's already known to be of this `size`.
+  #[inline]
+  #[must_use]
+  pub const fn new(size: EepromSize) -> Self {
+    Self { size }
+  }
+
+  /// The size of the chip.
+  #[inline]
+  #[must_use]
+  pub const fn size(self) -> EepromSize {
+    self.size
+  }
+
+  /// If there seems to be an EEPROM, from just reading its status.
+  ///
+  /// An EEPROM that isn't busy reads as 1 at every address, while without one
+  /// the reads give the ROM's open bus value (the low bits of the address), so
+  /// half of them are 0. A 32 MiB ROM with odd values in all of its last 16
+  /// bytes would look like an EEPROM too.
+  #[inline]
+  #[must_use]
+  pub fn is_present() -> bool {
+    with_eeprom_waits(|| {
+      (0..8).all(|i| {
+        // Safety: reading the EEPROM when it's not busy doesn't change it.
+        let status =
+          unsafe { (EEPROM_ADDR as *const u16).add(i).read_volatile() };
+        status & 1 == 1
+      })
+    })
+  }
+
+  /// Figures out the size of the EEPROM chip, by writing to block 0 as an
+  /// 8 KiB chip and then checking if the data reads back.
+  ///
+  /// Block 0 is read first and written back afterwards (at the right size),
+  /// so this only loses data if the power goes off partway through.
+  ///
+  /// There's no EEPROM ID, so this assumes there is an EEPROM. Also, mGBA
+  /// makes its EEPROM 8 KiB once it's been used that way, so with mGBA this
+  /// always gives [`EepromSize::_8KiB`] (which then does work).
+  ///
+  /// ## Failure
+  /// * [`SaveError::Timeout`] if a write doesn't finish in time.
+  #[inline]
+  pub fn detect_size() -> Result<EepromSize, SaveError> {
+    const PATTERN: [u8; 8] = [0x5A, 0xC3, 0x0F, 0x96, 0xA5, 0x3C, 0xF0, 0x69];
+    let small = Self::new(EepromSize::_512B);
+    let large = Self::new(EepromSize::_8KiB);
+    let mut old_small = [0; 8];
+    let mut old_large = [0; 8];
+    small.read_block(0, &mut old_small)?;
+    large.read_block(0, &mut old_large)?;
+    large.write_block(0, &PATTERN)?;
+    let mut check = [0; 8];
+    large.read_block(0, &mut check)?;
+    if check == PATTERN {
+      large.write_block(0, &old_large)?;
+      Ok(EepromSize::_8KiB)
+    } else {
+      small.write_block(0, &old_small)?;
+      Ok(EepromSize::_512B)
+    }
+  }
+
+  /// Checks a block number.
+  const fn check_block(self, block: usize) -> Result<(), SaveError> {
+    if block < self.size.block_count() {
+      Ok(())
+    } else {
+      Err(SaveError::OutOfBounds)
+    }
+  }
+
+  /// Reads block number `block`.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if there's no such block.
+  #[inline]
+  pub fn read_block(
+    self, block: usize, buffer: &mut [u8; 8],
+  ) -> Result<(), SaveError> {
+    self.check_block(block)?;
+    let address_bits = self.size.address_bits();
+    let mut request = [0_u16; 17];
+    pack_bits(&mut request, 0b11, 2);
+    pack_bits(&mut request[2..], block as u64, address_bits);
+    let request = &request[..address_bits + 3];
+    let mut reply = [0_u16; 68];
+    with_eeprom_waits(|| {
+      // Safety: the EEPROM can always be written and read like this.
+      unsafe {
+        dma3_copy_u16(request, EEPROM_ADDR as *mut u16);
+        let from = core::slice::from_raw_parts(EEPROM_ADDR as *const u16, 68);
+        dma3_copy_u16(from, reply.as_mut_ptr());
+      }
+    });
+    let value = reply[4..]
+      .iter()
+      .fold(0_u64, |value, &bit| value << 1 | u64::from(bit & 1));
+    *buffer = value.to_be_bytes();
+    Ok(())
+  }
+
+  /// Writes block number `block`, and waits for the write to finish.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if there's no such block.
+  /// * [`SaveError::Timeout`] if the chip doesn't finish in time.
+  #[inline]
+  pub fn write_block(
+    self, block: usize, data: &[u8; 8],
+  ) -> Result<(), SaveError> {
+    self.check_block(block)?;
+    let address_bits = self.size.address_bits();
+    let mut request = [0_u16; 81];
+    pack_bits(&mut request, 0b10, 2);
+    pack_bits(&mut request[2..], block as u64, address_bits);
+    pack_bits(&mut request[2 + address_bits..], u64::from_be_bytes(*data), 64);
+    // The last bit stays 0.
+    let request = &request[..address_bits + 67];
+    with_eeprom_waits(|| {
+      // Safety: the EEPROM can always be written like this.
+      unsafe { dma3_copy_u16(request, EEPROM_ADDR as *mut u16) };
+      let limit = ms_to_cycles(WRITE_TIMEOUT_MS);
+      let mut waited = 0;
+      // Safety: as above.
+      while unsafe { (EEPROM_ADDR as *const u16).read_volatile() } & 1 == 0 {
+        if waited >= limit {
+          return Err(SaveError::Timeout);
+        }
+        busy_wait_cycles(64);
+        waited += 64;
+      }
+      Ok(())
+    })
+  }
+
+  /// Reads `buffer.len()` bytes from the chip, starting at `offset`.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if the bytes go past the end of the chip.
+  #[inline]
+  pub fn read(self, offset: usize, buffer: &mut [u8]) -> Result<(), SaveError> {
+    self.check_bounds(offset, buffer.len())?;
+    let mut offset = offset;
+    let mut buffer = buffer;
+    while !buffer.is_empty() {
+      let in_block = offset % 8;
+      let count = buffer.len().min(8 - in_block);
+      let (now, rest) = buffer.split_at_mut(count);
+      let mut block = [0; 8];
+      self.read_block(offset / 8, &mut block)?;
+      now.copy_from_slice(&block[in_block..][..count]);
+      offset += count;
+      buffer = rest;
+    }
+    Ok(())
+  }
+
+  /// Writes `data` to the chip, starting at `offset`.
+  ///
+  /// The chip can only write whole blocks, so a block that's only partly
+  /// written is read first, and the new bytes are put into it. Blocks that
+  /// already hold the new data aren't written again.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if the bytes go past the end of the chip.
+  /// * [`SaveError::Timeout`] if a block write doesn't finish in time.
+  #[inline]
+  pub fn write(self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    self.check_bounds(offset, data.len())?;
+    let mut offset = offset;
+    let mut data = data;
+    while !data.is_empty() {
+      let in_block = offset % 8;
+      let count = data.len().min(8 - in_block);
+      let (now, rest) = data.split_at(count);
+      let mut block = [0; 8];
+      self.read_block(offset / 8, &mut block)?;
+      if &block[in_block..][..count] != now {
+        block[in_block..][..count].copy_from_slice(now);
+        self.write_block(offset / 8, &block)?;
+      }
+      offset += count;
+      data = rest;
+    }
+    Ok(())
+  }
+
+  /// Checks that `len` bytes from `offset` are all in the chip.
+  const fn check_bounds(
+    self, offset: usize, len: usize,
+  ) -> Result<(), SaveError> {
+    match offset.checked_add(len) {
+      Some(end) if end <= self.size.size() => Ok(()),
+      _ => Err(SaveError::OutOfBounds),
+    }
+  }
+}
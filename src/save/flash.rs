@@ -0,0 +1,389 @@
+//! Flash save memory, 64 KiB or 128 KiB at `0x0E00_0000`.
+//!
+//! Flash works a byte at a time like SRAM, but it's controlled by writing
+//! commands to the "magic" addresses `0x0E00_5555` and `0x0E00_2AAA`, and
+//! it's slow to change:
+//! * Programming a byte can only clear bits (turn 1s into 0s). Setting bits
+//!   back to 1 means erasing a whole 4 KiB sector (back to all `0xFF`).
+//! * The chip works on its own after each erase or program command, and is
+//!   polled until it's done.
+//! * The 128 KiB chips only show 64 KiB at a time, and a command picks which
+//!   "bank" is shown.
+//!
+//! [`FlashChip`] handles all of that: [`FlashChip::write`] erases sectors when
+//! it has to (keeping the rest of the sector), and the offsets go across the
+//! whole chip with the banks switched as needed.
+//!
+//! ```no_run
+//! # use gba::save::flash::*;
+//! if let Some(kind) = FlashChip::detect() {
+//!   let chip = FlashChip::new(kind);
+//!   chip.write(0x100, b"hello").unwrap();
+//!   let mut buffer = [0; 5];
+//!   chip.read(0x100, &mut buffer).unwrap();
+//! }
+//! ```
+//!
+//! Using this module puts the [`FLASH1M_SAVE_ID`] string in the ROM, so that
+//! emulators and flash carts give the game a 128 KiB chip (which also works for
+//! games that only use 64 KiB).
+//!
+//! Each command is several writes in a row, so an interrupt handler must not
+//! touch the save memory while one of these functions is running.
+
+use super::{use_slow_sram_waits, SaveError};
+use crate::{
+  interrupts::irq_free,
+  mem::copy_u8_unchecked,
+  timers::{busy_wait_cycles, ms_to_cycles},
+  Align4,
+};
+
+/// Where the flash is.
+const FLASH_BASE: usize = 0x0E00_0000;
+
+/// How much of the flash can be seen at once.
+const BANK_LEN: usize = 64 * 1024;
+
+/// How long to wait between checks of an erase or program command.
+const POLL_CYCLES: u32 = 64;
+
+/// The save type string that emulators and flash carts look for in the ROM.
+pub static FLASH1M_SAVE_ID: Align4<[u8; 12]> = Align4(*b"FLASH1M_V103");
+
+/// A kind of flash chip that's known to be used for GBA saves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FlashKind {
+  /// Atmel AT29LV512 (64 KiB), which is written 128 bytes at a time.
+  Atmel64K,
+  /// SST 39VF512 (64 KiB).
+  Sst64K,
+  /// Macronix MX29L512 (64 KiB).
+  Macronix64K,
+  /// Panasonic MN63F805MNP (64 KiB).
+  Panasonic64K,
+  /// Macronix MX29L010 (128 KiB).
+  Macronix128K,
+  /// Sanyo LE26FV10N1TS (128 KiB).
+  Sanyo128K,
+}
+impl FlashKind {
+  /// The kind of chip with these IDs, if it's a known one.
+  #[inline]
+  #[must_use]
+  pub const fn from_ids(manufacturer: u8, device: u8) -> Option<Self> {
+    Some(match (manufacturer, device) {
+      (0x1F, 0x3D) => Self::Atmel64K,
+      (0xBF, 0xD4) => Self::Sst64K,
+      (0xC2, 0x1C) => Self::Macronix64K,
+      (0x32, 0x1B) => Self::Panasonic64K,
+      (0xC2, 0x09) => Self::Macronix128K,
+      (0x62, 0x13) => Self::Sanyo128K,
+      _ => return None,
+    })
+  }
+
+  /// The size of the chip, in bytes.
+  #[inline]
+  #[must_use]
+  pub const fn size(self) -> usize {
+    match self {
+      Self::Macronix128K | Self::Sanyo128K => 2 * BANK_LEN,
+      _ => BANK_LEN,
+    }
+  }
+
+  /// The size of the chip's sectors, which are erased all at once.
+  ///
+  /// This is 4 KiB, except for the Atmel chip, which erases and writes 128 byte
+  /// pages.
+  #[inline]
+  #[must_use]
+  pub const fn sector_size(self) -> usize {
+    match self {
+      Self::Atmel64K => 128,
+      _ => 4 * 1024,
+    }
+  }
+
+  /// How many sectors the chip has.
+  #[inline]
+  #[must_use]
+  pub const fn sector_count(self) -> usize {
+    self.size() / self.sector_size()
+  }
+}
+
+/// Reads one byte of the current bank.
+fn flash_read(addr: usize) -> u8 {
+  // Safety: the save memory is always readable.
+  unsafe { ((FLASH_BASE + (addr % BANK_LEN)) as *const u8).read_volatile() }
+}
+
+/// Writes one byte of the current bank.
+fn flash_write(addr: usize, value: u8) {
+  // Safety: the save memory is always writable.
+  unsafe { ((FLASH_BASE + (addr % BANK_LEN)) as *mut u8).write_volatile(value) }
+}
+
+/// Sends a flash command.
+fn command(cmd: u8) {
+  flash_write(0x5555, 0xAA);
+  flash_write(0x2AAA, 0x55);
+  flash_write(0x5555, cmd);
+}
+
+/// Gets ready to access the flash.
+fn prepare() {
+  // Reading the save type string keeps it from being left out of the ROM.
+  // Safety: it's a normal static.
+  unsafe { core::ptr::read_volatile(FLASH1M_SAVE_ID.0.as_ptr()) };
+  use_slow_sram_waits();
+}
+
+/// Polls until the byte at `addr` (in the current bank) reads as `expected`,
+/// which means the last command is done.
+fn wait_for(
+  addr: usize, expected: u8, timeout_ms: u32,
+) -> Result<(), SaveError> {
+  let limit = ms_to_cycles(timeout_ms);
+  let mut waited = 0;
+  while flash_read(addr) != expected {
+    if waited >= limit {
+      // Stops whatever the chip is doing, and goes back to reading mode.
+      command(0xF0);
+      return Err(SaveError::Timeout);
+    }
+    busy_wait_cycles(POLL_CYCLES);
+    waited += POLL_CYCLES;
+  }
+  Ok(())
+}
+
+/// A flash chip of a known kind, from [`FlashChip::detect`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FlashChip {
+  kind: FlashKind,
+}
+impl FlashChip {
+  /// Erasing a sector can take this long, at the most.
+  const SECTOR_ERASE_MS: u32 = 500;
+  /// Erasing the whole chip can take this long, at the most.
+  const CHIP_ERASE_MS: u32 = 3000;
+  /// Programming a byte (or an Atmel page) can take this long, at the most.
+  const PROGRAM_MS: u32 = 40;
+
+  /// Asks the save chip for its IDs, and gives the kind of flash chip if it's
+  /// a known one.
+  ///
+  /// The ID command is written to `0x0E00_5555` and `0x0E00_2AAA`, which would
+  /// change those bytes if the cart has SRAM instead. When no flash chip is
+  /// found the old values of those two bytes are written back, so this is
+  /// safe to use on a cart with SRAM.
+  #[inline]
+  #[must_use]
+  pub fn detect() -> Option<FlashKind> {
+    prepare();
+    let old_5555 = flash_read(0x5555);
+    let old_2aaa = flash_read(0x2AAA);
+    let (manufacturer, device) = irq_free(|| {
+      command(0x90);
+      let ids = (flash_read(0), flash_read(1));
+      command(0xF0);
+      ids
+    });
+    let kind = FlashKind::from_ids(manufacturer, device);
+    if kind.is_none() {
+      flash_write(0x5555, old_5555);
+      flash_write(0x2AAA, old_2aaa);
+    }
+    kind
+  }
+
+  /// Uses a chip that's already known to be of this `kind`.
+  #[inline]
+  #[must_use]
+  pub const fn new(kind: FlashKind) -> Self {
+    Self { kind }
+  }
+
+  /// The kind of chip.
+  #[inline]
+  #[must_use]
+  pub const fn kind(self) -> FlashKind {
+    self.kind
+  }
+
+  /// Shows the bank that has `offset` in it (on a 128 KiB chip).
+  fn select_bank(self, offset: usize) {
+    if self.kind.size() > BANK_LEN {
+      command(0xB0);
+      flash_write(0, (offset / BANK_LEN) as u8);
+    }
+  }
+
+  /// Checks that `len` bytes from `offset` are all in the chip.
+  const fn check_bounds(
+    self, offset: usize, len: usize,
+  ) -> Result<(), SaveError> {
+    match offset.checked_add(len) {
+      Some(end) if end <= self.kind.size() => Ok(()),
+      _ => Err(SaveError::OutOfBounds),
+    }
+  }
+
+  /// Reads `buffer.len()` bytes from the chip, starting at `offset`.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if the bytes go past the end of the chip.
+  #[inline]
+  pub fn read(self, offset: usize, buffer: &mut [u8]) -> Result<(), SaveError> {
+    self.check_bounds(offset, buffer.len())?;
+    prepare();
+    let mut offset = offset;
+    let mut buffer = buffer;
+    while !buffer.is_empty() {
+      let count = buffer.len().min(BANK_LEN - offset % BANK_LEN);
+      let (now, rest) = buffer.split_at_mut(count);
+      self.select_bank(offset);
+      // Safety: the bounds were checked, and the bytes are all in this bank.
+      unsafe {
+        copy_u8_unchecked(
+          now.as_mut_ptr(),
+          (FLASH_BASE + offset % BANK_LEN) as *const u8,
+          count,
+        )
+      };
+      offset += count;
+      buffer = rest;
+    }
+    Ok(())
+  }
+
+  /// Erases sector number `sector`, setting all its bytes to `0xFF`.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if there's no such sector.
+  /// * [`SaveError::Timeout`] if the chip doesn't finish in time.
+  #[inline]
+  pub fn erase_sector(self, sector: usize) -> Result<(), SaveError> {
+    if sector >= self.kind.sector_count() {
+      return Err(SaveError::OutOfBounds);
+    }
+    prepare();
+    let offset = sector * self.kind.sector_size();
+    if self.kind == FlashKind::Atmel64K {
+      // Writing a page erases it first, so erase by writing all `0xFF`.
+      return self.program_page(offset, &[0xFF; 128]);
+    }
+    self.select_bank(offset);
+    irq_free(|| {
+      command(0x80);
+      flash_write(0x5555, 0xAA);
+      flash_write(0x2AAA, 0x55);
+      flash_write(offset, 0x30);
+    });
+    wait_for(offset, 0xFF, Self::SECTOR_ERASE_MS)
+  }
+
+  /// Erases the whole chip, setting all its bytes to `0xFF`.
+  ///
+  /// ## Failure
+  /// * [`SaveError::Timeout`] if the chip doesn't finish in time.
+  #[inline]
+  pub fn erase_all(self) -> Result<(), SaveError> {
+    prepare();
+    irq_free(|| {
+      command(0x80);
+      command(0x10);
+    });
+    wait_for(0, 0xFF, Self::CHIP_ERASE_MS)
+  }
+
+  /// Writes `data` to the chip, starting at `offset`.
+  ///
+  /// If a byte can't be written without setting bits, its sector is read into
+  /// a buffer on the stack (4 KiB), updated, erased, and then written back.
+  /// Otherwise the bytes are just programmed, which is much faster.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if the bytes go past the end of the chip.
+  /// * [`SaveError::Timeout`] if the chip doesn't finish an erase or a program
+  ///   command in time.
+  #[inline]
+  pub fn write(self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    self.check_bounds(offset, data.len())?;
+    prepare();
+    let sector_size = self.kind.sector_size();
+    let mut offset = offset;
+    let mut data = data;
+    while !data.is_empty() {
+      let count = data.len().min(sector_size - offset % sector_size);
+      let (now, rest) = data.split_at(count);
+      self.write_in_sector(offset, now)?;
+      offset += count;
+      data = rest;
+    }
+    Ok(())
+  }
+
+  /// Writes `data` at `offset`, which are all in the same sector.
+  fn write_in_sector(
+    self, offset: usize, data: &[u8],
+  ) -> Result<(), SaveError> {
+    let sector_size = self.kind.sector_size();
+    let start = offset - offset % sector_size;
+    self.select_bank(offset);
+    if self.kind == FlashKind::Atmel64K {
+      let mut page = [0; 128];
+      self.read(start, &mut page)?;
+      page[offset - start..][..data.len()].copy_from_slice(data);
+      return self.program_page(start, &page);
+    }
+
+    let needs_erase = data
+      .iter()
+      .enumerate()
+      .any(|(i, &new)| flash_read(offset + i) & new != new);
+    if !needs_erase {
+      return self.program_bytes(offset, data);
+    }
+    let mut buffer = [0; 4 * 1024];
+    let sector = &mut buffer[..sector_size];
+    self.read(start, sector)?;
+    sector[offset - start..][..data.len()].copy_from_slice(data);
+    self.erase_sector(start / sector_size)?;
+    self.program_bytes(start, sector)
+  }
+
+  /// Programs `data` at `offset` (all in one bank), one byte at a time.
+  fn program_bytes(self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    self.select_bank(offset);
+    for (i, &byte) in data.iter().enumerate() {
+      if flash_read(offset + i) == byte {
+        continue;
+      }
+      irq_free(|| {
+        command(0xA0);
+        flash_write(offset + i, byte);
+      });
+      wait_for(offset + i, byte, Self::PROGRAM_MS)?;
+    }
+    Ok(())
+  }
+
+  /// Programs one Atmel page, which erases it at the same time.
+  fn program_page(
+    self, offset: usize, page: &[u8; 128],
+  ) -> Result<(), SaveError> {
+    // The chip times out if there's a gap of more than 150 microseconds
+    // between the bytes, so interrupts have to wait.
+    irq_free(|| {
+      command(0xA0);
+      for (i, &byte) in page.iter().enumerate() {
+        flash_write(offset + i, byte);
+      }
+    });
+    wait_for(offset + 127, page[127], Self::PROGRAM_MS)
+  }
+}
@@ -0,0 +1,224 @@
+//! Save memory in a buffer in RAM, with faults on purpose.
+//!
+//! [`MemoryMedia`] does everything that the cart's save memory does, without a
+//! cart, so save code can be tested in a test ROM (or an emulator) without
+//! waiting on real flash timing. It can also hold a copy of a save while it's
+//! worked on.
+//!
+//! The interesting part of save code is what happens when things go wrong, so
+//! `MemoryMedia` can make them go wrong in a way that's the same every run:
+//! * [`fail_after`](MemoryMedia::fail_after) cuts the power partway through the
+//!   writes, after some number of bytes. Going through every cut in turn checks
+//!   that a save can't be lost however it's torn.
+//! * [`flip_random_bit`](MemoryMedia::flip_random_bit) and
+//!   [`set_flip_on_write`](MemoryMedia::set_flip_on_write) change bits (from a
+//!   [`Xoshiro128`] with a set seed), like memory that went bad, which the slot
+//!   CRC-32 should catch.
+//!
+//! ```no_run
+//! # use gba::{prelude::*, save::mem::MemoryMedia};
+//! let mut bytes = [0xFF; 2 * SLOT_LEN];
+//! let media = MemoryMedia::new(&mut bytes);
+//! media.save_slot(1, &1234_u32).unwrap();
+//! assert_eq!(media.load_slot::<u32>(1), Ok(1234));
+//!
+//! // the power goes off 6 bytes into the next save.
+//! media.fail_after(6);
+//! assert_eq!(media.save_slot(1, &5678_u32), Err(SaveError::Timeout));
+//! media.restore_power();
+//! assert_eq!(media.load_slot::<u32>(1), Err(SaveError::Corrupt));
+//! ```
+
+use core::cell::Cell;
+
+use super::{SaveError, SaveMemory};
+use crate::random::Xoshiro128;
+
+/// Save memory in a buffer in RAM, which can fail on purpose.
+///
+/// By default this works like SRAM: bytes can be written at any time, and
+/// nothing fails. See the [module docs](self) for the faults.
+#[derive(Debug)]
+pub struct MemoryMedia<'a> {
+  bytes: &'a [Cell<u8>],
+  sector_size: usize,
+  written: Cell<usize>,
+  write_limit: Cell<Option<usize>>,
+  flip_on_write: Cell<bool>,
+  rng: Cell<[u32; 4]>,
+}
+impl<'a> MemoryMedia<'a> {
+  /// Uses `bytes` as the save memory.
+  ///
+  /// The random bits come from
+  /// [`Xoshiro128::from_seed(0)`](Xoshiro128::from_seed), until
+  /// [`with_seed`](Self::with_seed) changes that.
+  #[inline]
+  #[must_use]
+  pub fn new(bytes: &'a mut [u8]) -> Self {
+    Self {
+      bytes: Cell::from_mut(bytes).as_slice_of_cells(),
+      sector_size: 1,
+      written: Cell::new(0),
+      write_limit: Cell::new(None),
+      flip_on_write: Cell::new(false),
+      rng: Cell::new(Xoshiro128::from_seed(0).to_state()),
+    }
+  }
+
+  /// Makes this work like flash, with sectors of `sector_size` bytes.
+  ///
+  /// [`prepare_write`](SaveMemory::prepare_write) erases (sets to `0xFF`)
+  /// every sector that the write will touch, so anything else in those
+  /// sectors is lost, the same as with [`SaveMedia`](super::SaveMedia).
+  ///
+  /// ## Panics
+  /// * `sector_size` must be a power of two.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn with_flash_sectors(self, sector_size: usize) -> Self {
+    assert!(sector_size.is_power_of_two(), "sector_size must be a power of 2");
+    Self { sector_size, ..self }
+  }
+
+  /// Seeds the generator for the random bits.
+  #[inline]
+  #[must_use]
+  pub fn with_seed(self, seed: u32) -> Self {
+    self.rng.set(Xoshiro128::from_seed(seed).to_state());
+    self
+  }
+
+  /// How many bytes have been written so far.
+  ///
+  /// This counts each byte written, not each write, and doesn't count
+  /// erases. Save some data, check this, and that's how many places there are
+  /// to [`fail_after`](Self::fail_after).
+  #[inline]
+  #[must_use]
+  pub fn bytes_written(&self) -> usize {
+    self.written.get()
+  }
+
+  /// Cuts the power after `bytes` more bytes are written.
+  ///
+  /// The write that goes past that is cut off partway through (its first
+  /// bytes are written and the rest aren't), and it and every write after it
+  /// gives [`SaveError::Timeout`], like a chip that stopped answering.
+  /// Flash erases fail the same way once the power is off. Reads still work,
+  /// to look at what got written.
+  #[inline]
+  pub fn fail_after(&self, bytes: usize) {
+    self.write_limit.set(Some(bytes));
+  }
+
+  /// Turns the power back on, after [`fail_after`](Self::fail_after).
+  #[inline]
+  pub fn restore_power(&self) {
+    self.write_limit.set(None);
+  }
+
+  /// Sets if every write has one random bit of it flipped.
+  ///
+  /// This is memory (or a connection to it) that's gone bad, where nothing
+  /// saved comes back the same.
+  #[inline]
+  pub fn set_flip_on_write(&self, flip: bool) {
+    self.flip_on_write.set(flip);
+  }
+
+  /// Flips one random bit of the `len` bytes at `offset`, giving the offset of
+  /// the byte that changed.
+  ///
+  /// ## Panics
+  /// * `len` can't be 0, and the bytes must be in the memory.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn flip_random_bit(&self, offset: usize, len: usize) -> usize {
+    let range = self.range(offset, len).expect("the bytes must be in memory");
+    assert!(len > 0, "there must be a byte to flip");
+    let (index, bit) = self.random_bit(len);
+    range[index].set(range[index].get() ^ bit);
+    offset + index
+  }
+
+  /// A random index below `len` and one random bit.
+  #[inline]
+  fn random_bit(&self, len: usize) -> (usize, u8) {
+    let mut rng = Xoshiro128::from_state(self.rng.get());
+    let r = rng.next_u32();
+    self.rng.set(rng.to_state());
+    ((r >> 3) as usize % len, 1 << (r & 7))
+  }
+
+  /// The bytes at `offset`, if there are `len` of them.
+  #[inline]
+  fn range(&self, offset: usize, len: usize) -> Result<&[Cell<u8>], SaveError> {
+    offset
+      .checked_add(len)
+      .and_then(|end| self.bytes.get(offset..end))
+      .ok_or(SaveError::OutOfBounds)
+  }
+}
+impl SaveMemory for MemoryMedia<'_> {
+  #[inline]
+  fn len(&self) -> usize {
+    self.bytes.len()
+  }
+
+  #[inline]
+  fn sector_size(&self) -> usize {
+    self.sector_size
+  }
+
+  #[inline]
+  fn read(&self, offset: usize, buffer: &mut [u8]) -> Result<(), SaveError> {
+    let range = self.range(offset, buffer.len())?;
+    for (b, cell) in buffer.iter_mut().zip(range) {
+      *b = cell.get();
+    }
+    Ok(())
+  }
+
+  #[inline]
+  fn write(&self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    let range = self.range(offset, data.len())?;
+    let count = match self.write_limit.get() {
+      Some(left) => {
+        let count = left.min(data.len());
+        self.write_limit.set(Some(left - count));
+        count
+      }
+      None => data.len(),
+    };
+    for (cell, &b) in range.iter().zip(&data[..count]) {
+      cell.set(b);
+    }
+    if self.flip_on_write.get() && count > 0 {
+      let (index, bit) = self.random_bit(count);
+      range[index].set(range[index].get() ^ bit);
+    }
+    self.written.set(self.written.get() + count);
+    if count == data.len() {
+      Ok(())
+    } else {
+      Err(SaveError::Timeout)
+    }
+  }
+
+  #[inline]
+  fn prepare_write(&self, offset: usize, len: usize) -> Result<(), SaveError> {
+    if self.sector_size == 1 {
+      return Ok(());
+    }
+    self.range(offset, len)?;
+    let start = offset & !(self.sector_size - 1);
+    let end = (offset + len).next_multiple_of(self.sector_size).min(self.len());
+    if self.write_limit.get() == Some(0) {
+      return Err(SaveError::Timeout);
+    }
+    self.bytes[start..end].iter().for_each(|cell| cell.set(0xFF));
+    Ok(())
+  }
+}
@@ -0,0 +1,257 @@
+//! Upgrading saves made by older versions of a game.
+//!
+//! When a game's save format changes, the saves that players already have
+//! are still in the old format. A [`SaveSchema`] lists one upgrade function
+//! for each format version, which turns data from that version into data for
+//! the next one. [`load_with_migration`](SaveSchema::load_with_migration)
+//! reads the version from the slot's header, runs as many upgrades as it takes
+//! to get to the current version, saves the result back, and gives the value.
+//!
+//! ```no_run
+//! # use gba::{arena::Arena, prelude::*, save::migrate::*};
+//! // Version 1 was just a high score. Version 2 added the play time.
+//! fn add_play_time(old: &[u8], out: &mut [u8]) -> Result<usize, MigrateError> {
+//!   let Ok(score) = <[u8; 4]>::try_from(old) else {
+//!     return Err(MigrateError::BadData);
+//!   };
+//!   out[..4].copy_from_slice(&score);
+//!   out[4..8].fill(0);
+//!   Ok(8)
+//! }
+//! static SCHEMA: SaveSchema = SaveSchema::new(1, &[add_play_time]);
+//!
+//! #[link_section = ".ewram"]
+//! static mut SCRATCH: [u8; 8 * 1024] = [0; 8 * 1024];
+//! // Safety: this is the only place that uses the static.
+//! let mut arena = Arena::new(unsafe { &mut *core::ptr::addr_of_mut!(SCRATCH) });
+//!
+//! let media = SaveMedia::init().unwrap();
+//! let [score, play_time] = SCHEMA
+//!   .load_with_migration::<[u32; 2], _>(&media, 0, 1, &mut arena)
+//!   .unwrap();
+//! // ...
+//! SCHEMA.save(&media, 0, &[score, play_time + 1]).unwrap();
+//! ```
+//!
+//! ## Power loss
+//!
+//! The upgraded save is written over the old one, and if the power goes off
+//! partway through that, the old save would be lost. So an upgrade goes
+//! through a second "backup" slot:
+//! 1. The upgraded data is saved to the backup slot. The header is written last
+//!    (see [`save_slot_versioned`](SaveMemory::save_slot_versioned)), so the
+//!    backup only counts once all of it is there.
+//! 2. The upgraded data is saved to the main slot.
+//! 3. The backup slot is [cleared](SaveMemory::clear_slot).
+//!
+//! A finished backup at the current version means that the power went off
+//! during step 2 or 3, and the next load finishes the job from the backup.
+//! Anything else in the backup slot is ignored, so if the power goes off in
+//! step 1 the old save is still in the main slot, and it's just upgraded
+//! again. The backup slot is only used during an upgrade, but it has to be
+//! kept free for it.
+//!
+//! ## Errors, not panics
+//!
+//! Save data can be anything (a cart from someone else's game, or memory that
+//! went bad), and the CRC-32 only catches some of that. Upgrade functions
+//! should check the old data and give [`MigrateError::BadData`] for anything
+//! they don't expect, rather than indexing out of bounds. The lengths they
+//! give are also checked.
+
+use super::{SaveError, SaveMemory, SLOT_HEADER_LEN};
+use crate::arena::Arena;
+
+/// Upgrades save data from one format version to the next.
+///
+/// `old` is the data at the old version. The new data goes in `out`, and
+/// the function gives how long it is. `out` is as long as the most data that
+/// fits in a slot, and it can have leftover bytes in it from before.
+pub type UpgradeFn =
+  fn(old: &[u8], out: &mut [u8]) -> Result<usize, MigrateError>;
+
+/// An error while loading and upgrading a save.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MigrateError {
+  /// An error from the save memory or the slot, such as
+  /// [`SaveError::Empty`] when there's no save yet.
+  Save(SaveError),
+  /// The save is from a newer version of the format than the schema knows
+  /// about (such as a save from a later version of the game).
+  TooNew(u16),
+  /// The save is from an older version than the schema's oldest, so there's
+  /// no way to upgrade it.
+  TooOld(u16),
+  /// An upgrade couldn't make sense of the data, or gave a length longer than
+  /// its output.
+  BadData,
+  /// The arena didn't have room for the scratch space (see
+  /// [`load_with_migration`](SaveSchema::load_with_migration)).
+  OutOfSpace,
+}
+impl From<SaveError> for MigrateError {
+  #[inline]
+  fn from(error: SaveError) -> Self {
+    Self::Save(error)
+  }
+}
+
+/// The versions of a save format, and how to upgrade from each one to the
+/// next.
+///
+/// See the [module docs](self) for an overview.
+#[derive(Debug, Clone, Copy)]
+pub struct SaveSchema {
+  oldest_version: u16,
+  upgrades: &'static [UpgradeFn],
+}
+impl SaveSchema {
+  /// A schema that starts at `oldest_version`.
+  ///
+  /// `upgrades[0]` upgrades from `oldest_version` to the version after it,
+  /// `upgrades[1]` from that to the next one, and so on. The current version
+  /// is the one after the last upgrade, so with no upgrades it's just
+  /// `oldest_version`.
+  ///
+  /// ## Panics
+  /// * The current version has to fit in a `u16`.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(
+    oldest_version: u16, upgrades: &'static [UpgradeFn],
+  ) -> Self {
+    assert!(
+      upgrades.len() <= (u16::MAX - oldest_version) as usize,
+      "too many versions to fit in a u16"
+    );
+    Self { oldest_version, upgrades }
+  }
+
+  /// The oldest version that can be upgraded.
+  #[inline]
+  #[must_use]
+  pub const fn oldest_version(&self) -> u16 {
+    self.oldest_version
+  }
+
+  /// The version that saves are made at.
+  #[inline]
+  #[must_use]
+  pub const fn current_version(&self) -> u16 {
+    self.oldest_version + self.upgrades.len() as u16
+  }
+
+  /// Saves `value` to slot number `slot`, at the current version.
+  ///
+  /// See [`save_slot_versioned`](SaveMemory::save_slot_versioned).
+  #[inline]
+  pub fn save<T: bytemuck::NoUninit, M: SaveMemory>(
+    &self, media: &M, slot: usize, value: &T,
+  ) -> Result<(), SaveError> {
+    let data = bytemuck::bytes_of(value);
+    media.save_slot_versioned(slot, self.current_version(), data)
+  }
+
+  /// Loads a value from slot number `slot`, upgrading it first if it's from
+  /// an older version.
+  ///
+  /// An upgraded save is saved back to `slot` at the current version, using
+  /// `backup_slot` along the way (see the [module docs](self#power-loss)).
+  /// This also finishes an upgrade that was cut off.
+  ///
+  /// The upgrades run in two buffers from `arena`, which are freed again
+  /// after. Each is the size of a slot's data, so with 4 KiB slots the arena
+  /// needs about 8 KiB free.
+  ///
+  /// ## Failure
+  /// * [`MigrateError::OutOfSpace`] if the buffers don't fit in `arena`.
+  /// * [`MigrateError::TooNew`] or [`MigrateError::TooOld`] if the saved
+  ///   version isn't one the schema has.
+  /// * [`MigrateError::BadData`], or any other error, from an upgrade.
+  /// * [`SaveError::WrongLength`] if the data (after any upgrades) isn't the
+  ///   size of `T`.
+  /// * Otherwise, any error from
+  ///   [`load_slot_versioned`](SaveMemory::load_slot_versioned), or from saving
+  ///   or clearing the slots.
+  ///
+  /// ## Panics
+  /// * The two slots must be different.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn load_with_migration<T: bytemuck::Pod, M: SaveMemory>(
+    &self, media: &M, slot: usize, backup_slot: usize, arena: &mut Arena,
+  ) -> Result<T, MigrateError> {
+    assert_ne!(slot, backup_slot, "the backup slot must be a different slot");
+    let len = media.slot_len().saturating_sub(SLOT_HEADER_LEN);
+    arena.scope(|arena| {
+      let a = arena.alloc_slice::<u8>(len).ok_or(MigrateError::OutOfSpace)?;
+      let b = arena.alloc_slice::<u8>(len).ok_or(MigrateError::OutOfSpace)?;
+      let current = self.current_version();
+
+      match media.load_slot_versioned(backup_slot, a) {
+        Ok((version, len)) if version == current => {
+          // An upgrade was cut off after the backup was saved. The slot
+          // could be anywhere in being rewritten (even with a header that
+          // checks out, if it was cut off partway through the header), so
+          // it's always rewritten from the backup.
+          let data = &a[..len];
+          media.save_slot_versioned(slot, current, data)?;
+          media.clear_slot(backup_slot)?;
+          return read_value(data);
+        }
+        Err(SaveError::OutOfBounds) => {
+          return Err(SaveError::OutOfBounds.into())
+        }
+        _ => (),
+      }
+
+      let (version, len) = media.load_slot_versioned(slot, a)?;
+      if version == current {
+        return read_value(&a[..len]);
+      }
+      let data = self.upgrade(version, a, len, b)?;
+      media.save_slot_versioned(backup_slot, current, data)?;
+      media.save_slot_versioned(slot, current, data)?;
+      media.clear_slot(backup_slot)?;
+      read_value(data)
+    })
+  }
+
+  /// Runs the upgrades from `version` to the current version, on the `len`
+  /// bytes at the start of `data`, using `spare` for the output.
+  ///
+  /// The two buffers swap places after each upgrade, and this gives the
+  /// final data, which is in whichever one the last upgrade used.
+  #[inline]
+  fn upgrade<'b>(
+    &self, version: u16, data: &'b mut [u8], len: usize, spare: &'b mut [u8],
+  ) -> Result<&'b [u8], MigrateError> {
+    if version > self.current_version() {
+      return Err(MigrateError::TooNew(version));
+    }
+    let Some(first) = version.checked_sub(self.oldest_version) else {
+      return Err(MigrateError::TooOld(version));
+    };
+    let (mut data, mut spare, mut len) = (data, spare, len);
+    for upgrade in &self.upgrades[usize::from(first)..] {
+      let new_len = upgrade(&data[..len], spare)?;
+      if new_len > spare.len() {
+        return Err(MigrateError::BadData);
+      }
+      core::mem::swap(&mut data, &mut spare);
+      len = new_len;
+    }
+    Ok(&data[..len])
+  }
+}
+
+/// Reads a `T` from data that should be exactly its size.
+#[inline]
+fn read_value<T: bytemuck::Pod>(data: &[u8]) -> Result<T, MigrateError> {
+  if data.len() == core::mem::size_of::<T>() {
+    Ok(bytemuck::pod_read_unaligned(data))
+  } else {
+    Err(SaveError::WrongLength.into())
+  }
+}
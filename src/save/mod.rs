@@ -0,0 +1,634 @@
+//! Module for the cartridge's save memory.
+//!
+//! Carts keep save data in one of a few kinds of chips, and each kind is
+//! accessed in its own way:
+//!
+//! * [`sram`]: battery backed SRAM, read and written a byte at a time.
+//! * [`flash`]: 64 KiB or 128 KiB of flash memory, which has to be erased
+//!   before it's written.
+//! * [`eeprom`]: 512 bytes or 8 KiB of serial EEPROM, read and written 8 bytes
+//!   at a time.
+//!
+//! [`SaveMedia`] finds which kind the cart has, and then reads and writes any
+//! of them the same way. It's one [`SaveMemory`], and
+//! [`MemoryMedia`](mem::MemoryMedia) is another, which keeps the "save" in a
+//! buffer in RAM (eg: for testing save code).
+//!
+//! On top of the plain byte access there's a small "slot" layer. The save
+//! memory is split into slots of [`SLOT_LEN`] bytes, and each saved value gets
+//! a header with a magic value, the format version, the length, and a CRC-32
+//! of the data. [`SaveMedia::load_slot`] checks all of that, so a save that was
+//! cut off partway through (because the power went off) or that went bad some
+//! other way gives [`SaveError::Corrupt`] instead of loading garbage.
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! let media = SaveMedia::init().unwrap();
+//! let high_scores: [u32; 4] = [9000, 7000, 5000, 3000];
+//! media.save_slot(0, &high_scores).unwrap();
+//! match media.load_slot::<[u32; 4]>(0) {
+//!   Ok(loaded) => assert_eq!(loaded, high_scores),
+//!   Err(SaveError::Empty) => { /* a new game */ }
+//!   Err(_) => { /* warn the player that the save is lost */ }
+//! }
+//! ```
+//!
+//! The [`save_slot`] and [`load_slot`] functions (and the `_bytes` versions)
+//! are the same thing for SRAM, without the detection.
+//!
+//! When a game's save format changes between versions, [`migrate`] upgrades
+//! the old saves.
+
+#[cfg(feature = "on_gba")]
+pub mod eeprom;
+#[cfg(feature = "on_gba")]
+pub mod flash;
+pub mod mem;
+pub mod migrate;
+#[cfg(feature = "on_gba")]
+pub mod sram;
+
+/// An error from the save memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SaveError {
+  /// The offset and length go past the end of the save memory, or the value
+  /// is too big for a slot.
+  OutOfBounds,
+  /// The slot doesn't have a saved value in it.
+  Empty,
+  /// The slot's header or data is wrong: either the save was cut off partway
+  /// through, or the memory went bad.
+  Corrupt,
+  /// The slot holds a value of a different size than the one asked for.
+  WrongLength,
+  /// The slot holds a different version of the data than the one asked for
+  /// (see [`migrate`]).
+  WrongVersion,
+  /// The save chip didn't finish an erase or a write in time.
+  Timeout,
+  /// No save memory was found.
+  NoMedia,
+}
+
+/// Sets the SRAM wait states in [`WAITCNT`](crate::mmio::WAITCNT) to 8
+/// cycles, which SRAM and flash both need.
+#[cfg(feature = "on_gba")]
+pub(crate) fn use_slow_sram_waits() {
+  use crate::{mmio::WAITCNT, waitstate::WaitCycles};
+  // Safety: this only changes the SRAM wait setting, to the slowest setting.
+  unsafe { WAITCNT.write(WAITCNT.read().with_sram(WaitCycles::_8)) };
+}
+
+/// The size of each save slot, including the [`SLOT_HEADER_LEN`] byte header.
+pub const SLOT_LEN: usize = 4 * 1024;
+
+/// The size of the header at the start of each slot.
+///
+/// The header is the bytes `b"GBAS"`, the `u16` format version, the `u16`
+/// length of the data, and then the `u32` CRC-32 of the data, all little
+/// endian. The version is [`SLOT_VERSION`] unless it's saved with
+/// [`save_slot_versioned`](SaveMemory::save_slot_versioned).
+pub const SLOT_HEADER_LEN: usize = 12;
+
+/// The most data that fits in one slot (except with the 512 byte EEPROM, see
+/// [`SaveMedia::slot_len`]).
+pub const SLOT_DATA_LEN: usize = SLOT_LEN - SLOT_HEADER_LEN;
+
+/// The format version of the data that the plain slot functions (such as
+/// [`SaveMemory::save_slot`]) save and load.
+pub const SLOT_VERSION: u16 = 1;
+
+const SLOT_MAGIC: [u8; 4] = *b"GBAS";
+
+/// The CRC-32 (the common "IEEE" one, as used by zip and png) of `bytes`.
+///
+/// This works a bit at a time, with no lookup table, so it's small but not
+/// fast: about 1 KiB per frame.
+#[inline]
+#[must_use]
+pub const fn crc32(bytes: &[u8]) -> u32 {
+  let mut crc = !0_u32;
+  let mut i = 0;
+  while i < bytes.len() {
+    crc ^= bytes[i] as u32;
+    let mut bit = 0;
+    while bit < 8 {
+      let mask = (crc & 1).wrapping_neg();
+      crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+      bit += 1;
+    }
+    i += 1;
+  }
+  !crc
+}
+
+/// Makes the header for a slot holding `data`, at format version `version`.
+const fn slot_header(version: u16, data: &[u8]) -> [u8; SLOT_HEADER_LEN] {
+  let version = version.to_le_bytes();
+  let len = (data.len() as u16).to_le_bytes();
+  let crc = crc32(data).to_le_bytes();
+  [
+    SLOT_MAGIC[0],
+    SLOT_MAGIC[1],
+    SLOT_MAGIC[2],
+    SLOT_MAGIC[3],
+    version[0],
+    version[1],
+    len[0],
+    len[1],
+    crc[0],
+    crc[1],
+    crc[2],
+    crc[3],
+  ]
+}
+
+/// Checks a slot's header, giving the version, the data length, and the
+/// CRC-32.
+const fn check_slot_header(
+  header: &[u8; SLOT_HEADER_LEN],
+) -> Result<(u16, usize, u32), SaveError> {
+  let [m0, m1, m2, m3, v0, v1, l0, l1, c0, c1, c2, c3] = *header;
+  if m0 != SLOT_MAGIC[0]
+    || m1 != SLOT_MAGIC[1]
+    || m2 != SLOT_MAGIC[2]
+    || m3 != SLOT_MAGIC[3]
+  {
+    // Erased memory, memory that was never written, or some other data.
+    return Err(SaveError::Empty);
+  }
+  let len = u16::from_le_bytes([l0, l1]) as usize;
+  if len > SLOT_DATA_LEN {
+    return Err(SaveError::Corrupt);
+  }
+  Ok((u16::from_le_bytes([v0, v1]), len, u32::from_le_bytes([c0, c1, c2, c3])))
+}
+
+/// Memory that saves can be kept in, read and written with byte offsets.
+///
+/// The required methods are the byte access, and the slot layer (see the
+/// [module docs](self)) is built on top of that, so it works the same with
+/// any of these. [`SaveMedia`] is the cart's save memory, and
+/// [`MemoryMedia`](mem::MemoryMedia) is a buffer in RAM.
+#[allow(clippy::len_without_is_empty)]
+pub trait SaveMemory {
+  /// The size of the memory, in bytes.
+  fn len(&self) -> usize;
+
+  /// The smallest part of the memory that's written at once.
+  ///
+  /// This is how much a write of a single byte can end up rewriting. By
+  /// default this is 1.
+  #[inline]
+  fn sector_size(&self) -> usize {
+    1
+  }
+
+  /// Reads `buffer.len()` bytes, starting at `offset`.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if the bytes go past the end.
+  fn read(&self, offset: usize, buffer: &mut [u8]) -> Result<(), SaveError>;
+
+  /// Writes `data`, starting at `offset`.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if the bytes go past the end.
+  /// * Anything else that the memory can fail with, such as
+  ///   [`SaveError::Timeout`].
+  fn write(&self, offset: usize, data: &[u8]) -> Result<(), SaveError>;
+
+  /// Gets `len` bytes starting at `offset` ready to be written, before a slot
+  /// is saved there.
+  ///
+  /// Flash erases the sectors here, so that each byte of the slot is only
+  /// written once. By default this does nothing.
+  ///
+  /// ## Failure
+  /// * Same as [`write`](Self::write).
+  #[inline]
+  fn prepare_write(&self, offset: usize, len: usize) -> Result<(), SaveError> {
+    let _ = (offset, len);
+    Ok(())
+  }
+
+  /// The size of each slot: [`SLOT_LEN`], or all of the memory if it's
+  /// smaller than that (such as the 512 byte EEPROM).
+  #[inline]
+  #[must_use]
+  fn slot_len(&self) -> usize {
+    self.len().min(SLOT_LEN)
+  }
+
+  /// How many slots fit in the memory.
+  #[inline]
+  #[must_use]
+  fn slot_count(&self) -> usize {
+    self.len().checked_div(self.slot_len()).unwrap_or(0)
+  }
+
+  /// Saves `value` to slot number `slot`.
+  ///
+  /// See [`save_slot_bytes`](Self::save_slot_bytes).
+  #[inline]
+  fn save_slot<T: bytemuck::NoUninit>(
+    &self, slot: usize, value: &T,
+  ) -> Result<(), SaveError> {
+    self.save_slot_bytes(slot, bytemuck::bytes_of(value))
+  }
+
+  /// Loads a value from slot number `slot`.
+  ///
+  /// ## Failure
+  /// * [`SaveError::WrongLength`] if the slot's data isn't the size of `T`.
+  /// * Otherwise, as [`load_slot_bytes`](Self::load_slot_bytes).
+  #[inline]
+  fn load_slot<T: bytemuck::Pod>(&self, slot: usize) -> Result<T, SaveError> {
+    let mut value = T::zeroed();
+    let len = self.load_slot_bytes(slot, bytemuck::bytes_of_mut(&mut value))?;
+    if len == core::mem::size_of::<T>() {
+      Ok(value)
+    } else {
+      Err(SaveError::WrongLength)
+    }
+  }
+
+  /// Saves `data` to slot number `slot`, at [`SLOT_VERSION`].
+  ///
+  /// See [`save_slot_versioned`](Self::save_slot_versioned).
+  #[inline]
+  fn save_slot_bytes(&self, slot: usize, data: &[u8]) -> Result<(), SaveError> {
+    self.save_slot_versioned(slot, SLOT_VERSION, data)
+  }
+
+  /// Loads the data in slot number `slot` into `buffer`, giving the length of
+  /// the data.
+  ///
+  /// ## Failure
+  /// * [`SaveError::WrongVersion`] if the data isn't at [`SLOT_VERSION`].
+  /// * Otherwise, as [`load_slot_versioned`](Self::load_slot_versioned).
+  #[inline]
+  fn load_slot_bytes(
+    &self, slot: usize, buffer: &mut [u8],
+  ) -> Result<usize, SaveError> {
+    match self.load_slot_versioned(slot, buffer)? {
+      (SLOT_VERSION, len) => Ok(len),
+      _ => Err(SaveError::WrongVersion),
+    }
+  }
+
+  /// Saves `data` to slot number `slot`, marked as format version `version`.
+  ///
+  /// The magic value is written first, then the data, then the rest of the
+  /// header. If the save is cut off partway through, the header's CRC-32
+  /// won't match the data that's there, so loading the slot gives
+  /// [`SaveError::Corrupt`]. Before any of that the slot is
+  /// [prepared](Self::prepare_write) (with flash, its sectors are erased), so
+  /// that each byte is only written once.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if `data` doesn't fit in a slot (with the
+  ///   header), or there's no such slot.
+  /// * Otherwise, as [`write`](Self::write).
+  #[inline]
+  fn save_slot_versioned(
+    &self, slot: usize, version: u16, data: &[u8],
+  ) -> Result<(), SaveError> {
+    let offset = slot_offset(self, slot)?;
+    if SLOT_HEADER_LEN + data.len() > self.slot_len() {
+      return Err(SaveError::OutOfBounds);
+    }
+    self.prepare_write(offset, SLOT_HEADER_LEN + data.len())?;
+    let header = slot_header(version, data);
+    self.write(offset, &header[..4])?;
+    self.write(offset + SLOT_HEADER_LEN, data)?;
+    self.write(offset + 4, &header[4..])
+  }
+
+  /// Loads the data in slot number `slot` into `buffer`, giving the format
+  /// version and the length of the data.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if there's no such slot.
+  /// * [`SaveError::Empty`] if nothing was saved to the slot.
+  /// * [`SaveError::Corrupt`] if the header or the CRC-32 is wrong.
+  /// * [`SaveError::WrongLength`] if the data doesn't fit in `buffer`.
+  #[inline]
+  fn load_slot_versioned(
+    &self, slot: usize, buffer: &mut [u8],
+  ) -> Result<(u16, usize), SaveError> {
+    let offset = slot_offset(self, slot)?;
+    let mut header = [0; SLOT_HEADER_LEN];
+    self.read(offset, &mut header)?;
+    let (version, len, crc) = check_slot_header(&header)?;
+    if SLOT_HEADER_LEN + len > self.slot_len() {
+      return Err(SaveError::Corrupt);
+    }
+    let Some(data) = buffer.get_mut(..len) else {
+      return Err(SaveError::WrongLength);
+    };
+    self.read(offset + SLOT_HEADER_LEN, data)?;
+    if crc32(data) == crc {
+      Ok((version, len))
+    } else {
+      Err(SaveError::Corrupt)
+    }
+  }
+
+  /// Makes slot number `slot` empty, by clearing its magic value and its
+  /// CRC-32.
+  ///
+  /// The CRC-32 is cleared too so that the old data can't come back: without
+  /// it, a later save to the slot that's cut off right after its magic value
+  /// would find the old header still matching the old data.
+  ///
+  /// This only writes zeros (so flash doesn't need an erase for it), and if
+  /// it's cut off partway through the slot is either left as it was or
+  /// empty.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if there's no such slot.
+  /// * Otherwise, as [`write`](Self::write).
+  #[inline]
+  fn clear_slot(&self, slot: usize) -> Result<(), SaveError> {
+    let offset = slot_offset(self, slot)?;
+    self.write(offset, &[0; 4])?;
+    self.write(offset + 8, &[0; 4])
+  }
+}
+
+/// The offset of slot number `slot`.
+fn slot_offset<M: SaveMemory + ?Sized>(
+  media: &M, slot: usize,
+) -> Result<usize, SaveError> {
+  if slot < media.slot_count() {
+    Ok(slot * media.slot_len())
+  } else {
+    Err(SaveError::OutOfBounds)
+  }
+}
+
+/// The save memory of the cart, as found by [`SaveMedia::init`].
+///
+/// All kinds of save memory work through the same methods here, with byte
+/// offsets, so code that saves (such as the slot methods) doesn't need to know
+/// which kind the cart has.
+#[cfg(feature = "on_gba")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SaveMedia {
+  /// Battery backed SRAM, see [`sram`].
+  Sram,
+  /// A flash chip, see [`flash`].
+  Flash(flash::FlashChip),
+  /// An EEPROM chip, see [`eeprom`].
+  Eeprom(eeprom::Eeprom),
+}
+#[cfg(feature = "on_gba")]
+impl SaveMedia {
+  /// Looks for each kind of save memory, and uses the first one found.
+  ///
+  /// The checks are done in an order that doesn't change any saved data:
+  /// 1. EEPROM, with [`Eeprom::is_present`](eeprom::Eeprom::is_present), which
+  ///    only reads. Its size is then found with
+  ///    [`Eeprom::detect_size`](eeprom::Eeprom::detect_size), which does write,
+  ///    but puts the old data back.
+  /// 2. Flash, with [`FlashChip::detect`](flash::FlashChip::detect).
+  /// 3. SRAM, with [`sram::is_present`], which changes a byte and then puts it
+  ///    back.
+  ///
+  /// This links in all three drivers, so the ROM has all three save type
+  /// strings in it. Emulators and flash carts that pick the save type from
+  /// those strings (or from the first access to the save memory) may need the
+  /// save type set by hand.
+  ///
+  /// ## Failure
+  /// * [`SaveError::NoMedia`] if none of them are found.
+  /// * [`SaveError::Timeout`] if the EEPROM size check times out.
+  #[inline]
+  pub fn init() -> Result<Self, SaveError> {
+    use eeprom::Eeprom;
+    if Eeprom::is_present() {
+      return Ok(Self::Eeprom(Eeprom::new(Eeprom::detect_size()?)));
+    }
+    if let Some(kind) = flash::FlashChip::detect() {
+      return Ok(Self::Flash(flash::FlashChip::new(kind)));
+    }
+    if sram::is_present() {
+      return Ok(Self::Sram);
+    }
+    Err(SaveError::NoMedia)
+  }
+
+  /// A short name for the kind of save memory, such as `"Flash 128K"`.
+  #[inline]
+  #[must_use]
+  pub const fn name(&self) -> &'static str {
+    use eeprom::EepromSize;
+    match self {
+      Self::Sram => "SRAM",
+      Self::Flash(chip) => match chip.kind().size() {
+        0x1_0000 => "Flash 64K",
+        _ => "Flash 128K",
+      },
+      Self::Eeprom(chip) => match chip.size() {
+        EepromSize::_512B => "EEPROM 512B",
+        EepromSize::_8KiB => "EEPROM 8K",
+      },
+    }
+  }
+
+  /// The size of the save memory, in bytes.
+  #[inline]
+  #[must_use]
+  #[allow(clippy::len_without_is_empty)]
+  pub const fn len(&self) -> usize {
+    match self {
+      Self::Sram => sram::SRAM_LEN,
+      Self::Flash(chip) => chip.kind().size(),
+      Self::Eeprom(chip) => chip.size().size(),
+    }
+  }
+
+  /// The smallest part of the save memory that's written at once.
+  ///
+  /// This is how much a write of a single byte can end up rewriting: 1 for
+  /// SRAM, 8 for EEPROM, and 4 KiB (or 128 bytes) for flash.
+  #[inline]
+  #[must_use]
+  pub const fn sector_size(&self) -> usize {
+    match self {
+      Self::Sram => 1,
+      Self::Flash(chip) => chip.kind().sector_size(),
+      Self::Eeprom(_) => 8,
+    }
+  }
+
+  /// Reads `buffer.len()` bytes, starting at `offset`.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if the bytes go past the end.
+  #[inline]
+  pub fn read(
+    &self, offset: usize, buffer: &mut [u8],
+  ) -> Result<(), SaveError> {
+    match self {
+      Self::Sram => sram::read(offset, buffer),
+      Self::Flash(chip) => chip.read(offset, buffer),
+      Self::Eeprom(chip) => chip.read(offset, buffer),
+    }
+  }
+
+  /// Writes `data`, starting at `offset`.
+  ///
+  /// ## Failure
+  /// * [`SaveError::OutOfBounds`] if the bytes go past the end.
+  /// * [`SaveError::Timeout`] if a flash or EEPROM write doesn't finish in
+  ///   time.
+  #[inline]
+  pub fn write(&self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    match self {
+      Self::Sram => sram::write(offset, data),
+      Self::Flash(chip) => chip.write(offset, data),
+      Self::Eeprom(chip) => chip.write(offset, data),
+    }
+  }
+
+  /// The size of each slot: [`SLOT_LEN`], or all of the save memory if it's
+  /// smaller than that (the 512 byte EEPROM).
+  #[inline]
+  #[must_use]
+  pub const fn slot_len(&self) -> usize {
+    if self.len() < SLOT_LEN {
+      self.len()
+    } else {
+      SLOT_LEN
+    }
+  }
+
+  /// How many slots fit in the save memory.
+  #[inline]
+  #[must_use]
+  pub const fn slot_count(&self) -> usize {
+    self.len() / self.slot_len()
+  }
+
+  /// Saves `value` to slot number `slot`.
+  ///
+  /// See [`SaveMemory::save_slot`].
+  #[inline]
+  pub fn save_slot<T: bytemuck::NoUninit>(
+    &self, slot: usize, value: &T,
+  ) -> Result<(), SaveError> {
+    SaveMemory::save_slot(self, slot, value)
+  }
+
+  /// Loads a value from slot number `slot`.
+  ///
+  /// See [`SaveMemory::load_slot`].
+  #[inline]
+  pub fn load_slot<T: bytemuck::Pod>(
+    &self, slot: usize,
+  ) -> Result<T, SaveError> {
+    SaveMemory::load_slot(self, slot)
+  }
+
+  /// Saves `data` to slot number `slot`.
+  ///
+  /// See [`SaveMemory::save_slot_bytes`].
+  #[inline]
+  pub fn save_slot_bytes(
+    &self, slot: usize, data: &[u8],
+  ) -> Result<(), SaveError> {
+    SaveMemory::save_slot_bytes(self, slot, data)
+  }
+
+  /// Loads the data in slot number `slot` into `buffer`, giving the length of
+  /// the data.
+  ///
+  /// See [`SaveMemory::load_slot_bytes`].
+  #[inline]
+  pub fn load_slot_bytes(
+    &self, slot: usize, buffer: &mut [u8],
+  ) -> Result<usize, SaveError> {
+    SaveMemory::load_slot_bytes(self, slot, buffer)
+  }
+}
+#[cfg(feature = "on_gba")]
+impl SaveMemory for SaveMedia {
+  #[inline]
+  fn len(&self) -> usize {
+    SaveMedia::len(self)
+  }
+
+  #[inline]
+  fn sector_size(&self) -> usize {
+    SaveMedia::sector_size(self)
+  }
+
+  #[inline]
+  fn read(&self, offset: usize, buffer: &mut [u8]) -> Result<(), SaveError> {
+    SaveMedia::read(self, offset, buffer)
+  }
+
+  #[inline]
+  fn write(&self, offset: usize, data: &[u8]) -> Result<(), SaveError> {
+    SaveMedia::write(self, offset, data)
+  }
+
+  #[inline]
+  fn prepare_write(&self, offset: usize, len: usize) -> Result<(), SaveError> {
+    if let Self::Flash(chip) = self {
+      let sector_size = chip.kind().sector_size();
+      for sector in offset / sector_size..(offset + len).div_ceil(sector_size) {
+        chip.erase_sector(sector)?;
+      }
+    }
+    Ok(())
+  }
+
+  #[inline]
+  fn slot_len(&self) -> usize {
+    SaveMedia::slot_len(self)
+  }
+}
+
+/// Saves `value` to slot number `slot` of the [`sram`].
+///
+/// See [`SaveMedia::save_slot_bytes`].
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn save_slot<T: bytemuck::NoUninit>(
+  slot: usize, value: &T,
+) -> Result<(), SaveError> {
+  SaveMedia::Sram.save_slot(slot, value)
+}
+
+/// Loads a value from slot number `slot` of the [`sram`].
+///
+/// See [`SaveMedia::load_slot`].
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn load_slot<T: bytemuck::Pod>(slot: usize) -> Result<T, SaveError> {
+  SaveMedia::Sram.load_slot(slot)
+}
+
+/// Saves `data` to slot number `slot` of the [`sram`].
+///
+/// See [`SaveMedia::save_slot_bytes`].
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn save_slot_bytes(slot: usize, data: &[u8]) -> Result<(), SaveError> {
+  SaveMedia::Sram.save_slot_bytes(slot, data)
+}
+
+/// Loads the data in slot number `slot` of the [`sram`] into `buffer`, giving
+/// the length of the data.
+///
+/// See [`SaveMedia::load_slot_bytes`].
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn load_slot_bytes(
+  slot: usize, buffer: &mut [u8],
+) -> Result<usize, SaveError> {
+  SaveMedia::Sram.load_slot_bytes(slot, buffer)
+}
@@ -0,0 +1,102 @@
+//! Battery backed SRAM, 32 KiB at `0x0E00_0000`.
+//!
+//! The SRAM is on an 8-bit bus, so it can only be read and written one byte at
+//! a time: a `u16` or `u32` access gives the wrong values. The functions here
+//! always copy one byte at a time, using code that runs from IWRAM
+//! ([`copy_u8_unchecked`]), and they also set the SRAM wait states in
+//! [`WAITCNT`](crate::mmio::WAITCNT) to 8 cycles, which the SRAM needs.
+//!
+//! Using this module also puts the [`SRAM_SAVE_ID`] string in the ROM, so that
+//! emulators and flash carts can tell that the game saves to SRAM.
+
+use super::SaveError;
+use crate::{mem::copy_u8_unchecked, Align4};
+
+/// The size of the SRAM.
+pub const SRAM_LEN: usize = 32 * 1024;
+
+/// Where the SRAM is.
+const SRAM_BASE: usize = 0x0E00_0000;
+
+/// The save type string that emulators and flash carts look for in the ROM.
+pub static SRAM_SAVE_ID: Align4<[u8; 12]> = Align4(*b"SRAM_V113\0\0\0");
+
+/// Gets ready to access the SRAM.
+fn prepare() {
+  // Reading the save type string keeps it from being left out of the ROM.
+  // Safety: it's a normal static.
+  unsafe { core::ptr::read_volatile(SRAM_SAVE_ID.0.as_ptr()) };
+  super::use_slow_sram_waits();
+}
+
+/// If there seems to be SRAM, from writing a byte and reading it back.
+///
+/// The last byte of the SRAM is flipped, checked, and then put back (even if
+/// something goes wrong partway through).
+#[inline]
+#[must_use]
+pub fn is_present() -> bool {
+  /// Puts the old byte back when it's dropped.
+  struct Restore(u8);
+  impl Drop for Restore {
+    fn drop(&mut self) {
+      write(SRAM_LEN - 1, &[self.0]).ok();
+    }
+  }
+  let mut old = [0];
+  if read(SRAM_LEN - 1, &mut old).is_err() {
+    return false;
+  }
+  let _restore = Restore(old[0]);
+  let flipped = !old[0];
+  let mut check = [0];
+  write(SRAM_LEN - 1, &[flipped]).is_ok()
+    && read(SRAM_LEN - 1, &mut check).is_ok()
+    && check[0] == flipped
+}
+
+/// Checks that `len` bytes from `offset` are all in the SRAM.
+const fn check_bounds(offset: usize, len: usize) -> Result<(), SaveError> {
+  match offset.checked_add(len) {
+    Some(end) if end <= SRAM_LEN => Ok(()),
+    _ => Err(SaveError::OutOfBounds),
+  }
+}
+
+/// Reads `buffer.len()` bytes from the SRAM, starting at `offset`.
+///
+/// ## Failure
+/// * [`SaveError::OutOfBounds`] if the bytes go past the end of the SRAM.
+#[inline]
+pub fn read(offset: usize, buffer: &mut [u8]) -> Result<(), SaveError> {
+  check_bounds(offset, buffer.len())?;
+  prepare();
+  // Safety: the bounds were checked, and the SRAM is always readable.
+  unsafe {
+    copy_u8_unchecked(
+      buffer.as_mut_ptr(),
+      (SRAM_BASE + offset) as *const u8,
+      buffer.len(),
+    )
+  };
+  Ok(())
+}
+
+/// Writes `data` to the SRAM, starting at `offset`.
+///
+/// ## Failure
+/// * [`SaveError::OutOfBounds`] if the bytes go past the end of the SRAM.
+#[inline]
+pub fn write(offset: usize, data: &[u8]) -> Result<(), SaveError> {
+  check_bounds(offset, data.len())?;
+  prepare();
+  // Safety: the bounds were checked, and the SRAM is always writable.
+  unsafe {
+    copy_u8_unchecked(
+      (SRAM_BASE + offset) as *mut u8,
+      data.as_ptr(),
+      data.len(),
+    )
+  };
+  Ok(())
+}
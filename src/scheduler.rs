@@ -0,0 +1,274 @@
+//! Software timers, counted in frames.
+//!
+//! There are only four hardware timers (and sound usually needs two of them),
+//! while most of what a game waits on is counted in frames anyway: a screen
+//! shake that lasts half a second, a sound effect 20 frames after an
+//! explosion, an enemy that fires every 90 frames. A [`Scheduler`] keeps a
+//! fixed number of these as software timers.
+//!
+//! Each timer has an event, which is any `Copy` type the game picks: usually a
+//! small enum that it matches on, or a function pointer that it calls. Each
+//! [`tick`](Scheduler::tick) moves the scheduler forward one frame and gives
+//! the events of the timers that are due.
+//!
+//! ```no_run
+//! # use gba::{prelude::*, scheduler::*};
+//! #[derive(Debug, Clone, Copy)]
+//! enum Event {
+//!   Blink,
+//!   Explode,
+//! }
+//!
+//! let mut timers = Scheduler::<Event, 8>::new();
+//! timers.every(30, Event::Blink).unwrap();
+//! let fuse = timers.after(120, Event::Explode).unwrap();
+//! loop {
+//!   wait_for_vblank();
+//!   for event in timers.tick().iter() {
+//!     match event {
+//!       Event::Blink => { /* ... */ }
+//!       Event::Explode => { /* ... */ }
+//!     }
+//!   }
+//!   if KEYINPUT.read().a() {
+//!     // defused
+//!     timers.cancel(fuse);
+//!   }
+//!   # break;
+//! }
+//! ```
+//!
+//! ## Order
+//! Timers that are due on the same tick fire in the order they were made,
+//! however long each one waited. A repeating timer keeps its place in that
+//! order every time it fires.
+//!
+//! ## Missed frames
+//! [`tick`](Scheduler::tick) counts as one frame, so with a frame missed the
+//! timers run behind. [`update`](Scheduler::update) instead goes by a
+//! [`FrameInstant`], moving forward however many frames passed since the last
+//! update. Each timer fires at most once per update, even if it was due more
+//! than once in that time, and a repeating timer stays on its schedule (30
+//! frames apart stays 30 frames apart, counted from when it was made).
+//!
+//! ## With an interrupt
+//! To tick from the vblank interrupt handler, keep the scheduler in an
+//! [`IrqMutex`](crate::interrupts::IrqMutex). Then timers made or cancelled by
+//! the main program can't be changed partway through by a tick.
+//!
+//! ```no_run
+//! # use gba::{prelude::*, scheduler::*};
+//! static TIMERS: IrqMutex<Scheduler<fn(), 8>> = IrqMutex::new(Scheduler::new());
+//!
+//! extern "C" fn irq_handler(bits: IrqBits) {
+//!   if bits.vblank() {
+//!     let due = TIMERS.with_in_handler(|timers| timers.tick());
+//!     // call the events after the timers are unlocked, so that they can
+//!     // make more timers.
+//!     due.iter().for_each(|event| event());
+//!   }
+//! }
+//!
+//! fn beep() {
+//!   // ...
+//! }
+//!
+//! // In the main program:
+//! TIMERS.with(|timers| timers.after(60, beep as fn())).unwrap();
+//! ```
+
+use crate::{collections::ArrayVec, time::FrameInstant};
+
+/// Names one timer in a [`Scheduler`], to cancel it or check on it.
+///
+/// Handles aren't reused by a scheduler (until 2^32 timers are made), so a
+/// handle to a timer that already fired or was cancelled doesn't name any
+/// timer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerHandle(u32);
+
+/// One timer in a [`Scheduler`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Timer<E> {
+  id: u32,
+  /// Frames until it fires, which is at least 1.
+  left: u32,
+  /// Frames between firings, or 0 if it only fires once.
+  period: u32,
+  event: E,
+}
+
+/// Up to `N` software timers, each with an event of type `E`.
+///
+/// See the [module docs](self) for an overview.
+#[derive(Debug, Clone)]
+pub struct Scheduler<E, const N: usize> {
+  timers: ArrayVec<Timer<E>, N>,
+  next_id: u32,
+  last_update: Option<FrameInstant>,
+}
+impl<E: Copy, const N: usize> Scheduler<E, N> {
+  /// A scheduler with no timers.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { timers: ArrayVec::new(), next_id: 0, last_update: None }
+  }
+
+  /// The number of timers that are waiting to fire.
+  #[inline]
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.timers.len()
+  }
+
+  /// If there are no timers waiting to fire.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.timers.is_empty()
+  }
+
+  /// How many timers can be waiting at once.
+  #[inline]
+  #[must_use]
+  pub const fn capacity(&self) -> usize {
+    N
+  }
+
+  /// Makes a timer that fires `event` once, `frames` frames from now.
+  ///
+  /// With `frames` as 1, it fires on the next tick.
+  ///
+  /// ## Failure
+  /// * If there are already `N` timers, this gives `event` back.
+  ///
+  /// ## Panics
+  /// * `frames` can't be 0.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn after(&mut self, frames: u32, event: E) -> Result<TimerHandle, E> {
+    assert!(frames > 0, "a timer must wait at least 1 frame");
+    self.add(Timer { id: self.next_id, left: frames, period: 0, event })
+  }
+
+  /// Makes a timer that fires `event` every `frames` frames, starting
+  /// `frames` frames from now, until it's [cancelled](Self::cancel).
+  ///
+  /// ## Failure
+  /// * If there are already `N` timers, this gives `event` back.
+  ///
+  /// ## Panics
+  /// * `frames` can't be 0.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn every(&mut self, frames: u32, event: E) -> Result<TimerHandle, E> {
+    assert!(frames > 0, "a timer must wait at least 1 frame");
+    self.add(Timer { id: self.next_id, left: frames, period: frames, event })
+  }
+
+  /// Adds a timer to the end of the firing order.
+  #[inline]
+  fn add(&mut self, timer: Timer<E>) -> Result<TimerHandle, E> {
+    let id = timer.id;
+    self.timers.push(timer).map_err(|timer| timer.event)?;
+    self.next_id = self.next_id.wrapping_add(1);
+    Ok(TimerHandle(id))
+  }
+
+  /// Cancels a timer, giving its event.
+  ///
+  /// If the timer already fired (for a one-shot timer) or was cancelled,
+  /// this does nothing and gives `None`.
+  #[inline]
+  pub fn cancel(&mut self, handle: TimerHandle) -> Option<E> {
+    let index = self.index_of(handle)?;
+    self.timers.remove(index).map(|timer| timer.event)
+  }
+
+  /// If the timer is still waiting to fire.
+  #[inline]
+  #[must_use]
+  pub fn is_scheduled(&self, handle: TimerHandle) -> bool {
+    self.index_of(handle).is_some()
+  }
+
+  /// The number of ticks until the timer next fires, if it's still waiting.
+  ///
+  /// This is at least 1, for a timer that fires on the next tick.
+  #[inline]
+  #[must_use]
+  pub fn remaining(&self, handle: TimerHandle) -> Option<u32> {
+    self.index_of(handle).map(|index| self.timers[index].left)
+  }
+
+  /// Cancels every timer.
+  #[inline]
+  pub fn clear(&mut self) {
+    self.timers.clear();
+  }
+
+  /// Moves forward one frame, giving the events of the timers that fire, in
+  /// the order the timers were made.
+  #[inline]
+  pub fn tick(&mut self) -> ArrayVec<E, N> {
+    self.advance(1)
+  }
+
+  /// Moves forward by the frames since the last update, giving the events of
+  /// the timers that fire, in the order the timers were made.
+  ///
+  /// `now` is usually [`FrameInstant::now`]. The first update counts as one
+  /// frame, the same as a [`tick`](Self::tick). See the
+  /// [module docs](self#missed-frames) for how timers catch up.
+  #[inline]
+  pub fn update(&mut self, now: FrameInstant) -> ArrayVec<E, N> {
+    let frames = self.last_update.map_or(1, |last| now.since(last));
+    self.last_update = Some(now);
+    self.advance(frames)
+  }
+
+  /// Moves forward `frames` frames, giving the events of the timers that
+  /// fire, in the order the timers were made.
+  ///
+  /// Each timer fires at most once, however many times it was due. Moving
+  /// forward 0 frames fires nothing.
+  #[inline]
+  pub fn advance(&mut self, frames: u32) -> ArrayVec<E, N> {
+    let mut fired = ArrayVec::new();
+    if frames == 0 {
+      return fired;
+    }
+    let mut i = 0;
+    while i < self.timers.len() {
+      let timer = &mut self.timers[i];
+      if timer.left > frames {
+        timer.left -= frames;
+        i += 1;
+        continue;
+      }
+      // Each timer fires at most once, so there's always room.
+      let _ = fired.push(timer.event);
+      if timer.period == 0 {
+        self.timers.remove(i);
+      } else {
+        let late = frames - timer.left;
+        timer.left = timer.period - late % timer.period;
+        i += 1;
+      }
+    }
+    fired
+  }
+
+  /// Where the timer is in the list, if it's still there.
+  #[inline]
+  fn index_of(&self, handle: TimerHandle) -> Option<usize> {
+    self.timers.iter().position(|timer| timer.id == handle.0)
+  }
+}
+impl<E: Copy, const N: usize> Default for Scheduler<E, N> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
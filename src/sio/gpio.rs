@@ -0,0 +1,172 @@
+//! General purpose mode: the four link port pins as digital inputs and outputs.
+//!
+//! In general purpose mode the serial hardware is out of the way, and each of
+//! the four data pins of the link port (SC, SD, SI, and SO) can be set as an
+//! input or an output, and read or set directly through
+//! [`RCNT`](crate::mmio::RCNT). This is handy for custom hardware: LEDs,
+//! buttons, sensors, or bit-banged protocols.
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! let gpio = enter_gpio_mode(PinDirections::new().with_output(Pin::So, true));
+//! gpio.set_pin(Pin::So, true);
+//! let button_down = !gpio.pin(Pin::Si);
+//! gpio.exit();
+//! ```
+//!
+//! The pins are 3.3 volts, with no protection, so be careful what's connected.
+
+use crate::macros::{pub_const_fn_new_zeroed, u8_bool_field};
+
+/// One of the four link port data pins.
+///
+/// The discriminant is the pin's bit in the levels and directions values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Pin {
+  Sc = 0,
+  Sd = 1,
+  Si = 2,
+  So = 3,
+}
+impl Pin {
+  /// This pin's bit, for [`Gpio::write_pins`] and [`Gpio::read_pins`].
+  #[inline]
+  #[must_use]
+  pub const fn mask(self) -> u8 {
+    1 << (self as u8)
+  }
+}
+
+/// Which of the link port pins are outputs (the rest are inputs).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct PinDirections(u8);
+impl PinDirections {
+  pub_const_fn_new_zeroed!();
+  u8_bool_field!(0, sc_output, with_sc_output);
+  u8_bool_field!(1, sd_output, with_sd_output);
+  u8_bool_field!(2, si_output, with_si_output);
+  u8_bool_field!(3, so_output, with_so_output);
+
+  /// If `pin` is an output.
+  #[inline]
+  #[must_use]
+  pub const fn is_output(self, pin: Pin) -> bool {
+    self.0 & pin.mask() != 0
+  }
+
+  /// Sets if `pin` is an output.
+  #[inline]
+  #[must_use]
+  pub const fn with_output(self, pin: Pin, output: bool) -> Self {
+    if output {
+      Self(self.0 | pin.mask())
+    } else {
+      Self(self.0 & !pin.mask())
+    }
+  }
+
+  /// The direction bits, one per pin (bit 0 is SC).
+  #[inline]
+  #[must_use]
+  pub const fn to_u8(self) -> u8 {
+    self.0
+  }
+
+  /// Makes directions from the low four bits of `bits`.
+  #[inline]
+  #[must_use]
+  pub const fn from_u8(bits: u8) -> Self {
+    Self(bits & 0b1111)
+  }
+}
+
+/// Puts the link port into general purpose mode.
+///
+/// All output pins start low, and the SI interrupt starts off. The old
+/// [`RCNT`](crate::mmio::RCNT) value is kept in the [`Gpio`] handle, and is
+/// put back by [`Gpio::exit`].
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn enter_gpio_mode(directions: PinDirections) -> Gpio {
+  use super::{LinkPortControl, PortMode};
+  use crate::mmio::RCNT;
+  let previous = RCNT.read();
+  RCNT.write(
+    LinkPortControl::new()
+      .with_pin_directions(directions.to_u8() as u16)
+      .with_mode(PortMode::Gpio),
+  );
+  Gpio { previous }
+}
+
+/// The link port in general purpose mode, from [`enter_gpio_mode`].
+#[cfg(feature = "on_gba")]
+#[derive(Debug)]
+pub struct Gpio {
+  previous: super::LinkPortControl,
+}
+#[cfg(feature = "on_gba")]
+impl Gpio {
+  /// Sets the level of every output pin from the low four bits of `levels`
+  /// (bit 0 is SC, see [`Pin::mask`]).
+  ///
+  /// Bits for input pins are ignored.
+  #[inline]
+  pub fn write_pins(&self, levels: u8) {
+    use crate::mmio::RCNT;
+    RCNT.apply(|rcnt| *rcnt = rcnt.with_pin_levels((levels & 0b1111) as u16));
+  }
+
+  /// Reads the level of all four pins, as the low four bits.
+  #[inline]
+  #[must_use]
+  pub fn read_pins(&self) -> u8 {
+    crate::mmio::RCNT.read().pin_levels() as u8
+  }
+
+  /// Sets the level of one output pin.
+  #[inline]
+  pub fn set_pin(&self, pin: Pin, high: bool) {
+    let levels = self.read_pins();
+    self.write_pins(if high {
+      levels | pin.mask()
+    } else {
+      levels & !pin.mask()
+    });
+  }
+
+  /// If one pin is high.
+  #[inline]
+  #[must_use]
+  pub fn pin(&self, pin: Pin) -> bool {
+    self.read_pins() & pin.mask() != 0
+  }
+
+  /// Changes which pins are outputs.
+  #[inline]
+  pub fn set_directions(&self, directions: PinDirections) {
+    use crate::mmio::RCNT;
+    RCNT.apply(|rcnt| {
+      *rcnt = rcnt.with_pin_directions(directions.to_u8() as u16)
+    });
+  }
+
+  /// Sets if the serial interrupt is sent when SI goes from high to low.
+  ///
+  /// SI should be an input for this. The serial interrupt also has to be on
+  /// in [`IE`](crate::mmio::IE).
+  #[inline]
+  pub fn set_si_irq(&self, enabled: bool) {
+    use crate::mmio::RCNT;
+    RCNT.apply(|rcnt| *rcnt = rcnt.with_si_irq(enabled));
+  }
+
+  /// Leaves general purpose mode, putting back the `RCNT` value from before
+  /// [`enter_gpio_mode`].
+  #[inline]
+  pub fn exit(self) {
+    crate::mmio::RCNT.write(self.previous);
+  }
+}
@@ -0,0 +1,183 @@
+//! JOY Bus mode: the GBA as a device on a GameCube controller port.
+//!
+//! In JOY Bus mode the GBA is always the device, and the master (usually a
+//! GameCube) sends commands to it. The hardware answers the commands on its
+//! own, so the GBA only has to keep its registers up to date:
+//! * `0x00` (status) and `0xFF` (reset): the master reads the device type and
+//!   [`JoyStatus`]. A reset also sets the `reset` flag of [`JoyControl`].
+//! * `0x14` (read): the master reads [`JOY_TRANS`](crate::mmio::JOY_TRANS),
+//!   which sets the `send_complete` flag.
+//! * `0x15` (write): the master writes [`JOY_RECV`](crate::mmio::JOY_RECV),
+//!   which sets the `receive_complete` flag.
+//!
+//! Each of those sets the serial interrupt, and [`joybus_irq`] turns the flags
+//! into a queue of [`JoyEvent`] values, which the main program reads with
+//! [`poll`]:
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! use gba::sio::joybus::*;
+//! RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+//! set_handler(Interrupt::Serial, joybus_irq);
+//! enter_joybus_mode();
+//! loop {
+//!   while let Some(event) = poll() {
+//!     match event {
+//!       JoyEvent::Reset => { /* the master is starting over */ }
+//!       JoyEvent::Received(word) => joybus_send(word),
+//!       JoyEvent::SendComplete => {}
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! The master only waits a short time for each answer, so the interrupt
+//! handler is kept small, and is placed in IWRAM.
+
+#[cfg(feature = "on_gba")]
+use super::{LinkPortControl, PortMode};
+use crate::macros::{
+  pub_const_fn_new_zeroed, u16_bool_field, u8_bool_field, u8_int_field,
+};
+
+/// JOY Bus control.
+///
+/// * `reset`: The master sent a reset command.
+/// * `receive_complete`: The master wrote to
+///   [`JOY_RECV`](crate::mmio::JOY_RECV).
+/// * `send_complete`: The master read [`JOY_TRANS`](crate::mmio::JOY_TRANS).
+/// * `irq_on_reset`: Sends the serial interrupt for the reset command (the read
+///   and write commands always send it).
+///
+/// The three flags are cleared by writing `true` to them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct JoyControl(u16);
+impl JoyControl {
+  pub_const_fn_new_zeroed!();
+  u16_bool_field!(0, reset, with_reset);
+  u16_bool_field!(1, receive_complete, with_receive_complete);
+  u16_bool_field!(2, send_complete, with_send_complete);
+  u16_bool_field!(6, irq_on_reset, with_irq_on_reset);
+}
+
+/// JOY Bus status, which the master reads with the status and reset commands.
+///
+/// * `receive_pending`: [`JOY_RECV`](crate::mmio::JOY_RECV) was written by the
+///   master and hasn't been read by the GBA yet (read only).
+/// * `send_pending`: [`JOY_TRANS`](crate::mmio::JOY_TRANS) was written by the
+///   GBA and hasn't been read by the master yet (read only).
+/// * `user_flags`: Two bits (0 to 3) with no set meaning, for the game and the
+///   master to use however they like.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct JoyStatus(u8);
+impl JoyStatus {
+  pub_const_fn_new_zeroed!();
+  u8_bool_field!(1, receive_pending, with_receive_pending);
+  u8_bool_field!(3, send_pending, with_send_pending);
+  u8_int_field!(4 - 5, user_flags, with_user_flags);
+}
+
+/// Something the JOY Bus master did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JoyEvent {
+  /// The master sent a reset command.
+  Reset,
+  /// The master wrote a value.
+  Received(u32),
+  /// The master read the value from [`joybus_send`].
+  SendComplete,
+}
+
+/// The number of events the queue for [`poll`] can hold.
+///
+/// If the queue is full when more events come in, the new events are dropped.
+pub const JOY_EVENT_QUEUE_LEN: usize = 16;
+
+#[cfg(feature = "on_gba")]
+static EVENTS: crate::interrupts::IrqMutex<
+  crate::collections::RingDeque<JoyEvent, JOY_EVENT_QUEUE_LEN>,
+> = crate::interrupts::IrqMutex::new(crate::collections::RingDeque::new());
+
+/// Puts the link port into JOY Bus mode.
+///
+/// This empties the [`poll`] queue, clears any old flags, sets
+/// [`JOYCNT`](crate::mmio::JOYCNT) to send the interrupt for resets too, and
+/// then sets [`RCNT`](crate::mmio::RCNT) to JOY Bus mode. The serial interrupt
+/// still has to be set to call [`joybus_irq`].
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn enter_joybus_mode() {
+  use crate::mmio::{JOYCNT, RCNT};
+  EVENTS.with(|events| events.clear());
+  JOYCNT.write(
+    JoyControl::new()
+      .with_reset(true)
+      .with_receive_complete(true)
+      .with_send_complete(true)
+      .with_irq_on_reset(true),
+  );
+  RCNT.write(LinkPortControl::new().with_mode(PortMode::JoyBus));
+}
+
+/// Leaves JOY Bus mode, going back to normal serial mode.
+///
+/// This clears [`RCNT`](crate::mmio::RCNT) and turns off the reset
+/// interrupt.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn exit_joybus_mode() {
+  use crate::mmio::{JOYCNT, RCNT};
+  RCNT.write(LinkPortControl::new());
+  JOYCNT.write(JoyControl::new());
+}
+
+/// The serial interrupt handler for JOY Bus mode.
+///
+/// Set this with [`set_handler`](crate::interrupts::set_handler), or call it
+/// from your own handler when the `serial` bit is set. It clears the
+/// [`JOYCNT`](crate::mmio::JOYCNT) flags and queues one [`JoyEvent`] for each.
+/// For a write, it reads [`JOY_RECV`](crate::mmio::JOY_RECV) right away, so
+/// that the master sees the GBA as ready for the next value.
+#[cfg(feature = "on_gba")]
+#[link_section = ".iwram.joybus_irq"]
+#[inline(never)]
+pub extern "C" fn joybus_irq(_: crate::interrupts::IrqBits) {
+  use crate::mmio::{JOYCNT, JOY_RECV};
+  let flags = JOYCNT.read();
+  JOYCNT.write(flags);
+  EVENTS.with_in_handler(|events| {
+    if flags.reset() {
+      let _ = events.push_back(JoyEvent::Reset);
+    }
+    if flags.receive_complete() {
+      let _ = events.push_back(JoyEvent::Received(JOY_RECV.read()));
+    }
+    if flags.send_complete() {
+      let _ = events.push_back(JoyEvent::SendComplete);
+    }
+  });
+}
+
+/// Takes the oldest [`JoyEvent`] from the queue, if there is one.
+#[cfg(feature = "on_gba")]
+#[inline]
+#[must_use]
+pub fn poll() -> Option<JoyEvent> {
+  EVENTS.with(|events| events.pop_front())
+}
+
+/// Sets the value the master gets with its next read command.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn joybus_send(word: u32) {
+  crate::mmio::JOY_TRANS.write(word);
+}
+
+/// Sets the two user flags of [`JOYSTAT`](crate::mmio::JOYSTAT).
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn set_joybus_user_flags(flags: u8) {
+  crate::mmio::JOYSTAT.write(JoyStatus::new().with_user_flags(flags));
+}
@@ -0,0 +1,123 @@
+//! Module for the serial port (the link cable port).
+//!
+//! The serial port can run in several modes, with the mode picked by the top
+//! bits of [`RCNT`](crate::mmio::RCNT) and bits 12-13 of
+//! [`SIOCNT`](crate::mmio::SIOCNT). The meaning of the other `SIOCNT` bits
+//! depends on the mode, so each mode has its own control type, and its own
+//! typed alias of the `SIOCNT` address in [`mmio`](crate::mmio). `RCNT` is a
+//! [`LinkPortControl`].
+//!
+//! * [`normal`]: one 8-bit or 32-bit value swapped between two devices.
+//! * [`multiplayer`]: 2 to 4 GBAs each share a 16-bit value every transfer.
+//! * [`uart`]: a plain serial line, with a buffered driver.
+//! * [`gpio`]: the four link port pins as plain digital inputs and outputs.
+//! * [`joybus`]: the GBA as a device for a GameCube (or other JOY Bus master).
+
+pub mod gpio;
+pub mod joybus;
+#[cfg(feature = "on_gba")]
+pub mod multiboot;
+pub mod multiplayer;
+pub mod normal;
+pub mod uart;
+
+use crate::macros::{
+  pub_const_fn_new_zeroed, register_enum_field, register_int_field,
+  u16_bool_field,
+};
+
+/// Which set of modes the link port uses, see [`LinkPortControl`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum PortMode {
+  /// The serial modes, picked with `SIOCNT`.
+  #[default]
+  Sio = 0 << 14,
+  /// The same as `Sio` (bit 14 doesn't matter when bit 15 is clear).
+  SioAlt = 1 << 14,
+  /// General purpose pins, see [`gpio`].
+  Gpio = 2 << 14,
+  /// JOY Bus mode, see [`joybus`].
+  JoyBus = 3 << 14,
+}
+
+/// Link port mode selection, and pin control for general purpose mode.
+///
+/// * `pin_levels`: The level of each pin (bit 0 is SC, then SD, SI, and SO).
+///   Reading gives the level on the pin, writing sets the level of output pins.
+/// * `pin_directions`: Which pins are outputs (in the same bit order).
+/// * `si_irq`: Sends the serial interrupt when the SI pin goes from high to
+///   low.
+/// * `mode`: The [`PortMode`].
+///
+/// All fields other than `mode` are only used in general purpose mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct LinkPortControl(u16);
+impl LinkPortControl {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(0 - 3, pin_levels, with_pin_levels, PIN_LEVELS_MASK);
+  register_int_field!(
+    4 - 7,
+    pin_directions,
+    with_pin_directions,
+    PIN_DIRECTIONS_MASK
+  );
+  u16_bool_field!(8, si_irq, with_si_irq);
+  register_enum_field!(
+    14 - 15: PortMode [Sio, SioAlt, Gpio, JoyBus],
+    mode, with_mode, MODE_MASK
+  );
+
+  /// Unwrap this value into its raw `u16` form.
+  #[inline]
+  #[must_use]
+  pub const fn to_u16(self) -> u16 {
+    self.0
+  }
+}
+
+/// The baud rate for multi-player mode (and UART mode).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum BaudRate {
+  #[default]
+  _9600 = 0,
+  _38400 = 1,
+  _57600 = 2,
+  _115200 = 3,
+}
+impl BaudRate {
+  /// The rate in bits per second.
+  #[inline]
+  #[must_use]
+  pub const fn bits_per_second(self) -> u32 {
+    match self {
+      Self::_9600 => 9600,
+      Self::_38400 => 38400,
+      Self::_57600 => 57600,
+      Self::_115200 => 115200,
+    }
+  }
+}
+
+/// A serial port wait went on for too long.
+///
+/// With a link cable that isn't plugged in (or an other side that isn't
+/// running), the hardware would otherwise just wait forever.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SioTimeout;
+
+/// Calls `done` until it gives `true`, up to `loops` times.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub(crate) fn spin_until(
+  loops: u32, mut done: impl FnMut() -> bool,
+) -> Result<(), SioTimeout> {
+  for _ in 0..loops {
+    if done() {
+      return Ok(());
+    }
+  }
+  Err(SioTimeout)
+}
@@ -0,0 +1,246 @@
+//! Sending a program to other GBAs over the link cable ("multiboot").
+//!
+//! A GBA that's turned on with no cartridge (or with Start + Select held)
+//! waits for a program to be sent over the link cable. The program goes into
+//! EWRAM, so it can be at most 256 KiB (header included), and it must be
+//! built to run from `0x0200_0000` (see the crate's linker scripts).
+//!
+//! Sending works in two parts:
+//! 1. A handshake with the clients, done here in multi-player mode. This finds
+//!    the clients, sends them the program's header, and swaps the values needed
+//!    for the second part.
+//! 2. The [`MultiBoot`](crate::bios::MultiBoot) BIOS function, which sends the
+//!    rest of the program.
+//!
+//! [`send`] does both. The BIOS function blocks until it's done and can't
+//! report progress, so the `progress` callback of [`send_with_progress`] is
+//! only called during the handshake.
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # use gba::sio::multiboot;
+//! # static PAYLOAD: Align4<[u8; 0x200]> = Align4([0; 0x200]);
+//! match multiboot::send(&PAYLOAD.0, ClientMask::new().with_client1(true)) {
+//!   Ok(()) => { /* the client is running the program now */ }
+//!   Err(MbError::ClientsMissing(missing)) => { /* `missing` didn't answer */ }
+//!   Err(_) => { /* some other problem */ }
+//! }
+//! ```
+
+use super::{multiplayer::MultiplayerSession, BaudRate};
+use crate::{
+  bios::{multi_boot, ClientMask, MbError, MbMode, MultiBootParam},
+  timers::{busy_wait_cycles, CPU_CYCLES_PER_SECOND},
+};
+
+/// The palette setting sent in the handshake if you don't pick one: color 0,
+/// moving right, at the slowest speed.
+///
+/// The value is `0x81 + color * 0x10 + direction * 8 + speed * 2`, with color
+/// `0..=6`, direction `0..=1`, and speed `0..=3`, or `0xF1 + color * 2` for a
+/// fixed palette.
+pub const DEFAULT_PALETTE_DATA: u8 = 0x81;
+
+/// How many times [`send_with_progress`] looks for clients, 1/16th of a
+/// second apart, before giving up (so about 2 seconds).
+const SEARCH_ROUNDS: u32 = 32;
+
+/// What [`send_with_progress`] is doing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MultibootProgress {
+  /// Looking for the clients.
+  FindingClients,
+  /// Sending the header, with this many of its 96 halfwords sent so far.
+  Header(u8),
+  /// Waiting for the clients to be ready for the program.
+  Palette,
+  /// The handshake is done, and the BIOS is about to send the program.
+  Sending,
+}
+
+/// Sends `rom_image` to `clients`, with the default palette and no progress
+/// reports.
+///
+/// See [`send_with_progress`].
+#[inline]
+pub fn send(rom_image: &[u8], clients: ClientMask) -> Result<(), MbError> {
+  send_with_progress(rom_image, clients, DEFAULT_PALETTE_DATA, |_| ())
+}
+
+/// Sends `rom_image` to `clients` in multi-player mode.
+///
+/// * `rom_image` is the whole program, header included, aligned to 4.
+/// * `clients` are the clients that must all get the program.
+/// * `palette_data` is the boot logo palette setting (see
+///   [`DEFAULT_PALETTE_DATA`]).
+/// * `progress` is called at each step of the handshake.
+///
+/// Two bytes of the header are fixed while it's sent, so the program doesn't
+/// have to have them right: the fixed value `0x96` at `0xB2`, and the header
+/// checksum at `0xBD`. The rest of the header (the entry point and the logo)
+/// must already be correct.
+///
+/// ## Failure
+/// * [`MbError::BadImage`] if `rom_image` is the wrong size or alignment (see
+///   [`MultiBootParam::new`]), or `clients` is empty.
+/// * [`MbError::NoClients`] if the link never gets ready at all.
+/// * [`MbError::NotParent`] if this GBA isn't the multi-player parent.
+/// * [`MbError::ClientsMissing`] with the clients that didn't answer, or
+///   answered wrong.
+/// * [`MbError::LinkError`] if a transfer fails partway through.
+/// * [`MbError::Failed`] if the BIOS function fails.
+#[inline]
+pub fn send_with_progress(
+  rom_image: &[u8], clients: ClientMask, palette_data: u8,
+  mut progress: impl FnMut(MultibootProgress),
+) -> Result<(), MbError> {
+  let mut param = MultiBootParam::new(rom_image)?;
+  if clients.is_empty() {
+    return Err(MbError::BadImage);
+  }
+  let mask = u16::from(clients.to_u8());
+
+  progress(MultibootProgress::FindingClients);
+  let mut link = find_clients(clients)?;
+
+  // Check in with the clients, then send the header.
+  link.exchange_all(0x6100 | mask, |n, reply| reply == 0x7200 | (1 << n))?;
+  let header = fixed_header(rom_image);
+  for (i, halfword) in header.chunks_exact(2).enumerate() {
+    let word = u16::from_le_bytes([halfword[0], halfword[1]]);
+    // The high byte of each reply counts down, only the low byte is checked.
+    link.exchange_all(word, |n, reply| reply & 0xFF == 1 << n)?;
+    progress(MultibootProgress::Header(i as u8 + 1));
+  }
+  link.exchange_all(0x6200, |n, reply| reply == 1 << n)?;
+  link.exchange_all(0x6200 | mask, |n, reply| reply == 0x7200 | (1 << n))?;
+
+  // Send the palette until every client answers with its random byte.
+  progress(MultibootProgress::Palette);
+  let palette = 0x6300 | u16::from(palette_data);
+  let mut client_data = [0xFF_u8; 3];
+  let mut rounds = 0;
+  loop {
+    let replies = link.exchange(palette)?;
+    let mut waiting = ClientMask::new();
+    for (n, reply) in replies.iter().enumerate().skip(1) {
+      if !clients.contains(n) {
+        continue;
+      }
+      match *reply {
+        Some(reply) if reply & 0xFF00 == 0x7300 => {
+          client_data[n - 1] = reply as u8;
+        }
+        Some(reply) if reply == 0x7200 | (1 << n) => {
+          waiting = ClientMask::from_u8(waiting.to_u8() | (1 << n));
+        }
+        _ => return Err(MbError::ClientsMissing(ClientMask::from_u8(1 << n))),
+      }
+    }
+    if waiting.is_empty() {
+      break;
+    }
+    rounds += 1;
+    if rounds > SEARCH_ROUNDS {
+      return Err(MbError::ClientsMissing(waiting));
+    }
+    busy_wait_cycles(CPU_CYCLES_PER_SECOND / 16);
+  }
+
+  // The final handshake value.
+  let handshake =
+    client_data.iter().fold(0x11_u8, |sum, &data| sum.wrapping_add(data));
+  link.exchange_all(0x6400 | u16::from(handshake), |_, reply| {
+    reply & 0xFF00 == 0x7300
+  })?;
+
+  param.handshake_data = handshake;
+  param.client_data = client_data;
+  param.palette_data = palette_data;
+  param.client_bit = clients;
+  progress(MultibootProgress::Sending);
+  busy_wait_cycles(CPU_CYCLES_PER_SECOND / 16);
+  multi_boot(&mut param, MbMode::MultiPlay)
+}
+
+/// The multi-player link, and the clients being sent to.
+struct Link {
+  session: MultiplayerSession,
+  clients: ClientMask,
+}
+impl Link {
+  /// Does one exchange as the parent.
+  fn exchange(&mut self, word: u16) -> Result<[Option<u16>; 4], MbError> {
+    self.session.exchange(word).map_err(|_| MbError::LinkError)
+  }
+
+  /// Does one exchange, and checks every client's reply with `ok`.
+  fn exchange_all(
+    &mut self, word: u16, ok: impl Fn(usize, u16) -> bool,
+  ) -> Result<(), MbError> {
+    let replies = self.exchange(word)?;
+    let mut wrong = 0;
+    for (n, reply) in replies.iter().enumerate().skip(1) {
+      if !self.clients.contains(n) {
+        continue;
+      }
+      match *reply {
+        Some(reply) if ok(n, reply) => (),
+        _ => wrong |= 1 << n,
+      }
+    }
+    if wrong == 0 {
+      Ok(())
+    } else {
+      Err(MbError::ClientsMissing(ClientMask::from_u8(wrong)))
+    }
+  }
+}
+
+/// Sets up multi-player mode and sends `0x6200` until all of `clients` answer
+/// with `0x720n` (where `n` is the client's bit).
+fn find_clients(clients: ClientMask) -> Result<Link, MbError> {
+  let mut session = None;
+  let mut found = 0;
+  for _ in 0..SEARCH_ROUNDS {
+    if session.is_none() {
+      session = MultiplayerSession::establish(BaudRate::_115200).ok();
+    }
+    if let Some(link) = session.as_mut() {
+      if !link.is_parent() {
+        return Err(MbError::NotParent);
+      }
+      for _ in 0..15 {
+        let Ok(replies) = link.exchange(0x6200) else {
+          break;
+        };
+        for (n, reply) in replies.iter().enumerate().skip(1) {
+          if *reply == Some(0x7200 | (1 << n)) {
+            found |= 1 << n;
+          }
+        }
+        if found & clients.to_u8() == clients.to_u8() {
+          return Ok(Link { session: *link, clients });
+        }
+      }
+    }
+    busy_wait_cycles(CPU_CYCLES_PER_SECOND / 16);
+  }
+  match session {
+    None => Err(MbError::NoClients),
+    Some(_) => Err(MbError::ClientsMissing(ClientMask::from_u8(
+      clients.to_u8() & !found,
+    ))),
+  }
+}
+
+/// The program's header, with the fixed byte and the checksum set.
+fn fixed_header(rom_image: &[u8]) -> [u8; MultiBootParam::HEADER_LEN] {
+  let mut header = [0; MultiBootParam::HEADER_LEN];
+  header.copy_from_slice(&rom_image[..MultiBootParam::HEADER_LEN]);
+  header[0xB2] = 0x96;
+  let sum =
+    header[0xA0..0xBD].iter().fold(0_u8, |sum, &byte| sum.wrapping_add(byte));
+  header[0xBD] = 0_u8.wrapping_sub(sum).wrapping_sub(0x19);
+  header
+}
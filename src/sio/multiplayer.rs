@@ -0,0 +1,280 @@
+//! Multi-player mode: 2 to 4 GBAs linked together with link cables.
+//!
+//! In multi-player mode one GBA is the *parent* (player 0), and the others are
+//! *children* (players 1 to 3). Which GBA is which depends on how the cables
+//! are plugged in: the parent is the one with the small (purple) plug of the
+//! cable linked to it. Each GBA can tell if it's the parent from the SI
+//! terminal, and the SD terminal shows if all the linked GBAs are in
+//! multi-player mode and ready.
+//!
+//! Each transfer works like this:
+//! 1. Every GBA puts the 16-bit value it wants to share into
+//!    [`SIOMLT_SEND`](crate::mmio::SIOMLT_SEND).
+//! 2. The parent sets the start bit of `SIOCNT`. Children can't start a
+//!    transfer, they just take part in the next one the parent starts.
+//! 3. The value from each player ends up in the `SIOMULTI` register for that
+//!    player, on every GBA (including the GBA's own value).
+//! 4. The serial interrupt is sent on every GBA once the transfer is done.
+//!
+//! A player that isn't connected gives `0xFFFF` (which is also what the
+//! `SIOMULTI` registers are reset to when a transfer starts), so `0xFFFF`
+//! should never be used as real data. [`MultiplayerSession`] turns it into
+//! `None`.
+//!
+//! A transfer of `n` players takes about `n * 18` bits of time, so at 115200
+//! baud even four players only takes about 0.6 ms. Doing one transfer per
+//! frame, at the same point of the parent's frame, is the usual setup.
+
+use super::BaudRate;
+#[cfg(feature = "on_gba")]
+use super::LinkPortControl;
+use crate::macros::{register_enum_field, register_int_field, u16_bool_field};
+
+/// Serial control in multi-player mode.
+///
+/// * `baud_rate`: The speed of each transfer, this must be the same on all
+///   GBAs.
+/// * `si_child`: The SI terminal (read only). `false` on the parent, `true` on
+///   the children.
+/// * `sd_ready`: The SD terminal (read only). `true` if all linked GBAs are in
+///   multi-player mode, `false` if something isn't connected properly.
+/// * `player_id`: This GBA's player number (read only), 0 for the parent, or 1
+///   to 3. This is only set after the first transfer.
+/// * `error`: The last transfer had an error (read only).
+/// * `start`: The parent writes `true` to start a transfer. When read this is
+///   `true` while a transfer is busy (on the parent and the children).
+/// * `irq_enabled`: Sends the serial interrupt when each transfer finishes.
+///
+/// Unlike most control types, [`new`](Self::new) isn't all zero bits, since
+/// bits 12-13 have to be `10` to pick multi-player mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct MultiplayerControl(u16);
+impl MultiplayerControl {
+  /// The mode bits (12-13) that pick multi-player mode.
+  const MODE: u16 = 0b10 << 12;
+
+  /// A multi-player mode control at 9600 baud, with all other bits off.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self(Self::MODE)
+  }
+  register_enum_field!(
+    0 - 1: BaudRate [_9600, _38400, _57600, _115200],
+    baud_rate, with_baud_rate, BAUD_RATE_MASK
+  );
+  u16_bool_field!(2, si_child, with_si_child);
+  u16_bool_field!(3, sd_ready, with_sd_ready);
+  register_int_field!(4 - 5, player_id, with_player_id, PLAYER_ID_MASK);
+  u16_bool_field!(6, error, with_error);
+  u16_bool_field!(7, start, with_start);
+  u16_bool_field!(14, irq_enabled, with_irq_enabled);
+}
+impl Default for MultiplayerControl {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Why a [`MultiplayerSession`] action failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MultiplayerError {
+  /// The SD terminal is low: another GBA isn't connected, or isn't in
+  /// multi-player mode yet.
+  NotReady,
+  /// Only the parent can start a transfer.
+  NotParent,
+  /// No transfer finished in time (on a child this usually means the parent
+  /// has stopped doing transfers).
+  Timeout,
+  /// The error bit was set after the transfer.
+  Transfer,
+}
+
+/// Turns the "no data" value of `0xFFFF` into `None`.
+#[inline]
+#[must_use]
+const fn data_from(word: u16) -> Option<u16> {
+  if word == 0xFFFF {
+    None
+  } else {
+    Some(word)
+  }
+}
+
+/// How many scanlines (about two frames) [`MultiplayerSession::exchange`]
+/// waits for a transfer before giving up.
+#[cfg(feature = "on_gba")]
+const EXCHANGE_TIMEOUT_LINES: u32 = 2 * 228;
+
+/// A link cable session in multi-player mode.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// let mut session = loop {
+///   if let Ok(session) = MultiplayerSession::establish(BaudRate::_115200) {
+///     break session;
+///   }
+///   spin_until_vblank();
+/// };
+/// loop {
+///   spin_until_vblank();
+///   if let Ok(words) = session.exchange(KEYINPUT.read().to_u16()) {
+///     // `words[n]` is the value from player `n`.
+///   }
+/// }
+/// ```
+#[cfg(feature = "on_gba")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MultiplayerSession {
+  control: MultiplayerControl,
+  parent: bool,
+  player_id: Option<u8>,
+}
+#[cfg(feature = "on_gba")]
+impl MultiplayerSession {
+  /// Puts the serial port into multi-player mode and checks the link.
+  ///
+  /// This clears [`RCNT`](crate::mmio::RCNT) (serial mode) and sets
+  /// [`SIOCNT_MULTI`](crate::mmio::SIOCNT_MULTI) with the baud rate given and
+  /// `irq_enabled` on. The serial interrupt is only *sent* if it's also on in
+  /// [`IE`](crate::mmio::IE), but the bit is needed for
+  /// [`IF`](crate::mmio::IF) to show when a transfer is done.
+  ///
+  /// ## Failure
+  /// * [`MultiplayerError::NotReady`] if the SD terminal is low. The other GBAs
+  ///   might just not have called `establish` yet, so it's fine to try again
+  ///   each frame until this works.
+  #[inline]
+  pub fn establish(baud: BaudRate) -> Result<Self, MultiplayerError> {
+    use crate::{
+      interrupts::IrqBits,
+      mmio::{IF, RCNT, SIOCNT_MULTI},
+    };
+    let control =
+      MultiplayerControl::new().with_baud_rate(baud).with_irq_enabled(true);
+    RCNT.write(LinkPortControl::new());
+    SIOCNT_MULTI.write(control);
+    IF.write(IrqBits::new().with_serial(true));
+    let status = SIOCNT_MULTI.read();
+    if !status.sd_ready() {
+      return Err(MultiplayerError::NotReady);
+    }
+    Ok(Self { control, parent: !status.si_child(), player_id: None })
+  }
+
+  /// If this GBA is the parent (player 0).
+  #[inline]
+  #[must_use]
+  pub const fn is_parent(&self) -> bool {
+    self.parent
+  }
+
+  /// This GBA's player number, once a transfer has finished.
+  #[inline]
+  #[must_use]
+  pub const fn player_id(&self) -> Option<u8> {
+    self.player_id
+  }
+
+  /// Sets the value this GBA gives in the next transfer.
+  ///
+  /// Don't send `0xFFFF`, since the other players will see it as "no data".
+  #[inline]
+  pub fn send(&self, word: u16) {
+    crate::mmio::SIOMLT_SEND.write(word);
+  }
+
+  /// Starts a transfer, using what each GBA last [sent](Self::send).
+  ///
+  /// ## Failure
+  /// * [`MultiplayerError::NotParent`] on a child.
+  /// * [`MultiplayerError::NotReady`] if the SD terminal is low, or if a
+  ///   transfer is already busy.
+  #[inline]
+  pub fn start(&self) -> Result<(), MultiplayerError> {
+    use crate::mmio::SIOCNT_MULTI;
+    if !self.parent {
+      return Err(MultiplayerError::NotParent);
+    }
+    let status = SIOCNT_MULTI.read();
+    if !status.sd_ready() || status.start() {
+      return Err(MultiplayerError::NotReady);
+    }
+    SIOCNT_MULTI.write(self.control.with_start(true));
+    Ok(())
+  }
+
+  /// Reads the values from the last transfer, by player number.
+  ///
+  /// Players that aren't connected are `None`. This also records this GBA's
+  /// [player number](Self::player_id). For an interrupt driven setup, turn on
+  /// the serial interrupt in [`IE`](crate::mmio::IE) and call this from the
+  /// interrupt handler.
+  ///
+  /// ## Failure
+  /// * [`MultiplayerError::Transfer`] if the error bit is set.
+  #[inline]
+  pub fn received(&mut self) -> Result<[Option<u16>; 4], MultiplayerError> {
+    use crate::mmio::{
+      SIOCNT_MULTI, SIOMULTI0, SIOMULTI1, SIOMULTI2, SIOMULTI3,
+    };
+    let status = SIOCNT_MULTI.read();
+    if status.error() {
+      return Err(MultiplayerError::Transfer);
+    }
+    self.player_id = Some(status.player_id() as u8);
+    Ok([
+      data_from(SIOMULTI0.read()),
+      data_from(SIOMULTI1.read()),
+      data_from(SIOMULTI2.read()),
+      data_from(SIOMULTI3.read()),
+    ])
+  }
+
+  /// Does one full transfer: sends `word`, waits for the transfer to finish,
+  /// and gives the [received](Self::received) values.
+  ///
+  /// On the parent this starts the transfer. On a child this waits for the
+  /// parent to start one (or uses one that already finished since the last
+  /// exchange). Either way it waits by polling the serial bit of
+  /// [`IF`](crate::mmio::IF), so the serial interrupt must *not* be on in
+  /// [`IE`](crate::mmio::IE) (the interrupt handler would clear the bit
+  /// first).
+  ///
+  /// ## Failure
+  /// * Any error from [`start`](Self::start) (on the parent) or `received`.
+  /// * [`MultiplayerError::Timeout`] if no transfer finishes within about two
+  ///   frames.
+  #[inline]
+  pub fn exchange(
+    &mut self, word: u16,
+  ) -> Result<[Option<u16>; 4], MultiplayerError> {
+    use crate::{
+      interrupts::IrqBits,
+      mmio::{IF, VCOUNT},
+    };
+    let serial = IrqBits::new().with_serial(true);
+    self.send(word);
+    if self.parent {
+      IF.write(serial);
+      self.start()?;
+    }
+    let mut line = VCOUNT.read();
+    let mut lines_waited = 0;
+    while !IF.read().serial() {
+      let now = VCOUNT.read();
+      if now != line {
+        line = now;
+        lines_waited += 1;
+        if lines_waited > EXCHANGE_TIMEOUT_LINES {
+          return Err(MultiplayerError::Timeout);
+        }
+      }
+    }
+    IF.write(serial);
+    self.received()
+  }
+}
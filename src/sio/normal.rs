@@ -0,0 +1,254 @@
+//! Normal mode: one device clocks a value out while clocking a value in.
+//!
+//! Normal mode is the simplest serial mode. Two devices are linked, and one of
+//! them (the *master*) drives the shift clock on SC. Each clock pulse shifts
+//! one bit out on SO and one bit in on SI, so after 8 or 32 pulses the two
+//! sides have swapped values. The other side (the *slave*) uses the master's
+//! clock, and has to have its own value ready, with its start bit set, before
+//! the master begins.
+//!
+//! There's no hardware handshake, so the usual protocol (from GBATEK) uses the
+//! SO level between transfers as a "ready" flag:
+//! * The slave holds SO high while it's busy, and low once it's ready for the
+//!   next transfer.
+//! * The master waits until it sees SI (the slave's SO) low, then starts the
+//!   transfer.
+//!
+//! [`NormalMode::transfer_u8`] and [`NormalMode::transfer_u32`] do the master
+//! side of that, and [`NormalMode::respond`] does the slave side. Every wait
+//! gives up with [`SioTimeout`] after the [configured number of
+//! loops](NormalMode::with_timeout_loops).
+
+#[cfg(feature = "on_gba")]
+use super::LinkPortControl;
+use super::SioTimeout;
+use crate::macros::{
+  pub_const_fn_new_zeroed, register_enum_field, u16_bool_field,
+};
+
+/// The speed of the internal shift clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum ShiftSpeed {
+  #[default]
+  _256KHz = 0,
+  _2MHz = 1 << 1,
+}
+
+/// Serial control in normal mode.
+///
+/// * `internal_clock`: This side is the master, and drives the shift clock.
+///   Otherwise the clock comes from the other side.
+/// * `speed`: The internal shift clock speed (only used by the master).
+/// * `si_high`: The level of the SI pin (read only).
+/// * `so_idle_high`: The level of the SO pin between transfers.
+/// * `start`: Write `true` to start (the master) or be ready for (the slave) a
+///   transfer. When read this is `true` until the transfer is done.
+/// * `transfer_32bit`: Transfer 32 bits through
+///   [`SIODATA32`](crate::mmio::SIODATA32), otherwise 8 bits through
+///   [`SIODATA8`](crate::mmio::SIODATA8).
+/// * `irq_enabled`: Sends the serial interrupt when a transfer is done.
+///
+/// Normal mode is bits 12-13 being `00` or `01`, so a zeroed value is already
+/// in normal mode (bit 12 is `transfer_32bit`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct NormalControl(u16);
+impl NormalControl {
+  pub_const_fn_new_zeroed!();
+  u16_bool_field!(0, internal_clock, with_internal_clock);
+  register_enum_field!(
+    1 - 1: ShiftSpeed [_256KHz, _2MHz],
+    speed, with_speed, SPEED_MASK
+  );
+  u16_bool_field!(2, si_high, with_si_high);
+  u16_bool_field!(3, so_idle_high, with_so_idle_high);
+  u16_bool_field!(7, start, with_start);
+  u16_bool_field!(12, transfer_32bit, with_transfer_32bit);
+  u16_bool_field!(14, irq_enabled, with_irq_enabled);
+}
+
+/// Transfers in normal mode, with a timeout on every wait.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// let link = NormalMode::new().with_speed(ShiftSpeed::_2MHz);
+/// match link.transfer_u32(0x1234_5678) {
+///   Ok(reply) => { /* the slave's value */ }
+///   Err(SioTimeout) => { /* no slave, or it wasn't ready */ }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct NormalMode {
+  speed: ShiftSpeed,
+  timeout_loops: u32,
+}
+impl NormalMode {
+  /// The default number of wait loops before giving up.
+  ///
+  /// Each loop is a register read and a check, so this is on the order of a
+  /// few frames.
+  pub const DEFAULT_TIMEOUT_LOOPS: u32 = 0x1_0000;
+
+  /// Transfers at 256KHz, with the default timeout.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self {
+      speed: ShiftSpeed::_256KHz,
+      timeout_loops: Self::DEFAULT_TIMEOUT_LOOPS,
+    }
+  }
+
+  /// Sets the shift clock speed used when this side is the master.
+  ///
+  /// The 2MHz speed only works reliably with short cables, and with a slave
+  /// that's a GBA (or something else that keeps up).
+  #[inline]
+  #[must_use]
+  pub const fn with_speed(self, speed: ShiftSpeed) -> Self {
+    Self { speed, ..self }
+  }
+
+  /// Sets how many loops each wait can go for before giving up.
+  #[inline]
+  #[must_use]
+  pub const fn with_timeout_loops(self, timeout_loops: u32) -> Self {
+    Self { timeout_loops, ..self }
+  }
+
+  /// The shift clock speed.
+  #[inline]
+  #[must_use]
+  pub const fn speed(&self) -> ShiftSpeed {
+    self.speed
+  }
+
+  /// The number of loops each wait can go for.
+  #[inline]
+  #[must_use]
+  pub const fn timeout_loops(&self) -> u32 {
+    self.timeout_loops
+  }
+
+  /// The control value for the master side.
+  #[inline]
+  #[must_use]
+  const fn master_control(&self, transfer_32bit: bool) -> NormalControl {
+    NormalControl::new()
+      .with_internal_clock(true)
+      .with_speed(self.speed)
+      .with_so_idle_high(true)
+      .with_transfer_32bit(transfer_32bit)
+  }
+}
+impl Default for NormalMode {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+#[cfg(feature = "on_gba")]
+impl NormalMode {
+  /// Does the master side of a transfer, using `control` for the mode.
+  fn master_transfer(
+    &self, control: NormalControl, write: impl FnOnce(),
+  ) -> Result<(), SioTimeout> {
+    use super::spin_until;
+    use crate::mmio::{RCNT, SIOCNT_NORMAL};
+    RCNT.write(LinkPortControl::new());
+    SIOCNT_NORMAL.write(control);
+    write();
+    spin_until(self.timeout_loops, || !SIOCNT_NORMAL.read().si_high())?;
+    SIOCNT_NORMAL.write(control.with_start(true));
+    spin_until(self.timeout_loops, || !SIOCNT_NORMAL.read().start())
+  }
+
+  /// Sends a byte as the master, and gives the byte the slave sent.
+  ///
+  /// This clears [`RCNT`](crate::mmio::RCNT) (serial mode) and
+  /// [`SIOCNT_NORMAL`](crate::mmio::SIOCNT_NORMAL) to 8-bit master mode, puts
+  /// `out` in [`SIODATA8`](crate::mmio::SIODATA8), waits for the slave to be
+  /// ready (SI low), then starts the transfer and waits for it to finish.
+  ///
+  /// ## Failure
+  /// * [`SioTimeout`] if the slave is never ready, or the transfer never
+  ///   finishes.
+  #[inline]
+  pub fn transfer_u8(&self, out: u8) -> Result<u8, SioTimeout> {
+    use crate::mmio::SIODATA8;
+    self.master_transfer(self.master_control(false), || SIODATA8.write(out))?;
+    Ok(SIODATA8.read())
+  }
+
+  /// Sends a `u32` as the master, and gives the `u32` the slave sent.
+  ///
+  /// This is the same as [`transfer_u8`](Self::transfer_u8), except in 32-bit
+  /// mode, through [`SIODATA32`](crate::mmio::SIODATA32).
+  ///
+  /// ## Failure
+  /// * [`SioTimeout`] if the slave is never ready, or the transfer never
+  ///   finishes.
+  #[inline]
+  pub fn transfer_u32(&self, out: u32) -> Result<u32, SioTimeout> {
+    use crate::mmio::SIODATA32;
+    self.master_transfer(self.master_control(true), || SIODATA32.write(out))?;
+    Ok(SIODATA32.read())
+  }
+
+  /// Gets ready for the master's next 32-bit transfer, sending `next_out`.
+  ///
+  /// This puts `next_out` in [`SIODATA32`](crate::mmio::SIODATA32), then sets
+  /// the start bit with an external clock, and drops SO low to tell the master
+  /// this side is ready. The serial interrupt is sent when the transfer is
+  /// done (if it's on in [`IE`](crate::mmio::IE)), and then
+  /// [`take_response`](Self::take_response) gives the master's value.
+  #[inline]
+  pub fn prepare_response(&self, next_out: u32) {
+    use crate::mmio::{RCNT, SIOCNT_NORMAL, SIODATA32};
+    let control = NormalControl::new()
+      .with_transfer_32bit(true)
+      .with_so_idle_high(true)
+      .with_irq_enabled(true);
+    RCNT.write(LinkPortControl::new());
+    SIOCNT_NORMAL.write(control);
+    SIODATA32.write(next_out);
+    SIOCNT_NORMAL.write(control.with_start(true).with_so_idle_high(false));
+  }
+
+  /// After a transfer set up with [`prepare_response`](Self::prepare_response)
+  /// is done, sets SO high (busy) and gives the master's value.
+  #[inline]
+  #[must_use]
+  pub fn take_response(&self) -> u32 {
+    use crate::mmio::{SIOCNT_NORMAL, SIODATA32};
+    SIOCNT_NORMAL.write(
+      NormalControl::new()
+        .with_transfer_32bit(true)
+        .with_so_idle_high(true)
+        .with_irq_enabled(true),
+    );
+    SIODATA32.read()
+  }
+
+  /// Does the slave side of one 32-bit transfer: sends `next_out`, and gives
+  /// the master's value.
+  ///
+  /// This is [`prepare_response`](Self::prepare_response), then polling the
+  /// start bit until the master has done the transfer, then
+  /// [`take_response`](Self::take_response).
+  ///
+  /// ## Failure
+  /// * [`SioTimeout`] if the master doesn't do a transfer in time. The start
+  ///   bit is cleared and SO is set high again, so a late transfer from the
+  ///   master won't be half received.
+  #[inline]
+  pub fn respond(&self, next_out: u32) -> Result<u32, SioTimeout> {
+    use super::spin_until;
+    use crate::mmio::SIOCNT_NORMAL;
+    self.prepare_response(next_out);
+    let done = spin_until(self.timeout_loops, || !SIOCNT_NORMAL.read().start());
+    let received = self.take_response();
+    done.map(|()| received)
+  }
+}
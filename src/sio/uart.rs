@@ -0,0 +1,342 @@
+//! UART mode: a plain serial line, for talking to a PC or other device.
+//!
+//! In UART mode the link port sends and receives bytes one bit at a time at a
+//! set [`BaudRate`], with a start bit, 7 or 8 data bits, an optional parity
+//! bit, and a stop bit. The SI pin receives and the SO pin sends. The SC and
+//! SD pins can be used as CTS (input) and RTS (output) for hardware flow
+//! control.
+//!
+//! [`UartControl`] is the `SIOCNT` setting for this mode. On top of that,
+//! [`start_uart`] runs a small driver: bytes to send and bytes received are
+//! kept in ring buffers of [`UART_BUFFER_LEN`] bytes each, and [`uart_irq`]
+//! moves bytes between those and the hardware. The [`Uart`] handle can then be
+//! used from anywhere, including with `write!`:
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! use core::fmt::Write;
+//! RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+//! set_handler(Interrupt::Serial, uart_irq);
+//! start_uart(UartConfig::new(BaudRate::_115200));
+//! writeln!(Uart, "hello from the GBA").ok();
+//! if let Some(byte) = Uart.read_byte() {
+//!   Uart.write_byte(byte);
+//! }
+//! ```
+//!
+//! Every `Uart` call also services the hardware itself, so the driver keeps
+//! working (slower) without the interrupt, even with interrupts off. That
+//! makes it usable as a log sink from a panic handler:
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! use core::fmt::Write;
+//! #[panic_handler]
+//! fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+//!   writeln!(Uart, "{info}").ok();
+//!   loop {}
+//! }
+//! ```
+//!
+//! If the UART was never started, `Uart` writes are just dropped.
+
+use super::BaudRate;
+#[cfg(feature = "on_gba")]
+use super::LinkPortControl;
+#[cfg(feature = "on_gba")]
+use crate::collections::RingDeque;
+use crate::macros::{register_enum_field, u16_bool_field};
+
+/// The parity bit setting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum Parity {
+  #[default]
+  Even = 0,
+  Odd = 1 << 3,
+}
+
+/// Serial control in UART mode.
+///
+/// * `baud_rate`: The speed of the line.
+/// * `cts_enabled`: Only send while the SC pin (CTS) is low. The GBA also
+///   drives the SD pin (RTS) low while it's ready to receive.
+/// * `parity`: Even or odd parity (only used if `parity_enabled` is set).
+/// * `send_full`: The send data register (or FIFO) is full (read only).
+/// * `receive_empty`: There's no received data to read (read only).
+/// * `error`: A byte was received with a framing or parity error, or the
+///   receive FIFO overflowed (read only).
+/// * `eight_bits`: Use 8 data bits (otherwise it's 7).
+/// * `fifo_enabled`: Use the 4 byte send and receive FIFOs, rather than just
+///   one byte each way.
+/// * `parity_enabled`: Send and check a parity bit.
+/// * `send_enabled`: Turns on sending.
+/// * `receive_enabled`: Turns on receiving.
+/// * `irq_enabled`: Sends the serial interrupt when a byte is sent or received,
+///   or on an error.
+///
+/// The data goes through [`SIODATA8`](crate::mmio::SIODATA8).
+///
+/// Like [`MultiplayerControl`](super::multiplayer::MultiplayerControl),
+/// [`new`](Self::new) isn't all zero bits, since bits 12-13 have to be `11` to
+/// pick UART mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct UartControl(u16);
+impl UartControl {
+  /// The mode bits (12-13) that pick UART mode.
+  const MODE: u16 = 0b11 << 12;
+
+  /// A UART mode control at 9600 baud, with all other bits off.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self(Self::MODE)
+  }
+  register_enum_field!(
+    0 - 1: BaudRate [_9600, _38400, _57600, _115200],
+    baud_rate, with_baud_rate, BAUD_RATE_MASK
+  );
+  u16_bool_field!(2, cts_enabled, with_cts_enabled);
+  register_enum_field!(
+    3 - 3: Parity [Even, Odd],
+    parity, with_parity, PARITY_MASK
+  );
+  u16_bool_field!(4, send_full, with_send_full);
+  u16_bool_field!(5, receive_empty, with_receive_empty);
+  u16_bool_field!(6, error, with_error);
+  u16_bool_field!(7, eight_bits, with_eight_bits);
+  u16_bool_field!(8, fifo_enabled, with_fifo_enabled);
+  u16_bool_field!(9, parity_enabled, with_parity_enabled);
+  u16_bool_field!(10, send_enabled, with_send_enabled);
+  u16_bool_field!(11, receive_enabled, with_receive_enabled);
+  u16_bool_field!(14, irq_enabled, with_irq_enabled);
+
+  /// If the mode bits are set to UART mode.
+  #[inline]
+  #[must_use]
+  pub const fn is_uart_mode(self) -> bool {
+    self.0 & Self::MODE == Self::MODE
+  }
+}
+impl Default for UartControl {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// The settings for [`start_uart`].
+///
+/// The driver always uses 8 data bits, the FIFOs, sending, receiving, and the
+/// serial interrupt, so only the line settings can be picked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct UartConfig {
+  baud: BaudRate,
+  parity: Option<Parity>,
+  flow_control: bool,
+}
+impl UartConfig {
+  /// A config at the baud rate given, with no parity and no flow control.
+  #[inline]
+  #[must_use]
+  pub const fn new(baud: BaudRate) -> Self {
+    Self { baud, parity: None, flow_control: false }
+  }
+
+  /// Sets the parity bit (or `None` for no parity bit).
+  #[inline]
+  #[must_use]
+  pub const fn with_parity(self, parity: Option<Parity>) -> Self {
+    Self { parity, ..self }
+  }
+
+  /// Sets if CTS/RTS flow control (on the SC and SD pins) is used.
+  #[inline]
+  #[must_use]
+  pub const fn with_flow_control(self, flow_control: bool) -> Self {
+    Self { flow_control, ..self }
+  }
+
+  /// The `SIOCNT` setting for this config.
+  #[inline]
+  #[must_use]
+  pub const fn to_control(self) -> UartControl {
+    let control = UartControl::new()
+      .with_baud_rate(self.baud)
+      .with_cts_enabled(self.flow_control)
+      .with_eight_bits(true)
+      .with_fifo_enabled(true)
+      .with_send_enabled(true)
+      .with_receive_enabled(true)
+      .with_irq_enabled(true);
+    match self.parity {
+      Some(parity) => control.with_parity_enabled(true).with_parity(parity),
+      None => control,
+    }
+  }
+}
+
+/// The number of bytes in each of the driver's send and receive buffers.
+pub const UART_BUFFER_LEN: usize = 64;
+
+/// Counts of the problems the driver has seen, see [`Uart::counters`].
+///
+/// The counts wrap around if they get past `u32::MAX`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct UartCounters {
+  /// Received bytes that were dropped because the receive buffer was full.
+  pub overruns: u32,
+  /// Times the hardware error flag was seen: a framing or parity error, or
+  /// bytes lost because the receive FIFO filled up before it was read.
+  pub line_errors: u32,
+}
+
+/// Everything the driver shares with the interrupt handler.
+#[cfg(feature = "on_gba")]
+struct UartState {
+  running: bool,
+  send: RingDeque<u8, UART_BUFFER_LEN>,
+  receive: RingDeque<u8, UART_BUFFER_LEN>,
+  counters: UartCounters,
+}
+
+#[cfg(feature = "on_gba")]
+static UART: crate::interrupts::IrqMutex<UartState> =
+  crate::interrupts::IrqMutex::new(UartState {
+    running: false,
+    send: RingDeque::new(),
+    receive: RingDeque::new(),
+    counters: UartCounters { overruns: 0, line_errors: 0 },
+  });
+
+/// Moves received bytes into the receive buffer, and buffered bytes out to the
+/// hardware for as long as it has room.
+#[cfg(feature = "on_gba")]
+fn service(state: &mut UartState) {
+  use crate::mmio::{SIOCNT_UART, SIODATA8};
+  if !state.running {
+    return;
+  }
+  if SIOCNT_UART.read().error() {
+    state.counters.line_errors = state.counters.line_errors.wrapping_add(1);
+  }
+  while !SIOCNT_UART.read().receive_empty() {
+    if state.receive.push_back(SIODATA8.read()).is_err() {
+      state.counters.overruns = state.counters.overruns.wrapping_add(1);
+    }
+  }
+  while !SIOCNT_UART.read().send_full() {
+    match state.send.pop_front() {
+      Some(byte) => SIODATA8.write(byte),
+      None => break,
+    }
+  }
+}
+
+/// Sets up the serial port in UART mode and starts the driver.
+///
+/// This clears [`RCNT`](crate::mmio::RCNT) (serial mode), empties the driver's
+/// buffers, resets the counters, and sets
+/// [`SIOCNT_UART`](crate::mmio::SIOCNT_UART) from `config` (with the FIFOs
+/// reset). The serial interrupt still has to be set to call [`uart_irq`] for
+/// the driver to run in the background.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn start_uart(config: UartConfig) {
+  use crate::mmio::{RCNT, SIOCNT_UART};
+  let control = config.to_control();
+  UART.with(|state| {
+    RCNT.write(LinkPortControl::new());
+    // Turning the FIFO off and on again resets it.
+    SIOCNT_UART.write(control.with_fifo_enabled(false));
+    SIOCNT_UART.write(control);
+    state.send.clear();
+    state.receive.clear();
+    state.counters = UartCounters::default();
+    state.running = true;
+  });
+}
+
+/// Stops the driver, and turns off sending, receiving, and the interrupt.
+///
+/// Bytes still in the send buffer are dropped.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn stop_uart() {
+  use crate::mmio::SIOCNT_UART;
+  UART.with(|state| {
+    state.running = false;
+    SIOCNT_UART.write(UartControl::new());
+  });
+}
+
+/// The serial interrupt handler for the UART driver.
+///
+/// Set this with [`set_handler`](crate::interrupts::set_handler), or call it
+/// from your own handler when the `serial` bit is set.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub extern "C" fn uart_irq(_: crate::interrupts::IrqBits) {
+  UART.with_in_handler(service);
+}
+
+/// A handle to the UART driver started with [`start_uart`].
+///
+/// There's only one driver, so this is just a name for it, and any number of
+/// them can be made. All methods turn interrupts off while they touch the
+/// driver's buffers.
+#[cfg(feature = "on_gba")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Uart;
+#[cfg(feature = "on_gba")]
+impl Uart {
+  /// Sends a byte, waiting for room in the send buffer if it's full.
+  ///
+  /// The byte is dropped if the driver isn't running. With flow control on,
+  /// this waits for as long as the other side holds CTS high.
+  #[inline]
+  pub fn write_byte(&self, byte: u8) {
+    while !UART.with(|state| {
+      if !state.running {
+        return true;
+      }
+      let pushed = state.send.push_back(byte).is_ok();
+      service(state);
+      pushed
+    }) {}
+  }
+
+  /// Sends all of `bytes`, see [`write_byte`](Self::write_byte).
+  #[inline]
+  pub fn write_bytes(&self, bytes: &[u8]) {
+    for &byte in bytes {
+      self.write_byte(byte);
+    }
+  }
+
+  /// Takes the next received byte, if there is one.
+  #[inline]
+  #[must_use]
+  pub fn read_byte(&self) -> Option<u8> {
+    UART.with(|state| {
+      service(state);
+      state.receive.pop_front()
+    })
+  }
+
+  /// The driver's error counts since it was started.
+  #[inline]
+  #[must_use]
+  pub fn counters(&self) -> UartCounters {
+    UART.with(|state| state.counters)
+  }
+}
+#[cfg(feature = "on_gba")]
+impl core::fmt::Write for Uart {
+  #[inline]
+  fn write_str(&mut self, s: &str) -> core::fmt::Result {
+    self.write_bytes(s.as_bytes());
+    Ok(())
+  }
+}
@@ -0,0 +1,432 @@
+//! Mixing several PCM voices into one stream.
+//!
+//! A [`Mixer`] has a fixed number of voice slots. Each [`Voice`] plays a
+//! signed 8-bit sample at its own rate (for pitch shifting), volume, and
+//! panning, and can loop part of the sample. The mixer implements
+//! [`StreamFill`], so it plugs right into
+//! [`fill_stream`](super::stream::fill_stream).
+//!
+//! Sample positions and rates are 20.12 fixed point: the upper 20 bits are
+//! the sample index, so a sample can be up to about a million samples long,
+//! and a rate of `1 << 12` plays the sample at the stream's own rate.
+//!
+//! ## Performance
+//!
+//! The inner loops are ARM code in IWRAM, and they don't divide. Each voice
+//! costs very roughly 20 to 30 CPU cycles per output sample when the samples
+//! are read from ROM, so 4 voices at 18157 Hz is about 10% to 15% of the CPU.
+//! Those numbers are estimates, so measure your own use with the
+//! [`profile`](crate::profile) module.
+//!
+//! ## Sharing With An Interrupt
+//!
+//! If you fill the stream from an interrupt handler, keep the mixer in an
+//! [`IrqMutex`](crate::interrupts::IrqMutex). Then starting and stopping
+//! voices from the main program can't happen in the middle of a fill.
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # use gba::sound::{mixer::*, stream::*};
+//! static MIXER: IrqMutex<Mixer<4>> = IrqMutex::new(Mixer::new());
+//! static BLIP: [i8; 4] = [0, 64, 0, -64];
+//!
+//! extern "C" fn irq_handler(bits: IrqBits) {
+//!   if bits.vblank() {
+//!     stream_vblank();
+//!     MIXER.with_in_handler(fill_stream);
+//!   }
+//! }
+//!
+//! // In the main program:
+//! MIXER.with(|mixer| mixer.play(0, Voice::new(&BLIP).with_loop(0, 4)));
+//! ```
+
+use super::stream::{StreamFill, MAX_STREAM_SAMPLES};
+
+/// A rate of 1.0 as 20.12 fixed point.
+const RATE_ONE: u32 = 1 << 12;
+
+/// One sample playing in a [`Mixer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Voice {
+  source: &'static [i8],
+  position: u32,
+  rate: u32,
+  volume: u8,
+  pan: u8,
+  end: u32,
+  loop_start: Option<u32>,
+}
+impl Voice {
+  /// A voice that plays all of `source` once, at the stream's rate, full
+  /// volume, and center pan.
+  #[inline]
+  #[must_use]
+  pub const fn new(source: &'static [i8]) -> Self {
+    Self {
+      source,
+      position: 0,
+      rate: RATE_ONE,
+      volume: 64,
+      pan: 32,
+      end: source.len() as u32,
+      loop_start: None,
+    }
+  }
+
+  /// Sets the playback rate, as 20.12 fixed point.
+  ///
+  /// `1 << 12` plays one sample of the source for each output sample, `2 << 12`
+  /// plays an octave higher, and so on.
+  #[inline]
+  #[must_use]
+  pub const fn with_rate(self, rate: u32) -> Self {
+    Self { rate, ..self }
+  }
+
+  /// Sets the rate to play a sample recorded at `source_hz` on a stream
+  /// running at `output_hz`.
+  ///
+  /// This divides once, here, so that the mixing doesn't have to.
+  #[inline]
+  #[must_use]
+  pub const fn with_pitch(self, source_hz: u32, output_hz: u32) -> Self {
+    let rate = ((source_hz as u64) << 12) / output_hz as u64;
+    Self { rate: rate as u32, ..self }
+  }
+
+  /// Sets the volume, 0 to 64 (higher values are clamped to 64).
+  #[inline]
+  #[must_use]
+  pub const fn with_volume(self, volume: u8) -> Self {
+    Self { volume: if volume > 64 { 64 } else { volume }, ..self }
+  }
+
+  /// Sets the panning, from 0 (left) to 64 (right), with 32 as center. Higher
+  /// values are clamped to 64.
+  ///
+  /// At center pan the voice plays at full volume on both sides. This only
+  /// matters for stereo streams.
+  #[inline]
+  #[must_use]
+  pub const fn with_pan(self, pan: u8) -> Self {
+    Self { pan: if pan > 64 { 64 } else { pan }, ..self }
+  }
+
+  /// Loops the samples from `start` up to (but not including) `end`.
+  ///
+  /// The voice plays from the start of the sample, and then loops forever once
+  /// it gets to `end`. `end` is clamped to the length of the sample, and if
+  /// `start` isn't less than `end` the voice doesn't loop (but still stops at
+  /// `end`).
+  #[inline]
+  #[must_use]
+  pub const fn with_loop(self, start: usize, end: usize) -> Self {
+    let len = self.source.len();
+    let end = if end > len { len } else { end };
+    let loop_start = if start < end { Some(start as u32) } else { None };
+    Self { end: end as u32, loop_start, ..self }
+  }
+
+  /// The current position, as 20.12 fixed point.
+  #[inline]
+  #[must_use]
+  pub const fn position(&self) -> u32 {
+    self.position
+  }
+
+  /// The playback rate, as 20.12 fixed point.
+  #[inline]
+  #[must_use]
+  pub const fn rate(&self) -> u32 {
+    self.rate
+  }
+
+  /// The volume (0 to 64).
+  #[inline]
+  #[must_use]
+  pub const fn volume(&self) -> u8 {
+    self.volume
+  }
+
+  /// The panning (0 to 64).
+  #[inline]
+  #[must_use]
+  pub const fn pan(&self) -> u8 {
+    self.pan
+  }
+
+  /// Sets the volume without restarting the voice.
+  #[inline]
+  pub fn set_volume(&mut self, volume: u8) {
+    self.volume = volume.min(64);
+  }
+
+  /// Sets the rate (20.12 fixed point) without restarting the voice.
+  #[inline]
+  pub fn set_rate(&mut self, rate: u32) {
+    self.rate = rate;
+  }
+
+  /// Moves `position` forward by `rate`, handling the loop.
+  ///
+  /// Gives the new position, or `None` if a non-looping voice has ended.
+  #[inline]
+  #[must_use]
+  pub const fn advance(&self, position: u32) -> Option<u32> {
+    let mut position = position + self.rate;
+    let end = self.end << 12;
+    if position >= end {
+      match self.loop_start {
+        Some(start) => {
+          let loop_len = end - (start << 12);
+          while position >= end {
+            position -= loop_len;
+          }
+        }
+        None => return None,
+      }
+    }
+    Some(position)
+  }
+
+  /// The volumes for the left and right side.
+  #[inline]
+  #[must_use]
+  const fn side_volumes(&self) -> (i32, i32) {
+    let left = if self.pan > 32 { (64 - self.pan) * 2 } else { 64 };
+    let right = if self.pan < 32 { self.pan * 2 } else { 64 };
+    let volume = self.volume as i32;
+    ((volume * left as i32) >> 6, (volume * right as i32) >> 6)
+  }
+}
+
+/// The end, and the loop length (0 for no loop) of a voice, as 20.12 fixed
+/// point.
+#[inline]
+fn loop_settings(voice: &Voice) -> (u32, u32) {
+  let end = voice.end << 12;
+  let loop_len = match voice.loop_start {
+    Some(start) => end - (start << 12),
+    None => 0,
+  };
+  (end, loop_len)
+}
+
+// Note: the mixing loops are plain `while` loops that don't call anything
+// (not even iterator methods, or `Voice::advance`), because Thumb functions
+// can't be inlined into these ARM ones.
+
+crate::iwram_code! {
+  /// Adds a voice into `acc`. Gives `false` if the voice ended.
+  fn mix_voice(
+    acc: &mut [i16], voice: &mut Voice, end: u32, loop_len: u32,
+  ) -> bool {
+    let (source, rate, volume) = (voice.source, voice.rate, voice.volume as i32);
+    let mut position = voice.position;
+    let mut i = 0;
+    while i < acc.len() {
+      let sample = source[(position >> 12) as usize] as i32;
+      acc[i] += ((sample * volume) >> 6) as i16;
+      i += 1;
+      position += rate;
+      while position >= end {
+        if loop_len == 0 {
+          return false;
+        }
+        position -= loop_len;
+      }
+    }
+    voice.position = position;
+    true
+  }
+}
+
+crate::iwram_code! {
+  /// Adds a voice into `left` and `right`. Gives `false` if the voice ended.
+  fn mix_voice_stereo(
+    left: &mut [i16], right: &mut [i16], voice: &mut Voice,
+    (left_volume, right_volume): (i32, i32), end: u32, loop_len: u32,
+  ) -> bool {
+    let (source, rate) = (voice.source, voice.rate);
+    let mut position = voice.position;
+    let mut i = 0;
+    while i < left.len() && i < right.len() {
+      let sample = source[(position >> 12) as usize] as i32;
+      left[i] += ((sample * left_volume) >> 6) as i16;
+      right[i] += ((sample * right_volume) >> 6) as i16;
+      i += 1;
+      position += rate;
+      while position >= end {
+        if loop_len == 0 {
+          return false;
+        }
+        position -= loop_len;
+      }
+    }
+    voice.position = position;
+    true
+  }
+}
+
+/// Scales the mix by the master volume, and saturates it into `out`.
+#[inline]
+fn mix_down(acc: &[i16], out: &mut [i8], master_volume: i32) {
+  for (out, acc) in out.iter_mut().zip(acc) {
+    let scaled = (*acc as i32 * master_volume) >> 6;
+    *out = scaled.clamp(i8::MIN as i32, i8::MAX as i32) as i8;
+  }
+}
+
+/// Mixes up to `N` voices.
+///
+/// Each voice adds `sample * volume / 64` to the mix, and the final mix is
+/// scaled by the master volume and clamped to the `i8` range. With more than
+/// one loud voice playing at once the output will clip, so lower the voice
+/// volumes (or the master volume) to leave some headroom.
+#[derive(Debug, Clone)]
+pub struct Mixer<const N: usize> {
+  voices: [Option<Voice>; N],
+  master_volume: u8,
+}
+impl<const N: usize> Mixer<N> {
+  /// A mixer with no voices playing, and a master volume of 64.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { voices: [None; N], master_volume: 64 }
+  }
+
+  /// Starts `voice` in slot `slot`, replacing whatever was playing there.
+  ///
+  /// ## Panics
+  /// * If `slot` is `N` or more.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn play(&mut self, slot: usize, voice: Voice) {
+    self.voices[slot] = Some(voice);
+  }
+
+  /// Stops the voice in slot `slot`.
+  ///
+  /// ## Panics
+  /// * If `slot` is `N` or more.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn stop(&mut self, slot: usize) {
+    self.voices[slot] = None;
+  }
+
+  /// Stops all voices.
+  #[inline]
+  pub fn stop_all(&mut self) {
+    self.voices = [None; N];
+  }
+
+  /// The voice playing in `slot`, if any (to change its volume or rate).
+  ///
+  /// ## Panics
+  /// * If `slot` is `N` or more.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn voice_mut(&mut self, slot: usize) -> Option<&mut Voice> {
+    self.voices[slot].as_mut()
+  }
+
+  /// If a voice is playing in `slot`.
+  ///
+  /// ## Panics
+  /// * If `slot` is `N` or more.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn is_playing(&self, slot: usize) -> bool {
+    self.voices[slot].is_some()
+  }
+
+  /// The first slot with no voice playing.
+  #[inline]
+  #[must_use]
+  pub fn free_slot(&self) -> Option<usize> {
+    self.voices.iter().position(Option::is_none)
+  }
+
+  /// Sets the master volume, 0 to 64 (higher values are clamped to 64).
+  #[inline]
+  pub fn set_master_volume(&mut self, volume: u8) {
+    self.master_volume = volume.min(64);
+  }
+
+  /// Mixes the voices into `out`, as mono.
+  ///
+  /// Voices that reach their end are removed.
+  #[inline]
+  pub fn mix(&mut self, out: &mut [i8]) {
+    let mut acc = [0_i16; MAX_STREAM_SAMPLES];
+    for out in out.chunks_mut(MAX_STREAM_SAMPLES) {
+      let acc = &mut acc[..out.len()];
+      acc.fill(0);
+      for slot in self.voices.iter_mut() {
+        if let Some(voice) = slot {
+          let (end, loop_len) = loop_settings(voice);
+          if !mix_voice(acc, voice, end, loop_len) {
+            *slot = None;
+          }
+        }
+      }
+      mix_down(acc, out, self.master_volume as i32);
+    }
+  }
+
+  /// Mixes the voices into `left` and `right`, using each voice's panning.
+  ///
+  /// Voices that reach their end are removed.
+  ///
+  /// ## Panics
+  /// * If `left` and `right` aren't the same length.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn mix_stereo(&mut self, left: &mut [i8], right: &mut [i8]) {
+    assert_eq!(left.len(), right.len());
+    const HALF: usize = MAX_STREAM_SAMPLES / 2;
+    let mut acc_left = [0_i16; HALF];
+    let mut acc_right = [0_i16; HALF];
+    for (left, right) in left.chunks_mut(HALF).zip(right.chunks_mut(HALF)) {
+      let acc_left = &mut acc_left[..left.len()];
+      let acc_right = &mut acc_right[..right.len()];
+      acc_left.fill(0);
+      acc_right.fill(0);
+      for slot in self.voices.iter_mut() {
+        if let Some(voice) = slot {
+          let (end, loop_len) = loop_settings(voice);
+          let volumes = voice.side_volumes();
+          if !mix_voice_stereo(
+            acc_left, acc_right, voice, volumes, end, loop_len,
+          ) {
+            *slot = None;
+          }
+        }
+      }
+      mix_down(acc_left, left, self.master_volume as i32);
+      mix_down(acc_right, right, self.master_volume as i32);
+    }
+  }
+}
+impl<const N: usize> Default for Mixer<N> {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+impl<const N: usize> StreamFill for Mixer<N> {
+  #[inline]
+  fn fill(&mut self, samples: &mut [i8]) {
+    self.mix(samples);
+  }
+
+  #[inline]
+  fn fill_stereo(&mut self, left: &mut [i8], right: &mut [i8]) {
+    self.mix_stereo(left, right);
+  }
+}
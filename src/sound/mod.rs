@@ -0,0 +1,421 @@
+use crate::macros::{
+  pub_const_fn_new_zeroed, register_enum_field, register_int_field,
+  u16_bool_field, u8_bool_field, u8_int_field,
+};
+
+#[cfg(feature = "on_gba")]
+pub mod mixer;
+pub mod noise;
+#[cfg(feature = "on_gba")]
+pub mod stream;
+pub mod tone;
+pub mod wave;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct SweepControl(u8);
+impl SweepControl {
+  pub_const_fn_new_zeroed!();
+  u8_int_field!(0 - 2, sweep_num, with_sweep_num);
+  u8_bool_field!(3, sweep_decreasing, with_sweep_decreasing);
+  u8_int_field!(4 - 6, sweep_time, with_sweep_time);
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct TonePattern(u16);
+impl TonePattern {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(0 - 5, length, with_length, LENGTH_MASK);
+  register_int_field!(6 - 7, duty, with_duty, DUTY_MASK);
+  register_int_field!(8 - 10, step_time, with_step_time, STEP_TIME_MASK);
+  u16_bool_field!(11, step_increasing, with_step_increasing);
+  register_int_field!(12 - 15, volume, with_volume, VOLUME_MASK);
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct ToneFrequency(u16);
+impl ToneFrequency {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(0 - 10, frequency, with_frequency, FREQUENCY_MASK);
+  u16_bool_field!(14, stop_when_expired, with_stop_when_expired);
+  u16_bool_field!(15, enabled, with_enabled);
+
+  /// Sets the frequency to the closest value for a tone of `hz`.
+  ///
+  /// See [`rate_from_hz`](tone::rate_from_hz).
+  #[inline]
+  #[must_use]
+  pub const fn with_hz(self, hz: u32) -> Option<Self> {
+    match tone::rate_from_hz(hz) {
+      Some(rate) => Some(self.with_frequency(rate)),
+      None => None,
+    }
+  }
+}
+
+/// `2^(n/12)` for each semitone `n` of an octave, as 16.16 fixed point.
+const SEMITONE_RATIOS: [u32; 12] = [
+  65536, 69433, 73562, 77936, 82570, 87480, 92682, 98193, 104032, 110218,
+  116772, 123715,
+];
+
+/// Moves a sample rate (or a frequency) up or down by a number of semitones.
+///
+/// This uses a fixed-point table instead of the BIOS, so it works with any
+/// sample, and in `const` contexts. The output can be used for the Direct
+/// Sound timer, or with [`ToneFrequency::with_hz`].
+///
+/// ```
+/// # use gba::sound::note_to_sample_rate;
+/// assert_eq!(note_to_sample_rate(440, 12), 880);
+/// assert_eq!(note_to_sample_rate(440, -12), 220);
+/// assert_eq!(note_to_sample_rate(440, 3), 523);
+/// ```
+///
+/// The output saturates at `u32::MAX`.
+#[inline]
+#[must_use]
+pub const fn note_to_sample_rate(base_rate: u32, semitone_offset: i8) -> u32 {
+  let octave = (semitone_offset as i32).div_euclid(12);
+  let semitone = (semitone_offset as i32).rem_euclid(12) as usize;
+  let scaled = base_rate as u64 * SEMITONE_RATIOS[semitone] as u64;
+  // `octave` is always in -11 ..= 10, so this is never more than 27 or less
+  // than 6. Adding half before the shift rounds to nearest.
+  let shift = 16 - octave;
+  let out = (scaled + (1 << (shift - 1))) >> shift;
+  if out > u32::MAX as u64 {
+    u32::MAX
+  } else {
+    out as u32
+  }
+}
+
+/// The wave channel's bank settings, and its on/off switch.
+///
+/// This is the type of `SOUND3CNT_L`, see
+/// [`WAVE_BANK`](crate::mmio::WAVE_BANK).
+/// * `two_banks`: play both banks as one 64 sample wave.
+/// * `bank1`: play bank 1 (otherwise bank 0). The other bank is the one that
+///   can be accessed through [`WAVE_RAM`](crate::mmio::WAVE_RAM).
+/// * `enabled`: the channel is on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct WaveBank(u8);
+impl WaveBank {
+  pub_const_fn_new_zeroed!();
+  u8_bool_field!(5, two_banks, with_two_banks);
+  u8_bool_field!(6, bank1, with_bank1);
+  u8_bool_field!(7, enabled, with_enabled);
+}
+
+/// The wave channel's length and volume.
+///
+/// This is the type of `SOUND3CNT_H`, see
+/// [`WAVE_LEN_VOLUME`](crate::mmio::WAVE_LEN_VOLUME). The volume is easiest to
+/// set with [`with_wave_volume`](Self::with_wave_volume).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct WaveLenVolume(u16);
+impl WaveLenVolume {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(0 - 7, length, with_length, LENGTH_MASK);
+  register_int_field!(13 - 14, volume, with_volume, VOLUME_MASK);
+  u16_bool_field!(15, force75, with_force75);
+}
+
+/// The wave channel's sample rate and control bits.
+///
+/// This is the type of `SOUND3CNT_X`, see
+/// [`WAVE_FREQ`](crate::mmio::WAVE_FREQ). Setting `enabled` restarts the
+/// channel, and it always reads back as unset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct WaveFrequency(u16);
+impl WaveFrequency {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(0 - 10, sample_rate, with_sample_rate, SAMPLE_RATE_MASK);
+  u16_bool_field!(14, stop_when_expired, with_stop_when_expired);
+  u16_bool_field!(15, enabled, with_enabled);
+}
+
+/// The noise channel's length and envelope.
+///
+/// This is the type of `SOUND4CNT_L`, see
+/// [`NOISE_LEN_ENV`](crate::mmio::NOISE_LEN_ENV).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct NoiseLenEnvelope(u16);
+impl NoiseLenEnvelope {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(0 - 5, length, with_length, LENGTH_MASK);
+  register_int_field!(8 - 10, step_time, with_step_time, STEP_TIME_MASK);
+  u16_bool_field!(11, step_increasing, with_step_increasing);
+  register_int_field!(12 - 15, volume, with_volume, VOLUME_MASK);
+}
+
+/// The noise channel's frequency settings and control bits.
+///
+/// This is the type of `SOUND4CNT_H`, see
+/// [`NOISE_FREQ`](crate::mmio::NOISE_FREQ). The `r` and `s` fields are the
+/// dividing ratio and the shift clock, which are easier to set with
+/// [`with_dividing_ratio`](Self::with_dividing_ratio) and
+/// [`with_shift_clock`](Self::with_shift_clock). Setting `enabled` restarts
+/// the channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct NoiseFrequency(u16);
+impl NoiseFrequency {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(0 - 2, r, with_r, R_MASK);
+  u16_bool_field!(3, counter7, with_counter7);
+  register_int_field!(4 - 7, s, with_s, S_MASK);
+  u16_bool_field!(14, stop_when_expired, with_stop_when_expired);
+  u16_bool_field!(15, enabled, with_enabled);
+}
+
+/// The volume and left/right output of the four DMG (PSG) channels.
+///
+/// This is the type of `SOUNDCNT_L`, see
+/// [`LEFT_RIGHT_VOLUME`](crate::mmio::LEFT_RIGHT_VOLUME). The volumes go from 0
+/// to 7.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct LeftRightVolume(u16);
+impl LeftRightVolume {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(
+    0 - 2,
+    right_volume,
+    with_right_volume,
+    RIGHT_VOLUME_MASK
+  );
+  register_int_field!(4 - 6, left_volume, with_left_volume, LEFT_VOLUME_MASK);
+
+  u16_bool_field!(8, tone1_right, with_tone1_right);
+  u16_bool_field!(9, tone2_right, with_tone2_right);
+  u16_bool_field!(10, wave_right, with_wave_right);
+  u16_bool_field!(11, noise_right, with_noise_right);
+
+  u16_bool_field!(12, tone1_left, with_tone1_left);
+  u16_bool_field!(13, tone2_left, with_tone2_left);
+  u16_bool_field!(14, wave_left, with_wave_left);
+  u16_bool_field!(15, noise_left, with_noise_left);
+}
+
+/// All four DMG channels on both sides, at full volume.
+///
+/// The mix of the DMG channels with Direct Sound is then set with
+/// [`SoundMix::with_psg`].
+#[inline]
+#[must_use]
+pub const fn dmg_stereo_defaults() -> LeftRightVolume {
+  LeftRightVolume::new()
+    .with_right_volume(7)
+    .with_left_volume(7)
+    .with_tone1_right(true)
+    .with_tone2_right(true)
+    .with_wave_right(true)
+    .with_noise_right(true)
+    .with_tone1_left(true)
+    .with_tone2_left(true)
+    .with_wave_left(true)
+    .with_noise_left(true)
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum PsgMix {
+  #[default]
+  _25 = 0,
+  _50 = 1,
+  _100 = 2,
+}
+
+/// Mixing of the DMG channels and Direct Sound channels A and B.
+///
+/// This is the type of `SOUNDCNT_H`, see
+/// [`SOUND_MIX`](crate::mmio::SOUND_MIX).
+/// * `psg` is how loud the DMG channels are, all together.
+/// * `sound_a_full` / `sound_b_full` make that channel 100% volume instead of
+///   50%.
+/// * `sound_a_timer` / `sound_b_timer` pick which timer (0 when unset, 1 when
+///   set) the channel takes samples at.
+/// * `sound_a_reset` / `sound_b_reset` are write-only: writing them set empties
+///   that channel's FIFO, and they always read back as unset.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct SoundMix(u16);
+impl SoundMix {
+  pub_const_fn_new_zeroed!();
+  register_enum_field!(0 - 1: PsgMix [_25, _50, _100], psg, with_psg, PSG_MASK);
+  u16_bool_field!(2, sound_a_full, with_sound_a_full);
+  u16_bool_field!(3, sound_b_full, with_sound_b_full);
+
+  u16_bool_field!(8, sound_a_right, with_sound_a_right);
+  u16_bool_field!(9, sound_a_left, with_sound_a_left);
+  u16_bool_field!(10, sound_a_timer, with_sound_a_timer);
+  u16_bool_field!(11, sound_a_reset, with_sound_a_reset);
+
+  u16_bool_field!(12, sound_b_right, with_sound_b_right);
+  u16_bool_field!(13, sound_b_left, with_sound_b_left);
+  u16_bool_field!(14, sound_b_timer, with_sound_b_timer);
+  u16_bool_field!(15, sound_b_reset, with_sound_b_reset);
+}
+
+/// The sound master enable, and which DMG channels are playing.
+///
+/// This is the type of `SOUNDCNT_X`, see
+/// [`SOUND_ENABLED`](crate::mmio::SOUND_ENABLED). The `_playing` flags are
+/// read-only, and only `enabled` can be written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct SoundEnable(u8);
+impl SoundEnable {
+  pub_const_fn_new_zeroed!();
+  u8_bool_field!(0, tone1_playing, with_tone1_playing);
+  u8_bool_field!(1, tone2_playing, with_tone2_playing);
+  u8_bool_field!(2, wave_playing, with_wave_playing);
+  u8_bool_field!(3, noise_playing, with_noise_playing);
+
+  u8_bool_field!(7, enabled, with_enabled);
+}
+
+/// Turns on the sound hardware.
+///
+/// While the master enable is off, all the other sound registers (except
+/// [`SOUNDBIAS`](crate::mmio::SOUNDBIAS), and the wave RAM) are reset and
+/// can't be written. So call this *before* setting up
+/// [`LEFT_RIGHT_VOLUME`](crate::mmio::LEFT_RIGHT_VOLUME),
+/// [`SOUND_MIX`](crate::mmio::SOUND_MIX), or any of the channels.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// gba::sound::enable();
+/// LEFT_RIGHT_VOLUME.write(dmg_stereo_defaults());
+/// SOUND_MIX.write(SoundMix::new().with_psg(PsgMix::_100));
+/// ```
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn enable() {
+  crate::mmio::SOUND_ENABLED.write(SoundEnable::new().with_enabled(true));
+}
+
+/// The resolution and rate of the final sound output.
+///
+/// The GBA's output is a PWM signal, and this trades its resolution for its
+/// rate. The DMG channels are best at 9 bits, but Direct Sound samples are
+/// only 8 bits to begin with, so a higher rate gives them less aliasing noise
+/// without losing anything. See [`SampleCycle::for_stream_rate`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum SampleCycle {
+  /// 9 bits at 32.768 kHz.
+  #[default]
+  _9bit = 0 << 14,
+  /// 8 bits at 65.536 kHz.
+  _8bit = 1 << 14,
+  /// 7 bits at 131.072 kHz.
+  _7bit = 2 << 14,
+  /// 6 bits at 262.144 kHz.
+  _6bit = 3 << 14,
+}
+impl SampleCycle {
+  /// The output rate, in Hz.
+  #[inline]
+  #[must_use]
+  pub const fn hz(self) -> u32 {
+    32768 << ((self as u16) >> 14)
+  }
+
+  /// The cycle with the most resolution that still runs at least twice as
+  /// fast as `sample_hz`.
+  ///
+  /// Going past twice the sample rate of a stream doesn't help it, and costs
+  /// resolution.
+  #[inline]
+  #[must_use]
+  pub const fn for_stream_rate(sample_hz: u32) -> Self {
+    if sample_hz <= 16384 {
+      Self::_9bit
+    } else if sample_hz <= 32768 {
+      Self::_8bit
+    } else if sample_hz <= 65536 {
+      Self::_7bit
+    } else {
+      Self::_6bit
+    }
+  }
+}
+
+/// The sound output bias level, and the [`SampleCycle`].
+///
+/// This is the type of [`SOUNDBIAS`](crate::mmio::SOUNDBIAS). The bias level
+/// (0 to 511) is the "middle point" that the output swings around, and it
+/// should almost always stay at the [`BOOT_DEFAULT`](Self::BOOT_DEFAULT)
+/// level. Changing the bias while sound is playing (or at all, while the
+/// speaker is on) makes a pop, so use [`ramp_bias_level`] if you need to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct SoundBias(u16);
+impl SoundBias {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(1 - 9, bias_level, with_bias_level, BIAS_LEVEL_MASK);
+  register_enum_field!(
+    14 - 15: SampleCycle [_9bit, _8bit, _7bit, _6bit],
+    sample_cycle, with_sample_cycle, SAMPLE_CYCLE_MASK
+  );
+
+  /// The value that the official BIOS sets at boot (a bias level of `0x100`,
+  /// with 9-bit sampling).
+  pub const BOOT_DEFAULT: Self = Self::new().with_bias_level(0x100);
+}
+
+/// Makes sure that [`SOUNDBIAS`](crate::mmio::SOUNDBIAS) has a usable value.
+///
+/// The official BIOS slowly raises the bias to [`SoundBias::BOOT_DEFAULT`] at
+/// boot, but some replacement BIOSes leave it at 0, which makes all sound
+/// output clip. When [`detect_bios`](crate::bios::detect_bios) doesn't find an
+/// official BIOS, this writes the default value. With an official BIOS it
+/// does nothing, because writing the bias all at once can make a "pop" sound.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn ensure_sound_bias() {
+  if !crate::bios::detect_bios().is_official() {
+    crate::mmio::SOUNDBIAS.write(SoundBias::BOOT_DEFAULT);
+  }
+}
+
+/// Sets the [`SampleCycle`], keeping the current bias level.
+///
+/// This only changes the sampling part of
+/// [`SOUNDBIAS`](crate::mmio::SOUNDBIAS), so it doesn't cause a pop the way a
+/// bias change does.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn set_resampling(cycle: SampleCycle) {
+  use crate::mmio::SOUNDBIAS;
+  SOUNDBIAS.write(SOUNDBIAS.read().with_sample_cycle(cycle));
+}
+
+/// Moves the bias level to `level` one step at a time, to avoid a pop.
+///
+/// Each step waits 512 CPU cycles (with
+/// [`busy_wait_cycles`](crate::timers::busy_wait_cycles)), so a full swing from
+/// 0 to the default level takes about two frames. The sample cycle is kept as
+/// it is.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn ramp_bias_level(level: u16) {
+  use crate::mmio::SOUNDBIAS;
+  let level = level.min(511);
+  let mut bias = SOUNDBIAS.read();
+  while bias.bias_level() != level {
+    let current = bias.bias_level();
+    let next = if current < level { current + 1 } else { current - 1 };
+    bias = bias.with_bias_level(next);
+    SOUNDBIAS.write(bias);
+    crate::timers::busy_wait_cycles(512);
+  }
+}
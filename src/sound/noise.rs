@@ -0,0 +1,202 @@
+//! Playing noise on the noise channel.
+//!
+//! The noise channel makes a pseudo-random stream of bits with a shift
+//! register (a "polynomial counter"), clocked at
+//! `524288 / ratio / 2^(shift + 1)` Hz. The ratio is a [`DividingRatio`], where
+//! `_0_5` counts as 0.5. Higher clock rates sound like a hiss, and lower ones
+//! sound like a rumble.
+//!
+//! The [`CounterWidth`] changes the character of the noise. With the 15-bit
+//! counter the noise doesn't repeat for a long time, so it sounds like white
+//! noise. With the 7-bit counter the pattern repeats every 127 steps, which
+//! sounds like a buzzy or metallic tone.
+//!
+//! The presets ([`white_noise`], [`metallic`], and [`buzz`]) are a starting
+//! point, and the fields of a [`NoiseSettings`] can be changed from there.
+
+use super::{tone::Envelope, NoiseFrequency, NoiseLenEnvelope};
+
+/// How many bits the noise channel's counter uses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CounterWidth {
+  /// 15 bits: white noise.
+  #[default]
+  _15,
+  /// 7 bits: a buzzy, repeating noise.
+  _7,
+}
+
+/// The dividing ratio of the noise channel's clock.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum DividingRatio {
+  /// Divide by 0.5 (so, multiply by 2).
+  #[default]
+  _0_5 = 0,
+  _1 = 1,
+  _2 = 2,
+  _3 = 3,
+  _4 = 4,
+  _5 = 5,
+  _6 = 6,
+  _7 = 7,
+}
+
+impl NoiseLenEnvelope {
+  /// Sets the envelope.
+  #[inline]
+  #[must_use]
+  pub const fn with_envelope(self, envelope: Envelope) -> Self {
+    self
+      .with_volume(envelope.volume() as u16)
+      .with_step_time(envelope.step_time() as u16)
+      .with_step_increasing(envelope.increasing())
+  }
+
+  /// Sets the length in 1/256ths of a second (clamped to 1 to 64).
+  ///
+  /// The length is only used if `stop_when_expired` is set when the noise is
+  /// started.
+  #[inline]
+  #[must_use]
+  pub const fn with_length_256ths(self, length: u8) -> Self {
+    let length = if length == 0 {
+      1
+    } else if length > 64 {
+      64
+    } else {
+      length
+    };
+    self.with_length(64 - length as u16)
+  }
+}
+
+impl NoiseFrequency {
+  /// Sets the dividing ratio.
+  #[inline]
+  #[must_use]
+  pub const fn with_dividing_ratio(self, ratio: DividingRatio) -> Self {
+    self.with_r(ratio as u16)
+  }
+
+  /// Sets the counter width.
+  #[inline]
+  #[must_use]
+  pub const fn with_counter_width(self, width: CounterWidth) -> Self {
+    self.with_counter7(matches!(width, CounterWidth::_7))
+  }
+
+  /// Sets the shift clock (0 to 15).
+  ///
+  /// Each step up halves the clock rate. With 14 or 15 the counter doesn't get
+  /// clocked at all, so the noise stops changing.
+  #[inline]
+  #[must_use]
+  pub const fn with_shift_clock(self, shift: u8) -> Self {
+    self.with_s(shift as u16)
+  }
+}
+
+/// All the settings needed to play the noise channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NoiseSettings {
+  /// The volume envelope.
+  pub envelope: Envelope,
+  /// The shift clock (0 to 13), see [`NoiseFrequency::with_shift_clock`].
+  pub shift_clock: u8,
+  /// The counter width.
+  pub width: CounterWidth,
+  /// The dividing ratio.
+  pub ratio: DividingRatio,
+  /// `None` to play until stopped, or the number of 1/256ths of a second to
+  /// play for (1 to 64).
+  pub length: Option<u8>,
+}
+impl NoiseSettings {
+  /// The register values to start the noise channel with these settings
+  /// (including the restart bit).
+  #[inline]
+  #[must_use]
+  pub const fn to_registers(self) -> (NoiseLenEnvelope, NoiseFrequency) {
+    let len_env = NoiseLenEnvelope::new().with_envelope(self.envelope);
+    let frequency = NoiseFrequency::new()
+      .with_shift_clock(self.shift_clock)
+      .with_counter_width(self.width)
+      .with_dividing_ratio(self.ratio)
+      .with_enabled(true);
+    match self.length {
+      Some(length) => (
+        len_env.with_length_256ths(length),
+        frequency.with_stop_when_expired(true),
+      ),
+      None => (len_env, frequency),
+    }
+  }
+}
+
+/// A bright hiss: the 15-bit counter at the fastest clock.
+///
+/// Good for cymbals and snares (with a short fading envelope). Raise the
+/// `shift_clock` to get lower, rougher noise, for explosions and such.
+#[inline]
+#[must_use]
+pub const fn white_noise() -> NoiseSettings {
+  NoiseSettings {
+    envelope: Envelope::constant(12),
+    shift_clock: 0,
+    width: CounterWidth::_15,
+    ratio: DividingRatio::_0_5,
+    length: None,
+  }
+}
+
+/// A high ringing tone: the 7-bit counter at a fast clock.
+///
+/// Good for bells, clanks, and hits on metal.
+#[inline]
+#[must_use]
+pub const fn metallic() -> NoiseSettings {
+  NoiseSettings {
+    envelope: Envelope::constant(12),
+    shift_clock: 2,
+    width: CounterWidth::_7,
+    ratio: DividingRatio::_1,
+    length: None,
+  }
+}
+
+/// A low buzz: the 7-bit counter at a slow clock.
+///
+/// The short repeating pattern gives the classic "broken speaker" buzz, good
+/// for engines, alarms, and error sounds.
+#[inline]
+#[must_use]
+pub const fn buzz() -> NoiseSettings {
+  NoiseSettings {
+    envelope: Envelope::constant(12),
+    shift_clock: 6,
+    width: CounterWidth::_7,
+    ratio: DividingRatio::_4,
+    length: None,
+  }
+}
+
+/// Starts (or restarts) the noise channel.
+///
+/// The length/envelope register is written first, then the frequency with the
+/// restart bit set.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn play_noise(settings: NoiseSettings) {
+  use crate::mmio::{NOISE_FREQ, NOISE_LEN_ENV};
+  let (len_env, frequency) = settings.to_registers();
+  NOISE_LEN_ENV.write(len_env);
+  NOISE_FREQ.write(frequency);
+}
+
+/// Stops the noise channel right away.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn stop_noise() {
+  crate::mmio::NOISE_LEN_ENV.write(NoiseLenEnvelope::new());
+}
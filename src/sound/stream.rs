@@ -0,0 +1,439 @@
+//! Streaming PCM samples through Direct Sound.
+//!
+//! Direct Sound plays signed 8-bit samples from a FIFO, taking one sample each
+//! time a timer overflows, and a DMA unit keeps the FIFO topped up from memory.
+//! This module runs all of that for you, double buffered:
+//! * The driver has two buffers per channel (in EWRAM), each holding one
+//!   frame's worth of samples.
+//! * At each vblank, [`stream_vblank`] points the DMA at the buffer that was
+//!   filled most recently.
+//! * During the frame your code fills the other buffer, with [`fill_stream`]
+//!   and something that implements [`StreamFill`].
+//!
+//! If the next buffer isn't ready in time, the driver plays a frame of
+//! silence instead of old or half written samples.
+//!
+//! ## Sample Rates
+//!
+//! To keep the DMA in step with vblank, the number of samples played each
+//! frame must be a whole number, and a multiple of 16 (the DMA always moves 16
+//! samples at a time). Only some sample rates work that way, so you pick a
+//! rate with [`StreamRate::nearest`], and the stream plays at the closest rate
+//! that does. If your samples were recorded at some other rate, the
+//! [`StreamFill`] has to resample them.
+//!
+//! ```
+//! # use gba::sound::stream::StreamRate;
+//! let rate = StreamRate::nearest(16384);
+//! assert_eq!(rate.hz(), 18157);
+//! assert_eq!(rate.samples_per_frame(), 304);
+//! assert_eq!(rate.cycles_per_sample(), 924);
+//! ```
+//!
+//! ## Setup
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # use gba::sound::stream::*;
+//! struct Quiet;
+//! impl StreamFill for Quiet {
+//!   fn fill(&mut self, samples: &mut [i8]) {
+//!     samples.fill(0);
+//!   }
+//! }
+//!
+//! extern "C" fn irq_handler(bits: IrqBits) {
+//!   if bits.vblank() {
+//!     stream_vblank();
+//!   }
+//! }
+//!
+//! RUST_IRQ_HANDLER.write(Some(irq_handler));
+//! init_vblank_irq();
+//! gba::sound::enable();
+//! start_stream(StreamConfig::new(StreamRate::nearest(16384))).unwrap();
+//! let mut quiet = Quiet;
+//! loop {
+//!   wait_for_vblank();
+//!   fill_stream(&mut quiet);
+//! }
+//! ```
+
+use super::SampleCycle;
+use crate::{
+  dma::{
+    setup_sound_dma_a, setup_sound_dma_b, stop_sound_dma_a, stop_sound_dma_b,
+  },
+  gba_cell::GbaCell,
+  mmio::{timer_reload, SOUND_ENABLED, SOUND_MIX},
+  timers::{Timer, TimerControl, CPU_CYCLES_PER_SECOND},
+};
+use core::cell::UnsafeCell;
+
+/// The number of CPU cycles in one frame (228 lines of 1232 cycles).
+const CYCLES_PER_FRAME: u32 = 280_896;
+
+/// The most samples per frame that a stream can use (which is 42048 Hz).
+pub const MAX_STREAM_SAMPLES: usize = 704;
+
+/// A sample rate that works for streaming.
+///
+/// See the [module docs](self) for why only some rates work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamRate {
+  samples_per_frame: u16,
+}
+impl StreamRate {
+  /// The usable rate that's closest to `hz`.
+  ///
+  /// The lowest usable rate is 955 Hz (16 samples per frame), and the highest
+  /// is 42048 Hz (704 samples per frame).
+  #[inline]
+  #[must_use]
+  pub const fn nearest(hz: u32) -> Self {
+    let mut best = Self { samples_per_frame: 16 };
+    let mut samples = 16;
+    while samples <= MAX_STREAM_SAMPLES as u16 {
+      if let Some(rate) = Self::from_samples_per_frame(samples) {
+        if rate.hz().abs_diff(hz) < best.hz().abs_diff(hz) {
+          best = rate;
+        }
+      }
+      samples += 16;
+    }
+    best
+  }
+
+  /// The rate that plays `samples` samples per frame, if that's usable.
+  ///
+  /// It must be a multiple of 16, no more than [`MAX_STREAM_SAMPLES`], and
+  /// evenly divide the 280896 CPU cycles of a frame.
+  #[inline]
+  #[must_use]
+  pub const fn from_samples_per_frame(samples: u16) -> Option<Self> {
+    if samples == 0
+      || !samples.is_multiple_of(16)
+      || samples as usize > MAX_STREAM_SAMPLES
+      || !CYCLES_PER_FRAME.is_multiple_of(samples as u32)
+    {
+      None
+    } else {
+      Some(Self { samples_per_frame: samples })
+    }
+  }
+
+  /// How many samples play each frame.
+  #[inline]
+  #[must_use]
+  pub const fn samples_per_frame(self) -> usize {
+    self.samples_per_frame as usize
+  }
+
+  /// How many CPU cycles each sample plays for.
+  #[inline]
+  #[must_use]
+  pub const fn cycles_per_sample(self) -> u32 {
+    CYCLES_PER_FRAME / self.samples_per_frame as u32
+  }
+
+  /// The sample rate, in Hz (rounded down).
+  #[inline]
+  #[must_use]
+  pub const fn hz(self) -> u32 {
+    CPU_CYCLES_PER_SECOND / self.cycles_per_sample()
+  }
+
+  /// The timer reload value to overflow once per sample (with the timer at
+  /// [`TimerScale::_1`](crate::timers::TimerScale::_1)).
+  #[inline]
+  #[must_use]
+  pub const fn timer_reload(self) -> u16 {
+    (self.cycles_per_sample() as u16).wrapping_neg()
+  }
+}
+
+/// The settings for [`start_stream`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StreamConfig {
+  rate: StreamRate,
+  timer: Timer,
+  stereo: bool,
+  sample_cycle: Option<SampleCycle>,
+}
+impl StreamConfig {
+  /// Mono output at `rate`, using Timer 0.
+  #[inline]
+  #[must_use]
+  pub const fn new(rate: StreamRate) -> Self {
+    Self {
+      rate,
+      timer: Timer::Timer0,
+      stereo: false,
+      sample_cycle: Some(SampleCycle::for_stream_rate(rate.hz())),
+    }
+  }
+
+  /// Sets which timer the stream uses.
+  ///
+  /// Direct Sound can only be driven by Timer 0 or Timer 1, and
+  /// [`start_stream`] gives an error for the others.
+  #[inline]
+  #[must_use]
+  pub const fn with_timer(self, timer: Timer) -> Self {
+    Self { timer, ..self }
+  }
+
+  /// Sets stereo output.
+  ///
+  /// In stereo, Sound A plays on the left and Sound B plays on the right, and
+  /// the stream uses both DMA1 and DMA2. In mono, Sound A plays on both sides
+  /// and only DMA1 is used.
+  #[inline]
+  #[must_use]
+  pub const fn with_stereo(self, stereo: bool) -> Self {
+    Self { stereo, ..self }
+  }
+
+  /// Sets the [`SampleCycle`] to use while streaming, or `None` to leave it
+  /// as it is.
+  ///
+  /// By default this is [`SampleCycle::for_stream_rate`] of the stream's rate.
+  /// The DMG channels sound best with [`SampleCycle::_9bit`], so if you're
+  /// mostly using those, you might want to set this to `None`.
+  #[inline]
+  #[must_use]
+  pub const fn with_sample_cycle(
+    self, sample_cycle: Option<SampleCycle>,
+  ) -> Self {
+    Self { sample_cycle, ..self }
+  }
+
+  /// The sample rate.
+  #[inline]
+  #[must_use]
+  pub const fn rate(&self) -> StreamRate {
+    self.rate
+  }
+}
+
+/// Something that makes the samples for a stream.
+pub trait StreamFill {
+  /// Writes the next frame of samples (for mono output).
+  fn fill(&mut self, samples: &mut [i8]);
+
+  /// Writes the next frame of samples (for stereo output).
+  ///
+  /// By default this fills `left` and copies it to `right`.
+  #[inline]
+  fn fill_stereo(&mut self, left: &mut [i8], right: &mut [i8]) {
+    self.fill(left);
+    right.copy_from_slice(left);
+  }
+}
+
+/// The stream buffers: Sound A 0 and 1, then Sound B 0 and 1.
+#[repr(C, align(4))]
+struct StreamBuffers(UnsafeCell<[[i8; MAX_STREAM_SAMPLES]; 4]>);
+unsafe impl Sync for StreamBuffers {}
+
+crate::ewram_data! {
+  static STREAM_BUFFERS: StreamBuffers =
+    StreamBuffers(UnsafeCell::new([[0; MAX_STREAM_SAMPLES]; 4]));
+}
+
+/// Played when the next buffer isn't ready.
+static SILENCE: [u32; MAX_STREAM_SAMPLES / 4] = [0; MAX_STREAM_SAMPLES / 4];
+
+static STREAM_RUNNING: GbaCell<bool> = GbaCell::new(false);
+static STREAM_STARTED: GbaCell<bool> = GbaCell::new(false);
+static STREAM_STEREO: GbaCell<bool> = GbaCell::new(false);
+static STREAM_TIMER1: GbaCell<bool> = GbaCell::new(false);
+static STREAM_SAMPLES_PER_FRAME: GbaCell<u16> = GbaCell::new(0);
+/// The buffer that's next to be filled (0 or 1).
+static STREAM_FILL_INDEX: GbaCell<u8> = GbaCell::new(0);
+/// If the buffer at the fill index has been filled.
+static STREAM_READY: GbaCell<bool> = GbaCell::new(false);
+static STREAM_SAMPLES_PLAYED: GbaCell<u32> = GbaCell::new(0);
+
+/// A pointer to one of the stream buffers.
+#[inline]
+fn stream_buffer(sound_b: bool, index: u8) -> *mut i8 {
+  let buffers = STREAM_BUFFERS.0.get().cast::<[i8; MAX_STREAM_SAMPLES]>();
+  let i = (sound_b as usize) * 2 + index as usize;
+  unsafe { buffers.add(i).cast() }
+}
+
+/// Starts a stream.
+///
+/// This uses the master sound enable (turning it on if it's off), the timer
+/// from `config`, DMA1 (and DMA2 in stereo), and the Direct Sound parts of
+/// [`SOUND_MIX`]. The DMG channel settings are left as they are. The sample
+/// cycle in [`SOUNDBIAS`](crate::mmio::SOUNDBIAS) is also set, unless the
+/// config says not to.
+///
+/// Nothing plays until the next [`stream_vblank`], and that first frame is
+/// silence. Call [`fill_stream`] to start giving it samples.
+///
+/// ## Failure
+/// * If the timer isn't Timer 0 or Timer 1 you get an error.
+#[inline]
+pub fn start_stream(config: StreamConfig) -> Result<(), ()> {
+  let timer1 = match config.timer {
+    Timer::Timer0 => false,
+    Timer::Timer1 => true,
+    _ => return Err(()),
+  };
+  stop_stream();
+  for sound_b in [false, true] {
+    for index in [0, 1] {
+      let buffer = stream_buffer(sound_b, index);
+      unsafe { buffer.write_bytes(0, MAX_STREAM_SAMPLES) };
+    }
+  }
+  STREAM_STEREO.write(config.stereo);
+  STREAM_TIMER1.write(timer1);
+  STREAM_SAMPLES_PER_FRAME.write(config.rate.samples_per_frame);
+  STREAM_FILL_INDEX.write(0);
+  STREAM_READY.write(false);
+  STREAM_SAMPLES_PLAYED.write(0);
+  STREAM_STARTED.write(false);
+
+  if !SOUND_ENABLED.read().enabled() {
+    super::enable();
+  }
+  if let Some(cycle) = config.sample_cycle {
+    super::set_resampling(cycle);
+  }
+  let stereo = config.stereo;
+  SOUND_MIX.write(
+    SOUND_MIX
+      .read()
+      .with_sound_a_full(true)
+      .with_sound_a_left(true)
+      .with_sound_a_right(!stereo)
+      .with_sound_a_timer(timer1)
+      .with_sound_a_reset(true)
+      .with_sound_b_full(stereo)
+      .with_sound_b_left(false)
+      .with_sound_b_right(stereo)
+      .with_sound_b_timer(timer1)
+      .with_sound_b_reset(true),
+  );
+  STREAM_RUNNING.write(true);
+  Ok(())
+}
+
+/// Stops the stream, its timer, and its DMA.
+#[inline]
+pub fn stop_stream() {
+  if !STREAM_RUNNING.read() {
+    return;
+  }
+  STREAM_RUNNING.write(false);
+  stop_sound_dma_a();
+  stop_sound_dma_b();
+  let timer = if STREAM_TIMER1.read() { Timer::Timer1 } else { Timer::Timer0 };
+  timer.stop();
+  SOUND_MIX.write(
+    SOUND_MIX
+      .read()
+      .with_sound_a_left(false)
+      .with_sound_a_right(false)
+      .with_sound_a_reset(true)
+      .with_sound_b_left(false)
+      .with_sound_b_right(false)
+      .with_sound_b_reset(true),
+  );
+}
+
+/// Swaps the stream's buffers. Call this at the start of every vblank.
+///
+/// Call it from your interrupt handler (when the vblank bit is set), or set it
+/// as the vblank handler with
+/// [`set_handler`](crate::interrupts::set_handler). It does nothing if no
+/// stream is running.
+///
+/// The first call after [`start_stream`] also starts the timer. The timer's
+/// first period is made longer by half a FIFO refill (8 samples), so that
+/// from then on each swap lands halfway between two DMA transfers, instead of
+/// racing one.
+#[inline]
+pub extern "C" fn stream_vblank() {
+  if !STREAM_RUNNING.read() {
+    return;
+  }
+  let samples = STREAM_SAMPLES_PER_FRAME.read();
+  let (a, b) = if STREAM_READY.read() {
+    let index = STREAM_FILL_INDEX.read();
+    STREAM_FILL_INDEX.write(index ^ 1);
+    STREAM_READY.write(false);
+    (stream_buffer(false, index), stream_buffer(true, index))
+  } else {
+    let silence = SILENCE.as_ptr().cast::<i8>().cast_mut();
+    (silence, silence)
+  };
+  unsafe { setup_sound_dma_a(a.cast()) };
+  if STREAM_STEREO.read() {
+    unsafe { setup_sound_dma_b(b.cast()) };
+  }
+
+  if STREAM_STARTED.read() {
+    let played = STREAM_SAMPLES_PLAYED.read();
+    STREAM_SAMPLES_PLAYED.write(played.wrapping_add(samples as u32));
+  } else {
+    STREAM_STARTED.write(true);
+    let rate = StreamRate { samples_per_frame: samples };
+    let cycles = rate.cycles_per_sample();
+    let first = (cycles * 9).min(u16::MAX as u32) as u16;
+    let timer =
+      if STREAM_TIMER1.read() { Timer::Timer1 } else { Timer::Timer0 };
+    timer.start(first.wrapping_neg(), TimerControl::new());
+    // The reload value is only used on the next overflow, so the first period
+    // stays long.
+    timer_reload(timer.index()).write(rate.timer_reload());
+  }
+}
+
+/// Fills the stream's next buffer, if it's waiting to be filled.
+///
+/// Call this once per frame, after [`stream_vblank`] has run (eg: right after
+/// [`wait_for_vblank`](crate::video::wait_for_vblank)). The buffer that
+/// `source` fills plays starting at the next vblank, so it has to finish by
+/// then, or that frame will be silent.
+///
+/// * **Returns:** If a buffer was filled. This is `false` if there's no stream
+///   running, or if the next buffer is already filled (when this is called more
+///   than once in a frame).
+#[inline]
+pub fn fill_stream<S: StreamFill + ?Sized>(source: &mut S) -> bool {
+  if !STREAM_RUNNING.read() || STREAM_READY.read() {
+    return false;
+  }
+  let index = STREAM_FILL_INDEX.read();
+  let samples = STREAM_SAMPLES_PER_FRAME.read() as usize;
+  // Safety: the DMA isn't reading this buffer, and won't be until after
+  // `STREAM_READY` is set.
+  let left = unsafe {
+    core::slice::from_raw_parts_mut(stream_buffer(false, index), samples)
+  };
+  if STREAM_STEREO.read() {
+    let right = unsafe {
+      core::slice::from_raw_parts_mut(stream_buffer(true, index), samples)
+    };
+    source.fill_stereo(left, right);
+  } else {
+    source.fill(left);
+  }
+  STREAM_READY.write(true);
+  true
+}
+
+/// How many samples have played since the stream started.
+///
+/// This goes up by the samples per frame at each [`stream_vblank`], and wraps
+/// around at `u32::MAX`.
+#[inline]
+#[must_use]
+pub fn stream_samples_played() -> u32 {
+  STREAM_SAMPLES_PLAYED.read()
+}
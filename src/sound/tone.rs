@@ -0,0 +1,302 @@
+//! Playing notes on the tone channels.
+//!
+//! Tone 1 and tone 2 are the two square wave channels. They work the same,
+//! except that tone 1 also has a frequency sweep. Each channel has three parts
+//! to its settings:
+//! * A [`Duty`] cycle (the shape of the square wave).
+//! * An [`Envelope`] (the volume, and how it changes over time).
+//! * A frequency "rate" value. A rate of `n` (0 to 2047) plays at `131072 /
+//!   (2048 - n)` Hz. Use [`rate_from_hz`] to get the rate for a frequency, or
+//!   [`Note::to_tone_period`] for a musical note.
+//!
+//! The length of a tone is either continuous (it plays until it's stopped or
+//! changed), or a number of 1/256ths of a second. The length counter only
+//! runs while the length enable bit (`stop_when_expired`) is set in the
+//! frequency register, so that bit has to be written along with the restart
+//! bit each time a limited length tone is started.
+//!
+//! The master sound enable has to be on for any of this to work, see
+//! [`enable`](super::enable).
+
+use super::{SweepControl, ToneFrequency, TonePattern};
+
+/// The duty cycle of a square wave: how much of each wave is "high".
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum Duty {
+  /// 12.5% high, a thin and buzzy sound.
+  _12_5 = 0,
+  /// 25% high.
+  _25 = 1,
+  /// 50% high, a "pure" square wave.
+  #[default]
+  _50 = 2,
+  /// 75% high (which sounds the same as 25%).
+  _75 = 3,
+}
+
+/// A volume envelope for a channel.
+///
+/// The volume starts at `volume` (0 to 15). If `step_time` isn't 0 the volume
+/// then moves by 1, up or down, every `step_time / 64` seconds until it gets to
+/// 15 or 0.
+///
+/// An envelope that starts at 0 and goes down turns the channel off the moment
+/// it's set, which is what [`stop_tone1`] and [`stop_tone2`] use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Envelope {
+  volume: u8,
+  step_time: u8,
+  increasing: bool,
+}
+impl Envelope {
+  /// Makes an envelope, or gives `None` if `volume` is more than 15 or
+  /// `step_time` is more than 7.
+  #[inline]
+  #[must_use]
+  pub const fn new(
+    volume: u8, step_time: u8, increasing: bool,
+  ) -> Option<Self> {
+    if volume > 15 || step_time > 7 {
+      None
+    } else {
+      Some(Self { volume, step_time, increasing })
+    }
+  }
+
+  /// An envelope that stays at `volume` (which is masked to 0 to 15).
+  #[inline]
+  #[must_use]
+  pub const fn constant(volume: u8) -> Self {
+    Self { volume: volume & 0xF, step_time: 0, increasing: false }
+  }
+
+  /// The starting volume.
+  #[inline]
+  #[must_use]
+  pub const fn volume(self) -> u8 {
+    self.volume
+  }
+
+  /// How long each volume step takes, in 1/64ths of a second (0 is no steps).
+  #[inline]
+  #[must_use]
+  pub const fn step_time(self) -> u8 {
+    self.step_time
+  }
+
+  /// If the volume goes up (rather than down) with each step.
+  #[inline]
+  #[must_use]
+  pub const fn increasing(self) -> bool {
+    self.increasing
+  }
+}
+
+/// A frequency sweep for tone 1.
+///
+/// Every `time / 128` seconds the rate `n` changes by `n >> shift`, going up
+/// (higher pitch) or down. If the sweep would push the rate past 2047 the
+/// channel turns off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Sweep {
+  time: u8,
+  shift: u8,
+  decreasing: bool,
+}
+impl Sweep {
+  /// Makes a sweep, or gives `None` if `time` isn't 1 to 7 or `shift` is more
+  /// than 7.
+  #[inline]
+  #[must_use]
+  pub const fn new(time: u8, shift: u8, decreasing: bool) -> Option<Self> {
+    if time == 0 || time > 7 || shift > 7 {
+      None
+    } else {
+      Some(Self { time, shift, decreasing })
+    }
+  }
+
+  /// The sweep register setting for this sweep.
+  #[inline]
+  #[must_use]
+  pub const fn to_control(self) -> SweepControl {
+    SweepControl::new()
+      .with_sweep_num(self.shift)
+      .with_sweep_decreasing(self.decreasing)
+      .with_sweep_time(self.time)
+  }
+}
+
+impl TonePattern {
+  /// Sets the duty cycle.
+  #[inline]
+  #[must_use]
+  pub const fn with_duty_cycle(self, duty: Duty) -> Self {
+    self.with_duty(duty as u16)
+  }
+
+  /// Sets the envelope.
+  #[inline]
+  #[must_use]
+  pub const fn with_envelope(self, envelope: Envelope) -> Self {
+    self
+      .with_volume(envelope.volume as u16)
+      .with_step_time(envelope.step_time as u16)
+      .with_step_increasing(envelope.increasing)
+  }
+
+  /// Sets the length in 1/256ths of a second (clamped to 1 to 64).
+  ///
+  /// The length is only used if `stop_when_expired` is set when the tone is
+  /// started.
+  #[inline]
+  #[must_use]
+  pub const fn with_length_256ths(self, length: u8) -> Self {
+    let length = if length == 0 {
+      1
+    } else if length > 64 {
+      64
+    } else {
+      length
+    };
+    self.with_length(64 - length as u16)
+  }
+}
+
+/// Gets the tone rate value for a frequency in Hz.
+///
+/// The tone channels play at `131072 / (2048 - n)` Hz, so they can go from 64
+/// Hz up to 131072 Hz, and this gives `None` outside of that range. Higher
+/// notes are less exact, since the rate is `2048 - (131072 / hz)` rounded to
+/// the nearest whole number.
+#[inline]
+#[must_use]
+pub const fn rate_from_hz(hz: u32) -> Option<u16> {
+  if hz < 64 || hz > 131072 {
+    return None;
+  }
+  Some((2048 - ((131072 + hz / 2) / hz)) as u16)
+}
+
+/// A musical note, from C3 to B7.
+///
+/// The discriminant of each note is its MIDI key number (middle C, `C4`, is
+/// 60), and the pitches are equal temperament with A4 at 440 Hz.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// gba::sound::enable();
+/// LEFT_RIGHT_VOLUME.write(dmg_stereo_defaults());
+/// play_tone2(Note::A4.to_tone_period(), Duty::_50, Envelope::constant(15), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+#[rustfmt::skip]
+pub enum Note {
+  C3 = 48, CSharp3, D3, DSharp3, E3, F3, FSharp3, G3, GSharp3, A3, ASharp3, B3,
+  C4, CSharp4, D4, DSharp4, E4, F4, FSharp4, G4, GSharp4, A4, ASharp4, B4,
+  C5, CSharp5, D5, DSharp5, E5, F5, FSharp5, G5, GSharp5, A5, ASharp5, B5,
+  C6, CSharp6, D6, DSharp6, E6, F6, FSharp6, G6, GSharp6, A6, ASharp6, B6,
+  C7, CSharp7, D7, DSharp7, E7, F7, FSharp7, G7, GSharp7, A7, ASharp7, B7,
+}
+impl Note {
+  /// The tone rate to play this note on a tone channel.
+  ///
+  /// This is the same as [`rate_from_hz`] with the note's frequency (it's
+  /// just looked up from a table).
+  #[inline]
+  #[must_use]
+  pub const fn to_tone_period(self) -> u16 {
+    TONE_PERIODS[(self as u8 - Self::C3 as u8) as usize]
+  }
+
+  /// The note's MIDI key number.
+  #[inline]
+  #[must_use]
+  pub const fn to_midi_key(self) -> u8 {
+    self as u8
+  }
+}
+
+/// The tone rate of each [`Note`], in order.
+///
+/// Each value is `2048 - 131072 / hz`, rounded to the nearest whole number.
+const TONE_PERIODS: [u16; 60] = [
+  1046, 1102, 1155, 1205, 1253, 1297, 1339, 1379, 1417, 1452, 1486, 1517, 1547,
+  1575, 1602, 1627, 1650, 1673, 1694, 1714, 1732, 1750, 1767, 1783, 1798, 1812,
+  1825, 1837, 1849, 1860, 1871, 1881, 1890, 1899, 1907, 1915, 1923, 1930, 1936,
+  1943, 1949, 1954, 1959, 1964, 1969, 1974, 1978, 1982, 1985, 1989, 1992, 1995,
+  1998, 2001, 2004, 2006, 2009, 2011, 2013, 2015,
+];
+
+/// The pattern and frequency register values to start a tone.
+#[inline]
+#[must_use]
+const fn tone_settings(
+  rate: u16, duty: Duty, envelope: Envelope, length: Option<u8>,
+) -> (TonePattern, ToneFrequency) {
+  let pattern =
+    TonePattern::new().with_duty_cycle(duty).with_envelope(envelope);
+  let frequency = ToneFrequency::new().with_frequency(rate).with_enabled(true);
+  match length {
+    Some(length) => (
+      pattern.with_length_256ths(length),
+      frequency.with_stop_when_expired(true),
+    ),
+    None => (pattern, frequency),
+  }
+}
+
+/// Starts (or restarts) a tone on tone 1.
+///
+/// * `rate` is the 11-bit frequency rate, see [`rate_from_hz`] and
+///   [`Note::to_tone_period`].
+/// * `length` is `None` for a continuous tone, or the number of 1/256ths of a
+///   second to play for (1 to 64).
+///
+/// The sweep, pattern, and frequency registers are written in that order, with
+/// the restart bit set in the last write so all the new settings are used.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn play_tone1(
+  rate: u16, duty: Duty, envelope: Envelope, sweep: Option<Sweep>,
+  length: Option<u8>,
+) {
+  use crate::mmio::{TONE1_FREQUENCY, TONE1_PATTERN, TONE1_SWEEP};
+  let (pattern, frequency) = tone_settings(rate, duty, envelope, length);
+  TONE1_SWEEP.write(match sweep {
+    Some(sweep) => sweep.to_control(),
+    None => SweepControl::new(),
+  });
+  TONE1_PATTERN.write(pattern);
+  TONE1_FREQUENCY.write(frequency);
+}
+
+/// Stops tone 1 right away.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn stop_tone1() {
+  crate::mmio::TONE1_PATTERN.write(TonePattern::new());
+}
+
+/// Starts (or restarts) a tone on tone 2.
+///
+/// This is the same as [`play_tone1`], except that tone 2 has no sweep.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn play_tone2(
+  rate: u16, duty: Duty, envelope: Envelope, length: Option<u8>,
+) {
+  use crate::mmio::{TONE2_FREQUENCY, TONE2_PATTERN};
+  let (pattern, frequency) = tone_settings(rate, duty, envelope, length);
+  TONE2_PATTERN.write(pattern);
+  TONE2_FREQUENCY.write(frequency);
+}
+
+/// Stops tone 2 right away.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn stop_tone2() {
+  crate::mmio::TONE2_PATTERN.write(TonePattern::new());
+}
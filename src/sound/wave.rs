@@ -0,0 +1,174 @@
+//! Playing samples on the wave channel.
+//!
+//! The wave channel plays a short loop of 4-bit samples from wave RAM. Each
+//! byte of wave RAM holds two samples, and the high nibble plays first. There
+//! are two banks of wave RAM, each holding 32 samples (16 bytes). The channel
+//! plays one bank, or both banks one after the other as a 64 sample loop.
+//!
+//! The quirk of wave RAM is that [`WAVE_RAM`](crate::mmio::WAVE_RAM) always
+//! accesses the bank that *isn't* selected to play. [`write_wave_ram`] handles
+//! that for you.
+//!
+//! A rate of `n` (0 to 2047) plays samples at `2097152 / (2048 - n)` Hz, so a
+//! 32 sample wave plays as a tone of `65536 / (2048 - n)` Hz. Use
+//! [`wave_rate_from_hz`] to get the rate for a tone.
+
+use super::{WaveFrequency, WaveLenVolume};
+
+/// A 32 sample square wave.
+pub const WAVE_SQUARE: [u8; 16] = [
+  0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0x00, 0x00, 0x00, 0x00, 0x00,
+  0x00, 0x00, 0x00,
+];
+
+/// A 32 sample rising saw wave.
+pub const WAVE_SAW: [u8; 16] = [
+  0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xAA, 0xBB, 0xCC,
+  0xDD, 0xEE, 0xFF,
+];
+
+/// A 32 sample triangle wave.
+pub const WAVE_TRIANGLE: [u8; 16] = [
+  0x01, 0x23, 0x45, 0x67, 0x89, 0xAB, 0xCD, 0xEF, 0xFE, 0xDC, 0xBA, 0x98, 0x76,
+  0x54, 0x32, 0x10,
+];
+
+/// A 32 sample sine wave.
+pub const WAVE_SINE: [u8; 16] = [
+  0x89, 0xAC, 0xDE, 0xEF, 0xFF, 0xEE, 0xDC, 0xA9, 0x86, 0x53, 0x21, 0x10, 0x00,
+  0x11, 0x23, 0x56,
+];
+
+/// The output volume of the wave channel.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WaveVolume {
+  /// No sound (the channel still runs).
+  Mute,
+  /// 25% volume.
+  _25,
+  /// 50% volume.
+  _50,
+  /// 75% volume.
+  _75,
+  /// Full volume.
+  #[default]
+  _100,
+}
+
+impl WaveLenVolume {
+  /// Sets the output volume.
+  ///
+  /// 75% is a separate bit from the other volume levels, and this sets both
+  /// parts.
+  #[inline]
+  #[must_use]
+  pub const fn with_wave_volume(self, volume: WaveVolume) -> Self {
+    let (bits, force75) = match volume {
+      WaveVolume::Mute => (0, false),
+      WaveVolume::_100 => (1, false),
+      WaveVolume::_50 => (2, false),
+      WaveVolume::_25 => (3, false),
+      WaveVolume::_75 => (0, true),
+    };
+    self.with_volume(bits).with_force75(force75)
+  }
+
+  /// Sets the length in 1/256ths of a second (clamped to at least 1).
+  ///
+  /// The length is only used if `stop_when_expired` is set when the channel
+  /// is started.
+  #[inline]
+  #[must_use]
+  pub const fn with_length_256ths(self, length: u8) -> Self {
+    let length = if length == 0 { 1 } else { length };
+    self.with_length((256 - length as u16) & 0xFF)
+  }
+}
+
+/// Gets the wave rate value for a tone of `hz`, when playing a 32 sample
+/// wave.
+///
+/// A rate `n` plays a 32 sample wave at `65536 / (2048 - n)` Hz, so this gives
+/// `None` for frequencies under 32 Hz or over 65536 Hz. When playing 64
+/// samples (both banks) the tone is an octave lower than this.
+#[inline]
+#[must_use]
+pub const fn wave_rate_from_hz(hz: u32) -> Option<u16> {
+  if hz < 32 || hz > 65536 {
+    return None;
+  }
+  Some((2048 - ((65536 + hz / 2) / hz)) as u16)
+}
+
+/// Writes 16 bytes (32 samples) to one bank of wave RAM.
+///
+/// * `bank1`: if the samples go in bank 1 (otherwise bank 0).
+///
+/// Only the bank not being played can be written, so this switches the
+/// playback bank to the *other* bank while writing, then switches it back. If
+/// the channel is playing the bank being written, it will briefly play the
+/// other bank. Write the bank that isn't playing (or turn the channel off
+/// first) to avoid that.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn write_wave_ram(bank1: bool, samples: &[u8; 16]) {
+  use crate::mmio::{WAVE_BANK, WAVE_RAM};
+  let bank = WAVE_BANK.read();
+  WAVE_BANK.write(bank.with_bank1(!bank1));
+  for (i, chunk) in samples.chunks_exact(4).enumerate() {
+    let word = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+    WAVE_RAM.index(i).write(word);
+  }
+  WAVE_BANK.write(bank);
+}
+
+/// Writes 32 bytes (64 samples) across both banks of wave RAM, and sets the
+/// channel to play both banks, starting from bank 0.
+///
+/// The channel should be stopped (see [`stop_wave`]) before calling this.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn write_wave_ram_64(samples: &[u8; 32]) {
+  use crate::mmio::WAVE_BANK;
+  let (low, high) = samples.split_at(16);
+  write_wave_ram(false, low.try_into().unwrap());
+  write_wave_ram(true, high.try_into().unwrap());
+  WAVE_BANK.write(WAVE_BANK.read().with_two_banks(true).with_bank1(false));
+}
+
+/// Starts (or restarts) the wave channel.
+///
+/// * `rate` is the 11-bit frequency rate, see [`wave_rate_from_hz`].
+/// * `length` is `None` to play continuously, or the number of 1/256ths of a
+///   second to play for (1 to 255).
+///
+/// The bank settings (which bank, and if both banks play) are kept as they
+/// are. The channel is turned on, then the volume, then the frequency with
+/// the restart bit set.
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn play_wave(rate: u16, volume: WaveVolume, length: Option<u8>) {
+  use crate::mmio::{WAVE_BANK, WAVE_FREQ, WAVE_LEN_VOLUME};
+  WAVE_BANK.write(WAVE_BANK.read().with_enabled(true));
+  let len_volume = WaveLenVolume::new().with_wave_volume(volume);
+  let frequency =
+    WaveFrequency::new().with_sample_rate(rate).with_enabled(true);
+  match length {
+    Some(length) => {
+      WAVE_LEN_VOLUME.write(len_volume.with_length_256ths(length));
+      WAVE_FREQ.write(frequency.with_stop_when_expired(true));
+    }
+    None => {
+      WAVE_LEN_VOLUME.write(len_volume);
+      WAVE_FREQ.write(frequency);
+    }
+  }
+}
+
+/// Stops the wave channel right away (by turning it off).
+#[inline]
+#[cfg(feature = "on_gba")]
+pub fn stop_wave() {
+  use crate::mmio::WAVE_BANK;
+  WAVE_BANK.write(WAVE_BANK.read().with_enabled(false));
+}
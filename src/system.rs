@@ -0,0 +1,238 @@
+//! The soft reset key combo.
+//!
+//! Commercial games reset when A, B, Start, and Select are all held, and
+//! players expect that to work. A [`SoftResetCombo`] is that: it can run a
+//! cleanup function first (to finish writing a save, for example), then it
+//! resets the memory and registers picked with [`RamResetFlags`] and starts
+//! the program over with [`SoftReset`](crate::bios::SoftReset).
+//!
+//! There are two ways to check for the combo:
+//! * Call [`poll`](SoftResetCombo::poll) once a frame with the keys read that
+//!   frame. The reset happens right there, in the main program.
+//! * Call [`arm_interrupt`](SoftResetCombo::arm_interrupt), which has the
+//!   keypad interrupt fire when all the combo keys are held. By default the
+//!   handler only *requests* a reset, and the next `poll` (or
+//!   [`reset_if_requested`]) does it in the main program, where it's safe to
+//!   stop whatever was going on (such as between save writes). An immediate
+//!   interrupt instead resets right from the handler, which still works when
+//!   the main program is stuck, but then the cleanup function runs inside the
+//!   interrupt handler.
+//!
+//! While the combo stays held the program keeps resetting, so it only starts
+//! again once the keys are let go.
+
+use crate::{
+  bios::{reset_and_restart, RamResetFlags, ResetTarget},
+  gba_cell::GbaCell,
+  interrupts::{clear_handler, irq_free, set_handler, Interrupt, IrqBits},
+  keys::{Key, KeyControl, KeyInput},
+  mmio::{KEYCNT, KEYINPUT},
+};
+
+static IRQ_KEYS: GbaCell<KeyInput> = GbaCell::new(KeyInput::new());
+static IRQ_FLAGS: GbaCell<RamResetFlags> = GbaCell::new(RamResetFlags::ALL);
+static IRQ_TARGET: GbaCell<ResetTarget> = GbaCell::new(ResetTarget::Rom);
+static IRQ_CLEANUP: GbaCell<Option<fn()>> = GbaCell::new(None);
+static IRQ_IMMEDIATE: GbaCell<bool> = GbaCell::new(false);
+static RESET_REQUESTED: GbaCell<bool> = GbaCell::new(false);
+
+/// A key combo that resets the system.
+///
+/// ```no_run
+/// # use gba::{prelude::*, system::SoftResetCombo};
+/// fn flush_saves() {
+///   // finish any save writes that are in progress.
+/// }
+/// const RESET: SoftResetCombo = SoftResetCombo::new().with_cleanup(flush_saves);
+/// loop {
+///   VBlankIntrWait();
+///   RESET.poll(KEYINPUT.read());
+///   // the rest of the frame.
+/// }
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct SoftResetCombo {
+  keys: KeyInput,
+  flags: RamResetFlags,
+  target: ResetTarget,
+  cleanup: Option<fn()>,
+}
+impl SoftResetCombo {
+  /// The usual combo: A, B, Start, and Select all at once.
+  pub const DEFAULT_KEYS: KeyInput =
+    KeyInput::from_keys(&[Key::A, Key::B, Key::Start, Key::Select]);
+
+  /// The usual reset: everything, like turning the GBA off and on.
+  ///
+  /// For a program running from EWRAM (such as a multiboot program), don't
+  /// reset the EWRAM, and use [`ResetTarget::Ewram`].
+  pub const DEFAULT_FLAGS: RamResetFlags = RamResetFlags::ALL;
+
+  /// The default combo and reset flags, restarting from the ROM with no
+  /// cleanup.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self {
+      keys: Self::DEFAULT_KEYS,
+      flags: Self::DEFAULT_FLAGS,
+      target: ResetTarget::Rom,
+      cleanup: None,
+    }
+  }
+
+  /// Sets the keys that must all be held to reset.
+  #[inline]
+  #[must_use]
+  pub const fn with_keys(self, keys: KeyInput) -> Self {
+    Self { keys, ..self }
+  }
+
+  /// Sets what to clear with
+  /// [`RegisterRamReset`](crate::bios::RegisterRamReset) before restarting.
+  #[inline]
+  #[must_use]
+  pub const fn with_flags(self, flags: RamResetFlags) -> Self {
+    Self { flags, ..self }
+  }
+
+  /// Sets where the program starts again.
+  #[inline]
+  #[must_use]
+  pub const fn with_target(self, target: ResetTarget) -> Self {
+    Self { target, ..self }
+  }
+
+  /// Sets a function to call just before resetting.
+  ///
+  /// This is the place to finish off anything that mustn't be cut short,
+  /// such as writing a save. It should return once it's done, and the reset
+  /// happens after that.
+  #[inline]
+  #[must_use]
+  pub const fn with_cleanup(self, cleanup: fn()) -> Self {
+    Self { cleanup: Some(cleanup), ..self }
+  }
+
+  /// The keys that must all be held to reset.
+  #[inline]
+  #[must_use]
+  pub const fn keys(&self) -> KeyInput {
+    self.keys
+  }
+
+  /// What's cleared before restarting.
+  #[inline]
+  #[must_use]
+  pub const fn flags(&self) -> RamResetFlags {
+    self.flags
+  }
+
+  /// Where the program starts again.
+  #[inline]
+  #[must_use]
+  pub const fn target(&self) -> ResetTarget {
+    self.target
+  }
+
+  /// Resets if all the combo keys are held in `keys`, or if the interrupt
+  /// requested a reset.
+  ///
+  /// Otherwise this returns right away.
+  #[inline]
+  pub fn poll(&self, keys: KeyInput) {
+    if keys.contains(self.keys) || RESET_REQUESTED.read() {
+      self.reset()
+    }
+  }
+
+  /// Runs the cleanup function (if any), and then resets.
+  #[inline]
+  pub fn reset(&self) -> ! {
+    reset_with(self.cleanup, self.flags, self.target)
+  }
+
+  /// Sets the keypad interrupt to fire when all the combo keys are held.
+  ///
+  /// This sets [`KEYCNT`] and the keypad handler of the
+  /// [`irq_table_dispatch`](crate::interrupts::irq_table_dispatch) table (so
+  /// that has to be the [`RUST_IRQ_HANDLER`](crate::RUST_IRQ_HANDLER)), and
+  /// [`IME`](crate::mmio::IME) must be on for the interrupt to happen.
+  ///
+  /// * If `immediate` is `false`, the handler just requests a reset, which the
+  ///   next [`poll`](Self::poll) or [`reset_if_requested`] does.
+  /// * If `immediate` is `true`, the handler runs the cleanup function and
+  ///   resets right away. The cleanup function then runs inside the interrupt
+  ///   handler, with other interrupts masked, so it mustn't wait on any
+  ///   interrupt itself.
+  #[inline]
+  pub fn arm_interrupt(&self, immediate: bool) {
+    irq_free(|| {
+      IRQ_KEYS.write(self.keys);
+      IRQ_FLAGS.write(self.flags);
+      IRQ_TARGET.write(self.target);
+      IRQ_CLEANUP.write(self.cleanup);
+      IRQ_IMMEDIATE.write(immediate);
+      KEYCNT.write(KeyControl::from_keys(self.keys, true));
+      set_handler(Interrupt::Keypad, keypad_handler);
+    });
+  }
+}
+impl Default for SoftResetCombo {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Turns off the keypad interrupt set with
+/// [`arm_interrupt`](SoftResetCombo::arm_interrupt), and forgets any request
+/// that it made.
+#[inline]
+pub fn disarm_interrupt() {
+  irq_free(|| {
+    clear_handler(Interrupt::Keypad);
+    KEYCNT.write(KeyControl::new());
+    RESET_REQUESTED.write(false);
+  });
+}
+
+/// If the keypad interrupt has requested a reset.
+#[inline]
+#[must_use]
+pub fn reset_requested() -> bool {
+  RESET_REQUESTED.read()
+}
+
+/// Resets with the settings given to
+/// [`arm_interrupt`](SoftResetCombo::arm_interrupt), if the keypad interrupt
+/// has requested it.
+///
+/// This is for when there's no [`SoftResetCombo`] around to
+/// [`poll`](SoftResetCombo::poll) with.
+#[inline]
+pub fn reset_if_requested() {
+  if RESET_REQUESTED.read() {
+    reset_with(IRQ_CLEANUP.read(), IRQ_FLAGS.read(), IRQ_TARGET.read())
+  }
+}
+
+fn reset_with(
+  cleanup: Option<fn()>, flags: RamResetFlags, target: ResetTarget,
+) -> ! {
+  if let Some(cleanup) = cleanup {
+    cleanup();
+  }
+  reset_and_restart(flags, target)
+}
+
+unsafe extern "C" fn keypad_handler(_: IrqBits) {
+  // the keypad interrupt might have been set up for something else since.
+  if !KEYINPUT.read().contains(IRQ_KEYS.read()) {
+    return;
+  }
+  if IRQ_IMMEDIATE.read() {
+    reset_with(IRQ_CLEANUP.read(), IRQ_FLAGS.read(), IRQ_TARGET.read())
+  }
+  RESET_REQUESTED.write(true);
+}
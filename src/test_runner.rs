@@ -0,0 +1,387 @@
+//! A test runner for `#[test_case]` functions, run in a ROM under mGBA.
+//!
+//! This is only here with the `test_runner` feature. The test crate uses the
+//! nightly `custom_test_frameworks` feature with [`runner`] as its runner, and
+//! [`test_panic_handler`] as its panic handler:
+//!
+//! ```ignore
+//! #![no_std]
+//! #![no_main]
+//! #![feature(custom_test_frameworks)]
+//! #![test_runner(gba::test_runner::runner)]
+//! #![reexport_test_harness_main = "test_main"]
+//!
+//! #[panic_handler]
+//! fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+//!   gba::test_runner::test_panic_handler(info)
+//! }
+//!
+//! #[no_mangle]
+//! extern "C" fn main() -> ! {
+//!   test_main();
+//!   unreachable!()
+//! }
+//!
+//! #[test_case]
+//! fn one_plus_one() {
+//!   assert_eq!(1 + 1, 2);
+//! }
+//! ```
+//!
+//! The crate's own ROM tests are in `tests/rom_tests.rs`, and `cargo test-rom`
+//! runs them.
+//!
+//! ## Results
+//! Each test's name and result goes to the mGBA log, the same way as
+//! [`mgba_info!`](crate::mgba_info), followed by a count of how many passed
+//! and failed. Then the runner ends with `swi 0x03` (the BIOS `Stop`) and the
+//! exit code in `r0`: 0 if everything passed, 1 if anything failed. That's
+//! what mGBA's headless test runner waits for, so in CI use
+//! `mgba-rom-test -S 0x03 -R 0` as the cargo runner, and the process exit code
+//! is the result.
+//!
+//! The runner checks for mGBA first (see
+//! [`environment`](crate::environment)). Anywhere else there's nowhere for the
+//! results to go, so it doesn't run any tests: it says why on the screen (and
+//! in no$gba's log, if that's where it is), and stops.
+//!
+//! ## Panics and timeouts
+//! A panic fails just that test. The panic handler jumps back to where the
+//! runner called the test (like `longjmp`), with the stack put back as it was,
+//! and the runner goes on to the next test. Nothing is dropped on the way, so a
+//! test that panics while holding something (such as an
+//! [`IrqMutex`](crate::interrupts::IrqMutex) lock) can leave it stuck for the
+//! rest of the tests.
+//!
+//! A test that takes longer than [`DEFAULT_TIMEOUT_SECONDS`] (or its own
+//! timeout, see [`TimedTest`]) fails as timed out, the same way as a panic.
+//! The timeout is timer 3's overflow interrupt, set with
+//! [`set_handler`](crate::interrupts::set_handler), and [`RUST_IRQ_HANDLER`] is
+//! [`irq_table_dispatch`] while a test runs. So a test can use the other
+//! entries of the handler table, but not timer 3, and it can't replace
+//! `RUST_IRQ_HANDLER` unless it puts the runner's handler back. A test that
+//! hangs with interrupts off can't be stopped.
+//!
+//! Before each test the runner saves `IE` and `IME`, and puts them back after,
+//! along with clearing the handler table, so one test's interrupt setup
+//! doesn't carry over to the next. After a failed test, all four DMA channels
+//! are stopped too.
+
+use crate::{
+  asm_runtime::force_a32,
+  collections::ArrayString,
+  dma::DmaControl,
+  environment::{detect, is_mgba, Environment},
+  gba_cell::GbaCell,
+  interrupts::{
+    clear_handler, irq_table_dispatch, set_handler, Interrupt, IrqBits,
+  },
+  mmio::{DMA_CONTROL, IE, IME, TIMER3_CONTROL},
+  timers::{Timer, TimerControl, TimerScale},
+  RUST_IRQ_HANDLER,
+};
+use core::{fmt::Write, panic::PanicInfo};
+
+/// How long a test can run before it fails, unless it has its own timeout.
+pub const DEFAULT_TIMEOUT_SECONDS: u16 = 10;
+
+/// What [`__gba_test_try_call`] gives when the test returned.
+const PASSED: u32 = 0;
+
+/// What [`__gba_test_try_call`] gives when the test panicked.
+const PANICKED: u32 = 1;
+
+/// What [`__gba_test_try_call`] gives when the test ran out of time.
+const TIMED_OUT: u32 = 2;
+
+/// If a test is running (so a panic can go back to the runner).
+static IN_TEST: GbaCell<bool> = GbaCell::new(false);
+
+/// The seconds left before the running test times out.
+static SECONDS_LEFT: GbaCell<u16> = GbaCell::new(0);
+
+/// If a test is running right now.
+#[inline]
+pub(crate) fn in_test() -> bool {
+  IN_TEST.read()
+}
+
+/// Something the runner can run as a test.
+///
+/// This is implemented for every `Fn()` (which is what a `#[test_case]`
+/// function is), and for [`TimedTest`].
+pub trait Testable {
+  /// The name to log for the test.
+  fn name(&self) -> &str;
+
+  /// How long the test can run before it fails.
+  #[inline]
+  fn timeout_seconds(&self) -> u16 {
+    DEFAULT_TIMEOUT_SECONDS
+  }
+
+  /// Runs the test, which panics if it fails.
+  fn run(&self);
+}
+impl<F: Fn()> Testable for F {
+  #[inline]
+  fn name(&self) -> &str {
+    core::any::type_name::<F>()
+  }
+
+  #[inline]
+  fn run(&self) {
+    self()
+  }
+}
+
+/// A test with its own name and timeout.
+///
+/// `#[test_case]` also works on statics, so a test that needs more (or less)
+/// time than [`DEFAULT_TIMEOUT_SECONDS`] can be declared like this:
+///
+/// ```ignore
+/// #[test_case]
+/// static SLOW_FILL: TimedTest = TimedTest::new("slow_fill", slow_fill, 30);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct TimedTest {
+  name: &'static str,
+  test: fn(),
+  timeout_seconds: u16,
+}
+impl TimedTest {
+  /// Makes a test that fails if it runs for more than `timeout_seconds`.
+  #[inline]
+  #[must_use]
+  pub const fn new(
+    name: &'static str, test: fn(), timeout_seconds: u16,
+  ) -> Self {
+    Self { name, test, timeout_seconds }
+  }
+}
+impl Testable for TimedTest {
+  #[inline]
+  fn name(&self) -> &str {
+    self.name
+  }
+
+  #[inline]
+  fn timeout_seconds(&self) -> u16 {
+    self.timeout_seconds
+  }
+
+  #[inline]
+  fn run(&self) {
+    (self.test)()
+  }
+}
+
+extern "C" {
+  /// Calls `f(data)`, giving [`PASSED`] once it returns, or the code passed to
+  /// [`__gba_test_resume`] if that's called first.
+  fn __gba_test_try_call(f: extern "C" fn(*const ()), data: *const ()) -> u32;
+
+  /// Goes back to the last [`__gba_test_try_call`], which then gives `code`.
+  fn __gba_test_resume(code: u32) -> !;
+}
+
+// The resume point is the registers that a call has to keep (`r4-r11`), and
+// the `sp` and `lr` of the `__gba_test_try_call` call. Going back there means
+// putting those back, and then returning as if `f` had returned.
+//
+// Resuming can happen from the Rust IRQ handler (for a timeout). The assembly
+// runtime calls the handler in System mode with interrupts masked, and the
+// BIOS has pushed to the IRQ stack, so resuming also unmasks interrupts and
+// resets `sp_irq` to where the BIOS starts it.
+core::arch::global_asm! {
+  ".section .bss.__gba_test_resume_point,\"aw\",%nobits",
+  ".align 2",
+  "__gba_test_resume_point:",
+  ".space 40",
+  ".previous",
+
+  bracer::put_fn_in_section!(".text.__gba_test_try_call"),
+  ".align 2",
+  ".global __gba_test_try_call",
+  ".type __gba_test_try_call, %function",
+  ".global __gba_test_resume",
+  ".type __gba_test_resume, %function",
+
+  force_a32!{
+    // __gba_test_try_call(f: r0, data: r1) -> code
+    "__gba_test_try_call:",
+    "ldr    r12, =__gba_test_resume_point",
+    "stmia  r12, {{r4-r11, sp, lr}}",
+    "mov    r12, r0",
+    "mov    r0, r1",
+    bracer::a32_fake_blx!("r12"),
+    "ldr    r12, =__gba_test_resume_point",
+    "ldr    lr, [r12, #36]",
+    "mov    r0, #{passed}",
+    "bx     lr",
+
+    // __gba_test_resume(code: r0) -> !
+    "__gba_test_resume:",
+    bracer::a32_set_cpu_control!(IRQ, irq_masked = true, fiq_masked = true),
+    "ldr    sp, ={irq_stack}",
+    bracer::a32_set_cpu_control!(System, irq_masked = false, fiq_masked = false),
+    "ldr    r12, =__gba_test_resume_point",
+    "ldmia  r12, {{r4-r11, sp, lr}}",
+    "bx     lr",
+  },
+
+  passed = const PASSED,
+  irq_stack = const 0x0300_7FA0,
+}
+
+/// Runs one test with [`__gba_test_try_call`].
+extern "C" fn call_test(data: *const ()) {
+  // Safety: this is the `&&dyn Testable` that `run_one` passed.
+  let test = unsafe { *data.cast::<&dyn Testable>() };
+  test.run();
+}
+
+/// The IRQ handler for the runner, which counts down the timeout.
+extern "C" fn timeout_handler(bits: IrqBits) {
+  if !bits.timer3() || !IN_TEST.read() {
+    return;
+  }
+  let left = SECONDS_LEFT.read().saturating_sub(1);
+  SECONDS_LEFT.write(left);
+  if left == 0 {
+    TIMER3_CONTROL.write(TimerControl::new());
+    IN_TEST.write(false);
+    // Safety: a test is running, so there's a resume point.
+    unsafe { __gba_test_resume(TIMED_OUT) };
+  }
+}
+
+/// Runs one test, giving how it went.
+fn run_one(test: &dyn Testable) -> u32 {
+  let saved_ie = IE.read();
+  let saved_ime = IME.read();
+
+  RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+  SECONDS_LEFT.write(test.timeout_seconds().max(1));
+  set_handler(Interrupt::Timer3, timeout_handler);
+  IME.write(true);
+  // with no reload and a /256 prescale, this overflows once per second.
+  Timer::Timer3.start(
+    0,
+    TimerControl::new().with_scale(TimerScale::_256).with_overflow_irq(true),
+  );
+
+  IN_TEST.write(true);
+  let data: *const &dyn Testable = &test;
+  // Safety: `call_test` is an ordinary function, and the resume point is only
+  // used while `IN_TEST` is set.
+  let result = unsafe { __gba_test_try_call(call_test, data.cast()) };
+  IN_TEST.write(false);
+
+  TIMER3_CONTROL.write(TimerControl::new());
+  if result != PASSED {
+    for control in DMA_CONTROL.iter() {
+      // Safety: stopping a transfer is always fine.
+      unsafe { control.write(DmaControl::new()) };
+    }
+  }
+  IME.write(false);
+  for source in Interrupt::ALL {
+    clear_handler(source);
+  }
+  IE.write(saved_ie);
+  RUST_IRQ_HANDLER.write(None);
+  IME.write(saved_ime);
+  result
+}
+
+/// Runs every test, logs the results, and ends with the exit code (see the
+/// [module docs](self)).
+///
+/// This is the `#![test_runner]`.
+#[inline(never)]
+pub fn runner(tests: &[&dyn Testable]) -> ! {
+  if !is_mgba() {
+    not_in_mgba();
+  }
+  crate::mgba_info!("running {} tests", tests.len());
+  let mut failed = 0;
+  for &test in tests {
+    match run_one(test) {
+      PASSED => crate::mgba_info!("test {} ... ok", test.name()),
+      TIMED_OUT => {
+        failed += 1;
+        crate::mgba_error!(
+          "test {} ... FAILED (timed out after {} seconds)",
+          test.name(),
+          test.timeout_seconds()
+        );
+      }
+      _ => {
+        failed += 1;
+        crate::mgba_error!("test {} ... FAILED", test.name());
+      }
+    }
+  }
+  let passed = tests.len() - failed;
+  if failed == 0 {
+    crate::mgba_info!("test result: ok. {passed} passed; 0 failed");
+  } else {
+    crate::mgba_error!("test result: FAILED. {passed} passed; {failed} failed");
+  }
+  exit(u32::from(failed != 0))
+}
+
+/// Says that the tests need mGBA (in the log if there is one, and on the
+/// screen), and stops without running any.
+fn not_in_mgba() -> ! {
+  let mut text = ArrayString::<256>::new();
+  write!(
+    text,
+    "The tests need mGBA, since the results go to its log and exit code.\n\n"
+  )
+  .ok();
+  match detect() {
+    Environment::Nocash { id } => write!(text, "Found: {id}"),
+    _ => write!(text, "Found: no known emulator"),
+  }
+  .ok();
+  crate::mgba_error!("{text}");
+  crate::panic_screen::message_screen(text.as_bytes())
+}
+
+/// The panic handler for a test crate.
+///
+/// During a test, this logs the panic and goes back to the runner, which counts
+/// the test as failed. Outside of a test, it logs the panic and exits with a
+/// failure.
+#[inline]
+pub fn test_panic_handler(info: &PanicInfo) -> ! {
+  crate::mgba_error!("{info}");
+  if IN_TEST.read() {
+    IN_TEST.write(false);
+    // Safety: a test is running, so there's a resume point.
+    unsafe { __gba_test_resume(PANICKED) }
+  }
+  exit(1)
+}
+
+/// Stops with `code` in `r0`, for mGBA's test runner.
+#[instruction_set(arm::t32)]
+fn exit(code: u32) -> ! {
+  IME.write(false);
+  loop {
+    // Safety: `Stop` is fine with interrupts off, and when there's no test
+    // runner to exit, it just stays stopped.
+    unsafe {
+      core::arch::asm!(
+        "swi #0x03",
+        inout("r0") code => _,
+        out("r1") _,
+        out("r3") _,
+        options(nomem, nostack),
+      )
+    };
+  }
+}
@@ -0,0 +1,143 @@
+//! A global frame count, for timing things in frames without passing a
+//! counter around.
+//!
+//! The assembly runtime's interrupt handler adds one to [`frame_count`] on
+//! every vblank interrupt, before any
+//! [`RUST_IRQ_HANDLER`](crate::RUST_IRQ_HANDLER) runs (so this works with any
+//! handler, or none). The vblank interrupt has to be on for it to count, such
+//! as with [`init_vblank_irq`](crate::video::init_vblank_irq).
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # use gba::time::*;
+//! init_vblank_irq();
+//! let start = FrameInstant::now();
+//! loop {
+//!   wait_for_vblank();
+//!   if every_n_frames(30) {
+//!     // blink a cursor...
+//!   }
+//!   if start.elapsed() >= 120 {
+//!     // two seconds are up...
+//!   }
+//!   # break;
+//! }
+//! ```
+//!
+//! ## Wrapping
+//! The count is a `u32`, so it wraps after 2^32 frames (about 2.27 years).
+//! Compare [`FrameInstant`]s with [`since`](FrameInstant::since) (which is a
+//! wrapping subtraction) rather than with `<`, and the wrap doesn't matter as
+//! long as the times are less than 2^32 frames apart.
+//!
+//! ## Why reading it is safe
+//! The count is only written by the interrupt handler, which runs with
+//! interrupts masked, so no other handler can interrupt the increment.
+//! Reading it is a single aligned 32-bit `ldr`, which the CPU can't stop
+//! halfway through for an interrupt, so the main program always sees either
+//! the old count or the new one, never a mix of the two.
+//!
+//! [`FramePacer`](crate::pacing::FramePacer),
+//! [`AnimationPlayer::timed`](crate::video::animation::AnimationPlayer::timed),
+//! and [`KeyRepeat::timed`](crate::keys::KeyRepeat::timed) use the count to
+//! keep their timing right when a frame is missed.
+
+#[cfg(feature = "on_gba")]
+use crate::{
+  gba_cell::GbaCell,
+  mmio::{DISPSTAT, IE},
+};
+
+/// The number of vblank interrupts, which the assembly runtime's interrupt
+/// handler updates.
+#[cfg(feature = "on_gba")]
+pub(crate) static FRAME_COUNT: GbaCell<u32> = GbaCell::new(0);
+
+/// The number of vblank interrupts so far (wrapping).
+///
+/// See the [module docs](self) for how this is counted.
+///
+/// ## Panics
+/// * With debug assertions on, if the vblank interrupt is off in [`DISPSTAT`]
+///   or [`IE`], since then the count isn't going up.
+#[cfg(feature = "on_gba")]
+#[inline]
+#[must_use]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn frame_count() -> u32 {
+  if cfg!(debug_assertions) {
+    assert!(
+      DISPSTAT.read().irq_vblank() && IE.read().vblank(),
+      "frame_count isn't counting: the vblank interrupt is off (see init_vblank_irq)"
+    );
+  }
+  FRAME_COUNT.read()
+}
+
+/// If [`frame_count`] is a multiple of `n`, which it is once every `n`
+/// frames.
+///
+/// Call this once per frame. Unless `n` is a power of two, the wrap of the
+/// count puts one gap that isn't `n` frames long every 2.27 years.
+///
+/// ## Panics
+/// * If `n` is 0.
+#[cfg(feature = "on_gba")]
+#[inline]
+#[must_use]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn every_n_frames(n: u32) -> bool {
+  assert!(n > 0, "every_n_frames needs a frame count that isn't 0");
+  frame_count().is_multiple_of(n)
+}
+
+/// A point in time, as a [`frame_count`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct FrameInstant(u32);
+impl FrameInstant {
+  /// The current frame.
+  #[cfg(feature = "on_gba")]
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn now() -> Self {
+    Self(frame_count())
+  }
+
+  /// The instant at a frame count.
+  #[inline]
+  #[must_use]
+  pub const fn from_frame_count(count: u32) -> Self {
+    Self(count)
+  }
+
+  /// The frame count of this instant.
+  #[inline]
+  #[must_use]
+  pub const fn frame_count(self) -> u32 {
+    self.0
+  }
+
+  /// The frames from `earlier` to this instant (wrapping).
+  #[inline]
+  #[must_use]
+  pub const fn since(self, earlier: Self) -> u32 {
+    self.0.wrapping_sub(earlier.0)
+  }
+
+  /// The frames from this instant until now.
+  #[cfg(feature = "on_gba")]
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn elapsed(self) -> u32 {
+    Self::now().since(self)
+  }
+
+  /// The instant `frames` after this one (wrapping).
+  #[inline]
+  #[must_use]
+  pub const fn add_frames(self, frames: u32) -> Self {
+    Self(self.0.wrapping_add(frames))
+  }
+}
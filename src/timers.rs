@@ -61,7 +61,9 @@
 //! Note that this doesn't work for timer 0, because that timer ignores the
 //! cascade bit.
 
-use crate::macros::{pub_const_fn_new_zeroed, u16_bool_field, u16_enum_field};
+use crate::macros::{
+  pub_const_fn_new_zeroed, register_enum_field, u16_bool_field,
+};
 
 /// A number of CPU cycles per timer tick.
 ///
@@ -101,8 +103,424 @@ pub enum TimerScale {
 pub struct TimerControl(u16);
 impl TimerControl {
   pub_const_fn_new_zeroed!();
-  u16_enum_field!(0 - 1: TimerScale, scale, with_scale);
+  register_enum_field!(
+    0 - 1: TimerScale [_1, _64, _256, _1024],
+    scale, with_scale, SCALE_MASK
+  );
   u16_bool_field!(2, cascade, with_cascade);
   u16_bool_field!(6, overflow_irq, with_overflow_irq);
   u16_bool_field!(7, enabled, with_enabled);
 }
+
+/// One of the four timer units.
+///
+/// This is a shorthand for using the timer MMIO controls (eg:
+/// [`timer_control`](crate::mmio::timer_control)) by number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+#[allow(missing_docs)]
+pub enum Timer {
+  Timer0 = 0,
+  Timer1 = 1,
+  Timer2 = 2,
+  Timer3 = 3,
+}
+#[cfg(feature = "on_gba")]
+impl Timer {
+  /// The timer's index (`0..4`).
+  #[inline]
+  #[must_use]
+  pub const fn index(self) -> usize {
+    self as usize
+  }
+
+  /// Sets the reload value and control, which starts the timer.
+  ///
+  /// The timer is disabled first, so the count always starts over from
+  /// `reload`, even if the timer was already running. The `control` is used as
+  /// given except that the `enabled` bit is always set.
+  #[inline]
+  pub fn start(self, reload: u16, control: TimerControl) {
+    use crate::mmio::{timer_control, timer_reload};
+    timer_control(self.index()).write(TimerControl::new());
+    timer_reload(self.index()).write(reload);
+    timer_control(self.index()).write(control.with_enabled(true));
+  }
+
+  /// Stops the timer.
+  ///
+  /// The count stays at the value it stopped at.
+  #[inline]
+  pub fn stop(self) {
+    crate::mmio::timer_control(self.index()).write(TimerControl::new());
+  }
+
+  /// The current count.
+  #[inline]
+  #[must_use]
+  pub fn count(self) -> u16 {
+    crate::mmio::timer_counter(self.index()).read()
+  }
+
+  /// If the timer is enabled.
+  #[inline]
+  #[must_use]
+  pub fn is_running(self) -> bool {
+    crate::mmio::timer_control(self.index()).read().enabled()
+  }
+}
+
+/// Reads a counter split across a high and low part without tearing.
+///
+/// If the high part changes while reading the low part then the low part
+/// overflowed partway through, so try again.
+#[cfg(feature = "on_gba")]
+#[inline]
+#[must_use]
+fn untorn_read<H: PartialEq>(
+  mut read_high: impl FnMut() -> H, mut read_low: impl FnMut() -> u16,
+) -> (H, u16) {
+  loop {
+    let high = read_high();
+    let low = read_low();
+    if read_high() == high {
+      return (high, low);
+    }
+  }
+}
+
+/// Two timers cascaded together into a single 32-bit counter.
+///
+/// The lower timer ticks at the scale given to [`start`](Self::start), and
+/// the upper timer ticks once each time the lower one overflows.
+///
+/// Once started, the count wraps around after:
+///
+/// | Scale | Ticks per second | Wraps after |
+/// |:-:|:-:|:-:|
+/// | 1 | 16,777,216 | 256 seconds |
+/// | 64 | 262,144 | about 4.55 hours |
+/// | 256 | 65,536 | about 18.2 hours |
+/// | 1024 | 16,384 | about 72.8 hours |
+///
+/// To get seconds, divide the count by the ticks per second for the scale
+/// used. With a scale of 1 each tick is one CPU cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct WideTimer {
+  low: Timer,
+}
+impl WideTimer {
+  /// Uses `low` and the timer after it.
+  ///
+  /// Pick the pair so that it doesn't conflict with other uses of the timers
+  /// (such as a sound driver that uses timers 0 and 1).
+  ///
+  /// ## Panics
+  /// * `low` can't be timer 3, since there's no timer after it.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(low: Timer) -> Self {
+    assert!(!matches!(low, Timer::Timer3), "timer 3 has no timer after it");
+    Self { low }
+  }
+
+  /// The lower timer of the pair.
+  #[inline]
+  #[must_use]
+  pub const fn low(self) -> Timer {
+    self.low
+  }
+
+  /// The upper timer of the pair.
+  #[inline]
+  #[must_use]
+  pub const fn high(self) -> Timer {
+    match self.low {
+      Timer::Timer0 => Timer::Timer1,
+      Timer::Timer1 => Timer::Timer2,
+      _ => Timer::Timer3,
+    }
+  }
+}
+#[cfg(feature = "on_gba")]
+impl WideTimer {
+  /// Starts counting from 0 with the lower timer at the scale given.
+  #[inline]
+  pub fn start(self, scale: TimerScale) {
+    self.low.stop();
+    self.high().start(0, TimerControl::new().with_cascade(true));
+    self.low.start(0, TimerControl::new().with_scale(scale));
+  }
+
+  /// Stops both timers. The count keeps its value.
+  #[inline]
+  pub fn stop(self) {
+    self.low.stop();
+    self.high().stop();
+  }
+
+  /// Gets the current count.
+  #[inline]
+  #[must_use]
+  pub fn read(self) -> u32 {
+    let (high, low) = untorn_read(|| self.high().count(), || self.low.count());
+    (u32::from(high) << 16) | u32::from(low)
+  }
+}
+
+/// All four timers cascaded together into a single 64-bit counter.
+///
+/// At a scale of 1 this counts CPU cycles, and won't wrap around for over
+/// 34,000 years, so it's good for profiling entire play sessions. Of course,
+/// it uses every timer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct WideTimer64;
+#[cfg(feature = "on_gba")]
+impl WideTimer64 {
+  /// Starts counting from 0 with timer 0 at the scale given.
+  #[inline]
+  pub fn start(self, scale: TimerScale) {
+    Timer::Timer0.stop();
+    let cascade = TimerControl::new().with_cascade(true);
+    Timer::Timer3.start(0, cascade);
+    Timer::Timer2.start(0, cascade);
+    Timer::Timer1.start(0, cascade);
+    Timer::Timer0.start(0, TimerControl::new().with_scale(scale));
+  }
+
+  /// Stops all four timers. The count keeps its value.
+  #[inline]
+  pub fn stop(self) {
+    for timer in [Timer::Timer0, Timer::Timer1, Timer::Timer2, Timer::Timer3] {
+      timer.stop();
+    }
+  }
+
+  /// Gets the current count.
+  #[inline]
+  #[must_use]
+  pub fn read(self) -> u64 {
+    let read_high = || {
+      let (t3, t2) = (Timer::Timer3.count(), Timer::Timer2.count());
+      (t3, t2, Timer::Timer1.count())
+    };
+    let ((t3, t2, t1), t0) = untorn_read(read_high, || Timer::Timer0.count());
+    (u64::from(t3) << 48)
+      | (u64::from(t2) << 32)
+      | (u64::from(t1) << 16)
+      | u64::from(t0)
+  }
+}
+
+/// The number of CPU cycles per second.
+pub const CPU_CYCLES_PER_SECOND: u32 = 16_777_216;
+
+/// Converts milliseconds to CPU cycles, rounding down.
+///
+/// This is exact (aside from the rounding) for the whole `u32` range of
+/// milliseconds, but the output saturates at `u32::MAX` (about 256 seconds).
+#[inline]
+#[must_use]
+pub const fn ms_to_cycles(ms: u32) -> u32 {
+  // 125 ms is exactly 2,097,152 cycles, and the 124 ms max remainder times the
+  // cycles per second still fits in a u32.
+  const CYCLES_PER_125_MS: u32 = CPU_CYCLES_PER_SECOND / 8;
+  let whole = (ms / 125).saturating_mul(CYCLES_PER_125_MS);
+  let rest = (ms % 125) * CPU_CYCLES_PER_SECOND / 1000;
+  whole.saturating_add(rest)
+}
+
+/// The timer used by [`busy_wait_cycles`] and [`delay_ms`].
+#[cfg(feature = "on_gba")]
+static DELAY_TIMER: crate::gba_cell::GbaCell<u8> =
+  crate::gba_cell::GbaCell::new(Timer::Timer1 as u8);
+
+/// Sets which timer [`busy_wait_cycles`] and [`delay_ms`] use.
+///
+/// The default is timer 1. That keeps clear of the default timers of the rest
+/// of the crate: timer 0 for a [sound stream](crate::sound::stream), timers 2
+/// and 3 for the [`profile`](crate::profile) stopwatch, and timer 3 for the
+/// test runner's timeout. The crate also waits with
+/// [`busy_wait_cycles`] itself (eg: when writing to flash or EEPROM saves), so
+/// if timer 1 is needed for something else, pick another timer here that's
+/// free.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn set_delay_timer(timer: Timer) {
+  DELAY_TIMER.write(timer as u8);
+}
+
+/// Blocks for at least `n` CPU cycles.
+///
+/// This uses a timer rather than a counted loop, so it's accurate no matter
+/// what the wait state settings are (or where the code is running from). The
+/// wait is split into chunks of at most 32,768 cycles, and each chunk can run
+/// over by the few cycles it takes to poll the timer.
+///
+/// The timer (set with [`set_delay_timer`]) is restarted and then left
+/// stopped. This **blocks** the CPU, and shouldn't be called from an
+/// interrupt handler, since it may also be in use by the code that was
+/// interrupted.
+///
+/// ## Panics
+/// * With debug assertions on, this checks that the timer isn't already
+///   running, since then something else owns it (see [`set_delay_timer`]).
+#[cfg(feature = "on_gba")]
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn busy_wait_cycles(n: u32) {
+  const CHUNK: u32 = 0x8000;
+  let timer = match DELAY_TIMER.read() {
+    0 => Timer::Timer0,
+    1 => Timer::Timer1,
+    2 => Timer::Timer2,
+    _ => Timer::Timer3,
+  };
+  if cfg!(debug_assertions) {
+    assert!(
+      !timer.is_running(),
+      "the delay timer is already running (see set_delay_timer)"
+    );
+  }
+  let mut left = n;
+  while left > 0 {
+    let chunk = left.min(CHUNK) as u16;
+    timer.start(0, TimerControl::new().with_scale(TimerScale::_1));
+    while timer.count() < chunk {}
+    left -= u32::from(chunk);
+  }
+  timer.stop();
+}
+
+/// Blocks for at least `ms` milliseconds.
+///
+/// This is [`busy_wait_cycles`] done in chunks of 125 ms, so any `u32` can be
+/// used. The same warnings apply: it blocks, and shouldn't be used from an
+/// interrupt handler.
+///
+/// ## Panics
+/// * With debug assertions on, the same checks as [`busy_wait_cycles`].
+#[cfg(feature = "on_gba")]
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn delay_ms(ms: u32) {
+  for _ in 0..(ms / 125) {
+    busy_wait_cycles(ms_to_cycles(125));
+  }
+  busy_wait_cycles(ms_to_cycles(ms % 125));
+}
+
+/// Blocks until `n` vblanks have started.
+///
+/// This uses [`spin_until_vblank`](crate::video::spin_until_vblank), so it
+/// works without any interrupts being set up. It blocks, and shouldn't be
+/// used from an interrupt handler.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn delay_frames(n: u32) {
+  for _ in 0..n {
+    crate::video::spin_until_vblank();
+  }
+}
+
+/// Sets the function called when `timer` overflows.
+///
+/// This is the timer's entry of the
+/// [handler table](crate::interrupts::set_handler), so it's called when
+/// [`RUST_IRQ_HANDLER`](crate::RUST_IRQ_HANDLER) is
+/// [`irq_table_dispatch`](crate::interrupts::irq_table_dispatch). It also
+/// sets or clears the timer's bit in [`IE`](crate::mmio::IE), but doesn't
+/// touch the timer itself: the timer still needs `overflow_irq` set in its
+/// control to actually send the interrupt (see [`periodic_irq`] for a way to
+/// do all of that at once).
+///
+/// The table and `IE` are updated with [`IME`](crate::mmio::IME) off, so this
+/// is safe to call while interrupts are enabled. Passing `None` removes the
+/// handler.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn set_timer_handler(
+  timer: Timer, handler: Option<crate::interrupts::IrqFn>,
+) {
+  use crate::interrupts::{clear_handler, set_handler, Interrupt};
+  let source = match timer {
+    Timer::Timer0 => Interrupt::Timer0,
+    Timer::Timer1 => Interrupt::Timer1,
+    Timer::Timer2 => Interrupt::Timer2,
+    Timer::Timer3 => Interrupt::Timer3,
+  };
+  match handler {
+    Some(handler) => set_handler(source, handler),
+    None => clear_handler(source),
+  }
+}
+
+/// Picks a scale and reload value so that a timer overflows `hz` times per
+/// second.
+///
+/// The smallest scale that can reach the rate is used, and the number of ticks
+/// is rounded to the nearest whole tick, so the actual rate can be slightly
+/// off from `hz` when it doesn't divide the CPU clock evenly.
+///
+/// Gives `None` if `hz` is 0 or more than [`CPU_CYCLES_PER_SECOND`].
+#[inline]
+#[must_use]
+pub const fn overflow_rate_settings(hz: u32) -> Option<(TimerScale, u16)> {
+  if hz == 0 || hz > CPU_CYCLES_PER_SECOND {
+    return None;
+  }
+  let scales = [
+    (TimerScale::_1, 1),
+    (TimerScale::_64, 64),
+    (TimerScale::_256, 256),
+    (TimerScale::_1024, 1024),
+  ];
+  let mut i = 0;
+  while i < scales.len() {
+    let (scale, cycles_per_tick) = scales[i];
+    let ticks_per_second = CPU_CYCLES_PER_SECOND / cycles_per_tick;
+    let ticks = (ticks_per_second + hz / 2) / hz;
+    if ticks >= 1 && ticks <= 0x1_0000 {
+      // A reload of `0x1_0000 - ticks` overflows every `ticks` ticks.
+      return Some((scale, (ticks as u16).wrapping_neg()));
+    }
+    i += 1;
+  }
+  None
+}
+
+/// A timer can't overflow at the rate asked for.
+///
+/// This is what [`periodic_irq`] gives when [`overflow_rate_settings`] has no
+/// settings for the rate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct UnreachableRate;
+
+/// Runs `handler` `hz` times per second from a timer's overflow interrupt.
+///
+/// This sets the handler (with [`set_timer_handler`]), starts the timer with
+/// the settings from [`overflow_rate_settings`] and `overflow_irq` set, and
+/// turns on [`IME`](crate::mmio::IME). You still need
+/// [`RUST_IRQ_HANDLER`](crate::RUST_IRQ_HANDLER) to be
+/// [`irq_table_dispatch`](crate::interrupts::irq_table_dispatch).
+///
+/// ## Failure
+/// * If `hz` can't be reached (see [`overflow_rate_settings`]) nothing is
+///   changed and you get [`UnreachableRate`].
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn periodic_irq(
+  timer: Timer, hz: u32, handler: crate::interrupts::IrqFn,
+) -> Result<(), UnreachableRate> {
+  let Some((scale, reload)) = overflow_rate_settings(hz) else {
+    return Err(UnreachableRate);
+  };
+  set_timer_handler(timer, Some(handler));
+  timer.start(
+    reload,
+    TimerControl::new().with_scale(scale).with_overflow_irq(true),
+  );
+  crate::mmio::IME.write(true);
+  Ok(())
+}
@@ -0,0 +1,283 @@
+//! Sprite animation: cycling an object through a sequence of tiles.
+//!
+//! An [`Animation`] describes the frames, and an [`AnimationPlayer`] steps
+//! through them once per call to [`tick`](AnimationPlayer::tick), which should
+//! be done once per displayed frame (or see
+//! [`AnimationPlayer::timed`]). The player only reports a tile index when
+//! it changes, so OAM only needs to be updated on those frames.
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! static WALK: Animation =
+//!   Animation::new(&[(0, 8), (1, 8), (2, 8), (1, 8)], true);
+//! let mut player = AnimationPlayer::new();
+//! player.play(&WALK);
+//! let mut obj = ObjAttr::new();
+//! loop {
+//!   if let Some(tile) = player.tick() {
+//!     obj.set_tile_id(tile);
+//!   }
+//!   // ...
+//! }
+//! ```
+
+use crate::time::FrameInstant;
+
+/// A sequence of animation frames.
+///
+/// Each frame is a `(tile_index, duration)` pair, with the duration counted in
+/// displayed frames. A duration of 0 counts as 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Animation {
+  frames: &'static [(u16, u16)],
+  looping: bool,
+}
+impl Animation {
+  /// Makes an animation from a list of frames.
+  ///
+  /// If `looping` is set the animation starts over after the last frame,
+  /// otherwise it stops on the last frame.
+  ///
+  /// ## Panics
+  /// * There must be at least one frame.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(frames: &'static [(u16, u16)], looping: bool) -> Self {
+    assert!(!frames.is_empty(), "an animation needs at least one frame");
+    Self { frames, looping }
+  }
+
+  /// The frames of the animation.
+  #[inline]
+  #[must_use]
+  pub const fn frames(&self) -> &'static [(u16, u16)] {
+    self.frames
+  }
+
+  /// If the animation starts over after the last frame.
+  #[inline]
+  #[must_use]
+  pub const fn looping(&self) -> bool {
+    self.looping
+  }
+
+  /// The total length of one play through, in displayed frames.
+  #[inline]
+  #[must_use]
+  pub const fn total_duration(&self) -> u32 {
+    let mut total = 0;
+    let mut i = 0;
+    while i < self.frames.len() {
+      total += frame_duration(self.frames[i]) as u32;
+      i += 1;
+    }
+    total
+  }
+}
+
+#[inline]
+#[must_use]
+const fn frame_duration(frame: (u16, u16)) -> u16 {
+  if frame.1 == 0 {
+    1
+  } else {
+    frame.1
+  }
+}
+
+/// Plays an [`Animation`].
+///
+/// A player made with [`timed`](Self::timed) counts the frames that really
+/// went by between ticks, so the animation keeps the same speed when frames
+/// are missed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct AnimationPlayer {
+  animation: Option<&'static Animation>,
+  frame: usize,
+  /// Displayed frames of the current animation frame so far, 0 means that
+  /// the first frame hasn't been shown yet.
+  elapsed: u16,
+  paused: bool,
+  finished: bool,
+  timed: bool,
+  last_tick: Option<FrameInstant>,
+}
+impl AnimationPlayer {
+  /// Makes a player with no animation.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self {
+      animation: None,
+      frame: 0,
+      elapsed: 0,
+      paused: false,
+      finished: false,
+      timed: false,
+      last_tick: None,
+    }
+  }
+
+  /// Makes a player with no animation, that advances by the frames since the
+  /// last [`tick`](Self::tick) (from [`frame_count`](crate::time::frame_count))
+  /// rather than by one.
+  ///
+  /// The vblank interrupt has to be on (see the [`time`](crate::time)
+  /// module). Time spent paused doesn't count.
+  #[cfg(feature = "on_gba")]
+  #[inline]
+  #[must_use]
+  pub const fn timed() -> Self {
+    Self { timed: true, ..Self::new() }
+  }
+
+  /// Starts playing an animation from the beginning.
+  ///
+  /// This always starts over, even if the animation was already playing, and
+  /// it also un-pauses the player. The next [`tick`](Self::tick) gives the
+  /// first frame's tile.
+  #[inline]
+  pub fn play(&mut self, animation: &'static Animation) {
+    *self =
+      Self { animation: Some(animation), timed: self.timed, ..Self::new() };
+  }
+
+  /// Starts the current animation over from the beginning.
+  ///
+  /// This is how to replay an animation that isn't looping once it finishes.
+  #[inline]
+  pub fn restart(&mut self) {
+    if let Some(animation) = self.animation {
+      self.play(animation);
+    }
+  }
+
+  /// Stops playing, clearing the current animation.
+  #[inline]
+  pub fn stop(&mut self) {
+    *self = Self { timed: self.timed, ..Self::new() };
+  }
+
+  /// Pauses the player, so that [`tick`](Self::tick) does nothing.
+  #[inline]
+  pub fn pause(&mut self) {
+    self.paused = true;
+  }
+
+  /// Resumes the player after a pause.
+  #[inline]
+  pub fn resume(&mut self) {
+    self.paused = false;
+  }
+
+  /// If the player is paused.
+  #[inline]
+  #[must_use]
+  pub const fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  /// If a non-looping animation has finished showing its last frame.
+  ///
+  /// Looping animations never finish.
+  #[inline]
+  #[must_use]
+  pub const fn is_finished(&self) -> bool {
+    self.finished
+  }
+
+  /// The current animation, if any.
+  #[inline]
+  #[must_use]
+  pub const fn animation(&self) -> Option<&'static Animation> {
+    self.animation
+  }
+
+  /// The tile index of the current frame, if there's an animation.
+  #[inline]
+  #[must_use]
+  pub fn current_tile(&self) -> Option<u16> {
+    self.animation.map(|a| a.frames[self.frame].0)
+  }
+
+  /// Advances the animation by one displayed frame (or, if
+  /// [`timed`](Self::timed), the frames since the last tick).
+  ///
+  /// Gives the new tile index on frames where the tile changes (including the
+  /// first frame of an animation), and `None` on all other frames (or when
+  /// paused, finished, or there's no animation).
+  ///
+  /// ## Panics
+  /// * With debug assertions on, if this is [`timed`](Self::timed) and the
+  ///   vblank interrupt is off (see [`frame_count`](crate::time::frame_count)).
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn tick(&mut self) -> Option<u16> {
+    #[cfg(feature = "on_gba")]
+    if self.timed {
+      let now = FrameInstant::now();
+      let frames = self.last_tick.map_or(1, |last| now.since(last));
+      self.last_tick = Some(now);
+      return self.advance(frames);
+    }
+    self.advance(1)
+  }
+
+  /// Advances the animation by `frames` displayed frames.
+  ///
+  /// This gives the tile at the end if it changed at any point on the way
+  /// (and otherwise `None`), the same as [`tick`](Self::tick).
+  #[inline]
+  pub fn advance(&mut self, frames: u32) -> Option<u16> {
+    let animation = self.animation?;
+    // Past one play through, a looping animation repeats, and one that
+    // doesn't loop has finished, so there's no need to step through all of
+    // them.
+    let total = animation.total_duration();
+    let frames = if frames <= total {
+      frames
+    } else if animation.looping {
+      total + frames % total
+    } else {
+      total + 1
+    };
+    let mut changed = None;
+    for _ in 0..frames {
+      if let Some(tile) = self.step() {
+        changed = Some(tile);
+      }
+    }
+    changed
+  }
+
+  /// Advances the animation by one displayed frame.
+  #[inline]
+  fn step(&mut self) -> Option<u16> {
+    let animation = self.animation?;
+    if self.paused || self.finished {
+      return None;
+    }
+    let frames = animation.frames;
+    if self.elapsed == 0 {
+      self.elapsed = 1;
+      return Some(frames[0].0);
+    }
+    if self.elapsed < frame_duration(frames[self.frame]) {
+      self.elapsed += 1;
+      return None;
+    }
+    let old_tile = frames[self.frame].0;
+    if self.frame + 1 < frames.len() {
+      self.frame += 1;
+    } else if animation.looping {
+      self.frame = 0;
+    } else {
+      self.finished = true;
+      return None;
+    }
+    self.elapsed = 1;
+    let tile = frames[self.frame].0;
+    (tile != old_tile).then_some(tile)
+  }
+}
@@ -0,0 +1,292 @@
+//! Scrolling a text background over a tile map larger than the background.
+//!
+//! A text background is at most 64x64 tiles, and it wraps around at its
+//! edges. A level can be much larger than that if only the part in view is
+//! kept in the background: as the camera moves, each new column or row of
+//! tiles that scrolls into view is copied from the full map into the
+//! background, over the column or row that just scrolled out of view on the
+//! other side. This is "streaming" the map, and a [`TiledCamera`] does it.
+//!
+//! The screen is 240x160 pixels, so at most 31 columns and 21 rows of tiles
+//! are in view at once (when the view isn't lined up with the tile grid).
+//! That fits within even a 32x32 background, so every background size works.
+//! A larger background doesn't stream any less, it just uses more VRAM.
+//!
+//! The map can be anything that's a [`MapSource`], such as a plain
+//! [`TileGrid`](super::tilemap::TileGrid) or a
+//! [`MetatileMap`](super::tilemap::MetatileMap).
+//!
+//! ## Cost
+//!
+//! A camera that moves at most 8 pixels on each axis in a frame brings at most
+//! one new column and one new row into view, so [`update`](TiledCamera::update)
+//! copies at most 21 + 31 entries (see [`StreamStrips`]). Moving further than
+//! that in one frame copies the whole view (651 entries) instead, the same as
+//! [`jump_to`](TiledCamera::jump_to).
+
+use super::{
+  tilemap::{copy_region, screenblock_count, MapSource},
+  *,
+};
+
+/// The most tile columns in view at once.
+const VIEW_COLUMNS: usize = 31;
+
+/// The most tile rows in view at once.
+const VIEW_ROWS: usize = 21;
+
+/// The map tile at pixel `px`, and the last map tile in view when the screen
+/// starts at `px` and is `extent` pixels long.
+#[inline]
+#[must_use]
+const fn tile_span(px: usize, extent: usize) -> (usize, usize) {
+  (px / 8, (px + extent - 1) / 8)
+}
+
+/// The line of tiles (on one axis) that comes into view when the screen moves
+/// from `old` to `new`. Gives `Err` if more than one line came into view.
+#[inline]
+const fn entering(
+  old: usize, new: usize, extent: usize,
+) -> Result<Option<usize>, ()> {
+  let (old_first, old_last) = tile_span(old, extent);
+  let (new_first, new_last) = tile_span(new, extent);
+  if new_last > old_last {
+    if new_last - old_last == 1 {
+      Ok(Some(new_last))
+    } else {
+      Err(())
+    }
+  } else if new_first < old_first {
+    if old_first - new_first == 1 {
+      Ok(Some(new_first))
+    } else {
+      Err(())
+    }
+  } else {
+    Ok(None)
+  }
+}
+
+/// The tiles of a map to copy into a background when the camera moves.
+///
+/// Moving right or down, the new line is the last one in view, and moving
+/// left or up it's the first one in view.
+///
+/// When the camera crosses both a column and a row boundary in the same frame
+/// (moving diagonally), the column has to be copied for all the rows in the
+/// *new* view, and the row for all the columns in the new view. The tile in
+/// the corner where they cross is in both strips, and it's the one tile that
+/// neither the old column nor the old row would have covered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamStrips {
+  /// The map column that came into view, if any.
+  pub column: Option<usize>,
+  /// The map row that came into view, if any.
+  pub row: Option<usize>,
+  /// If the camera moved too far for strips, so the whole view must be
+  /// copied. When this is set, `column` and `row` are `None`.
+  pub redraw: bool,
+}
+impl StreamStrips {
+  /// Nothing to copy.
+  pub const NONE: Self = Self { column: None, row: None, redraw: false };
+
+  /// Copy the whole view.
+  pub const REDRAW: Self = Self { column: None, row: None, redraw: true };
+
+  /// The strips to copy when the top left of the screen moves from map pixel
+  /// `old` to map pixel `new`.
+  #[inline]
+  #[must_use]
+  pub const fn between(old: (usize, usize), new: (usize, usize)) -> Self {
+    let column = entering(old.0, new.0, 240);
+    let row = entering(old.1, new.1, 160);
+    match (column, row) {
+      (Ok(column), Ok(row)) => Self { column, row, redraw: false },
+      _ => Self::REDRAW,
+    }
+  }
+
+  /// The number of map entries these strips copy, at most.
+  ///
+  /// This counts a full 31 column by 21 row view, since the exact number
+  /// depends on how the view lines up with the tile grid.
+  #[inline]
+  #[must_use]
+  pub const fn max_entries(self) -> usize {
+    if self.redraw {
+      VIEW_COLUMNS * VIEW_ROWS
+    } else {
+      let columns = if self.column.is_some() { VIEW_ROWS } else { 0 };
+      let rows = if self.row.is_some() { VIEW_COLUMNS } else { 0 };
+      columns + rows
+    }
+  }
+}
+
+/// A camera over a tile map, streaming the part in view into a text
+/// background.
+///
+/// The camera position is the map pixel at the top left of the screen. It's
+/// kept within the map, so the map must be at least as big as the screen (30
+/// by 20 tiles).
+///
+/// The background itself has to be set up separately (eg: with
+/// [`setup_text_background`]), using the same screenblock and size given
+/// here.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// use gba::video::{camera::TiledCamera, tilemap::TileGrid};
+/// static MAP: [TextEntry; 128 * 128] = [TextEntry::new(); 128 * 128];
+/// setup_text_background(BgLayer::Bg0, 0, 28, TextBackgroundSize::_32x32, 0);
+/// let size = TextBackgroundSize::_32x32;
+/// let map = TileGrid::new(&MAP, 128, 128);
+/// let mut camera = TiledCamera::new(map, BgLayer::Bg0, 28, size);
+/// camera.jump_to(0, 0);
+/// let (mut x, mut y) = (0, 0);
+/// loop {
+///   let (dx, dy) = KEYINPUT.read().dpad();
+///   x = x.saturating_add_signed(dx.to_i32() as isize);
+///   y = y.saturating_add_signed(dy.to_i32() as isize);
+///   VBlankIntrWait();
+///   camera.update(x, y);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TiledCamera<M> {
+  map: M,
+  width: usize,
+  height: usize,
+  layer: BgLayer,
+  screenblock: usize,
+  size: TextBackgroundSize,
+  x: usize,
+  y: usize,
+  drawn: bool,
+}
+impl<M: MapSource> TiledCamera<M> {
+  /// Makes a camera over a map, shown on `layer` with tilemap starting at
+  /// `screenblock`.
+  ///
+  /// Nothing is written until the first [`update`](Self::update) or
+  /// [`jump_to`](Self::jump_to), which draws the whole view.
+  ///
+  /// ## Panics
+  /// * The map must be at least 30 by 20 tiles.
+  /// * The background's tilemap must fit within background VRAM.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn new(
+    map: M, layer: BgLayer, screenblock: usize, size: TextBackgroundSize,
+  ) -> Self {
+    let (width, height) = map.size();
+    assert!(width >= 30 && height >= 20, "map smaller than the screen");
+    assert!(
+      screenblock + screenblock_count(size) <= 32,
+      "tilemap out of range"
+    );
+    Self {
+      map,
+      width,
+      height,
+      layer,
+      screenblock,
+      size,
+      x: 0,
+      y: 0,
+      drawn: false,
+    }
+  }
+
+  /// The map being shown.
+  #[inline]
+  #[must_use]
+  pub const fn map(&self) -> &M {
+    &self.map
+  }
+
+  /// The camera position, in map pixels.
+  #[inline]
+  #[must_use]
+  pub const fn position(&self) -> (usize, usize) {
+    (self.x, self.y)
+  }
+
+  /// The furthest the camera can go right and down, in map pixels.
+  #[inline]
+  #[must_use]
+  pub const fn max_position(&self) -> (usize, usize) {
+    (self.width * 8 - 240, self.height * 8 - 160)
+  }
+
+  /// The map's width and height, in tiles.
+  #[inline]
+  #[must_use]
+  pub const fn map_size(&self) -> (usize, usize) {
+    (self.width, self.height)
+  }
+
+  /// Moves the camera, copying what came into view and setting the layer's
+  /// scroll.
+  ///
+  /// The position is clamped to the map. This should be called during vblank,
+  /// so the new tiles and the new scroll show up on the same frame. Gives the
+  /// strips that were copied.
+  #[inline]
+  pub fn update(&mut self, x: usize, y: usize) -> StreamStrips {
+    let (max_x, max_y) = self.max_position();
+    let new = (x.min(max_x), y.min(max_y));
+    let strips = if self.drawn {
+      StreamStrips::between((self.x, self.y), new)
+    } else {
+      StreamStrips::REDRAW
+    };
+    (self.x, self.y) = new;
+    let (first_column, last_column) = tile_span(self.x, 240);
+    let (first_row, last_row) = tile_span(self.y, 160);
+    let columns = last_column - first_column + 1;
+    let rows = last_row - first_row + 1;
+    if strips.redraw {
+      self.copy(first_column, first_row, columns, rows);
+    } else {
+      if let Some(column) = strips.column {
+        self.copy(column, first_row, 1, rows);
+      }
+      if let Some(row) = strips.row {
+        self.copy(first_column, row, columns, 1);
+      }
+    }
+    self.drawn = true;
+    BgScroll::new(self.x as u16, self.y as u16).write_to(self.layer);
+    strips
+  }
+
+  /// Moves the camera and copies the whole view, no matter how far it moved.
+  ///
+  /// This is for the start of a level, or a teleport. A whole view is many
+  /// times more to copy than a strip, so during vblank it doesn't leave much
+  /// time for anything else. When the display can go blank for a moment
+  /// (such as while loading a level), do it in forced blank instead (see
+  /// [`with_forced_blank`]).
+  #[inline]
+  pub fn jump_to(&mut self, x: usize, y: usize) {
+    self.drawn = false;
+    self.update(x, y);
+  }
+
+  /// Copies a rectangle of the map (in map tiles) into the background, at
+  /// the same tile positions (wrapped to the background size).
+  fn copy(&self, x: usize, y: usize, width: usize, height: usize) {
+    copy_region(
+      &self.map,
+      (x, y),
+      (x, y),
+      (width, height),
+      self.screenblock,
+      self.size,
+    );
+  }
+}
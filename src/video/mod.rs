@@ -103,12 +103,30 @@ use bytemuck::{Pod, TransparentWrapper, Zeroable};
 use crate::prelude::*;
 use crate::{
   macros::{
-    pub_const_fn_new_zeroed, u16_bool_field, u16_enum_field, u16_int_field,
+    pub_const_fn_new_zeroed, register_enum_field, register_int_field,
+    u16_bool_field,
   },
-  mem::{copy_u32x8_unchecked, set_u32x80_unchecked},
+  math::Vec2,
+  mem::{copy_u32x8_unchecked, copy_words_to, set_u32x80_unchecked},
 };
 
+pub mod animation;
+#[cfg(feature = "on_gba")]
+pub mod blit;
+pub mod camera;
+pub mod mode3;
+pub mod mode4;
+pub mod mode5;
 pub mod obj;
+pub mod palram;
+mod raster;
+pub mod sprite_alloc;
+pub mod sprite_sheet;
+pub mod sprite_sort;
+pub mod text;
+pub mod tile_alloc;
+pub mod tilemap;
+pub mod vram;
 
 /// An RGB555 color value (packed into `u16`).
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -125,11 +143,13 @@ impl Color {
   pub const MAGENTA: Color = Color(0b0_11111_00000_11111);
   pub const CYAN: Color = Color(0b0_11111_11111_00000);
   pub const WHITE: Color = Color(0b0_11111_11111_11111);
+  pub const GRAY: Color = Color(0b0_10000_10000_10000);
+  pub const ORANGE: Color = Color(0b0_00000_10000_11111);
 
   pub_const_fn_new_zeroed!();
-  u16_int_field!(0 - 4, red, with_red);
-  u16_int_field!(5 - 9, green, with_green);
-  u16_int_field!(10 - 14, blue, with_blue);
+  register_int_field!(0 - 4, red, with_red, RED_MASK);
+  register_int_field!(5 - 9, green, with_green, GREEN_MASK);
+  register_int_field!(10 - 14, blue, with_blue, BLUE_MASK);
 
   /// Constructs a new color value from the given channel values.
   #[inline]
@@ -137,6 +157,63 @@ impl Color {
   pub const fn from_rgb(r: u16, g: u16, b: u16) -> Self {
     Self(r & 0b11111 | (g & 0b11111) << 5 | (b & 0b11111) << 10)
   }
+
+  /// Constructs a new color value from 8-bit channel values, such as the
+  /// usual `#RRGGBB` notation.
+  ///
+  /// Each channel is scaled to `0..=31` and rounded to the nearest value, so
+  /// 0 and 255 are exactly 0 and 31.
+  ///
+  /// ```
+  /// # use gba::video::Color;
+  /// const SKY: Color = Color::from_rgb8(0x87, 0xCE, 0xEB);
+  /// assert_eq!(SKY, Color::from_rgb(16, 25, 29));
+  /// ```
+  #[inline]
+  #[must_use]
+  pub const fn from_rgb8(r: u8, g: u8, b: u8) -> Self {
+    const fn reduce(v: u8) -> u16 {
+      (v as u16 * 31 + 127) / 255
+    }
+    Self::from_rgb(reduce(r), reduce(g), reduce(b))
+  }
+
+  /// Mixes this color `weight / 16` of the way towards `other`.
+  ///
+  /// Each channel is interpolated separately and rounded. A `weight` of 0 is
+  /// exactly `self` and 16 is exactly `other`, the same scale as the
+  /// hardware's blend coefficients. Weights over 16 are clamped to 16.
+  #[inline]
+  #[must_use]
+  pub const fn blend(self, other: Self, weight: u16) -> Self {
+    let w = clamp_blend(weight);
+    const fn mix(a: u16, b: u16, w: u16) -> u16 {
+      (a * (16 - w) + b * w + 8) / 16
+    }
+    Self::from_rgb(
+      mix(self.red(), other.red(), w),
+      mix(self.green(), other.green(), w),
+      mix(self.blue(), other.blue(), w),
+    )
+  }
+
+  /// The perceived brightness of this color, in `0..=31`.
+  ///
+  /// This weighs the channels like the usual (BT.601) luma formula, so green
+  /// counts the most and blue the least.
+  #[inline]
+  #[must_use]
+  pub const fn luminance(self) -> u16 {
+    (self.red() * 77 + self.green() * 150 + self.blue() * 29 + 128) >> 8
+  }
+
+  /// A gray with the same [`luminance`](Self::luminance) as this color.
+  #[inline]
+  #[must_use]
+  pub const fn grayscale(self) -> Self {
+    let l = self.luminance();
+    Self::from_rgb(l, l, l)
+  }
 }
 
 unsafe impl Zeroable for Color {}
@@ -168,7 +245,10 @@ pub enum VideoMode {
 pub struct DisplayControl(u16);
 impl DisplayControl {
   pub_const_fn_new_zeroed!();
-  u16_enum_field!(0 - 2: VideoMode, video_mode, with_video_mode);
+  register_enum_field!(
+    0 - 2: VideoMode [_0, _1, _2, _3, _4, _5],
+    video_mode, with_video_mode, VIDEO_MODE_MASK
+  );
   u16_bool_field!(4, show_frame1, with_show_frame1);
   u16_bool_field!(5, hblank_oam_free, with_hblank_oam_free);
   u16_bool_field!(6, obj_vram_1d, with_obj_vram_1d);
@@ -183,6 +263,194 @@ impl DisplayControl {
   u16_bool_field!(15, enable_obj_win, with_enable_obj_win);
 }
 
+/// One of the two pages (frames) of a page flipped bitmap mode.
+///
+/// Video modes 4 and 5 each have two bitmaps in VRAM, and the `show_frame1`
+/// bit of [`DisplayControl`] selects which one is displayed. The usual approach
+/// is to draw to the page that's *not* displayed and then flip the pages
+/// during vblank, so that the player never sees a partly drawn frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BitmapPage {
+  #[default]
+  Page0 = 0,
+  Page1 = 1,
+}
+impl BitmapPage {
+  /// The frame index of this page within the VRAM of the video mode.
+  #[inline]
+  #[must_use]
+  pub const fn index(self) -> usize {
+    self as usize
+  }
+
+  /// The other page.
+  #[inline]
+  #[must_use]
+  pub const fn other(self) -> Self {
+    match self {
+      Self::Page0 => Self::Page1,
+      Self::Page1 => Self::Page0,
+    }
+  }
+}
+
+/// The page currently selected for display by [`DISPCNT`].
+#[inline]
+#[must_use]
+pub fn displayed_page() -> BitmapPage {
+  if DISPCNT.read().show_frame1() {
+    BitmapPage::Page1
+  } else {
+    BitmapPage::Page0
+  }
+}
+
+/// Toggles which page is displayed, returning the page that's now offscreen.
+///
+/// The returned page is the one that should be drawn to next. You should
+/// generally only flip the page during vblank.
+#[inline]
+pub fn flip_page() -> BitmapPage {
+  let dispcnt = DISPCNT.read();
+  let shown = !dispcnt.show_frame1();
+  DISPCNT.write(dispcnt.with_show_frame1(shown));
+  if shown {
+    BitmapPage::Page0
+  } else {
+    BitmapPage::Page1
+  }
+}
+
+/// All of the settings of [`DISPCNT`], as one value.
+///
+/// A [`DisplayControl`] is the register's bits. This is the same settings
+/// with a type for each one, so that a whole video setup (eg: for one screen
+/// of a game) can be written down in one place, and then set with a single
+/// write by [`apply`](Self::apply):
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// VideoConfig {
+///   mode: VideoMode::_0,
+///   obj_mapping: ObjTileMapping::OneD,
+///   show_bg: [true, true, false, false],
+///   show_obj: true,
+///   ..VideoConfig::new()
+/// }
+/// .apply();
+/// ```
+///
+/// The `obj_mapping` is also what a [`TileAllocator`] and
+/// [`copy_obj_4bpp`] need to know (see [`ObjTileMapping`]), so keep them
+/// matching.
+///
+/// Showing a background that isn't in the video mode (such as BG0 in mode 3)
+/// doesn't do anything.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct VideoConfig {
+  /// The video mode.
+  pub mode: VideoMode,
+  /// The page shown in video modes 4 and 5.
+  pub page: BitmapPage,
+  /// How object tiles are found in object VRAM.
+  pub obj_mapping: ObjTileMapping,
+  /// Lets OAM be written during hblank.
+  ///
+  /// This gives the CPU time to change OAM during hblank (eg: for a raster
+  /// effect), but it also takes away some of the time the hardware has to
+  /// draw objects, so fewer fit on each line.
+  pub hblank_oam_free: bool,
+  /// Blanks the whole display, which allows any video memory access.
+  pub forced_blank: bool,
+  /// The backgrounds to show, by [`BgLayer::index`].
+  pub show_bg: [bool; 4],
+  /// If objects are shown.
+  pub show_obj: bool,
+  /// If window 0 is on.
+  pub enable_win0: bool,
+  /// If window 1 is on.
+  pub enable_win1: bool,
+  /// If the object window is on.
+  pub enable_obj_win: bool,
+}
+impl VideoConfig {
+  /// Video mode 0 with nothing shown and 2D object mapping, the same as a
+  /// [`DISPCNT`] of 0.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self::from_display_control(DisplayControl::new())
+  }
+
+  /// The settings in a [`DisplayControl`].
+  #[inline]
+  #[must_use]
+  pub const fn from_display_control(d: DisplayControl) -> Self {
+    Self {
+      mode: d.video_mode(),
+      page: if d.show_frame1() { BitmapPage::Page1 } else { BitmapPage::Page0 },
+      obj_mapping: if d.obj_vram_1d() {
+        ObjTileMapping::OneD
+      } else {
+        ObjTileMapping::TwoD
+      },
+      hblank_oam_free: d.hblank_oam_free(),
+      forced_blank: d.forced_blank(),
+      show_bg: [d.show_bg0(), d.show_bg1(), d.show_bg2(), d.show_bg3()],
+      show_obj: d.show_obj(),
+      enable_win0: d.enable_win0(),
+      enable_win1: d.enable_win1(),
+      enable_obj_win: d.enable_obj_win(),
+    }
+  }
+
+  /// These settings as a [`DisplayControl`].
+  #[inline]
+  #[must_use]
+  pub const fn to_display_control(&self) -> DisplayControl {
+    DisplayControl::new()
+      .with_video_mode(self.mode)
+      .with_show_frame1(matches!(self.page, BitmapPage::Page1))
+      .with_hblank_oam_free(self.hblank_oam_free)
+      .with_obj_vram_1d(matches!(self.obj_mapping, ObjTileMapping::OneD))
+      .with_forced_blank(self.forced_blank)
+      .with_show_bg0(self.show_bg[0])
+      .with_show_bg1(self.show_bg[1])
+      .with_show_bg2(self.show_bg[2])
+      .with_show_bg3(self.show_bg[3])
+      .with_show_obj(self.show_obj)
+      .with_enable_win0(self.enable_win0)
+      .with_enable_win1(self.enable_win1)
+      .with_enable_obj_win(self.enable_obj_win)
+  }
+
+  /// The settings that [`DISPCNT`] has now.
+  #[inline]
+  #[must_use]
+  pub fn read_current() -> Self {
+    Self::from_display_control(DISPCNT.read())
+  }
+
+  /// Writes these settings to [`DISPCNT`], all at once.
+  ///
+  /// Changing the mode or the object mapping while the display is drawing can
+  /// show a glitched frame, so that's usually done during vblank (or in
+  /// forced blank).
+  #[inline]
+  pub fn apply(&self) {
+    DISPCNT.write(self.to_display_control());
+  }
+}
+
+/// [`DISPSTAT`]: Display status and interrupt control.
+///
+/// * The `currently_*` flags (bits 0-2) are read-only status flags, and writes
+///   to them are ignored. Use [`read_only_flags`](Self::read_only_flags) and
+///   [`without_read_only_flags`](Self::without_read_only_flags) to split a
+///   value read from `DISPSTAT` into the status part and the settings part.
+/// * The `irq_*` bits enable the display interrupts.
+/// * The `vcount_setting` is the scanline that sets `currently_vcount`, and
+///   fires a vcount interrupt if `irq_vcount` is set.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct DisplayStatus(u16);
@@ -194,23 +462,482 @@ impl DisplayStatus {
   u16_bool_field!(3, irq_vblank, with_irq_vblank);
   u16_bool_field!(4, irq_hblank, with_irq_hblank);
   u16_bool_field!(5, irq_vcount, with_irq_vcount);
-  u16_int_field!(8 - 15, vcount_setting, with_vcount_setting);
+  register_int_field!(
+    8 - 15,
+    vcount_setting,
+    with_vcount_setting,
+    VCOUNT_SETTING_MASK
+  );
+
+  /// Only the read-only status flags of this value, with all settings cleared.
+  #[inline]
+  #[must_use]
+  pub const fn read_only_flags(self) -> Self {
+    Self(self.0 & 0b111)
+  }
+
+  /// Only the settings of this value, with the read-only flags cleared.
+  #[inline]
+  #[must_use]
+  pub const fn without_read_only_flags(self) -> Self {
+    Self(self.0 & !0b111)
+  }
+}
+
+/// Turns on the undocumented "green swap" effect.
+///
+/// With [`GREEN_SWAP`] set, each pair of pixels on a line (x = 0 and 1, 2
+/// and 3, and so on) swaps their green channels. The whole image is still
+/// there, offset only in green, which gives a cheap shimmer (or a fake
+/// interlace when toggled every frame).
+///
+/// Nothing else in the hardware uses this, and it's off at boot. If the
+/// screen ever looks faintly "striped" in green, something has set it by
+/// accident (such as a stray 32-bit write to [`DISPCNT`], which also writes
+/// the halfword after it).
+#[inline]
+pub fn enable_green_swap() {
+  GREEN_SWAP.write(true);
+}
+
+/// Turns off the green swap effect (see [`enable_green_swap`]).
+#[inline]
+pub fn disable_green_swap() {
+  GREEN_SWAP.write(false);
 }
 
+/// Reads the scanline currently being processed from
+/// [`VCOUNT`].
+///
+/// Lines 0-159 are drawn to the screen, and lines 160-227 are vblank.
+#[inline]
+#[must_use]
+pub fn vcount() -> u16 {
+  VCOUNT.read()
+}
+
+/// The size of a text mode background, in tiles.
+///
+/// Larger backgrounds use more than one screenblock, in order: the top left
+/// 32x32 area, then the area to its right (if any), then the area below (if
+/// any).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum TextBackgroundSize {
+  /// One screenblock.
+  #[default]
+  _32x32 = 0 << 14,
+  /// Two screenblocks, side by side.
+  _64x32 = 1 << 14,
+  /// Two screenblocks, one above the other.
+  _32x64 = 2 << 14,
+  /// Four screenblocks, in a square.
+  _64x64 = 3 << 14,
+}
+
+/// The size of an affine background, in tiles.
+///
+/// Affine backgrounds always use a single screenblock, but the amount of that
+/// screenblock (and maybe following screenblocks) that's used depends on the
+/// size, because each tile entry is one byte.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum AffineBackgroundSize {
+  /// 256 bytes of tile entries.
+  #[default]
+  _16x16 = 0 << 14,
+  /// 1k of tile entries.
+  _32x32 = 1 << 14,
+  /// 4k of tile entries.
+  _64x64 = 2 << 14,
+  /// 16k of tile entries.
+  _128x128 = 3 << 14,
+}
+
+/// [`BG0CNT`] through [`BG3CNT`]: Background control.
+///
+/// * `priority` is the draw priority (`0..=3`, lower is closer to the viewer).
+/// * `charblock` is which charblock (`0..=3`) the tile indexes start from.
+/// * `mosaic` turns on the [`Mosaic`] effect for this background.
+/// * `bpp8` makes the background use 8bpp tiles instead of 4bpp. Affine
+///   backgrounds always use 8bpp no matter what this is set to.
+/// * `screenblock` is which screenblock (`0..=31`) the tilemap starts at.
+/// * `is_affine_wrapping` makes an affine background wrap around at the edges
+///   instead of showing as transparent past them. Text backgrounds always wrap
+///   and ignore this bit.
+/// * The size field means something different for text backgrounds and for
+///   affine backgrounds, so there's a typed accessor for each:
+///   [`text_size`](Self::text_size) and [`affine_size`](Self::affine_size). The
+///   raw `size` accessors also remain.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct BackgroundControl(u16);
 impl BackgroundControl {
   pub_const_fn_new_zeroed!();
-  u16_int_field!(0 - 1, priority, with_priority);
-  u16_int_field!(2 - 3, charblock, with_charblock);
+  register_int_field!(0 - 1, priority, with_priority, PRIORITY_MASK);
+  register_int_field!(2 - 3, charblock, with_charblock, CHARBLOCK_MASK);
   u16_bool_field!(6, mosaic, with_mosaic);
   u16_bool_field!(7, bpp8, with_bpp8);
-  u16_int_field!(8 - 12, screenblock, with_screenblock);
+  register_int_field!(8 - 12, screenblock, with_screenblock, SCREENBLOCK_MASK);
   u16_bool_field!(13, is_affine_wrapping, with_is_affine_wrapping);
-  u16_int_field!(14 - 15, size, with_size);
+  register_int_field!(14 - 15, size, with_size, SIZE_MASK);
+  register_enum_field!(
+    14 - 15: TextBackgroundSize [_32x32, _64x32, _32x64, _64x64],
+    text_size, with_text_size, TEXT_SIZE_MASK
+  );
+  register_enum_field!(
+    14 - 15: AffineBackgroundSize [_16x16, _32x32, _64x64, _128x128],
+    affine_size, with_affine_size, AFFINE_SIZE_MASK
+  );
+}
+
+/// Sets up a text mode background layer and turns it on.
+///
+/// This writes the layer's background control register (4bpp, no mosaic) and
+/// then sets that layer's display bit within [`DISPCNT`]. The video mode isn't
+/// changed, so this should be used with a video mode where the layer is a text
+/// layer.
+#[inline]
+pub fn setup_text_background(
+  layer: BgLayer, charblock: u16, screenblock: u16, size: TextBackgroundSize,
+  priority: u16,
+) {
+  let control = BackgroundControl::new()
+    .with_priority(priority)
+    .with_charblock(charblock)
+    .with_screenblock(screenblock)
+    .with_text_size(size);
+  show_text_background(layer, control);
+}
+
+/// Sets up an 8bpp (256 color) text mode background layer and turns it on.
+///
+/// This is the same as [`setup_text_background`], but with 8bpp tiles (see
+/// [`copy_tiles_8bpp`]). Each pixel of an 8bpp tile indexes the whole
+/// background palette, and the `palbank` of the tilemap entries is ignored.
+///
+/// An 8bpp tile is 64 bytes, so a charblock holds 256 of them, and tile
+/// indexes count 8bpp tiles. The 10-bit tile index can still reach up to 1024
+/// tiles, past the end of the charblock and into the following ones.
+#[inline]
+pub fn setup_text_background_8bpp(
+  layer: BgLayer, charblock: u16, screenblock: u16, size: TextBackgroundSize,
+  priority: u16,
+) {
+  let control = BackgroundControl::new()
+    .with_priority(priority)
+    .with_charblock(charblock)
+    .with_bpp8(true)
+    .with_screenblock(screenblock)
+    .with_text_size(size);
+  show_text_background(layer, control);
+}
+
+fn show_text_background(layer: BgLayer, control: BackgroundControl) {
+  layer.control().write(control);
+  let dispcnt = DISPCNT.read();
+  DISPCNT.write(match layer {
+    BgLayer::Bg0 => dispcnt.with_show_bg0(true),
+    BgLayer::Bg1 => dispcnt.with_show_bg1(true),
+    BgLayer::Bg2 => dispcnt.with_show_bg2(true),
+    BgLayer::Bg3 => dispcnt.with_show_bg3(true),
+  });
 }
 
+/// One of the four background layers.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BgLayer {
+  #[default]
+  Bg0 = 0,
+  Bg1 = 1,
+  Bg2 = 2,
+  Bg3 = 3,
+}
+impl BgLayer {
+  /// The index of the layer, `0..=3`.
+  #[inline]
+  #[must_use]
+  pub const fn index(self) -> usize {
+    self as usize
+  }
+
+  /// The background control register for this layer.
+  #[inline]
+  #[must_use]
+  pub const fn control(self) -> VolAddress<BackgroundControl, Safe, Safe> {
+    BG_CONTROL.index(self as usize)
+  }
+}
+
+/// A shadow copy of a text background's scroll offset.
+///
+/// The `BGxHOFS` and `BGxVOFS` registers are write-only, so you can't read the
+/// current offset back out of the hardware to adjust it. Instead, keep one of
+/// these for each background, adjust it as you like during the frame, and then
+/// [`write_to`](Self::write_to) the background during vblank.
+///
+/// The hardware only uses the low 9 bits of each offset, so the offsets here
+/// are always kept in the range `0..512`, with scrolling wrapping around.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BgScroll {
+  x: u16,
+  y: u16,
+}
+impl BgScroll {
+  const MASK: u16 = 0b1_1111_1111;
+
+  /// Makes a new scroll offset (wrapped to `0..512`).
+  #[inline]
+  #[must_use]
+  pub const fn new(x: u16, y: u16) -> Self {
+    Self { x: x & Self::MASK, y: y & Self::MASK }
+  }
+
+  /// The horizontal offset.
+  #[inline]
+  #[must_use]
+  pub const fn x(self) -> u16 {
+    self.x
+  }
+
+  /// The vertical offset.
+  #[inline]
+  #[must_use]
+  pub const fn y(self) -> u16 {
+    self.y
+  }
+
+  /// Sets the offset (wrapped to `0..512`).
+  #[inline]
+  pub const fn set(&mut self, x: u16, y: u16) {
+    *self = Self::new(x, y);
+  }
+
+  /// Moves the offset by the amounts given, wrapping around at 512.
+  ///
+  /// Note that a *positive* offset moves the background *left* (or up) on the
+  /// screen, since the offset is where the screen's corner is within the
+  /// background.
+  #[inline]
+  pub const fn scroll_by(&mut self, dx: i16, dy: i16) {
+    *self =
+      Self::new(self.x.wrapping_add_signed(dx), self.y.wrapping_add_signed(dy));
+  }
+
+  /// Writes the offset to the scroll registers of the layer given.
+  #[inline]
+  pub fn write_to(self, layer: BgLayer) {
+    BG_HOFS.index(layer.index()).write(self.x);
+    BG_VOFS.index(layer.index()).write(self.y);
+  }
+}
+
+/// The affine parameters of background layer 2 or 3.
+///
+/// For each screen pixel the hardware finds the background pixel to show by
+/// applying the matrix `[pa, pb; pc, pd]` to the screen position, and then
+/// adding `(x, y)`. That makes this matrix the *inverse* of the
+/// transformation that you see on screen. `(x, y)` is the background position
+/// shown at the top left of the screen.
+///
+/// The affine registers are write-only, so keep a copy of the last value
+/// written if you need it. Also, the hardware copies the `x` and `y`
+/// registers into internal registers at the start of each vblank, and then
+/// advances those internal registers each scanline. That means new reference
+/// point values only take effect starting on the next frame (unless you
+/// change them mid-frame on purpose, for raster effects).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct BgAffineParams {
+  /// How far right in the background to go for each pixel right on screen.
+  pub pa: i16fx8,
+  /// How far right in the background to go for each line down the screen.
+  pub pb: i16fx8,
+  /// How far down in the background to go for each pixel right on screen.
+  pub pc: i16fx8,
+  /// How far down in the background to go for each line down the screen.
+  pub pd: i16fx8,
+  /// The background x position shown at the top left of the screen.
+  pub x: i32fx8,
+  /// The background y position shown at the top left of the screen.
+  pub y: i32fx8,
+}
+impl BgAffineParams {
+  /// The identity transformation, the background is displayed normally.
+  pub const IDENTITY: Self = Self {
+    pa: i16fx8::from_bits(1 << 8),
+    pb: i16fx8::from_bits(0),
+    pc: i16fx8::from_bits(0),
+    pd: i16fx8::from_bits(1 << 8),
+    x: i32fx8::from_bits(0),
+    y: i32fx8::from_bits(0),
+  };
+
+  /// Parameters to scale and then rotate the background around a point.
+  ///
+  /// * `scale_x` and `scale_y` are how much bigger the background should look
+  ///   (eg: 2.0 is twice as big). A scale of 0 is treated as the smallest
+  ///   possible scale rather than dividing by 0.
+  /// * `angle` is a counter-clockwise [binary angle](crate::math).
+  /// * `center` is the background pixel to scale and rotate around.
+  /// * `displacement` is the screen pixel where `center` should appear.
+  ///
+  /// Very small scales give matrix entries too big for 8.8, which are clamped
+  /// to the largest (or smallest) value.
+  ///
+  /// For other transformations (such as a shear, or several put together),
+  /// build an [`AffineMatrix`] and use
+  /// [`to_bg_params`](AffineMatrix::to_bg_params).
+  #[inline]
+  #[must_use]
+  pub fn from_scale_rotation(
+    scale_x: i16fx8, scale_y: i16fx8, angle: u16, center: (i32, i32),
+    displacement: (i16, i16),
+  ) -> Self {
+    let displacement = Vec2::new(displacement.0.into(), displacement.1.into());
+    AffineMatrix::from_scale_rotation(scale_x, scale_y, angle)
+      .to_bg_params(displacement, center.into())
+  }
+
+  /// The hardware reference point registers are only 28 bits, sign extended.
+  /// This wraps a value into that range the same way the hardware will: the
+  /// top 4 bits are dropped, so a value past the limit (about 524,288 pixels
+  /// either way) wraps around to the other sign.
+  #[inline]
+  #[must_use]
+  const fn sign_extend_28(v: i32fx8) -> i32fx8 {
+    i32fx8::from_bits((v.to_bits() << 4) >> 4)
+  }
+
+  /// Writes all of the parameters to background layer 2.
+  #[inline]
+  pub fn write_bg2(self) {
+    BG2PA.write(self.pa);
+    BG2PB.write(self.pb);
+    BG2PC.write(self.pc);
+    BG2PD.write(self.pd);
+    BG2X.write(Self::sign_extend_28(self.x));
+    BG2Y.write(Self::sign_extend_28(self.y));
+  }
+
+  /// Writes all of the parameters to background layer 3.
+  #[inline]
+  pub fn write_bg3(self) {
+    BG3PA.write(self.pa);
+    BG3PB.write(self.pb);
+    BG3PC.write(self.pc);
+    BG3PD.write(self.pd);
+    BG3X.write(Self::sign_extend_28(self.x));
+    BG3Y.write(Self::sign_extend_28(self.y));
+  }
+}
+
+/// [`WIN0H`], [`WIN1H`], [`WIN0V`], and [`WIN1V`]: One axis of a window's
+/// bounds.
+///
+/// * `start` is the first pixel (or line) inside the window.
+/// * `end` is the first pixel (or line) *after* the window, so it's exclusive.
+///
+/// If `end` is past the edge of the screen, or less than `start`, the hardware
+/// acts as if `end` were the edge of the screen. The
+/// [`horizontal`](Self::horizontal) and [`vertical`](Self::vertical)
+/// constructors clamp their inputs so that you don't hit that case by
+/// accident.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct WindowRange(u16);
+impl WindowRange {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(0 - 7, end, with_end, END_MASK);
+  register_int_field!(8 - 15, start, with_start, START_MASK);
+
+  /// Makes a range from the raw `start` and `end` values, with no clamping.
+  #[inline]
+  #[must_use]
+  pub const fn from_start_end(start: u8, end: u8) -> Self {
+    Self((start as u16) << 8 | end as u16)
+  }
+
+  /// A horizontal range covering `left..right`, clamped to the screen width.
+  #[inline]
+  #[must_use]
+  pub const fn horizontal(left: u8, right: u8) -> Self {
+    Self::clamped(left, right, 240)
+  }
+
+  /// A vertical range covering `top..bottom`, clamped to the screen height.
+  #[inline]
+  #[must_use]
+  pub const fn vertical(top: u8, bottom: u8) -> Self {
+    Self::clamped(top, bottom, 160)
+  }
+
+  #[inline]
+  #[must_use]
+  const fn clamped(start: u8, end: u8, size: u8) -> Self {
+    let end = if end > size { size } else { end };
+    let start = if start > end { end } else { start };
+    Self::from_start_end(start, end)
+  }
+}
+
+/// The bounds of window 0 or window 1.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Window {
+  pub horizontal: WindowRange,
+  pub vertical: WindowRange,
+}
+impl Window {
+  /// A `w` by `h` window with its top left at `(x, y)`.
+  ///
+  /// The window is clamped to the screen, so a rectangle that's partly off
+  /// screen covers just the visible part, and one that's entirely off screen
+  /// is empty.
+  #[inline]
+  #[must_use]
+  pub const fn rect(x: i32, y: i32, w: i32, h: i32) -> Self {
+    const fn axis(pos: i32, len: i32, size: i32) -> (u8, u8) {
+      let len = if len < 0 { 0 } else { len };
+      let start = clamp_i32(pos, 0, size);
+      let end = clamp_i32(pos.saturating_add(len), 0, size);
+      (start as u8, end as u8)
+    }
+    const fn clamp_i32(val: i32, lo: i32, hi: i32) -> i32 {
+      if val < lo {
+        lo
+      } else if val > hi {
+        hi
+      } else {
+        val
+      }
+    }
+    let (left, right) = axis(x, w, 240);
+    let (top, bottom) = axis(y, h, 160);
+    Self {
+      horizontal: WindowRange::horizontal(left, right),
+      vertical: WindowRange::vertical(top, bottom),
+    }
+  }
+
+  /// Writes these bounds to [`WIN0H`] and [`WIN0V`].
+  #[inline]
+  pub fn write_win0(self) {
+    WIN0H.write(self.horizontal);
+    WIN0V.write(self.vertical);
+  }
+
+  /// Writes these bounds to [`WIN1H`] and [`WIN1V`].
+  #[inline]
+  pub fn write_win1(self) {
+    WIN1H.write(self.horizontal);
+    WIN1V.write(self.vertical);
+  }
+}
+
+/// [`WININ`]: Which layers (and if color effects) are enabled inside of window
+/// 0 and window 1.
+///
+/// When a pixel is inside both windows then window 0's settings are used.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct WindowInside(u16);
@@ -230,6 +957,12 @@ impl WindowInside {
   u16_bool_field!(13, win1_effect, with_win1_effect);
 }
 
+/// [`WINOUT`]: Which layers (and if color effects) are enabled outside of all
+/// windows, and inside the object window.
+///
+/// The object window is made of the non-transparent pixels of all objects
+/// using [`ObjEffectMode::Window`]. Window 0 and window 1 both take priority
+/// over the object window.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct WindowOutside(u16);
@@ -249,15 +982,95 @@ impl WindowOutside {
   u16_bool_field!(13, obj_win_effect, with_obj_win_effect);
 }
 
+/// [`MOSAIC`]: The size of the mosaic effect.
+///
+/// Each value is the number of *extra* pixels in that direction for each
+/// mosaic block, so 0 is no visible effect, and 15 gives 16x16 blocks.
+/// Backgrounds and objects have separate sizes, and the effect only applies to
+/// backgrounds that have [`BackgroundControl::mosaic`] set, or objects that
+/// have [`ObjAttr0::mosaic`] set.
+///
+/// The `with_bg_h` style setters clamp the value to `0..=15`, while the
+/// `with_bg_h_extra` style setters just truncate to 4 bits.
+///
+/// The `MOSAIC` register is write-only. Reading it will not give a meaningful
+/// value, so keep a copy of the last value written if you need it.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct Mosaic(u16);
 impl Mosaic {
   pub_const_fn_new_zeroed!();
-  u16_int_field!(0 - 3, bg_h_extra, with_bg_h_extra);
-  u16_int_field!(4 - 7, bg_v_extra, with_bg_v_extra);
-  u16_int_field!(8 - 11, obj_h_extra, with_obj_h_extra);
-  u16_int_field!(12 - 15, obj_v_extra, with_obj_v_extra);
+  register_int_field!(0 - 3, bg_h_extra, with_bg_h_extra, BG_H_EXTRA_MASK);
+  register_int_field!(4 - 7, bg_v_extra, with_bg_v_extra, BG_V_EXTRA_MASK);
+  register_int_field!(8 - 11, obj_h_extra, with_obj_h_extra, OBJ_H_EXTRA_MASK);
+  register_int_field!(12 - 15, obj_v_extra, with_obj_v_extra, OBJ_V_EXTRA_MASK);
+
+  /// The same size in all four directions, clamped to `0..=15`.
+  #[inline]
+  #[must_use]
+  pub const fn splat(extra: u16) -> Self {
+    Self::new()
+      .with_bg_h(extra)
+      .with_bg_v(extra)
+      .with_obj_h(extra)
+      .with_obj_v(extra)
+  }
+
+  /// Sets the background horizontal size, clamped to `0..=15`.
+  #[inline]
+  #[must_use]
+  pub const fn with_bg_h(self, extra: u16) -> Self {
+    self.with_bg_h_extra(clamp_u4(extra))
+  }
+
+  /// Sets the background vertical size, clamped to `0..=15`.
+  #[inline]
+  #[must_use]
+  pub const fn with_bg_v(self, extra: u16) -> Self {
+    self.with_bg_v_extra(clamp_u4(extra))
+  }
+
+  /// Sets the object horizontal size, clamped to `0..=15`.
+  #[inline]
+  #[must_use]
+  pub const fn with_obj_h(self, extra: u16) -> Self {
+    self.with_obj_h_extra(clamp_u4(extra))
+  }
+
+  /// Sets the object vertical size, clamped to `0..=15`.
+  #[inline]
+  #[must_use]
+  pub const fn with_obj_v(self, extra: u16) -> Self {
+    self.with_obj_v_extra(clamp_u4(extra))
+  }
+}
+
+#[inline]
+#[must_use]
+const fn clamp_u4(val: u16) -> u16 {
+  if val > 15 {
+    15
+  } else {
+    val
+  }
+}
+
+/// Gives the mosaic size for a "pixelate" screen transition.
+///
+/// The size increases evenly from 0 when `frames_elapsed` is 0 up to the full
+/// 15 when `frames_elapsed` reaches `total_frames`. To pixelate back in, count
+/// the frames down instead of up. If `total_frames` is 0 the full size is used.
+#[inline]
+#[must_use]
+pub const fn mosaic_fade(frames_elapsed: u16, total_frames: u16) -> Mosaic {
+  let extra = crate::math::interp::remap_bits(
+    frames_elapsed as i64,
+    0,
+    total_frames as i64,
+    0,
+    15,
+  );
+  Mosaic::splat(extra as u16)
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -270,6 +1083,11 @@ pub enum ColorEffectMode {
   Darken = 3 << 6,
 }
 
+/// [`BLDCNT`]: Selects the color special effect and the layers it applies to.
+///
+/// * "target1" layers are the ones that the effect is applied to.
+/// * "target2" layers are only used by alpha blending, as the layers that the
+///   target1 layers blend with.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct BlendControl(u16);
@@ -281,21 +1099,429 @@ impl BlendControl {
   u16_bool_field!(3, target1_bg3, with_target1_bg3);
   u16_bool_field!(4, target1_obj, with_target1_obj);
   u16_bool_field!(5, target1_backdrop, with_target1_backdrop);
-  u16_enum_field!(6 - 7: ColorEffectMode, mode, with_mode);
+  register_enum_field!(
+    6 - 7: ColorEffectMode [NoEffect, AlphaBlend, Brighten, Darken],
+    mode, with_mode, MODE_MASK
+  );
   u16_bool_field!(8, target2_bg0, with_target2_bg0);
   u16_bool_field!(9, target2_bg1, with_target2_bg1);
   u16_bool_field!(10, target2_bg2, with_target2_bg2);
   u16_bool_field!(11, target2_bg3, with_target2_bg3);
   u16_bool_field!(12, target2_obj, with_target2_obj);
   u16_bool_field!(13, target2_backdrop, with_target2_backdrop);
+
+  /// Sets all six target1 layer bits at once.
+  #[inline]
+  #[must_use]
+  pub const fn with_target1_all(self, enabled: bool) -> Self {
+    self
+      .with_target1_bg0(enabled)
+      .with_target1_bg1(enabled)
+      .with_target1_bg2(enabled)
+      .with_target1_bg3(enabled)
+      .with_target1_obj(enabled)
+      .with_target1_backdrop(enabled)
+  }
+
+  /// Sets all six target2 layer bits at once.
+  #[inline]
+  #[must_use]
+  pub const fn with_target2_all(self, enabled: bool) -> Self {
+    self
+      .with_target2_bg0(enabled)
+      .with_target2_bg1(enabled)
+      .with_target2_bg2(enabled)
+      .with_target2_bg3(enabled)
+      .with_target2_obj(enabled)
+      .with_target2_backdrop(enabled)
+  }
+}
+
+/// [`BLDALPHA`]: The alpha blending coefficients.
+///
+/// When alpha blending, each output color channel is `target1 * eva / 16 +
+/// target2 * evb / 16` (capped at the max channel value). The hardware treats
+/// any coefficient over 16 as if it were 16, and the setters here clamp to
+/// `0..=16` so that stored values match what's displayed.
+///
+/// The `BLDALPHA` register is write-only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct BlendAlpha(u16);
+impl BlendAlpha {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(0 - 4, eva, with_eva_unclamped, EVA_MASK);
+  register_int_field!(8 - 12, evb, with_evb_unclamped, EVB_MASK);
+
+  /// Makes a value from both coefficients, clamped to `0..=16`.
+  #[inline]
+  #[must_use]
+  pub const fn from_coefficients(eva: u16, evb: u16) -> Self {
+    Self::new().with_eva(eva).with_evb(evb)
+  }
+
+  /// Sets the target1 coefficient, clamped to `0..=16`.
+  #[inline]
+  #[must_use]
+  pub const fn with_eva(self, eva: u16) -> Self {
+    self.with_eva_unclamped(clamp_blend(eva))
+  }
+
+  /// Sets the target2 coefficient, clamped to `0..=16`.
+  #[inline]
+  #[must_use]
+  pub const fn with_evb(self, evb: u16) -> Self {
+    self.with_evb_unclamped(clamp_blend(evb))
+  }
+}
+
+/// [`BLDY`]: The brightness coefficient for brighten and darken effects.
+///
+/// When brightening each channel moves `evy / 16` of the way up to white, and
+/// when darkening each channel moves `evy / 16` of the way down to black. The
+/// hardware treats any value over 16 as if it were 16, and the setter here
+/// clamps to `0..=16`.
+///
+/// The `BLDY` register is write-only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct BlendBrightness(u16);
+impl BlendBrightness {
+  pub_const_fn_new_zeroed!();
+  register_int_field!(0 - 4, evy, with_evy_unclamped, EVY_MASK);
+
+  /// Sets the coefficient, clamped to `0..=16`.
+  #[inline]
+  #[must_use]
+  pub const fn with_evy(self, evy: u16) -> Self {
+    self.with_evy_unclamped(clamp_blend(evy))
+  }
+}
+
+#[inline]
+#[must_use]
+const fn clamp_blend(val: u16) -> u16 {
+  if val > 16 {
+    16
+  } else {
+    val
+  }
+}
+
+/// Darkens all layers `progress / 16` of the way to black.
+///
+/// This overwrites [`BLDCNT`] and [`BLDY`]. A `progress` of 0 is no change,
+/// and 16 (or more) is fully black.
+#[inline]
+pub fn fade_to_black(progress: u16) {
+  BLDCNT.write(
+    BlendControl::new()
+      .with_target1_all(true)
+      .with_mode(ColorEffectMode::Darken),
+  );
+  BLDY.write(BlendBrightness::new().with_evy(progress));
+}
+
+/// Brightens all layers `progress / 16` of the way to white.
+///
+/// This overwrites [`BLDCNT`] and [`BLDY`]. A `progress` of 0 is no change,
+/// and 16 (or more) is fully white.
+#[inline]
+pub fn fade_to_white(progress: u16) {
+  BLDCNT.write(
+    BlendControl::new()
+      .with_target1_all(true)
+      .with_mode(ColorEffectMode::Brighten),
+  );
+  BLDY.write(BlendBrightness::new().with_evy(progress));
 }
 
 /// Data for a 4-bit-per-pixel tile.
+///
+/// Each `u32` is one row of 8 pixels, with the left most pixel in the lowest 4
+/// bits.
 pub type Tile4 = [u32; 8];
 
 /// Data for an 8-bit-per-pixel tile.
+///
+/// Each pair of `u32` values is one row of 8 pixels, with the left most pixel
+/// in the lowest 8 bits of the first value.
 pub type Tile8 = [u32; 16];
 
+/// Packs 4bpp tile bytes (eg: as exported by graphics tools) into a [`Tile4`].
+///
+/// Each byte is two pixels, using the low 4 bits for the left pixel. This is
+/// a `const fn`, so the conversion can happen at compile time.
+#[inline]
+#[must_use]
+pub const fn tile4_from_bytes(bytes: [u8; 32]) -> Tile4 {
+  let mut tile = [0; 8];
+  let mut i = 0;
+  while i < 8 {
+    let b = 4 * i;
+    tile[i] =
+      u32::from_le_bytes([bytes[b], bytes[b + 1], bytes[b + 2], bytes[b + 3]]);
+    i += 1;
+  }
+  tile
+}
+
+/// Packs 8bpp tile bytes (eg: as exported by graphics tools) into a [`Tile8`].
+///
+/// Each byte is one pixel. This is a `const fn`, so the conversion can happen
+/// at compile time.
+#[inline]
+#[must_use]
+pub const fn tile8_from_bytes(bytes: [u8; 64]) -> Tile8 {
+  let mut tile = [0; 16];
+  let mut i = 0;
+  while i < 16 {
+    let b = 4 * i;
+    tile[i] =
+      u32::from_le_bytes([bytes[b], bytes[b + 1], bytes[b + 2], bytes[b + 3]]);
+    i += 1;
+  }
+  tile
+}
+
+/// Adds `offset` to every non-zero pixel of a [`Tile8`].
+///
+/// An 8bpp tile's pixels index the whole palette, so an image drawn with its
+/// own colors at indexes `1..n` can be moved to use any other part of the
+/// palette, such as banks picked with
+/// [`PalBankAllocator::alloc_banks`](palram::PalBankAllocator::alloc_banks).
+/// Pixels that are 0 stay 0 (transparent), and the sums wrap around at 256.
+#[inline]
+#[must_use]
+pub const fn tile8_offset_indexes(tile: Tile8, offset: u8) -> Tile8 {
+  let mut out = [0; 16];
+  let mut i = 0;
+  while i < 16 {
+    let mut bytes = tile[i].to_le_bytes();
+    let mut b = 0;
+    while b < 4 {
+      if bytes[b] != 0 {
+        bytes[b] = bytes[b].wrapping_add(offset);
+      }
+      b += 1;
+    }
+    out[i] = u32::from_le_bytes(bytes);
+    i += 1;
+  }
+  out
+}
+
+/// Flips a [`Tile4`] horizontally.
+#[inline]
+#[must_use]
+pub const fn tile4_flip_h(tile: Tile4) -> Tile4 {
+  let mut out = [0; 8];
+  let mut i = 0;
+  while i < 8 {
+    // reverse the bytes, then swap the two pixels within each byte.
+    let row = tile[i].swap_bytes();
+    out[i] = ((row >> 4) & 0x0F0F_0F0F) | ((row & 0x0F0F_0F0F) << 4);
+    i += 1;
+  }
+  out
+}
+
+/// Flips a [`Tile4`] vertically.
+#[inline]
+#[must_use]
+pub const fn tile4_flip_v(tile: Tile4) -> Tile4 {
+  let mut out = [0; 8];
+  let mut i = 0;
+  while i < 8 {
+    out[i] = tile[7 - i];
+    i += 1;
+  }
+  out
+}
+
+/// Flips a [`Tile8`] horizontally.
+#[inline]
+#[must_use]
+pub const fn tile8_flip_h(tile: Tile8) -> Tile8 {
+  let mut out = [0; 16];
+  let mut i = 0;
+  while i < 16 {
+    out[i] = tile[i + 1].swap_bytes();
+    out[i + 1] = tile[i].swap_bytes();
+    i += 2;
+  }
+  out
+}
+
+/// Flips a [`Tile8`] vertically.
+#[inline]
+#[must_use]
+pub const fn tile8_flip_v(tile: Tile8) -> Tile8 {
+  let mut out = [0; 16];
+  let mut i = 0;
+  while i < 16 {
+    out[i] = tile[14 - i];
+    out[i + 1] = tile[15 - i];
+    i += 2;
+  }
+  out
+}
+
+/// Copies 4bpp tiles into a charblock, starting at the tile index given.
+///
+/// This uses [`copy_words`](crate::mem::copy_words), so all but the smallest
+/// copies go by DMA3, and VRAM is only written with full 32-bit writes.
+///
+/// ## Panics
+/// * The charblock must be in `0..4`.
+/// * All of the tiles must fit within the charblock.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_tiles_4bpp(charblock: usize, first_tile: usize, tiles: &[Tile4]) {
+  let block = charblock_4bpp(charblock);
+  assert!(first_tile + tiles.len() <= block.len(), "tiles out of range");
+  let p = block.index(first_tile).as_usize() as *mut u32;
+  unsafe { copy_words_to(p, tiles.as_ptr().cast(), tiles.len() * 8) };
+}
+
+/// Copies 8bpp tiles into a charblock, starting at the tile index given.
+///
+/// This uses [`copy_words`](crate::mem::copy_words), so all but the smallest
+/// copies go by DMA3, and VRAM is only written with full 32-bit writes.
+///
+/// ## Panics
+/// * The charblock must be in `0..4`.
+/// * All of the tiles must fit within the charblock.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_tiles_8bpp(charblock: usize, first_tile: usize, tiles: &[Tile8]) {
+  let block = charblock_8bpp(charblock);
+  assert!(first_tile + tiles.len() <= block.len(), "tiles out of range");
+  let p = block.index(first_tile).as_usize() as *mut u32;
+  unsafe { copy_words_to(p, tiles.as_ptr().cast(), tiles.len() * 16) };
+}
+
+/// The range of screenblocks that share VRAM with a range of tiles.
+///
+/// Charblocks and screenblocks are two different views of the same
+/// background VRAM: each charblock is 16k, and each screenblock is 2k, so
+/// charblock `n` covers screenblocks `n*8 .. n*8+8`. This function gives the
+/// screenblocks touched by `tile_count` tiles, starting from tile index
+/// `first_tile` within `charblock`, so that you can check a tilemap won't be
+/// overwritten by tile data (or the other way around).
+///
+/// The range ends at 32 even if the tiles would go past the end of background
+/// VRAM.
+#[inline]
+#[must_use]
+pub const fn screenblocks_used_by_tiles(
+  charblock: usize, first_tile: usize, tile_count: usize, bpp8: bool,
+) -> core::ops::Range<usize> {
+  let tile_size = if bpp8 { size_of::<Tile8>() } else { size_of::<Tile4>() };
+  let start = charblock * (BG_TILE_REGION_SIZE / 4) + first_tile * tile_size;
+  let end = start + tile_count * tile_size;
+  let first = start / SCREENBLOCK_INDEX_OFFSET;
+  let last = end.div_ceil(SCREENBLOCK_INDEX_OFFSET);
+  let first = if first > 32 { 32 } else { first };
+  let last = if last > 32 { 32 } else { last };
+  first..last
+}
+
+/// A view of an affine background's tilemap.
+///
+/// Each entry of an affine tilemap is a single byte, but VRAM can't be written
+/// one byte at a time (a byte write is copied into both halves of the 16-bit
+/// location). This view does the necessary 16-bit read-modify-write when
+/// setting an entry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AffineScreenblock {
+  base: usize,
+  size: AffineBackgroundSize,
+}
+impl AffineScreenblock {
+  /// Views the tilemap starting at the screenblock index given, for a
+  /// background of the given size.
+  ///
+  /// ## Panics
+  /// * The screenblock index must be in `0..32`, and the tilemap (which is
+  ///   larger than one screenblock for the 64x64 and 128x128 sizes) must fit
+  ///   within background VRAM.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(screenblock: usize, size: AffineBackgroundSize) -> Self {
+    let offset = screenblock * SCREENBLOCK_INDEX_OFFSET;
+    let width = Self::width_of(size);
+    assert!(screenblock < 32, "screenblock index out of range");
+    assert!(
+      offset + width * width <= BG_TILE_REGION_SIZE,
+      "affine tilemap goes past the end of background VRAM"
+    );
+    let base = CHARBLOCK0_4BPP.index(0).as_usize() + offset;
+    Self { base, size }
+  }
+
+  #[inline]
+  #[must_use]
+  const fn width_of(size: AffineBackgroundSize) -> usize {
+    match size {
+      AffineBackgroundSize::_16x16 => 16,
+      AffineBackgroundSize::_32x32 => 32,
+      AffineBackgroundSize::_64x64 => 64,
+      AffineBackgroundSize::_128x128 => 128,
+    }
+  }
+
+  /// The width (and height) of the tilemap, in tiles.
+  #[inline]
+  #[must_use]
+  pub const fn width(self) -> usize {
+    Self::width_of(self.size)
+  }
+
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  fn pair_addr(self, x: usize, y: usize) -> VolAddress<u8x2, Safe, Safe> {
+    let w = self.width();
+    assert!(x < w && y < w, "affine tilemap position out of range");
+    let byte = y * w + x;
+    unsafe { VolAddress::new(self.base + (byte & !1)) }
+  }
+
+  /// Gets the tile index at the position given.
+  ///
+  /// ## Panics
+  /// * `x` and `y` must both be less than the width.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn read(self, x: usize, y: usize) -> u8 {
+    let pair = self.pair_addr(x, y).read();
+    if x & 1 == 0 {
+      pair.low()
+    } else {
+      pair.high()
+    }
+  }
+
+  /// Sets the tile index at the position given.
+  ///
+  /// ## Panics
+  /// * `x` and `y` must both be less than the width.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn write(self, x: usize, y: usize, tile: u8) {
+    let addr = self.pair_addr(x, y);
+    let pair = addr.read();
+    addr.write(if x & 1 == 0 {
+      pair.with_low(tile)
+    } else {
+      pair.with_high(tile)
+    });
+  }
+}
+
 /// An entry within a tile mode tilemap.
 ///
 /// * `tile` is the index of the tile, offset from the `charblock` that the
@@ -312,10 +1538,10 @@ pub type Tile8 = [u32; 16];
 pub struct TextEntry(u16);
 impl TextEntry {
   pub_const_fn_new_zeroed!();
-  u16_int_field!(0 - 9, tile, with_tile);
+  register_int_field!(0 - 9, tile, with_tile, TILE_MASK);
   u16_bool_field!(10, hflip, with_hflip);
   u16_bool_field!(11, vflip, with_vflip);
-  u16_int_field!(12 - 15, palbank, with_palbank);
+  register_int_field!(12 - 15, palbank, with_palbank, PALBANK_MASK);
 
   /// Shorthand for `TextEntry::new().with_tile(id)`
   #[inline]
@@ -323,6 +1549,86 @@ impl TextEntry {
   pub const fn from_tile(id: u16) -> Self {
     Self(id & 0b11_1111_1111)
   }
+
+  /// Unwraps the entry into its raw `u16` form.
+  #[inline]
+  #[must_use]
+  pub const fn to_u16(self) -> u16 {
+    self.0
+  }
+}
+impl From<TextEntry> for u16 {
+  #[inline]
+  fn from(value: TextEntry) -> Self {
+    value.to_u16()
+  }
+}
+impl From<u16> for TextEntry {
+  #[inline]
+  fn from(value: u16) -> Self {
+    Self(value)
+  }
+}
+
+/// The index of a tilemap position within a text background's entries.
+///
+/// The index counts [`TextEntry`] values from the start of the background's
+/// first screenblock. Backgrounds larger than 32x32 are *not* stored as one
+/// big row-major grid. Instead each 32x32 area is a separate screenblock,
+/// stored one after the other, so (for example) in a 64 wide background the
+/// entry at `(32, 0)` comes right after the entry at `(31, 31)`.
+///
+/// Positions past the edge of the background wrap around, the same as the
+/// background display does.
+#[inline]
+#[must_use]
+pub const fn text_entry_index(
+  size: TextBackgroundSize, x: usize, y: usize,
+) -> usize {
+  let (wide, tall) = match size {
+    TextBackgroundSize::_32x32 => (false, false),
+    TextBackgroundSize::_64x32 => (true, false),
+    TextBackgroundSize::_32x64 => (false, true),
+    TextBackgroundSize::_64x64 => (true, true),
+  };
+  let block_x = if wide { (x / 32) % 2 } else { 0 };
+  let block_y = if tall { (y / 32) % 2 } else { 0 };
+  let block = block_y * if wide { 2 } else { 1 } + block_x;
+  block * 1024 + (y % 32) * 32 + (x % 32)
+}
+
+/// Fills a rectangle of a text background's tilemap from a slice of entries.
+///
+/// * `screenblock` is the background's first screenblock, and `size` is the
+///   background's size. The correct screenblock for each position is picked
+///   automatically (see [`text_entry_index`]).
+/// * The rectangle is `width` by `height` entries, and its top left is at `(x,
+///   y)`. Positions past the edge of the background wrap around.
+/// * Row `r` of the rectangle is read from `entries[r * stride..]`, so a
+///   rectangle can be copied out of a larger map by using the map's width as
+///   the stride.
+///
+/// ## Panics
+/// * The tilemap must fit within background VRAM.
+/// * `entries` must hold enough data for every row.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn fill_text_region(
+  screenblock: usize, size: TextBackgroundSize, (x, y): (usize, usize),
+  (width, height): (usize, usize), entries: &[TextEntry], stride: usize,
+) {
+  let blocks = tilemap::screenblock_count(size);
+  assert!(screenblock + blocks <= 32, "tilemap out of range");
+  let base = text_screenblock(screenblock).index(0, 0).as_usize();
+  for row in 0..height {
+    let src = &entries[row * stride..][..width];
+    for (col, entry) in src.iter().enumerate() {
+      let i = text_entry_index(size, x + col, y + row);
+      let addr: VolAddress<TextEntry, Safe, Safe> =
+        unsafe { VolAddress::new(base + i * size_of::<TextEntry>()) };
+      addr.write(*entry);
+    }
+  }
 }
 
 #[inline]
@@ -372,3 +1678,197 @@ pub fn video4_set_indexmap(indexes: &Video4Indexmap, frame: usize) {
     copy_u32x8_unchecked(p, indexes as *const _ as *const _, 1200_usize)
   };
 }
+
+/// Spins until the *start* of the next vertical blank.
+///
+/// If this is called during vblank it first waits for vblank to end, so there
+/// will always be a full vblank period available when this returns.
+///
+/// This busy loops on [`VCOUNT`], which keeps the CPU running the whole time
+/// and so burns through battery power. It's better to use [`wait_for_vblank`]
+/// if you're able to set up the vblank interrupt.
+#[inline]
+pub fn spin_until_vblank() {
+  while VCOUNT.read() >= 160 {}
+  while VCOUNT.read() < 160 {}
+}
+
+/// Spins until the *start* of the next vertical draw period.
+///
+/// If this is called during vdraw it first waits for vblank, so there will
+/// always be a full vdraw period available when this returns.
+///
+/// Like with [`spin_until_vblank`], this burns battery power.
+#[inline]
+pub fn spin_until_vdraw() {
+  while VCOUNT.read() < 160 {}
+  while VCOUNT.read() >= 160 {}
+}
+
+/// Runs `f` with the display in forced blank, for reconfiguring video memory
+/// without showing the half-loaded result.
+///
+/// During forced blank the screen is white and nothing is drawn, so video
+/// memory (VRAM, PALRAM, and OAM) can be accessed at full speed, for as long
+/// as it takes. This is the time to switch video modes, load tiles and
+/// palettes, and set up OAM (such as going from a title screen to the game).
+///
+/// The important part is *when* forced blank starts and stops. Setting or
+/// clearing it partway through drawing a frame shows that frame split in two,
+/// with a line of garbage between the two parts. So this waits for vblank
+/// before setting forced blank, and after `f` returns it waits for vblank
+/// again (if `f` didn't end in one) before clearing it, so the first frame
+/// afterwards is drawn whole, from the top, with the new settings.
+///
+/// The waits spin on [`VCOUNT`] (see [`spin_until_vblank`]), so no interrupts
+/// are needed. If `f` writes to [`DISPCNT`] it must keep `forced_blank` set,
+/// or the display comes back early: `with_forced_blank(true)` on the new
+/// settings, which this then clears.
+///
+/// Forced blank is always off afterwards, even if it was already on before
+/// (as it can be at boot), so calls shouldn't be nested.
+#[inline]
+pub fn with_forced_blank<R>(f: impl FnOnce() -> R) -> R {
+  if !DISPCNT.read().forced_blank() {
+    while VCOUNT.read() < 160 {}
+    DISPCNT.apply(|d| *d = d.with_forced_blank(true));
+  }
+  let out = f();
+  while VCOUNT.read() < 160 {}
+  DISPCNT.apply(|d| *d = d.with_forced_blank(false));
+  out
+}
+
+/// Sets up the vblank interrupt so that [`wait_for_vblank`] works.
+///
+/// This sets the `irq_vblank` bit of [`DISPSTAT`], the `vblank` bit of [`IE`],
+/// and turns on [`IME`]. Other settings in those registers are left alone, and
+/// `DISPSTAT` and `IE` are updated with `IME` off (see [`irq_free`]).
+///
+/// Nothing else is needed: the assembly runtime's interrupt handler always
+/// acknowledges interrupts (including for `VBlankIntrWait`), even when no
+/// [`RUST_IRQ_HANDLER`] is set.
+#[inline]
+pub fn init_vblank_irq() {
+  irq_free(|| {
+    DISPSTAT.apply(|s| *s = s.with_irq_vblank(true));
+    IE.apply(|bits| *bits = bits.with_vblank(true));
+  });
+  IME.write(true);
+}
+
+/// Halts the CPU until the next vblank interrupt, using
+/// [`VBlankIntrWait`].
+///
+/// Because the CPU is halted this saves battery power compared to
+/// [`spin_until_vblank`], but the vblank interrupt *must* be configured first
+/// (such as with [`init_vblank_irq`]) or this will never return:
+/// * [`DISPSTAT`] must have `irq_vblank` set.
+/// * [`IE`] must have the `vblank` bit set.
+/// * [`IME`] must be enabled.
+///
+/// This is the recommended way to idle between frames. To idle until some
+/// other interrupt, use [`IntrWait`] (or [`Halt`] for any interrupt at all).
+///
+/// ## Panics
+/// * With debug assertions on, this checks the above settings, and panics
+///   instead of hanging if any of them are missing.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn wait_for_vblank() {
+  if cfg!(debug_assertions) {
+    assert!(
+      DISPSTAT.read().irq_vblank(),
+      "wait_for_vblank would hang: DISPSTAT's irq_vblank is off (see init_vblank_irq)"
+    );
+    assert!(
+      IE.read().vblank(),
+      "wait_for_vblank would hang: IE's vblank bit is off (see init_vblank_irq)"
+    );
+    assert!(
+      IME.read(),
+      "wait_for_vblank would hang: IME is off (see init_vblank_irq)"
+    );
+  }
+  VBlankIntrWait();
+}
+
+/// Spins until the *start* of the scanline given.
+///
+/// If [`VCOUNT`] is already on that line then this waits for the line to end
+/// first, so it always returns right as the line begins (or as close to that
+/// as a busy loop can get). This is the polling version of
+/// [`set_scanline_irq`], and it burns battery power just like
+/// [`spin_until_vblank`].
+///
+/// ## Panics
+/// * The line must be less than 228.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn spin_until_scanline(line: u8) {
+  assert!(line < 228, "scanline out of range");
+  let line = u16::from(line);
+  while VCOUNT.read() == line {}
+  while VCOUNT.read() != line {}
+}
+
+/// Makes the vcount interrupt fire at the start of the scanline given.
+///
+/// This sets up all three registers involved:
+/// * [`DISPSTAT`] gets the `vcount_setting` and `irq_vcount` set.
+/// * [`IE`] gets the `vcounter` bit set.
+/// * [`IME`] is enabled.
+///
+/// `DISPSTAT` and `IE` are updated with `IME` off (see [`irq_free`]), so this
+/// can't race with an interrupt handler that changes them too.
+///
+/// The interrupt fires when [`VCOUNT`] changes to the line, which is the
+/// *start* of that line's horizontal draw. The display is already drawing the
+/// line by the time a handler runs, so changes to video registers usually
+/// only affect the *next* line cleanly. Only writes that happen during the
+/// line's hblank will affect the rest of the line in a predictable way.
+///
+/// To have a function called at the scanline, see
+/// [`set_scanline_irq_handler`].
+///
+/// ## Panics
+/// * The line must be less than 228.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn set_scanline_irq(line: u8) {
+  assert!(line < 228, "scanline out of range");
+  irq_free(|| {
+    DISPSTAT.apply(|s| {
+      *s = s.with_vcount_setting(u16::from(line)).with_irq_vcount(true)
+    });
+    IE.apply(|bits| *bits = bits.with_vcounter(true));
+  });
+  IME.write(true);
+}
+
+/// Stops the vcount interrupt from firing.
+///
+/// This clears the `irq_vcount` bit of [`DISPSTAT`] and the `vcounter` bit of
+/// [`IE`], but leaves [`IME`] alone since other interrupts might be in use.
+#[inline]
+pub fn clear_scanline_irq() {
+  irq_free(|| {
+    DISPSTAT.apply(|s| *s = s.with_irq_vcount(false));
+    IE.apply(|bits| *bits = bits.with_vcounter(false));
+  });
+}
+
+/// Sets the function called for vcount interrupts.
+///
+/// This is the [`Interrupt::VCounter`] entry of the
+/// [handler table](crate::interrupts::set_handler), so it's called when
+/// [`RUST_IRQ_HANDLER`] is [`irq_table_dispatch`]. The handler is called from
+/// within the interrupt, so it should be short. Passing `None` removes the
+/// handler, which also clears the `vcounter` bit of [`IE`].
+#[inline]
+pub fn set_scanline_irq_handler(handler: Option<IrqFn>) {
+  match handler {
+    Some(handler) => set_handler(Interrupt::VCounter, handler),
+    None => clear_handler(Interrupt::VCounter),
+  }
+}
@@ -0,0 +1,301 @@
+//! Drawing to the [`VIDEO3_VRAM`] bitmap.
+//!
+//! In video mode 3 background layer 2 is a single 240x160 bitmap of direct
+//! [`Color`] values.
+//!
+//! All of the drawing functions here take `i32` coordinates and clip against
+//! the screen bounds, so shapes that are partly (or fully) off the screen just
+//! draw whatever part of them is visible.
+//!
+//! ## Text
+//!
+//! [`draw_text`] draws a string with the built-in [`CGA_8X8_THICK`] font,
+//! with a background color or (with `None`) just the glyphs' lines over
+//! whatever's already there. For a scrolling log that takes up the whole
+//! screen, a [`BitmapConsole`] keeps track of the cursor and can be used with
+//! [`write!`].
+
+use super::{
+  raster,
+  text::{glyph_for, glyph_row, COLUMNS, ROWS},
+};
+use crate::prelude::*;
+use core::fmt;
+
+/// The width of the mode 3 bitmap.
+pub const WIDTH: i32 = 240;
+
+/// The height of the mode 3 bitmap.
+pub const HEIGHT: i32 = 160;
+
+#[inline]
+fn span(y: usize, x0: usize, x1: usize, color: Color) {
+  let row = VIDEO3_VRAM.get_row(y).unwrap();
+  for addr in row.iter().skip(x0).take(x1 - x0 + 1) {
+    addr.write(color);
+  }
+}
+
+/// Fills the entire bitmap with a color.
+///
+/// This uses [`video3_clear_to`], which writes two pixels at a time.
+#[inline]
+pub fn clear_to(color: Color) {
+  video3_clear_to(color);
+}
+
+/// Fills the entire bitmap with a color, using DMA3.
+///
+/// This is about as fast as [`clear_to`], but doesn't use any IWRAM code.
+/// See [`dma3_fill_u32`] for timing details.
+#[cfg(feature = "on_gba")]
+#[inline]
+pub fn dma_clear_to(color: Color) {
+  let word = u32::from(color.0) | (u32::from(color.0) << 16);
+  let dest = VIDEO3_VRAM.index(0, 0).as_usize() as *mut u32;
+  unsafe { crate::dma::dma3_fill_u32(word, dest, 240 * 160 / 2) };
+}
+
+/// Sets a single pixel, if it's on the screen.
+#[inline]
+pub fn put_pixel(x: i32, y: i32, color: Color) {
+  if let Some((x, y)) = raster::clip_point(x, y, WIDTH, HEIGHT) {
+    VIDEO3_VRAM.index(x, y).write(color);
+  }
+}
+
+/// Draws a line from `(x0, y0)` to `(x1, y1)`, including both end points.
+#[inline]
+pub fn line(x0: i32, y0: i32, x1: i32, y1: i32, color: Color) {
+  raster::line(x0, y0, x1, y1, WIDTH, HEIGHT, |x, y| {
+    VIDEO3_VRAM.index(x, y).write(color)
+  });
+}
+
+/// Draws the outline of a `w` by `h` rectangle with its top left at `(x, y)`.
+#[inline]
+pub fn rect(x: i32, y: i32, w: i32, h: i32, color: Color) {
+  raster::rect(x, y, w, h, WIDTH, HEIGHT, |y, x0, x1| span(y, x0, x1, color));
+}
+
+/// Fills a `w` by `h` rectangle with its top left at `(x, y)`.
+#[inline]
+pub fn rect_filled(x: i32, y: i32, w: i32, h: i32, color: Color) {
+  raster::rect_filled(x, y, w, h, WIDTH, HEIGHT, |y, x0, x1| {
+    span(y, x0, x1, color)
+  });
+}
+
+/// Blends a `w` by `h` rectangle with its top left at `(x, y)` `weight / 16`
+/// of the way towards a color.
+///
+/// This reads back each pixel, so it's much slower than [`rect_filled`], but
+/// it can tint or shade part of the screen (see [`Color::blend`]).
+#[inline]
+pub fn rect_blended(x: i32, y: i32, w: i32, h: i32, color: Color, weight: u16) {
+  raster::rect_filled(x, y, w, h, WIDTH, HEIGHT, |y, x0, x1| {
+    let row = VIDEO3_VRAM.get_row(y).unwrap();
+    for addr in row.iter().skip(x0).take(x1 - x0 + 1) {
+      addr.write(addr.read().blend(color, weight));
+    }
+  });
+}
+
+/// Draws the outline of a circle of radius `r` centered on `(cx, cy)`.
+#[inline]
+pub fn circle(cx: i32, cy: i32, r: i32, color: Color) {
+  raster::circle(cx, cy, r, WIDTH, HEIGHT, |x, y| {
+    VIDEO3_VRAM.index(x, y).write(color)
+  });
+}
+
+/// Fills a circle of radius `r` centered on `(cx, cy)`.
+#[inline]
+pub fn circle_filled(cx: i32, cy: i32, r: i32, color: Color) {
+  raster::circle_filled(cx, cy, r, WIDTH, HEIGHT, |y, x0, x1| {
+    span(y, x0, x1, color)
+  });
+}
+
+/// Draws one glyph of the built-in font with its top left at `(x, y)`.
+///
+/// Any of the font's 256 glyphs can be drawn this way (eg: the box drawing
+/// glyphs of [`Cga8x8Thick`]). The glyph's lines are `fg`, and the rest of the
+/// 8x8 cell is `bg`, or is left alone if `bg` is `None`.
+#[inline]
+pub fn draw_glyph(x: i32, y: i32, glyph: u8, fg: Color, bg: Option<Color>) {
+  if x <= -8 || x >= WIDTH || y <= -8 || y >= HEIGHT {
+    return;
+  }
+  for dy in 0..8 {
+    let py = y + dy;
+    if !(0..HEIGHT).contains(&py) {
+      continue;
+    }
+    let bits = glyph_row(glyph, dy as usize);
+    for dx in 0..8 {
+      let px = x + dx;
+      if !(0..WIDTH).contains(&px) {
+        continue;
+      }
+      let color = if bits & (1 << dx) != 0 {
+        fg
+      } else if let Some(bg) = bg {
+        bg
+      } else {
+        continue;
+      };
+      VIDEO3_VRAM.index(px as usize, py as usize).write(color);
+    }
+  }
+}
+
+/// Draws text with the built-in font, with the top left of the first
+/// character at `(x, y)`.
+///
+/// Each character is 8x8 pixels. A `'\n'` goes back to `x`, 8 pixels down.
+/// Text doesn't wrap, anything past the edges of the screen is cut off.
+/// Characters outside of the printable ASCII range are shown as `?`. See
+/// [`draw_glyph`] for the colors.
+#[inline]
+pub fn draw_text(x: i32, y: i32, fg: Color, bg: Option<Color>, text: &str) {
+  let (mut cx, mut cy) = (x, y);
+  for c in text.chars() {
+    if c == '\n' {
+      (cx, cy) = (x, cy.saturating_add(8));
+      continue;
+    }
+    draw_glyph(cx, cy, glyph_for(c), fg, bg);
+    cx = cx.saturating_add(8);
+  }
+}
+
+/// A text console that takes up the whole mode 3 screen, scrolling up when it
+/// gets to the bottom.
+///
+/// * There are [`COLUMNS`] by [`ROWS`] characters, drawn with the built-in font
+///   (see [`draw_glyph`]) in `fg` on `bg`.
+/// * Text wraps to the next line after the last column, or at a `'\n'`.
+/// * When a new line is needed on the last row, the whole screen moves up by 8
+///   pixels (with DMA3), and the last row is cleared to `bg`.
+/// * Characters outside of the printable ASCII range are shown as `?`.
+///
+/// Scrolling moves everything on the screen, not just the text, so a console
+/// is for when the text is all that's there.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// # use gba::video::mode3::BitmapConsole;
+/// use core::fmt::Write;
+/// let mut console = BitmapConsole::new(Color::WHITE, Color::BLACK);
+/// console.clear();
+/// writeln!(console, "hello, {}", 42).ok();
+/// ```
+#[cfg(feature = "on_gba")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BitmapConsole {
+  fg: Color,
+  bg: Color,
+  col: u8,
+  row: u8,
+}
+#[cfg(feature = "on_gba")]
+impl BitmapConsole {
+  /// Makes a console with the cursor at the top left.
+  ///
+  /// This doesn't draw anything, so call [`clear`](Self::clear) first unless
+  /// the screen is already `bg`.
+  #[inline]
+  #[must_use]
+  pub const fn new(fg: Color, bg: Color) -> Self {
+    Self { fg, bg, col: 0, row: 0 }
+  }
+
+  /// The cursor position as `(column, row)`.
+  #[inline]
+  #[must_use]
+  pub const fn cursor(&self) -> (u8, u8) {
+    (self.col, self.row)
+  }
+
+  /// Moves the cursor, wrapping to the screen.
+  #[inline]
+  pub fn set_cursor(&mut self, col: u8, row: u8) {
+    self.col = col % COLUMNS;
+    self.row = row % ROWS;
+  }
+
+  /// Changes the colors for text drawn after this.
+  #[inline]
+  pub fn set_colors(&mut self, fg: Color, bg: Color) {
+    self.fg = fg;
+    self.bg = bg;
+  }
+
+  /// Fills the screen with the background color, and moves the cursor to the
+  /// top left.
+  #[inline]
+  pub fn clear(&mut self) {
+    dma_clear_to(self.bg);
+    self.col = 0;
+    self.row = 0;
+  }
+
+  /// Moves the whole screen up by one row of text, and clears the last row.
+  #[inline]
+  fn scroll(&self) {
+    // One row of text is 8 lines of pixels, 2 pixels per word.
+    const WORDS: usize = 240 * 8 / 2;
+    let base = VIDEO3_VRAM.index(0, 0).as_usize() as *mut u32;
+    for row in 1..usize::from(ROWS) {
+      // Safety: each copy is between two rows of text in VRAM, which don't
+      // overlap, and nothing in Rust holds a reference to VRAM.
+      unsafe {
+        let src = core::slice::from_raw_parts(base.add(row * WORDS), WORDS);
+        crate::dma::dma3_copy_u32(src, base.add((row - 1) * WORDS));
+      }
+    }
+    let word = u32::from(self.bg.0) | (u32::from(self.bg.0) << 16);
+    let last = usize::from(ROWS - 1) * WORDS;
+    // Safety: this is the last row of text in VRAM.
+    unsafe { crate::dma::dma3_fill_u32(word, base.add(last), WORDS) };
+  }
+
+  #[inline]
+  fn newline(&mut self) {
+    self.col = 0;
+    if self.row + 1 < ROWS {
+      self.row += 1;
+    } else {
+      self.scroll();
+    }
+  }
+
+  /// Prints a single byte, using the font's glyph for that byte.
+  ///
+  /// Unlike when using [`write!`], any byte can be printed with this, except
+  /// that `b'\n'` still starts a new line.
+  #[inline]
+  pub fn write_byte(&mut self, byte: u8) {
+    if byte == b'\n' {
+      self.newline();
+      return;
+    }
+    if self.col >= COLUMNS {
+      self.newline();
+    }
+    let (x, y) = (i32::from(self.col) * 8, i32::from(self.row) * 8);
+    draw_glyph(x, y, byte, self.fg, Some(self.bg));
+    self.col += 1;
+  }
+}
+#[cfg(feature = "on_gba")]
+impl fmt::Write for BitmapConsole {
+  #[inline]
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    for c in s.chars() {
+      self.write_byte(if c == '\n' { b'\n' } else { glyph_for(c) });
+    }
+    Ok(())
+  }
+}
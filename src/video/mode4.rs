@@ -0,0 +1,208 @@
+//! Drawing to the [`VIDEO4_VRAM`] bitmaps.
+//!
+//! In video mode 4 background layer 2 is a 240x160 bitmap of 8-bit palette
+//! indexes into [`BG_PALETTE`]. There are two of these bitmaps, and they can be
+//! page flipped (see [`BitmapPage`]).
+//!
+//! ## The Byte Write Hazard
+//!
+//! VRAM can't actually be written one byte at a time. If you do an 8-bit write
+//! then the byte is copied into *both* halves of the 16-bit location being
+//! written, which overwrites the neighboring pixel as well. That's why
+//! [`VIDEO4_VRAM`] is modeled as holding [`u8x2`] values, and why
+//! [`put_pixel`] has to read the two pixels, change one of them, and then write
+//! them both back. That read-modify-write is fairly slow, so when you can you
+//! should draw two pixels at once with [`put_pixel_pair`], or draw entire
+//! spans with [`hline`] (which uses 32-bit writes for most of the span).
+//! Text from [`draw_text`] is drawn a pixel pair at a time too, and only reads
+//! a pair back when just one of its pixels changes.
+
+use super::{
+  raster,
+  text::{glyph_for, glyph_row},
+};
+use crate::{mem::set_u32x80_unchecked, prelude::*};
+
+/// The width of a mode 4 bitmap.
+pub const WIDTH: i32 = 240;
+
+/// The height of a mode 4 bitmap.
+pub const HEIGHT: i32 = 160;
+
+#[inline]
+fn row(page: BitmapPage, y: usize) -> VolBlock<u8x2, Safe, Safe, 120> {
+  VIDEO4_VRAM.get_frame(page.index()).unwrap().get_row(y).unwrap()
+}
+
+#[inline]
+fn put_unchecked(page: BitmapPage, x: usize, y: usize, index: u8) {
+  let addr = row(page, y).index(x / 2);
+  let pair = addr.read();
+  addr.write(if x & 1 == 0 {
+    pair.with_low(index)
+  } else {
+    pair.with_high(index)
+  });
+}
+
+#[inline]
+fn span(page: BitmapPage, y: usize, x0: usize, x1: usize, index: u8) {
+  let row = row(page, y);
+  let pair = u8x2::from([index, index]);
+  let word = u32::from_ne_bytes([index; 4]);
+  let mut x = x0;
+  let mut end = x1 + 1;
+  if x % 2 == 1 {
+    put_unchecked(page, x, y, index);
+    x += 1;
+  }
+  if end % 2 == 1 && end > x {
+    put_unchecked(page, end - 1, y, index);
+    end -= 1;
+  }
+  // `x` and `end` are now both even.
+  if x & 0b11 != 0 && x < end {
+    row.index(x / 2).write(pair);
+    x += 2;
+  }
+  while x + 4 <= end {
+    // Safety: rows start aligned to 4, and `x` is a multiple of 4.
+    unsafe { row.index(x / 2).cast::<u32>() }.write(word);
+    x += 4;
+  }
+  if x < end {
+    row.index(x / 2).write(pair);
+  }
+}
+
+/// Fills the entire page with a palette index.
+#[inline]
+pub fn clear_to(page: BitmapPage, index: u8) {
+  let word = u32::from_ne_bytes([index; 4]);
+  let p = VIDEO4_VRAM.get_frame(page.index()).unwrap().as_usize() as *mut _;
+  unsafe { set_u32x80_unchecked(p, word, 120_usize) };
+}
+
+/// Sets a single pixel, if it's on the screen.
+///
+/// This must do a read-modify-write of two pixels (see the module docs).
+#[inline]
+pub fn put_pixel(page: BitmapPage, x: i32, y: i32, index: u8) {
+  if let Some((x, y)) = raster::clip_point(x, y, WIDTH, HEIGHT) {
+    put_unchecked(page, x, y, index);
+  }
+}
+
+/// Sets two horizontally adjacent pixels at once, if they're on the screen.
+///
+/// The low byte of `indexes` is the left pixel and the high byte is the right
+/// pixel. Pixels are paired starting from even columns, so if `x` is odd it's
+/// rounded down.
+#[inline]
+pub fn put_pixel_pair(page: BitmapPage, x: i32, y: i32, indexes: u16) {
+  if let Some((x, y)) = raster::clip_point(x, y, WIDTH, HEIGHT) {
+    row(page, y).index(x / 2).write(u8x2::from(indexes.to_le_bytes()));
+  }
+}
+
+/// Draws a horizontal line from `x0` to `x1` (inclusive) on row `y`.
+#[inline]
+pub fn hline(page: BitmapPage, x0: i32, x1: i32, y: i32, index: u8) {
+  if let (true, Some((x0, x1))) =
+    ((0..HEIGHT).contains(&y), raster::clip_span(x0, x1, WIDTH))
+  {
+    span(page, y as usize, x0, x1, index);
+  }
+}
+
+/// Draws a line from `(x0, y0)` to `(x1, y1)`, including both end points.
+#[inline]
+pub fn line(page: BitmapPage, x0: i32, y0: i32, x1: i32, y1: i32, index: u8) {
+  raster::line(x0, y0, x1, y1, WIDTH, HEIGHT, |x, y| {
+    put_unchecked(page, x, y, index)
+  });
+}
+
+/// Fills a `w` by `h` rectangle with its top left at `(x, y)`.
+#[inline]
+pub fn rect_filled(
+  page: BitmapPage, x: i32, y: i32, w: i32, h: i32, index: u8,
+) {
+  raster::rect_filled(x, y, w, h, WIDTH, HEIGHT, |y, x0, x1| {
+    span(page, y, x0, x1, index)
+  });
+}
+
+/// Draws one glyph of the built-in font with its top left at `(x, y)`.
+///
+/// This is like [`mode3::draw_glyph`], with palette
+/// indexes for the colors: the glyph's lines are `fg`, and the rest of the
+/// 8x8 cell is `bg`, or is left alone if `bg` is `None`.
+#[inline]
+pub fn draw_glyph(
+  page: BitmapPage, x: i32, y: i32, glyph: u8, fg: u8, bg: Option<u8>,
+) {
+  if x <= -8 || x >= WIDTH || y <= -8 || y >= HEIGHT {
+    return;
+  }
+  for dy in 0..8 {
+    let py = y + dy;
+    if !(0..HEIGHT).contains(&py) {
+      continue;
+    }
+    let bits = glyph_row(glyph, dy as usize);
+    let pixel = |px: i32| -> Option<u8> {
+      let dx = px - x;
+      if !(0..8).contains(&dx) {
+        None
+      } else if bits & (1 << dx) != 0 {
+        Some(fg)
+      } else {
+        bg
+      }
+    };
+    let vram_row = row(page, py as usize);
+    // Pairs start on even columns, so the first pair can start left of `x`.
+    let mut px = x & !1;
+    while px < x + 8 {
+      if (0..WIDTH).contains(&px) {
+        let addr = vram_row.index(px as usize / 2);
+        match (pixel(px), pixel(px + 1)) {
+          (Some(left), Some(right)) => addr.write(u8x2::from([left, right])),
+          (Some(left), None) => addr.write(addr.read().with_low(left)),
+          (None, Some(right)) => addr.write(addr.read().with_high(right)),
+          (None, None) => (),
+        }
+      }
+      px += 2;
+    }
+  }
+}
+
+/// Draws text with the built-in font, with the top left of the first
+/// character at `(x, y)`.
+///
+/// This works like [`mode3::draw_text`], with
+/// palette indexes for the colors (see [`draw_glyph`]).
+#[inline]
+pub fn draw_text(
+  page: BitmapPage, x: i32, y: i32, fg: u8, bg: Option<u8>, text: &str,
+) {
+  let (mut cx, mut cy) = (x, y);
+  for c in text.chars() {
+    if c == '\n' {
+      (cx, cy) = (x, cy.saturating_add(8));
+      continue;
+    }
+    draw_glyph(page, cx, cy, glyph_for(c), fg, bg);
+    cx = cx.saturating_add(8);
+  }
+}
+
+/// Toggles the displayed page, returning the page that's now offscreen.
+///
+/// This is the same as [`flip_page`], it's here for convenience.
+#[inline]
+pub fn flip() -> BitmapPage {
+  flip_page()
+}
@@ -0,0 +1,101 @@
+//! Drawing to the [`VIDEO5_VRAM`] bitmaps.
+//!
+//! In video mode 5 background layer 2 is a 160x128 bitmap of direct [`Color`]
+//! values. There are two of these bitmaps, and they can be page flipped (see
+//! [`BitmapPage`]). Page 0 starts at `0x0600_0000` and page 1 starts at
+//! `0x0600_A000`.
+//!
+//! Because the bitmap is smaller than the screen, by default it only covers the
+//! top left part of the display. Background layer 2 is an affine layer in this
+//! mode though, so [`stretch_to_screen`] can be used to scale the bitmap up to
+//! cover the whole screen.
+
+use super::raster;
+use crate::{mem::set_u32x80_unchecked, prelude::*};
+
+/// The width of a mode 5 bitmap.
+pub const WIDTH: i32 = 160;
+
+/// The height of a mode 5 bitmap.
+pub const HEIGHT: i32 = 128;
+
+#[inline]
+fn span(page: BitmapPage, y: usize, x0: usize, x1: usize, color: Color) {
+  let row = VIDEO5_VRAM.get_frame(page.index()).unwrap().get_row(y).unwrap();
+  for addr in row.iter().skip(x0).take(x1 - x0 + 1) {
+    addr.write(color);
+  }
+}
+
+/// Fills the entire page with a color.
+#[inline]
+pub fn clear_to(page: BitmapPage, color: Color) {
+  let word = u32::from(color.0) << 16 | u32::from(color.0);
+  let p = VIDEO5_VRAM.get_frame(page.index()).unwrap().as_usize() as *mut _;
+  unsafe { set_u32x80_unchecked(p, word, 128_usize) };
+}
+
+/// Sets a single pixel, if it's within the bitmap.
+#[inline]
+pub fn put_pixel(page: BitmapPage, x: i32, y: i32, color: Color) {
+  if let Some((x, y)) = raster::clip_point(x, y, WIDTH, HEIGHT) {
+    VIDEO5_VRAM.get_frame(page.index()).unwrap().index(x, y).write(color);
+  }
+}
+
+/// Draws a line from `(x0, y0)` to `(x1, y1)`, including both end points.
+#[inline]
+pub fn line(
+  page: BitmapPage, x0: i32, y0: i32, x1: i32, y1: i32, color: Color,
+) {
+  let frame = VIDEO5_VRAM.get_frame(page.index()).unwrap();
+  raster::line(x0, y0, x1, y1, WIDTH, HEIGHT, |x, y| {
+    frame.index(x, y).write(color)
+  });
+}
+
+/// Fills a `w` by `h` rectangle with its top left at `(x, y)`.
+#[inline]
+pub fn rect_filled(
+  page: BitmapPage, x: i32, y: i32, w: i32, h: i32, color: Color,
+) {
+  raster::rect_filled(x, y, w, h, WIDTH, HEIGHT, |y, x0, x1| {
+    span(page, y, x0, x1, color)
+  });
+}
+
+/// Toggles the displayed page, returning the page that's now offscreen.
+///
+/// This is the same as [`flip_page`], it's here for convenience.
+#[inline]
+pub fn flip() -> BitmapPage {
+  flip_page()
+}
+
+/// Sets the BG2 affine parameters so the bitmap is stretched to fill the
+/// entire 240x160 screen.
+///
+/// Each screen pixel steps `160/240` of a bitmap pixel horizontally and
+/// `128/160` of a bitmap pixel vertically. Those don't come out exactly in
+/// 8-bit fixed point, so the values are rounded to the nearest step.
+#[inline]
+pub fn stretch_to_screen() {
+  BG2PA.write(i16fx8::from_bits(0xAB));
+  BG2PB.write(i16fx8::from_bits(0));
+  BG2PC.write(i16fx8::from_bits(0));
+  BG2PD.write(i16fx8::from_bits(0xCD));
+  BG2X.write(i32fx8::from_bits(0));
+  BG2Y.write(i32fx8::from_bits(0));
+}
+
+/// Resets the BG2 affine parameters so that the bitmap is shown at its normal
+/// size in the top left of the screen.
+#[inline]
+pub fn unstretch() {
+  BG2PA.write(i16fx8::from_bits(1 << 8));
+  BG2PB.write(i16fx8::from_bits(0));
+  BG2PC.write(i16fx8::from_bits(0));
+  BG2PD.write(i16fx8::from_bits(1 << 8));
+  BG2X.write(i32fx8::from_bits(0));
+  BG2Y.write(i32fx8::from_bits(0));
+}
@@ -30,8 +30,39 @@
 //! the [ObjDisplayStyle] of all [ObjAttr0] fields so that any objects you're
 //! not using don't appear on the screen. Otherwise, you'll end up with
 //! un-configured objects appearing in the upper left corner of the display.
+//! [`init_oam`] does this (and also sets every affine parameter set to the
+//! identity matrix).
+//!
+//! ## Tile indexes and 8bpp objects
+//!
+//! An object's tile index (in [ObjAttr2]) always counts 32 byte units of
+//! object VRAM, which is the size of a 4bpp tile, even for an 8bpp (256
+//! color) object. An 8bpp tile is 64 bytes, so it takes two of those "slots",
+//! and 8bpp tile `n` is at tile index `2 * n` (see
+//! [`ObjAttr::set_tile_8bpp`]). The index of an 8bpp object should always be
+//! even.
+//!
+//! How the rest of an object's tiles are found depends on the
+//! `obj_vram_1d` setting of [`DISPCNT`]:
+//! * With 1D mapping, the object's tiles are one after the other, row by row,
+//!   so an object uses [`ObjSize::tile_slots`] slots in a row. An 8bpp object's
+//!   tiles are 2 slots apart. [`copy_obj_tiles_4bpp`] and
+//!   [`copy_obj_tiles_8bpp`] copy tiles in this order.
+//! * With 2D mapping, object VRAM is treated as a 32 slot wide grid, and each
+//!   row of the object's tiles starts 32 slots after the previous one. An 8bpp
+//!   object's row is twice as many slots wide, so only 16 8bpp tiles fit across
+//!   the grid, and the hardware ignores the low bit of the tile index.
+//!
+//! [`ObjTileMapping`] does the index math for either one.
+//! [`copy_obj_4bpp`] and [`copy_obj_8bpp`] copy a whole object's tiles to
+//! where that mapping needs them, and a [`TileAllocator`] set up with
+//! [`with_obj_mapping`](TileAllocator::with_obj_mapping) finds room for them.
+//!
+//! 8bpp pixels index the whole object palette, and the object's `palbank` is
+//! ignored.
 
 use super::*;
+use crate::math::Vec2;
 
 /// How the object should be displayed.
 ///
@@ -52,18 +83,41 @@ pub enum ObjDisplayStyle {
 }
 
 /// What special effect the object interacts with
+///
+/// This works the same with any [`ObjDisplayStyle`] other than
+/// `NotDisplayed` (which turns the object off entirely, window included).
+/// With the affine styles the effect applies to the transformed object, and
+/// with `DoubleSizeAffine` to all of its doubled area.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(u16)]
 pub enum ObjEffectMode {
   /// The default, no special effect interaction
   #[default]
   Normal = 0 << 10,
-  /// The object counts as a potential 1st target for alpha blending,
-  /// regardless of the actual blend control settings register configuration.
+  /// The object is always alpha blended with whatever is under it.
+  ///
+  /// This ignores both the `target1_obj` bit and the effect mode of
+  /// [`BLDCNT`]: the object is always a first target, and always blends with
+  /// the [`BLDALPHA`] coefficients, even when `BLDCNT` is set to some other
+  /// effect (or none). The pixel under it still has to be one of `BLDCNT`'s
+  /// target2 layers, or nothing happens. Where it isn't, `BLDCNT`'s own
+  /// brighten or darken effect (if any) can apply to the object instead.
+  ///
+  /// See [`OamShadow::set_translucent`].
   SemiTransparent = 1 << 10,
   /// The object is not displayed. Instead, all non-transparent pixels in this
   /// object become part of the "OBJ Window" mask.
+  ///
+  /// The mask is only used when [`DISPCNT`] has `enable_obj_win` set, and
+  /// [`WINOUT`] picks the layers shown within it. See
+  /// [`OamShadow::set_obj_window`].
   Window = 2 << 10,
+  /// Not a valid setting.
+  ///
+  /// The hardware doesn't define what this does, so don't use it. It's here
+  /// so that reading attr0 from OAM that was never set (which is garbage at
+  /// boot) still gives a value.
+  Prohibited = 3 << 10,
 }
 
 /// The shape of an object.
@@ -86,18 +140,182 @@ pub enum ObjShape {
   Vertical = 2 << 14,
 }
 
+/// The size of an object, combining its shape and size settings.
+///
+/// The hardware stores the shape in [`ObjAttr0`] and the size in [`ObjAttr1`],
+/// and the meaning of each depends on the other. Using this type for both
+/// means only the 12 valid combinations can be picked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[allow(missing_docs)]
+pub enum ObjSize {
+  #[default]
+  _8x8,
+  _16x16,
+  _32x32,
+  _64x64,
+  _16x8,
+  _32x8,
+  _32x16,
+  _64x32,
+  _8x16,
+  _8x32,
+  _16x32,
+  _32x64,
+}
+impl ObjSize {
+  /// Combines a shape and size setting (`0..4`, this is truncated).
+  #[inline]
+  #[must_use]
+  pub const fn from_shape_size(shape: ObjShape, size: u16) -> Self {
+    use ObjSize::*;
+    let sizes = match shape {
+      ObjShape::Square => [_8x8, _16x16, _32x32, _64x64],
+      ObjShape::Horizontal => [_16x8, _32x8, _32x16, _64x32],
+      ObjShape::Vertical => [_8x16, _8x32, _16x32, _32x64],
+    };
+    sizes[(size & 0b11) as usize]
+  }
+
+  /// The shape setting for this size.
+  #[inline]
+  #[must_use]
+  pub const fn shape(self) -> ObjShape {
+    match self as u8 / 4 {
+      0 => ObjShape::Square,
+      1 => ObjShape::Horizontal,
+      _ => ObjShape::Vertical,
+    }
+  }
+
+  /// The size setting (`0..4`) for this size.
+  #[inline]
+  #[must_use]
+  pub const fn size(self) -> u16 {
+    (self as u16) & 0b11
+  }
+
+  /// The `(width, height)` in pixels.
+  #[inline]
+  #[must_use]
+  pub const fn dimensions(self) -> (u16, u16) {
+    use ObjSize::*;
+    match self {
+      _8x8 => (8, 8),
+      _16x16 => (16, 16),
+      _32x32 => (32, 32),
+      _64x64 => (64, 64),
+      _16x8 => (16, 8),
+      _32x8 => (32, 8),
+      _32x16 => (32, 16),
+      _64x32 => (64, 32),
+      _8x16 => (8, 16),
+      _8x32 => (8, 32),
+      _16x32 => (16, 32),
+      _32x64 => (32, 64),
+    }
+  }
+
+  /// The number of 8x8 tiles an object of this size uses.
+  #[inline]
+  #[must_use]
+  pub const fn tile_count(self) -> u16 {
+    let (w, h) = self.dimensions();
+    (w / 8) * (h / 8)
+  }
+
+  /// The number of 32 byte tile slots an object of this size uses in object
+  /// VRAM (with 1D mapping), which is twice the tile count for an 8bpp
+  /// object.
+  #[inline]
+  #[must_use]
+  pub const fn tile_slots(self, bpp8: bool) -> u16 {
+    if bpp8 {
+      self.tile_count() * 2
+    } else {
+      self.tile_count()
+    }
+  }
+}
+
+/// How an object's tiles are laid out in object VRAM, set by the
+/// `obj_vram_1d` bit of [`DISPCNT`].
+///
+/// See the [module docs](self) for the details of each one. 2D is the default
+/// because it's what a `DISPCNT` of 0 gives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ObjTileMapping {
+  /// Each row of an object's tiles is 32 slots after the one before, as if
+  /// object VRAM was a grid 32 slots wide.
+  #[default]
+  TwoD,
+  /// An object's tiles are all one after the other.
+  OneD,
+}
+impl ObjTileMapping {
+  /// The mapping that [`DISPCNT`] is set to now.
+  #[inline]
+  #[must_use]
+  pub fn current() -> Self {
+    if DISPCNT.read().obj_vram_1d() {
+      Self::OneD
+    } else {
+      Self::TwoD
+    }
+  }
+
+  /// The slot of the tile `tile_x` tiles across and `tile_y` tiles down in an
+  /// object that starts at `first_slot`.
+  ///
+  /// For an 8bpp object this is the first of the tile's two slots.
+  #[inline]
+  #[must_use]
+  pub const fn tile_slot(
+    self, first_slot: u16, size: ObjSize, bpp8: bool, tile_x: u16, tile_y: u16,
+  ) -> u16 {
+    let step = if bpp8 { 2 } else { 1 };
+    let row_slots = match self {
+      Self::TwoD => 32,
+      Self::OneD => (size.dimensions().0 / 8) * step,
+    };
+    first_slot + tile_y * row_slots + tile_x * step
+  }
+
+  /// The number of slots from the first slot of an object to just past its
+  /// last one.
+  ///
+  /// With 1D mapping this is the same as [`ObjSize::tile_slots`]. With 2D
+  /// mapping it includes the rest of each row of the grid (that the object
+  /// doesn't use) up to its last row.
+  #[inline]
+  #[must_use]
+  pub const fn slot_span(self, size: ObjSize, bpp8: bool) -> u16 {
+    let (w, h) = size.dimensions();
+    self.tile_slot(0, size, bpp8, w / 8 - 1, h / 8 - 1)
+      + if bpp8 { 2 } else { 1 }
+  }
+}
+
 /// Object Attributes, field 0 of the entry.
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct ObjAttr0(u16);
 impl ObjAttr0 {
   pub_const_fn_new_zeroed!();
-  u16_int_field!(0 - 7, y, with_y);
-  u16_enum_field!(8 - 9: ObjDisplayStyle, style, with_style);
-  u16_enum_field!(10 - 11: ObjEffectMode, mode, with_mode);
+  register_int_field!(0 - 7, y, with_y, Y_MASK);
+  register_enum_field!(
+    8 - 9: ObjDisplayStyle [Normal, Affine, NotDisplayed, DoubleSizeAffine],
+    style, with_style, STYLE_MASK
+  );
+  register_enum_field!(
+    10 - 11: ObjEffectMode [Normal, SemiTransparent, Window, Prohibited],
+    mode, with_mode, MODE_MASK
+  );
   u16_bool_field!(12, mosaic, with_mosaic);
   u16_bool_field!(13, bpp8, with_bpp8);
-  u16_enum_field!(14 - 15: ObjShape, shape, with_shape);
+  register_enum_field!(
+    14 - 15: ObjShape [Square, Horizontal, Vertical],
+    shape, with_shape, SHAPE_MASK
+  );
 }
 
 /// Object Attributes, field 1 of the entry.
@@ -106,22 +324,30 @@ impl ObjAttr0 {
 pub struct ObjAttr1(u16);
 impl ObjAttr1 {
   pub_const_fn_new_zeroed!();
-  u16_int_field!(0 - 8, x, with_x);
-  u16_int_field!(9 - 13, affine_index, with_affine_index);
+  register_int_field!(0 - 8, x, with_x, X_MASK);
+  register_int_field!(
+    9 - 13,
+    affine_index,
+    with_affine_index,
+    AFFINE_INDEX_MASK
+  );
   u16_bool_field!(12, hflip, with_hflip);
   u16_bool_field!(13, vflip, with_vflip);
-  u16_int_field!(14 - 15, size, with_size);
+  register_int_field!(14 - 15, size, with_size, SIZE_MASK);
 }
 
 /// Object Attributes, field 2 of the entry.
+///
+/// `tile_id` is in 32 byte units even for 8bpp objects (see the
+/// [module docs](self)).
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(transparent)]
 pub struct ObjAttr2(u16);
 impl ObjAttr2 {
   pub_const_fn_new_zeroed!();
-  u16_int_field!(0 - 9, tile_id, with_tile_id);
-  u16_int_field!(10 - 11, priority, with_priority);
-  u16_int_field!(12 - 15, palbank, with_palbank);
+  register_int_field!(0 - 9, tile_id, with_tile_id, TILE_ID_MASK);
+  register_int_field!(10 - 11, priority, with_priority, PRIORITY_MASK);
+  register_int_field!(12 - 15, palbank, with_palbank, PALBANK_MASK);
 }
 
 /// Object Attributes.
@@ -133,28 +359,802 @@ impl ObjAttr2 {
 pub struct ObjAttr(pub ObjAttr0, pub ObjAttr1, pub ObjAttr2);
 #[allow(missing_docs)]
 impl ObjAttr {
+  /// An object that isn't displayed.
+  pub const HIDDEN: Self = Self(
+    ObjAttr0::new().with_style(ObjDisplayStyle::NotDisplayed),
+    ObjAttr1::new(),
+    ObjAttr2::new(),
+  );
+
   #[inline]
   pub const fn new() -> Self {
     Self(ObjAttr0::new(), ObjAttr1::new(), ObjAttr2::new())
   }
+  /// The object's size, from the shape in attr0 and the size in attr1.
+  #[inline]
+  #[must_use]
+  pub const fn size(self) -> ObjSize {
+    ObjSize::from_shape_size(self.0.shape(), self.1.size())
+  }
+  /// Sets both the shape and size bits.
+  #[inline]
+  #[must_use]
+  pub const fn with_size(self, size: ObjSize) -> Self {
+    Self(self.0.with_shape(size.shape()), self.1.with_size(size.size()), self.2)
+  }
+  #[inline]
+  pub const fn set_size(&mut self, size: ObjSize) {
+    *self = self.with_size(size);
+  }
+  /// Makes the object use affine display with the affine slot given.
+  ///
+  /// The `double_size` option doubles the area that the object is drawn
+  /// within, so that a rotated or scaled up object isn't clipped at the edges
+  /// of its normal size.
+  #[inline]
+  #[must_use]
+  pub const fn with_affine(self, slot: AffineSlot, double_size: bool) -> Self {
+    let style = if double_size {
+      ObjDisplayStyle::DoubleSizeAffine
+    } else {
+      ObjDisplayStyle::Affine
+    };
+    Self(
+      self.0.with_style(style),
+      self.1.with_affine_index(slot.index() as u16),
+      self.2,
+    )
+  }
+  #[inline]
+  pub const fn set_affine(&mut self, slot: AffineSlot, double_size: bool) {
+    *self = self.with_affine(slot, double_size);
+  }
   #[inline]
-  pub fn set_y(&mut self, y: u16) {
+  pub const fn set_y(&mut self, y: u16) {
     self.0 = self.0.with_y(y);
   }
   #[inline]
-  pub fn set_style(&mut self, style: ObjDisplayStyle) {
+  pub const fn set_style(&mut self, style: ObjDisplayStyle) {
     self.0 = self.0.with_style(style);
   }
+  /// Sets the special effect mode (see [`ObjEffectMode`]).
   #[inline]
-  pub fn set_x(&mut self, x: u16) {
+  pub const fn set_mode(&mut self, mode: ObjEffectMode) {
+    self.0 = self.0.with_mode(mode);
+  }
+  /// Sets if the object uses the mosaic effect.
+  ///
+  /// The size of the mosaic blocks is set by the object half of [`MOSAIC`].
+  #[inline]
+  pub const fn set_mosaic(&mut self, mosaic: bool) {
+    self.0 = self.0.with_mosaic(mosaic);
+  }
+  #[inline]
+  pub const fn set_x(&mut self, x: u16) {
     self.1 = self.1.with_x(x);
   }
   #[inline]
-  pub fn set_tile_id(&mut self, id: u16) {
+  pub const fn set_tile_id(&mut self, id: u16) {
     self.2 = self.2.with_tile_id(id);
   }
+  /// Sets the tile index from an 8bpp tile number.
+  ///
+  /// The hardware counts tile indexes in 32 byte (4bpp tile) units no matter
+  /// the color depth, so this sets the index to `2 * tile`, which is where
+  /// 8bpp tile `tile` starts. It doesn't set 8bpp mode, see
+  /// [`with_bpp8`](Self::with_bpp8).
+  #[inline]
+  pub const fn set_tile_8bpp(&mut self, tile: u16) {
+    self.set_tile_id(tile * 2);
+  }
+  /// Sets if the object uses 8bpp (256 color) tiles.
+  #[inline]
+  #[must_use]
+  pub const fn with_bpp8(self, bpp8: bool) -> Self {
+    Self(self.0.with_bpp8(bpp8), self.1, self.2)
+  }
+  #[inline]
+  pub const fn set_bpp8(&mut self, bpp8: bool) {
+    self.0 = self.0.with_bpp8(bpp8);
+  }
   #[inline]
-  pub fn set_palbank(&mut self, palbank: u16) {
+  pub const fn set_palbank(&mut self, palbank: u16) {
     self.2 = self.2.with_palbank(palbank);
   }
 }
+
+/// Copies 4bpp tiles into object VRAM, starting at tile slot `first_slot`.
+///
+/// This uses [`copy_words`](crate::mem::copy_words), so all but the smallest
+/// copies go by DMA3, and VRAM is only written with full 32-bit writes.
+///
+/// ## Panics
+/// * All of the tiles must fit within object VRAM (slots `0..1024`).
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_obj_tiles_4bpp(first_slot: usize, tiles: &[Tile4]) {
+  assert!(first_slot + tiles.len() <= OBJ_TILES.len(), "tiles out of range");
+  let p = OBJ_TILES.index(first_slot).as_usize() as *mut u32;
+  unsafe { copy_words_to(p, tiles.as_ptr().cast(), tiles.len() * 8) };
+}
+
+/// Copies 8bpp tiles into object VRAM, starting at tile slot `first_slot`.
+///
+/// Each tile takes two slots, so the tile index to use for the first tile is
+/// `first_slot` (see the [module docs](self)). This uses
+/// [`copy_words`](crate::mem::copy_words), so all but the smallest copies go
+/// by DMA3, and VRAM is only written with full 32-bit writes.
+///
+/// ## Panics
+/// * The first slot must be even.
+/// * All of the tiles must fit within object VRAM (slots `0..1024`).
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_obj_tiles_8bpp(first_slot: usize, tiles: &[Tile8]) {
+  assert!(
+    first_slot.is_multiple_of(2),
+    "8bpp tiles must start on an even slot"
+  );
+  let slots = tiles.len() * 2;
+  assert!(first_slot + slots <= OBJ_TILES.len(), "tiles out of range");
+  let p = OBJ_TILES.index(first_slot).as_usize() as *mut u32;
+  unsafe { copy_words_to(p, tiles.as_ptr().cast(), slots * 8) };
+}
+
+/// Copies the 4bpp tiles of a whole object into object VRAM, to where
+/// `mapping` puts them for an object at tile slot `first_slot`.
+///
+/// `tiles` are in rows, left to right and then top to bottom, which is also the
+/// 1D order. With 2D mapping each row goes 32 slots after the one before, and
+/// the slots in between are left alone.
+///
+/// ## Panics
+/// * There must be [`tile_count`](ObjSize::tile_count) tiles.
+/// * With 2D mapping, each row has to fit in one row of the grid (the first
+///   slot's column plus the object's width can be at most 32).
+/// * All of the object must fit within object VRAM (slots `0..1024`).
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_obj_4bpp(
+  first_slot: usize, size: ObjSize, mapping: ObjTileMapping, tiles: &[Tile4],
+) {
+  copy_obj_rows(first_slot, size, false, mapping, tiles);
+}
+
+/// Copies the 8bpp tiles of a whole object into object VRAM, to where
+/// `mapping` puts them for an object at tile slot `first_slot`.
+///
+/// This is like [`copy_obj_4bpp`], with two slots per tile.
+///
+/// ## Panics
+/// * The first slot must be even.
+/// * Otherwise the same as [`copy_obj_4bpp`].
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_obj_8bpp(
+  first_slot: usize, size: ObjSize, mapping: ObjTileMapping, tiles: &[Tile8],
+) {
+  assert!(
+    first_slot.is_multiple_of(2),
+    "8bpp tiles must start on an even slot"
+  );
+  copy_obj_rows(first_slot, size, true, mapping, tiles);
+}
+
+/// Copies an object's tiles one row at a time, for [`copy_obj_4bpp`] and
+/// [`copy_obj_8bpp`].
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+fn copy_obj_rows<T>(
+  first_slot: usize, size: ObjSize, bpp8: bool, mapping: ObjTileMapping,
+  tiles: &[T],
+) {
+  assert_eq!(
+    tiles.len(),
+    usize::from(size.tile_count()),
+    "the wrong number of tiles for the object size"
+  );
+  let (w, _) = size.dimensions();
+  let row_tiles = usize::from(w / 8);
+  let row_slots = if bpp8 { row_tiles * 2 } else { row_tiles };
+  if mapping == ObjTileMapping::TwoD {
+    assert!(
+      first_slot % 32 + row_slots <= 32,
+      "the object's rows don't fit in the 2D grid"
+    );
+  }
+  let span = usize::from(mapping.slot_span(size, bpp8));
+  assert!(first_slot + span <= OBJ_TILES.len(), "tiles out of range");
+  for (y, row) in tiles.chunks_exact(row_tiles).enumerate() {
+    let slot = mapping.tile_slot(first_slot as u16, size, bpp8, 0, y as u16);
+    let p = OBJ_TILES.index(usize::from(slot)).as_usize() as *mut u32;
+    // Safety: the asserts mean the row is within object VRAM, and 8bpp tiles
+    // are just two 8 word slots each.
+    unsafe { copy_words_to(p, row.as_ptr().cast(), row_slots * 8) };
+  }
+}
+
+/// Writes the attributes of object `index` in OAM.
+///
+/// This writes the three attribute fields one at a time, leaving the affine
+/// parameter halfword that follows them in OAM unchanged.
+///
+/// ## Panics
+/// * The index must be in `0..128`.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn write_obj_attr(index: usize, attr: ObjAttr) {
+  OBJ_ATTR0.index(index).write(attr.0);
+  OBJ_ATTR1.index(index).write(attr.1);
+  OBJ_ATTR2.index(index).write(attr.2);
+}
+
+/// Reads the attributes of object `index` from OAM.
+///
+/// ## Panics
+/// * The index must be in `0..128`.
+#[inline]
+#[must_use]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn read_obj_attr(index: usize) -> ObjAttr {
+  ObjAttr(
+    OBJ_ATTR0.index(index).read(),
+    OBJ_ATTR1.index(index).read(),
+    OBJ_ATTR2.index(index).read(),
+  )
+}
+
+/// Sets all 128 objects in OAM to be not displayed.
+///
+/// OAM isn't cleared at boot, so call this before turning on the object layer.
+/// Only attr0 of each object is written, so the affine parameters are kept.
+#[inline]
+pub fn hide_all_objects() {
+  for addr in OBJ_ATTR0.iter() {
+    addr.write(ObjAttr::HIDDEN.0);
+  }
+}
+
+/// Hides `count` objects in OAM, starting at object `start`.
+///
+/// Like [`hide_all_objects`], only attr0 of each object is written.
+///
+/// ## Panics
+/// * The objects must all be within `0..128`.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn hide_objects(start: usize, count: usize) {
+  assert!(
+    start <= 128 && count <= 128 - start,
+    "objects {start}..{} are out of range",
+    start + count
+  );
+  for addr in OBJ_ATTR0.iter().skip(start).take(count) {
+    addr.write(ObjAttr::HIDDEN.0);
+  }
+}
+
+/// All of OAM, as words.
+const OAM_WORDS: VolBlock<u32, Safe, Safe, 256> =
+  unsafe { VolBlock::new(0x0700_0000) };
+
+/// The words of the first four entries of a new [`OamShadow`], which repeat
+/// through the rest of it.
+const INIT_OAM_WORDS: [u32; 8] = {
+  let entries = OamShadow::new().entries;
+  let four = [entries[0], entries[1], entries[2], entries[3]];
+  unsafe { core::mem::transmute::<[OamEntry; 4], [u32; 8]>(four) }
+};
+
+/// Sets all of OAM to a known state: every object hidden, and every affine
+/// parameter set the identity matrix.
+///
+/// OAM isn't cleared at boot, so call this before turning on the object layer
+/// (or else junk objects show up). Afterwards OAM is the same as after
+/// [`commit`](OamShadow::commit)ing a new [`OamShadow`].
+///
+/// This writes all 1 KiB of OAM a word at a time, which takes a small part of
+/// vblank, so it's also fine to call while the object layer is on (during
+/// vblank, so that no frame shows only some objects hidden).
+#[inline]
+pub fn init_oam() {
+  for (i, addr) in OAM_WORDS.iter().enumerate() {
+    addr.write(INIT_OAM_WORDS[i % 8]);
+  }
+}
+
+/// One of the 32 affine parameter sets in OAM.
+///
+/// Affine objects use [`ObjAttr::with_affine`] to select one of these. Many
+/// objects can share the same slot.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct AffineSlot(u8);
+impl AffineSlot {
+  /// Makes a handle to affine slot `index`.
+  ///
+  /// ## Panics
+  /// * The index must be in `0..32`.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(index: u8) -> Self {
+    assert!(index < 32, "affine slot out of range");
+    Self(index)
+  }
+
+  /// The slot's index.
+  #[inline]
+  #[must_use]
+  pub const fn index(self) -> usize {
+    self.0 as usize
+  }
+}
+
+/// The 2x2 matrix of an affine object, `[pa, pb; pc, pd]`.
+///
+/// The hardware uses this matrix to go from a screen pixel offset (relative
+/// to the object's center) to a texture pixel offset. That makes it the
+/// *inverse* of what you see: a matrix value of 0.5 makes the object look
+/// *twice* as big. The constructors here take the scale that you want to see
+/// on screen and handle the inversion for you.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct AffineMatrix {
+  pub pa: i16fx8,
+  pub pb: i16fx8,
+  pub pc: i16fx8,
+  pub pd: i16fx8,
+}
+impl AffineMatrix {
+  /// The identity matrix, the object is displayed normally.
+  pub const IDENTITY: Self = Self {
+    pa: i16fx8::from_bits(1 << 8),
+    pb: i16fx8::from_bits(0),
+    pc: i16fx8::from_bits(0),
+    pd: i16fx8::from_bits(1 << 8),
+  };
+
+  /// The identity matrix, the object is displayed normally.
+  #[inline]
+  #[must_use]
+  pub const fn identity() -> Self {
+    Self::IDENTITY
+  }
+
+  /// A matrix to scale and then rotate the object.
+  ///
+  /// * `scale_x` and `scale_y` are how much bigger the object should look (eg:
+  ///   2.0 is twice as big). A scale of 0 is treated as the smallest possible
+  ///   scale rather than dividing by 0.
+  /// * `angle` is a counter-clockwise [binary angle](crate::math).
+  #[inline]
+  #[must_use]
+  pub const fn from_scale_rotation(
+    scale_x: i16fx8, scale_y: i16fx8, angle: u16,
+  ) -> Self {
+    let sx = match scale_x.to_bits() {
+      0 => 1,
+      bits => bits as i32,
+    };
+    let sy = match scale_y.to_bits() {
+      0 => 1,
+      bits => bits as i32,
+    };
+    let sin = crate::math::sin(angle).to_bits() as i32;
+    let cos = crate::math::cos(angle).to_bits() as i32;
+    // the trig values have 14 fractional bits and scales have 8, so dividing
+    // and then multiplying by 4 gives the 8 fractional bits we want. Very
+    // small scales give entries too big for 8.8, which are clamped.
+    Self {
+      pa: clamp_8_8(((cos * 4) / sx) as i64),
+      pb: clamp_8_8(((-sin * 4) / sx) as i64),
+      pc: clamp_8_8(((sin * 4) / sy) as i64),
+      pd: clamp_8_8(((cos * 4) / sy) as i64),
+    }
+  }
+
+  /// A matrix that rotates the object counter-clockwise.
+  #[inline]
+  #[must_use]
+  pub const fn from_rotation(angle: u16) -> Self {
+    let one = i16fx8::from_bits(1 << 8);
+    Self::from_scale_rotation(one, one, angle)
+  }
+
+  /// A matrix that scales the object, with the scale as it appears on screen.
+  #[inline]
+  #[must_use]
+  pub const fn from_scale(scale_x: i16fx8, scale_y: i16fx8) -> Self {
+    Self::from_scale_rotation(scale_x, scale_y, 0)
+  }
+
+  /// A matrix that shears the object sideways: each row is pushed right by
+  /// `shear` pixels for every pixel it is below the center.
+  #[inline]
+  #[must_use]
+  pub const fn from_shear_x(shear: i16fx8) -> Self {
+    Self { pb: i16fx8::from_bits(-shear.to_bits()), ..Self::IDENTITY }
+  }
+
+  /// A matrix that shears the object up and down: each column is pushed down
+  /// by `shear` pixels for every pixel it is right of the center.
+  #[inline]
+  #[must_use]
+  pub const fn from_shear_y(shear: i16fx8) -> Self {
+    Self { pc: i16fx8::from_bits(-shear.to_bits()), ..Self::IDENTITY }
+  }
+
+  /// The transformation of `self` followed by the transformation of `other`,
+  /// as they appear on screen.
+  ///
+  /// So `from_scale(s, s).then(from_rotation(a))` scales and then rotates, the
+  /// same as [`from_scale_rotation`](Self::from_scale_rotation). Since the
+  /// hardware matrix is the inverse of what's seen, this is the matrix product
+  /// `self * other`.
+  ///
+  /// Each entry is worked out with 16 fractional bits, rounded to the nearest
+  /// 8.8 value at the end, and clamped if it doesn't fit.
+  #[inline]
+  #[must_use]
+  pub const fn then(self, other: Self) -> Self {
+    let [a, b, c, d] = self.to_obj_params();
+    let [e, f, g, h] = other.to_obj_params();
+    Self {
+      pa: dot_8_8(a, e, b, g),
+      pb: dot_8_8(a, f, b, h),
+      pc: dot_8_8(c, e, d, g),
+      pd: dot_8_8(c, f, d, h),
+    }
+  }
+
+  /// The matrix that undoes this one.
+  ///
+  /// ## Failure
+  /// * `None` if the determinant is 0, or so small that an entry of the inverse
+  ///   doesn't fit in 8.8 (the object would be squashed flat on screen, so
+  ///   there's nothing to undo).
+  #[inline]
+  #[must_use]
+  pub const fn inverse(self) -> Option<Self> {
+    let [a, b, c, d] = self.to_obj_params();
+    // 16 fractional bits.
+    let det = a as i64 * d as i64 - b as i64 * c as i64;
+    if det == 0 {
+      return None;
+    }
+    // each entry is divided by the determinant. An entry with 8 fractional
+    // bits, shifted up by 16, over 16 fractional bits, gives 8 again.
+    let entries = [d as i64, -(b as i64), -(c as i64), a as i64];
+    let mut out = [0; 4];
+    let mut i = 0;
+    while i < 4 {
+      let q = div_rounded(entries[i] << 16, det);
+      if q < i16::MIN as i64 || q > i16::MAX as i64 {
+        return None;
+      }
+      out[i] = q as i16;
+      i += 1;
+    }
+    Some(Self {
+      pa: i16fx8::from_bits(out[0]),
+      pb: i16fx8::from_bits(out[1]),
+      pc: i16fx8::from_bits(out[2]),
+      pd: i16fx8::from_bits(out[3]),
+    })
+  }
+
+  /// The matrix as the raw `[pa, pb, pc, pd]` bits, in the order the
+  /// [`ObjAffineSet`] BIOS call writes them.
+  #[inline]
+  #[must_use]
+  pub const fn to_obj_params(self) -> [i16; 4] {
+    [self.pa.to_bits(), self.pb.to_bits(), self.pc.to_bits(), self.pd.to_bits()]
+  }
+
+  /// The background parameters for this matrix, placed so that the
+  /// background pixel `texture_origin` shows at the screen pixel
+  /// `display_origin`.
+  ///
+  /// The whole background scales, rotates, and so on around that point. The
+  /// hardware's reference point is where the *top left* of the screen lands in
+  /// the background, so this works it out by taking `display_origin` back
+  /// through the matrix: `x = texture_origin.x - (pa * display_origin.x + pb *
+  /// display_origin.y)`, and the same for `y` with `pc` and `pd`.
+  #[inline]
+  #[must_use]
+  pub const fn to_bg_params(
+    self, display_origin: Vec2<i32>, texture_origin: Vec2<i32>,
+  ) -> BgAffineParams {
+    let [a, b, c, d] = self.to_obj_params();
+    let (a, b, c, d) = (a as i32, b as i32, c as i32, d as i32);
+    let (dx, dy) = (display_origin.x, display_origin.y);
+    let x = (texture_origin.x << 8) - (a * dx + b * dy);
+    let y = (texture_origin.y << 8) - (c * dx + d * dy);
+    BgAffineParams {
+      pa: self.pa,
+      pb: self.pb,
+      pc: self.pc,
+      pd: self.pd,
+      x: i32fx8::from_bits(x),
+      y: i32fx8::from_bits(y),
+    }
+  }
+}
+/// `a * b + c * d` for 8.8 bits, rounded to the nearest 8.8 value and clamped.
+const fn dot_8_8(a: i16, b: i16, c: i16, d: i16) -> i16fx8 {
+  // each product fits in an `i32`, but the sum might not.
+  let sum = (a as i32 * b as i32) as i64 + (c as i32 * d as i32) as i64;
+  clamp_8_8((sum + (1 << 7)) >> 8)
+}
+
+/// 8.8 `bits`, clamped to the range of an `i16fx8`.
+///
+/// This only uses `from_bits`, so it works the same with the `fixed` feature.
+const fn clamp_8_8(bits: i64) -> i16fx8 {
+  let clamped = if bits > i16::MAX as i64 {
+    i16::MAX
+  } else if bits < i16::MIN as i64 {
+    i16::MIN
+  } else {
+    bits as i16
+  };
+  i16fx8::from_bits(clamped)
+}
+
+/// `n / d`, rounded to the nearest (with halves away from zero).
+const fn div_rounded(n: i64, d: i64) -> i64 {
+  let q = (n.abs() + d.abs() / 2) / d.abs();
+  if (n < 0) != (d < 0) {
+    -q
+  } else {
+    q
+  }
+}
+
+impl Default for AffineMatrix {
+  #[inline]
+  fn default() -> Self {
+    Self::IDENTITY
+  }
+}
+
+/// Writes an affine matrix to its slot in OAM.
+///
+/// Each slot's four values are spread out across the unused halfwords of four
+/// object entries, this writes only to those halfwords.
+#[inline]
+pub fn write_affine_matrix(slot: AffineSlot, matrix: AffineMatrix) {
+  AFFINE_PARAM_A.index(slot.index()).write(matrix.pa);
+  AFFINE_PARAM_B.index(slot.index()).write(matrix.pb);
+  AFFINE_PARAM_C.index(slot.index()).write(matrix.pc);
+  AFFINE_PARAM_D.index(slot.index()).write(matrix.pd);
+}
+
+/// One 8 byte OAM entry: an object's attributes, and one affine halfword.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+struct OamEntry {
+  attr: ObjAttr,
+  affine: i16,
+}
+
+/// A copy of all of OAM, kept in RAM.
+///
+/// Changing OAM while the display is drawing can cause glitches in the
+/// objects, so the usual pattern is to edit a shadow copy during the frame,
+/// then copy it all to OAM with [`commit`](Self::commit) right after the start
+/// of vblank.
+///
+/// The layout is exactly that of OAM: 128 entries of 8 bytes, where each entry
+/// is an object's three attributes followed by one halfword of an affine
+/// parameter set. Affine set `n` is spread across the last halfword of
+/// objects `4n` through `4n + 3`.
+///
+/// A new shadow has every object [hidden](ObjAttr::HIDDEN) and every affine
+/// set as the identity matrix.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[repr(C, align(4))]
+pub struct OamShadow {
+  entries: [OamEntry; 128],
+}
+impl OamShadow {
+  /// Makes a shadow with all objects hidden.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    let mut entries = [OamEntry { attr: ObjAttr::HIDDEN, affine: 0 }; 128];
+    let mut i = 0;
+    while i < 128 {
+      // The `pa` and `pd` of each affine set are 1.0
+      entries[i].affine = 1 << 8;
+      entries[i + 3].affine = 1 << 8;
+      i += 4;
+    }
+    Self { entries }
+  }
+
+  /// Gets the attributes of object `index`.
+  ///
+  /// ## Panics
+  /// * The index must be in `0..128`.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn obj(&self, index: usize) -> &ObjAttr {
+    &self.entries[index].attr
+  }
+
+  /// Gets the attributes of object `index` mutably.
+  ///
+  /// ## Panics
+  /// * The index must be in `0..128`.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn obj_mut(&mut self, index: usize) -> &mut ObjAttr {
+    &mut self.entries[index].attr
+  }
+
+  /// Gets affine parameter set `index` mutably, as `[pa, pb, pc, pd]`.
+  ///
+  /// The values are the raw bits of 8.8 fixed point numbers, use
+  /// [`i16fx8::to_bits`](crate::fixed::Fixed::to_bits) to convert.
+  ///
+  /// ## Panics
+  /// * The index must be in `0..32`.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn affine_mut(&mut self, index: usize) -> [&mut i16; 4] {
+    let [a, b, c, d] = &mut self.entries[index * 4..][..4] else {
+      unreachable!()
+    };
+    [&mut a.affine, &mut b.affine, &mut c.affine, &mut d.affine]
+  }
+
+  /// Sets an affine matrix in the shadow.
+  #[inline]
+  pub fn set_affine(&mut self, slot: AffineSlot, matrix: AffineMatrix) {
+    let [pa, pb, pc, pd] = self.affine_mut(slot.index());
+    *pa = matrix.pa.to_bits();
+    *pb = matrix.pb.to_bits();
+    *pc = matrix.pc.to_bits();
+    *pd = matrix.pd.to_bits();
+  }
+
+  /// Sets an affine matrix in the shadow, using the BIOS to compute it.
+  ///
+  /// See [`ObjAffineSource`] for how the BIOS interprets the scale.
+  #[inline]
+  pub fn set_affine_from(
+    &mut self, slot: AffineSlot, source: &ObjAffineSource,
+  ) {
+    // The pointer has to come from the whole array, since the BIOS writes to
+    // the next three entries as well.
+    unsafe {
+      let entry = self.entries.as_mut_ptr().add(slot.index() * 4);
+      let dest = core::ptr::addr_of_mut!((*entry).affine);
+      ObjAffineSet(source, dest, 1, OBJ_AFFINE_STRIDE_OAM)
+    };
+  }
+
+  /// Hides all objects, while keeping the affine parameters.
+  #[inline]
+  pub fn hide_all(&mut self) {
+    for entry in self.entries.iter_mut() {
+      entry.attr = ObjAttr::HIDDEN;
+    }
+  }
+
+  /// Makes object `index` semi-transparent, and sets the blend coefficients.
+  ///
+  /// A semi-transparent object always blends with what's under it (see
+  /// [`ObjEffectMode::SemiTransparent`]), so [`BLDCNT`]'s `target1_obj` and
+  /// effect mode don't matter. This writes `alpha` to [`BLDALPHA`] right away
+  /// (it's one setting for the whole screen, shared with any other alpha
+  /// blending), and if `BLDCNT` doesn't have any target2 layers yet, it makes
+  /// every background and the backdrop target2 layers, so the object has
+  /// something to blend with. A `BLDCNT` that already has target2 layers is
+  /// left as it is.
+  ///
+  /// The object's attributes still need to be [`commit`](Self::commit)ted.
+  ///
+  /// ## Panics
+  /// * The index must be in `0..128`.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn set_translucent(&mut self, index: usize, alpha: BlendAlpha) {
+    self.entries[index].attr.set_mode(ObjEffectMode::SemiTransparent);
+    BLDALPHA.write(alpha);
+    let bldcnt = BLDCNT.read();
+    let has_target2 = bldcnt.target2_bg0()
+      || bldcnt.target2_bg1()
+      || bldcnt.target2_bg2()
+      || bldcnt.target2_bg3()
+      || bldcnt.target2_obj()
+      || bldcnt.target2_backdrop();
+    if !has_target2 {
+      BLDCNT.write(bldcnt.with_target2_all(true).with_target2_obj(false));
+    }
+  }
+
+  /// Makes object `index` part of the object window mask, instead of being
+  /// drawn (see [`ObjEffectMode::Window`]).
+  ///
+  /// ## Panics
+  /// * The index must be in `0..128`.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn set_obj_window(&mut self, index: usize) {
+    self.entries[index].attr.set_mode(ObjEffectMode::Window);
+  }
+
+  /// Makes object `index` a normal object again, with no special effect.
+  ///
+  /// ## Panics
+  /// * The index must be in `0..128`.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn clear_effect(&mut self, index: usize) {
+    self.entries[index].attr.set_mode(ObjEffectMode::Normal);
+  }
+
+  /// Sets if object `index` uses the mosaic effect.
+  ///
+  /// ## Panics
+  /// * The index must be in `0..128`.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn set_mosaic(&mut self, index: usize, mosaic: bool) {
+    self.entries[index].attr.set_mosaic(mosaic);
+  }
+
+  /// Hides `count` objects, starting at object `start`, while keeping the
+  /// affine parameters.
+  ///
+  /// ## Panics
+  /// * The objects must all be within `0..128`.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn hide_range(&mut self, start: usize, count: usize) {
+    for entry in &mut self.entries[start..][..count] {
+      entry.attr = ObjAttr::HIDDEN;
+    }
+  }
+
+  /// Copies the entire shadow to OAM.
+  ///
+  /// This should be called right after waiting for vblank.
+  #[inline]
+  pub fn commit(&self) {
+    self.commit_count(128);
+  }
+
+  /// Copies only the first `count` entries to OAM.
+  ///
+  /// The count is rounded up to a multiple of 4 (a 32 byte chunk), and
+  /// clamped to 128. Because the affine parameters are interleaved with the
+  /// objects, this also only copies the affine sets within those entries
+  /// (set `n` is within entries `4n..4n + 4`).
+  ///
+  /// The copy is done with [`copy_words`](crate::mem::copy_words), so it goes
+  /// by DMA3.
+  #[inline]
+  pub fn commit_count(&self, count: usize) {
+    let chunks = count.min(128).div_ceil(4);
+    unsafe {
+      copy_words_to(
+        OBJ_ATTR_ALL.index(0).as_usize() as *mut u32,
+        self.entries.as_ptr().cast(),
+        chunks * 8,
+      )
+    };
+  }
+}
+impl Default for OamShadow {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
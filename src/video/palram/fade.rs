@@ -0,0 +1,287 @@
+//! Fading palettes over several frames.
+//!
+//! The hardware brightness effect (see
+//! [`fade_to_black`]) only affects the layers
+//! picked in [`BLDCNT`], and can only go to black or white. Fading the palette
+//! itself instead affects everything drawn with the faded entries, and it can
+//! go to any color, or from one whole palette to another.
+//!
+//! Everything here works on a range of all 512 entries of PALRAM: `0..256` is
+//! the background palette and `256..512` is the object palette (see
+//! [`Palette::range`] and [`PalBank::range`]), so one fade can cover both.
+//! Unlike [`write_bank`](super::write_bank), index 0 of each bank is written
+//! like any other entry, so a range starting at 0 also fades the backdrop.
+//!
+//! ## Snapshots
+//!
+//! The colors being faded from are kept in a [`PaletteSnapshot`], which is a
+//! copy of all 512 entries. That's 1 KiB, which is a lot of IWRAM (or stack),
+//! so the usual place for one is an EWRAM static (see
+//! [`ewram_data!`](crate::ewram_data)).
+//!
+//! ## Timing
+//!
+//! Each step of a fade blends and writes every entry of the range, so it
+//! should be done during vblank, before the next frame starts to draw. The
+//! cost is in proportion to the length of the range, and the `palette_fade`
+//! example times a full 512 entry step and logs the cycle count to mGBA, to
+//! compare with the 83,776 cycles of vblank. The blending does all three
+//! channels of a color at once, but fading just the banks that are in use
+//! still leaves more of vblank for everything else.
+
+use super::{PalBank, Palette};
+use crate::prelude::*;
+use core::ops::Range;
+
+/// All of PALRAM: the background palette, then the object palette.
+const PALRAM: VolBlock<Color, Safe, Safe, 512> =
+  unsafe { VolBlock::new(0x0500_0000) };
+
+/// Where [`spread`] puts each channel: red in bits 0-4, blue in 10-14, and
+/// green in 21-25.
+///
+/// Each channel has room above it to be multiplied by up to 16 (and rounded),
+/// so all three can be blended at once with plain `u32` math.
+const SPREAD_MASK: u32 = 0x03E0_7C1F;
+
+/// Half of 16 in each channel of a spread color, to round to the nearest.
+const SPREAD_HALF: u32 = 8 | 8 << 10 | 8 << 21;
+
+#[inline]
+#[must_use]
+const fn spread(color: Color) -> u32 {
+  let c = color.0 as u32;
+  (c | c << 16) & SPREAD_MASK
+}
+
+/// Turns a sum of spread colors, weighted to a total of 16, back into a color.
+#[inline]
+#[must_use]
+const fn unspread(sum: u32) -> Color {
+  let c = (sum >> 4) & SPREAD_MASK;
+  Color((c | c >> 16) as u16 & 0x7FFF)
+}
+
+/// A copy of all 512 PALRAM entries, to fade from (or to).
+///
+/// Entries outside of whatever range is captured are left as they were, so
+/// one snapshot can hold the background and object palettes from different
+/// times.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[repr(C, align(4))]
+pub struct PaletteSnapshot([Color; 512]);
+impl PaletteSnapshot {
+  /// A snapshot of all black.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self([Color::BLACK; 512])
+  }
+
+  /// Copies a range of PALRAM into the snapshot.
+  ///
+  /// ## Panics
+  /// * The range must be within `0..512`.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn capture(&mut self, range: Range<usize>) {
+    let start = range.start;
+    for (color, addr) in self.0[range].iter_mut().zip(PALRAM.iter().skip(start))
+    {
+      *color = addr.read();
+    }
+  }
+
+  /// Writes a range of the snapshot back to PALRAM.
+  ///
+  /// ## Panics
+  /// * The range must be within `0..512`.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn restore(&self, range: Range<usize>) {
+    let start = range.start;
+    for (color, addr) in self.0[range].iter().zip(PALRAM.iter().skip(start)) {
+      addr.write(*color);
+    }
+  }
+
+  /// The colors of the snapshot, indexed like PALRAM.
+  #[inline]
+  #[must_use]
+  pub const fn colors(&self) -> &[Color; 512] {
+    &self.0
+  }
+
+  /// The colors of the snapshot, for building a palette to fade to with
+  /// [`fade_between`].
+  #[inline]
+  #[must_use]
+  pub fn colors_mut(&mut self) -> &mut [Color; 512] {
+    &mut self.0
+  }
+
+  /// The colors of one palette in the snapshot.
+  #[inline]
+  #[must_use]
+  pub fn palette_mut(&mut self, palette: Palette) -> &mut [Color] {
+    &mut self.0[palette.range()]
+  }
+
+  /// The colors of one bank in the snapshot.
+  #[inline]
+  #[must_use]
+  pub fn bank_mut(&mut self, bank: PalBank) -> &mut [Color] {
+    &mut self.0[bank.range()]
+  }
+}
+impl Default for PaletteSnapshot {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Writes a range of PALRAM with the colors of `a` blended `progress / 16` of
+/// the way to the colors of `b`.
+///
+/// This is a cross-fade: 0 writes exactly the colors of `a`, and 16 (or more)
+/// writes exactly the colors of `b`. Each color is blended like
+/// [`Color::blend`].
+///
+/// ## Panics
+/// * The range must be within `0..512`.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn fade_between(
+  a: &PaletteSnapshot, b: &PaletteSnapshot, range: Range<usize>, progress: u16,
+) {
+  let w = u32::from(progress.min(16));
+  let start = range.start;
+  let pairs = a.0[range.clone()].iter().zip(&b.0[range]);
+  for ((a, b), addr) in pairs.zip(PALRAM.iter().skip(start)) {
+    addr.write(unspread(spread(*a) * (16 - w) + spread(*b) * w + SPREAD_HALF));
+  }
+}
+
+/// Fades a range of PALRAM to one color over a number of frames.
+///
+/// Making the fade takes a snapshot of the range, and then each
+/// [`step`](Self::step) (once per frame, during vblank) writes the snapshot
+/// blended a little further towards the target color. After the last step the
+/// whole range is exactly the target color. [`step_back`](Self::step_back)
+/// goes the other way, so a fade out can be turned around to fade back in,
+/// and [`cancel`](Self::cancel) puts the snapshot back all at once.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// use gba::video::palram::fade::{FadeToColor, PaletteSnapshot};
+/// gba::ewram_data! {
+///   static mut SNAPSHOT: PaletteSnapshot = PaletteSnapshot::new();
+/// }
+/// let snapshot = unsafe { &mut *core::ptr::addr_of_mut!(SNAPSHOT) };
+/// // both palettes, to black, over half a second.
+/// let mut fade = FadeToColor::new(snapshot, 0..512, Color::BLACK, 30);
+/// loop {
+///   VBlankIntrWait();
+///   if fade.step() {
+///     break;
+///   }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct FadeToColor<'a> {
+  snapshot: &'a PaletteSnapshot,
+  range: Range<usize>,
+  target: Color,
+  frame: u16,
+  total_frames: u16,
+}
+impl<'a> FadeToColor<'a> {
+  /// Takes a snapshot of `range` and makes a fade of it to `target` over
+  /// `total_frames` steps.
+  ///
+  /// Nothing is written until the first step. With 0 frames the first step
+  /// goes straight to the target color.
+  ///
+  /// ## Panics
+  /// * The range must be within `0..512`.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn new(
+    snapshot: &'a mut PaletteSnapshot, range: Range<usize>, target: Color,
+    total_frames: u16,
+  ) -> Self {
+    snapshot.capture(range.clone());
+    Self { snapshot, range, target, frame: 0, total_frames }
+  }
+
+  /// Advances the fade one frame towards the target color, and writes it.
+  ///
+  /// Gives `true` once the fade is done (every step after that just writes
+  /// the target color again).
+  #[inline]
+  pub fn step(&mut self) -> bool {
+    self.frame = (self.frame + 1).min(self.total_frames);
+    self.write();
+    self.is_done()
+  }
+
+  /// Moves the fade one frame back towards the snapshot, and writes it.
+  ///
+  /// Gives `true` once the fade is all the way back at the snapshot colors.
+  #[inline]
+  pub fn step_back(&mut self) -> bool {
+    self.frame = self.frame.saturating_sub(1);
+    self.write();
+    self.frame == 0
+  }
+
+  /// Stops the fade, writing the snapshot back to PALRAM.
+  #[inline]
+  pub fn cancel(self) {
+    self.snapshot.restore(self.range);
+  }
+
+  /// How many steps the fade is along.
+  #[inline]
+  #[must_use]
+  pub const fn frame(&self) -> u16 {
+    self.frame
+  }
+
+  /// How many steps the whole fade takes.
+  #[inline]
+  #[must_use]
+  pub const fn total_frames(&self) -> u16 {
+    self.total_frames
+  }
+
+  /// If the fade has reached the target color.
+  #[inline]
+  #[must_use]
+  pub const fn is_done(&self) -> bool {
+    self.frame == self.total_frames
+  }
+
+  /// How far along the fade is, as a [`Color::blend`] weight (`0..=16`).
+  #[inline]
+  #[must_use]
+  pub const fn weight(&self) -> u16 {
+    if self.total_frames == 0 {
+      16
+    } else {
+      let total = self.total_frames as u32;
+      ((self.frame as u32 * 16 + total / 2) / total) as u16
+    }
+  }
+
+  fn write(&self) {
+    let w = u32::from(self.weight());
+    let target = spread(self.target) * w + SPREAD_HALF;
+    let start = self.range.start;
+    let colors = self.snapshot.0[self.range.clone()].iter();
+    for (color, addr) in colors.zip(PALRAM.iter().skip(start)) {
+      addr.write(unspread(spread(*color) * (16 - w) + target));
+    }
+  }
+}
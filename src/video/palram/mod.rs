@@ -0,0 +1,292 @@
+//! Management of the palette banks in PALRAM.
+//!
+//! The [`BG_PALETTE`] and [`OBJ_PALETTE`] each have 256 colors. When drawing
+//! with 4bpp graphics, each palette is instead used as sixteen banks of 16
+//! colors each, and every tile (or object) picks one bank. A [`PalBank`] is a
+//! handle to one of these banks, and a [`PalBankAllocator`] keeps track of
+//! which banks are in use so that palettes can be loaded as they're needed.
+//!
+//! ## Index 0
+//!
+//! Index 0 of every bank is the "transparent" color, so it's never actually
+//! drawn by any tile or object. The one exception is that index 0 of the
+//! *background* palette is also the backdrop color, which is shown wherever
+//! nothing else is drawn. Because of this the functions here that write a
+//! whole bank (or a whole palette) never write to index 0. Use
+//! [`set_backdrop`] to set the backdrop color.
+//!
+//! For fading whole palettes (or parts of them) over several frames, see the
+//! [`fade`] module. For changing colors partway down the screen, see the
+//! [`raster_palette`] module.
+
+use crate::prelude::*;
+use core::ops::Range;
+
+pub mod fade;
+#[cfg(feature = "on_gba")]
+pub mod raster_palette;
+
+/// Either the background palette or the object palette.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Palette {
+  #[default]
+  Bg,
+  Obj,
+}
+impl Palette {
+  /// All 256 entries of this palette.
+  #[inline]
+  #[must_use]
+  pub const fn entries(self) -> VolBlock<Color, Safe, Safe, 256> {
+    match self {
+      Self::Bg => BG_PALETTE,
+      Self::Obj => OBJ_PALETTE,
+    }
+  }
+
+  /// Where this palette is in all 512 entries of PALRAM, for the [`fade`]
+  /// functions.
+  #[inline]
+  #[must_use]
+  pub const fn range(self) -> Range<usize> {
+    match self {
+      Self::Bg => 0..256,
+      Self::Obj => 256..512,
+    }
+  }
+}
+
+/// One bank of 16 colors within a palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PalBank {
+  palette: Palette,
+  index: u8,
+}
+impl PalBank {
+  /// A handle to a bank.
+  ///
+  /// ## Panics
+  /// * The index must be in `0..16`.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(palette: Palette, index: u8) -> Self {
+    assert!(index < 16, "palbank index out of range");
+    Self { palette, index }
+  }
+
+  /// The palette that this bank is part of.
+  #[inline]
+  #[must_use]
+  pub const fn palette(self) -> Palette {
+    self.palette
+  }
+
+  /// The bank's index, which is the `palbank` value for tiles and objects.
+  #[inline]
+  #[must_use]
+  pub const fn index(self) -> u16 {
+    self.index as u16
+  }
+
+  /// The 16 colors of this bank.
+  #[inline]
+  #[must_use]
+  pub const fn colors(self) -> VolBlock<Color, Safe, Safe, 16> {
+    match self.palette {
+      Palette::Bg => bg_palbank(self.index as usize),
+      Palette::Obj => obj_palbank(self.index as usize),
+    }
+  }
+
+  /// Where this bank is in all 512 entries of PALRAM, for the [`fade`]
+  /// functions.
+  #[inline]
+  #[must_use]
+  pub const fn range(self) -> Range<usize> {
+    let start = self.palette.range().start + self.index as usize * 16;
+    start..start + 16
+  }
+}
+
+/// Sets the backdrop color, which is index 0 of the background palette.
+#[inline]
+pub fn set_backdrop(color: Color) {
+  BACKDROP_COLOR.write(color);
+}
+
+/// Writes the colors of a bank.
+///
+/// Index 0 is skipped (see the [module docs](self)).
+#[inline]
+pub fn write_bank(bank: PalBank, colors: &[Color; 16]) {
+  for (addr, color) in bank.colors().iter().zip(colors.iter()).skip(1) {
+    addr.write(*color);
+  }
+}
+
+/// Writes the colors of a bank, each blended `weight / 16` of the way towards
+/// `target`.
+///
+/// This is a palette fade: call it once per frame with the same `colors` and
+/// a growing `weight` to fade that bank to `target` (see [`Color::blend`]).
+/// Unlike [`fade_to_black`] it only affects the one bank, and can fade to any
+/// color. Index 0 is skipped (see the [module docs](self)).
+#[inline]
+pub fn write_bank_blended(
+  bank: PalBank, colors: &[Color; 16], target: Color, weight: u16,
+) {
+  for (addr, color) in bank.colors().iter().zip(colors.iter()).skip(1) {
+    addr.write(color.blend(target, weight));
+  }
+}
+
+/// Writes an entire palette, for use with 8bpp graphics.
+///
+/// Index 0 is skipped (see the [module docs](self)).
+#[inline]
+pub fn write_full_palette(palette: Palette, colors: &[Color; 256]) {
+  for (addr, color) in palette.entries().iter().zip(colors.iter()).skip(1) {
+    addr.write(*color);
+  }
+}
+
+/// Writes colors for 8bpp graphics, starting at the first entry of a bank.
+///
+/// An 8bpp image usually only uses part of a palette. With its colors starting
+/// at index `first.index() * 16` of the palette, and its tiles moved to use
+/// those indexes (see [`tile8_offset_indexes`]), other graphics can use the
+/// rest of the palette.
+/// The colors can fill any number of banks, and only the last one can be
+/// partly filled.
+///
+/// Unlike [`write_bank`], index 0 of each bank is written, because 8bpp
+/// pixels can use it: with 8bpp graphics only index 0 of the whole palette is
+/// transparent. That one entry is still skipped (see the
+/// [module docs](self)).
+///
+/// ## Panics
+/// * The colors must fit within the palette.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn write_banks_8bpp(first: PalBank, colors: &[Color]) {
+  let start = usize::from(first.index) * 16;
+  assert!(start + colors.len() <= 256, "colors past the end of the palette");
+  let entries = first.palette.entries().iter().skip(start);
+  for (i, (addr, color)) in entries.zip(colors.iter()).enumerate() {
+    if start + i != 0 {
+      addr.write(*color);
+    }
+  }
+}
+
+/// Keeps track of which banks of one palette are in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PalBankAllocator {
+  palette: Palette,
+  used: u16,
+}
+impl PalBankAllocator {
+  /// Makes an allocator for the palette given, with all banks free.
+  #[inline]
+  #[must_use]
+  pub const fn new(palette: Palette) -> Self {
+    Self { palette, used: 0 }
+  }
+
+  /// Allocates the lowest free bank, if any.
+  #[inline]
+  pub fn alloc_bank(&mut self) -> Option<PalBank> {
+    let free = !self.used;
+    if free == 0 {
+      return None;
+    }
+    let index = free.trailing_zeros() as u8;
+    self.used |= 1 << index;
+    Some(PalBank::new(self.palette, index))
+  }
+
+  /// Allocates `count` banks in a row, for the colors of 8bpp graphics.
+  ///
+  /// Gives the first bank, which is the lowest one that starts a long enough
+  /// run of free banks, or `None` if there's no such run (or if `count` is 0
+  /// or more than 16). The banks are freed with
+  /// [`free_banks`](Self::free_banks).
+  ///
+  /// With `count` of 16 this claims the whole palette, which only works when
+  /// no banks at all are in use.
+  #[inline]
+  pub fn alloc_banks(&mut self, count: usize) -> Option<PalBank> {
+    if count == 0 || count > 16 {
+      return None;
+    }
+    let run = (u32::MAX >> (32 - count)) as u16;
+    let first = (0..=16 - count).find(|i| self.used & (run << i) == 0)?;
+    self.used |= run << first;
+    Some(PalBank::new(self.palette, first as u8))
+  }
+
+  /// Frees `count` banks in a row, starting at `first`.
+  ///
+  /// Banks past the end of the palette are ignored.
+  ///
+  /// ## Panics
+  /// * The bank must be from the same palette as this allocator.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn free_banks(&mut self, first: PalBank, count: usize) {
+    assert_eq!(first.palette, self.palette, "bank is from the wrong palette");
+    let run = if count >= 16 { u32::MAX } else { (1 << count) - 1 };
+    self.used &= !((run << first.index) as u16);
+  }
+
+  /// Marks a bank as free again.
+  ///
+  /// ## Panics
+  /// * The bank must be from the same palette as this allocator.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn free_bank(&mut self, bank: PalBank) {
+    assert_eq!(bank.palette, self.palette, "bank is from the wrong palette");
+    self.used &= !(1 << bank.index);
+  }
+
+  /// If a bank is currently allocated.
+  #[inline]
+  #[must_use]
+  pub const fn is_used(&self, bank: PalBank) -> bool {
+    (self.used & (1 << bank.index)) != 0
+  }
+
+  /// Frees all banks.
+  #[inline]
+  pub fn reset(&mut self) {
+    self.used = 0;
+  }
+
+  /// Gets a bank holding the colors given, reusing an allocated bank if one
+  /// already holds exactly these colors.
+  ///
+  /// Otherwise a new bank is allocated and the colors are written to it. Gives
+  /// `None` if a new bank is needed but none are free. As with
+  /// [`write_bank`], index 0 is not written, and it's not compared either.
+  #[inline]
+  pub fn find_or_alloc(&mut self, colors: &[Color; 16]) -> Option<PalBank> {
+    let existing =
+      (0..16).map(|i| PalBank::new(self.palette, i)).find(|bank| {
+        self.is_used(*bank)
+          && bank
+            .colors()
+            .iter()
+            .zip(colors.iter())
+            .skip(1)
+            .all(|(a, c)| a.read() == *c)
+      });
+    if existing.is_some() {
+      return existing;
+    }
+    let bank = self.alloc_bank()?;
+    write_bank(bank, colors);
+    Some(bank)
+  }
+}
@@ -0,0 +1,361 @@
+//! Changing palette colors partway down the screen.
+//!
+//! The palette can be rewritten during the hblank between two scanlines, and
+//! the lines below then draw with the new colors. That's how a game gets more
+//! colors on screen than the palette holds (eg: one set of colors for the
+//! status bar and another for the playfield), or a gradient from a single
+//! palette entry. A [`RasterPalette`] does this from a table of
+//! [`RasterEntry`] values, each of which gives the first line to draw with
+//! some new colors, and which palette entries they go in:
+//!
+//! ```no_run
+//! # use gba::{prelude::*, video::palram::raster_palette::*};
+//! static STATUS_BAR: [Color; 4] =
+//!   [Color::BLACK, Color::WHITE, Color::GRAY, Color::RED];
+//! static PLAYFIELD: [Color; 4] =
+//!   [Color::BLUE, Color::GREEN, Color::YELLOW, Color::WHITE];
+//! static ENTRIES: [RasterEntry; 2] = [
+//!   RasterEntry::new(0, 0..4, &STATUS_BAR),
+//!   RasterEntry::new(24, 0..4, &PLAYFIELD),
+//! ];
+//! RUST_IRQ_HANDLER.write(Some(irq_table_dispatch));
+//! let raster = RasterPalette::start(3, &ENTRIES);
+//! IME.write(true);
+//! ```
+//!
+//! ## Timing
+//!
+//! The raster palette runs from the vblank and vcount entries of the
+//! [handler table](crate::interrupts::set_handler), so
+//! [`RUST_IRQ_HANDLER`](crate::RUST_IRQ_HANDLER) must be
+//! [`irq_table_dispatch`](crate::interrupts::irq_table_dispatch). Each frame
+//! starts over at vblank, which is done by [`restart_raster_palette`] (this is
+//! the same as with an [`HblankEffect`](crate::dma::HblankEffect)). To use the
+//! vblank interrupt for other things too, set your own vblank handler after
+//! starting the raster palette, and call [`restart_raster_palette`] from it.
+//!
+//! * Entries for line 0 are written during vblank, so they're how the colors at
+//!   the top of the screen are put back each frame. Without one, the top of
+//!   each frame keeps whatever colors the last entry of the frame before left.
+//! * Every other entry is written during the hblank just before its line. The
+//!   vcount interrupt is set for the line before, and from there the colors are
+//!   written either by DMA or by the CPU:
+//!   * If no line (other than line 0) has more than one entry, each line's
+//!     colors are a single range, and the handler sets up a DMA that runs at
+//!     that line's hblank. The DMA runs on its own at the start of the hblank,
+//!     and it's the faster of the two.
+//!   * Otherwise, the handler waits (inside the interrupt) for the hblank and
+//!     then writes all of that line's entries itself, as ARM code in IWRAM.
+//!
+//! The hblank is only 272 cycles long, and colors written after it ends show
+//! up partway across the next line. About [`MAX_DMA_LINE_COLORS`] colors fit
+//! by DMA, or [`MAX_CPU_LINE_COLORS`] by the CPU, and with debug assertions
+//! on [`start`](RasterPalette::start) checks each line against that. These are
+//! estimates for colors read from the ROM with the default wait states, so a
+//! line that's right at the limit should be checked on hardware.
+//!
+//! Another interrupt that runs long can make a change land a line late. The
+//! vcount interrupt is used for this, so it can't be used for anything else
+//! (such as [`set_scanline_irq`](crate::video::set_scanline_irq)) while the
+//! raster palette runs.
+
+use super::super::Color;
+use crate::{
+  dma::{
+    DestAddrControl, DmaControl, DmaRegisters, DmaStartTime, SrcAddrControl,
+  },
+  gba_cell::GbaCell,
+  interrupts::{clear_handler, irq_free, set_handler, Interrupt, IrqBits},
+  mmio::DISPSTAT,
+};
+use core::ops::Range;
+
+/// About how many colors a DMA can write during one hblank.
+///
+/// See the [module docs](self#timing).
+pub const MAX_DMA_LINE_COLORS: usize = 64;
+
+/// About how many colors the CPU can write during one hblank, when one line
+/// has more than one entry.
+///
+/// See the [module docs](self#timing).
+pub const MAX_CPU_LINE_COLORS: usize = 24;
+
+/// Some colors to write to part of PALRAM at the start of a scanline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RasterEntry {
+  line: u8,
+  start: u16,
+  colors: &'static [Color],
+}
+impl RasterEntry {
+  /// Puts `colors` in the PALRAM entries `range`, for line `line` and the
+  /// lines below it.
+  ///
+  /// The range counts all 512 entries of PALRAM: `0..256` is the background
+  /// palette and `256..512` is the object palette (see
+  /// [`Palette::range`](super::Palette::range)). Index 0 is the backdrop.
+  ///
+  /// ## Panics
+  /// * The line must be less than 160.
+  /// * The range can't be empty, and must end at 512 or less.
+  /// * There must be one color for each entry of the range.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(
+    line: u8, range: Range<usize>, colors: &'static [Color],
+  ) -> Self {
+    assert!(line < 160, "line out of range");
+    assert!(range.start < range.end && range.end <= 512, "range out of bounds");
+    assert!(
+      range.end - range.start == colors.len(),
+      "there must be one color for each entry of the range"
+    );
+    Self { line, start: range.start as u16, colors }
+  }
+
+  /// The first line drawn with the new colors.
+  #[inline]
+  #[must_use]
+  pub const fn line(&self) -> u8 {
+    self.line
+  }
+
+  /// The PALRAM entries that the colors go in.
+  #[inline]
+  #[must_use]
+  pub const fn range(&self) -> Range<usize> {
+    self.start as usize..self.start as usize + self.colors.len()
+  }
+
+  /// The colors.
+  #[inline]
+  #[must_use]
+  pub const fn colors(&self) -> &'static [Color] {
+    self.colors
+  }
+
+  /// The address of the first PALRAM entry.
+  #[inline]
+  #[must_use]
+  const fn dest(&self) -> usize {
+    0x0500_0000 + self.start as usize * 2
+  }
+}
+
+/// The table of the running raster palette, or 0 for none.
+static RASTER_ENTRIES: GbaCell<u32> = GbaCell::new(0);
+/// The length of the table.
+static RASTER_LEN: GbaCell<u16> = GbaCell::new(0);
+/// The index of the next entry to write.
+static RASTER_NEXT: GbaCell<u16> = GbaCell::new(0);
+/// The DMA unit the raster palette uses.
+static RASTER_CHANNEL: GbaCell<u8> = GbaCell::new(0);
+/// If the raster palette writes by DMA, rather than with the CPU.
+static RASTER_DMA: GbaCell<bool> = GbaCell::new(false);
+
+/// A table of palette changes, applied each frame as the screen is drawn.
+///
+/// See the [module docs](self) for how it works. There's one raster palette
+/// at a time, and starting another replaces it.
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct RasterPalette {
+  channel: usize,
+  dma: bool,
+}
+impl RasterPalette {
+  /// Starts writing `entries` each frame, beginning with the next vblank.
+  ///
+  /// The entries must be sorted by line, and entries for the same line are
+  /// written in the order they're in. If they're written by DMA (see the
+  /// [module docs](self#timing)), it's with the DMA unit given. The unit is
+  /// set aside either way, and shouldn't be used for anything else while the
+  /// raster palette runs, and DMA 0 can't read from ROM, so use DMA 1, 2, or
+  /// 3 if the colors are in ROM.
+  ///
+  /// This sets the vblank and vcount handlers (see the
+  /// [module docs](self#timing)), and turns on those interrupts in
+  /// [`DISPSTAT`] and [`IE`](crate::mmio::IE), but you still need to enable
+  /// [`IME`](crate::mmio::IME).
+  ///
+  /// ## Panics
+  /// * The channel must be in `0..4`.
+  /// * The entries must be sorted by line.
+  /// * With debug assertions on, each line's colors must fit in its hblank (see
+  ///   [`MAX_DMA_LINE_COLORS`] and [`MAX_CPU_LINE_COLORS`]). Line 0 is written
+  ///   during vblank, so it can have any number of colors.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn start(channel: usize, entries: &'static [RasterEntry]) -> Self {
+    assert!(channel < 4, "DMA channel out of range");
+    assert!(
+      entries.windows(2).all(|w| w[0].line <= w[1].line),
+      "the entries must be sorted by line"
+    );
+    assert!(entries.len() <= usize::from(u16::MAX), "too many entries");
+    let dma =
+      !entries.windows(2).any(|w| w[0].line != 0 && w[0].line == w[1].line);
+    if cfg!(debug_assertions) {
+      let (limit, how) = if dma {
+        (MAX_DMA_LINE_COLORS, "by DMA")
+      } else {
+        (MAX_CPU_LINE_COLORS, "by the CPU")
+      };
+      let mut i = 0;
+      while i < entries.len() {
+        let line = entries[i].line;
+        let end =
+          i + entries[i..].iter().take_while(|e| e.line == line).count();
+        let colors: usize =
+          entries[i..end].iter().map(|e| e.colors.len()).sum();
+        assert!(
+          line == 0 || colors <= limit,
+          "line {line} changes {colors} colors, more than fit in an hblank {how}"
+        );
+        i = end;
+      }
+    }
+    irq_free(|| {
+      RASTER_ENTRIES.write(entries.as_ptr() as u32);
+      RASTER_LEN.write(entries.len() as u16);
+      RASTER_NEXT.write(entries.len() as u16);
+      RASTER_CHANNEL.write(channel as u8);
+      RASTER_DMA.write(dma);
+      DISPSTAT.apply(|s| *s = s.with_irq_vblank(true).with_irq_vcount(false));
+      set_handler(Interrupt::VBlank, restart_raster_palette);
+      set_handler(Interrupt::VCounter, write_raster_line);
+    });
+    Self { channel, dma }
+  }
+
+  /// The DMA unit set aside for this raster palette.
+  #[inline]
+  #[must_use]
+  pub const fn channel(&self) -> usize {
+    self.channel
+  }
+
+  /// If the colors are written by DMA, rather than by the CPU.
+  #[inline]
+  #[must_use]
+  pub const fn uses_dma(&self) -> bool {
+    self.dma
+  }
+
+  /// Stops the raster palette.
+  ///
+  /// This turns off the vcount interrupt and removes its handler, and leaves
+  /// the vblank interrupt enabled, since other code might be using it (the
+  /// vblank handler does nothing once the raster palette is stopped). The
+  /// palette keeps whatever colors were last written.
+  #[inline]
+  pub fn stop(self) {
+    irq_free(|| {
+      RASTER_ENTRIES.write(0);
+      DISPSTAT.apply(|s| *s = s.with_irq_vcount(false));
+      clear_handler(Interrupt::VCounter);
+      let regs = DmaRegisters::of(self.channel);
+      unsafe { regs.control.write(DmaControl::new()) };
+    });
+  }
+}
+
+/// The entries of the running raster palette, if there is one.
+#[inline]
+fn running_entries() -> Option<&'static [RasterEntry]> {
+  let ptr = RASTER_ENTRIES.read() as *const RasterEntry;
+  if ptr.is_null() {
+    return None;
+  }
+  let len = usize::from(RASTER_LEN.read());
+  // Safety: these are from the `&'static` slice given to `start`.
+  Some(unsafe { core::slice::from_raw_parts(ptr, len) })
+}
+
+/// Sets up the vcount interrupt for the entry at `next`, if there is one.
+#[inline]
+fn schedule(entries: &[RasterEntry], next: usize) {
+  RASTER_NEXT.write(next as u16);
+  DISPSTAT.apply(|s| {
+    *s = match entries.get(next) {
+      Some(entry) => {
+        s.with_vcount_setting(u16::from(entry.line - 1)).with_irq_vcount(true)
+      }
+      None => s.with_irq_vcount(false),
+    }
+  });
+}
+
+/// The vblank handler that starts the [`RasterPalette`] over for a new frame.
+///
+/// [`RasterPalette::start`] sets this as the vblank handler, so it only needs
+/// calling directly from a vblank handler of your own. It does nothing unless
+/// `bits` has the `vblank` bit set and a raster palette is running.
+#[inline]
+pub extern "C" fn restart_raster_palette(bits: IrqBits) {
+  if !bits.vblank() {
+    return;
+  }
+  let Some(entries) = running_entries() else { return };
+  let top = entries.iter().take_while(|e| e.line == 0).count();
+  write_colors(&entries[..top]);
+  schedule(entries, top);
+}
+
+/// The vcount handler, which writes the entries for the next line.
+extern "C" fn write_raster_line(_: IrqBits) {
+  let Some(entries) = running_entries() else { return };
+  let next = usize::from(RASTER_NEXT.read());
+  let Some(first) = entries.get(next) else { return };
+  let end =
+    next + entries[next..].iter().take_while(|e| e.line == first.line).count();
+  if RASTER_DMA.read() {
+    let regs = DmaRegisters::of(usize::from(RASTER_CHANNEL.read()));
+    unsafe {
+      regs.control.write(DmaControl::new());
+      regs.src.write(first.colors.as_ptr().cast());
+      regs.dest.write(first.dest() as *mut _);
+      regs.count.write(first.colors.len() as u16);
+      regs.control.write(
+        DmaControl::new()
+          .with_dest_addr_control(DestAddrControl::Increment)
+          .with_src_addr_control(SrcAddrControl::Increment)
+          .with_start_time(DmaStartTime::HBlank)
+          .with_enabled(true),
+      );
+    }
+  } else {
+    wait_for_hblank();
+    write_colors(&entries[next..end]);
+  }
+  schedule(entries, end);
+}
+
+crate::iwram_code! {
+  /// Waits until the display is in hblank.
+  fn wait_for_hblank() {
+    let dispstat = 0x0400_0004 as *const u16;
+    // Safety: this is the `DISPSTAT` register, and bit 1 is the hblank flag.
+    while unsafe { dispstat.read_volatile() } & (1 << 1) == 0 {}
+  }
+}
+
+crate::iwram_code! {
+  /// Writes the colors of each entry, in order.
+  fn write_colors(entries: &[RasterEntry]) {
+    let mut i = 0;
+    while i < entries.len() {
+      let colors = entries[i].colors;
+      let dest = (0x0500_0000 + entries[i].start as usize * 2) as *mut u16;
+      let mut j = 0;
+      while j < colors.len() {
+        // Safety: `new` checked that the whole range is in PALRAM.
+        unsafe { dest.add(j).write_volatile(colors[j].0) };
+        j += 1;
+      }
+      i += 1;
+    }
+  }
+}
@@ -0,0 +1,205 @@
+//! Shape rasterization shared by the bitmap video modes.
+//!
+//! Everything here is pure integer math. Each shape is broken down into either
+//! single pixels or horizontal spans, which have already been clipped to the
+//! bitmap size given, and the caller decides how to actually write them.
+
+use crate::math::Rect;
+
+/// Clips the inclusive range `a..=b` (in either order) to `0..size`.
+#[inline]
+#[must_use]
+pub(crate) fn clip_span(a: i32, b: i32, size: i32) -> Option<(usize, usize)> {
+  let (lo, hi) = if a <= b { (a, b) } else { (b, a) };
+  if hi < 0 || lo >= size {
+    None
+  } else {
+    Some((lo.max(0) as usize, hi.min(size - 1) as usize))
+  }
+}
+
+/// Converts a point to `usize` coordinates if it's within the bitmap.
+#[inline]
+#[must_use]
+pub(crate) fn clip_point(
+  x: i32, y: i32, width: i32, height: i32,
+) -> Option<(usize, usize)> {
+  if (0..width).contains(&x) && (0..height).contains(&y) {
+    Some((x as usize, y as usize))
+  } else {
+    None
+  }
+}
+
+/// Walks a Bresenham line from `(x0, y0)` to `(x1, y1)`, inclusive.
+///
+/// Points outside of the bitmap are skipped, and the walk ends early once the
+/// line has left the bitmap for good.
+#[inline]
+pub(crate) fn line(
+  mut x0: i32, mut y0: i32, x1: i32, y1: i32, width: i32, height: i32,
+  mut plot: impl FnMut(usize, usize),
+) {
+  let dx = (x1 - x0).abs();
+  let dy = -(y1 - y0).abs();
+  let sx = if x0 < x1 { 1 } else { -1 };
+  let sy = if y0 < y1 { 1 } else { -1 };
+  let mut err = dx + dy;
+  let mut was_inside = false;
+  loop {
+    match clip_point(x0, y0, width, height) {
+      Some((x, y)) => {
+        plot(x, y);
+        was_inside = true;
+      }
+      // A straight line can't come back once it leaves.
+      None if was_inside => return,
+      None => (),
+    }
+    if x0 == x1 && y0 == y1 {
+      return;
+    }
+    let e2 = 2 * err;
+    if e2 >= dy {
+      err += dy;
+      x0 += sx;
+    }
+    if e2 <= dx {
+      err += dx;
+      y0 += sy;
+    }
+  }
+}
+
+/// Gives each row span of a filled rectangle as `(y, x_start, x_end)`.
+///
+/// A rectangle with a non-positive width or height has no spans.
+#[inline]
+pub(crate) fn rect_filled(
+  x: i32, y: i32, w: i32, h: i32, width: i32, height: i32,
+  mut span: impl FnMut(usize, usize, usize),
+) {
+  let bitmap = Rect::new(0, 0, width, height);
+  if let Some(visible) = Rect::new(x, y, w, h).intersection(bitmap) {
+    let x_end = (visible.right() - 1) as usize;
+    for row in visible.y..visible.bottom() {
+      span(row as usize, visible.x as usize, x_end);
+    }
+  }
+}
+
+/// Walks the outline of a rectangle as `(y, x_start, x_end)` spans.
+#[inline]
+pub(crate) fn rect(
+  x: i32, y: i32, w: i32, h: i32, width: i32, height: i32,
+  mut span: impl FnMut(usize, usize, usize),
+) {
+  if w <= 0 || h <= 0 {
+    return;
+  }
+  let x_end = x.saturating_add(w - 1);
+  let y_end = y.saturating_add(h - 1);
+  let mut row_span = |row: i32| {
+    if let (true, Some((x0, x1))) =
+      ((0..height).contains(&row), clip_span(x, x_end, width))
+    {
+      span(row as usize, x0, x1);
+    }
+  };
+  row_span(y);
+  if h > 1 {
+    row_span(y_end);
+  }
+  if h > 2 {
+    if let Some((y0, y1)) = clip_span(y + 1, y_end - 1, height) {
+      for row in y0..=y1 {
+        if (0..width).contains(&x) {
+          span(row, x as usize, x as usize);
+        }
+        if w > 1 && (0..width).contains(&x_end) {
+          span(row, x_end as usize, x_end as usize);
+        }
+      }
+    }
+  }
+}
+
+/// Walks the outline of a circle using the midpoint algorithm.
+#[inline]
+pub(crate) fn circle(
+  cx: i32, cy: i32, r: i32, width: i32, height: i32,
+  mut plot: impl FnMut(usize, usize),
+) {
+  if r < 0 {
+    return;
+  }
+  let mut plot_i = |x: i32, y: i32| {
+    if let Some((x, y)) = clip_point(x, y, width, height) {
+      plot(x, y);
+    }
+  };
+  let mut x = r;
+  let mut y = 0;
+  let mut err = 1 - r;
+  while x >= y {
+    plot_i(cx + x, cy + y);
+    plot_i(cx + y, cy + x);
+    plot_i(cx - y, cy + x);
+    plot_i(cx - x, cy + y);
+    plot_i(cx - x, cy - y);
+    plot_i(cx - y, cy - x);
+    plot_i(cx + y, cy - x);
+    plot_i(cx + x, cy - y);
+    y += 1;
+    if err < 0 {
+      err += 2 * y + 1;
+    } else {
+      x -= 1;
+      err += 2 * (y - x) + 1;
+    }
+  }
+}
+
+/// Gives each row span of a filled circle as `(y, x_start, x_end)`.
+///
+/// Each row is given exactly once.
+#[inline]
+pub(crate) fn circle_filled(
+  cx: i32, cy: i32, r: i32, width: i32, height: i32,
+  mut span: impl FnMut(usize, usize, usize),
+) {
+  if r < 0 {
+    return;
+  }
+  for dy in -r..=r {
+    let row = cy + dy;
+    if !(0..height).contains(&row) {
+      continue;
+    }
+    // widest dx with dx*dx + dy*dy <= r*r + r, which matches the outline.
+    let limit = r * r + r - dy * dy;
+    let mut dx = isqrt(limit);
+    while dx * dx > limit {
+      dx -= 1;
+    }
+    if let Some((x0, x1)) = clip_span(cx - dx, cx + dx, width) {
+      span(row as usize, x0, x1);
+    }
+  }
+}
+
+/// Integer square root (rounded down) of a non-negative value.
+#[inline]
+#[must_use]
+const fn isqrt(n: i32) -> i32 {
+  if n <= 0 {
+    return 0;
+  }
+  let mut x = n;
+  let mut y = (x + 1) / 2;
+  while y < x {
+    x = y;
+    y = (x + n / x) / 2;
+  }
+  x
+}
@@ -0,0 +1,213 @@
+//! Runtime allocation of object slots in an [`OamShadow`].
+//!
+//! A [`SpriteAllocator`] owns an OAM shadow and hands out [`SpriteHandle`]s to
+//! the object entries within it, so that different parts of a game can each
+//! make and remove objects without needing to coordinate which OAM index each
+//! one uses.
+//!
+//! Each handle holds a generation count for its slot, and the slot's count is
+//! increased whenever the slot is freed. That way a handle that's kept after
+//! being freed won't affect whatever object gets that slot next, it will just
+//! stop working.
+
+use super::*;
+
+/// A handle to one object entry of a [`SpriteAllocator`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SpriteHandle {
+  index: u8,
+  generation: u16,
+}
+impl SpriteHandle {
+  /// The OAM index of the object this handle is for.
+  #[inline]
+  #[must_use]
+  pub const fn index(self) -> usize {
+    self.index as usize
+  }
+}
+
+/// Tracks which of the 128 object entries of an [`OamShadow`] are in use.
+///
+/// Free entries are always kept [hidden](ObjAttr::HIDDEN), so you can
+/// [`commit`](OamShadow::commit) the shadow at any time without stray objects
+/// showing up.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpriteAllocator {
+  oam: OamShadow,
+  used: u128,
+  generations: [u16; 128],
+}
+impl SpriteAllocator {
+  /// Makes an allocator with every object free (and hidden).
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self { oam: OamShadow::new(), used: 0, generations: [0; 128] }
+  }
+
+  /// Allocates one object entry.
+  ///
+  /// The lowest free index is used, and the entry starts out hidden. Gives
+  /// `None` if all 128 entries are in use.
+  #[inline]
+  pub fn alloc(&mut self) -> Option<SpriteHandle> {
+    let index = (!self.used).trailing_zeros() as usize;
+    if index >= 128 {
+      return None;
+    }
+    self.used |= 1 << index;
+    Some(self.handle(index))
+  }
+
+  /// Allocates `N` object entries with consecutive indexes.
+  ///
+  /// This is for objects that are drawn as a group and need a particular
+  /// order (an object with a lower index draws on top). Gives `None` if there
+  /// isn't a free run that's long enough (or if `N` is 0).
+  #[inline]
+  pub fn alloc_run<const N: usize>(&mut self) -> Option<[SpriteHandle; N]> {
+    if N == 0 || N > 128 {
+      return None;
+    }
+    let mask = u128::MAX >> (128 - N);
+    let start = (0..=(128 - N)).find(|&i| self.used & (mask << i) == 0)?;
+    self.used |= mask << start;
+    Some(core::array::from_fn(|i| self.handle(start + i)))
+  }
+
+  #[inline]
+  #[must_use]
+  fn handle(&self, index: usize) -> SpriteHandle {
+    SpriteHandle { index: index as u8, generation: self.generations[index] }
+  }
+
+  /// If the handle is for an entry that's currently allocated.
+  ///
+  /// This is `false` once the handle has been freed, even if the entry has
+  /// since been given out again.
+  #[inline]
+  #[must_use]
+  pub const fn is_valid(&self, handle: SpriteHandle) -> bool {
+    let index = handle.index as usize;
+    (self.used & (1 << index)) != 0
+      && self.generations[index] == handle.generation
+  }
+
+  /// Frees an entry, and hides its object.
+  ///
+  /// Returns `false` (doing nothing) if the handle wasn't valid.
+  #[inline]
+  pub fn free(&mut self, handle: SpriteHandle) -> bool {
+    if !self.is_valid(handle) {
+      return false;
+    }
+    let index = handle.index();
+    self.used &= !(1 << index);
+    self.generations[index] = self.generations[index].wrapping_add(1);
+    *self.oam.obj_mut(index) = ObjAttr::HIDDEN;
+    true
+  }
+
+  /// Gets the attributes of an allocated object.
+  ///
+  /// Gives `None` if the handle isn't valid.
+  #[inline]
+  #[must_use]
+  pub fn get(&self, handle: SpriteHandle) -> Option<&ObjAttr> {
+    self.is_valid(handle).then(|| self.oam.obj(handle.index()))
+  }
+
+  /// Gets the attributes of an allocated object mutably.
+  ///
+  /// Gives `None` if the handle isn't valid.
+  #[inline]
+  #[must_use]
+  pub fn get_mut(&mut self, handle: SpriteHandle) -> Option<&mut ObjAttr> {
+    if self.is_valid(handle) {
+      Some(self.oam.obj_mut(handle.index()))
+    } else {
+      None
+    }
+  }
+
+  /// Makes an allocated object semi-transparent, with
+  /// [`OamShadow::set_translucent`].
+  ///
+  /// Returns `false` (doing nothing) if the handle wasn't valid.
+  #[inline]
+  pub fn set_translucent(
+    &mut self, handle: SpriteHandle, alpha: BlendAlpha,
+  ) -> bool {
+    if !self.is_valid(handle) {
+      return false;
+    }
+    self.oam.set_translucent(handle.index(), alpha);
+    true
+  }
+
+  /// Makes an allocated object part of the object window mask, with
+  /// [`OamShadow::set_obj_window`].
+  ///
+  /// Returns `false` (doing nothing) if the handle wasn't valid.
+  #[inline]
+  pub fn set_obj_window(&mut self, handle: SpriteHandle) -> bool {
+    if !self.is_valid(handle) {
+      return false;
+    }
+    self.oam.set_obj_window(handle.index());
+    true
+  }
+
+  /// The number of entries currently allocated.
+  #[inline]
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.used.count_ones() as usize
+  }
+
+  /// If no entries are allocated.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.used == 0
+  }
+
+  /// Frees all entries, hiding every object.
+  ///
+  /// All handles given out so far become invalid.
+  #[inline]
+  pub fn reset(&mut self) {
+    for index in 0..128 {
+      if self.used & (1 << index) != 0 {
+        self.generations[index] = self.generations[index].wrapping_add(1);
+      }
+    }
+    self.used = 0;
+    self.oam.hide_all();
+  }
+
+  /// The OAM shadow being managed.
+  #[inline]
+  #[must_use]
+  pub const fn oam(&self) -> &OamShadow {
+    &self.oam
+  }
+
+  /// The OAM shadow being managed, mutably.
+  ///
+  /// This is mostly for setting affine parameters. Changing an object entry
+  /// directly with this bypasses the allocator's tracking, so take care to
+  /// leave free entries hidden.
+  #[inline]
+  #[must_use]
+  pub fn oam_mut(&mut self) -> &mut OamShadow {
+    &mut self.oam
+  }
+}
+impl Default for SpriteAllocator {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -0,0 +1,329 @@
+//! Loading the frames of a multi-tile object into object VRAM.
+//!
+//! Where each tile of an object has to go depends on the object's size, if
+//! it's 8bpp, and the [`ObjTileMapping`] set in [`DISPCNT`]. Getting any of
+//! those wrong shows the object as stripes of the wrong tiles. A
+//! [`SpriteSheet`] loads one or more animation frames of an object, all the
+//! same size, and then gives the tile index of each frame for attr2:
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # let walk_tiles: &[Tile4] = &[];
+//! let mut slots =
+//!   TileAllocator::new_obj().with_obj_mapping(ObjTileMapping::current());
+//! let walk =
+//!   SpriteSheet::alloc_4bpp(&mut slots, ObjSize::_32x32, walk_tiles).unwrap();
+//! let mut obj = ObjAttr::new().with_size(ObjSize::_32x32);
+//! obj.2 = walk.attr2(0).with_palbank(2);
+//! ```
+//!
+//! ## Frame layout
+//!
+//! The tiles of each frame are given in rows, left to right and then top to
+//! bottom, one frame after the other.
+//! * With 1D mapping the frames are one after the other in VRAM, each
+//!   [`tile_slots`](ObjSize::tile_slots) long.
+//! * With 2D mapping the frames are side by side across the 32 slot wide grid,
+//!   as many as fit, and then the next ones go below those.
+//!
+//! ## With an animation player
+//!
+//! An [`Animation`] can use frame numbers in place of tile indexes, and the
+//! [`tick`](AnimationPlayer::tick) output then goes through
+//! [`tile_id`](SpriteSheet::tile_id). Or, with the frames known ahead of time,
+//! [`tile_ids`](SpriteSheet::tile_ids) gives every frame's tile index to build
+//! the animation's frame list from.
+
+use super::*;
+
+/// The frames of an object, loaded into object VRAM.
+///
+/// See the [module docs](self) for an overview.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpriteSheet {
+  first_slot: u16,
+  size: ObjSize,
+  bpp8: bool,
+  mapping: ObjTileMapping,
+  frames: u16,
+  /// How many frames are side by side in the grid, with 2D mapping.
+  across: u16,
+}
+impl SpriteSheet {
+  /// Copies 4bpp frames into object VRAM, starting at `first_slot`, laid out
+  /// for `mapping`.
+  ///
+  /// With 2D mapping the frames go across the grid from the column that
+  /// `first_slot` is in, to the end of the row. The slots in between the
+  /// frames' tiles are left alone.
+  ///
+  /// ## Panics
+  /// * `tiles` must be a whole number of frames (at least one) of
+  ///   [`tile_count`](ObjSize::tile_count) tiles each.
+  /// * With 2D mapping, at least one frame has to fit across the grid from
+  ///   `first_slot`.
+  /// * Every frame must fit within object VRAM, and in a bitmap video mode (3,
+  ///   4, or 5) within the upper half of it (slots `512..1024`), since the
+  ///   bitmap uses the lower half.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn upload_4bpp(
+    first_slot: TileIndex, size: ObjSize, mapping: ObjTileMapping,
+    tiles: &[Tile4],
+  ) -> Self {
+    let sheet = Self::layout(first_slot, size, false, mapping, tiles.len());
+    sheet.copy(tiles, copy_obj_4bpp);
+    sheet
+  }
+
+  /// Copies 8bpp frames into object VRAM, starting at `first_slot`, laid out
+  /// for `mapping`.
+  ///
+  /// This is like [`upload_4bpp`](Self::upload_4bpp), with two slots per tile.
+  ///
+  /// ## Panics
+  /// * The first slot must be even.
+  /// * Otherwise the same as [`upload_4bpp`](Self::upload_4bpp).
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn upload_8bpp(
+    first_slot: TileIndex, size: ObjSize, mapping: ObjTileMapping,
+    tiles: &[Tile8],
+  ) -> Self {
+    assert!(
+      first_slot.0.is_multiple_of(2),
+      "8bpp tiles must start on an even slot"
+    );
+    let sheet = Self::layout(first_slot, size, true, mapping, tiles.len());
+    sheet.copy(tiles, copy_obj_8bpp);
+    sheet
+  }
+
+  /// Allocates room for 4bpp frames from `slots`, and copies them there.
+  ///
+  /// The frames are laid out for the allocator's
+  /// [`obj_mapping`](TileAllocator::obj_mapping). With 2D mapping this takes
+  /// a whole rectangle of the grid, so when the last row of frames isn't full
+  /// the rest of that row is allocated too.
+  ///
+  /// Gives `None` (and copies nothing) if there isn't room.
+  ///
+  /// ## Panics
+  /// * Same as [`upload_4bpp`](Self::upload_4bpp).
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn alloc_4bpp(
+    slots: &mut TileAllocator, size: ObjSize, tiles: &[Tile4],
+  ) -> Option<Self> {
+    let first_slot = Self::alloc(slots, size, false, tiles.len())?;
+    Some(Self::upload_4bpp(first_slot, size, slots.obj_mapping(), tiles))
+  }
+
+  /// Allocates room for 8bpp frames from `slots`, and copies them there.
+  ///
+  /// This is like [`alloc_4bpp`](Self::alloc_4bpp), with two slots per tile.
+  ///
+  /// ## Panics
+  /// * Same as [`upload_8bpp`](Self::upload_8bpp).
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn alloc_8bpp(
+    slots: &mut TileAllocator, size: ObjSize, tiles: &[Tile8],
+  ) -> Option<Self> {
+    let first_slot = Self::alloc(slots, size, true, tiles.len())?;
+    Some(Self::upload_8bpp(first_slot, size, slots.obj_mapping(), tiles))
+  }
+
+  /// Gives the slots of a sheet from [`alloc_4bpp`](Self::alloc_4bpp) or
+  /// [`alloc_8bpp`](Self::alloc_8bpp) back to `slots`.
+  #[inline]
+  pub fn free(self, slots: &mut TileAllocator) {
+    match self.mapping {
+      ObjTileMapping::OneD => {
+        let frame_slots = self.size.tile_slots(self.bpp8);
+        slots.free(
+          TileIndex(self.first_slot),
+          usize::from(frame_slots * self.frames),
+        );
+      }
+      ObjTileMapping::TwoD => {
+        let width = self.frames.min(self.across) * self.row_slots();
+        let rows = self.frames.div_ceil(self.across) * self.tile_rows();
+        for row in 0..rows {
+          slots.free(TileIndex(self.first_slot + row * 32), usize::from(width));
+        }
+      }
+    }
+  }
+
+  /// The number of frames.
+  #[inline]
+  #[must_use]
+  pub const fn frame_count(&self) -> u16 {
+    self.frames
+  }
+
+  /// The size of the object.
+  #[inline]
+  #[must_use]
+  pub const fn size(&self) -> ObjSize {
+    self.size
+  }
+
+  /// If the frames are 8bpp.
+  #[inline]
+  #[must_use]
+  pub const fn bpp8(&self) -> bool {
+    self.bpp8
+  }
+
+  /// The mapping the frames are laid out for.
+  #[inline]
+  #[must_use]
+  pub const fn mapping(&self) -> ObjTileMapping {
+    self.mapping
+  }
+
+  /// The tile index to put in attr2 to show `frame`.
+  ///
+  /// ## Panics
+  /// * The frame must be less than [`frame_count`](Self::frame_count).
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn tile_id(&self, frame: u16) -> u16 {
+    assert!(frame < self.frames, "the frame is out of range");
+    // `layout` checked that every frame's slot is in object VRAM.
+    self.slot(frame) as u16
+  }
+
+  /// An [`ObjAttr2`] set to show `frame`, with the priority and palbank
+  /// left at 0.
+  ///
+  /// ## Panics
+  /// * Same as [`tile_id`](Self::tile_id).
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn attr2(&self, frame: u16) -> ObjAttr2 {
+    ObjAttr2::new().with_tile_id(self.tile_id(frame))
+  }
+
+  /// The tile index of every frame, in order.
+  #[inline]
+  pub fn tile_ids(&self) -> impl Iterator<Item = u16> + '_ {
+    (0..self.frames).map(|frame| self.tile_id(frame))
+  }
+
+  /// The first slot of `frame`.
+  #[inline]
+  #[must_use]
+  const fn slot(&self, frame: u16) -> usize {
+    let frame = frame as usize;
+    let first_slot = self.first_slot as usize;
+    match self.mapping {
+      ObjTileMapping::OneD => {
+        first_slot + frame * self.size.tile_slots(self.bpp8) as usize
+      }
+      ObjTileMapping::TwoD => {
+        let across = self.across as usize;
+        let x = frame % across * self.row_slots() as usize;
+        let y = frame / across * self.tile_rows() as usize;
+        first_slot + y * 32 + x
+      }
+    }
+  }
+
+  /// The number of slots across one row of a frame's tiles.
+  #[inline]
+  #[must_use]
+  const fn row_slots(&self) -> u16 {
+    let tiles = self.size.dimensions().0 / 8;
+    if self.bpp8 {
+      tiles * 2
+    } else {
+      tiles
+    }
+  }
+
+  /// The number of rows of tiles in a frame.
+  #[inline]
+  #[must_use]
+  const fn tile_rows(&self) -> u16 {
+    self.size.dimensions().1 / 8
+  }
+
+  /// Works out where the frames go, and checks that they fit.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  fn layout(
+    first_slot: TileIndex, size: ObjSize, bpp8: bool, mapping: ObjTileMapping,
+    tile_count: usize,
+  ) -> Self {
+    let frames = Self::frames_of(size, tile_count);
+    let mut sheet =
+      Self { first_slot: first_slot.0, size, bpp8, mapping, frames, across: 1 };
+    if mapping == ObjTileMapping::TwoD {
+      let room = 32 - first_slot.0 % 32;
+      sheet.across = room / sheet.row_slots();
+      assert!(sheet.across > 0, "the object doesn't fit across the 2D grid");
+    }
+    let last = sheet.slot(frames - 1);
+    let end = last + usize::from(mapping.slot_span(size, bpp8));
+    assert!(end <= OBJ_TILES.len(), "the frames don't fit in object VRAM");
+    if DISPCNT.read().video_mode() as u16 >= VideoMode::_3 as u16 {
+      assert!(
+        first_slot.0 >= 512,
+        "in a bitmap mode only object slots 512..1024 can be used"
+      );
+    }
+    sheet
+  }
+
+  /// The number of frames in `tile_count` tiles.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  fn frames_of(size: ObjSize, tile_count: usize) -> u16 {
+    let frame_tiles = usize::from(size.tile_count());
+    assert!(
+      tile_count > 0 && tile_count.is_multiple_of(frame_tiles),
+      "the tiles aren't a whole number of frames"
+    );
+    // anything this big won't fit in object VRAM anyway.
+    (tile_count / frame_tiles).min(usize::from(u16::MAX)) as u16
+  }
+
+  /// Copies each frame with `copy_frame`.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  fn copy<T>(
+    &self, tiles: &[T], copy_frame: fn(usize, ObjSize, ObjTileMapping, &[T]),
+  ) {
+    let frame_tiles = usize::from(self.size.tile_count());
+    for (frame, tiles) in tiles.chunks_exact(frame_tiles).enumerate() {
+      let slot = self.slot(frame as u16);
+      copy_frame(slot, self.size, self.mapping, tiles);
+    }
+  }
+
+  /// Allocates the slots for `tile_count` tiles of frames.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  fn alloc(
+    slots: &mut TileAllocator, size: ObjSize, bpp8: bool, tile_count: usize,
+  ) -> Option<TileIndex> {
+    let frames = usize::from(Self::frames_of(size, tile_count));
+    let align = if bpp8 { 2 } else { 1 };
+    match slots.obj_mapping() {
+      ObjTileMapping::OneD => slots.alloc_aligned(tile_count * align, align),
+      ObjTileMapping::TwoD => {
+        let (w, h) = size.dimensions();
+        let row_slots = usize::from(w / 8) * align;
+        let across = frames.min(32 / row_slots);
+        let rows = frames.div_ceil(across) * usize::from(h / 8);
+        slots.alloc_rect(across * row_slots, rows, align)
+      }
+    }
+  }
+}
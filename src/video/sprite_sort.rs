@@ -0,0 +1,307 @@
+//! Drawing objects in a sorted order.
+//!
+//! When objects overlap, the one with the lower OAM index is drawn on top. In
+//! a top-down game, characters lower on the screen should be in front of the
+//! ones above them, so as they walk past each other their OAM order has to
+//! keep changing. A [`SpriteSorter`] does that: each sprite has a sort key
+//! (usually the y of its feet), and every frame the sprites are written into
+//! the front of an [`OamShadow`] in key order, with the rest hidden.
+//!
+//! Handles stay with their sprite no matter which OAM index it ends up at, so
+//! nothing needs to keep track of where each sprite is this frame.
+//!
+//! ## Priority
+//!
+//! An object's `priority` (in attr2) sets how it's drawn relative to the
+//! backgrounds, but the hardware picks which object is on top by OAM index
+//! alone. A lower priority object in front of a higher priority one (by
+//! index) hides it, even where the background between them should be in
+//! front, which shows as a "hole" in the background. To avoid that, sprites
+//! are sorted by priority first (priority 0 at the front), and by key within
+//! each priority.
+//!
+//! ## Affine objects
+//!
+//! Only the objects' attributes move between OAM entries when sorting. The
+//! affine parameter sets (which are spread through the same entries) stay
+//! where they are, so the affine slot that an object uses still points at the
+//! same matrix wherever the object ends up. Set the matrices with
+//! [`oam_mut`](SpriteSorter::oam_mut).
+//!
+//! ## Cost
+//!
+//! Sorting uses an insertion sort over the order from the previous frame.
+//! Sprites usually only move a little each frame, so the order is almost
+//! sorted already and each sprite only moves a step or two. The worst case
+//! (the whole order reversed) is much slower, but that only happens after a
+//! scene changes all at once.
+
+use super::*;
+
+/// Marks a sprite that hasn't been sorted since it was added.
+const NOT_SORTED: u8 = u8::MAX;
+
+/// A handle to one sprite of a [`SpriteSorter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SortHandle {
+  index: u8,
+  generation: u16,
+}
+
+/// Keeps up to 128 sprites, and writes them to an OAM shadow sorted by
+/// priority and sort key.
+///
+/// Sprites with a *larger* key are drawn in front of those with a smaller
+/// key, so for a top-down game the key is the y position of the bottom of the
+/// sprite. Sprites with equal keys stay in the order they were in the last
+/// frame, so they don't flicker back and forth.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// let mut sorter = SpriteSorter::new();
+/// let hero = sorter.add(ObjAttr::new(), 0).unwrap();
+/// loop {
+///   // move the hero, and update its key to match.
+///   let obj = sorter.get_mut(hero).unwrap();
+///   obj.set_y(40);
+///   sorter.set_key(hero, 40 + 16);
+///
+///   VBlankIntrWait();
+///   sorter.commit_sorted();
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SpriteSorter {
+  oam: OamShadow,
+  attrs: [ObjAttr; 128],
+  keys: [u16; 128],
+  generations: [u16; 128],
+  used: u128,
+  /// The sprites in use, in draw order from the last sort (plus any added
+  /// since, at the end).
+  order: [u8; 128],
+  count: u8,
+  /// The OAM index each sprite was written to by the last sort, or
+  /// `NOT_SORTED`.
+  oam_indexes: [u8; 128],
+}
+impl SpriteSorter {
+  /// Makes a sorter with no sprites, and an OAM shadow with every object
+  /// hidden.
+  #[inline]
+  #[must_use]
+  pub const fn new() -> Self {
+    Self {
+      oam: OamShadow::new(),
+      attrs: [ObjAttr::HIDDEN; 128],
+      keys: [0; 128],
+      generations: [0; 128],
+      used: 0,
+      order: [0; 128],
+      count: 0,
+      oam_indexes: [NOT_SORTED; 128],
+    }
+  }
+
+  /// Adds a sprite, with its attributes and sort key.
+  ///
+  /// Gives `None` if there are already 128 sprites.
+  #[inline]
+  pub fn add(&mut self, attr: ObjAttr, key: u16) -> Option<SortHandle> {
+    let index = (!self.used).trailing_zeros() as usize;
+    if index >= 128 {
+      return None;
+    }
+    self.used |= 1 << index;
+    self.attrs[index] = attr;
+    self.keys[index] = key;
+    self.oam_indexes[index] = NOT_SORTED;
+    self.order[usize::from(self.count)] = index as u8;
+    self.count += 1;
+    Some(SortHandle { index: index as u8, generation: self.generations[index] })
+  }
+
+  /// Removes a sprite.
+  ///
+  /// Returns `false` (doing nothing) if the handle wasn't valid.
+  #[inline]
+  pub fn remove(&mut self, handle: SortHandle) -> bool {
+    if !self.is_valid(handle) {
+      return false;
+    }
+    let index = usize::from(handle.index);
+    self.used &= !(1 << index);
+    self.generations[index] = self.generations[index].wrapping_add(1);
+    let count = usize::from(self.count);
+    if let Some(pos) =
+      self.order[..count].iter().position(|&i| i == handle.index)
+    {
+      self.order.copy_within(pos + 1..count, pos);
+      self.count -= 1;
+    }
+    true
+  }
+
+  /// If the handle is for a sprite that's still in the sorter.
+  #[inline]
+  #[must_use]
+  pub const fn is_valid(&self, handle: SortHandle) -> bool {
+    let index = handle.index as usize;
+    (self.used & (1 << index)) != 0
+      && self.generations[index] == handle.generation
+  }
+
+  /// Gets the attributes of a sprite.
+  ///
+  /// Gives `None` if the handle isn't valid.
+  #[inline]
+  #[must_use]
+  pub fn get(&self, handle: SortHandle) -> Option<&ObjAttr> {
+    self.is_valid(handle).then(|| &self.attrs[usize::from(handle.index)])
+  }
+
+  /// Gets the attributes of a sprite mutably.
+  ///
+  /// Gives `None` if the handle isn't valid.
+  #[inline]
+  #[must_use]
+  pub fn get_mut(&mut self, handle: SortHandle) -> Option<&mut ObjAttr> {
+    if self.is_valid(handle) {
+      Some(&mut self.attrs[usize::from(handle.index)])
+    } else {
+      None
+    }
+  }
+
+  /// The sort key of a sprite.
+  ///
+  /// Gives `None` if the handle isn't valid.
+  #[inline]
+  #[must_use]
+  pub fn key(&self, handle: SortHandle) -> Option<u16> {
+    self.is_valid(handle).then(|| self.keys[usize::from(handle.index)])
+  }
+
+  /// Sets the sort key of a sprite.
+  ///
+  /// Returns `false` (doing nothing) if the handle wasn't valid.
+  #[inline]
+  pub fn set_key(&mut self, handle: SortHandle, key: u16) -> bool {
+    if !self.is_valid(handle) {
+      return false;
+    }
+    self.keys[usize::from(handle.index)] = key;
+    true
+  }
+
+  /// The number of sprites.
+  #[inline]
+  #[must_use]
+  pub const fn len(&self) -> usize {
+    self.count as usize
+  }
+
+  /// If there are no sprites.
+  #[inline]
+  #[must_use]
+  pub const fn is_empty(&self) -> bool {
+    self.count == 0
+  }
+
+  /// Removes every sprite.
+  ///
+  /// All handles given out so far become invalid.
+  #[inline]
+  pub fn clear(&mut self) {
+    for &index in &self.order[..usize::from(self.count)] {
+      let index = usize::from(index);
+      self.generations[index] = self.generations[index].wrapping_add(1);
+    }
+    self.used = 0;
+    self.count = 0;
+  }
+
+  /// The OAM index that a sprite was written to by the last sort.
+  ///
+  /// Gives `None` if the handle isn't valid, or if the sprite was added since
+  /// the last sort.
+  #[inline]
+  #[must_use]
+  pub fn oam_index(&self, handle: SortHandle) -> Option<usize> {
+    if !self.is_valid(handle) {
+      return None;
+    }
+    match self.oam_indexes[usize::from(handle.index)] {
+      NOT_SORTED => None,
+      n => Some(usize::from(n)),
+    }
+  }
+
+  /// Sorts the sprites, and writes them to the front of the OAM shadow.
+  ///
+  /// Sprite `n` in draw order goes to object `n` of the shadow, and every
+  /// object after the last sprite is hidden. Gives the number of sprites
+  /// written. Only object attributes are changed, not affine parameters.
+  #[inline]
+  pub fn sort(&mut self) -> usize {
+    let count = usize::from(self.count);
+    for i in 1..count {
+      let current = self.order[i];
+      let mut j = i;
+      while j > 0 && self.in_front(current, self.order[j - 1]) {
+        self.order[j] = self.order[j - 1];
+        j -= 1;
+      }
+      self.order[j] = current;
+    }
+    for (n, &index) in self.order[..count].iter().enumerate() {
+      *self.oam.obj_mut(n) = self.attrs[usize::from(index)];
+      self.oam_indexes[usize::from(index)] = n as u8;
+    }
+    self.oam.hide_range(count, 128 - count);
+    count
+  }
+
+  /// Sorts the sprites into the OAM shadow (see [`sort`](Self::sort)), and
+  /// then copies the whole shadow to OAM.
+  ///
+  /// This should be called during vblank.
+  #[inline]
+  pub fn commit_sorted(&mut self) -> usize {
+    let count = self.sort();
+    self.oam.commit();
+    count
+  }
+
+  /// The OAM shadow that sprites are sorted into.
+  #[inline]
+  #[must_use]
+  pub const fn oam(&self) -> &OamShadow {
+    &self.oam
+  }
+
+  /// The OAM shadow that sprites are sorted into, mutably.
+  ///
+  /// This is for setting affine parameters. Object attributes changed here
+  /// are overwritten by the next sort.
+  #[inline]
+  #[must_use]
+  pub fn oam_mut(&mut self) -> &mut OamShadow {
+    &mut self.oam
+  }
+
+  /// If sprite `a` goes in front of sprite `b`.
+  #[inline]
+  #[must_use]
+  fn in_front(&self, a: u8, b: u8) -> bool {
+    let (a, b) = (usize::from(a), usize::from(b));
+    let (pa, pb) = (self.attrs[a].2.priority(), self.attrs[b].2.priority());
+    pa < pb || (pa == pb && self.keys[a] > self.keys[b])
+  }
+}
+impl Default for SpriteSorter {
+  #[inline]
+  fn default() -> Self {
+    Self::new()
+  }
+}
@@ -0,0 +1,213 @@
+//! Printing text to a tiled background.
+//!
+//! This uses the built-in [`CGA_8X8_THICK`] font. The font is stored in ROM at
+//! 1bpp (8 bytes per glyph), and [`load_font_4bpp`] expands it into 4bpp tiles
+//! using whichever two palette indexes you like. The font has all 256 glyphs
+//! of [Code Page 437][cp437], which matches ASCII for the printable range
+//! (`0x20..=0x7E`), so each glyph's tile is just `first_tile + byte`.
+//!
+//! Once the font is loaded, a [`TextWriter`] can print to a text background's
+//! screenblock using the [`write!`] macro, since it implements
+//! [`core::fmt::Write`].
+//!
+//! [cp437]: https://en.wikipedia.org/wiki/Code_page_437
+
+use crate::prelude::*;
+use core::fmt;
+
+/// The number of text columns visible on the screen (with no scrolling).
+pub const COLUMNS: u8 = 30;
+
+/// The number of text rows visible on the screen (with no scrolling).
+pub const ROWS: u8 = 20;
+
+/// One row (`0..8`) of a font glyph, with bit `x` set where column `x` is
+/// part of the glyph.
+#[inline]
+#[must_use]
+pub(crate) const fn glyph_row(glyph: u8, y: usize) -> u8 {
+  (CGA_8X8_THICK[2 * glyph as usize + y / 4] >> (8 * (y % 4))) as u8
+}
+
+/// The glyph that shows a character: the same byte for printable ASCII, and
+/// `?` for anything else.
+#[inline]
+#[must_use]
+pub(crate) const fn glyph_for(c: char) -> u8 {
+  match c {
+    ' '..='~' => c as u8,
+    _ => b'?',
+  }
+}
+
+/// Expands one 1bpp glyph into a 4bpp tile.
+#[inline]
+#[must_use]
+const fn glyph_tile(glyph: u8, fg: u8, bg: u8) -> Tile4 {
+  let mut tile = [0; 8];
+  let mut y = 0;
+  while y < 8 {
+    let row = glyph_row(glyph, y);
+    let mut x = 0;
+    while x < 8 {
+      let index = if row & (1 << x) != 0 { fg } else { bg };
+      tile[y] |= ((index & 0xF) as u32) << (4 * x);
+      x += 1;
+    }
+    y += 1;
+  }
+  tile
+}
+
+/// Loads all 256 font glyphs into a charblock as 4bpp tiles.
+///
+/// * `first_tile` is the tile index within the charblock for glyph 0.
+/// * `fg` and `bg` are the palette indexes (`0..16`) to use for the glyph's
+///   lines and the background. A `bg` of 0 makes the background transparent.
+///
+/// When `bg` is 0, or when `fg` is `bg + 1`, the BIOS [`BitUnPack`] does the
+/// expanding (using the offset to pick the indexes). Other combinations are
+/// expanded one tile at a time in Rust, which is slower.
+///
+/// ## Panics
+/// * All 256 tiles must fit within the charblock.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn load_font_4bpp(charblock: usize, first_tile: usize, fg: u8, bg: u8) {
+  let block = charblock_4bpp(charblock);
+  assert!(first_tile + 256 <= block.len(), "font tiles out of range");
+  #[cfg(feature = "on_gba")]
+  {
+    let (fg, bg) = (fg & 0xF, bg & 0xF);
+    // A 1 pixel becomes `1 + offset`, and a 0 pixel stays 0 unless bit 31
+    // ("touch zero") is set, in which case it becomes `offset`.
+    let offset = if bg == 0 && fg != 0 {
+      Some(u32::from(fg - 1))
+    } else if fg == bg + 1 {
+      Some(u32::from(bg) | (1 << 31))
+    } else {
+      None
+    };
+    if let Some(offset) = offset {
+      let tiles = block.as_region().sub_slice(first_tile..first_tile + 256);
+      Cga8x8Thick.bitunpack_4bpp(tiles, offset);
+      return;
+    }
+  }
+  for glyph in 0..=255 {
+    block
+      .index(first_tile + usize::from(glyph))
+      .write(glyph_tile(glyph, fg, bg));
+  }
+}
+
+/// Prints text to a 32x32 text screenblock.
+///
+/// * Text is printed starting from the top left, and wraps to the next line
+///   after [`COLUMNS`] characters, or at a `'\n'`.
+/// * When the cursor goes past the last visible row ([`ROWS`]) it wraps back
+///   around to the top row. Each time a new line is started that row is
+///   cleared, so the newest text is always readable.
+/// * Characters outside of the printable ASCII range are shown as `?`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextWriter {
+  screenblock: usize,
+  first_tile: u16,
+  palbank: u16,
+  col: u8,
+  row: u8,
+}
+impl TextWriter {
+  /// Makes a writer for the screenblock given, with the cursor at the top
+  /// left.
+  ///
+  /// * `first_tile` and `palbank` should match how the font was loaded with
+  ///   [`load_font_4bpp`].
+  ///
+  /// ## Panics
+  /// * The screenblock must be in `0..32`.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(screenblock: usize, first_tile: u16, palbank: u16) -> Self {
+    assert!(screenblock < 32, "screenblock index out of range");
+    Self { screenblock, first_tile, palbank, col: 0, row: 0 }
+  }
+
+  /// The cursor position as `(column, row)`.
+  #[inline]
+  #[must_use]
+  pub const fn cursor(&self) -> (u8, u8) {
+    (self.col, self.row)
+  }
+
+  /// Moves the cursor, wrapping to the visible area.
+  #[inline]
+  pub fn set_cursor(&mut self, col: u8, row: u8) {
+    self.col = col % COLUMNS;
+    self.row = row % ROWS;
+  }
+
+  #[inline]
+  fn entry(&self, byte: u8) -> TextEntry {
+    TextEntry::from_tile(self.first_tile + u16::from(byte))
+      .with_palbank(self.palbank)
+  }
+
+  #[inline]
+  fn clear_row(&self, row: u8) {
+    let blank = self.entry(b' ');
+    let sb = text_screenblock(self.screenblock);
+    for addr in
+      sb.get_row(usize::from(row)).unwrap().iter().take(usize::from(COLUMNS))
+    {
+      addr.write(blank);
+    }
+  }
+
+  /// Clears the visible area to spaces, and moves the cursor to the top left.
+  #[inline]
+  pub fn clear(&mut self) {
+    for row in 0..ROWS {
+      self.clear_row(row);
+    }
+    self.col = 0;
+    self.row = 0;
+  }
+
+  #[inline]
+  fn newline(&mut self) {
+    self.col = 0;
+    self.row = (self.row + 1) % ROWS;
+    self.clear_row(self.row);
+  }
+
+  /// Prints a single byte, using the font's glyph for that byte.
+  ///
+  /// Unlike when using [`write!`], any byte can be printed with this (eg: the
+  /// box drawing glyphs of [`Cga8x8Thick`]), except that `b'\n'` still starts
+  /// a new line.
+  #[inline]
+  pub fn write_byte(&mut self, byte: u8) {
+    if byte == b'\n' {
+      self.newline();
+      return;
+    }
+    if self.col >= COLUMNS {
+      self.newline();
+    }
+    let sb = text_screenblock(self.screenblock);
+    sb.index(usize::from(self.col), usize::from(self.row))
+      .write(self.entry(byte));
+    self.col += 1;
+  }
+}
+impl fmt::Write for TextWriter {
+  #[inline]
+  fn write_str(&mut self, s: &str) -> fmt::Result {
+    for c in s.chars() {
+      self.write_byte(if c == '\n' { b'\n' } else { glyph_for(c) });
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,263 @@
+//! Runtime allocation of tile slots in VRAM.
+//!
+//! A [`TileAllocator`] tracks which tile slots of some part of VRAM are in use,
+//! so that graphics can be loaded and unloaded as a game runs (eg: streaming
+//! in the frames of sprite animations).
+//!
+//! Slots are always counted in 4bpp tile units (32 bytes), because that's how
+//! object tile indexes work even for 8bpp objects. An 8bpp tile takes two
+//! slots, and 8bpp allocations always start on an even slot.
+//!
+//! Object allocations go where the object's tiles need to be for its
+//! [`ObjTileMapping`]. That's 1D mapping unless it's changed with
+//! [`with_obj_mapping`](TileAllocator::with_obj_mapping): with 2D mapping,
+//! [`alloc_obj`](TileAllocator::alloc_obj) finds a free rectangle in the 32
+//! slot wide grid instead of a free run.
+//!
+//! The allocator doesn't need a heap. It's a fixed size value with a `const`
+//! constructor, so it can be placed in a `static` (eg: within a
+//! [critical section](https://docs.rs/critical-section) mutex).
+
+use super::obj::{ObjSize, ObjTileMapping};
+
+/// The number of 4bpp tile slots in object VRAM.
+const MAX_SLOTS: usize = 1024;
+
+/// The index of the first 4bpp tile slot of an allocation.
+///
+/// For objects this can be used directly as the tile index. For an 8bpp
+/// background, divide by 2 to get the tile index used in the tilemap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(transparent)]
+pub struct TileIndex(pub u16);
+
+/// Tracks the free tile slots within a range of VRAM, using first-fit
+/// allocation.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TileAllocator {
+  used: [u32; MAX_SLOTS / 32],
+  start: u16,
+  end: u16,
+  obj_mapping: ObjTileMapping,
+}
+impl TileAllocator {
+  /// Manages tile slots `start..end`.
+  ///
+  /// ## Panics
+  /// * `end` can't be more than 1024, and `start` must be less than `end`.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(start: u16, end: u16) -> Self {
+    assert!(start < end && end as usize <= MAX_SLOTS, "invalid slot range");
+    Self {
+      used: [0; MAX_SLOTS / 32],
+      start,
+      end,
+      obj_mapping: ObjTileMapping::OneD,
+    }
+  }
+
+  /// Manages all 512 tile slots of one background charblock.
+  ///
+  /// Indexes are relative to the start of the charblock.
+  #[inline]
+  #[must_use]
+  pub const fn new_charblock() -> Self {
+    Self::new(0, 512)
+  }
+
+  /// Manages all 1024 tile slots of object VRAM.
+  #[inline]
+  #[must_use]
+  pub const fn new_obj() -> Self {
+    Self::new(0, 1024)
+  }
+
+  /// Manages object VRAM when using a bitmap video mode (3, 4, or 5).
+  ///
+  /// The bitmap modes use the lower half of object VRAM for the bitmap, so
+  /// only tile slots `512..1024` are available to objects.
+  #[inline]
+  #[must_use]
+  pub const fn new_obj_bitmap_mode() -> Self {
+    Self::new(512, 1024)
+  }
+
+  /// Sets the mapping that [`alloc_obj`](Self::alloc_obj) and
+  /// [`free_obj`](Self::free_obj) place objects for.
+  ///
+  /// This should match [`DISPCNT`](crate::mmio::DISPCNT), such as with
+  /// `TileAllocator::new_obj().with_obj_mapping(ObjTileMapping::current())`.
+  #[inline]
+  #[must_use]
+  pub const fn with_obj_mapping(self, obj_mapping: ObjTileMapping) -> Self {
+    Self { obj_mapping, ..self }
+  }
+
+  /// The mapping that objects are placed for.
+  #[inline]
+  #[must_use]
+  pub const fn obj_mapping(&self) -> ObjTileMapping {
+    self.obj_mapping
+  }
+
+  #[inline]
+  #[must_use]
+  const fn is_used(&self, slot: usize) -> bool {
+    (self.used[slot / 32] & (1 << (slot % 32))) != 0
+  }
+
+  #[inline]
+  fn set_range(&mut self, start: usize, count: usize, used: bool) {
+    for slot in start..(start + count) {
+      let bit = 1 << (slot % 32);
+      if used {
+        self.used[slot / 32] |= bit;
+      } else {
+        self.used[slot / 32] &= !bit;
+      }
+    }
+  }
+
+  /// Finds `count` free slots in a row, starting on a multiple of `align`.
+  #[inline]
+  pub(crate) fn alloc_aligned(
+    &mut self, count: usize, align: usize,
+  ) -> Option<TileIndex> {
+    if count == 0 {
+      return None;
+    }
+    let end = usize::from(self.end);
+    let mut start = usize::from(self.start).next_multiple_of(align);
+    while start + count <= end {
+      match (start..start + count).rev().find(|&slot| self.is_used(slot)) {
+        // skip past the used slot, and try again.
+        Some(used) => start = (used + 1).next_multiple_of(align),
+        None => {
+          self.set_range(start, count, true);
+          return Some(TileIndex(start as u16));
+        }
+      }
+    }
+    None
+  }
+
+  /// Finds a free rectangle of the 32 slot wide grid, `width` slots across
+  /// and `rows` tall, with its first slot on a multiple of `align`.
+  #[inline]
+  pub(crate) fn alloc_rect(
+    &mut self, width: usize, rows: usize, align: usize,
+  ) -> Option<TileIndex> {
+    let end = usize::from(self.end);
+    let mut start = usize::from(self.start).next_multiple_of(align);
+    while start + (rows - 1) * 32 + width <= end {
+      let free = start % 32 + width <= 32
+        && (0..rows).all(|row| {
+          let first = start + row * 32;
+          (first..first + width).all(|slot| !self.is_used(slot))
+        });
+      if free {
+        for row in 0..rows {
+          self.set_range(start + row * 32, width, true);
+        }
+        return Some(TileIndex(start as u16));
+      }
+      start += align;
+    }
+    None
+  }
+
+  /// Allocates `n_tiles` 4bpp tile slots in a row.
+  ///
+  /// Gives `None` if there's no free run that's long enough (or if `n_tiles`
+  /// is 0).
+  #[inline]
+  pub fn alloc(&mut self, n_tiles: usize) -> Option<TileIndex> {
+    self.alloc_aligned(n_tiles, 1)
+  }
+
+  /// Allocates space for `n_tiles` 8bpp tiles in a row.
+  ///
+  /// This uses two slots per tile, and the first slot is always even.
+  #[inline]
+  pub fn alloc_8bpp(&mut self, n_tiles: usize) -> Option<TileIndex> {
+    self.alloc_aligned(n_tiles * 2, 2)
+  }
+
+  /// Allocates the tile slots for one object of the size given.
+  ///
+  /// With 1D mapping this is the object's
+  /// [`tile_slots`](ObjSize::tile_slots) in a row. With 2D mapping it's a
+  /// rectangle of the grid, with each row of the object's tiles 32 slots after
+  /// the last. Either way that's where
+  /// [`copy_obj_4bpp`](super::obj::copy_obj_4bpp)
+  /// and [`copy_obj_8bpp`](super::obj::copy_obj_8bpp) put the tiles. For an
+  /// 8bpp object the first slot is always even, and the index can be used
+  /// directly as the object's tile index.
+  #[inline]
+  pub fn alloc_obj(&mut self, size: ObjSize, bpp8: bool) -> Option<TileIndex> {
+    let align = if bpp8 { 2 } else { 1 };
+    match self.obj_mapping {
+      ObjTileMapping::OneD => {
+        self.alloc_aligned(usize::from(size.tile_slots(bpp8)), align)
+      }
+      ObjTileMapping::TwoD => {
+        let (w, h) = size.dimensions();
+        self.alloc_rect(usize::from(w / 8) * align, usize::from(h / 8), align)
+      }
+    }
+  }
+
+  /// Frees the slots of one object allocated with
+  /// [`alloc_obj`](Self::alloc_obj).
+  #[inline]
+  pub fn free_obj(&mut self, index: TileIndex, size: ObjSize, bpp8: bool) {
+    match self.obj_mapping {
+      ObjTileMapping::OneD => {
+        self.free(index, usize::from(size.tile_slots(bpp8)))
+      }
+      ObjTileMapping::TwoD => {
+        let (w, h) = size.dimensions();
+        let width = usize::from(w / 8) * if bpp8 { 2 } else { 1 };
+        for row in 0..h / 8 {
+          self.free(TileIndex(index.0 + row * 32), width);
+        }
+      }
+    }
+  }
+
+  /// Frees `n_tiles` 4bpp tile slots, starting at the index given.
+  ///
+  /// Any part of the range that's outside of this allocator's range is
+  /// ignored.
+  #[inline]
+  pub fn free(&mut self, index: TileIndex, n_tiles: usize) {
+    let start = usize::from(index.0).max(usize::from(self.start));
+    let end = (usize::from(index.0) + n_tiles).min(usize::from(self.end));
+    if start < end {
+      self.set_range(start, end - start, false);
+    }
+  }
+
+  /// Frees the slots of `n_tiles` 8bpp tiles, starting at the index given.
+  #[inline]
+  pub fn free_8bpp(&mut self, index: TileIndex, n_tiles: usize) {
+    self.free(index, n_tiles * 2)
+  }
+
+  /// Frees every slot.
+  #[inline]
+  pub fn reset(&mut self) {
+    self.used = [0; MAX_SLOTS / 32];
+  }
+
+  /// The number of free slots.
+  #[inline]
+  #[must_use]
+  pub fn free_slots(&self) -> usize {
+    (usize::from(self.start)..usize::from(self.end))
+      .filter(|&slot| !self.is_used(slot))
+      .count()
+  }
+}
@@ -0,0 +1,371 @@
+//! Tile map data for levels, and loading it into text backgrounds.
+//!
+//! A level is usually much bigger than a background, and it's stored in ROM
+//! in whatever form is smallest, then copied into a background a part at a
+//! time. Anything that can give the [`TextEntry`] for a tile position is a
+//! [`MapSource`], and there are two kinds here:
+//!
+//! * A [`TileGrid`] is one entry per tile, row by row.
+//! * A [`MetatileMap`] is a grid of indexes into a table of [`Metatile`]s, each
+//!   of which is a square of 2x2 (or 4x4) entries. Levels are mostly made of a
+//!   few repeating shapes, so this is much smaller, and each metatile also has
+//!   collision flags that can be looked up by position.
+//!
+//! Both are made with `const fn`s from slices, so a level can be exported as
+//! Rust source (or included bytes) and used directly from ROM, with no
+//! parsing at runtime:
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! use gba::video::tilemap::{Metatile, MetatileMap};
+//! const SOLID: u8 = 1 << 0;
+//! const SKY: Metatile<2> = Metatile::new([[TextEntry::new(); 2]; 2], 0);
+//! const WALL: Metatile<2> =
+//!   Metatile::new([[TextEntry::from_tile(1); 2]; 2], SOLID);
+//! static METATILES: [Metatile<2>; 2] = [SKY, WALL];
+//! static GRID: [u16; 16 * 10] = [0; 16 * 10];
+//! static LEVEL: MetatileMap<'static, 2> =
+//!   MetatileMap::new(&[], &METATILES, &GRID, 16, 10);
+//! ```
+//!
+//! [`load_region`] copies part of a map into a background once (eg: for a
+//! screen that doesn't scroll), and a
+//! [`TiledCamera`](super::camera::TiledCamera) streams a map in as it scrolls.
+
+use super::*;
+
+/// A tile map, which can give the background entry for each tile position.
+pub trait MapSource {
+  /// The width and height of the map, in tiles.
+  fn size(&self) -> (usize, usize);
+
+  /// The entry for the tile at `(x, y)`.
+  ///
+  /// This is only called with positions within the map's size.
+  fn entry(&self, x: usize, y: usize) -> TextEntry;
+}
+impl<M: MapSource + ?Sized> MapSource for &M {
+  #[inline]
+  fn size(&self) -> (usize, usize) {
+    (**self).size()
+  }
+  #[inline]
+  fn entry(&self, x: usize, y: usize) -> TextEntry {
+    (**self).entry(x, y)
+  }
+}
+
+/// A tile map stored as one entry per tile, row by row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct TileGrid<'a> {
+  entries: &'a [TextEntry],
+  width: usize,
+  height: usize,
+}
+impl<'a> TileGrid<'a> {
+  /// Makes a `width` by `height` map, with `width` entries per row.
+  ///
+  /// ## Panics
+  /// * `entries` must hold at least `width * height` entries.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(
+    entries: &'a [TextEntry], width: usize, height: usize,
+  ) -> Self {
+    assert!(entries.len() >= width * height, "map data too short");
+    Self { entries, width, height }
+  }
+
+  /// Decompresses a `width` by `height` map from LZ77 data, into new space in
+  /// an [`Arena`](crate::arena::Arena).
+  ///
+  /// This is for maps that are stored compressed in ROM. They're unpacked
+  /// when a level loads, and freed along with the rest of the level's memory.
+  ///
+  /// ## Failure
+  /// * If the data's header is bad, or if its size isn't exactly `width *
+  ///   height` entries, you get [`DecompressError::BadHeader`].
+  /// * If there isn't room in the arena you get
+  ///   [`DecompressError::DestTooSmall`].
+  /// * On an error nothing is allocated.
+  #[cfg(feature = "on_gba")]
+  #[inline]
+  pub fn decompress_lz77_in(
+    src: &[u32], arena: &'a crate::arena::Arena, width: usize, height: usize,
+  ) -> Result<Self, DecompressError> {
+    let count = width.checked_mul(height).ok_or(DecompressError::BadHeader)?;
+    if count.checked_mul(2) != Some(lz77_decompressed_len(src)?) {
+      return Err(DecompressError::BadHeader);
+    }
+    let dest = arena
+      .alloc_uninit_slice::<TextEntry>(count)
+      .ok_or(DecompressError::DestTooSmall)?;
+    // Safety: the header says the data is `count` entries, which is what was
+    // allocated, so every entry is written.
+    let entries = unsafe {
+      LZ77UnCompReadNormalWrite8bit(
+        src.as_ptr().cast(),
+        dest.as_mut_ptr().cast(),
+      );
+      &*(dest as *mut [core::mem::MaybeUninit<TextEntry>]
+        as *const [TextEntry])
+    };
+    Ok(Self { entries, width, height })
+  }
+
+  /// The entries, row by row.
+  #[inline]
+  #[must_use]
+  pub const fn entries(&self) -> &'a [TextEntry] {
+    self.entries
+  }
+}
+impl MapSource for TileGrid<'_> {
+  #[inline]
+  fn size(&self) -> (usize, usize) {
+    (self.width, self.height)
+  }
+  #[inline]
+  fn entry(&self, x: usize, y: usize) -> TextEntry {
+    self.entries[y * self.width + x]
+  }
+}
+
+/// A square of `N` by `N` background entries, and its collision flags.
+///
+/// The meaning of the `flags` bits is up to you (eg: solid, ladder, water,
+/// damaging), the crate only stores them and looks them up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Metatile<const N: usize> {
+  /// The entries, row by row.
+  pub entries: [[TextEntry; N]; N],
+  /// The collision flags.
+  pub flags: u8,
+}
+impl<const N: usize> Metatile<N> {
+  /// Makes a metatile from its rows of entries and its flags.
+  #[inline]
+  #[must_use]
+  pub const fn new(entries: [[TextEntry; N]; N], flags: u8) -> Self {
+    Self { entries, flags }
+  }
+
+  /// The entry at `(x, y)` within the metatile.
+  ///
+  /// ## Panics
+  /// * Both `x` and `y` must be less than `N`.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn entry(&self, x: usize, y: usize) -> TextEntry {
+    self.entries[y][x]
+  }
+}
+
+/// A tile map stored as a grid of [`Metatile`] indexes.
+///
+/// * `tileset` is the tile graphics the entries use (see
+///   [`load_tileset`](Self::load_tileset)). It can be empty if the tiles are
+///   loaded some other way.
+/// * `metatiles` is the table of metatiles, which `grid` indexes into. The
+///   indexes are `u16`, so a map can have many more kinds of metatile than the
+///   1024 tiles a background can use.
+/// * `grid` is the metatile index for each metatile position, row by row,
+///   `width` per row.
+///
+/// Sizes and positions of the map as a [`MapSource`] are in tiles, while the
+/// `width` and `height` given here are in metatiles, so the map is `width *
+/// N` by `height * N` tiles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct MetatileMap<'a, const N: usize> {
+  tileset: &'a [Tile4],
+  metatiles: &'a [Metatile<N>],
+  grid: &'a [u16],
+  width: usize,
+  height: usize,
+}
+impl<'a, const N: usize> MetatileMap<'a, N> {
+  /// Makes a map that's `width` by `height` metatiles.
+  ///
+  /// Every index in the grid is checked here, so that looking up metatiles
+  /// afterwards can't go out of bounds. For a map made in a `const` (or
+  /// `static`) that check happens at compile time.
+  ///
+  /// ## Panics
+  /// * `N` can't be 0.
+  /// * `grid` must hold at least `width * height` indexes.
+  /// * Every index used must be within `metatiles`.
+  #[inline]
+  #[must_use]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub const fn new(
+    tileset: &'a [Tile4], metatiles: &'a [Metatile<N>], grid: &'a [u16],
+    width: usize, height: usize,
+  ) -> Self {
+    assert!(N > 0, "metatiles can't be empty");
+    assert!(grid.len() >= width * height, "map data too short");
+    let mut i = 0;
+    while i < width * height {
+      assert!((grid[i] as usize) < metatiles.len(), "metatile out of range");
+      i += 1;
+    }
+    Self { tileset, metatiles, grid, width, height }
+  }
+
+  /// The tile graphics of the map.
+  #[inline]
+  #[must_use]
+  pub const fn tileset(&self) -> &'a [Tile4] {
+    self.tileset
+  }
+
+  /// The table of metatiles.
+  #[inline]
+  #[must_use]
+  pub const fn metatiles(&self) -> &'a [Metatile<N>] {
+    self.metatiles
+  }
+
+  /// The width and height of the map, in metatiles.
+  #[inline]
+  #[must_use]
+  pub const fn size_in_metatiles(&self) -> (usize, usize) {
+    (self.width, self.height)
+  }
+
+  /// The metatile index at metatile position `(x, y)`.
+  ///
+  /// Gives `None` outside of the map.
+  #[inline]
+  #[must_use]
+  pub const fn metatile_index(&self, x: usize, y: usize) -> Option<u16> {
+    if x < self.width && y < self.height {
+      Some(self.grid[y * self.width + x])
+    } else {
+      None
+    }
+  }
+
+  /// The metatile covering the map pixel at `(x, y)`.
+  ///
+  /// Gives `None` outside of the map. Negative positions are outside too, so
+  /// positions (such as an object's, which can be off the map) can be used
+  /// directly.
+  #[inline]
+  #[must_use]
+  pub const fn metatile_at(&self, x: i32, y: i32) -> Option<&'a Metatile<N>> {
+    if x < 0 || y < 0 {
+      return None;
+    }
+    let size = N * 8;
+    match self.metatile_index(x as usize / size, y as usize / size) {
+      Some(index) => Some(&self.metatiles[index as usize]),
+      None => None,
+    }
+  }
+
+  /// The collision flags of the metatile covering the map pixel at `(x, y)`.
+  ///
+  /// Outside of the map this is 0 (no flags).
+  #[inline]
+  #[must_use]
+  pub const fn flags_at(&self, x: i32, y: i32) -> u8 {
+    match self.metatile_at(x, y) {
+      Some(metatile) => metatile.flags,
+      None => 0,
+    }
+  }
+
+  /// Copies the tileset into a background charblock, starting at
+  /// `first_tile`.
+  ///
+  /// The tiles can go past the end of the charblock into the next one (a
+  /// background's entries can use up to 1024 tiles from its charblock).
+  ///
+  /// ## Panics
+  /// * The tiles have to fit within background VRAM.
+  #[inline]
+  #[cfg_attr(feature = "track_caller", track_caller)]
+  pub fn load_tileset(&self, charblock: usize, first_tile: usize) {
+    let start = charblock * 512 + first_tile;
+    assert!(start + self.tileset.len() <= 2048, "tileset out of range");
+    let tiles = CHARBLOCK0_4BPP.index(0).as_usize();
+    for (i, tile) in self.tileset.iter().enumerate() {
+      let addr: VolAddress<Tile4, Safe, Safe> =
+        unsafe { VolAddress::new(tiles + (start + i) * size_of::<Tile4>()) };
+      addr.write(*tile);
+    }
+  }
+}
+impl<const N: usize> MapSource for MetatileMap<'_, N> {
+  #[inline]
+  fn size(&self) -> (usize, usize) {
+    (self.width * N, self.height * N)
+  }
+  #[inline]
+  fn entry(&self, x: usize, y: usize) -> TextEntry {
+    let index = self.grid[(y / N) * self.width + x / N];
+    self.metatiles[index as usize].entry(x % N, y % N)
+  }
+}
+
+/// Copies a rectangle of a map into the top left of a text background.
+///
+/// Map tile `(x + col, y + row)` goes to background position `(col, row)`,
+/// for a rectangle that's `width` by `height` tiles. This is for screens that
+/// don't scroll, or that only scroll within what's loaded.
+///
+/// * `screenblock` is the background's first screenblock, and `size` is the
+///   background's size (see [`fill_text_region`]).
+///
+/// ## Panics
+/// * The rectangle must be within the map.
+/// * The tilemap must fit within background VRAM.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn load_region<M: MapSource + ?Sized>(
+  map: &M, (x, y): (usize, usize), (width, height): (usize, usize),
+  screenblock: usize, size: TextBackgroundSize,
+) {
+  let (map_width, map_height) = map.size();
+  assert!(
+    x + width <= map_width && y + height <= map_height,
+    "region out of the map"
+  );
+  copy_region(map, (x, y), (0, 0), (width, height), screenblock, size);
+}
+
+/// The number of screenblocks a text background uses.
+#[inline]
+#[must_use]
+pub(crate) const fn screenblock_count(size: TextBackgroundSize) -> usize {
+  match size {
+    TextBackgroundSize::_32x32 => 1,
+    TextBackgroundSize::_64x64 => 4,
+    _ => 2,
+  }
+}
+
+/// Copies a `width` by `height` rectangle of map tiles from `src` in the map
+/// to `dst` in the background (wrapped to the background size).
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub(crate) fn copy_region<M: MapSource + ?Sized>(
+  map: &M, src: (usize, usize), dst: (usize, usize),
+  (width, height): (usize, usize), screenblock: usize,
+  size: TextBackgroundSize,
+) {
+  assert!(screenblock + screenblock_count(size) <= 32, "tilemap out of range");
+  let base = text_screenblock(screenblock).index(0, 0).as_usize();
+  for row in 0..height {
+    for col in 0..width {
+      let i = text_entry_index(size, dst.0 + col, dst.1 + row);
+      let addr: VolAddress<TextEntry, Safe, Safe> =
+        unsafe { VolAddress::new(base + i * size_of::<TextEntry>()) };
+      addr.write(map.entry(src.0 + col, src.1 + row));
+    }
+  }
+}
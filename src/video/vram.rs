@@ -0,0 +1,92 @@
+//! Copying byte data into VRAM (and PALRAM and OAM), which can't be written
+//! one byte at a time.
+//!
+//! Asset converters often output `&[u8]`, and the obvious way to copy that is
+//! a byte at a time. That doesn't work with video memory: a byte written to
+//! PALRAM or the background part of VRAM is written to *both* halves of its
+//! halfword, and a byte written to the object part of VRAM or to OAM is just
+//! ignored. Either way every other byte ends up wrong. [`copy_u8_src`] does the
+//! same copy with only 16-bit and 32-bit writes:
+//!
+//! ```no_run
+//! # use gba::prelude::*;
+//! # let tiles: &[u8] = &[];
+//! // tile 3 onward.
+//! vram::copy_u8_src(tiles, CHARBLOCK0_4BPP.as_region(), 3 * 32);
+//! ```
+//!
+//! [`copy_u8_unchecked`](crate::mem::copy_u8_unchecked) checks for video
+//! memory (with [`in_video_memory`](crate::mem::in_video_memory)) when debug
+//! assertions are on, to catch this mistake.
+
+use core::mem::size_of;
+
+use voladdress::{Safe, VolRegion};
+
+/// Copies bytes to `dest`, starting `byte_offset` bytes into it, using only
+/// 16-bit and 32-bit writes.
+///
+/// * If the copy starts on an odd byte, that byte is put in with a
+///   read-modify-write of its halfword.
+/// * The middle is written a word at a time (with a halfword before it if it
+///   doesn't start on a multiple of 4, and one after if there's a halfword
+///   left).
+/// * If the copy ends on an even byte, that last byte is put in with another
+///   read-modify-write.
+///
+/// `dest` can be a region of any type (such as tiles, or palette entries),
+/// `byte_offset` is counted in bytes from its start either way. The bytes of
+/// `dest` outside of the copy are left as they were. `src` can have any
+/// alignment.
+///
+/// ## Panics
+/// * `dest` must be aligned to 2.
+/// * The copy has to fit in `dest`: `byte_offset + src.len()` can be at most
+///   the size of `dest` in bytes.
+#[inline]
+#[cfg_attr(feature = "track_caller", track_caller)]
+pub fn copy_u8_src<T>(
+  src: &[u8], dest: VolRegion<T, Safe, Safe>, byte_offset: usize,
+) {
+  assert!(dest.as_usize() & 1 == 0, "dest must be aligned to 2");
+  assert!(
+    byte_offset
+      .checked_add(src.len())
+      .is_some_and(|end| end <= dest.len() * size_of::<T>()),
+    "the copy doesn't fit in dest"
+  );
+  let mut src = src;
+  let mut addr = dest.as_usize() + byte_offset;
+  // Safety: the asserts mean every halfword (and word) written below is in
+  // `dest`, which is aligned to 2.
+  unsafe {
+    if addr & 1 != 0 {
+      let Some((&byte, rest)) = src.split_first() else { return };
+      let p = (addr - 1) as *mut u16;
+      p.write_volatile((p.read_volatile() & 0x00FF) | (u16::from(byte) << 8));
+      src = rest;
+      addr += 1;
+    }
+    if addr & 2 != 0 && src.len() >= 2 {
+      (addr as *mut u16).write_volatile(u16::from_le_bytes([src[0], src[1]]));
+      src = &src[2..];
+      addr += 2;
+    }
+    let mut words = src.chunks_exact(4);
+    for w in &mut words {
+      (addr as *mut u32)
+        .write_volatile(u32::from_le_bytes([w[0], w[1], w[2], w[3]]));
+      addr += 4;
+    }
+    src = words.remainder();
+    if src.len() >= 2 {
+      (addr as *mut u16).write_volatile(u16::from_le_bytes([src[0], src[1]]));
+      src = &src[2..];
+      addr += 2;
+    }
+    if let [byte] = *src {
+      let p = addr as *mut u16;
+      p.write_volatile((p.read_volatile() & 0xFF00) | u16::from(byte));
+    }
+  }
+}
@@ -0,0 +1,205 @@
+//! Module for the cartridge wait states.
+//!
+//! Every access to the cart (ROM, and the SRAM or flash) takes some number of
+//! wait cycles, set in [`WAITCNT`](crate::mmio::WAITCNT). The ROM is mapped
+//! three times, at `0x0800_0000`, `0x0A00_0000`, and `0x0C00_0000`, and each
+//! of those regions has its own setting. The *first* access is one that isn't
+//! right after the last address, and a *second* access is one that is (such as
+//! the next instruction, most of the time).
+//!
+//! Faster settings are a big speed up for code that runs from the ROM, and
+//! the prefetch buffer (which reads ahead while the CPU is busy with other
+//! things) helps even more. But a cart that can't keep up gives garbage, which
+//! usually crashes the game right away. The assembly runtime sets
+//! [`WaitstateControl::fast_commercial_cart`] before `main`.
+
+use crate::macros::{
+  pub_const_fn_new_zeroed, register_enum_field, u16_bool_field,
+};
+
+/// How many wait cycles an access takes.
+///
+/// This is used for the SRAM wait, and for the first access to each ROM
+/// region.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum WaitCycles {
+  #[default]
+  _4 = 0,
+  _3 = 1,
+  _2 = 2,
+  _8 = 3,
+}
+
+/// How many wait cycles a second access to a ROM region takes.
+///
+/// | Region | `Slow` | `Fast` |
+/// |:-|:-:|:-:|
+/// | 0 | 2 | 1 |
+/// | 1 | 4 | 1 |
+/// | 2 | 8 | 1 |
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum SecondAccess {
+  #[default]
+  Slow = 0,
+  Fast = 1,
+}
+
+/// The clock signal that's put out on the cart's PHI pin.
+///
+/// Nothing needs this except some special hardware, so leave it off.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum PhiOutput {
+  #[default]
+  Disabled = 0,
+  /// 4.19 MHz.
+  _4MHz = 1,
+  /// 8.38 MHz.
+  _8MHz = 2,
+  /// 16.78 MHz.
+  _16MHz = 3,
+}
+
+/// Wait state control.
+///
+/// * `sram`: The wait for each SRAM (or flash) access. The SRAM and flash
+///   drivers in [`save`](crate::save) set this to 8 cycles themselves.
+/// * `ws0_first`, `ws0_second`: Region 0, at `0x0800_0000`, which is where the
+///   game runs from.
+/// * `ws1_first`, `ws1_second`: Region 1, at `0x0A00_0000`.
+/// * `ws2_first`, `ws2_second`: Region 2, at `0x0C00_0000`, which is also where
+///   an EEPROM is.
+/// * `phi`: The [`PhiOutput`].
+/// * `prefetch`: If the prefetch buffer is on.
+/// * `cgb_cart`: If the cart in the slot is a Game Boy (Color) cart. This is
+///   read only.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct WaitstateControl(u16);
+impl WaitstateControl {
+  pub_const_fn_new_zeroed!();
+  register_enum_field!(
+    value 0 - 1: WaitCycles [_4, _3, _2, _8],
+    sram, with_sram, SRAM_MASK
+  );
+  register_enum_field!(
+    value 2 - 3: WaitCycles [_4, _3, _2, _8],
+    ws0_first, with_ws0_first, WS0_FIRST_MASK
+  );
+  register_enum_field!(
+    value 4 - 4: SecondAccess [Slow, Fast],
+    ws0_second, with_ws0_second, WS0_SECOND_MASK
+  );
+  register_enum_field!(
+    value 5 - 6: WaitCycles [_4, _3, _2, _8],
+    ws1_first, with_ws1_first, WS1_FIRST_MASK
+  );
+  register_enum_field!(
+    value 7 - 7: SecondAccess [Slow, Fast],
+    ws1_second, with_ws1_second, WS1_SECOND_MASK
+  );
+  register_enum_field!(
+    value 8 - 9: WaitCycles [_4, _3, _2, _8],
+    ws2_first, with_ws2_first, WS2_FIRST_MASK
+  );
+  register_enum_field!(
+    value 10 - 10: SecondAccess [Slow, Fast],
+    ws2_second, with_ws2_second, WS2_SECOND_MASK
+  );
+  register_enum_field!(
+    value 11 - 12: PhiOutput [Disabled, _4MHz, _8MHz, _16MHz],
+    phi, with_phi, PHI_MASK
+  );
+  u16_bool_field!(14, prefetch, with_prefetch);
+  u16_bool_field!(15, cgb_cart, with_cgb_cart);
+
+  /// The setting the GBA starts with: 4 wait cycles for everything (2 for the
+  /// second accesses to region 0), and no prefetch.
+  ///
+  /// Every cart works with this.
+  #[inline]
+  #[must_use]
+  pub const fn default_hardware() -> Self {
+    Self::new()
+  }
+
+  /// The setting almost every commercial game uses: 3,1 wait cycles for region
+  /// 0, 4,4 for region 1, 8,8 for region 2, 8 for the SRAM, and the prefetch
+  /// buffer on.
+  ///
+  /// Region 0 is the one that matters, since the game runs from it, so this is
+  /// about as fast as the ROM can go. All official carts handle it, and so do
+  /// most flash carts, but some cheaper or older flash carts (ones that load
+  /// the game into slow PSRAM) can't. On those the game crashes right away, and
+  /// [`default_hardware`](Self::default_hardware) with the prefetch buffer on
+  /// is the fastest safe setting.
+  #[inline]
+  #[must_use]
+  pub const fn fast_commercial_cart() -> Self {
+    Self::new()
+      .with_sram(WaitCycles::_8)
+      .with_ws0_first(WaitCycles::_3)
+      .with_ws0_second(SecondAccess::Fast)
+      .with_ws1_first(WaitCycles::_4)
+      .with_ws1_second(SecondAccess::Slow)
+      .with_ws2_first(WaitCycles::_8)
+      .with_ws2_second(SecondAccess::Slow)
+      .with_prefetch(true)
+  }
+
+  /// Unwrap this value into its raw `u16` form.
+  #[inline]
+  #[must_use]
+  pub const fn to_u16(self) -> u16 {
+    self.0
+  }
+}
+
+/// Changes [`WAITCNT`](crate::mmio::WAITCNT), and puts the old setting back
+/// when it's dropped.
+///
+/// ```no_run
+/// # use gba::prelude::*;
+/// let slow = WAITCNT.read().with_ws2_first(WaitCycles::_8);
+/// // Safety: region 2 isn't being used for anything else right now.
+/// let _guard = unsafe { WaitstateGuard::new(slow) };
+/// // ... access the hardware in region 2 ...
+/// ```
+#[cfg(feature = "on_gba")]
+#[derive(Debug)]
+pub struct WaitstateGuard {
+  old: WaitstateControl,
+}
+#[cfg(feature = "on_gba")]
+impl WaitstateGuard {
+  /// Sets the wait states to `setting`, until the guard is dropped.
+  ///
+  /// ## Safety
+  /// * `setting` has to work for the cart, at least for as long as the guard is
+  ///   around (see [`WAITCNT`](crate::mmio::WAITCNT)).
+  #[inline]
+  #[must_use]
+  pub unsafe fn new(setting: WaitstateControl) -> Self {
+    use crate::mmio::WAITCNT;
+    let old = WAITCNT.read();
+    WAITCNT.write(setting);
+    Self { old }
+  }
+
+  /// The setting that's put back when the guard is dropped.
+  #[inline]
+  #[must_use]
+  pub const fn old(&self) -> WaitstateControl {
+    self.old
+  }
+}
+#[cfg(feature = "on_gba")]
+impl Drop for WaitstateGuard {
+  #[inline]
+  fn drop(&mut self) {
+    // Safety: this was the setting before.
+    unsafe { crate::mmio::WAITCNT.write(self.old) };
+  }
+}
@@ -0,0 +1,3806 @@
+#![no_std]
+#![no_main]
+#![feature(custom_test_frameworks)]
+#![test_runner(gba::test_runner::runner)]
+#![reexport_test_harness_main = "test_main"]
+
+//! ROM tests for the crate, run with `cargo test-rom`.
+//!
+//! See the [`test_runner`](gba::test_runner) module for how these run, and
+//! how to get a pass or fail out of mGBA.
+
+use core::{cell::Cell, mem::size_of, ptr::addr_of_mut};
+use gba::{
+  arena::Arena,
+  bios::{
+    cpu_copy16, cpu_copy32, cpu_fast_copy, cpu_fast_fill, cpu_fill16,
+    cpu_fill32, diff_16bit_unfiltered_len, diff_8bit_unfiltered_len,
+    huff_decompressed_len, lz77_decompressed_len, midi_key_to_freq,
+    rl_decompressed_len, ArcTan, ArcTan2, DecompressError, Div, DivArm,
+    DivOutput, Sqrt, WaveData,
+  },
+  builtin_art::CGA_8X8_THICK,
+  collections::{ArrayString, ArrayVec, RingDeque},
+  debug_log::{debug_backend, set_debug_backend, DebugBackend},
+  dma::{
+    dma3_copy_u16_slice, dma3_copy_u32_slice, dma3_fill_u32_slice,
+    DestAddrControl, DmaControl, DmaStartTime, SrcAddrControl,
+  },
+  environment::{detect, is_mgba, is_nocash, Environment},
+  fixed::{i16fx14, i16fx8, i32fx16, i32fx8},
+  interrupts::{irq_free, IrqBits, IrqMutex},
+  keys::{
+    arm_keypad_interrupt, chord_held,
+    replay::{InputPlayback, InputRecorder},
+    Combo, Key, KeyControl, KeyInput, KeyRepeat, KeyTracker, SocdPolicy,
+    TriBool,
+  },
+  math::{
+    collide::*,
+    cos,
+    interp::{
+      ease_in_out_cubic, ease_in_quad, ease_out_quad, inverse_lerp, lerp,
+      lerp_unclamped, remap, smoothstep, Easing, Tween, ONE,
+    },
+    sin, Rect, Vec2,
+  },
+  mem::{
+    copy_halfwords_with, copy_words_with, in_video_memory, COPY_DMA_MIN_WORDS,
+    COPY_FAST_SET_MIN_WORDS,
+  },
+  mmio::{
+    charblock_4bpp, charblock_8bpp, text_screenblock, AFFINE_PARAM_A,
+    AFFINE_PARAM_B, AFFINE_PARAM_D, BG3CNT, BG3VOFS, BG_CONTROL, BG_HOFS,
+    BG_PALETTE, BG_VOFS, BLDALPHA, BLDCNT, DISPCNT, DISPSTAT, DMA1_COUNT,
+    DMA3_CONTROL, DMA3_DEST, DMA3_SRC, DMA_CONTROL, DMA_COUNT, DMA_DEST,
+    DMA_SRC, GREEN_SWAP, IE, IME, KEYCNT, LEFT_RIGHT_VOLUME, OBJ_ATTR0,
+    OBJ_ATTR2, OBJ_ATTR_ALL, OBJ_PALETTE, OBJ_TILES, RCNT, SOUND_ENABLED,
+    SOUND_MIX, TIMER2_CONTROL, TIMER_CONTROL, TIMER_COUNT, TIMER_RELOAD,
+    VCOUNT, VIDEO3_VRAM, VIDEO4_VRAM,
+  },
+  pacing::FramePacer,
+  perf::{Counters, FrameStats, CYCLES_PER_FRAME},
+  random::{Gen32, KeypressSeeder, Lcg32, Xoshiro128},
+  rom::{Header, HeaderError, MultibootHeader},
+  save::{
+    mem::MemoryMedia,
+    migrate::{MigrateError, SaveSchema},
+    SaveError, SaveMemory, SLOT_HEADER_LEN, SLOT_LEN,
+  },
+  scheduler::Scheduler,
+  sio::{
+    gpio::{enter_gpio_mode, Pin, PinDirections},
+    LinkPortControl, PortMode,
+  },
+  sound::{
+    dmg_stereo_defaults,
+    mixer::{Mixer, Voice},
+    noise::{
+      buzz, metallic, white_noise, CounterWidth, DividingRatio, NoiseSettings,
+    },
+    note_to_sample_rate,
+    stream::{StreamConfig, StreamRate, MAX_STREAM_SAMPLES},
+    tone::{play_tone2, rate_from_hz, stop_tone2, Duty, Envelope, Note},
+    LeftRightVolume, NoiseFrequency, NoiseLenEnvelope, PsgMix, SoundMix,
+    ToneFrequency, TonePattern,
+  },
+  test_runner::TimedTest,
+  time::FrameInstant,
+  timers::{
+    busy_wait_cycles, delay_ms, ms_to_cycles, periodic_irq, Timer,
+    TimerControl, TimerScale, UnreachableRate, WideTimer,
+    CPU_CYCLES_PER_SECOND,
+  },
+  video::{
+    animation::{Animation, AnimationPlayer},
+    camera::{StreamStrips, TiledCamera},
+    copy_tiles_4bpp, copy_tiles_8bpp, disable_green_swap, enable_green_swap,
+    fill_text_region, init_vblank_irq,
+    mode3::{self, BitmapConsole},
+    mode4,
+    obj::{
+      copy_obj_4bpp, hide_objects, init_oam, AffineMatrix, OamShadow, ObjAttr,
+      ObjAttr0, ObjAttr1, ObjDisplayStyle, ObjEffectMode, ObjShape, ObjSize,
+      ObjTileMapping,
+    },
+    palram::{
+      fade::{fade_between, FadeToColor, PaletteSnapshot},
+      raster_palette::{RasterEntry, RasterPalette},
+      write_banks_8bpp, PalBank, PalBankAllocator, Palette,
+    },
+    screenblocks_used_by_tiles, spin_until_scanline, spin_until_vblank,
+    sprite_alloc::SpriteAllocator,
+    sprite_sheet::SpriteSheet,
+    sprite_sort::SpriteSorter,
+    text_entry_index, tile4_flip_h, tile4_flip_v, tile4_from_bytes,
+    tile8_flip_h, tile8_flip_v, tile8_from_bytes, tile8_offset_indexes,
+    tile_alloc::{TileAllocator, TileIndex},
+    tilemap::{load_region, MapSource, Metatile, MetatileMap, TileGrid},
+    vram, wait_for_vblank, with_forced_blank, AffineBackgroundSize,
+    AffineScreenblock, BackgroundControl, BgLayer, BgScroll, BitmapPage,
+    BlendAlpha, BlendControl, Color, ColorEffectMode, DisplayControl, Mosaic,
+    TextBackgroundSize, TextEntry, Tile4, Tile8, VideoConfig, VideoMode,
+    WindowInside,
+  },
+  waitstate::{SecondAccess, WaitCycles, WaitstateControl},
+  Align4,
+};
+use voladdress::{Safe, VolAddress, VolRegion};
+
+#[cfg(not(feature = "panic_handler"))]
+#[panic_handler]
+fn panic_handler(info: &core::panic::PanicInfo) -> ! {
+  gba::test_runner::test_panic_handler(info)
+}
+
+#[no_mangle]
+extern "C" fn main() -> ! {
+  test_main();
+  unreachable!()
+}
+
+#[test_case]
+fn keys_are_low_active() {
+  let none = KeyInput::new();
+  assert_eq!(none.to_u16(), 0xFFFF);
+  assert!(!none.a() && !none.any());
+
+  let a = none.with_a(true);
+  assert_eq!(a.to_u16() & 0x3FF, 0x3FE);
+  assert!(a.a() && !a.b());
+  assert_eq!(a.with_a(false), none);
+}
+
+#[test_case]
+fn key_sets() {
+  let ab = KeyInput::new().with_a(true).union(KeyInput::new().with_b(true));
+  assert_eq!(ab, KeyInput::from_keys(&[Key::A, Key::B]));
+  assert_eq!(ab.count(), 2);
+  assert!(ab.contains(KeyInput::new().with_b(true)));
+  assert_eq!(ab.intersection(KeyInput::new().with_b(true)).count(), 1);
+
+  let mut pressed = ab.union(KeyInput::new().with_l(true)).iter_pressed();
+  assert_eq!(pressed.next(), Some(Key::A));
+  assert_eq!(pressed.next(), Some(Key::B));
+  assert_eq!(pressed.next(), Some(Key::L));
+  assert_eq!(pressed.next(), None);
+}
+
+#[test_case]
+fn fixed_mul_and_div() {
+  let a = i32fx8::from_f32(1.5);
+  let b = i32fx8::from_int(-3);
+  assert_eq!(a.mul(b), i32fx8::from_f32(-4.5));
+  assert_eq!(b.div(a), i32fx8::from_int(-2));
+  assert_eq!(a.mul(a).to_f32(), 2.25);
+  // multiplying rounds down, like a shift.
+  let tiny = i32fx8::from_bits(1);
+  assert_eq!(tiny.mul(i32fx8::from_f32(0.5)).to_bits(), 0);
+  assert_eq!(tiny.neg().mul(i32fx8::from_f32(0.5)).to_bits(), -1);
+
+  let half = i16fx8::from_f32(0.5);
+  assert_eq!(half.add(half), i16fx8::from_int(1));
+  assert_eq!(i16fx8::from_int(7).int(), 7);
+}
+
+#[test_case]
+fn dma3_copies_and_fills() {
+  let src: [u32; 16] = core::array::from_fn(|i| (i as u32) * 0x0101_0101);
+  let mut dest = [0_u32; 16];
+  dma3_copy_u32_slice(&src, &mut dest);
+  assert_eq!(src, dest);
+
+  dma3_fill_u32_slice(&mut dest, 0xDEAD_BEEF);
+  assert!(dest.iter().all(|&word| word == 0xDEAD_BEEF));
+}
+
+#[test_case]
+fn gba_asserts_that_pass() {
+  let mut evaluated = 0;
+  gba::gba_assert!({
+    evaluated += 1;
+    true
+  });
+  gba::gba_assert!(evaluated == 1, "evaluated {evaluated} times");
+  gba::gba_debug_assert!(cfg!(debug_assertions), "only checked in debug");
+}
+
+#[test_case]
+fn included_data_is_aligned() {
+  // 14 bytes, which is a whole number of `u16`, but not of `u32`.
+  static TEXT: &[u16] = gba::include_u16_data!("../examples/foo.txt");
+  assert_eq!(TEXT.len(), 7);
+  assert!(TEXT.as_ptr().cast::<u32>().is_aligned());
+  assert_eq!(TEXT[0], u16::from_le_bytes(*b"us"));
+
+  const ARROW: &[Tile4] =
+    gba::include_tiles_4bpp!("../examples/assets/arrow.4bpp");
+  let bytes = gba::include_aligned_bytes!("../examples/assets/arrow.4bpp");
+  assert_eq!(ARROW, &[tile4_from_bytes(bytes.0)]);
+  let words = gba::include_u32_data!("../examples/assets/arrow.4bpp");
+  assert_eq!(ARROW[0].as_slice(), words);
+
+  let palette = gba::include_palette!("../examples/assets/arrow.pal");
+  assert_eq!(palette.len(), 16);
+  assert_eq!(&palette[..2], &[Color::BLACK, Color::WHITE]);
+}
+
+#[test_case]
+fn rom_header_layout_and_complement() {
+  assert_eq!(core::mem::size_of::<Header>(), 192);
+  assert_eq!(core::mem::size_of::<MultibootHeader>(), 0xE4);
+
+  // `gbafix -tGBAEXAMPLE -cAGBE -m01 -r1` gives a complement of 0x0A.
+  let header = Header::new()
+    .with_entry_branch(0xC0)
+    .with_title("GBAEXAMPLE")
+    .with_game_code("AGBE")
+    .with_maker_code("01")
+    .with_software_version(1);
+  assert_eq!(header.complement(), 0x0A);
+  assert_eq!(header.validate(), Ok(()));
+  let bytes = header.to_bytes();
+  assert_eq!(&bytes[..4], &[0x2E, 0x00, 0x00, 0xEA]);
+  assert_eq!(&bytes[0xA0..0xAC], b"GBAEXAMPLE\0\0");
+  assert_eq!((bytes[0xB2], bytes[0xBC], bytes[0xBD]), (0x96, 1, 0x0A));
+  assert_eq!(Header::from_bytes(&bytes), header);
+
+  let mut broken = bytes;
+  broken[0xA0] = b'X';
+  assert_eq!(
+    Header::from_bytes(&broken).validate(),
+    Err(HeaderError::BadComplement { expected: 0xF9, found: 0x0A })
+  );
+  broken[4] ^= 1;
+  assert_eq!(Header::from_bytes(&broken).validate(), Err(HeaderError::BadLogo));
+
+  // the crate's own header, at the start of this ROM (which is always
+  // readable).
+  let rom = unsafe { &*(0x0800_0000 as *const [u8; 192]) };
+  assert_eq!(Header::from_bytes(rom).validate(), Ok(()));
+}
+
+#[test_case]
+fn color_conversion_and_blending() {
+  assert_eq!(Color::from_rgb8(0, 0, 0), Color::BLACK);
+  assert_eq!(Color::from_rgb8(255, 255, 255), Color::WHITE);
+  assert_eq!(Color::from_rgb8(0xFF, 0x80, 0), Color::ORANGE);
+  // rounds to the nearest step: 4 is just under half a step, 5 just over.
+  assert_eq!(Color::from_rgb8(4, 5, 8).0, Color::from_rgb(0, 1, 1).0);
+  assert_eq!(Color::from_rgb8(251, 250, 247), Color::from_rgb(31, 30, 30));
+
+  let a = Color::from_rgb(31, 7, 0);
+  let b = Color::from_rgb(0, 20, 31);
+  assert_eq!(a.blend(b, 0), a);
+  assert_eq!(a.blend(b, 16), b);
+  assert_eq!(a.blend(b, 99), b);
+  assert_eq!(a.blend(b, 8), Color::from_rgb(16, 14, 16));
+  assert_eq!(Color::WHITE.blend(Color::WHITE, 5), Color::WHITE);
+  assert_eq!(Color::BLACK.blend(Color::WHITE, 8), Color::GRAY);
+
+  assert_eq!(Color::WHITE.luminance(), 31);
+  assert_eq!(Color::BLACK.luminance(), 0);
+  assert!(Color::GREEN.luminance() > Color::RED.luminance());
+  assert!(Color::RED.luminance() > Color::BLUE.luminance());
+  assert_eq!(Color::GRAY.grayscale(), Color::GRAY);
+  assert_eq!(Color::RED.grayscale(), Color::from_rgb(9, 9, 9));
+}
+
+#[test_case]
+fn palette_fades_match_color_blend() {
+  let bank = PalBank::new(Palette::Obj, 15);
+  let colors: [Color; 16] =
+    core::array::from_fn(|i| Color::from_rgb(i as u16 * 2, 31 - i as u16, 7));
+  let write = |colors: &[Color; 16]| {
+    for (addr, color) in bank.colors().iter().zip(colors) {
+      addr.write(*color);
+    }
+  };
+  let read = || -> [Color; 16] {
+    core::array::from_fn(|i| bank.colors().index(i).read())
+  };
+  write(&colors);
+
+  let mut snapshot = PaletteSnapshot::new();
+  let mut fade = FadeToColor::new(&mut snapshot, bank.range(), Color::WHITE, 3);
+  assert_eq!(read(), colors, "nothing is written until the first step");
+  assert!(!fade.step());
+  assert_eq!(fade.weight(), 5);
+  assert_eq!(read(), colors.map(|c| c.blend(Color::WHITE, 5)));
+  assert!(!fade.step());
+  assert!(fade.step());
+  assert_eq!(read(), [Color::WHITE; 16]);
+  assert!(fade.step(), "stays done");
+  assert!(!fade.step_back());
+  assert_eq!(read(), colors.map(|c| c.blend(Color::WHITE, 11)));
+  fade.cancel();
+  assert_eq!(read(), colors);
+
+  let mut a = PaletteSnapshot::new();
+  a.capture(bank.range());
+  let mut b = a.clone();
+  for color in b.bank_mut(bank) {
+    *color = color.grayscale();
+  }
+  let grays = colors.map(Color::grayscale);
+  for progress in 0..=16 {
+    fade_between(&a, &b, bank.range(), progress);
+    let expected: [Color; 16] =
+      core::array::from_fn(|i| colors[i].blend(grays[i], progress));
+    assert_eq!(read(), expected, "progress {progress}");
+  }
+  assert_eq!(read(), grays);
+  fade_between(&a, &b, bank.range(), 0);
+  assert_eq!(read(), colors);
+}
+
+#[test_case]
+fn oam_init_hides_objects_and_resets_affine_sets() {
+  fn read_oam() -> [u32; 256] {
+    let oam = 0x0700_0000 as *const u32;
+    core::array::from_fn(|i| unsafe { oam.add(i).read_volatile() })
+  }
+  fn scribble() {
+    dma3_fill_u32_slice(
+      unsafe { core::slice::from_raw_parts_mut(0x0700_0000 as *mut u32, 256) },
+      0x1234_5678,
+    );
+  }
+
+  scribble();
+  init_oam();
+  let initialized = read_oam();
+  assert!(OBJ_ATTR0.iter().all(|addr| addr.read() == ObjAttr::HIDDEN.0));
+  for slot in 0..32 {
+    assert_eq!(AFFINE_PARAM_A.index(slot).read(), i16fx8::from_int(1));
+    assert_eq!(AFFINE_PARAM_B.index(slot).read(), i16fx8::from_int(0));
+    assert_eq!(AFFINE_PARAM_D.index(slot).read(), i16fx8::from_int(1));
+  }
+  // entry 0 is attr0 (hidden) and attr1 in its first word, then attr2 and
+  // `pa` of affine set 0.
+  assert_eq!(&initialized[..2], &[0x0000_0200, 0x0100_0000]);
+
+  scribble();
+  OamShadow::new().commit();
+  assert_eq!(read_oam(), initialized);
+  assert_eq!(OamShadow::new(), OamShadow::default());
+
+  OBJ_ATTR0.iter().for_each(|addr| addr.write(ObjAttr::new().0));
+  hide_objects(10, 5);
+  for (i, addr) in OBJ_ATTR0.iter().enumerate() {
+    assert_eq!(addr.read() == ObjAttr::HIDDEN.0, (10..15).contains(&i));
+  }
+  let mut shadow = OamShadow::new();
+  *shadow.obj_mut(3) = ObjAttr::new();
+  *shadow.obj_mut(4) = ObjAttr::new();
+  shadow.hide_range(4, 124);
+  assert_eq!(shadow.obj(3), &ObjAttr::new());
+  assert_eq!(shadow.obj(4), &ObjAttr::HIDDEN);
+  init_oam();
+}
+
+#[test_case]
+fn forced_blank_scope_ends_in_vblank() {
+  let inside = with_forced_blank(|| {
+    assert!(VCOUNT.read() >= 160, "forced blank starts in vblank");
+    DISPCNT.read().forced_blank()
+  });
+  assert!(inside);
+  assert!(!DISPCNT.read().forced_blank());
+  assert!(VCOUNT.read() >= 160, "forced blank ends in vblank");
+
+  enable_green_swap();
+  assert!(GREEN_SWAP.read());
+  disable_green_swap();
+  assert!(!GREEN_SWAP.read());
+}
+
+#[test_case]
+fn translucent_objects_set_up_blending() {
+  let alpha = BlendAlpha::from_coefficients(10, 6);
+  let mut shadow = OamShadow::new();
+  *shadow.obj_mut(0) = ObjAttr::new();
+  BLDCNT.write(BlendControl::new());
+  shadow.set_translucent(0, alpha);
+  assert_eq!(shadow.obj(0).0.mode(), ObjEffectMode::SemiTransparent);
+  // target1 and the mode are left alone, the object blends either way.
+  let bldcnt = BLDCNT.read();
+  assert_eq!(
+    bldcnt,
+    BlendControl::new().with_target2_all(true).with_target2_obj(false)
+  );
+  assert_eq!(bldcnt.mode(), ColorEffectMode::NoEffect);
+
+  // target2 layers that are already picked are kept.
+  let only_bg3 = BlendControl::new()
+    .with_target2_bg3(true)
+    .with_mode(ColorEffectMode::Brighten);
+  BLDCNT.write(only_bg3);
+  shadow.set_translucent(1, alpha);
+  assert_eq!(BLDCNT.read(), only_bg3);
+  BLDCNT.write(BlendControl::new());
+  BLDALPHA.write(BlendAlpha::new());
+
+  shadow.set_obj_window(0);
+  assert_eq!(shadow.obj(0).0.mode(), ObjEffectMode::Window);
+  shadow.set_mosaic(0, true);
+  assert!(shadow.obj(0).0.mosaic());
+  shadow.clear_effect(0);
+  assert_eq!(shadow.obj(0).0, ObjAttr::new().0.with_mosaic(true));
+}
+
+#[test_case]
+fn camera_strips_cover_what_comes_into_view() {
+  let strips = |column, row| StreamStrips { column, row, redraw: false };
+  // the view is 30 (or 31) columns and 20 (or 21) rows.
+  assert_eq!(StreamStrips::between((0, 0), (1, 0)), strips(Some(30), None));
+  assert_eq!(StreamStrips::between((0, 0), (0, 1)), strips(None, Some(20)));
+  assert_eq!(StreamStrips::between((8, 8), (7, 7)), strips(Some(0), Some(0)));
+  assert_eq!(StreamStrips::between((7, 7), (8, 8)), StreamStrips::NONE);
+  assert_eq!(StreamStrips::between((8, 0), (0, 0)), strips(Some(0), None));
+  assert_eq!(StreamStrips::between((0, 0), (16, 0)), StreamStrips::REDRAW);
+  assert_eq!(StreamStrips::between((0, 40), (1, 0)), StreamStrips::REDRAW);
+  assert_eq!(strips(Some(1), Some(1)).max_entries(), 31 + 21);
+  assert_eq!(StreamStrips::REDRAW.max_entries(), 31 * 21);
+
+  const W: usize = 40;
+  const H: usize = 30;
+  let mut map = [TextEntry::new(); W * H];
+  for (i, entry) in map.iter_mut().enumerate() {
+    *entry = TextEntry::from(((i / W) << 6 | (i % W)) as u16);
+  }
+  let tsb = text_screenblock(30);
+  let check = |columns: core::ops::RangeInclusive<usize>,
+               rows: core::ops::RangeInclusive<usize>| {
+    for y in rows {
+      for x in columns.clone() {
+        let entry = tsb.index(x % 32, y % 32).read();
+        assert_eq!(entry, map[y * W + x], "({x}, {y})");
+      }
+    }
+  };
+  let size = TextBackgroundSize::_32x32;
+  let mut camera =
+    TiledCamera::new(TileGrid::new(&map, W, H), BgLayer::Bg3, 30, size);
+  assert_eq!(camera.update(7, 7), StreamStrips::REDRAW);
+  check(0..=30, 0..=20);
+  // diagonally across both a column and a row boundary.
+  assert_eq!(camera.update(9, 9), strips(Some(31), Some(21)));
+  check(1..=31, 1..=21);
+  // clamped to the map.
+  camera.update(1000, 1000);
+  assert_eq!(camera.position(), camera.max_position());
+  assert_eq!(camera.max_position(), (W * 8 - 240, H * 8 - 160));
+  check(10..=39, 10..=29);
+}
+
+#[test_case]
+fn metatile_maps_expand_and_look_up() {
+  const SOLID: u8 = 1;
+  const fn quad(first: u16, flags: u8) -> Metatile<2> {
+    let e = TextEntry::from_tile;
+    Metatile::new(
+      [[e(first), e(first + 1)], [e(first + 2), e(first + 3)]],
+      flags,
+    )
+  }
+  static METATILES: [Metatile<2>; 3] = [quad(0, 0), quad(4, SOLID), quad(8, 0)];
+  // 3 by 2 metatiles.
+  static GRID: [u16; 6] = [0, 1, 2, 2, 1, 0];
+  const LEVEL: MetatileMap<'static, 2> =
+    MetatileMap::new(&[], &METATILES, &GRID, 3, 2);
+
+  assert_eq!(LEVEL.size(), (6, 4));
+  assert_eq!(LEVEL.size_in_metatiles(), (3, 2));
+  let tiles: [[u16; 6]; 4] = core::array::from_fn(|y| {
+    core::array::from_fn(|x| LEVEL.entry(x, y).tile())
+  });
+  assert_eq!(
+    tiles,
+    [
+      [0, 1, 4, 5, 8, 9],
+      [2, 3, 6, 7, 10, 11],
+      [8, 9, 4, 5, 0, 1],
+      [10, 11, 6, 7, 2, 3],
+    ]
+  );
+
+  assert_eq!(LEVEL.metatile_index(2, 1), Some(0));
+  assert_eq!(LEVEL.metatile_index(3, 0), None);
+  // metatiles are 16 pixels across.
+  assert_eq!(LEVEL.flags_at(16, 0), SOLID);
+  assert_eq!(LEVEL.flags_at(31, 15), SOLID);
+  assert_eq!(LEVEL.flags_at(32, 15), 0);
+  assert_eq!(LEVEL.flags_at(15, 16), 0);
+  assert_eq!(LEVEL.flags_at(16, 31), SOLID);
+  assert_eq!(LEVEL.flags_at(-1, 0), 0);
+  assert_eq!(LEVEL.flags_at(0, 32), 0);
+  assert_eq!(LEVEL.metatile_at(40, 20), Some(&METATILES[0]));
+
+  // a raw grid and a metatile map of the same tiles load the same.
+  let flat: [TextEntry; 24] =
+    core::array::from_fn(|i| TextEntry::from_tile(tiles[i / 6][i % 6]));
+  let grid = TileGrid::new(&flat, 6, 4);
+  let size = TextBackgroundSize::_64x32;
+  let tsb = text_screenblock(28);
+  let read = || -> [[u16; 3]; 2] {
+    core::array::from_fn(|y| {
+      core::array::from_fn(|x| tsb.index(x, y).read().tile())
+    })
+  };
+  load_region(&LEVEL, (3, 1), (3, 2), 28, size);
+  let from_metatiles = read();
+  assert_eq!(from_metatiles, [[7, 10, 11], [5, 0, 1]]);
+  load_region(&grid, (3, 1), (3, 2), 28, size);
+  assert_eq!(read(), from_metatiles);
+}
+
+#[test_case]
+fn bpp8_tiles_slots_and_palettes() {
+  let tile: Tile8 = core::array::from_fn(|i| 0x0300_0100 * (i as u32 & 1));
+  let moved = tile8_offset_indexes(tile, 16);
+  assert_eq!(moved[0], 0);
+  assert_eq!(moved[1], 0x1300_1100);
+  assert_eq!(tile8_offset_indexes([0xFF01_0000; 16], 2)[0], 0x0103_0000);
+
+  assert_eq!(ObjSize::_16x16.tile_slots(false), 4);
+  assert_eq!(ObjSize::_16x16.tile_slots(true), 8);
+  let mut slots = TileAllocator::new_obj();
+  let first = slots.alloc_obj(ObjSize::_8x8, false).unwrap();
+  let second = slots.alloc_obj(ObjSize::_16x16, true).unwrap();
+  assert_eq!((first.0, second.0), (0, 2));
+  assert_eq!(slots.alloc(1).unwrap().0, 1);
+  slots.free_obj(second, ObjSize::_16x16, true);
+  assert_eq!(slots.free_slots(), 1024 - 2);
+
+  let mut obj = ObjAttr::new().with_bpp8(true);
+  obj.set_tile_8bpp(5);
+  assert!(obj.0.bpp8());
+  assert_eq!(obj.2.tile_id(), 10);
+
+  let mut banks = PalBankAllocator::new(Palette::Obj);
+  assert_eq!(banks.alloc_bank().unwrap().index(), 0);
+  let run = banks.alloc_banks(3).unwrap();
+  assert_eq!(run.index(), 1);
+  assert!(banks.alloc_banks(16).is_none());
+  assert_eq!(banks.alloc_banks(12).unwrap().index(), 4);
+  assert!(banks.alloc_banks(1).is_none());
+  banks.free_banks(run, 3);
+  assert_eq!(banks.alloc_banks(2).unwrap().index(), 1);
+  assert!(banks.alloc_banks(0).is_none());
+
+  // index 0 of a bank is a real color for 8bpp, except at the very start.
+  let colors = [Color::RED, Color::GREEN, Color::BLUE];
+  let entries = Palette::Obj.entries();
+  entries.index(0).write(Color::WHITE);
+  write_banks_8bpp(PalBank::new(Palette::Obj, 0), &colors);
+  assert_eq!(entries.index(0).read(), Color::WHITE);
+  assert_eq!(entries.index(1).read(), Color::GREEN);
+  write_banks_8bpp(PalBank::new(Palette::Obj, 15), &colors);
+  assert_eq!(entries.index(240).read(), Color::RED);
+  assert_eq!(entries.index(242).read(), Color::BLUE);
+}
+
+#[test_case]
+fn sprite_sorter_orders_by_priority_and_key() {
+  let mut sorter = SpriteSorter::new();
+  let low = sorter.add(ObjAttr::new(), 10).unwrap();
+  let high = sorter.add(ObjAttr::new(), 50).unwrap();
+  let mut behind = ObjAttr::new();
+  behind.2 = behind.2.with_priority(1);
+  let far = sorter.add(behind, 90).unwrap();
+  let tie = sorter.add(ObjAttr::new(), 10).unwrap();
+  assert_eq!(sorter.oam_index(low), None);
+  *sorter.oam_mut().affine_mut(0)[1] = 0x1234;
+
+  // a larger key is in front, but only within the same priority.
+  assert_eq!(sorter.sort(), 4);
+  let order = [high, low, tie, far].map(|h| sorter.oam_index(h).unwrap());
+  assert_eq!(order, [0, 1, 2, 3]);
+  assert_eq!(sorter.oam().obj(3).2.priority(), 1);
+  assert_eq!(*sorter.oam().obj(4), ObjAttr::HIDDEN);
+  assert_eq!(*sorter.oam_mut().affine_mut(0)[1], 0x1234);
+
+  // equal keys keep the order from the last sort.
+  sorter.set_key(tie, 50);
+  sorter.set_key(high, 10);
+  sorter.sort();
+  let order = [tie, high, low, far].map(|h| sorter.oam_index(h).unwrap());
+  assert_eq!(order, [0, 1, 2, 3]);
+
+  assert!(sorter.remove(low));
+  assert!(!sorter.remove(low));
+  assert!(sorter.get(low).is_none());
+  assert_eq!(sorter.sort(), 3);
+  assert_eq!(sorter.oam_index(far), Some(2));
+  assert_eq!(*sorter.oam().obj(3), ObjAttr::HIDDEN);
+  let reused = sorter.add(ObjAttr::new(), 0).unwrap();
+  assert!(!sorter.is_valid(low) && sorter.is_valid(reused));
+  sorter.clear();
+  assert!(sorter.is_empty() && !sorter.is_valid(tie));
+}
+
+#[test_case]
+fn register_blocks_line_up_with_the_registers() {
+  const fn addr<T, R, W>(a: gba::mmio::VolAddress<T, R, W>) -> usize {
+    a.as_usize()
+  }
+  const _: () = {
+    assert!(addr(BG_CONTROL.index(3)) == addr(BG3CNT));
+    assert!(addr(BG_HOFS.index(1)) == 0x0400_0014);
+    assert!(addr(BG_VOFS.index(3)) == addr(BG3VOFS));
+    assert!(addr(DMA_SRC.index(3)) == addr(DMA3_SRC));
+    assert!(addr(DMA_DEST.index(3)) == addr(DMA3_DEST));
+    assert!(addr(DMA_COUNT.index(1)) == addr(DMA1_COUNT));
+    assert!(addr(DMA_CONTROL.index(3)) == addr(DMA3_CONTROL));
+    assert!(addr(TIMER_COUNT.index(2)) == 0x0400_0108);
+    assert!(addr(TIMER_RELOAD.index(3)) == 0x0400_010C);
+    assert!(addr(TIMER_CONTROL.index(2)) == addr(TIMER2_CONTROL));
+    // the attributes of each object skip over its affine halfword.
+    assert!(addr(OBJ_ATTR2.index(1)) == 0x0700_000C);
+    assert!(addr(OBJ_ATTR_ALL.index(127)) == 0x0700_03F8);
+  };
+  assert!(BG_HOFS.get(4).is_none() && DMA_CONTROL.get(4).is_none());
+  assert_eq!(DMA_CONTROL.iter().last(), Some(DMA3_CONTROL));
+  assert_eq!(TIMER_CONTROL.iter().count(), 4);
+}
+
+/// Sets every variant into `base`, checking that it reads back and that
+/// setting the first variant again gives back the same value (so no other
+/// field was touched).
+fn round_trip<T, E>(
+  base: T, variants: &[E], with: fn(T, E) -> T, get: fn(T) -> E,
+) where
+  T: Copy + PartialEq + core::fmt::Debug,
+  E: Copy + PartialEq + core::fmt::Debug,
+{
+  let cleared = with(base, variants[0]);
+  for &variant in variants {
+    let set = with(base, variant);
+    assert_eq!(get(set), variant);
+    assert_eq!(with(set, variants[0]), cleared);
+  }
+}
+
+#[test_case]
+fn enum_fields_round_trip() {
+  use VideoMode::*;
+  let display =
+    DisplayControl::new().with_show_frame1(true).with_show_obj(true);
+  let modes = [_0, _1, _2, _3, _4, _5];
+  round_trip(display, &modes, DisplayControl::with_video_mode, |d| {
+    d.video_mode()
+  });
+
+  let bg =
+    BackgroundControl::new().with_priority(3).with_is_affine_wrapping(true);
+  let text_sizes = [
+    TextBackgroundSize::_32x32,
+    TextBackgroundSize::_64x32,
+    TextBackgroundSize::_32x64,
+    TextBackgroundSize::_64x64,
+  ];
+  round_trip(bg, &text_sizes, BackgroundControl::with_text_size, |b| {
+    b.text_size()
+  });
+  let affine_sizes = [
+    AffineBackgroundSize::_16x16,
+    AffineBackgroundSize::_32x32,
+    AffineBackgroundSize::_64x64,
+    AffineBackgroundSize::_128x128,
+  ];
+  round_trip(bg, &affine_sizes, BackgroundControl::with_affine_size, |b| {
+    b.affine_size()
+  });
+
+  let dma = DmaControl::new()
+    .with_repeat(true)
+    .with_transfer_32bit(true)
+    .with_enabled(true);
+  let dests = [
+    DestAddrControl::Increment,
+    DestAddrControl::Decrement,
+    DestAddrControl::Fixed,
+    DestAddrControl::IncReload,
+  ];
+  round_trip(dma, &dests, DmaControl::with_dest_addr_control, |d| {
+    d.dest_addr_control()
+  });
+  let srcs = [
+    SrcAddrControl::Increment,
+    SrcAddrControl::Decrement,
+    SrcAddrControl::Fixed,
+  ];
+  round_trip(dma, &srcs, DmaControl::with_src_addr_control, |d| {
+    d.src_addr_control()
+  });
+  let times = [
+    DmaStartTime::Immediate,
+    DmaStartTime::VBlank,
+    DmaStartTime::HBlank,
+    DmaStartTime::Special,
+  ];
+  round_trip(dma, &times, DmaControl::with_start_time, |d| d.start_time());
+
+  // fields using plain values, shifted into place.
+  let cycles = [WaitCycles::_4, WaitCycles::_3, WaitCycles::_2, WaitCycles::_8];
+  let wait = WaitstateControl::new().with_prefetch(true);
+  round_trip(wait, &cycles, WaitstateControl::with_ws1_first, |w| {
+    w.ws1_first()
+  });
+  let second = [SecondAccess::Slow, SecondAccess::Fast];
+  round_trip(wait, &second, WaitstateControl::with_ws2_second, |w| {
+    w.ws2_second()
+  });
+  assert_eq!(WaitstateControl::fast_commercial_cart().to_u16(), 0x4317);
+  let modes =
+    [PortMode::Sio, PortMode::SioAlt, PortMode::Gpio, PortMode::JoyBus];
+  let port = LinkPortControl::new().with_si_irq(true);
+  round_trip(port, &modes, LinkPortControl::with_mode, |p| p.mode());
+}
+
+#[test_case]
+fn field_masks_match_their_bits() {
+  assert_eq!(DisplayControl::VIDEO_MODE_MASK, 0b111);
+  assert_eq!(BackgroundControl::PRIORITY_MASK, 0b11);
+  assert_eq!(BackgroundControl::SCREENBLOCK_MASK, 0b1_1111 << 8);
+  assert_eq!(BackgroundControl::TEXT_SIZE_MASK, 0b11 << 14);
+  assert_eq!(DmaControl::DEST_ADDR_CONTROL_MASK, 0b11 << 5);
+  assert_eq!(DmaControl::SRC_ADDR_CONTROL_MASK, 0b11 << 7);
+  assert_eq!(DmaControl::START_TIME_MASK, 0b11 << 12);
+  assert_eq!(WaitstateControl::WS2_SECOND_MASK, 1 << 10);
+  assert_eq!(WaitstateControl::PHI_MASK, 0b11 << 11);
+  assert_eq!(LinkPortControl::MODE_MASK, 0b11 << 14);
+}
+
+#[test_case]
+fn int_fields_stay_in_their_bits() {
+  let bg = BackgroundControl::new()
+    .with_priority(2)
+    .with_charblock(1)
+    .with_screenblock(31);
+  assert_eq!(bg.priority(), 2);
+  assert_eq!(bg.charblock(), 1);
+  assert_eq!(bg.screenblock(), 31);
+  // too-big values are cut off rather than spilling into the next field.
+  let wide = bg.with_charblock(0b111);
+  assert_eq!(wide.charblock(), 0b11);
+  assert_eq!(wide.screenblock(), 31);
+  assert_eq!(wide.priority(), 2);
+}
+
+#[test_case]
+fn register_builders_are_const() {
+  // all of these are built at compile time.
+  const GAME_DISPCNT: DisplayControl = DisplayControl::new()
+    .with_video_mode(VideoMode::_0)
+    .with_show_bg0(true)
+    .with_show_obj(true)
+    .with_obj_vram_1d(true);
+  const BG0: BackgroundControl = BackgroundControl::new()
+    .with_charblock(1)
+    .with_screenblock(28)
+    .with_text_size(TextBackgroundSize::_64x32)
+    .with_priority(2);
+  const FADE: BlendControl = BlendControl::new()
+    .with_target1_all(true)
+    .with_mode(ColorEffectMode::Darken);
+  const HALF: BlendAlpha = BlendAlpha::new().with_eva(8).with_evb(8);
+  const WINDOWS: WindowInside =
+    WindowInside::new().with_win0_bg0(true).with_win1_obj(true);
+  const PIXELATED: Mosaic = Mosaic::new().with_bg_h(4).with_obj_v(2);
+  const HBLANK_DMA: DmaControl = DmaControl::new()
+    .with_dest_addr_control(DestAddrControl::IncReload)
+    .with_start_time(DmaStartTime::HBlank)
+    .with_repeat(true)
+    .with_enabled(true);
+  const TICKS: TimerControl =
+    TimerControl::new().with_scale(TimerScale::_64).with_enabled(true);
+  const IRQS: IrqBits = IrqBits::new().with_vblank(true).with_timer3(true);
+  const COMBO: KeyControl = KeyControl::new().with_a(true).with_start(true);
+  const HELD: KeyInput = KeyInput::new().with_a(true).with_b(true);
+  const ONLY_B: KeyInput = HELD.difference(KeyInput::new().with_a(true));
+  const WAIT: WaitstateControl = WaitstateControl::fast_commercial_cart()
+    .with_phi(gba::waitstate::PhiOutput::Disabled);
+  const ENTRY: TextEntry =
+    TextEntry::from_tile(3).with_hflip(true).with_palbank(2);
+  const SPRITE: ObjAttr = {
+    let mut obj = ObjAttr::new().with_size(ObjSize::_16x16);
+    obj.set_x(100);
+    obj.set_y(40);
+    obj.set_tile_8bpp(3);
+    obj.set_bpp8(true);
+    obj.set_palbank(1);
+    obj
+  };
+  const SCROLL: BgScroll = {
+    let mut scroll = BgScroll::new(0, 0);
+    scroll.scroll_by(-1, 520);
+    scroll
+  };
+
+  assert_eq!(GAME_DISPCNT.video_mode(), VideoMode::_0);
+  assert!(GAME_DISPCNT.show_bg0() && !GAME_DISPCNT.show_bg1());
+  assert_eq!((BG0.charblock(), BG0.screenblock(), BG0.priority()), (1, 28, 2));
+  assert_eq!(FADE.mode(), ColorEffectMode::Darken);
+  assert_eq!((HALF.eva(), HALF.evb()), (8, 8));
+  assert!(WINDOWS.win0_bg0() && WINDOWS.win1_obj() && !WINDOWS.win0_obj());
+  assert_eq!((PIXELATED.bg_h_extra(), PIXELATED.obj_v_extra()), (4, 2));
+  assert_eq!(HBLANK_DMA.start_time(), DmaStartTime::HBlank);
+  assert_eq!(TICKS.scale(), TimerScale::_64);
+  assert!(IRQS.vblank() && IRQS.timer3() && !IRQS.hblank());
+  assert!(COMBO.a() && COMBO.start() && !COMBO.b());
+  assert!(ONLY_B.b() && !ONLY_B.a());
+  assert_eq!(WAIT.to_u16(), 0x4317);
+  assert_eq!((ENTRY.tile(), ENTRY.palbank()), (3, 2));
+  assert_eq!((SPRITE.1.x(), SPRITE.0.y(), SPRITE.2.tile_id()), (100, 40, 6));
+  assert!(SPRITE.0.bpp8());
+  assert_eq!((SCROLL.x(), SCROLL.y()), (511, 8));
+}
+
+/// Counts how many times it's been dropped.
+#[derive(Debug, Clone)]
+struct DropCounter<'a>(u32, &'a Cell<u32>);
+impl Drop for DropCounter<'_> {
+  fn drop(&mut self) {
+    self.1.set(self.1.get() + 1);
+  }
+}
+
+#[test_case]
+fn array_vec_pushes_removes_and_drops() {
+  let mut list: ArrayVec<u8, 4> = ArrayVec::new();
+  assert!(list.pop().is_none() && list.remove(0).is_none());
+  list.extend_from_slice(&[1, 2, 3]).unwrap();
+  assert_eq!(list.extend_from_slice(&[4, 5]), Err(()));
+  assert_eq!(list.insert(0, 0), Ok(()));
+  assert_eq!(list.push(9), Err(9));
+  assert!(list.is_full());
+  assert_eq!(list.as_slice(), &[0, 1, 2, 3]);
+  assert_eq!(list.swap_remove(1), Some(1));
+  assert_eq!(list.as_slice(), &[0, 3, 2]);
+  assert_eq!(list.remove(0), Some(0));
+  assert_eq!(list.insert(3, 7), Err(7));
+  list.retain(|&x| x != 3);
+  assert_eq!(list.as_slice(), &[2]);
+  let collected: ArrayVec<u8, 4> = (1..4).collect();
+  assert_eq!(collected.iter().sum::<u8>(), 6);
+
+  let drops = Cell::new(0);
+  {
+    let mut list: ArrayVec<DropCounter<'_>, 8> = ArrayVec::new();
+    for i in 0..6 {
+      list.push(DropCounter(i, &drops)).unwrap();
+    }
+    let extra = DropCounter(6, &drops);
+    let cloned = list.clone();
+    assert_eq!(cloned.len(), 6);
+    drop(cloned);
+    assert_eq!(drops.get(), 6);
+    // removing gives the element back, without dropping it.
+    let removed = list.swap_remove(0).unwrap();
+    assert_eq!((removed.0, drops.get()), (0, 6));
+    drop(removed);
+    list.retain(|d| d.0 % 2 == 0);
+    assert_eq!(drops.get(), 7 + 3);
+    assert_eq!(list.iter().map(|d| d.0).sum::<u32>(), 2 + 4);
+    list.push(extra).unwrap();
+    list.truncate(1);
+    assert_eq!(drops.get(), 12);
+  }
+  // the last one, when the list itself is dropped.
+  assert_eq!(drops.get(), 13);
+}
+
+#[test_case]
+fn array_string_truncates_on_char_boundaries() {
+  use core::fmt::Write;
+  let mut text: ArrayString<8> = ArrayString::new();
+  write!(text, "{}-{}", 12, 34).unwrap();
+  assert_eq!(text, "12-34");
+  assert!(!text.was_truncated());
+  assert_eq!(text.push_str("long"), Err(()));
+  assert!(!text.was_truncated());
+  // 'é' is two bytes, and only one byte is left after "12-34é".
+  assert!(write!(text, "éé").is_err());
+  assert_eq!(text.as_str(), "12-34é");
+  assert!(text.was_truncated());
+  assert_eq!(text.pop(), Some('é'));
+  assert_eq!(text.push('!'), Ok(()));
+  assert_eq!(text.len(), 6);
+  text.clear();
+  assert!(text.is_empty() && !text.was_truncated());
+  assert_eq!(ArrayString::<2>::try_from_str("abc"), Err(()));
+}
+
+#[test_case]
+fn ring_deque_wraps_and_drops() {
+  let mut queue: RingDeque<u8, 3> = RingDeque::new();
+  assert_eq!(queue.pop_front(), None);
+  for i in 0..3 {
+    queue.push_back(i).unwrap();
+  }
+  assert_eq!(queue.push_back(3), Err(3));
+  assert_eq!(queue.pop_front(), Some(0));
+  queue.push_back(3).unwrap();
+  // the ring has wrapped, so the elements are in two pieces.
+  assert_eq!(queue.as_slices(), (&[1, 2][..], &[3][..]));
+  assert!(queue.iter().copied().eq([1, 2, 3]));
+  assert!(queue.iter().rev().copied().eq([3, 2, 1]));
+  assert_eq!(queue.push_back_overwrite(4), Some(1));
+  assert_eq!(queue.pop_back(), Some(4));
+  queue.push_front(9).unwrap();
+  assert_eq!((queue.front(), queue.back()), (Some(&9), Some(&3)));
+  assert_eq!(queue.get(1), Some(&2));
+  assert_eq!(queue.get(3), None);
+
+  let drops = Cell::new(0);
+  {
+    let mut queue: RingDeque<DropCounter<'_>, 4> = RingDeque::new();
+    for i in 0..4 {
+      queue.push_back(DropCounter(i, &drops)).unwrap();
+    }
+    drop(queue.pop_front());
+    let oldest = queue.push_back_overwrite(DropCounter(4, &drops));
+    assert_eq!((oldest.as_ref().map(|d| d.0), drops.get()), (None, 1));
+    let oldest = queue.push_back_overwrite(DropCounter(5, &drops));
+    assert_eq!(oldest.as_ref().map(|d| d.0), Some(1));
+    drop(oldest);
+    assert_eq!(drops.get(), 2);
+  }
+  assert_eq!(drops.get(), 6);
+}
+
+#[test_case]
+fn arena_aligns_and_runs_out() {
+  static mut MEMORY: Align4<[u8; 32]> = Align4([0; 32]);
+  let arena = Arena::new(unsafe { &mut (*addr_of_mut!(MEMORY)).0 });
+  let byte = arena.alloc(1_u8).unwrap();
+  let word = arena.alloc(2_u32).unwrap();
+  assert!((word as *mut u32 as usize).is_multiple_of(4));
+  assert_eq!((*byte, *word, arena.used()), (1, 2, 8));
+  // zero sized allocations don't use any space, not even padding.
+  assert_eq!(arena.alloc(()), Some(&mut ()));
+  assert_eq!(arena.alloc_slice::<u16>(0).map(|s| s.len()), Some(0));
+  assert_eq!(arena.used(), 8);
+  assert_eq!(arena.alloc_slice::<u16>(3).unwrap(), &[0, 0, 0]);
+  let words = arena.alloc_slice_copy(&[1_u32, 2, 3]).unwrap();
+  assert!((words.as_ptr() as usize).is_multiple_of(4));
+  assert_eq!(
+    (&*words, arena.used(), arena.remaining()),
+    (&[1, 2, 3][..], 28, 4)
+  );
+  // 4 bytes are free, but a `u64` needs 4 bytes of padding first.
+  assert_eq!(arena.alloc(0_u64), None);
+  assert_eq!(arena.used(), 28);
+  assert_eq!(arena.alloc(5_u32).copied(), Some(5));
+  assert_eq!((arena.alloc(0_u8), arena.remaining()), (None, 0));
+  assert!(arena.alloc(()).is_some());
+  assert!(arena.alloc_uninit_slice::<u32>(usize::MAX).is_none());
+}
+
+#[test_case]
+fn arena_resets_to_nested_checkpoints() {
+  static mut MEMORY: Align4<[u8; 64]> = Align4([0; 64]);
+  let mut arena = Arena::new(unsafe { &mut (*addr_of_mut!(MEMORY)).0 });
+  let level = arena.checkpoint();
+  let first = arena.alloc([1_u8; 10]).unwrap() as *mut _ as usize;
+  let loading = arena.checkpoint();
+  assert_eq!(loading.used(), 10);
+  arena.alloc_slice::<u32>(4).unwrap();
+  assert_eq!(arena.used(), 28);
+  arena.reset_to(loading);
+  assert_eq!(arena.used(), 10);
+  arena.reset_to(loading);
+  assert_eq!(arena.used(), 10);
+  arena.reset_to(level);
+  assert_eq!(arena.used(), 0);
+  // `loading` is past the current position now, so it's ignored.
+  arena.reset_to(loading);
+  assert_eq!(arena.used(), 0);
+  // freed memory is handed out again.
+  let again = arena.alloc([2_u8; 10]).unwrap() as *mut _ as usize;
+  assert_eq!(first, again);
+  let sum = arena.scope(|arena| {
+    let a = arena.alloc(3_u32).unwrap();
+    let b = arena.alloc(4_u32).unwrap();
+    *a + *b
+  });
+  assert_eq!((sum, arena.used()), (7, 10));
+  arena.reset();
+  assert_eq!(arena.remaining(), 64);
+}
+
+fn px(x: i32, y: i32) -> Vec2<i32fx8> {
+  Vec2::new(i32fx8::from_int(x), i32fx8::from_int(y))
+}
+
+fn aabb(x: i32, y: i32, w: i32, h: i32) -> Aabb {
+  Aabb::new(px(x, y), px(w, h))
+}
+
+#[test_case]
+fn aabb_overlaps_only_inside_edges() {
+  let a = aabb(0, 0, 8, 8);
+  assert!(a.overlaps(aabb(7, 7, 8, 8)));
+  // touching along an edge, or at a corner, isn't overlapping.
+  assert!(!a.overlaps(aabb(8, 0, 8, 8)));
+  assert!(!a.overlaps(aabb(8, 8, 8, 8)));
+  // a zero sized box is a point, inside only if it's strictly inside.
+  assert!(a.overlaps(aabb(4, 4, 0, 0)));
+  assert!(!a.overlaps(aabb(8, 4, 0, 0)));
+  assert!(!aabb(4, 4, 0, 0).overlaps(aabb(4, 4, 0, 0)));
+  // negative sizes count as 0.
+  assert!(!aabb(20, 20, -40, -40).overlaps(a));
+  assert!(a.contains_point(px(0, 0)));
+  assert!(!a.contains_point(px(8, 0)));
+  assert_eq!(Aabb::from_rect(Rect::new(1, 2, 3, 4)), aabb(1, 2, 3, 4));
+}
+
+#[test_case]
+fn sweep_edge_cases() {
+  let wall = aabb(16, 0, 8, 32);
+  // a hit, with the box moved exactly up to the wall.
+  let hit = aabb(0, 8, 8, 8).sweep(px(16, 4), wall).unwrap();
+  assert_eq!(hit.normal, Vec2::new(-1, 0));
+  assert_eq!(hit.delta, Vec2::new(i32fx8::from_int(8), i32fx8::from_int(2)));
+  assert_eq!(hit.time, i16fx14::from_bits(1 << 13));
+  assert_eq!(
+    hit.slide_velocity(px(16, 4)),
+    Vec2::new(i32fx8::from_int(0), i32fx8::from_int(2))
+  );
+  // already touching and moving in: a hit right away.
+  let hit = aabb(8, 8, 8, 8).sweep(px(3, 0), wall).unwrap();
+  assert_eq!((hit.time.to_bits(), hit.delta), (0, px(0, 0)));
+  // moving away from, or sliding along, a touching wall isn't a hit.
+  assert_eq!(aabb(8, 8, 8, 8).sweep(px(-3, 0), wall), None);
+  assert_eq!(aabb(8, 8, 8, 8).sweep(px(0, 50), wall), None);
+  // ending up exactly touching is a hit at time 1.
+  let hit = aabb(0, 8, 8, 8).sweep(px(8, 0), wall).unwrap();
+  assert_eq!((hit.time.to_bits(), hit.delta), (1 << 14, px(8, 0)));
+  // falling short isn't.
+  assert_eq!(aabb(0, 8, 8, 8).sweep(px(7, 0), wall), None);
+  // zero velocity never hits, and overlapping boxes don't either.
+  assert_eq!(aabb(0, 8, 8, 8).sweep(px(0, 0), wall), None);
+  assert_eq!(aabb(12, 8, 8, 8).sweep(px(8, 0), wall), None);
+  // exactly onto a corner, the hit is vertical.
+  let hit = aabb(0, -16, 8, 8).sweep(px(16, 16), wall).unwrap();
+  assert_eq!((hit.normal, hit.delta), (Vec2::new(0, -1), px(8, 8)));
+  // a zero sized box works like a ray.
+  let hit = aabb(0, 4, 0, 0).sweep(px(100, 0), wall).unwrap();
+  assert_eq!((hit.normal, hit.delta), (Vec2::new(-1, 0), px(16, 0)));
+  // fast enough to jump right over the wall in one step.
+  assert!(aabb(0, 8, 8, 8).sweep(px(200, 0), wall).is_some());
+}
+
+#[test_case]
+fn sweep_matches_small_steps() {
+  const STEPS: i32 = 16;
+  let mut rng = Xoshiro128::from_seed(121);
+  let mut range = |n: u32| (rng.next_u32() % (2 * n + 1)) as i32 - n as i32;
+  for _ in 0..400 {
+    let bits = |x: i32| i32fx8::from_bits(x);
+    let a = Aabb::new(
+      Vec2::new(bits(range(4096)), bits(range(4096))),
+      Vec2::new(bits(range(1024).abs()), bits(range(1024).abs())),
+    );
+    let b = Aabb::new(
+      Vec2::new(bits(range(2048)), bits(range(2048))),
+      Vec2::new(bits(range(1024).abs()), bits(range(1024).abs())),
+    );
+    let v = Vec2::new(range(512) * STEPS, range(512) * STEPS);
+    let velocity = Vec2::new(bits(v.x), bits(v.y));
+    if a.overlaps(b) {
+      assert_eq!(a.sweep(velocity, b), None);
+      continue;
+    }
+    let hit = a.sweep(velocity, b);
+    let first_overlap = (0..=STEPS).find(|&k| {
+      a.translate(Vec2::new(bits(v.x / STEPS * k), bits(v.y / STEPS * k)))
+        .overlaps(b)
+    });
+    if let Some(k) = first_overlap {
+      // no tunneling, and the hit isn't after the first overlapping step.
+      let hit = hit.unwrap();
+      assert!(i32::from(hit.time.to_bits()) * STEPS <= k * (1 << 14));
+    }
+    if let Some(hit) = hit {
+      let moved = a.translate(hit.delta);
+      assert!(!moved.overlaps(b));
+      let (m, b_max) = (moved.max(), b.max());
+      match (hit.normal.x, hit.normal.y) {
+        (-1, 0) => assert_eq!(m.x, b.pos.x),
+        (1, 0) => assert_eq!(moved.pos.x, b_max.x),
+        (0, -1) => assert_eq!(m.y, b.pos.y),
+        (0, 1) => assert_eq!(moved.pos.y, b_max.y),
+        _ => panic!("bad normal"),
+      }
+    }
+  }
+}
+
+#[test_case]
+fn sweep_map_slides_along_floors_and_stops_at_walls() {
+  const SOLID: u8 = 1 << 0;
+  static METATILES: [Metatile<1>; 2] = [
+    Metatile::new([[TextEntry::new()]], 0),
+    Metatile::new([[TextEntry::from_tile(1)]], SOLID),
+  ];
+  #[rustfmt::skip]
+  static GRID: [u16; 16] = [
+    0, 0, 0, 1,
+    0, 0, 0, 1,
+    0, 0, 0, 1,
+    1, 1, 1, 1,
+  ];
+  let map = MetatileMap::new(&[], &METATILES, &GRID, 4, 4);
+  // falling much faster than a tile per frame still lands on the floor.
+  let (moved, blocked) =
+    move_and_slide(&map, aabb(4, 0, 8, 8), px(0, 100), SOLID);
+  assert_eq!((moved, blocked), (aabb(4, 16, 8, 8), Vec2::new(0, -1)));
+  // moving along the floor (and pressing into it) crosses the edges between
+  // its metatiles, and stops at the wall.
+  let (moved, blocked) =
+    move_and_slide(&map, aabb(2, 16, 8, 8), px(20, 4), SOLID);
+  assert_eq!((moved, blocked), (aabb(16, 16, 8, 8), Vec2::new(-1, -1)));
+  // nothing to hit in the open.
+  let (moved, blocked) =
+    move_and_slide(&map, aabb(0, 0, 4, 4), px(4, 4), SOLID);
+  assert_eq!((moved, blocked), (aabb(4, 4, 4, 4), Vec2::new(0, 0)));
+  let hit = sweep_map(&map, aabb(1, 1, 0, 0), px(100, 0), SOLID).unwrap();
+  assert_eq!((hit.normal, hit.delta), (Vec2::new(-1, 0), px(23, 0)));
+  // other flags don't block.
+  assert_eq!(sweep_map(&map, aabb(1, 1, 0, 0), px(100, 0), 1 << 1), None);
+}
+
+#[test_case]
+fn grid_broadphase_gives_each_pair_once() {
+  let mut grid: GridBroadphase<4, 4> = GridBroadphase::new(4);
+  let boxes = [
+    aabb(0, 0, 20, 20),
+    aabb(10, 10, 4, 4),
+    aabb(18, 18, 4, 4),
+    aabb(60, 60, 4, 4),
+    // off the grid, so it's counted in the nearest cell, (0, 3).
+    aabb(-100, 200, 4, 4),
+    aabb(0, 48, 16, 16),
+  ];
+  for (i, b) in boxes.iter().enumerate() {
+    grid.insert(i as u16, *b).unwrap();
+  }
+  let mut pairs: ArrayVec<(u16, u16), 16> = grid.pairs().collect();
+  pairs.sort_unstable();
+  assert_eq!(&pairs[..], &[(0, 1), (0, 2), (4, 5)]);
+  let mut near: ArrayVec<u16, 8> = grid.query(aabb(30, 30, 4, 4)).collect();
+  near.sort_unstable();
+  assert_eq!(&near[..], &[0, 2]);
+  for i in 6..128 {
+    grid.insert(i, aabb(40, 0, 1, 1)).unwrap();
+  }
+  assert_eq!(grid.insert(128, aabb(0, 0, 1, 1)), Err(128));
+  grid.clear();
+  assert!(grid.is_empty());
+  assert_eq!(grid.pairs().next(), None);
+}
+
+#[test_case]
+fn environment_is_mgba() {
+  assert_eq!(detect(), Environment::Mgba);
+  assert!(detect().is_known_emulator());
+  assert!(is_mgba());
+  assert!(!is_nocash());
+  assert!(gba::mgba::mgba_logging_available());
+  assert!(!gba::nocash::nocash_available());
+  set_debug_backend(None);
+  assert_eq!(debug_backend(), Some(DebugBackend::Mgba));
+}
+
+/// Ticks `pacer` at each count, giving the steps of each tick.
+fn pace(pacer: &mut FramePacer, counts: &[u32]) -> ArrayVec<u32, 16> {
+  counts.iter().map(|&count| pacer.tick_at(count).steps).collect()
+}
+
+#[test_case]
+fn frame_pacer_catches_up_on_lag() {
+  let mut pacer = FramePacer::new(1, 3);
+  assert_eq!(
+    &pace(&mut pacer, &[10, 11, 12, 14, 15, 15, 16])[..],
+    &[1, 1, 1, 2, 1, 0, 1]
+  );
+  assert_eq!(pacer.lag_frames_total(), 1);
+  // a long stall is capped, and the rest is dropped.
+  let frame = pacer.tick_at(26);
+  assert_eq!((frame.steps, frame.lag_frames), (3, 9));
+  assert_eq!(&pace(&mut pacer, &[27, 28])[..], &[1, 1]);
+  assert_eq!(pacer.lag_frames_total(), 10);
+  // the count wrapping makes no difference.
+  let mut pacer = FramePacer::new(1, 3);
+  assert_eq!(
+    &pace(&mut pacer, &[u32::MAX - 1, u32::MAX, 0, 2, 3])[..],
+    &[1, 1, 1, 2, 1]
+  );
+  assert_eq!(pacer.lag_frames_total(), 1);
+  // after a resync, the time since the last tick doesn't count.
+  pacer.resync();
+  assert_eq!(&pace(&mut pacer, &[500, 501])[..], &[1, 1]);
+  assert_eq!(pacer.lag_frames_total(), 1);
+}
+
+#[test_case]
+fn frame_pacer_interpolates_long_steps() {
+  let mut pacer = FramePacer::new(2, 4);
+  let ticks: ArrayVec<(u32, i16fx14), 8> = [0, 1, 2, 3, 6, 7]
+    .into_iter()
+    .map(|count| {
+      let frame = pacer.tick_at(count);
+      (frame.steps, frame.alpha)
+    })
+    .collect();
+  let half = i16fx14::from_bits(1 << 13);
+  let zero = i16fx14::from_bits(0);
+  assert_eq!(
+    &ticks[..],
+    &[(1, zero), (0, half), (1, zero), (0, half), (2, zero), (0, half)]
+  );
+  assert_eq!(pacer.lag_frames_total(), 2);
+}
+
+#[test_case]
+fn frame_count_advances_across_vblank_waits() {
+  static BLINK: Animation = Animation::new(&[(0, 3), (1, 8)], true);
+  let dispstat = DISPSTAT.read();
+  init_vblank_irq();
+
+  let mut player = AnimationPlayer::timed();
+  player.play(&BLINK);
+  assert_eq!(player.tick(), Some(0));
+  let mut repeat = KeyRepeat::timed(3, 1);
+  let a = KeyInput::new().with_a(true);
+  assert_eq!(repeat.update(a), a);
+
+  let start = FrameInstant::now();
+  wait_for_vblank();
+  let after_one = start.elapsed();
+  wait_for_vblank();
+  wait_for_vblank();
+  let after_three = start.elapsed();
+  // a vblank can land between `now` and the first wait, which then waits for
+  // the next one.
+  assert!((1..=2).contains(&after_one), "{after_one} frames after one wait");
+  assert_eq!(after_three, after_one + 2);
+  assert_eq!(FrameInstant::now().since(start), after_three);
+  assert_eq!(start.add_frames(after_three), FrameInstant::now());
+
+  // three or more frames went by, so both the timed helpers moved on.
+  assert_eq!(player.tick(), Some(1));
+  assert_eq!(repeat.update(a), a);
+  DISPSTAT.write(dispstat);
+}
+
+/// If column `x` of row `y` is part of a font glyph.
+fn glyph_pixel(glyph: u8, x: usize, y: usize) -> bool {
+  let row = CGA_8X8_THICK[usize::from(glyph) * 2 + y / 4] >> (8 * (y % 4));
+  row & (1 << x) != 0
+}
+
+#[test_case]
+fn mode3_text_clips_and_skips_transparent_pixels() {
+  mode3::clear_to(Color::BLUE);
+  mode3::draw_text(1, 2, Color::WHITE, Some(Color::RED), "A\nB");
+  mode3::draw_text(20, 2, Color::WHITE, None, "A");
+  for y in 0..8 {
+    for x in 0..8 {
+      let (a, b) = (glyph_pixel(b'A', x, y), glyph_pixel(b'B', x, y));
+      let opaque = if a { Color::WHITE } else { Color::RED };
+      let see_through = if a { Color::WHITE } else { Color::BLUE };
+      let below = if b { Color::WHITE } else { Color::RED };
+      assert_eq!(VIDEO3_VRAM.index(1 + x, 2 + y).read(), opaque);
+      assert_eq!(VIDEO3_VRAM.index(20 + x, 2 + y).read(), see_through);
+      assert_eq!(VIDEO3_VRAM.index(1 + x, 10 + y).read(), below);
+    }
+  }
+  assert_eq!(VIDEO3_VRAM.index(0, 2).read(), Color::BLUE);
+  assert_eq!(VIDEO3_VRAM.index(9, 2).read(), Color::BLUE);
+
+  // a glyph hanging off the right and bottom edges doesn't wrap around.
+  mode3::clear_to(Color::BLUE);
+  mode3::draw_glyph(236, 156, 0xDB, Color::WHITE, Some(Color::RED));
+  mode3::draw_glyph(-4, -4, 0xDB, Color::WHITE, Some(Color::RED));
+  assert_eq!(VIDEO3_VRAM.index(239, 159).read(), Color::WHITE);
+  assert_eq!(VIDEO3_VRAM.index(3, 3).read(), Color::WHITE);
+  assert_eq!(VIDEO3_VRAM.index(4, 4).read(), Color::BLUE);
+  assert_eq!(VIDEO3_VRAM.index(0, 157).read(), Color::BLUE);
+  assert_eq!(VIDEO3_VRAM.index(235, 159).read(), Color::BLUE);
+}
+
+#[test_case]
+fn mode4_text_keeps_neighboring_pixels() {
+  let page = BitmapPage::Page0;
+  let pixel = |x: usize, y: usize| {
+    let pair = VIDEO4_VRAM.get_frame(0).unwrap().index(x / 2, y).read();
+    <[u8; 2]>::from(pair)[x % 2]
+  };
+  mode4::clear_to(page, 5);
+  // 0xDB is a solid block, starting and ending halfway through a pair.
+  mode4::draw_glyph(page, 3, 0, 0xDB, 1, None);
+  assert_eq!(pixel(2, 0), 5);
+  for x in 3..11 {
+    assert_eq!(pixel(x, 7), 1);
+  }
+  assert_eq!(pixel(11, 0), 5);
+
+  mode4::clear_to(page, 5);
+  mode4::draw_text(page, 1, 8, 1, None, "A");
+  mode4::draw_text(page, 233, 8, 1, Some(2), "B");
+  for y in 0..8 {
+    for x in 0..8 {
+      let a = if glyph_pixel(b'A', x, y) { 1 } else { 5 };
+      assert_eq!(pixel(1 + x, 8 + y), a);
+      if 233 + x < 240 {
+        let b = if glyph_pixel(b'B', x, y) { 1 } else { 2 };
+        assert_eq!(pixel(233 + x, 8 + y), b);
+      }
+    }
+  }
+  assert_eq!(pixel(0, 8), 5);
+  assert_eq!(pixel(9, 8), 5);
+  assert_eq!(pixel(0, 9), 5);
+}
+
+#[test_case]
+fn bitmap_console_wraps_and_scrolls() {
+  use core::fmt::Write;
+  let mut console = BitmapConsole::new(Color::WHITE, Color::BLACK);
+  console.clear();
+  for _ in 0..31 {
+    console.write_byte(0xDB);
+  }
+  assert_eq!(console.cursor(), (1, 1));
+  assert_eq!(VIDEO3_VRAM.index(0, 8).read(), Color::WHITE);
+  console.set_cursor(0, 19);
+  writeln!(console, "ok").unwrap();
+  assert_eq!(console.cursor(), (0, 19));
+  // everything moved up a row, including the wrapped line.
+  assert_eq!(VIDEO3_VRAM.index(0, 0).read(), Color::WHITE);
+  assert_eq!(VIDEO3_VRAM.index(8, 0).read(), Color::BLACK);
+  for y in 0..8 {
+    for x in 0..8 {
+      let o = if glyph_pixel(b'o', x, y) { Color::WHITE } else { Color::BLACK };
+      assert_eq!(VIDEO3_VRAM.index(x, 144 + y).read(), o);
+      assert_eq!(VIDEO3_VRAM.index(x, 152 + y).read(), Color::BLACK);
+    }
+  }
+}
+
+/// Checks [`vram::copy_u8_src`] against a plain byte copy into RAM, for every
+/// source alignment, destination offset, and length that fits in `dest`.
+fn check_copy_u8_src<T>(dest: VolRegion<T, Safe, Safe>) {
+  const BYTES: usize = 96;
+  assert_eq!(dest.len() * size_of::<T>(), BYTES);
+  let at = |i: usize| (dest.as_usize() + i * 2) as *mut u16;
+  let src = Align4(core::array::from_fn::<u8, 44, _>(|i| 0xC0 + i as u8));
+  for src_start in 0..4 {
+    for offset in 0..8 {
+      for len in 0..=40 {
+        let mut expected: [u8; BYTES] = core::array::from_fn(|i| i as u8);
+        for i in 0..BYTES / 2 {
+          let halfword =
+            u16::from_le_bytes([expected[i * 2], expected[i * 2 + 1]]);
+          unsafe { at(i).write_volatile(halfword) };
+        }
+        let bytes = &src.0[src_start..src_start + len];
+        expected[offset..offset + len].copy_from_slice(bytes);
+        vram::copy_u8_src(bytes, dest, offset);
+        for i in 0..BYTES / 2 {
+          let got = unsafe { at(i).read_volatile() }.to_le_bytes();
+          assert_eq!(
+            got,
+            [expected[i * 2], expected[i * 2 + 1]],
+            "halfword {i}, with src_start {src_start}, offset {offset}, len {len}"
+          );
+        }
+      }
+    }
+  }
+}
+
+#[test_case]
+fn copy_u8_src_into_vram_palram_and_oam() {
+  assert!(in_video_memory(0x0500_0000));
+  assert!(in_video_memory(0x07FF_FFFF));
+  assert!(!in_video_memory(0x0400_0000));
+  assert!(!in_video_memory(0x0800_0000));
+  // object VRAM and OAM ignore byte writes, and PALRAM doubles them.
+  check_copy_u8_src(OBJ_TILES.as_region().sub_slice(..3));
+  check_copy_u8_src(BG_PALETTE.as_region().sub_slice(..48));
+  check_copy_u8_src(unsafe {
+    VolRegion::<u16, Safe, Safe>::from_raw_parts(
+      VolAddress::new(0x0700_0000),
+      48,
+    )
+  });
+  hide_objects(0, 128);
+}
+
+#[test_case]
+fn video_config_round_trips_through_dispcnt() {
+  let old = DISPCNT.read();
+  let config = VideoConfig {
+    mode: VideoMode::_4,
+    page: BitmapPage::Page1,
+    obj_mapping: ObjTileMapping::OneD,
+    hblank_oam_free: true,
+    forced_blank: true,
+    show_bg: [false, false, true, false],
+    show_obj: true,
+    enable_win0: false,
+    enable_win1: true,
+    enable_obj_win: true,
+  };
+  assert_eq!(
+    config.to_display_control(),
+    DisplayControl::new()
+      .with_video_mode(VideoMode::_4)
+      .with_show_frame1(true)
+      .with_hblank_oam_free(true)
+      .with_obj_vram_1d(true)
+      .with_forced_blank(true)
+      .with_show_bg2(true)
+      .with_show_obj(true)
+      .with_enable_win1(true)
+      .with_enable_obj_win(true)
+  );
+  config.apply();
+  assert_eq!(VideoConfig::read_current(), config);
+  assert_eq!(ObjTileMapping::current(), ObjTileMapping::OneD);
+  VideoConfig { forced_blank: true, ..VideoConfig::new() }.apply();
+  assert_eq!(ObjTileMapping::current(), ObjTileMapping::TwoD);
+  assert_eq!(VideoConfig::new().to_display_control(), DisplayControl::new());
+  DISPCNT.write(old);
+}
+
+#[test_case]
+fn obj_tiles_follow_the_mapping() {
+  let size = ObjSize::_32x32;
+  assert_eq!(ObjTileMapping::OneD.tile_slot(0, size, false, 1, 2), 9);
+  assert_eq!(ObjTileMapping::TwoD.tile_slot(0, size, false, 1, 2), 65);
+  assert_eq!(ObjTileMapping::OneD.tile_slot(4, size, true, 3, 3), 34);
+  assert_eq!(ObjTileMapping::TwoD.tile_slot(4, size, true, 3, 3), 106);
+  assert_eq!(ObjTileMapping::OneD.slot_span(size, false), 16);
+  assert_eq!(ObjTileMapping::TwoD.slot_span(size, false), 100);
+  assert_eq!(ObjTileMapping::TwoD.slot_span(size, true), 104);
+
+  // 2D allocations are rectangles of the grid.
+  let mut slots =
+    TileAllocator::new_obj().with_obj_mapping(ObjTileMapping::TwoD);
+  let a = slots.alloc_obj(size, false).unwrap();
+  let b = slots.alloc_obj(size, false).unwrap();
+  let c = slots.alloc_obj(ObjSize::_64x64, true).unwrap();
+  let d = slots.alloc_obj(ObjSize::_16x16, false).unwrap();
+  assert_eq!((a.0, b.0, c.0, d.0), (0, 4, 8, 24));
+  // the first 8 free columns for 4 rows are past `d`, from the third row.
+  assert_eq!(slots.alloc_obj(ObjSize::_64x32, false).unwrap().0, 88);
+  assert_eq!(slots.free_slots(), 1024 - 16 - 16 - 128 - 4 - 32);
+  slots.free_obj(b, size, false);
+  assert_eq!(slots.alloc_obj(ObjSize::_16x32, false).unwrap().0, 4);
+  assert!(slots.alloc_obj(ObjSize::_64x64, true).is_some());
+
+  // tiles land where the hardware looks for them.
+  let tiles: [Tile4; 16] = core::array::from_fn(|i| [i as u32 + 1; 8]);
+  OBJ_TILES.iter().take(128).for_each(|t| t.write([0; 8]));
+  copy_obj_4bpp(2, size, ObjTileMapping::TwoD, &tiles);
+  for y in 0..4 {
+    for x in 0..4 {
+      let slot = ObjTileMapping::TwoD.tile_slot(2, size, false, x, y);
+      let tile = OBJ_TILES.index(usize::from(slot)).read();
+      assert_eq!(tile, tiles[usize::from(y * 4 + x)]);
+    }
+  }
+  assert_eq!(OBJ_TILES.index(6).read(), [0; 8]);
+  assert_eq!(OBJ_TILES.index(33).read(), [0; 8]);
+  copy_obj_4bpp(8, size, ObjTileMapping::OneD, &tiles);
+  assert_eq!(OBJ_TILES.index(8 + 9).read(), tiles[9]);
+}
+
+#[test_case]
+fn sprite_sheet_frames_follow_the_mapping() {
+  let old = DISPCNT.read();
+  DISPCNT.write(DisplayControl::new().with_forced_blank(true));
+  let tiles: [Tile4; 20] = core::array::from_fn(|i| [i as u32 + 1; 8]);
+
+  let sheet = SpriteSheet::upload_4bpp(
+    TileIndex(10),
+    ObjSize::_16x16,
+    ObjTileMapping::OneD,
+    &tiles[..12],
+  );
+  assert_eq!(sheet.frame_count(), 3);
+  assert_eq!(
+    &sheet.tile_ids().collect::<ArrayVec<u16, 3>>()[..],
+    &[10, 14, 18]
+  );
+  assert_eq!(sheet.attr2(1).tile_id(), 14);
+  assert_eq!(OBJ_TILES.index(14 + 3).read(), tiles[4 + 3]);
+
+  // 4 frames fit across from slot 24, and the 5th goes below the first.
+  let sheet = SpriteSheet::upload_4bpp(
+    TileIndex(24),
+    ObjSize::_16x16,
+    ObjTileMapping::TwoD,
+    &tiles,
+  );
+  assert_eq!(
+    &sheet.tile_ids().collect::<ArrayVec<u16, 5>>()[..],
+    &[24, 26, 28, 30, 88]
+  );
+  assert_eq!(OBJ_TILES.index(88 + 32 + 1).read(), tiles[4 * 4 + 3]);
+  assert_eq!(OBJ_TILES.index(26 + 32).read(), tiles[4 + 2]);
+
+  let mut slots =
+    TileAllocator::new_obj().with_obj_mapping(ObjTileMapping::TwoD);
+  // in the ROM, since it's too big for the stack.
+  static BIG: [Tile4; 64 * 6] = [[0; 8]; 64 * 6];
+  let sheet =
+    SpriteSheet::alloc_4bpp(&mut slots, ObjSize::_64x64, &BIG).unwrap();
+  assert_eq!(sheet.tile_id(5), 8 + 8 * 32);
+  assert_eq!(slots.free_slots(), 512);
+  let second =
+    SpriteSheet::alloc_4bpp(&mut slots, ObjSize::_64x64, &BIG).unwrap();
+  assert_eq!(second.tile_id(0), 512);
+  assert!(
+    SpriteSheet::alloc_4bpp(&mut slots, ObjSize::_8x8, &BIG[..1]).is_none()
+  );
+  sheet.free(&mut slots);
+  second.free(&mut slots);
+  assert_eq!(slots.free_slots(), 1024);
+
+  let mut slots = TileAllocator::new_obj();
+  slots.alloc(1).unwrap();
+  let sheet =
+    SpriteSheet::alloc_8bpp(&mut slots, ObjSize::_16x16, &[[0; 16]; 8])
+      .unwrap();
+  assert_eq!((sheet.tile_id(0), sheet.tile_id(1)), (2, 10));
+  sheet.free(&mut slots);
+  assert_eq!(slots.free_slots(), 1023);
+  DISPCNT.write(old);
+}
+
+/// Format version 1 of the save was a `u16` score, version 2 made the score a
+/// `u32`, and version 3 added a `u32` play time.
+fn score_to_u32(old: &[u8], out: &mut [u8]) -> Result<usize, MigrateError> {
+  let Ok(score) = <[u8; 2]>::try_from(old) else {
+    return Err(MigrateError::BadData);
+  };
+  out[..4].copy_from_slice(&u32::from(u16::from_le_bytes(score)).to_le_bytes());
+  Ok(4)
+}
+fn add_play_time(old: &[u8], out: &mut [u8]) -> Result<usize, MigrateError> {
+  let Ok(score) = <[u8; 4]>::try_from(old) else {
+    return Err(MigrateError::BadData);
+  };
+  out[..4].copy_from_slice(&score);
+  out[4..8].copy_from_slice(&60_u32.to_le_bytes());
+  Ok(8)
+}
+static SCORE_SCHEMA: SaveSchema =
+  SaveSchema::new(1, &[score_to_u32, add_play_time]);
+
+/// Loads slot 0, with slot 1 as the backup, and checks that the scratch space
+/// is freed after.
+fn load_score<M: SaveMemory>(
+  media: &M, arena: &mut Arena,
+) -> Result<[u32; 2], MigrateError> {
+  let value = SCORE_SCHEMA.load_with_migration(media, 0, 1, arena);
+  assert_eq!(arena.used(), 0);
+  value
+}
+
+#[test_case]
+fn save_slots_catch_torn_and_corrupt_data() {
+  #[link_section = ".ewram"]
+  static mut BYTES: [u8; 2 * SLOT_LEN] = [0; 2 * SLOT_LEN];
+  let media = MemoryMedia::new(unsafe { &mut *addr_of_mut!(BYTES) });
+  let media = media.with_seed(130);
+  let scores = [9000_u32, 7000, 5000, 3000];
+  let len = SLOT_HEADER_LEN + size_of::<[u32; 4]>();
+  assert_eq!(media.load_slot::<[u32; 4]>(0), Err(SaveError::Empty));
+  let before = media.bytes_written();
+  media.save_slot(0, &scores).unwrap();
+  assert_eq!(media.bytes_written() - before, len);
+  assert_eq!(media.load_slot(0), Ok(scores));
+  assert_eq!(media.load_slot::<[u32; 3]>(0), Err(SaveError::WrongLength));
+
+  // a save cut off anywhere (in the header or the data) never loads as
+  // garbage: an empty slot is left empty or corrupt, and a slot with a save
+  // keeps that save or is corrupt.
+  for cut in 0..len {
+    let torn = [cut as u32; 4];
+    media.clear_slot(1).unwrap();
+    media.fail_after(cut);
+    assert_eq!(media.save_slot(1, &torn), Err(SaveError::Timeout));
+    media.restore_power();
+    let loaded = media.load_slot::<[u32; 4]>(1);
+    assert!(
+      matches!(loaded, Err(SaveError::Empty | SaveError::Corrupt)),
+      "cut {cut}: {loaded:?}"
+    );
+    media.save_slot(1, &scores).unwrap();
+    media.fail_after(cut);
+    assert_eq!(media.save_slot(1, &torn), Err(SaveError::Timeout));
+    media.restore_power();
+    let loaded = media.load_slot::<[u32; 4]>(1);
+    assert!(
+      matches!(loaded, Ok(s) if s == scores)
+        || loaded == Err(SaveError::Corrupt),
+      "cut {cut}: {loaded:?}"
+    );
+  }
+
+  // wrong magic, and bits flipped after the save or as it's written.
+  media.write(0, b"GBAX").unwrap();
+  assert_eq!(media.load_slot::<[u32; 4]>(0), Err(SaveError::Empty));
+  for _ in 0..32 {
+    media.save_slot(0, &scores).unwrap();
+    let at = media.flip_random_bit(0, len);
+    let loaded = media.load_slot::<[u32; 4]>(0);
+    assert!(loaded.is_err(), "bit flipped at {at}: {loaded:?}");
+  }
+  media.set_flip_on_write(true);
+  media.save_slot(0, &scores).unwrap();
+  assert!(media.load_slot::<[u32; 4]>(0).is_err());
+  media.set_flip_on_write(false);
+  media.save_slot(0, &scores).unwrap();
+  assert_eq!(media.load_slot(0), Ok(scores));
+
+  // like flash, each sector that a save touches is erased first.
+  static mut FLASH: [u8; 256] = [0; 256];
+  let flash = MemoryMedia::new(unsafe { &mut *addr_of_mut!(FLASH) });
+  let flash = flash.with_flash_sectors(128);
+  assert_eq!((flash.sector_size(), flash.slot_count()), (128, 1));
+  flash.save_slot(0, &scores).unwrap();
+  let mut bytes = [0; 2];
+  flash.read(127, &mut bytes[..1]).unwrap();
+  flash.read(128, &mut bytes[1..]).unwrap();
+  assert_eq!(bytes, [0xFF, 0]);
+  assert_eq!(flash.load_slot(0), Ok(scores));
+}
+
+#[test_case]
+fn saves_migrate_to_the_current_version() {
+  #[link_section = ".ewram"]
+  static mut BYTES: [u8; 2 * SLOT_LEN] = [0; 2 * SLOT_LEN];
+  #[link_section = ".ewram"]
+  static mut SCRATCH: [u8; 2 * SLOT_LEN] = [0; 2 * SLOT_LEN];
+  let media = MemoryMedia::new(unsafe { &mut *addr_of_mut!(BYTES) });
+  let arena = &mut Arena::new(unsafe { &mut *addr_of_mut!(SCRATCH) });
+  let mut buffer = [0; 16];
+  let old_save = 500_u16.to_le_bytes();
+  assert_eq!(SCORE_SCHEMA.current_version(), 3);
+  assert_eq!(
+    load_score(&media, arena),
+    Err(MigrateError::Save(SaveError::Empty))
+  );
+
+  // each older version goes through the rest of the chain, and is saved back
+  // at the current version, with the backup slot left empty.
+  media.save_slot_versioned(0, 1, &old_save).unwrap();
+  let before = media.bytes_written();
+  assert_eq!(load_score(&media, arena), Ok([500, 60]));
+  let upgrade_bytes = media.bytes_written() - before;
+  assert_eq!(media.load_slot_versioned(0, &mut buffer), Ok((3, 8)));
+  assert_eq!(media.load_slot_versioned(1, &mut buffer), Err(SaveError::Empty));
+  media.save_slot_versioned(0, 2, &70_000_u32.to_le_bytes()).unwrap();
+  assert_eq!(load_score(&media, arena), Ok([70_000, 60]));
+  assert_eq!(media.load_slot_versioned(0, &mut buffer), Ok((3, 8)));
+  SCORE_SCHEMA.save(&media, 0, &[1_u32, 2]).unwrap();
+  assert_eq!(load_score(&media, arena), Ok([1, 2]));
+
+  // saves that can't be upgraded are errors, and are left as they were.
+  for (version, data, error) in [
+    (4, &[0_u8; 8][..], MigrateError::TooNew(4)),
+    (0, &[0; 2], MigrateError::TooOld(0)),
+    (1, &[0; 3], MigrateError::BadData),
+    (3, &[0; 4], MigrateError::Save(SaveError::WrongLength)),
+  ] {
+    media.save_slot_versioned(0, version, data).unwrap();
+    assert_eq!(load_score(&media, arena), Err(error));
+    let len = data.len();
+    assert_eq!(media.load_slot_versioned(0, &mut buffer), Ok((version, len)));
+  }
+
+  // a corrupt backup is passed over, but with the slot corrupt too there's
+  // nothing to load.
+  media.save_slot_versioned(1, 3, &[0; 8]).unwrap();
+  media.flip_random_bit(SLOT_LEN + SLOT_HEADER_LEN, 8);
+  media.save_slot_versioned(0, 1, &old_save).unwrap();
+  assert_eq!(load_score(&media, arena), Ok([500, 60]));
+  media.save_slot_versioned(1, 3, &[0; 8]).unwrap();
+  media.flip_random_bit(SLOT_LEN + SLOT_HEADER_LEN, 8);
+  media.save_slot_versioned(0, 1, &old_save).unwrap();
+  media.flip_random_bit(SLOT_HEADER_LEN, 2);
+  assert_eq!(
+    load_score(&media, arena),
+    Err(MigrateError::Save(SaveError::Corrupt))
+  );
+
+  // without room in the arena for the scratch space.
+  let mark = arena.checkpoint();
+  arena.alloc_slice::<u8>(SLOT_LEN).unwrap();
+  let value =
+    SCORE_SCHEMA.load_with_migration::<[u32; 2], _>(&media, 0, 1, arena);
+  assert_eq!(value, Err(MigrateError::OutOfSpace));
+  arena.reset_to(mark);
+
+  // wherever the power goes off, the next load gets the upgraded save.
+  for cut in 0..upgrade_bytes {
+    media.save_slot_versioned(0, 1, &old_save).unwrap();
+    media.fail_after(cut);
+    assert!(load_score(&media, arena).is_err(), "cut {cut}");
+    media.restore_power();
+    assert_eq!(load_score(&media, arena), Ok([500, 60]), "cut {cut}");
+    assert_eq!(media.load_slot_versioned(0, &mut buffer), Ok((3, 8)));
+    assert_eq!(
+      media.load_slot_versioned(1, &mut buffer),
+      Err(SaveError::Empty)
+    );
+  }
+}
+
+/// Checks the colors partway down the screen for two frames, as
+/// `(line, index, color)`, in line order.
+fn check_raster_lines(checks: &[(u8, usize, Color)]) {
+  // a vblank first, to start from the top of a frame.
+  spin_until_scanline(160);
+  for _ in 0..2 {
+    for &(line, index, color) in checks {
+      spin_until_scanline(line);
+      let entry = if index < 256 {
+        BG_PALETTE.index(index)
+      } else {
+        OBJ_PALETTE.index(index - 256)
+      };
+      assert_eq!(entry.read(), color, "index {index} at line {line}");
+    }
+  }
+}
+
+#[test_case]
+fn raster_palette_changes_colors_down_the_screen() {
+  static SKY: [RasterEntry; 3] = [
+    RasterEntry::new(0, 0..1, &[Color(1)]),
+    RasterEntry::new(40, 0..1, &[Color(2)]),
+    RasterEntry::new(100, 0..1, &[Color(3)]),
+  ];
+  static SPLIT: [RasterEntry; 4] = [
+    RasterEntry::new(0, 0..2, &[Color(4), Color(5)]),
+    RasterEntry::new(0, 300..301, &[Color(6)]),
+    RasterEntry::new(60, 0..1, &[Color(7)]),
+    RasterEntry::new(60, 300..301, &[Color(8)]),
+  ];
+  let old = [BG_PALETTE.index(0).read(), BG_PALETTE.index(1).read()];
+  let old_obj = OBJ_PALETTE.index(44).read();
+  let dispstat = DISPSTAT.read();
+  IME.write(true);
+
+  // one range per line goes by DMA, and each frame starts over at the top.
+  let raster = RasterPalette::start(3, &SKY);
+  assert!(raster.uses_dma());
+  check_raster_lines(&[
+    (10, 0, Color(1)),
+    (39, 0, Color(1)),
+    (41, 0, Color(2)),
+    (99, 0, Color(2)),
+    (120, 0, Color(3)),
+  ]);
+  raster.stop();
+
+  // two ranges on one line are written by the CPU.
+  let raster = RasterPalette::start(3, &SPLIT);
+  assert!(!raster.uses_dma());
+  check_raster_lines(&[
+    (30, 0, Color(4)),
+    (30, 1, Color(5)),
+    (30, 300, Color(6)),
+    (61, 0, Color(7)),
+    (61, 1, Color(5)),
+    (61, 300, Color(8)),
+  ]);
+  raster.stop();
+
+  DISPSTAT.write(dispstat);
+  BG_PALETTE.index(0).write(old[0]);
+  BG_PALETTE.index(1).write(old[1]);
+  OBJ_PALETTE.index(44).write(old_obj);
+}
+
+#[test_case]
+fn scheduler_fires_timers_in_order() {
+  let mut timers = Scheduler::<u8, 4>::new();
+  let late = timers.after(3, 1).unwrap();
+  let repeat = timers.every(2, 2).unwrap();
+  let soon = timers.after(2, 3).unwrap();
+  assert_eq!(timers.remaining(late), Some(3));
+  assert_eq!(timers.len(), 3);
+
+  // the same frame fires in the order the timers were made.
+  assert_eq!(&timers.tick()[..], &[]);
+  assert_eq!(&timers.tick()[..], &[2, 3]);
+  assert!(!timers.is_scheduled(soon));
+  assert_eq!(&timers.tick()[..], &[1]);
+  assert_eq!(&timers.tick()[..], &[2]);
+  assert_eq!(&timers.tick()[..], &[]);
+  assert_eq!(&timers.tick()[..], &[2]);
+  assert_eq!(timers.remaining(repeat), Some(2));
+
+  // a timer that's done or cancelled can't be cancelled again.
+  assert_eq!(timers.cancel(late), None);
+  assert_eq!(timers.cancel(repeat), Some(2));
+  assert_eq!(timers.cancel(repeat), None);
+  assert!(timers.is_empty());
+  assert_eq!(&timers.advance(100)[..], &[]);
+
+  // running out of room gives the event back, and handles aren't reused.
+  let handles = [1, 2, 3, 4].map(|event| timers.after(5, event).unwrap());
+  assert_eq!(timers.every(1, 5), Err(5));
+  assert_eq!(timers.after(1, 5), Err(5));
+  assert!(!handles.contains(&soon));
+  assert_eq!(timers.cancel(handles[1]), Some(2));
+  let last = timers.after(1, 5).unwrap();
+  assert_eq!(&timers.tick()[..], &[5]);
+  assert!(!timers.is_scheduled(last));
+  assert_eq!(&timers.advance(4)[..], &[1, 3, 4]);
+  timers.clear();
+
+  // catching up fires once and stays on the schedule.
+  let repeat = timers.every(3, 9).unwrap();
+  timers.after(10, 8).unwrap();
+  assert_eq!(&timers.advance(10)[..], &[9, 8]);
+  // it was due on frames 3, 6, and 9, so next on 12.
+  assert_eq!(timers.remaining(repeat), Some(2));
+  assert_eq!(&timers.advance(0)[..], &[]);
+
+  // updates go by frame counts, wrapping. The first update is 1 frame, then
+  // 0, 2 (across the wrap), 3, and 17.
+  let mut timers = Scheduler::<u8, 2>::new();
+  timers.every(4, 1).unwrap();
+  let start = u32::MAX - 1;
+  assert_eq!(&timers.update(FrameInstant::from_frame_count(start))[..], &[]);
+  assert_eq!(&timers.update(FrameInstant::from_frame_count(start))[..], &[]);
+  assert_eq!(&timers.update(FrameInstant::from_frame_count(0))[..], &[]);
+  assert_eq!(&timers.update(FrameInstant::from_frame_count(3))[..], &[1]);
+  assert_eq!(&timers.update(FrameInstant::from_frame_count(20))[..], &[1]);
+}
+
+#[test_case]
+fn key_set_ops_ignore_the_unused_bits() {
+  let a = KeyInput::new().with_a(true);
+  let b = KeyInput::new().with_b(true);
+  let ab = a.union(b);
+  assert_eq!(ab.intersection(a), a);
+  assert_eq!(a.intersection(b), KeyInput::new());
+  assert_eq!(ab.difference(a), b);
+  assert!(ab.contains(a) && ab.contains(ab) && !a.contains(ab));
+  assert!(ab.contains(KeyInput::new()));
+  assert!(ab.any() && !ab.all() && !KeyInput::new().any());
+  assert_eq!((KeyInput::new().count(), a.count(), ab.count()), (0, 1, 2));
+  assert_eq!(ab, a | b);
+  assert_eq!(a, ab & a);
+  assert_eq!((ab ^ a, (!ab).count()), (b, 8));
+  assert!((!KeyInput::new()).all());
+
+  // the top six bits aren't keys, so whatever they hold never counts as a
+  // press, and results always have them set (released).
+  let top_clear = KeyInput::from(0x03FF);
+  assert!(!top_clear.any());
+  assert_eq!(top_clear.count(), 0);
+  assert_eq!(top_clear.union(a), a);
+  assert_eq!(top_clear.union(a).to_u16(), 0xFFFE);
+  let all = KeyInput::from(0x0000);
+  assert!(all.all() && all.any());
+  assert_eq!(all.count(), 10);
+  assert_eq!(all.intersection(all).to_u16(), 0xFC00);
+  assert!(all.contains(ab) && !ab.contains(all));
+  assert_eq!(all.iter_pressed().count(), 10);
+}
+
+#[test_case]
+fn key_tracker_finds_press_and_release_edges() {
+  let a = KeyInput::new().with_a(true);
+  let ab = a.with_b(true);
+  let mut keys = KeyTracker::new();
+  assert_eq!(keys, KeyTracker::default());
+  keys.update(a);
+  assert_eq!((keys.just_pressed(), keys.just_released()), (a, KeyInput::new()));
+  keys.update(ab);
+  assert_eq!(keys.held(), ab);
+  assert_eq!(keys.just_pressed(), KeyInput::new().with_b(true));
+  // holding the same keys isn't an edge.
+  keys.update(ab);
+  assert!(!keys.just_pressed().any() && !keys.just_released().any());
+  keys.update(KeyInput::new().with_b(true));
+  assert_eq!(keys.just_released(), a);
+  assert!(!keys.just_pressed().any());
+  keys.update(KeyInput::new());
+  assert_eq!(keys.just_released(), KeyInput::new().with_b(true));
+}
+
+#[test_case]
+fn key_repeat_fires_after_the_delay_then_each_interval() {
+  let a = KeyInput::new().with_a(true);
+  let b = KeyInput::new().with_b(true);
+  let mut repeat = KeyRepeat::new(3, 2);
+  let mut fired = [false; 10];
+  for fire in &mut fired {
+    *fire = repeat.update(a).a();
+  }
+  // the press, then 3 frames of delay, then every 2 frames.
+  let expected =
+    [true, false, false, true, false, true, false, true, false, true];
+  assert_eq!(fired, expected);
+
+  // each key is timed separately, and letting go starts it over.
+  assert_eq!(repeat.update(a.union(b)), b);
+  assert_eq!(repeat.update(KeyInput::new()), KeyInput::new());
+  assert_eq!(repeat.update(a), a);
+  assert_eq!(repeat.update(a), KeyInput::new());
+
+  // new timings are used the next time a key's timer starts.
+  repeat.delay = 1;
+  repeat.interval = 1;
+  repeat.update(KeyInput::new());
+  assert_eq!(repeat.update(a), a);
+  assert_eq!(repeat.update(a), a);
+  assert_eq!(repeat.update(a), a);
+}
+
+#[test_case]
+fn socd_policies_resolve_opposite_directions() {
+  use TriBool::*;
+  let left_right = KeyInput::new().with_left(true).with_right(true);
+  let up_down = KeyInput::new().with_up(true).with_down(true);
+  for (policy, both) in [
+    (SocdPolicy::Neutral, Neutral),
+    (SocdPolicy::FavorPlus, Plus),
+    (SocdPolicy::FavorMinus, Minus),
+  ] {
+    assert_eq!(left_right.column_direction_with(policy), both);
+    assert_eq!(up_down.row_direction_with(policy), both);
+    // one direction at a time is the same under every policy.
+    let left = KeyInput::new().with_left(true);
+    assert_eq!(left.column_direction_with(policy), Minus);
+    assert_eq!(left.row_direction_with(policy), Neutral);
+    let down = KeyInput::new().with_down(true);
+    assert_eq!(down.row_direction_with(policy), Plus);
+  }
+  assert_eq!(left_right.union(up_down).dpad(), (Plus, Plus));
+  let up_left = KeyInput::new().with_up(true).with_left(true);
+  assert_eq!(up_left.dpad(), (Minus, Minus));
+  assert_eq!((Minus.to_i32(), Neutral.to_i32(), Plus.to_i32()), (-1, 0, 1));
+}
+
+#[test_case]
+fn input_recordings_play_back_frame_for_frame() {
+  let a = KeyInput::new().with_a(true);
+  let frames = [a, a, a, KeyInput::new(), a, a];
+  let mut recorder = InputRecorder::<4>::new();
+  assert!(recorder.is_empty());
+  for keys in frames {
+    recorder.record(keys).unwrap();
+  }
+  // runs of the same input share an entry.
+  assert_eq!(recorder.as_slice(), &[(a, 3), (KeyInput::new(), 1), (a, 2)]);
+  assert!(recorder.playback().eq(frames));
+
+  // running out of room stops the recording, but keeps what was recorded.
+  recorder.record(KeyInput::new()).unwrap();
+  assert_eq!(recorder.record(a), Err(()));
+  assert_eq!(recorder.record(KeyInput::new()), Err(()));
+  assert!(recorder.is_full());
+  assert_eq!(recorder.len(), 4);
+  assert_eq!(recorder.playback().count(), 7);
+  recorder.clear();
+  assert!(recorder.is_empty() && !recorder.is_full());
+
+  // runs of 0 frames are skipped.
+  static DEMO: [(KeyInput, u16); 3] = [
+    (KeyInput::new(), 0),
+    (KeyInput::new().with_b(true), 2),
+    (KeyInput::new(), 0),
+  ];
+  let mut playback = InputPlayback::new(&DEMO);
+  assert!(!playback.is_finished());
+  assert_eq!(playback.next().map(|k| k.b()), Some(true));
+  assert_eq!(playback.next().map(|k| k.b()), Some(true));
+  assert!(playback.is_finished());
+  assert_eq!(playback.next(), None);
+}
+
+#[test_case]
+fn chords_and_combos_match_in_order() {
+  const A: KeyInput = KeyInput::new().with_a(true);
+  const B: KeyInput = KeyInput::new().with_b(true);
+  const UP: KeyInput = KeyInput::new().with_up(true);
+  const NONE: KeyInput = KeyInput::new();
+  let reset_keys = A.union(B).with_start(true).with_select(true);
+  assert!(chord_held(reset_keys.with_l(true), reset_keys));
+  assert!(!chord_held(A.union(B), reset_keys));
+
+  static STEPS: [KeyInput; 3] = [UP, UP, A];
+  let mut combo = Combo::new(&STEPS, Some(5));
+  assert!(!combo.update(UP) && !combo.update(NONE) && !combo.update(UP));
+  assert_eq!(combo.progress(), 2);
+  // pressing a key from an earlier step again doesn't count as wrong.
+  assert!(!combo.update(UP));
+  assert_eq!(combo.progress(), 2);
+  assert!(combo.update(A));
+  assert_eq!(combo.progress(), 0);
+
+  // a wrong key starts over, but can also start a new attempt.
+  for keys in [UP, B, UP, UP] {
+    combo.update(keys);
+  }
+  assert_eq!(combo.progress(), 2);
+  combo.update(B);
+  assert_eq!(combo.progress(), 0);
+  combo.update(UP);
+  combo.update(A);
+  assert_eq!(combo.progress(), 0);
+  combo.update(UP);
+  assert_eq!(combo.progress(), 1);
+
+  // waiting too long between steps starts over.
+  combo.reset();
+  combo.update(UP);
+  for _ in 0..6 {
+    combo.update(NONE);
+  }
+  assert_eq!(combo.progress(), 0);
+  combo.update(UP);
+  for _ in 0..4 {
+    combo.update(NONE);
+  }
+  combo.update(UP);
+  assert!(combo.update(A));
+}
+
+#[test_case]
+fn text_entries_pack_and_index_by_screenblock() {
+  let entry = TextEntry::from_tile(0x3FF).with_hflip(true).with_palbank(0xF);
+  assert_eq!(entry.to_u16(), 0xF7FF);
+  assert_eq!(u16::from(entry), 0xF7FF);
+  assert_eq!(TextEntry::from(0xF7FF), entry);
+  assert!(entry.hflip() && !entry.vflip());
+  // tile ids past 10 bits are cut off.
+  assert_eq!(TextEntry::from_tile(0x401).tile(), 1);
+
+  use TextBackgroundSize::*;
+  assert_eq!(text_entry_index(_32x32, 5, 3), 3 * 32 + 5);
+  assert_eq!(text_entry_index(_32x32, 37, 35), 3 * 32 + 5);
+  // each 32x32 area is its own screenblock.
+  assert_eq!(text_entry_index(_64x32, 31, 31), 1023);
+  assert_eq!(text_entry_index(_64x32, 32, 0), 1024);
+  assert_eq!(text_entry_index(_32x64, 0, 32), 1024);
+  assert_eq!(text_entry_index(_64x64, 32, 0), 1024);
+  assert_eq!(text_entry_index(_64x64, 0, 32), 2048);
+  assert_eq!(text_entry_index(_64x64, 33, 34), 3 * 1024 + 2 * 32 + 1);
+  assert_eq!(text_entry_index(_64x64, 64, 64), 0);
+
+  // a region that crosses the edge of a screenblock, and wraps around the
+  // edge of the background.
+  let entries: [TextEntry; 4] =
+    core::array::from_fn(|i| TextEntry::from_tile(i as u16 + 1));
+  fill_text_region(26, _64x32, (63, 0), (2, 2), &entries, 2);
+  let (left, right) = (text_screenblock(26), text_screenblock(27));
+  assert_eq!(right.index(31, 0).read().tile(), 1);
+  assert_eq!(left.index(0, 0).read().tile(), 2);
+  assert_eq!(right.index(31, 1).read().tile(), 3);
+  assert_eq!(left.index(0, 1).read().tile(), 4);
+}
+
+#[test_case]
+fn tile_allocator_is_first_fit() {
+  let mut tiles = TileAllocator::new_charblock();
+  assert_eq!(tiles.free_slots(), 512);
+  assert_eq!(tiles.alloc(0), None);
+  let a = tiles.alloc(4).unwrap();
+  let b = tiles.alloc(4).unwrap();
+  let c = tiles.alloc(4).unwrap();
+  assert_eq!((a, b, c), (TileIndex(0), TileIndex(4), TileIndex(8)));
+
+  // a freed run is reused by anything that fits, and skipped by anything
+  // that doesn't.
+  tiles.free(b, 4);
+  assert_eq!(tiles.alloc(5), Some(TileIndex(12)));
+  assert_eq!(tiles.alloc(3), Some(TileIndex(4)));
+  assert_eq!(tiles.alloc_8bpp(1), Some(TileIndex(18)));
+  assert_eq!(tiles.alloc(1), Some(TileIndex(7)));
+  // slot 17 was skipped to keep the 8bpp tile even.
+  assert_eq!(tiles.alloc(1), Some(TileIndex(17)));
+  tiles.free(TileIndex(17), 1);
+  assert_eq!(tiles.free_slots(), 512 - 19);
+
+  // running out, and freeing past the end of the range.
+  assert_eq!(tiles.alloc(512), None);
+  tiles.free(TileIndex(500), 100);
+  assert_eq!(tiles.free_slots(), 512 - 19);
+  tiles.reset();
+  assert_eq!(tiles.alloc(512), Some(TileIndex(0)));
+  assert_eq!(tiles.alloc(1), None);
+
+  // the bitmap modes only leave the top half of object VRAM.
+  let mut bitmap = TileAllocator::new_obj_bitmap_mode();
+  assert_eq!(bitmap.free_slots(), 512);
+  assert_eq!(bitmap.alloc_obj(ObjSize::_32x32, false), Some(TileIndex(512)));
+  let bpp8 = bitmap.alloc_obj(ObjSize::_8x8, true).unwrap();
+  assert_eq!(bpp8, TileIndex(528));
+  bitmap.free_8bpp(bpp8, 1);
+  assert_eq!(bitmap.free_slots(), 512 - 16);
+}
+
+#[test_case]
+fn sprite_handles_go_stale_once_freed() {
+  let mut sprites = SpriteAllocator::new();
+  assert!(sprites.is_empty());
+  let a = sprites.alloc().unwrap();
+  let b = sprites.alloc().unwrap();
+  assert_eq!((a.index(), b.index()), (0, 1));
+  assert_eq!(sprites.len(), 2);
+  assert_eq!(sprites.get(a), Some(&ObjAttr::HIDDEN));
+
+  sprites.get_mut(a).unwrap().set_tile_id(5);
+  assert!(sprites.free(a));
+  assert!(!sprites.free(a));
+  assert_eq!(*sprites.oam().obj(0), ObjAttr::HIDDEN);
+
+  // the freed slot is reused, but the old handle doesn't reach the new object.
+  let c = sprites.alloc().unwrap();
+  assert_eq!(c.index(), 0);
+  assert_ne!(a, c);
+  assert!(!sprites.is_valid(a) && sprites.is_valid(c));
+  assert_eq!(sprites.get(a), None);
+  assert!(sprites.get_mut(a).is_none());
+  assert!(!sprites.set_obj_window(a));
+
+  // a run skips over the one free slot, at 2.
+  let d = sprites.alloc().unwrap();
+  let e = sprites.alloc().unwrap();
+  sprites.free(d);
+  let run = sprites.alloc_run::<3>().unwrap();
+  assert_eq!(run.map(|h| h.index()), [4, 5, 6]);
+  assert_eq!(sprites.alloc_run::<0>(), None);
+  assert_eq!(sprites.len(), 6);
+
+  sprites.reset();
+  assert!(sprites.is_empty());
+  assert!(![b, c, e].into_iter().any(|h| sprites.is_valid(h)));
+  while sprites.alloc().is_some() {}
+  assert_eq!(sprites.len(), 128);
+  assert_eq!(sprites.alloc_run::<1>(), None);
+}
+
+#[test_case]
+fn animations_step_loop_and_finish() {
+  static ONCE: Animation = Animation::new(&[(4, 2), (5, 1), (6, 0)], false);
+  static SPIN: Animation = Animation::new(&[(0, 2), (1, 2)], true);
+  static HOLD: Animation = Animation::new(&[(3, 1), (3, 1)], true);
+  // a duration of 0 counts as 1.
+  assert_eq!(ONCE.total_duration(), 4);
+
+  let mut player = AnimationPlayer::new();
+  assert_eq!(player.tick(), None);
+  player.play(&ONCE);
+  let ticks: [Option<u16>; 6] = core::array::from_fn(|_| player.tick());
+  assert_eq!(ticks, [Some(4), None, Some(5), Some(6), None, None]);
+  assert!(player.is_finished());
+  assert_eq!(player.current_tile(), Some(6));
+  player.restart();
+  assert!(!player.is_finished());
+  assert_eq!(player.tick(), Some(4));
+  // skipping far ahead still finishes on the last frame.
+  assert_eq!(player.advance(1000), Some(6));
+  assert!(player.is_finished());
+
+  player.play(&SPIN);
+  assert_eq!(player.advance(1), Some(0));
+  assert_eq!(player.advance(2), Some(1));
+  // 9 frames is just over two loops, ending back on tile 1.
+  assert_eq!(player.advance(9), Some(1));
+  assert!(!player.is_finished());
+
+  player.pause();
+  assert!(player.is_paused());
+  assert_eq!(player.advance(5), None);
+  assert_eq!(player.current_tile(), Some(1));
+  player.resume();
+  assert_eq!(player.tick(), Some(0));
+
+  // moving to a frame with the same tile isn't a change.
+  player.play(&HOLD);
+  assert_eq!(player.tick(), Some(3));
+  assert_eq!(player.tick(), None);
+  assert_eq!(player.tick(), None);
+
+  player.stop();
+  assert_eq!(player.animation(), None);
+  assert_eq!(player.current_tile(), None);
+  assert_eq!(player.tick(), None);
+}
+
+#[test_case]
+fn wide_timer_carries_into_the_upper_timer() {
+  // timer 3 is the runner's timeout, so this test keeps to timers 0 and 1.
+  let wide = WideTimer::new(Timer::Timer0);
+  assert_eq!((wide.low(), wide.high()), (Timer::Timer0, Timer::Timer1));
+  assert_eq!(WideTimer::new(Timer::Timer2).high(), Timer::Timer3);
+
+  wide.start(TimerScale::_1);
+  assert!(wide.low().is_running() && wide.high().is_running());
+  let mut last = wide.read();
+  assert!(last < 0x1000, "{last:#X} just after starting");
+  // counting well past a few low timer overflows, the count never goes back.
+  while last < 0x3_0000 {
+    let now = wide.read();
+    assert!(now >= last, "{now:#X} read after {last:#X}");
+    last = now;
+  }
+  wide.stop();
+  assert!(!wide.low().is_running() && !wide.high().is_running());
+  let stopped = wide.read();
+  assert!(stopped >= last);
+  assert_eq!(wide.read(), stopped);
+
+  wide.start(TimerScale::_1);
+  assert!(wide.read() < 0x1000);
+  wide.stop();
+}
+
+#[test_case]
+fn bios_div_matches_checked_div() {
+  let cases = [(7, 2), (-7, 2), (7, -2), (-7, -2), (0, 5), (i32::MIN, 1)];
+  for (n, d) in cases {
+    let expected = DivOutput {
+      quotient: n / d,
+      remainder: n % d,
+      abs_quotient: (n / d).unsigned_abs(),
+    };
+    assert_eq!(Div(n, d), Some(expected), "{n} / {d}");
+    assert_eq!(DivArm(d, n), Some(expected), "{n} / {d} (arm)");
+  }
+  assert_eq!(Div(1, 0), None);
+  assert_eq!(Div(i32::MIN, -1), None);
+  assert_eq!(DivArm(0, 1), None);
+  assert_eq!(DivArm(-1, i32::MIN), None);
+}
+
+#[test_case]
+fn note_rates_agree_with_the_bios() {
+  // A4 and the notes around it, rounded to the nearest Hz.
+  for (offset, hz) in [(-24, 110), (-12, 220), (-9, 262), (-1, 415), (0, 440)]
+    .into_iter()
+    .chain([(1, 466), (7, 659), (12, 880), (24, 1760)])
+  {
+    assert_eq!(note_to_sample_rate(440, offset), hz, "{offset} semitones");
+  }
+  assert_eq!(note_to_sample_rate(u32::MAX, 127), u32::MAX);
+
+  // both ways of moving from the base key (60) agree, to within 0.1%.
+  let wave =
+    WaveData { kind: 0, stat: 0, freq: 13379 * 1024, loop_start: 0, size: 0 };
+  for (key, offset) in [(60, 0), (72, 12), (67, 7), (48, -12)] {
+    let bios = midi_key_to_freq(&wave, key, 0);
+    let table = note_to_sample_rate(13379, offset);
+    assert!(bios.abs_diff(table) <= table / 1000, "key {key}: {bios}, {table}");
+  }
+
+  let tone = ToneFrequency::new();
+  assert_eq!(tone.with_hz(63), None);
+  assert_eq!(tone.with_hz(131073), None);
+  assert_eq!(tone.with_hz(64).map(|f| f.frequency()), Some(0));
+  assert_eq!(tone.with_hz(440).map(|f| f.frequency()), Some(1750));
+  assert_eq!(tone.with_hz(131072).map(|f| f.frequency()), Some(2047));
+}
+
+#[test_case]
+fn tone2_plays_notes_for_their_length() {
+  assert_eq!((Note::C3.to_midi_key(), Note::A4.to_midi_key()), (48, 69));
+  assert_eq!(Note::B7.to_midi_key(), 107);
+  assert_eq!(Note::A4.to_tone_period(), 1750);
+  // the table is the rate of each note's frequency, give or take the rounding
+  // of that frequency to whole Hz.
+  let notes = [Note::C3, Note::A3, Note::C4, Note::FSharp5, Note::C6, Note::B7];
+  for note in notes {
+    let hz = note_to_sample_rate(440, note.to_midi_key() as i8 - 69);
+    let rate = rate_from_hz(hz).unwrap();
+    assert!(note.to_tone_period().abs_diff(rate) <= 1, "{note:?}");
+  }
+  assert_eq!(Envelope::new(16, 0, false), None);
+  assert_eq!(Envelope::new(15, 8, false), None);
+  assert_eq!(Envelope::constant(0x1F).volume(), 15);
+  assert_eq!(TonePattern::new().with_length_256ths(0).length(), 63);
+  assert_eq!(TonePattern::new().with_length_256ths(200).length(), 0);
+
+  let sound = SOUND_ENABLED.read();
+  gba::sound::enable();
+  play_tone2(
+    Note::A4.to_tone_period(),
+    Duty::_50,
+    Envelope::constant(15),
+    None,
+  );
+  assert!(SOUND_ENABLED.read().tone2_playing());
+  spin_until_vblank();
+  assert!(SOUND_ENABLED.read().tone2_playing(), "a continuous tone stopped");
+  stop_tone2();
+  assert!(!SOUND_ENABLED.read().tone2_playing());
+
+  // 1/256th of a second is over well within two frames.
+  play_tone2(
+    Note::C5.to_tone_period(),
+    Duty::_25,
+    Envelope::constant(8),
+    Some(1),
+  );
+  assert!(SOUND_ENABLED.read().tone2_playing());
+  spin_until_vblank();
+  spin_until_vblank();
+  assert!(!SOUND_ENABLED.read().tone2_playing(), "a 1/256 s tone kept going");
+  SOUND_ENABLED.write(sound);
+}
+
+#[test_case]
+fn fixed_point_math_matches_f64() {
+  const ONE_AND_A_QUARTER: i32fx16 = i32fx16::from_f32(1.25);
+  fn floor(f: f64) -> f64 {
+    let t = f as i64 as f64;
+    if t > f {
+      t - 1.0
+    } else {
+      t
+    }
+  }
+
+  assert_eq!(ONE_AND_A_QUARTER.to_bits(), 0x1_4000);
+  assert_eq!(i32fx16::from_f32(-0.5).to_bits(), -0x8000);
+  assert_eq!(i32fx16::from_int(-3).to_bits(), -3 << 16);
+  assert_eq!(i32fx16::from_int(3).to_f32(), 3.0);
+  // -0.5 * 1/256 truncates to -1/256, but rounds to 0.
+  let half = i32fx8::from_bits(-128);
+  let tiny = i32fx8::from_bits(1);
+  assert_eq!(half.mul(tiny).to_bits(), -1);
+  assert_eq!(half.mul_rounded(tiny).to_bits(), 0);
+
+  let mut rng = Lcg32::new(0x1234_5678);
+  for _ in 0..200 {
+    let a = (rng.next_u32() as i32) >> 10;
+    let b = (rng.next_u32() as i32) >> 14;
+    let (fa, fb) = (i32fx16::from_bits(a), i32fx16::from_bits(b));
+    let exact = (f64::from(a) / 65536.0) * (f64::from(b) / 65536.0) * 65536.0;
+    if exact.abs() < 2.0e9 {
+      assert_eq!(f64::from(fa.mul(fb).to_bits()), floor(exact), "{a} * {b}");
+      assert_eq!(f64::from(fa.mul_rounded(fb).to_bits()), floor(exact + 0.5));
+    }
+    let clamped = floor(exact).clamp(f64::from(i32::MIN), f64::from(i32::MAX));
+    assert_eq!(f64::from(fa.saturating_mul(fb).to_bits()), clamped);
+    if b != 0 {
+      let quotient = f64::from(a) / f64::from(b) * 65536.0;
+      if quotient.abs() < 2.0e9 {
+        assert_eq!(fa.div(fb).to_bits(), quotient as i32, "{a} / {b}");
+      }
+    }
+    assert_eq!(f64::from(fa.int()), floor(f64::from(a) / 65536.0));
+    let to_8 = floor(f64::from(a) / 256.0);
+    assert_eq!(f64::from(fa.to_i32fx8().to_bits()), to_8);
+    let to_16 = to_8.clamp(f64::from(i16::MIN), f64::from(i16::MAX));
+    assert_eq!(f64::from(fa.to_i16fx8().to_bits()), to_16);
+
+    let (a, b) = (rng.next_u32() as i16, (rng.next_u32() >> 16) as i16);
+    let (fa, fb) = (i16fx8::from_bits(a), i16fx8::from_bits(b));
+    let exact = floor(f64::from(a) * f64::from(b) / 256.0);
+    assert_eq!(fa.wrapping_mul(fb).to_bits(), exact as i64 as i16);
+    let clamped = exact.clamp(f64::from(i16::MIN), f64::from(i16::MAX));
+    assert_eq!(f64::from(fa.saturating_mul(fb).to_bits()), clamped);
+    assert_eq!(
+      i32::from(fa.saturating_add(fb).to_bits()),
+      (i32::from(a) + i32::from(b)).clamp(-32768, 32767)
+    );
+    assert_eq!(fa.widen().to_bits(), i32::from(a));
+  }
+}
+
+#[test_case]
+fn sin_and_cos_stay_within_two_bits() {
+  const PI: f64 = core::f64::consts::PI;
+  // the exact sine of a binary angle in `i16fx14` bits, by its Taylor series
+  // (folded into -pi/2..=pi/2, where it converges quickly).
+  fn exact_bits(angle: u16) -> f64 {
+    let x = f64::from(angle as i16) / 32768.0 * PI;
+    let x = if x > PI / 2.0 {
+      PI - x
+    } else if x < -PI / 2.0 {
+      -PI - x
+    } else {
+      x
+    };
+    let mut term = x;
+    let mut total = x;
+    for n in 1..12 {
+      term *= -x * x / f64::from((2 * n) * (2 * n + 1));
+      total += term;
+    }
+    total * 16384.0
+  }
+
+  for (angle, want) in
+    [(0, 0), (0x4000, 0x4000), (0x8000, 0), (0xC000, -0x4000)]
+  {
+    assert_eq!(sin(angle).to_bits(), want, "sin({angle:#X})");
+  }
+  let quarter = AffineMatrix::from_rotation(0x4000);
+  assert_eq!(
+    [quarter.pa, quarter.pb, quarter.pc, quarter.pd].map(|p| p.to_bits()),
+    [0, -256, 256, 0]
+  );
+
+  // every 61st angle hits every offset between the table's steps.
+  for angle in (0..=u16::MAX).step_by(61) {
+    let sin_error = (f64::from(sin(angle).to_bits()) - exact_bits(angle)).abs();
+    let cos_exact = exact_bits(angle.wrapping_add(0x4000));
+    let cos_error = (f64::from(cos(angle).to_bits()) - cos_exact).abs();
+    assert!(sin_error <= 2.0, "sin({angle:#X}) is {sin_error} bits off");
+    assert!(cos_error <= 2.0, "cos({angle:#X}) is {cos_error} bits off");
+  }
+
+  for step in 0..=u8::MAX {
+    let angle = u16::from(step) << 8;
+    let back = ArcTan2(cos(angle), sin(angle));
+    let error = (back.wrapping_sub(angle) as i16).unsigned_abs();
+    assert!(error < 0x100, "ArcTan2 gave {back:#X} for {angle:#X}");
+  }
+}
+
+#[test_case]
+fn random_generators_are_even_and_resumable() {
+  const SAMPLES: u32 = 12_000;
+  // fills `N` buckets from `roll`, and checks each is within 15% of even.
+  fn check_buckets<const N: usize>(
+    name: &str, mut roll: impl FnMut() -> usize,
+  ) {
+    let mut buckets = [0_u32; N];
+    for _ in 0..SAMPLES {
+      let i = roll();
+      assert!(i < N, "{name} gave {i}, out of 0..{N}");
+      buckets[i] += 1;
+    }
+    let even = SAMPLES / N as u32;
+    for (i, &count) in buckets.iter().enumerate() {
+      assert!(count.abs_diff(even) < even * 15 / 100, "{name} {i}: {count}");
+    }
+  }
+  fn check_generator(rng: &mut impl Gen32, name: &str) {
+    check_buckets::<6>(name, || usize::from(rng.next_bounded(6)));
+    check_buckets::<10>(name, || rng.next_bounded_u32(10) as usize);
+    // a bound just over half the range is where modulo bias would show most.
+    let big = (u32::MAX / 2) + 2;
+    check_buckets::<2>(name, || {
+      (rng.next_bounded_u32(big) >= big / 2) as usize
+    });
+
+    let hits = (0..SAMPLES).filter(|_| rng.chance(1, 4)).count() as u32;
+    assert!(hits.abs_diff(SAMPLES / 4) < SAMPLES / 40, "{name} chance: {hits}");
+    assert!((0..100).all(|_| rng.chance(5, 5)));
+    assert!(!(0..100).any(|_| rng.chance(0, 5)));
+  }
+
+  check_generator(&mut Lcg32::new(7), "Lcg32");
+  check_generator(&mut Xoshiro128::from_seed(7), "Xoshiro128");
+
+  // a saved state picks up with the same outputs.
+  let mut rng = Xoshiro128::from_seed(0xC0FFEE);
+  for _ in 0..37 {
+    rng.next_u32();
+  }
+  let mut resumed = Xoshiro128::from_state(rng.to_state());
+  assert!((0..100).all(|_| rng.next_u32() == resumed.next_u32()));
+  assert_ne!(Xoshiro128::from_state([0; 4]).to_state(), [0; 4]);
+
+  // only the first press seeds.
+  let mut seeder = KeypressSeeder::new();
+  assert!(seeder.update(KeyInput::new(), 123).is_none());
+  let press = KeyInput::new().with_a(true);
+  assert!(seeder.update(press, 456).is_some());
+  assert!(seeder.update(press, 789).is_none());
+}
+
+#[test_case]
+fn vec2_and_rect_math_handles_the_edges() {
+  let fx8 = i32fx8::from_bits;
+  let max = Vec2::new(i16fx8::from_bits(i16::MAX), i16fx8::from_bits(i16::MIN));
+  let one = Vec2::new(i16fx8::from_bits(1), i16fx8::from_bits(1));
+  let bits = |v: Vec2<i16fx8>| (v.x.to_bits(), v.y.to_bits());
+  assert_eq!(bits(max.wrapping_add(one)), (i16::MIN, -32767));
+  assert_eq!(bits(max.saturating_add(one)), (i16::MAX, -32767));
+  assert_eq!(bits(max.saturating_sub(one)), (i16::MAX - 1, i16::MIN));
+  let big = Vec2::new(i32::MAX, i32::MIN);
+  assert_eq!(big.wrapping_add(Vec2::new(1, -1)), Vec2::new(i32::MIN, i32::MAX));
+  assert_eq!(big.saturating_add(Vec2::new(1, -1)), big);
+
+  let v = Vec2::new(fx8(0x180), fx8(-0x180));
+  assert_eq!(v.to_pixels_floor(), Vec2::new(1, -2));
+  assert_eq!(v.to_pixels_nearest(), Vec2::new(2, -1));
+  let v = Vec2::new(fx8(0x17F), fx8(-0x181));
+  assert_eq!(v.to_pixels_nearest(), Vec2::new(1, -2));
+  assert_eq!(
+    Vec2::new(3, -4).to_fixed::<8>(),
+    Vec2::new(fx8(0x300), fx8(-0x400))
+  );
+
+  let v = Vec2::new(fx8(3 << 8), fx8(-4 << 8));
+  assert_eq!(v.dot(v), v.length_squared());
+  assert_eq!(v.length_squared().to_bits(), 25 << 8);
+  assert_eq!(v.length().to_bits(), 5 << 8);
+  let unit = v.normalize_fast();
+  assert_eq!((unit.x.to_bits(), unit.y.to_bits()), (153, -204));
+  // too big to square in 32 bits, which `length` still handles.
+  let far = Vec2::new(fx8(3 << 20), fx8(4 << 20));
+  assert_eq!(far.length().to_bits(), 5 << 20);
+  let zero = Vec2::new(fx8(0), fx8(0));
+  assert_eq!(zero.normalize_fast(), zero);
+
+  let a = Rect::new(10, 10, 20, 10);
+  assert!(a.contains_point(Vec2::new(29, 19)));
+  assert!(!a.contains_point(Vec2::new(30, 19)));
+  assert!(a.intersects(Rect::new(29, 19, 5, 5)));
+  assert!(!a.intersects(Rect::new(30, 10, 5, 5)));
+  assert!(!a.intersects(Rect::new(15, 15, 0, 5)));
+  let overlap = a.intersection(Rect::new(0, 15, 15, 100));
+  assert_eq!(overlap, Some(Rect::new(10, 15, 5, 5)));
+  let sprite = Rect::new(236, -3, 8, 8);
+  assert_eq!(sprite.clamp_within(Rect::SCREEN), Rect::new(232, 0, 8, 8));
+  let too_wide = Rect::new(-50, 100, 300, 8);
+  assert_eq!(too_wide.clamp_within(Rect::SCREEN), Rect::new(0, 100, 300, 8));
+  assert_eq!(Rect::new(i32::MAX - 4, 0, 10, 1).right(), i32::MAX);
+
+  // filled rectangles are clipped to the screen with `Rect`.
+  mode3::clear_to(Color::BLUE);
+  mode3::rect_filled(236, -3, 8, 8, Color::RED);
+  mode3::rect_filled(-5, -5, 3, 3, Color::RED);
+  mode3::rect_filled(i32::MAX - 4, 0, 10, 1, Color::RED);
+  assert_eq!(VIDEO3_VRAM.index(236, 0).read(), Color::RED);
+  assert_eq!(VIDEO3_VRAM.index(239, 4).read(), Color::RED);
+  assert_eq!(VIDEO3_VRAM.index(235, 0).read(), Color::BLUE);
+  assert_eq!(VIDEO3_VRAM.index(239, 5).read(), Color::BLUE);
+  assert_eq!(VIDEO3_VRAM.index(0, 0).read(), Color::BLUE);
+}
+
+#[test_case]
+fn affine_matrices_compose_and_invert() {
+  let fx8 = |bits: u32| i16fx8::from_bits(bits as i16);
+  // every entry has to be within `bits` of the identity.
+  let assert_near_identity = |m: AffineMatrix, bits: i16, what: &str| {
+    let identity = AffineMatrix::IDENTITY.to_obj_params();
+    for (got, want) in m.to_obj_params().into_iter().zip(identity) {
+      assert!((got - want).abs() <= bits, "{what}: {m:?}");
+    }
+  };
+
+  let quarter = AffineMatrix::from_rotation(0x4000);
+  let full = quarter.then(quarter).then(quarter).then(quarter);
+  assert_near_identity(full, 1, "four quarter turns");
+
+  let mut rng = Xoshiro128::from_seed(98);
+  for _ in 0..200 {
+    // scales from 0.5 to 2.0, and shears up to about 1.0 either way.
+    let sx = fx8(128 + rng.next_bounded_u32(385));
+    let sy = fx8(128 + rng.next_bounded_u32(385));
+    let angle = rng.next_u16();
+    let shear = fx8(rng.next_bounded_u32(512)).sub(fx8(256));
+    let m = AffineMatrix::from_scale(sx, sy)
+      .then(AffineMatrix::from_rotation(angle))
+      .then(AffineMatrix::from_shear_x(shear));
+    let inverse = m.inverse().expect("a scale of 0.5 or more has an inverse");
+    assert_near_identity(m.then(inverse), 2, "m * inverse");
+
+    let together = AffineMatrix::from_scale(sx, sy)
+      .then(AffineMatrix::from_rotation(angle))
+      .to_obj_params();
+    let direct = AffineMatrix::from_scale_rotation(sx, sy, angle);
+    for (a, b) in together.into_iter().zip(direct.to_obj_params()) {
+      assert!((a - b).abs() <= 4, "then vs from_scale_rotation");
+    }
+
+    // the hardware maps screen pixel `s` to `M * s + (x, y)`.
+    let display = Vec2::new(120, 80);
+    let texture = Vec2::new(rng.next_bounded_u32(512) as i32, 64);
+    let params = m.to_bg_params(display, texture);
+    let [a, b, c, d] = m.to_obj_params().map(i32::from);
+    let tx = a * display.x + b * display.y + params.x.to_bits();
+    let ty = c * display.x + d * display.y + params.y.to_bits();
+    assert_eq!((tx, ty), (texture.x << 8, texture.y << 8));
+  }
+
+  // a scale of 0 is the smallest scale, and the entries clamp to fit.
+  let squashed = AffineMatrix::from_scale_rotation(fx8(0), fx8(0), 0x8000);
+  assert_eq!(squashed.to_obj_params(), [i16::MIN, 0, 0, i16::MIN]);
+  let flat = AffineMatrix::from_scale(fx8(256), fx8(256))
+    .then(AffineMatrix { pd: fx8(0), ..AffineMatrix::IDENTITY });
+  assert!(flat.inverse().is_none(), "a zero determinant has no inverse");
+  let tiny = AffineMatrix { pa: fx8(1), pd: fx8(1), ..AffineMatrix::IDENTITY };
+  assert!(tiny.inverse().is_none(), "the inverse doesn't fit in 8.8");
+}
+
+#[test_case]
+fn interpolation_and_easing_match_f64() {
+  fn floor(f: f64) -> f64 {
+    let t = f as i64 as f64;
+    if t > f {
+      t - 1.0
+    } else {
+      t
+    }
+  }
+  fn check_lerp(a: i32, b: i32, t: i32) {
+    let (fa, fb) = (f64::from(a), f64::from(b));
+    let exact = fa + (fb - fa) * f64::from(t) / 65536.0;
+    let want = floor(exact).clamp(f64::from(i32::MIN), f64::from(i32::MAX));
+    let t_fx = i32fx16::from_bits(t);
+    assert_eq!(f64::from(lerp_unclamped(a, b, t_fx)), want, "{a} {b} {t}");
+    let clamped = t.clamp(0, 1 << 16);
+    let exact = fa + (fb - fa) * f64::from(clamped) / 65536.0;
+    assert_eq!(f64::from(lerp(a, b, t_fx)), floor(exact), "{a} {b} {t}");
+    if a != b {
+      let inverse = inverse_lerp(a, b, lerp(a, b, i32fx16::from_bits(clamped)));
+      // going back only loses what the rounding down lost.
+      let step = 65536.0 / (fb - fa).abs();
+      let off = f64::from(inverse.to_bits() - clamped).abs();
+      assert!(off <= step + 1.0, "inverse_lerp {a} {b} {t}: {off}");
+    }
+  }
+  fn check_curve(name: &str, curve: fn(i32fx16) -> i32fx16, f: fn(f64) -> f64) {
+    for bits in (-0x100..=0x1_0100).step_by(97) {
+      let t = f64::from(bits.clamp(0, 1 << 16)) / 65536.0;
+      let want = f(t) * 65536.0;
+      let got = f64::from(curve(i32fx16::from_bits(bits)).to_bits());
+      assert!((got - want).abs() <= 1.0, "{name}({bits:#X}): {got} vs {want}");
+    }
+    assert_eq!(curve(i32fx16::from_bits(0)).to_bits(), 0, "{name}(0)");
+    assert_eq!(curve(ONE), ONE, "{name}(1)");
+  }
+
+  let mut rng = Xoshiro128::from_seed(99);
+  for _ in 0..500 {
+    let a = (rng.next_u32() as i32) >> rng.next_bounded(24);
+    let b = (rng.next_u32() as i32) >> rng.next_bounded(24);
+    let t = (rng.next_u32() as i32) >> (12 + rng.next_bounded(8));
+    check_lerp(a, b, t);
+  }
+  check_lerp(i32::MIN, i32::MAX, 1 << 30);
+
+  check_curve("ease_in_quad", ease_in_quad, |t| t * t);
+  check_curve("ease_out_quad", ease_out_quad, |t| 1.0 - (1.0 - t) * (1.0 - t));
+  check_curve("smoothstep", smoothstep, |t| t * t * (3.0 - 2.0 * t));
+  check_curve("ease_in_out_cubic", ease_in_out_cubic, |t| {
+    if t < 0.5 {
+      4.0 * t * t * t
+    } else {
+      let u = 2.0 - 2.0 * t;
+      1.0 - u * u * u / 2.0
+    }
+  });
+
+  // fixed-point values saturate rather than wrap.
+  let top = i16fx8::from_bits(i16::MAX - 10);
+  let over =
+    lerp_unclamped(i16fx8::from_bits(0), top, i32fx16::from_bits(2 << 16));
+  assert_eq!(over.to_bits(), i16::MAX);
+  // remap is exact where a `t` in the middle couldn't be.
+  assert_eq!(remap(1_u16, 0, 3, 0_u16, 15), 5);
+  assert_eq!(remap(-5_i32, 0, 10, 100_i32, 200), 100);
+  assert_eq!(remap(7_i32, 7, 7, 0_u8, 9), 9);
+
+  let mut tween = Tween::new(10_i32, 20, 4, Easing::Linear);
+  let steps: [i32; 5] = core::array::from_fn(|_| tween.tick());
+  assert_eq!(steps, [12, 15, 17, 20, 20]);
+  assert!(tween.is_finished());
+  tween.reverse();
+  assert_eq!(tween.tick(), 17);
+  assert_eq!(Tween::new(1_u8, 2, 0, Easing::InQuad).value(), 2);
+}
+
+#[test_case]
+fn obj_and_sound_fields_pack_into_place() {
+  use ObjSize::*;
+  let attr0 = ObjAttr0::new().with_y(100).with_bpp8(true);
+  let styles = [
+    ObjDisplayStyle::Normal,
+    ObjDisplayStyle::Affine,
+    ObjDisplayStyle::NotDisplayed,
+    ObjDisplayStyle::DoubleSizeAffine,
+  ];
+  round_trip(attr0, &styles, ObjAttr0::with_style, ObjAttr0::style);
+  let modes = [
+    ObjEffectMode::Normal,
+    ObjEffectMode::SemiTransparent,
+    ObjEffectMode::Window,
+  ];
+  round_trip(attr0, &modes, ObjAttr0::with_mode, ObjAttr0::mode);
+  let shapes = [ObjShape::Square, ObjShape::Horizontal, ObjShape::Vertical];
+  round_trip(attr0, &shapes, ObjAttr0::with_shape, ObjAttr0::shape);
+  assert_eq!(attr0.with_y(0x1FF).y(), 0xFF);
+  assert!(attr0.with_y(0x1FF).bpp8());
+  assert_eq!(ObjAttr0::STYLE_MASK, 0b11 << 8);
+  assert_eq!(ObjAttr0::MODE_MASK, 0b11 << 10);
+  assert_eq!(ObjAttr0::SHAPE_MASK, 0b11 << 14);
+
+  let attr1 = ObjAttr1::new().with_x(511).with_affine_index(31);
+  assert_eq!((attr1.x(), attr1.affine_index(), attr1.size()), (511, 31, 0));
+  assert_eq!(attr1.with_x(512).x(), 0);
+  assert_eq!(ObjAttr1::X_MASK, 0x1FF);
+  assert_eq!(ObjAttr1::AFFINE_INDEX_MASK, 0b1_1111 << 9);
+  // the size is split between the shape in attr0 and the size in attr1.
+  let sizes = [
+    _8x8, _16x16, _32x32, _64x64, _16x8, _32x8, _32x16, _64x32, _8x16, _8x32,
+    _16x32, _32x64,
+  ];
+  round_trip(ObjAttr::new(), &sizes, ObjAttr::with_size, ObjAttr::size);
+
+  let mix = SoundMix::new().with_sound_a_full(true).with_sound_b_reset(true);
+  let psg = [PsgMix::_25, PsgMix::_50, PsgMix::_100];
+  round_trip(mix, &psg, SoundMix::with_psg, SoundMix::psg);
+  let noise = NoiseFrequency::new().with_r(5).with_s(0xC).with_counter7(true);
+  assert_eq!((noise.r(), noise.s(), noise.counter7()), (5, 0xC, true));
+  assert_eq!(noise.with_r(0b1111).s(), 0xC);
+  assert_eq!(NoiseFrequency::R_MASK, 0b111);
+  assert_eq!(NoiseFrequency::S_MASK, 0b1111 << 4);
+  let volume =
+    LeftRightVolume::new().with_left_volume(9).with_tone1_right(true);
+  assert_eq!(volume.left_volume(), 1);
+  assert!(volume.tone1_right() && !volume.tone1_left());
+}
+
+#[test_case]
+fn periodic_irq_rejects_unreachable_rates() {
+  extern "C" fn never(_: IrqBits) {}
+  let control = TIMER_CONTROL.index(0).read();
+  let ie = IE.read();
+  for hz in [0, CPU_CYCLES_PER_SECOND + 1] {
+    assert_eq!(periodic_irq(Timer::Timer0, hz, never), Err(UnreachableRate));
+  }
+  // nothing was changed.
+  assert_eq!(TIMER_CONTROL.index(0).read(), control);
+  assert_eq!(IE.read(), ie);
+}
+
+#[test_case]
+fn ms_to_cycles_is_exact_and_saturates() {
+  assert_eq!(ms_to_cycles(0), 0);
+  assert_eq!(ms_to_cycles(1), 16_777);
+  assert_eq!(ms_to_cycles(125), CPU_CYCLES_PER_SECOND / 8);
+  assert_eq!(ms_to_cycles(1000), CPU_CYCLES_PER_SECOND);
+  // with a remainder past the 125 ms chunks: 16,777,216 * 1.999 s.
+  assert_eq!(ms_to_cycles(1999), 33_537_654);
+  // the largest whole number of seconds that fits, then just past it.
+  assert_eq!(ms_to_cycles(255_000), 255 * CPU_CYCLES_PER_SECOND);
+  assert_eq!(ms_to_cycles(255_999), 4_294_950_518);
+  assert_eq!(ms_to_cycles(256_000), u32::MAX);
+  // a large value goes through the 125 ms chunks without overflowing.
+  assert_eq!(ms_to_cycles(u32::MAX), u32::MAX);
+}
+
+#[test_case]
+fn busy_wait_cycles_uses_then_stops_the_delay_timer() {
+  assert!(!Timer::Timer1.is_running());
+  busy_wait_cycles(100_000);
+  delay_ms(1);
+  assert!(!Timer::Timer1.is_running());
+}
+
+#[test_case]
+fn irq_free_nests_and_restores_ime() {
+  for outer in [false, true] {
+    IME.write(outer);
+    irq_free(|| {
+      assert!(!IME.read());
+      irq_free(|| {
+        assert!(!IME.read());
+        irq_free(|| assert!(!IME.read()));
+        assert!(!IME.read());
+      });
+      assert!(!IME.read());
+    });
+    assert_eq!(IME.read(), outer);
+  }
+}
+
+#[test_case]
+fn irq_mutex_can_be_borrowed_again_after_each_use() {
+  static COUNT: IrqMutex<u32> = IrqMutex::new(0);
+  IME.write(true);
+  for expected in 1..=3 {
+    assert_eq!(
+      COUNT.with(|count| {
+        *count += 1;
+        *count
+      }),
+      expected
+    );
+    assert!(IME.read());
+  }
+  COUNT.with_in_handler(|count| *count *= 10);
+  assert_eq!(irq_free(|| COUNT.with(|count| *count)), 30);
+  assert!(IME.read());
+}
+
+/// A guard value for the copy tests (not all one byte, so filling with it
+/// can't turn into a `memset`).
+const COPY_GUARD: u32 = 0xDEAD_BEEF;
+
+/// Word copy lengths that hit each edge of the size classes.
+const WORD_COPY_LENS: [usize; 10] = [
+  0,
+  1,
+  7,
+  8,
+  COPY_DMA_MIN_WORDS - 1,
+  COPY_DMA_MIN_WORDS,
+  COPY_FAST_SET_MIN_WORDS - 1,
+  COPY_FAST_SET_MIN_WORDS,
+  COPY_FAST_SET_MIN_WORDS + 1,
+  2 * COPY_FAST_SET_MIN_WORDS + 7,
+];
+
+/// Checks `copy_words_with` for every length, with a guard word on each side.
+fn check_word_copies(dest: &mut [u32]) {
+  let src: [u32; 80] = core::array::from_fn(|i| 0xC0DE_0000 | i as u32);
+  for len in WORD_COPY_LENS {
+    for allow_dma in [false, true] {
+      dest.fill(COPY_GUARD);
+      copy_words_with(&src[..len], &mut dest[1..][..len], allow_dma);
+      for (i, &word) in dest.iter().enumerate() {
+        let expected = match i.checked_sub(1) {
+          Some(offset) if offset < len => src[offset],
+          _ => COPY_GUARD,
+        };
+        assert_eq!(word, expected, "len {len} dma {allow_dma} at {i}");
+      }
+    }
+  }
+}
+
+/// Checks `copy_halfwords_with` for every length and pair of alignments, with
+/// guard halfwords around the copy.
+fn check_halfword_copies(dest: &mut [u16]) {
+  let mut src = Align4([0_u16; 160]);
+  for (i, h) in src.0.iter_mut().enumerate() {
+    *h = 0xA000 | i as u16;
+  }
+  let guard = COPY_GUARD as u16;
+  let lens = [
+    0,
+    1,
+    2,
+    3,
+    15,
+    16,
+    17,
+    2 * COPY_DMA_MIN_WORDS - 1,
+    2 * COPY_DMA_MIN_WORDS,
+    2 * COPY_FAST_SET_MIN_WORDS + 1,
+    2 * COPY_FAST_SET_MIN_WORDS + 3,
+  ];
+  // offsets of 0 and 1 halfwords give both the same and opposite parity.
+  for src_offset in 0..2 {
+    for dest_offset in 0..2 {
+      for len in lens {
+        for allow_dma in [false, true] {
+          dest.fill(guard);
+          let start = 2 + dest_offset;
+          copy_halfwords_with(
+            &src.0[src_offset..][..len],
+            &mut dest[start..][..len],
+            allow_dma,
+          );
+          for (i, &h) in dest.iter().enumerate() {
+            let expected = match i.checked_sub(start) {
+              Some(offset) if offset < len => src.0[src_offset + offset],
+              _ => guard,
+            };
+            assert_eq!(
+              h, expected,
+              "offsets {src_offset} {dest_offset} len {len} dma {allow_dma} at {i}"
+            );
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Some object VRAM (past what the other tests use), as words.
+fn vram_scratch_words() -> &'static mut [u32] {
+  // Safety: the tests run one at a time, and object VRAM takes word writes.
+  unsafe {
+    core::slice::from_raw_parts_mut(
+      OBJ_TILES.index(768).as_usize() as *mut u32,
+      100,
+    )
+  }
+}
+
+#[test_case]
+fn copy_words_handles_every_size_class() {
+  let mut ram = [0_u32; 100];
+  check_word_copies(&mut ram);
+  check_word_copies(vram_scratch_words());
+}
+
+#[test_case]
+fn copy_halfwords_handles_every_size_and_alignment() {
+  let mut ram = Align4([0_u16; 200]);
+  check_halfword_copies(&mut ram.0);
+  let vram = vram_scratch_words();
+  // Safety: the same memory, as twice as many halfwords.
+  let vram = unsafe {
+    core::slice::from_raw_parts_mut(vram.as_mut_ptr().cast(), vram.len() * 2)
+  };
+  check_halfword_copies(vram);
+}
+
+#[test_case]
+fn iter_pressed_goes_in_bit_order_and_skips_the_top_bits() {
+  // every key, plus all six of the unused bits "pressed" (low).
+  let all = KeyInput::from(0x0000);
+  let mut count = 0;
+  for (key, expected) in all.iter_pressed().zip(Key::ALL) {
+    assert_eq!(key, expected);
+    count += 1;
+  }
+  assert_eq!(count, Key::ALL.len());
+  // only the unused bits low: nothing counts as pressed.
+  assert_eq!(KeyInput::from(0x03FF).iter_pressed().next(), None);
+  let lr = KeyInput::from_keys(&[Key::L, Key::R]);
+  assert!(lr.iter_pressed().eq([Key::R, Key::L]));
+}
+
+#[test_case]
+fn key_input_display_joins_the_key_names() {
+  use core::fmt::Write;
+  let mut text = ArrayString::<80>::new();
+  let mut check = |keys: KeyInput, expected: &str| {
+    text.clear();
+    write!(text, "{keys}").unwrap();
+    assert_eq!(text.as_str(), expected);
+  };
+  check(KeyInput::new(), "NONE");
+  check(KeyInput::from(0x03FF), "NONE");
+  check(KeyInput::new().with_start(true), "START");
+  check(KeyInput::from_keys(&[Key::L, Key::A, Key::Up]), "A+UP+L");
+  check(KeyInput::from(0x0000), "A+B+SELECT+START+RIGHT+LEFT+UP+DOWN+R+L");
+}
+
+#[test_case]
+fn key_control_from_keys_only_sets_key_and_irq_bits() {
+  // bits 10-15 being low in the input would read as "pressed".
+  let all = KeyInput::from(0x0000);
+  let every_key = KeyControl::new()
+    .with_a(true)
+    .with_b(true)
+    .with_select(true)
+    .with_start(true)
+    .with_right(true)
+    .with_left(true)
+    .with_up(true)
+    .with_down(true)
+    .with_r(true)
+    .with_l(true)
+    .with_irq_enabled(true);
+  assert_eq!(KeyControl::from_keys(all, false), every_key);
+  assert_eq!(KeyControl::from_keys(all, true), every_key.with_irq_all(true));
+  assert_eq!(KeyControl::any_key(), every_key);
+  assert_eq!(
+    KeyControl::from_keys(KeyInput::from(0x03FF), true),
+    KeyControl::new().with_irq_enabled(true).with_irq_all(true)
+  );
+
+  // and the register itself reads back without bits 10-13.
+  let old = KEYCNT.read();
+  arm_keypad_interrupt(all, true);
+  let raw = unsafe { VolAddress::<u16, Safe, Safe>::new(0x0400_0132) };
+  assert_eq!(raw.read(), 0xC3FF);
+  assert!(IE.read().keypad());
+  KEYCNT.write(old);
+}
+
+/// If every pixel of the mode 3 bitmap is `color`.
+fn mode3_is_all(color: Color) -> bool {
+  (0..160).all(|y| (0..240).all(|x| VIDEO3_VRAM.index(x, y).read() == color))
+}
+
+#[test_case]
+fn mode3_shapes_clip_at_the_edges_and_corners() {
+  let px = |x, y| VIDEO3_VRAM.index(x, y).read();
+  mode3::clear_to(Color::BLUE);
+
+  // fully offscreen shapes draw nothing at all (and don't wrap around).
+  mode3::put_pixel(-1, 0, Color::RED);
+  mode3::put_pixel(240, 0, Color::RED);
+  mode3::put_pixel(0, 160, Color::RED);
+  mode3::line(-50, -10, 300, -10, Color::RED);
+  mode3::line(240, 0, 240, 159, Color::RED);
+  mode3::rect(-20, -20, 10, 10, Color::RED);
+  mode3::rect_filled(240, 160, 10, 10, Color::RED);
+  mode3::circle(-100, 80, 20, Color::RED);
+  mode3::circle_filled(120, 300, 50, Color::RED);
+  assert!(mode3_is_all(Color::BLUE));
+
+  // a line from off the top left corner, through it.
+  mode3::line(-10, -10, 10, 10, Color::RED);
+  assert_eq!(px(0, 0), Color::RED);
+  assert_eq!(px(10, 10), Color::RED);
+  assert_eq!(px(11, 11), Color::BLUE);
+  // a line running off the bottom right corner.
+  mode3::line(230, 150, 250, 170, Color::RED);
+  assert_eq!(px(239, 159), Color::RED);
+  assert_eq!(px(229, 149), Color::BLUE);
+
+  // only the visible edges of an outline are drawn.
+  mode3::rect(-2, -2, 5, 5, Color::GREEN);
+  assert_eq!(px(2, 0), Color::GREEN);
+  assert_eq!(px(0, 2), Color::GREEN);
+  assert_eq!(px(1, 1), Color::RED);
+  assert_eq!(px(3, 0), Color::BLUE);
+
+  // circles on the corners keep just the quarter that's on screen.
+  mode3::circle(239, 0, 4, Color::WHITE);
+  assert_eq!(px(235, 0), Color::WHITE);
+  assert_eq!(px(239, 4), Color::WHITE);
+  assert_eq!(px(238, 1), Color::BLUE);
+  mode3::circle_filled(0, 159, 3, Color::WHITE);
+  assert_eq!(px(0, 159), Color::WHITE);
+  assert_eq!(px(3, 159), Color::WHITE);
+  assert_eq!(px(0, 156), Color::WHITE);
+  assert_eq!(px(4, 159), Color::BLUE);
+}
+
+#[test_case]
+fn bg_scroll_wraps_around_at_512() {
+  let at = |x, y| BgScroll::new(x, y);
+  assert_eq!((at(512, 513).x(), at(512, 513).y()), (0, 1));
+  assert_eq!((at(1023, 1024).x(), at(1023, 1024).y()), (511, 0));
+  assert_eq!(at(u16::MAX, 0).x(), 511);
+
+  let mut scroll = BgScroll::new(0, 0);
+  scroll.scroll_by(-1, -512);
+  assert_eq!((scroll.x(), scroll.y()), (511, 0));
+  scroll.scroll_by(1, -1);
+  assert_eq!((scroll.x(), scroll.y()), (0, 511));
+  scroll.scroll_by(i16::MIN, i16::MAX);
+  // -32768 is a whole number of wraps, and 32767 is one short of one.
+  assert_eq!((scroll.x(), scroll.y()), (0, 510));
+  scroll.scroll_by(-1000, 1000);
+  assert_eq!((scroll.x(), scroll.y()), (24, 486));
+
+  scroll.set(600, 300);
+  assert_eq!(scroll, BgScroll::new(88, 300));
+}
+
+#[test_case]
+fn charblocks_and_screenblocks_share_background_vram() {
+  let addr = |a: usize| a - 0x0600_0000;
+  assert_eq!(addr(charblock_4bpp(0).index(0).as_usize()), 0);
+  assert_eq!(addr(charblock_4bpp(3).index(1).as_usize()), 3 * 0x4000 + 32);
+  assert_eq!(addr(charblock_8bpp(2).index(255).as_usize()), 0xBFC0);
+  assert_eq!(addr(text_screenblock(1).index(0, 0).as_usize()), 0x800);
+  assert_eq!(addr(text_screenblock(31).index(31, 31).as_usize()), 0xFFFE);
+  // charblock 1 starts right where screenblock 8 does.
+  assert_eq!(
+    charblock_4bpp(1).index(0).as_usize(),
+    text_screenblock(8).index(0, 0).as_usize()
+  );
+
+  assert_eq!(screenblocks_used_by_tiles(1, 0, 512, false), 8..16);
+  assert_eq!(screenblocks_used_by_tiles(0, 1, 1, false), 0..1);
+  assert_eq!(screenblocks_used_by_tiles(0, 63, 2, false), 0..2);
+  assert_eq!(screenblocks_used_by_tiles(2, 64, 0, false), 17..17);
+  assert_eq!(screenblocks_used_by_tiles(3, 0, 256, true), 24..32);
+  // going past the end of background VRAM stops at 32.
+  assert_eq!(screenblocks_used_by_tiles(3, 200, 100, true), 30..32);
+
+  let map = AffineScreenblock::new(24, AffineBackgroundSize::_32x32);
+  assert_eq!(map.width(), 32);
+  let pair = unsafe { VolAddress::<u16, Safe, Safe>::new(0x0600_C022) };
+  pair.write(0x1234);
+  map.write(3, 1, 0xAB);
+  assert_eq!(pair.read(), 0xAB34);
+  map.write(2, 1, 0xCD);
+  assert_eq!(pair.read(), 0xABCD);
+  assert_eq!((map.read(2, 1), map.read(3, 1)), (0xCD, 0xAB));
+}
+
+#[test_case]
+fn tiles_pack_flip_and_copy() {
+  let mut bytes4 = [0; 32];
+  bytes4[..4].copy_from_slice(&[0x21, 0x43, 0x65, 0x87]);
+  bytes4[28] = 0xFF;
+  let tile4 = tile4_from_bytes(bytes4);
+  assert_eq!(tile4, [0x8765_4321, 0, 0, 0, 0, 0, 0, 0xFF]);
+  assert_eq!(tile4_flip_h(tile4), [0x1234_5678, 0, 0, 0, 0, 0, 0, 0xFF00_0000]);
+  assert_eq!(tile4_flip_v(tile4), [0xFF, 0, 0, 0, 0, 0, 0, 0x8765_4321]);
+  assert_eq!(tile4_flip_h(tile4_flip_h(tile4)), tile4);
+
+  let mut bytes8 = [0; 64];
+  bytes8[..8].copy_from_slice(&[1, 2, 3, 4, 5, 6, 7, 8]);
+  bytes8[56] = 9;
+  let tile8 = tile8_from_bytes(bytes8);
+  assert_eq!(
+    (tile8[0], tile8[1], tile8[14], tile8[15]),
+    (0x0403_0201, 0x0807_0605, 9, 0)
+  );
+  let flipped = tile8_flip_h(tile8);
+  assert_eq!((flipped[0], flipped[1]), (0x0506_0708, 0x0102_0304));
+  assert_eq!((flipped[14], flipped[15]), (0, 0x0900_0000));
+  let flipped = tile8_flip_v(tile8);
+  assert_eq!((flipped[0], flipped[1]), (9, 0));
+  assert_eq!((flipped[14], flipped[15]), (0x0403_0201, 0x0807_0605));
+
+  let guard = [0xAAAA_AAAA; 8];
+  charblock_4bpp(2).index(4).write(guard);
+  charblock_4bpp(2).index(7).write(guard);
+  copy_tiles_4bpp(2, 5, &[tile4, tile4_flip_v(tile4)]);
+  assert_eq!(charblock_4bpp(2).index(5).read(), tile4);
+  assert_eq!(charblock_4bpp(2).index(6).read(), tile4_flip_v(tile4));
+  assert_eq!(charblock_4bpp(2).index(4).read(), guard);
+  assert_eq!(charblock_4bpp(2).index(7).read(), guard);
+  copy_tiles_8bpp(3, 255, &[tile8]);
+  assert_eq!(charblock_8bpp(3).index(255).read(), tile8);
+}
+
+#[test_case]
+fn pal_bank_allocator_fills_up_and_reuses_freed_banks() {
+  let bank = PalBank::new(Palette::Bg, 3);
+  assert_eq!(
+    (bank.palette(), bank.index(), bank.range()),
+    (Palette::Bg, 3, 48..64)
+  );
+  assert_eq!(PalBank::new(Palette::Obj, 15).range(), 496..512);
+  assert_eq!(
+    PalBank::new(Palette::Obj, 1).colors().index(0).as_usize(),
+    Palette::Obj.entries().index(16).as_usize()
+  );
+
+  let mut banks = PalBankAllocator::new(Palette::Bg);
+  for i in 0..16 {
+    let bank = banks.alloc_bank().unwrap();
+    assert_eq!((bank.palette(), bank.index()), (Palette::Bg, i));
+    assert!(banks.is_used(bank));
+  }
+  assert_eq!(banks.alloc_bank(), None);
+
+  // freed banks come back lowest first.
+  banks.free_bank(PalBank::new(Palette::Bg, 9));
+  banks.free_bank(PalBank::new(Palette::Bg, 4));
+  assert!(!banks.is_used(PalBank::new(Palette::Bg, 4)));
+  assert_eq!(banks.alloc_bank().unwrap().index(), 4);
+  assert_eq!(banks.alloc_bank().unwrap().index(), 9);
+  assert_eq!(banks.alloc_bank(), None);
+
+  banks.reset();
+  assert!(!banks.is_used(PalBank::new(Palette::Bg, 15)));
+  assert_eq!(banks.alloc_banks(16).unwrap().index(), 0);
+  banks.free_banks(PalBank::new(Palette::Bg, 14), 5);
+  assert_eq!(banks.alloc_banks(2).unwrap().index(), 14);
+
+  // a bank that already holds the colors is handed out again.
+  banks.reset();
+  let mut colors = [Color::BLACK; 16];
+  colors[1] = Color::RED;
+  let first = banks.find_or_alloc(&colors).unwrap();
+  assert_eq!(banks.find_or_alloc(&colors), Some(first));
+  colors[0] = Color::WHITE;
+  assert_eq!(banks.find_or_alloc(&colors), Some(first));
+  colors[2] = Color::GREEN;
+  let second = banks.find_or_alloc(&colors).unwrap();
+  assert_ne!(second, first);
+  assert_eq!(second.colors().index(2).read(), Color::GREEN);
+}
+
+/// Just past one full DMA3 transfer, so copies of it are split in two.
+const PAST_ONE_DMA: usize = 0xFFFF + 2;
+
+#[test_case]
+fn dma3_splits_long_copies_and_skips_empty_ones() {
+  static SRC: [u16; PAST_ONE_DMA] = {
+    let mut src = [0; PAST_ONE_DMA];
+    let mut i = 0;
+    while i < PAST_ONE_DMA {
+      src[i] = (i as u16) ^ 0x5A5A;
+      i += 1;
+    }
+    src
+  };
+  #[link_section = ".ewram"]
+  static mut DEST: [u16; PAST_ONE_DMA + 1] = [0; PAST_ONE_DMA + 1];
+  let dest = unsafe { &mut *addr_of_mut!(DEST) };
+  dest.fill(0x1234);
+
+  // an empty copy must not become the hardware's 65,536 element copy.
+  dma3_copy_u16_slice(&[], &mut dest[..0]);
+  assert!(dest.iter().all(|&h| h == 0x1234));
+
+  dma3_copy_u16_slice(&SRC, &mut dest[..PAST_ONE_DMA]);
+  // the first transfer ends at 0xFFFE, and the second does the last two.
+  for i in [0, 0xFFFE, 0xFFFF, 0x1_0000] {
+    assert_eq!(dest[i], SRC[i], "element {i:#X}");
+  }
+  assert!(dest[..PAST_ONE_DMA] == SRC[..]);
+  assert_eq!(dest[PAST_ONE_DMA], 0x1234);
+}
+
+#[test_case]
+fn bios_sqrt_and_arctan_match_float_references() {
+  const PI: f64 = core::f64::consts::PI;
+  // `core` has no float math, so these are worked out the long way.
+  fn sqrt(x: f64) -> f64 {
+    let mut root = if x < 1.0 { 1.0 } else { x / 2.0 };
+    for _ in 0..40 {
+      root = (root + x / root) / 2.0;
+    }
+    root
+  }
+  fn atan(t: f64) -> f64 {
+    if t.abs() > 1.0 {
+      return PI / 2.0 * t.signum() - atan(1.0 / t);
+    }
+    // halving the angle keeps the series short.
+    let u = t / (1.0 + sqrt(1.0 + t * t));
+    let (mut term, mut total) = (u, u);
+    for n in 1..30 {
+      term *= -u * u;
+      total += term / f64::from(2 * n + 1);
+    }
+    2.0 * total
+  }
+  // the angle's distance from `expected`, in binary angle units.
+  fn error(angle: u16, expected: f64) -> f64 {
+    let expected = expected / (2.0 * PI) * 65536.0;
+    let mut diff = f64::from(angle) - expected;
+    while diff > 32768.0 {
+      diff -= 65536.0;
+    }
+    while diff < -32768.0 {
+      diff += 65536.0;
+    }
+    diff.abs()
+  }
+
+  for x in (0..=u32::MAX).step_by(1_048_583).chain([1, 2, 3, 4, u32::MAX]) {
+    let root = Sqrt(x);
+    let exact = sqrt(f64::from(x));
+    let diff = exact - f64::from(root);
+    assert!((-1e-6..1.0).contains(&diff), "Sqrt({x}) is {root}");
+  }
+
+  // inside +/- pi/4 the BIOS ArcTan is accurate.
+  for bits in (-0x4000..=0x4000_i16).step_by(37) {
+    let angle = ArcTan(i16fx14::from_bits(bits)).to_bits();
+    let exact = atan(f64::from(bits) / 16384.0);
+    let off = error(angle as u16, exact);
+    assert!(off < 16.0, "ArcTan({bits:#X}) is {angle:#X}, {off} off");
+  }
+
+  for y in (-0x4000..=0x4000_i16).step_by(0x3F1) {
+    for x in (-0x4000..=0x4000_i16).step_by(0x3F1) {
+      let angle = ArcTan2(i16fx14::from_bits(x), i16fx14::from_bits(y));
+      let (fx, fy) = (f64::from(x), f64::from(y));
+      let exact = if fx > 0.0 {
+        atan(fy / fx)
+      } else if fx < 0.0 {
+        atan(fy / fx) + PI
+      } else {
+        PI / 2.0 * fy.signum()
+      };
+      let off = error(angle, exact);
+      assert!(off < 32.0, "ArcTan2({x:#X}, {y:#X}) is {angle:#X}, {off} off");
+    }
+  }
+}
+
+#[test_case]
+fn cpu_set_copies_and_fills_split_at_multiples_of_8() {
+  let src: [u32; 18] = core::array::from_fn(|i| 0x1000 + i as u32);
+  for len in [0, 1, 7, 8, 9, 15, 16, 17] {
+    // one extra word at the end, which must be left alone.
+    let mut dest = [0xAAAA_AAAA_u32; 18];
+    cpu_fast_copy(&src[..len], &mut dest[..len]);
+    assert_eq!(dest[..len], src[..len], "copy of {len}");
+    assert_eq!(dest[len], 0xAAAA_AAAA, "copy of {len}");
+
+    cpu_fast_fill(0x5555_5555, &mut dest[..len]);
+    assert!(dest[..len].iter().all(|&w| w == 0x5555_5555), "fill of {len}");
+    assert_eq!(dest[len], 0xAAAA_AAAA, "fill of {len}");
+  }
+
+  // fills repeat the one value, copies step through the source.
+  let mut words = [0_u32; 5];
+  cpu_copy32(&src[..5], &mut words);
+  assert_eq!(words, [0x1000, 0x1001, 0x1002, 0x1003, 0x1004]);
+  cpu_fill32(7, &mut words[1..4]);
+  assert_eq!(words, [0x1000, 7, 7, 7, 0x1004]);
+
+  let halves: [u16; 5] = [1, 2, 3, 4, 5];
+  let mut dest = [0_u16; 6];
+  cpu_copy16(&halves, &mut dest[..5]);
+  assert_eq!(dest, [1, 2, 3, 4, 5, 0]);
+  cpu_fill16(9, &mut dest[1..]);
+  assert_eq!(dest, [1, 9, 9, 9, 9, 9]);
+  cpu_fill16(3, &mut []);
+  cpu_copy16(&[], &mut dest[..0]);
+  assert_eq!(dest, [1, 9, 9, 9, 9, 9]);
+}
+
+#[test_case]
+fn compression_headers_give_the_size_for_their_own_type() {
+  use DecompressError::BadHeader;
+  // the largest size, with every bit of the 24 set.
+  let max = 0xFF_FFFF;
+  assert_eq!(lz77_decompressed_len(&[0x0000_0110]), Ok(1));
+  assert_eq!(lz77_decompressed_len(&[0xFFFF_FF10]), Ok(max));
+  assert_eq!(huff_decompressed_len(&[0x0001_0024, 0]), Ok(0x100));
+  assert_eq!(huff_decompressed_len(&[0x0000_2028, 0]), Ok(0x20));
+  assert_eq!(rl_decompressed_len(&[0x0012_3430]), Ok(0x1234));
+  assert_eq!(diff_8bit_unfiltered_len(&[0x0000_0581]), Ok(5));
+  assert_eq!(diff_16bit_unfiltered_len(&[0x0000_0682]), Ok(6));
+
+  // every type rejects the others' headers.
+  type HeaderLen = fn(&[u32]) -> Result<usize, DecompressError>;
+  let lens: [HeaderLen; 4] = [
+    lz77_decompressed_len,
+    huff_decompressed_len,
+    rl_decompressed_len,
+    diff_8bit_unfiltered_len,
+  ];
+  let headers = [0x0000_1010, 0x0000_1028, 0x0000_1030, 0x0000_1081];
+  for (i, len) in lens.iter().enumerate() {
+    for (j, header) in headers.iter().enumerate() {
+      assert_eq!(len(&[*header, 0]).is_ok(), i == j, "{i} with {header:#X}");
+    }
+  }
+
+  // no header at all, a size of 0, or bad low bits for the type.
+  assert_eq!(lz77_decompressed_len(&[]), Err(BadHeader));
+  assert_eq!(rl_decompressed_len(&[0x0000_0030]), Err(BadHeader));
+  assert_eq!(lz77_decompressed_len(&[0x0000_0111]), Err(BadHeader));
+  assert_eq!(huff_decompressed_len(&[0x0000_0122, 0]), Err(BadHeader));
+  assert_eq!(diff_8bit_unfiltered_len(&[0x0000_0182]), Err(BadHeader));
+  assert_eq!(diff_16bit_unfiltered_len(&[0x0000_0782]), Err(BadHeader));
+  // a Huffman tree of (3 + 1) * 2 bytes runs past the end of this source.
+  assert_eq!(huff_decompressed_len(&[0x0000_0128, 3]), Err(BadHeader));
+  assert_eq!(huff_decompressed_len(&[0x0000_0128, 3, 0]), Ok(1));
+}
+
+#[test_case]
+fn sound_master_registers_pack_their_fields() {
+  let raw = |addr| unsafe { VolAddress::<u16, Safe, Safe>::new(addr) }.read();
+  let was_enabled = SOUND_ENABLED.read();
+  gba::sound::enable();
+  assert!(SOUND_ENABLED.read().enabled());
+  assert_eq!(raw(0x0400_0084) & 0x80, 0x80);
+  let (volume, mix) = (LEFT_RIGHT_VOLUME.read(), SOUND_MIX.read());
+
+  // right volume in bits 0-2, left in 4-6, then right and left enables.
+  LEFT_RIGHT_VOLUME.write(dmg_stereo_defaults());
+  assert_eq!(raw(0x0400_0080), 0xFF77);
+  LEFT_RIGHT_VOLUME.write(
+    LeftRightVolume::new()
+      .with_right_volume(3)
+      .with_left_volume(5)
+      .with_wave_right(true)
+      .with_tone2_left(true),
+  );
+  assert_eq!(raw(0x0400_0080), 0x2453);
+
+  SOUND_MIX.write(
+    SoundMix::new()
+      .with_psg(PsgMix::_100)
+      .with_sound_a_full(true)
+      .with_sound_a_right(true)
+      .with_sound_b_left(true)
+      .with_sound_b_timer(true),
+  );
+  assert_eq!(raw(0x0400_0082), 0x6106);
+  // the FIFO resets are write-only, they read back unset.
+  SOUND_MIX
+    .write(SoundMix::new().with_sound_a_reset(true).with_sound_b_reset(true));
+  assert_eq!(raw(0x0400_0082), 0);
+  assert_eq!(SoundMix::PSG_MASK, 0b11);
+  assert_eq!(LeftRightVolume::RIGHT_VOLUME_MASK, 0b111);
+  assert_eq!(LeftRightVolume::LEFT_VOLUME_MASK, 0b111 << 4);
+
+  LEFT_RIGHT_VOLUME.write(volume);
+  SOUND_MIX.write(mix);
+  SOUND_ENABLED.write(was_enabled);
+}
+
+#[test_case]
+fn noise_settings_pack_into_the_channel_registers() {
+  let fading = Envelope::new(9, 3, true).unwrap();
+  let len_env = NoiseLenEnvelope::new().with_envelope(fading);
+  assert_eq!((len_env.volume(), len_env.step_time()), (9, 3));
+  assert!(len_env.step_increasing());
+  assert_eq!(NoiseLenEnvelope::VOLUME_MASK, 0b1111 << 12);
+  assert_eq!(NoiseLenEnvelope::STEP_TIME_MASK, 0b111 << 8);
+  // the register counts up to 64, so the length is stored as 64 minus it.
+  for (length, stored) in [(0, 63), (1, 63), (10, 54), (64, 0), (200, 0)] {
+    let len_env = len_env.with_length_256ths(length);
+    assert_eq!(len_env.length(), stored, "length of {length}");
+    assert_eq!(len_env.volume(), 9);
+  }
+
+  let frequency = NoiseFrequency::new()
+    .with_dividing_ratio(DividingRatio::_7)
+    .with_shift_clock(13)
+    .with_counter_width(CounterWidth::_7);
+  assert_eq!(
+    (frequency.r(), frequency.s(), frequency.counter7()),
+    (7, 13, true)
+  );
+  let wide = frequency.with_counter_width(CounterWidth::_15);
+  assert_eq!((wide.r(), wide.s(), wide.counter7()), (7, 13, false));
+
+  let (len_env, frequency) = white_noise().to_registers();
+  assert_eq!((len_env.volume(), len_env.length()), (12, 0));
+  assert_eq!(
+    (frequency.r(), frequency.s(), frequency.counter7()),
+    (0, 0, false)
+  );
+  assert!(frequency.enabled() && !frequency.stop_when_expired());
+  let (_, frequency) = metallic().to_registers();
+  assert_eq!(
+    (frequency.r(), frequency.s(), frequency.counter7()),
+    (1, 2, true)
+  );
+  let short = NoiseSettings { length: Some(16), ..buzz() };
+  let (len_env, frequency) = short.to_registers();
+  assert_eq!(
+    (frequency.r(), frequency.s(), frequency.counter7()),
+    (4, 6, true)
+  );
+  assert_eq!(len_env.length(), 48);
+  assert!(frequency.enabled() && frequency.stop_when_expired());
+}
+
+#[test_case]
+fn stream_rates_evenly_divide_a_frame() {
+  // 280,896 cycles per frame, which is 2^6 * 3 * 7 * 11 * 19.
+  let usable: ArrayVec<u16, 24> = (0..=720)
+    .filter(|&s| StreamRate::from_samples_per_frame(s).is_some())
+    .collect();
+  assert_eq!(
+    usable.as_slice(),
+    [
+      16, 32, 48, 64, 96, 112, 176, 192, 224, 304, 336, 352, 448, 528, 608,
+      672, 704
+    ]
+  );
+  for &samples in &usable {
+    let rate = StreamRate::from_samples_per_frame(samples).unwrap();
+    assert_eq!(rate.samples_per_frame(), usize::from(samples));
+    assert_eq!(rate.cycles_per_sample() * u32::from(samples), 280_896);
+    assert_eq!(
+      rate.timer_reload(),
+      rate.cycles_per_sample().wrapping_neg() as u16
+    );
+    assert!(rate.samples_per_frame() <= MAX_STREAM_SAMPLES);
+  }
+
+  let rate = StreamRate::nearest(16384);
+  assert_eq!((rate.samples_per_frame(), rate.cycles_per_sample()), (304, 924));
+  assert_eq!((rate.hz(), rate.timer_reload()), (18157, 64612));
+  assert_eq!(StreamRate::nearest(22050).samples_per_frame(), 352);
+  assert_eq!(StreamRate::nearest(32768).samples_per_frame(), 528);
+  // out of range requests get the closest end.
+  assert_eq!(StreamRate::nearest(0).hz(), 955);
+  assert_eq!(StreamRate::nearest(44100).hz(), 42048);
+  assert_eq!(StreamRate::nearest(u32::MAX).samples_per_frame(), 704);
+
+  let config = StreamConfig::new(rate).with_timer(Timer::Timer1);
+  assert_eq!(config.rate(), rate);
+}
+
+#[test_case]
+fn mixer_voices_step_by_their_rate_and_wrap_at_the_loop() {
+  static RAMP: [i8; 8] = [0, 10, 20, 30, 40, 50, 60, 70];
+  let voice = Voice::new(&RAMP);
+  assert_eq!(voice.with_pitch(8000, 16000).rate(), 0x800);
+  assert_eq!(voice.with_pitch(18157, 18157).rate(), 0x1000);
+  assert_eq!(voice.with_pitch(44100, 18157).rate(), 0x26DC);
+
+  // without a loop the voice ends at the last sample.
+  assert_eq!(voice.advance(6 << 12), Some(7 << 12));
+  assert_eq!(voice.advance(7 << 12), None);
+  assert_eq!(voice.with_loop(6, 2).advance(1 << 12), None);
+  // looping samples 2 to 5, even when one step goes around more than once.
+  let looped = voice.with_loop(2, 6);
+  assert_eq!(looped.with_rate(0x1800).advance(5 << 12), Some(0x2800));
+  assert_eq!(looped.with_rate(0x9000).advance(5 << 12), Some(2 << 12));
+  assert_eq!(voice.with_loop(2, 100).advance(7 << 12), Some(2 << 12));
+
+  let mut out = [0_i8; 8];
+  let mut mixer = Mixer::<2>::new();
+  mixer.play(0, voice.with_rate(0x800));
+  mixer.mix(&mut out[..6]);
+  assert_eq!(out[..6], [0, 0, 10, 10, 20, 20]);
+  assert_eq!(mixer.voice_mut(0).unwrap().position(), 3 << 12);
+
+  mixer.play(0, voice.with_loop(2, 4));
+  mixer.mix(&mut out);
+  assert_eq!(out, [0, 10, 20, 30, 20, 30, 20, 30]);
+  assert!(mixer.is_playing(0));
+
+  // two voices add up, and clip at the top of the `i8` range.
+  mixer.play(0, voice);
+  mixer.play(1, voice.with_volume(32));
+  mixer.mix(&mut out);
+  assert_eq!(out, [0, 15, 30, 45, 60, 75, 90, 105]);
+  mixer.play(0, voice);
+  mixer.play(1, voice);
+  let mut long = [1_i8; 10];
+  mixer.mix(&mut long);
+  assert_eq!(long, [0, 20, 40, 60, 80, 100, 120, 127, 0, 0]);
+  assert_eq!(mixer.free_slot(), Some(0));
+}
+
+#[test_case]
+fn gpio_pins_pack_into_rcnt() {
+  let masks = [Pin::Sc, Pin::Sd, Pin::Si, Pin::So].map(Pin::mask);
+  assert_eq!(masks, [0b0001, 0b0010, 0b0100, 0b1000]);
+  let directions =
+    PinDirections::new().with_output(Pin::So, true).with_sd_output(true);
+  assert_eq!(directions.to_u8(), 0b1010);
+  assert!(directions.is_output(Pin::Sd) && directions.so_output());
+  assert!(!directions.is_output(Pin::Sc) && !directions.si_output());
+  assert_eq!(directions.with_output(Pin::So, false).to_u8(), 0b0010);
+  assert_eq!(PinDirections::from_u8(0xF5).to_u8(), 0b0101);
+
+  let port = LinkPortControl::new()
+    .with_pin_levels(0b1001)
+    .with_pin_directions(0b0110)
+    .with_si_irq(true)
+    .with_mode(PortMode::Gpio);
+  assert_eq!(port.to_u16(), 0x8169);
+  assert_eq!(port.with_pin_levels(0x1F).pin_levels(), 0xF);
+  assert_eq!(port.with_pin_levels(0x1F).pin_directions(), 0b0110);
+
+  let before = RCNT.read();
+  let gpio = enter_gpio_mode(directions);
+  let rcnt = RCNT.read();
+  assert_eq!((rcnt.mode(), rcnt.pin_directions()), (PortMode::Gpio, 0b1010));
+  assert!(!rcnt.si_irq());
+  gpio.set_directions(PinDirections::from_u8(0b0001));
+  gpio.set_si_irq(true);
+  let rcnt = RCNT.read();
+  assert_eq!((rcnt.pin_directions(), rcnt.si_irq()), (0b0001, true));
+  gpio.exit();
+  assert_eq!(RCNT.read(), before);
+}
+
+#[test_case]
+fn frame_stats_roll_over_the_last_n_frames() {
+  const FRAME: u32 = CYCLES_PER_FRAME;
+  let summary = |stats: &FrameStats<4>| {
+    (
+      stats.average_cycles(),
+      stats.worst_frame_cycles(),
+      stats.cpu_usage_percent(),
+      stats.fps(),
+    )
+  };
+  let mut stats = FrameStats::<4>::new();
+  assert!(stats.is_empty());
+  assert_eq!((summary(&stats), stats.last_frame_cycles()), ((0, 0, 0, 60), 0));
+
+  for cycles in [100_000, 200_000, 140_448] {
+    stats.record(cycles);
+  }
+  assert_eq!((stats.len(), stats.last_frame_cycles()), (3, 140_448));
+  assert_eq!(summary(&stats), (146_816, 200_000, 52, 60));
+  // an overrun frame takes two vblanks, so 4 frames in 5 vblanks.
+  stats.record(300_000);
+  assert_eq!(summary(&stats), (185_112, 300_000, 66, 48));
+  // the 100,000 frame is the oldest, so it's the one replaced.
+  stats.record(50_000);
+  assert_eq!((stats.len(), stats.last_frame_cycles()), (4, 50_000));
+  assert_eq!(summary(&stats), (172_612, 300_000, 61, 48));
+  for _ in 0..3 {
+    stats.record(1);
+  }
+  assert_eq!(summary(&stats), (12_500, 50_000, 4, 60));
+  // the average is summed without overflowing.
+  stats.record(u32::MAX);
+  assert_eq!(stats.average_cycles(), 1 << 30);
+  assert_eq!(stats.fps(), 0);
+
+  // exactly one frame of cycles still fits, one more cycle doesn't.
+  for _ in 0..4 {
+    stats.record(FRAME);
+  }
+  assert_eq!(summary(&stats), (FRAME, FRAME, 100, 60));
+  stats.record(FRAME + 1);
+  assert_eq!(summary(&stats), (FRAME, FRAME + 1, 100, 48));
+
+  stats.clear();
+  assert!(stats.is_empty());
+  assert_eq!(summary(&stats), (0, 0, 0, 60));
+}
+
+#[test_case]
+fn nested_sections_only_count_their_own_cycles() {
+  // timer 3 is the runner's timeout, so this keeps to timers 0 and 1.
+  let wide = WideTimer::new(Timer::Timer0);
+  wide.start(TimerScale::_1);
+  let spin = |cycles| {
+    let end = wide.read() + cycles;
+    while wide.read() < end {}
+  };
+  let counters = Counters::<3>::new(wide);
+  let before = wide.read();
+  {
+    let _outer = counters.section("outer");
+    spin(2_000);
+    {
+      let _inner = counters.section("inner");
+      spin(20_000);
+    }
+    spin(2_000);
+  }
+  let whole = wide.read() - before;
+  wide.stop();
+
+  // the inner section's time isn't counted again in the outer one.
+  let (outer, inner) = (counters.cycles("outer"), counters.cycles("inner"));
+  assert!((4_000..5_000).contains(&outer), "outer counted {outer}");
+  assert!((20_000..20_500).contains(&inner), "inner counted {inner}");
+  assert_eq!(counters.total_cycles(), outer + inner);
+  assert!(counters.total_cycles() <= whole);
+  assert_eq!((counters.calls("outer"), counters.calls("inner")), (1, 1));
+  assert_eq!((counters.cycles("missing"), counters.calls("missing")), (0, 0));
+
+  let mut report = ArrayString::<256>::new();
+  counters.report(&mut report).unwrap();
+  let mut lines = report.as_str().lines().map(|line| line.split(' ').next());
+  assert_eq!(lines.next(), Some(Some("section")));
+  assert_eq!(lines.next(), Some(Some("inner")));
+  assert_eq!(lines.next(), Some(Some("outer")));
+  assert_eq!(lines.next(), Some(Some("total")));
+
+  counters.frame_reset();
+  assert_eq!((counters.total_cycles(), counters.calls("inner")), (0, 0));
+  drop(counters.section("inner"));
+  assert_eq!(counters.calls("inner"), 1);
+}
+
+extern "C" {
+  fn memcpy(dest: *mut u8, src: *const u8, count: usize) -> *mut u8;
+  fn memmove(dest: *mut u8, src: *const u8, count: usize) -> *mut u8;
+  fn memset(dest: *mut u8, byte: i32, count: usize) -> *mut u8;
+  fn __aeabi_memcpy(dest: *mut u8, src: *const u8, count: usize);
+  fn __aeabi_memclr(dest: *mut u8, count: usize);
+}
+
+/// The memory function tests check every length up to this.
+const MEM_FN_MAX_LEN: usize = 64;
+/// What's around each destination, which has to be left alone.
+const MEM_FN_GUARD: u8 = 0xEE;
+
+/// A byte that's different at each offset (and never the guard byte).
+fn mem_fn_pattern(i: usize) -> u8 {
+  (i as u8).wrapping_mul(7).wrapping_add(1) | 1
+}
+
+#[test_case]
+fn memcpy_handles_every_alignment_and_length() {
+  let mut src = Align4([0_u8; MEM_FN_MAX_LEN + 16]);
+  let mut dest = Align4([0_u8; MEM_FN_MAX_LEN + 16]);
+  let (src, dest) = (&mut src.0, &mut dest.0);
+  for (i, b) in src.iter_mut().enumerate() {
+    *b = mem_fn_pattern(i);
+  }
+  for src_align in 0..4 {
+    for dest_align in 0..4 {
+      for len in 0..=MEM_FN_MAX_LEN {
+        for use_aeabi in [false, true] {
+          dest.fill(MEM_FN_GUARD);
+          let s = src[4 + src_align..].as_ptr();
+          let d = dest[4 + dest_align..].as_mut_ptr();
+          // Safety: both buffers have room for the longest copy.
+          unsafe {
+            if use_aeabi {
+              __aeabi_memcpy(d, s, len);
+            } else {
+              assert_eq!(memcpy(d, s, len), d);
+            }
+          }
+          for (i, &b) in dest.iter().enumerate() {
+            let expected = match i.checked_sub(4 + dest_align) {
+              Some(offset) if offset < len => {
+                mem_fn_pattern(4 + src_align + offset)
+              }
+              _ => MEM_FN_GUARD,
+            };
+            assert_eq!(b, expected, "memcpy {src_align} {dest_align} {len}");
+          }
+        }
+      }
+    }
+  }
+}
+
+#[test_case]
+fn memmove_handles_every_overlap() {
+  let mut buffer = Align4([0_u8; MEM_FN_MAX_LEN + 32]);
+  let buffer = &mut buffer.0;
+  for src_offset in 0..12 {
+    for dest_offset in 0..12 {
+      for len in 0..=MEM_FN_MAX_LEN {
+        for (i, b) in buffer.iter_mut().enumerate() {
+          *b = mem_fn_pattern(i);
+        }
+        let base = buffer.as_mut_ptr();
+        // Safety: both ends stay inside the buffer.
+        unsafe {
+          let d = base.add(4 + dest_offset);
+          assert_eq!(memmove(d, base.add(4 + src_offset), len), d);
+        }
+        for (i, &b) in buffer.iter().enumerate() {
+          let expected = match i.checked_sub(4 + dest_offset) {
+            Some(offset) if offset < len => {
+              mem_fn_pattern(4 + src_offset + offset)
+            }
+            _ => mem_fn_pattern(i),
+          };
+          assert_eq!(b, expected, "memmove {src_offset} {dest_offset} {len}");
+        }
+      }
+    }
+  }
+}
+
+#[test_case]
+fn memset_handles_every_alignment_and_length() {
+  let mut dest = Align4([0_u8; MEM_FN_MAX_LEN + 16]);
+  let dest = &mut dest.0;
+  for dest_align in 0..4 {
+    for len in 0..=MEM_FN_MAX_LEN {
+      // only the low byte of the `i32` is used.
+      for (byte, clear) in [(0x1A5, false), (0, true)] {
+        dest.fill(MEM_FN_GUARD);
+        let d = dest[4 + dest_align..].as_mut_ptr();
+        // Safety: the buffer has room for the longest set.
+        unsafe {
+          if clear {
+            __aeabi_memclr(d, len);
+          } else {
+            assert_eq!(memset(d, byte, len), d);
+          }
+        }
+        for (i, &b) in dest.iter().enumerate() {
+          let expected = match i.checked_sub(4 + dest_align) {
+            Some(offset) if offset < len => byte as u8,
+            _ => MEM_FN_GUARD,
+          };
+          assert_eq!(b, expected, "memset {dest_align} {len}");
+        }
+      }
+    }
+  }
+}
+
+fn fill_a_lot() {
+  let mut buffer = [0_u32; 256];
+  for value in 0..64 {
+    dma3_fill_u32_slice(&mut buffer, value);
+    assert_eq!(buffer[255], value);
+  }
+}
+
+#[test_case]
+static FILL_A_LOT: TimedTest = TimedTest::new("fill_a_lot", fill_a_lot, 2);